@@ -0,0 +1,17 @@
+contract c {
+    function decode(
+        bytes calldata data
+    ) public pure returns (bytes4 tag, uint64 value) {
+        tag = bytes4(data[:4]);
+
+        // no copy of the data is made for abi.decode()
+        value = abi.decode(data[4:], (uint64));
+    }
+
+    function middle(int32[] memory arr) public pure returns (int32[] memory) {
+        // the slice is copied into a new array on assignment
+        int32[] memory mid = arr[1:arr.length - 1];
+
+        return mid;
+    }
+}
@@ -0,0 +1,18 @@
+contract counter {
+    uint64 count;
+
+    // increment is called far more often than any other function
+    @hot(100)
+    function increment() public {
+        count += 1;
+    }
+
+    @hot(10)
+    function get() public view returns (uint64) {
+        return count;
+    }
+
+    function reset() public {
+        count = 0;
+    }
+}
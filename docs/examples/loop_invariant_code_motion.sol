@@ -0,0 +1,10 @@
+contract test {
+    uint256[] values;
+    mapping(address => uint256) weights;
+
+    function weightedSum(address user) public view returns (uint256 sum) {
+        for (uint256 i = 0; i < values.length; i++) {
+            sum += values[i] * weights[user];
+        }
+    }
+}
@@ -0,0 +1,13 @@
+contract vault {
+    error InsufficientBalance(uint256 available, uint256 required);
+
+    mapping(address => uint256) balances;
+
+    function withdraw(uint256 amount) public {
+        uint256 balance = balances[msg.sender];
+
+        require(balance >= amount, InsufficientBalance(balance, amount));
+
+        balances[msg.sender] = balance - amount;
+    }
+}
@@ -0,0 +1,7 @@
+contract test {
+    function sum(uint64[] memory a) public pure returns (uint64 total) {
+        for (uint i = 0; i < a.length; i++) {
+            total += a[i] & 0xffff;
+        }
+    }
+}
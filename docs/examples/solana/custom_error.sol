@@ -0,0 +1,11 @@
+contract vault {
+    /// @notice Not enough funds in the vault
+    error InsufficientBalance(uint64 available, uint64 required);
+
+    function withdraw(uint64 amount, uint64 balance) public pure returns (uint64) {
+        // Fails with ProgramError::Custom(6000)
+        require(balance >= amount, InsufficientBalance(balance, amount));
+
+        return balance - amount;
+    }
+}
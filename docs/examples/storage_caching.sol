@@ -0,0 +1,13 @@
+contract test {
+    uint256 total;
+
+    function accumulate(uint256[] memory amounts) public {
+        for (uint256 i = 0; i < amounts.length; i++) {
+            if (amounts[i] > 100) {
+                total += 100;
+            } else {
+                total += amounts[i];
+            }
+        }
+    }
+}
@@ -1,11 +1,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::codegen::revert::solana_error_code;
+use crate::compute_units::ComputeUnitReport;
 use crate::sema::ast::{
     ArrayLength, Contract, Function, Namespace, Parameter, StructDecl, StructType, Tag, Type,
 };
 use anchor_syn::idl::types::{
-    Idl, IdlAccount, IdlAccountItem, IdlEnumVariant, IdlEvent, IdlEventField, IdlField,
-    IdlInstruction, IdlType, IdlTypeDefinition, IdlTypeDefinitionTy,
+    Idl, IdlAccount, IdlAccountItem, IdlEnumVariant, IdlErrorCode, IdlEvent, IdlEventField,
+    IdlField, IdlInstruction, IdlType, IdlTypeDefinition, IdlTypeDefinitionTy,
 };
 use base58::ToBase58;
 use num_traits::ToPrimitive;
@@ -67,11 +69,40 @@ pub fn generate_anchor_idl(contract_no: usize, ns: &Namespace, contract_version:
         accounts: vec![],
         types: type_manager.generate_custom_idl_types(),
         events,
-        errors: None,
+        errors: idl_errors(contract_no, ns),
         metadata,
     }
 }
 
+/// Generate IDL error codes, so that clients can decode the `ProgramError::Custom` code
+/// a custom error of the contract is reported with.
+fn idl_errors(contract_no: usize, ns: &Namespace) -> Option<Vec<IdlErrorCode>> {
+    let custom_errors = &ns.contracts[contract_no].custom_errors;
+
+    if custom_errors.is_empty() {
+        None
+    } else {
+        Some(
+            custom_errors
+                .iter()
+                .map(|error_no| {
+                    let error = &ns.errors[*error_no];
+
+                    IdlErrorCode {
+                        code: solana_error_code(contract_no, *error_no, ns),
+                        name: error.name.clone(),
+                        msg: error
+                            .tags
+                            .iter()
+                            .find(|tag| tag.tag == "notice")
+                            .map(|tag| tag.value.clone()),
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
 /// Generate IDL events for a contract.
 fn idl_events(
     contract: &Contract,
@@ -105,6 +136,44 @@ fn idl_events(
     }
 }
 
+/// Add the compute unit estimate of each function to the docs of its instruction
+pub fn add_compute_unit_docs(idl: &mut Idl, report: &ComputeUnitReport) {
+    for estimate in &report.functions {
+        let Some(instr) = idl
+            .instructions
+            .iter_mut()
+            .find(|instr| instr.name == estimate.name)
+        else {
+            continue;
+        };
+
+        let doc = if estimate.unbounded.is_empty() {
+            format!("Compute units: at most {}", estimate.compute_units)
+        } else {
+            format!(
+                "Compute units: estimated {}, not including {}",
+                estimate.compute_units,
+                estimate.unbounded.join(", ")
+            )
+        };
+
+        instr.docs.get_or_insert_with(Vec::new).push(doc);
+    }
+}
+
+/// The name of the instruction for the function in the IDL
+pub(crate) fn instruction_name(contract_no: usize, func_no: usize, ns: &Namespace) -> String {
+    let func = &ns.functions[func_no];
+
+    if func.is_constructor() {
+        "new".to_string()
+    } else if func.mangled_name_contracts.contains(&contract_no) {
+        func.mangled_name.clone()
+    } else {
+        func.id.name.clone()
+    }
+}
+
 /// Generate the IDL instructions for a contract.
 fn idl_instructions(
     contract_no: usize,
@@ -161,13 +230,7 @@ fn idl_instructions(
             });
         }
 
-        let name = if func.is_constructor() {
-            "new".to_string()
-        } else if func.mangled_name_contracts.contains(&contract_no) {
-            func.mangled_name.clone()
-        } else {
-            func.id.name.clone()
-        };
+        let name = instruction_name(contract_no, *func_no, ns);
 
         let accounts = func
             .solana_accounts
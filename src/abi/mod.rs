@@ -1,11 +1,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::compute_units::compute_unit_report;
 use crate::sema::ast::Namespace;
 use crate::Target;
 
 pub mod anchor;
 pub mod ethereum;
 pub mod polkadot;
+pub mod storage_diff;
+pub mod storage_layout;
 mod tests;
 
 pub fn generate_abi(
@@ -37,7 +40,11 @@ pub fn generate_abi(
                 );
             }
 
-            let idl = anchor::generate_anchor_idl(contract_no, ns, version);
+            let mut idl = anchor::generate_anchor_idl(contract_no, ns, version);
+
+            if let Ok(report) = compute_unit_report(contract_no, ns, code) {
+                anchor::add_compute_unit_docs(&mut idl, &report);
+            }
 
             (serde_json::to_string_pretty(&idl).unwrap(), "json")
         }
@@ -284,6 +284,17 @@ fn type_to_storage_layout(
     }
 }
 
+/// The root layout for the variables which are packed into one storage slot
+fn packed_root(slot: u32, leaves: Vec<FieldLayout<PortableForm>>) -> FieldLayout<PortableForm> {
+    let name = format!("packed_{slot}");
+    let root = RootLayout::new(
+        LayoutKey::new(slot),
+        Layout::Struct(StructLayout::new(name.clone(), leaves)),
+    );
+
+    FieldLayout::new(name, root)
+}
+
 /// Generate `InkProject` from `ast::Type` and `ast::Namespace`
 pub fn gen_project(contract_no: usize, ns: &ast::Namespace) -> InkProject {
     let mut registry = PortableRegistryBuilder::new();
@@ -291,24 +302,45 @@ pub fn gen_project(contract_no: usize, ns: &ast::Namespace) -> InkProject {
     // This is only used by off-chain tooling. At the moment there is no such tooling available yet.
     // So it is not exactly clear yet what this should look like.
     // For now it just contains all root layouts (you get all storage keys in use).
-    let fields: Vec<FieldLayout<PortableForm>> = ns.contracts[contract_no]
-        .layout
-        .iter()
-        .filter_map(|layout| {
-            let var = &ns.contracts[layout.contract_no].variables[layout.var_no];
-            if let Some(slot) = layout.slot.to_u32() {
-                let ty = resolve_ast(&layout.ty, ns, &mut registry);
-                let layout_key = LayoutKey::new(slot);
-                let root = RootLayout::new(
-                    layout_key,
-                    type_to_storage_layout(ty, layout_key, &registry),
-                );
-                Some(FieldLayout::new(var.name.clone(), root))
-            } else {
-                None
+    // Variables which are packed into the same storage slot share one root, with a leaf per
+    // variable.
+    let mut fields: Vec<FieldLayout<PortableForm>> = Vec::new();
+    let mut packed: Option<(u32, Vec<FieldLayout<PortableForm>>)> = None;
+
+    for layout in &ns.contracts[contract_no].layout {
+        let var = &ns.contracts[layout.contract_no].variables[layout.var_no];
+
+        let Some(slot) = layout.slot.to_u32() else {
+            continue;
+        };
+
+        let ty = resolve_ast(&layout.ty, ns, &mut registry);
+        let layout_key = LayoutKey::new(slot);
+        let var_layout = type_to_storage_layout(ty, layout_key, &registry);
+
+        if !matches!(&packed, Some((packed_slot, _)) if *packed_slot == slot) {
+            if let Some((packed_slot, leaves)) = packed.take() {
+                fields.push(packed_root(packed_slot, leaves));
             }
-        })
-        .collect();
+        }
+
+        if layout.offset.is_some() {
+            let leaf = FieldLayout::new(var.name.clone(), var_layout);
+
+            packed
+                .get_or_insert_with(|| (slot, Vec::new()))
+                .1
+                .push(leaf);
+        } else {
+            let root = RootLayout::new(layout_key, var_layout);
+            fields.push(FieldLayout::new(var.name.clone(), root));
+        }
+    }
+
+    if let Some((packed_slot, leaves)) = packed {
+        fields.push(packed_root(packed_slot, leaves));
+    }
+
     let contract_name = ns.contracts[contract_no].id.name.clone();
     let storage = Layout::Struct(StructLayout::new(contract_name, fields));
 
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Check whether a new version of a contract can be deployed over the storage of an old version,
+//! by comparing their storage layouts. Every variable of the old version must still exist in the
+//! new version at the same slot and offset, with a type which can read the existing value. New
+//! variables may be added after the existing ones, and new members may be added to the end of
+//! structs which are not stored in an array or mapping.
+
+use super::storage_layout::{StorageItem, StorageLayout, StorageType};
+use num_bigint::BigInt;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StorageChange {
+    /// The variable or struct member no longer exists
+    Removed { label: String },
+    /// The variable or struct member is stored in a different place
+    Moved {
+        label: String,
+        old_slot: String,
+        old_offset: usize,
+        new_slot: String,
+        new_offset: usize,
+    },
+    /// The type is of the same kind, but with fewer bits or elements
+    Shrunk {
+        label: String,
+        old_type: String,
+        new_type: String,
+    },
+    /// The type is different, so the existing value cannot be read
+    Retyped {
+        label: String,
+        old_type: String,
+        new_type: String,
+    },
+    /// The size of an array element or mapping value is different
+    Resized {
+        label: String,
+        old_size: String,
+        new_size: String,
+    },
+}
+
+impl fmt::Display for StorageChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageChange::Removed { label } => write!(f, "'{label}' has been removed"),
+            StorageChange::Moved {
+                label,
+                old_slot,
+                old_offset,
+                new_slot,
+                new_offset,
+            } => write!(
+                f,
+                "'{label}' moved from slot {old_slot} offset {old_offset} to slot {new_slot} offset {new_offset}"
+            ),
+            StorageChange::Shrunk {
+                label,
+                old_type,
+                new_type,
+            } => write!(f, "'{label}' shrunk from '{old_type}' to '{new_type}'"),
+            StorageChange::Retyped {
+                label,
+                old_type,
+                new_type,
+            } => write!(
+                f,
+                "type of '{label}' changed from '{old_type}' to '{new_type}'"
+            ),
+            StorageChange::Resized {
+                label,
+                old_size,
+                new_size,
+            } => write!(
+                f,
+                "size of '{label}' changed from {old_size} to {new_size} bytes"
+            ),
+        }
+    }
+}
+
+/// Compare the storage layout of a deployed contract with the layout of its new version, and
+/// return the changes which would corrupt the existing contract storage
+pub fn compare_storage_layouts(old: &StorageLayout, new: &StorageLayout) -> Vec<StorageChange> {
+    let mut diff = LayoutDiff {
+        old,
+        new,
+        changes: Vec::new(),
+    };
+
+    diff.items("", &old.storage, &new.storage);
+
+    diff.changes
+}
+
+struct LayoutDiff<'a> {
+    old: &'a StorageLayout,
+    new: &'a StorageLayout,
+    changes: Vec<StorageChange>,
+}
+
+impl LayoutDiff<'_> {
+    /// Compare storage variables or struct members. These are matched by name.
+    fn items(&mut self, prefix: &str, old_items: &[StorageItem], new_items: &[StorageItem]) {
+        for old_item in old_items {
+            let label = format!("{prefix}{}", old_item.label);
+
+            let Some(new_item) = new_items.iter().find(|item| item.label == old_item.label) else {
+                self.changes.push(StorageChange::Removed { label });
+                continue;
+            };
+
+            if old_item.slot != new_item.slot || old_item.offset != new_item.offset {
+                self.changes.push(StorageChange::Moved {
+                    label,
+                    old_slot: old_item.slot.clone(),
+                    old_offset: old_item.offset,
+                    new_slot: new_item.slot.clone(),
+                    new_offset: new_item.offset,
+                });
+                continue;
+            }
+
+            self.types(&label, &old_item.ty, &new_item.ty, true);
+        }
+    }
+
+    /// Compare two types. If the value is an array element or mapping value, it is not
+    /// resizable, since that would change where the following elements are stored.
+    fn types(&mut self, label: &str, old_id: &str, new_id: &str, resizable: bool) {
+        let (Some(old_ty), Some(new_ty)) = (self.old.types.get(old_id), self.new.types.get(new_id))
+        else {
+            // the layout does not describe the type, so only the identifiers can be compared
+            if old_id != new_id {
+                self.retyped(label, old_id, new_id);
+            }
+            return;
+        };
+
+        if old_ty.encoding != new_ty.encoding {
+            self.retyped(label, &old_ty.label, &new_ty.label);
+            return;
+        }
+
+        match old_ty.encoding.as_str() {
+            "mapping" => {
+                if self.type_label(&old_ty.key, true) != self.type_label(&new_ty.key, false) {
+                    self.retyped(label, &old_ty.label, &new_ty.label);
+                } else if let (Some(old_value), Some(new_value)) = (&old_ty.value, &new_ty.value) {
+                    self.types(&format!("{label}[]"), old_value, new_value, false);
+                }
+            }
+            "dynamic_array" => {
+                if let (Some(old_base), Some(new_base)) = (&old_ty.base, &new_ty.base) {
+                    self.types(&format!("{label}[]"), old_base, new_base, false);
+                }
+            }
+            "bytes" => (),
+            _ => self.inplace(label, old_ty, new_ty, resizable),
+        }
+    }
+
+    /// Compare two types which are stored in place: value types, structs and fixed size arrays
+    fn inplace(
+        &mut self,
+        label: &str,
+        old_ty: &StorageType,
+        new_ty: &StorageType,
+        resizable: bool,
+    ) {
+        let old_size = old_ty.number_of_bytes.parse::<BigInt>().ok();
+        let new_size = new_ty.number_of_bytes.parse::<BigInt>().ok();
+
+        match (old_ty, new_ty) {
+            (
+                StorageType {
+                    members: Some(old_members),
+                    ..
+                },
+                StorageType {
+                    members: Some(new_members),
+                    ..
+                },
+            ) => {
+                self.items(&format!("{label}."), old_members, new_members);
+
+                if !resizable && old_size != new_size {
+                    self.resized(label, old_ty, new_ty);
+                }
+            }
+            (
+                StorageType {
+                    base: Some(old_base),
+                    ..
+                },
+                StorageType {
+                    base: Some(new_base),
+                    ..
+                },
+            ) => {
+                self.types(&format!("{label}[]"), old_base, new_base, false);
+
+                if new_size < old_size {
+                    self.shrunk(label, old_ty, new_ty);
+                } else if !resizable && old_size != new_size {
+                    self.resized(label, old_ty, new_ty);
+                }
+            }
+            (
+                StorageType {
+                    value: Some(old_value),
+                    ..
+                },
+                StorageType {
+                    value: Some(new_value),
+                    ..
+                },
+            ) => {
+                // user defined value types are compared by their underlying type
+                self.types(label, old_value, new_value, resizable);
+            }
+            (
+                StorageType {
+                    members: None,
+                    base: None,
+                    value: None,
+                    ..
+                },
+                StorageType {
+                    members: None,
+                    base: None,
+                    value: None,
+                    ..
+                },
+            ) => {
+                if old_ty.label != new_ty.label {
+                    if same_kind(&old_ty.label, &new_ty.label) && new_size < old_size {
+                        self.shrunk(label, old_ty, new_ty);
+                    } else {
+                        self.retyped(label, &old_ty.label, &new_ty.label);
+                    }
+                }
+            }
+            _ => self.retyped(label, &old_ty.label, &new_ty.label),
+        }
+    }
+
+    fn type_label(&self, id: &Option<String>, old: bool) -> Option<String> {
+        let layout = if old { self.old } else { self.new };
+
+        id.as_ref()
+            .map(|id| layout.types.get(id).map_or(id, |ty| &ty.label).clone())
+    }
+
+    fn retyped(&mut self, label: &str, old_type: &str, new_type: &str) {
+        self.changes.push(StorageChange::Retyped {
+            label: label.to_owned(),
+            old_type: old_type.to_owned(),
+            new_type: new_type.to_owned(),
+        });
+    }
+
+    fn shrunk(&mut self, label: &str, old_ty: &StorageType, new_ty: &StorageType) {
+        self.changes.push(StorageChange::Shrunk {
+            label: label.to_owned(),
+            old_type: old_ty.label.clone(),
+            new_type: new_ty.label.clone(),
+        });
+    }
+
+    fn resized(&mut self, label: &str, old_ty: &StorageType, new_ty: &StorageType) {
+        self.changes.push(StorageChange::Resized {
+            label: label.to_owned(),
+            old_size: old_ty.number_of_bytes.clone(),
+            new_size: new_ty.number_of_bytes.clone(),
+        });
+    }
+}
+
+/// Are both types integers of the same signedness, or both fixed length byte arrays
+fn same_kind(old: &str, new: &str) -> bool {
+    let kind = |label: &str| {
+        label
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            .to_owned()
+    };
+
+    let old_kind = kind(old);
+
+    matches!(old_kind.as_str(), "int" | "uint" | "bytes") && old_kind == kind(new)
+}
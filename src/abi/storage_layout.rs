@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Storage layout report for a contract. The format follows the `storageLayout` output
+//! of solc, with the addition of the alignment of types on Solana.
+//!
+//! On Solana, the contract storage lives in a data account; the slot is always 0 and the
+//! offset is the byte offset into the account data. On other targets, the slot is the storage
+//! slot and the offset is the byte offset within the slot, which is non-zero only for values
+//! which are packed into a shared slot.
+
+use crate::sema::ast::{ArrayLength, Mapping, Namespace, StructType, Type};
+use crate::Target;
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
+use solang_parser::pt;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct StorageLayout {
+    pub storage: Vec<StorageItem>,
+    pub types: BTreeMap<String, StorageType>,
+    /// On Solana, the number of bytes of account data needed for the fixed layout. On other
+    /// targets, the number of storage slots.
+    #[serde(rename = "fixedLayoutSize", default)]
+    pub fixed_layout_size: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct StorageItem {
+    pub contract: String,
+    pub label: String,
+    pub offset: usize,
+    pub slot: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct StorageType {
+    pub encoding: String,
+    pub label: String,
+    #[serde(rename = "numberOfBytes")]
+    pub number_of_bytes: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<StorageItem>>,
+}
+
+/// Generate the storage layout report for the given contract. The contract layout must have been
+/// computed by codegen.
+pub fn gen_storage_layout(contract_no: usize, ns: &Namespace) -> StorageLayout {
+    let contract = &ns.contracts[contract_no];
+    let mut types = BTreeMap::new();
+
+    let storage = contract
+        .layout
+        .iter()
+        .map(|layout| {
+            let var_contract = &ns.contracts[layout.contract_no];
+            let var = &var_contract.variables[layout.var_no];
+
+            let (slot, offset) = if ns.target == Target::Solana {
+                (BigInt::zero(), layout.slot.to_usize().unwrap())
+            } else {
+                (layout.slot.clone(), layout.offset.unwrap_or_default())
+            };
+
+            StorageItem {
+                contract: contract_label(layout.contract_no, ns),
+                label: var.name.clone(),
+                offset,
+                slot: slot.to_string(),
+                ty: register_type(&layout.ty, ns, &mut types),
+            }
+        })
+        .collect();
+
+    StorageLayout {
+        storage,
+        types,
+        fixed_layout_size: contract.fixed_layout_size.to_string(),
+    }
+}
+
+/// The contract is identified by its source file and name, like solc does
+fn contract_label(contract_no: usize, ns: &Namespace) -> String {
+    let contract = &ns.contracts[contract_no];
+
+    source_label(&contract.loc, Some(&contract.id.name), ns)
+}
+
+fn source_label(loc: &pt::Loc, contract: Option<&str>, ns: &Namespace) -> String {
+    let file_name = ns.files[loc.file_no()]
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match contract {
+        Some(contract) => format!("{file_name}:{contract}"),
+        None => file_name,
+    }
+}
+
+/// Add the type and all the types it contains to the types map, and return its identifier
+fn register_type(ty: &Type, ns: &Namespace, types: &mut BTreeMap<String, StorageType>) -> String {
+    let id = type_id(ty, ns);
+
+    if types.contains_key(&id) {
+        return id;
+    }
+
+    let mut storage_type = StorageType {
+        encoding: "inplace".into(),
+        label: ty.to_string(ns),
+        number_of_bytes: type_size(ty, ns).to_string(),
+        alignment: if ns.target == Target::Solana {
+            Some(ty.storage_align(ns).to_string())
+        } else {
+            None
+        },
+        key: None,
+        value: None,
+        base: None,
+        members: None,
+    };
+
+    match ty {
+        Type::Mapping(Mapping { key, value, .. }) => {
+            storage_type.encoding = "mapping".into();
+            storage_type.key = Some(register_type(key, ns, types));
+            storage_type.value = Some(register_type(value, ns, types));
+        }
+        Type::String | Type::DynamicBytes => {
+            storage_type.encoding = "bytes".into();
+        }
+        Type::Array(_, dims) => {
+            if dims.last() == Some(&ArrayLength::Dynamic) {
+                storage_type.encoding = "dynamic_array".into();
+            }
+            storage_type.base = Some(register_type(&ty.array_elem(), ns, types));
+        }
+        Type::Struct(StructType::UserDefined(struct_no)) => {
+            let def = &ns.structs[*struct_no];
+            let contract = source_label(&def.loc, def.contract.as_deref(), ns);
+            let mut slot = BigInt::zero();
+            let mut members = Vec::new();
+
+            for (field_no, field) in def
+                .fields
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| !f.infinite_size)
+            {
+                let (field_slot, offset) = if ns.target == Target::Solana {
+                    (
+                        BigInt::zero(),
+                        def.storage_offsets[field_no].to_usize().unwrap(),
+                    )
+                } else {
+                    (slot.clone(), 0)
+                };
+
+                slot += field.ty.storage_slots(ns);
+
+                members.push(StorageItem {
+                    contract: contract.clone(),
+                    label: field.name_as_str().to_owned(),
+                    offset,
+                    slot: field_slot.to_string(),
+                    ty: register_type(&field.ty, ns, types),
+                });
+            }
+
+            storage_type.members = Some(members);
+        }
+        Type::UserType(no) => {
+            storage_type.value = Some(register_type(&ns.user_types[*no].ty, ns, types));
+        }
+        _ => (),
+    }
+
+    types.insert(id.clone(), storage_type);
+
+    id
+}
+
+/// Size of the type in storage, in bytes
+fn type_size(ty: &Type, ns: &Namespace) -> BigInt {
+    if ns.target == Target::Solana {
+        return ty.storage_slots(ns);
+    }
+
+    match ty {
+        Type::Bool
+        | Type::Address(_)
+        | Type::Contract(_)
+        | Type::Int(_)
+        | Type::Uint(_)
+        | Type::Bytes(_)
+        | Type::Enum(_)
+        | Type::UserType(_) => BigInt::from(ty.bytes(ns)),
+        _ => ty.storage_slots(ns) * 32,
+    }
+}
+
+/// Identifier for a type, in the same style as solc
+fn type_id(ty: &Type, ns: &Namespace) -> String {
+    match ty {
+        Type::Bool => "t_bool".into(),
+        Type::Address(false) => "t_address".into(),
+        Type::Address(true) => "t_address_payable".into(),
+        Type::Int(n) => format!("t_int{n}"),
+        Type::Uint(n) => format!("t_uint{n}"),
+        Type::Bytes(n) => format!("t_bytes{n}"),
+        Type::String => "t_string_storage".into(),
+        Type::DynamicBytes => "t_bytes_storage".into(),
+        Type::Contract(no) => format!("t_contract({}){}", ns.contracts[*no].id.name, no),
+        Type::Enum(no) => format!("t_enum({}){}", ns.enums[*no].id.name, no),
+        Type::Struct(StructType::UserDefined(no)) => {
+            format!("t_struct({}){}_storage", ns.structs[*no].id.name, no)
+        }
+        Type::UserType(no) => {
+            format!("t_userDefinedValueType({}){}", ns.user_types[*no].name, no)
+        }
+        Type::Mapping(Mapping { key, value, .. }) => {
+            format!("t_mapping({},{})", type_id(key, ns), type_id(value, ns))
+        }
+        Type::Array(_, dims) => {
+            let len = match dims.last() {
+                Some(ArrayLength::Fixed(len)) => len.to_string(),
+                _ => "dyn".into(),
+            };
+
+            format!("t_array({}){}_storage", type_id(&ty.array_elem(), ns), len)
+        }
+        Type::InternalFunction { .. } => "t_function_internal".into(),
+        Type::ExternalFunction { .. } => "t_function_external".into(),
+        _ => format!("t_{}", ty.to_string(ns)),
+    }
+}
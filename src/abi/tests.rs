@@ -3,13 +3,15 @@
 #![cfg(test)]
 
 use crate::abi::anchor::generate_anchor_idl;
+use crate::abi::storage_diff::{compare_storage_layouts, StorageChange};
+use crate::abi::storage_layout::gen_storage_layout;
 use crate::codegen::{codegen, Options};
 use crate::file_resolver::FileResolver;
 use crate::sema::ast::Namespace;
 use crate::{codegen, parse_and_resolve, Target};
 use anchor_syn::idl::types::{
-    IdlAccount, IdlAccountItem, IdlEnumVariant, IdlEvent, IdlEventField, IdlField, IdlType,
-    IdlTypeDefinition, IdlTypeDefinitionTy,
+    IdlAccount, IdlAccountItem, IdlEnumVariant, IdlErrorCode, IdlEvent, IdlEventField, IdlField,
+    IdlType, IdlTypeDefinition, IdlTypeDefinitionTy,
 };
 use semver::Version;
 use serde_json::json;
@@ -2117,6 +2119,218 @@ contract Test2 {
     );
 }
 
+#[test]
+fn errors() {
+    let src = r#"
+error Unauthorized(address caller);
+error Unused();
+
+contract vault {
+    /// @notice Not enough funds in the vault
+    error InsufficientBalance(uint64 available, uint64 required);
+
+    function withdraw(uint64 amount, uint64 balance) public pure {
+        if (amount > balance) {
+            revert InsufficientBalance(balance, amount);
+        }
+    }
+
+    function deny(address caller) public pure {
+        revert Unauthorized(caller);
+    }
+}
+
+contract owned {
+    function deny(address caller) public view {
+        require(caller == address(this), Unauthorized(caller));
+    }
+}
+    "#;
+
+    let mut ns = generate_namespace(src);
+    codegen(&mut ns, &Options::default());
+
+    let idl = generate_anchor_idl(0, &ns, "0.1.0");
+
+    assert_eq!(
+        idl.errors,
+        Some(vec![
+            IdlErrorCode {
+                code: 6000,
+                name: "Unauthorized".to_string(),
+                msg: None,
+            },
+            IdlErrorCode {
+                code: 6001,
+                name: "InsufficientBalance".to_string(),
+                msg: Some("Not enough funds in the vault".to_string()),
+            },
+        ])
+    );
+
+    // each contract numbers only the errors it may revert with
+    let idl = generate_anchor_idl(1, &ns, "0.1.0");
+
+    assert_eq!(
+        idl.errors,
+        Some(vec![IdlErrorCode {
+            code: 6000,
+            name: "Unauthorized".to_string(),
+            msg: None,
+        }])
+    );
+}
+
+#[test]
+fn storage_layout() {
+    let src = r#"
+contract Base {
+    uint64 a;
+    bool b;
+}
+
+contract caller is Base {
+    struct S {
+        uint8 x;
+        uint64 y;
+    }
+
+    S s;
+    mapping(address => uint32) m;
+    int16[] list;
+    string name;
+}"#;
+
+    let mut ns = generate_namespace(src);
+    codegen(&mut ns, &Options::default());
+
+    let layout = gen_storage_layout(1, &ns);
+
+    assert_eq!(
+        serde_json::to_value(&layout).unwrap(),
+        json!({
+            "storage": [
+                { "contract": "test.sol:Base", "label": "a", "offset": 16, "slot": "0", "type": "t_uint64" },
+                { "contract": "test.sol:Base", "label": "b", "offset": 24, "slot": "0", "type": "t_bool" },
+                { "contract": "test.sol:caller", "label": "s", "offset": 32, "slot": "0", "type": "t_struct(S)0_storage" },
+                { "contract": "test.sol:caller", "label": "m", "offset": 48, "slot": "0", "type": "t_mapping(t_address,t_uint32)" },
+                { "contract": "test.sol:caller", "label": "list", "offset": 1052, "slot": "0", "type": "t_array(t_int16)dyn_storage" },
+                { "contract": "test.sol:caller", "label": "name", "offset": 1056, "slot": "0", "type": "t_string_storage" },
+            ],
+            "types": {
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "32", "alignment": "8" },
+                "t_array(t_int16)dyn_storage": {
+                    "encoding": "dynamic_array",
+                    "label": "int16[]",
+                    "numberOfBytes": "4",
+                    "alignment": "4",
+                    "base": "t_int16",
+                },
+                "t_bool": { "encoding": "inplace", "label": "bool", "numberOfBytes": "1", "alignment": "1" },
+                "t_int16": { "encoding": "inplace", "label": "int16", "numberOfBytes": "2", "alignment": "2" },
+                "t_mapping(t_address,t_uint32)": {
+                    "encoding": "mapping",
+                    "label": "mapping(address => uint32)",
+                    "numberOfBytes": "1004",
+                    "alignment": "4",
+                    "key": "t_address",
+                    "value": "t_uint32",
+                },
+                "t_string_storage": { "encoding": "bytes", "label": "string", "numberOfBytes": "4", "alignment": "4" },
+                "t_struct(S)0_storage": {
+                    "encoding": "inplace",
+                    "label": "struct caller.S",
+                    "numberOfBytes": "16",
+                    "alignment": "8",
+                    "members": [
+                        { "contract": "test.sol:caller", "label": "x", "offset": 0, "slot": "0", "type": "t_uint8" },
+                        { "contract": "test.sol:caller", "label": "y", "offset": 8, "slot": "0", "type": "t_uint64" },
+                    ],
+                },
+                "t_uint32": { "encoding": "inplace", "label": "uint32", "numberOfBytes": "4", "alignment": "4" },
+                "t_uint64": { "encoding": "inplace", "label": "uint64", "numberOfBytes": "8", "alignment": "8" },
+                "t_uint8": { "encoding": "inplace", "label": "uint8", "numberOfBytes": "1", "alignment": "1" },
+            },
+            "fixedLayoutSize": "1060",
+        })
+    );
+}
+
+#[test]
+fn storage_diff() {
+    let old = r#"
+contract caller {
+    struct S {
+        uint8 x;
+        uint64 y;
+    }
+
+    uint64 a;
+    int32 b;
+    mapping(address => S) m;
+    bytes32 h;
+    bool f;
+    S s;
+}"#;
+
+    let new = r#"
+contract caller {
+    struct S {
+        uint8 x;
+        uint64 y;
+        bool w;
+    }
+
+    uint64 a;
+    uint32 b;
+    mapping(address => S) m;
+    bytes16 h;
+    bytes16 pad;
+    uint8 g;
+    S s;
+    uint64 n;
+}"#;
+
+    let mut old = generate_namespace(old);
+    codegen(&mut old, &Options::default());
+    let old = gen_storage_layout(0, &old);
+
+    let mut new = generate_namespace(new);
+    codegen(&mut new, &Options::default());
+    let new = gen_storage_layout(0, &new);
+
+    assert!(compare_storage_layouts(&old, &old).is_empty());
+
+    let changes = compare_storage_layouts(&old, &new);
+
+    assert_eq!(
+        changes,
+        vec![
+            StorageChange::Retyped {
+                label: "b".into(),
+                old_type: "int32".into(),
+                new_type: "uint32".into(),
+            },
+            StorageChange::Resized {
+                label: "m[]".into(),
+                old_size: "16".into(),
+                new_size: "24".into(),
+            },
+            StorageChange::Shrunk {
+                label: "h".into(),
+                old_type: "bytes32".into(),
+                new_type: "bytes16".into(),
+            },
+            StorageChange::Removed { label: "f".into() },
+        ]
+    );
+
+    assert_eq!(
+        changes[1].to_string(),
+        "size of 'm[]' changed from 16 to 24 bytes"
+    );
+}
+
 fn idl_account(name: &str, is_mut: bool, is_signer: bool) -> IdlAccountItem {
     IdlAccountItem::IdlAccount(IdlAccount {
         name: name.to_string(),
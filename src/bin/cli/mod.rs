@@ -12,11 +12,14 @@ use itertools::Itertools;
 use semver::Version;
 use serde::Deserialize;
 use solang::{
-    codegen::{OptimizationLevel, Options},
+    codegen::{
+        passes::{Pass, PassTimings},
+        DispatchStrategy, OptimizationLevel, Options,
+    },
     file_resolver::FileResolver,
     Target,
 };
-use std::{ffi::OsString, path::PathBuf, process::exit};
+use std::{ffi::OsString, path::PathBuf, process::exit, sync::Arc};
 
 mod test;
 #[derive(Parser)]
@@ -46,11 +49,20 @@ pub enum Commands {
 
     #[command(about = "Create a new Solang project")]
     New(New),
+
+    #[command(
+        about = "Check that a new contract version keeps the storage layout of the deployed version"
+    )]
+    StorageDiff(StorageDiff),
+
+    #[cfg(feature = "test_runner")]
+    #[command(about = "Run the unit tests of Solidity contracts in an embedded virtual machine")]
+    Test(Test),
 }
 
 #[derive(Args)]
 pub struct New {
-    #[arg(name = "TARGETNAME",required= true, long = "target", value_parser = ["solana", "polkadot", "evm"], help = "Target to build for [possible values: solana, polkadot]", num_args = 1, hide_possible_values = true)]
+    #[arg(name = "TARGETNAME",required= true, long = "target", value_parser = ["solana", "polkadot", "evm"], help = "Target to build for [possible values: solana, polkadot, evm]", num_args = 1, hide_possible_values = true)]
     pub target_name: String,
 
     #[arg(name = "INPUT", help = "Name of the project", num_args = 1, value_parser = ValueParser::os_string())]
@@ -66,6 +78,18 @@ pub struct IdlCommand {
     pub output: Option<PathBuf>,
 }
 
+#[derive(Args)]
+pub struct StorageDiff {
+    #[arg(name = "OLD", help = "Storage layout of the deployed contract, as written by --emit storage-layout", required = true, value_parser = ValueParser::path_buf(), num_args = 1)]
+    pub old_layout: PathBuf,
+
+    #[clap(flatten)]
+    pub package: DocPackage,
+
+    #[clap(flatten)]
+    pub target: TargetArg,
+}
+
 #[derive(Args)]
 pub struct LanguageServerCommand {
     #[clap(flatten)]
@@ -160,9 +184,17 @@ impl Compile {
                         .map(|contract_names| contract_names.map(String::from).collect())
                 }
                 "VERSION" => self.package.version = matches.get_one::<String>("VERSION").cloned(),
+                "SOLIDITYVERSION" => {
+                    self.package.solidity_version =
+                        matches.get_one::<String>("SOLIDITYVERSION").cloned()
+                }
 
                 // CompilerOutput args
                 "EMIT" => self.compiler_output.emit = matches.get_one::<String>("EMIT").cloned(),
+                "CFGDOTANNOTATIONS" => {
+                    self.compiler_output.cfg_dot_annotations =
+                        *matches.get_one::<bool>("CFGDOTANNOTATIONS").unwrap()
+                }
                 "OUTPUT" => {
                     self.compiler_output.output_directory =
                         matches.get_one::<String>("OUTPUT").cloned()
@@ -198,6 +230,23 @@ impl Compile {
                 "RELEASE" => {
                     self.debug_features.release = *matches.get_one::<bool>("RELEASE").unwrap()
                 }
+                "PRINTAFTER" => {
+                    self.debug_features.print_after = matches
+                        .get_many::<String>("PRINTAFTER")
+                        .map(|passes| passes.cloned().collect())
+                        .unwrap_or_default()
+                }
+                "PRINTAFTERALL" => {
+                    self.debug_features.print_after_all =
+                        *matches.get_one::<bool>("PRINTAFTERALL").unwrap()
+                }
+                "TIMEPASSES" => {
+                    self.debug_features.time_passes =
+                        *matches.get_one::<bool>("TIMEPASSES").unwrap()
+                }
+                "VERIFYCFG" => {
+                    self.debug_features.verify_cfg = *matches.get_one::<bool>("VERIFYCFG").unwrap()
+                }
 
                 // Optimizations args
                 "DEADSTORAGE" => {
@@ -221,7 +270,16 @@ impl Compile {
                         .get_one::<bool>("COMMONSUBEXPRESSIONELIMINATION")
                         .unwrap()
                 }
+                "INLINE" => self.optimizations.inline = *matches.get_one::<bool>("INLINE").unwrap(),
+                "LICM" => self.optimizations.licm = *matches.get_one::<bool>("LICM").unwrap(),
+                "STORAGECACHE" => {
+                    self.optimizations.storage_cache =
+                        *matches.get_one::<bool>("STORAGECACHE").unwrap()
+                }
                 "OPT" => self.optimizations.opt_level = matches.get_one::<String>("OPT").cloned(),
+                "DISPATCH" => {
+                    self.optimizations.dispatch = matches.get_one::<String>("DISPATCH").cloned()
+                }
 
                 "TARGET" => self.target_arg.name = matches.get_one::<String>("TARGET").cloned(),
                 "ADDRESS_LENGTH" => {
@@ -240,12 +298,60 @@ impl Compile {
     }
 }
 
+#[cfg(feature = "test_runner")]
+#[derive(Args)]
+pub struct Test {
+    #[arg(name = "CONFFILE", help = "Take arguments from configuration file", long = "config-file", value_parser = ValueParser::os_string(), num_args = 0..=1, default_value = "solang.toml")]
+    pub configuration_file: Option<OsString>,
+
+    #[clap(flatten)]
+    pub package: CompilePackage,
+
+    #[clap(flatten)]
+    pub target_arg: CompileTargetArg,
+
+    #[clap(flatten)]
+    pub debug_features: DebugFeatures,
+
+    #[clap(flatten)]
+    pub optimizations: Optimizations,
+
+    #[arg(
+        name = "FILTER",
+        help = "Only run tests whose name contains this string",
+        long = "filter",
+        num_args = 1
+    )]
+    pub filter: Option<String>,
+
+    #[arg(name = "VERBOSE" ,help = "show the logs of passing tests too", short = 'v', action = ArgAction::SetTrue, long = "verbose")]
+    pub verbose: bool,
+}
+
+#[cfg(feature = "test_runner")]
+impl Test {
+    /// Take the package, target, debug and optimization settings from the configuration file,
+    /// except for the args explicitly provided at runtime.
+    pub fn overwrite_with_config(&mut self, mut config: Compile, matches: &ArgMatches) {
+        config.overwrite_with_matches(matches);
+
+        self.package = config.package;
+        self.target_arg = config.target_arg;
+        self.debug_features = config.debug_features;
+        self.optimizations = config.optimizations;
+    }
+}
+
 #[derive(Args, Deserialize, Default, Debug, PartialEq)]
 pub struct CompilerOutput {
-    #[arg(name = "EMIT", help = "Emit compiler state at early stage", long = "emit", num_args = 1, value_parser = ["ast-dot", "cfg", "llvm-ir", "llvm-bc", "object", "asm"])]
+    #[arg(name = "EMIT", help = "Emit compiler state at early stage", long = "emit", num_args = 1, value_parser = ["ast-dot", "cfg", "llvm-ir", "llvm-bc", "object", "asm", "cfg-dot", "storage-layout", "cu-report", "callgraph", "removed-checks"])]
     #[serde(deserialize_with = "deserialize_emit", default)]
     pub emit: Option<String>,
 
+    #[arg(name = "CFGDOTANNOTATIONS", help = "Include the phis and reaching definitions in --emit cfg-dot", long = "cfg-dot-annotations", action = ArgAction::SetTrue)]
+    #[serde(default)]
+    pub cfg_dot_annotations: bool,
+
     #[arg(name = "STD-JSON",help = "mimic solidity json output on stdout", conflicts_with_all = ["VERBOSE", "OUTPUT", "EMIT"], action = ArgAction::SetTrue, long = "standard-json")]
     #[serde(default)]
     pub std_json_output: bool,
@@ -265,7 +371,7 @@ pub struct CompilerOutput {
 
 #[derive(Args)]
 pub struct TargetArg {
-    #[arg(name = "TARGET",required= true, long = "target", value_parser = ["solana", "polkadot", "evm"], help = "Target to build for [possible values: solana, polkadot]", num_args = 1, hide_possible_values = true)]
+    #[arg(name = "TARGET",required= true, long = "target", value_parser = ["solana", "polkadot", "evm"], help = "Target to build for [possible values: solana, polkadot, evm]", num_args = 1, hide_possible_values = true)]
     pub name: String,
 
     #[arg(name = "ADDRESS_LENGTH", help = "Address length on the Polkadot Parachain", long = "address-length", num_args = 1, value_parser = value_parser!(u64).range(4..1024))]
@@ -277,7 +383,7 @@ pub struct TargetArg {
 
 #[derive(Args, Deserialize, Debug, PartialEq)]
 pub struct CompileTargetArg {
-    #[arg(name = "TARGET", long = "target", value_parser = ["solana", "polkadot", "evm"], help = "Target to build for [possible values: solana, polkadot]", num_args = 1, hide_possible_values = true)]
+    #[arg(name = "TARGET", long = "target", value_parser = ["solana", "polkadot", "evm"], help = "Target to build for [possible values: solana, polkadot, evm]", num_args = 1, hide_possible_values = true)]
     pub name: Option<String>,
 
     #[arg(name = "ADDRESS_LENGTH", help = "Address length on the Polkadot Parachain", long = "address-length", num_args = 1, value_parser = value_parser!(u64).range(4..1024))]
@@ -325,6 +431,10 @@ pub struct CompilePackage {
     #[arg(name = "VERSION", help = "specify contracts version", long = "version", num_args = 1, value_parser = ValueParser::new(parse_version))]
     #[serde(default, deserialize_with = "deserialize_version")]
     pub version: Option<String>,
+
+    #[arg(name = "SOLIDITYVERSION", help = "Solidity language level which pragma solidity is checked against", long = "solidity-version", num_args = 1, value_parser = ValueParser::new(parse_version))]
+    #[serde(default, deserialize_with = "deserialize_version")]
+    pub solidity_version: Option<String>,
 }
 
 #[derive(Args, Deserialize, Debug, PartialEq)]
@@ -348,6 +458,22 @@ pub struct DebugFeatures {
     #[arg(name = "RELEASE", help = "Disable all debugging features such as prints, logging runtime errors, and logging api return codes", long = "release", action = ArgAction::SetTrue)]
     #[serde(default)]
     pub release: bool,
+
+    #[arg(name = "PRINTAFTER", help = "Print the CFGs after the given codegen pass", long = "print-after", num_args = 1, action = ArgAction::Append, value_parser = ["constant-folding", "vector-to-slice", "strength-reduce", "dead-storage", "storage-cache", "cse", "licm"])]
+    #[serde(default, rename(deserialize = "print-after"))]
+    pub print_after: Vec<String>,
+
+    #[arg(name = "PRINTAFTERALL", help = "Print the CFGs after every codegen pass", long = "print-after-all", action = ArgAction::SetTrue)]
+    #[serde(default, rename(deserialize = "print-after-all"))]
+    pub print_after_all: bool,
+
+    #[arg(name = "TIMEPASSES", help = "Report the time spent in each compiler pass", long = "time-passes", action = ArgAction::SetTrue)]
+    #[serde(default, rename(deserialize = "time-passes"))]
+    pub time_passes: bool,
+
+    #[arg(name = "VERIFYCFG", help = "Check that the CFG is well formed after each codegen pass. This is always done in debug builds of solang", long = "verify-cfg", action = ArgAction::SetTrue)]
+    #[serde(default, rename(deserialize = "verify-cfg"))]
+    pub verify_cfg: bool,
 }
 
 impl Default for DebugFeatures {
@@ -358,6 +484,10 @@ impl Default for DebugFeatures {
             log_prints: true,
             generate_debug_info: false,
             release: false,
+            print_after: Vec::new(),
+            print_after_all: false,
+            time_passes: false,
+            verify_cfg: false,
         }
     }
 }
@@ -387,10 +517,26 @@ pub struct Optimizations {
     )]
     pub common_subexpression_elimination: bool,
 
+    #[arg(name = "INLINE", help = "Disable function inlining codegen optimization", long = "no-inline", action = ArgAction::SetFalse, display_order = 6)]
+    #[serde(default = "default_true")]
+    pub inline: bool,
+
+    #[arg(name = "LICM", help = "Disable loop-invariant code motion", long = "no-licm", action = ArgAction::SetFalse, display_order = 7)]
+    #[serde(default = "default_true")]
+    pub licm: bool,
+
+    #[arg(name = "STORAGECACHE", help = "Disable caching of storage values in local variables", long = "no-storage-cache", action = ArgAction::SetFalse, display_order = 8)]
+    #[serde(default = "default_true", rename(deserialize = "storage-cache"))]
+    pub storage_cache: bool,
+
     #[arg(name = "OPT", help = "Set llvm optimizer level ", short = 'O', default_value = "default", value_parser = ["none", "less", "default", "aggressive"], num_args = 1)]
     #[serde(rename(deserialize = "llvm-IR-optimization-level"))]
     pub opt_level: Option<String>,
 
+    #[arg(name = "DISPATCH", help = "Set how the dispatcher matches the function selector", long = "dispatch", default_value = "linear", value_parser = ["linear", "binary-search", "perfect-hash"], num_args = 1)]
+    #[serde(rename(deserialize = "dispatch"))]
+    pub dispatch: Option<String>,
+
     #[cfg(feature = "wasm_opt")]
     #[arg(
         name = "WASM_OPT",
@@ -566,14 +712,29 @@ pub fn options_arg(debug: &DebugFeatures, optimizations: &Optimizations) -> Opti
         OptimizationLevel::Default
     };
 
+    let dispatch = if let Some(dispatch) = &optimizations.dispatch {
+        match dispatch.as_str() {
+            "linear" => DispatchStrategy::Linear,
+            "binary-search" => DispatchStrategy::BinarySearch,
+            "perfect-hash" => DispatchStrategy::PerfectHash,
+            _ => unreachable!(),
+        }
+    } else {
+        DispatchStrategy::Linear
+    };
+
     Options {
         dead_storage: optimizations.dead_storage,
         constant_folding: optimizations.constant_folding,
         strength_reduce: optimizations.strength_reduce,
         vector_to_slice: optimizations.vector_to_slice,
         common_subexpression_elimination: optimizations.common_subexpression_elimination,
+        inline: optimizations.inline,
+        licm: optimizations.licm,
+        storage_cache: optimizations.storage_cache,
         generate_debug_information: debug.generate_debug_info,
         opt_level,
+        dispatch,
         log_api_return_codes: debug.log_api_return_codes && !debug.release,
         log_runtime_errors: debug.log_runtime_errors && !debug.release,
         log_prints: debug.log_prints && !debug.release,
@@ -583,6 +744,21 @@ pub fn options_arg(debug: &DebugFeatures, optimizations: &Optimizations) -> Opti
         } else {
             None
         }),
+        print_after: if debug.print_after_all {
+            Pass::ALL.to_vec()
+        } else {
+            debug
+                .print_after
+                .iter()
+                .map(|pass| pass.parse().unwrap())
+                .collect()
+        },
+        time_passes: if debug.time_passes {
+            Some(Arc::new(PassTimings::default()))
+        } else {
+            None
+        },
+        verify_cfg: debug.verify_cfg || cfg!(debug_assertions),
     }
 }
 
@@ -657,10 +833,10 @@ where
     match str {
         Some(value) => {
             match value.as_str() {
-                "ast-dot"|"cfg"|"llvm-ir"|"llvm-bc"|"object"|"asm" =>
+                "ast-dot"|"cfg"|"llvm-ir"|"llvm-bc"|"object"|"asm"|"cfg-dot"|"storage-layout"|"cu-report"|"callgraph"|"removed-checks" =>
                     Ok(Some(value))
                 ,
-                _ => Err(serde::de::Error::custom("Invalid option for `emit`. Valid options are: `ast-dot`, `cfg`, `llvm-ir`, `llvm-bc`, `object`, `asm`"))
+                _ => Err(serde::de::Error::custom("Invalid option for `emit`. Valid options are: `ast-dot`, `cfg`, `llvm-ir`, `llvm-bc`, `object`, `asm`, `cfg-dot`, `storage-layout`, `cu-report`, `callgraph`, `removed-checks`"))
             }
         }
         None => Ok(None),
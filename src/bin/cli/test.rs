@@ -15,7 +15,7 @@ mod tests {
 
     #[test]
     fn parse_compile_options() {
-        let mut command: Vec<&str> = "solang compile flipper.sol --target polkadot --value-length=31 --address-length=33 --no-dead-storage --no-constant-folding --no-strength-reduce --no-vector-to-slice --no-cse -O aggressive".split(' ').collect();
+        let mut command: Vec<&str> = "solang compile flipper.sol --target polkadot --value-length=31 --address-length=33 --no-dead-storage --no-constant-folding --no-strength-reduce --no-vector-to-slice --no-cse --no-inline --no-licm --no-storage-cache -O aggressive".split(' ').collect();
         let mut cli = Cli::parse_from(command);
 
         if let Commands::Compile(compile_args) = cli.command {
@@ -31,6 +31,9 @@ mod tests {
             assert!(!compile_args.optimizations.dead_storage);
             assert!(!compile_args.optimizations.vector_to_slice);
             assert!(!compile_args.optimizations.strength_reduce);
+            assert!(!compile_args.optimizations.inline);
+            assert!(!compile_args.optimizations.licm);
+            assert!(!compile_args.optimizations.storage_cache);
             assert_eq!(compile_args.optimizations.opt_level.unwrap(), "aggressive");
         }
 
@@ -44,6 +47,21 @@ mod tests {
             assert!(!compile_args.debug_features.log_runtime_errors);
             assert!(compile_args.debug_features.release);
         }
+
+        command = "solang compile flipper.sol --target solana --solidity-version 0.8.4"
+            .split(' ')
+            .collect();
+        cli = Cli::parse_from(command);
+
+        if let Commands::Compile(compile_args) = cli.command {
+            assert_eq!(compile_args.package.solidity_version.unwrap(), "0.8.4");
+        }
+
+        command = "solang compile flipper.sol --target solana --solidity-version 0.8"
+            .split(' ')
+            .collect();
+
+        assert!(Cli::try_parse_from(command).is_err());
     }
 
     #[test]
@@ -52,7 +70,8 @@ mod tests {
         input_files = ["flipper.sol"]   # Files to be compiled. You can define multiple files as : input_files = ["file1", "file2", ..]
         contracts = ["flipper"] # Contracts to include from the compiled files
         import_path = ["path1", "path2"]
-        import_map = {map1="path", map2="path2"}    # Maps to import. Define as : import_paths = ["map=path/to/map", "map2=path/to/map2", ..]"#;
+        import_map = {map1="path", map2="path2"}    # Maps to import. Define as : import_paths = ["map=path/to/map", "map2=path/to/map2", ..]
+        solidity_version = "0.8.4""#;
 
         let package: cli::CompilePackage = toml::from_str(package_toml).unwrap();
 
@@ -69,6 +88,7 @@ mod tests {
                 ("map2".to_owned(), PathBuf::from("path2"))
             ]
         );
+        assert_eq!(package.solidity_version.unwrap(), "0.8.4");
 
         package_toml = r#"
             input_files = ["flipper.sol"]
@@ -112,6 +132,9 @@ mod tests {
         strength-reduce = false
         vector-to-slice = false
         common-subexpression-elimination = true
+        inline = false
+        licm = false
+        storage-cache = false
         llvm-IR-optimization-level = "aggressive""#;
 
         let opt: cli::Optimizations = toml::from_str(opt_toml).unwrap();
@@ -121,6 +144,9 @@ mod tests {
         assert!(!opt.constant_folding);
         assert!(!opt.strength_reduce);
         assert!(!opt.vector_to_slice);
+        assert!(!opt.inline);
+        assert!(!opt.licm);
+        assert!(!opt.storage_cache);
         assert_eq!(opt.opt_level.unwrap(), "aggressive");
     }
 
@@ -186,10 +212,12 @@ mod tests {
                     import_path: Some(vec![]),
                     import_map: Some(vec![]),
                     authors: None,
-                    version: Some("0.1.0".to_string())
+                    version: Some("0.1.0".to_string()),
+                    solidity_version: None
                 },
                 compiler_output: cli::CompilerOutput {
                     emit: None,
+                    cfg_dot_annotations: false,
                     std_json_output: false,
                     output_directory: None,
                     output_meta: None,
@@ -205,7 +233,11 @@ mod tests {
                     log_runtime_errors: true,
                     log_prints: true,
                     generate_debug_info: false,
-                    release: false
+                    release: false,
+                    print_after: vec![],
+                    print_after_all: false,
+                    time_passes: false,
+                    verify_cfg: false
                 },
                 optimizations: cli::Optimizations {
                     dead_storage: true,
@@ -213,14 +245,18 @@ mod tests {
                     strength_reduce: true,
                     vector_to_slice: true,
                     common_subexpression_elimination: true,
+                    inline: true,
+                    licm: true,
+                    storage_cache: true,
                     opt_level: Some("aggressive".to_owned()),
+                    dispatch: None,
                     #[cfg(feature = "wasm_opt")]
                     wasm_opt_passes: None
                 }
             }
         );
 
-        let command = "solang compile flipper.sol sesa.sol --config-file solang.toml --contract-authors not_sesa --target polkadot --value-length=31 --address-length=33 --no-dead-storage --no-constant-folding --no-strength-reduce --no-vector-to-slice --no-cse -O aggressive".split(' ');
+        let command = "solang compile flipper.sol sesa.sol --config-file solang.toml --contract-authors not_sesa --target polkadot --value-length=31 --address-length=33 --no-dead-storage --no-constant-folding --no-strength-reduce --no-vector-to-slice --no-cse --no-inline --no-licm --no-storage-cache -O aggressive --dispatch binary-search".split(' ');
 
         let matches = Cli::command().get_matches_from(command);
 
@@ -241,10 +277,12 @@ mod tests {
                     import_path: Some(vec![]),
                     import_map: Some(vec![]),
                     authors: Some(vec!["not_sesa".to_owned()]),
-                    version: Some("0.1.0".to_string())
+                    version: Some("0.1.0".to_string()),
+                    solidity_version: None
                 },
                 compiler_output: cli::CompilerOutput {
                     emit: None,
+                    cfg_dot_annotations: false,
                     std_json_output: false,
                     output_directory: None,
                     output_meta: None,
@@ -260,7 +298,11 @@ mod tests {
                     log_runtime_errors: true,
                     log_prints: true,
                     generate_debug_info: false,
-                    release: false
+                    release: false,
+                    print_after: vec![],
+                    print_after_all: false,
+                    time_passes: false,
+                    verify_cfg: false
                 },
                 optimizations: cli::Optimizations {
                     dead_storage: false,
@@ -268,11 +310,58 @@ mod tests {
                     strength_reduce: false,
                     vector_to_slice: false,
                     common_subexpression_elimination: false,
+                    inline: false,
+                    licm: false,
+                    storage_cache: false,
                     opt_level: Some("aggressive".to_owned()),
+                    dispatch: Some("binary-search".to_owned()),
                     #[cfg(feature = "wasm_opt")]
                     wasm_opt_passes: None
                 }
             }
         );
     }
+
+    #[cfg(feature = "test_runner")]
+    #[test]
+    fn parse_test_options() {
+        let command: Vec<&str> =
+            "solang test counter.sol --target solana --filter Increment --no-cse -v"
+                .split(' ')
+                .collect();
+        let cli = Cli::parse_from(command);
+
+        if let Commands::Test(test_args) = cli.command {
+            assert_eq!(
+                test_args.package.input.unwrap(),
+                vec![PathBuf::from("counter.sol")]
+            );
+            assert_eq!(test_args.target_arg.name.unwrap(), "solana");
+            assert_eq!(test_args.filter.unwrap(), "Increment");
+            assert!(!test_args.optimizations.common_subexpression_elimination);
+            assert!(test_args.verbose);
+        } else {
+            unreachable!();
+        }
+
+        // the settings of the configuration file are used, unless given on the command line
+        use clap::FromArgMatches;
+
+        let command = "solang test --target polkadot".split(' ');
+        let matches = Cli::command().get_matches_from(command);
+        let test_matches = matches.subcommand_matches("test").unwrap();
+        let mut test_args = cli::Test::from_arg_matches(test_matches).unwrap();
+
+        let toml = include_str!("../../../examples/solana/solana_config.toml");
+        let config: cli::Compile = toml::from_str(toml).unwrap();
+
+        test_args.overwrite_with_config(config, test_matches);
+
+        assert_eq!(
+            test_args.package.input.unwrap(),
+            vec![PathBuf::from("flipper.sol")]
+        );
+        assert_eq!(test_args.target_arg.name.unwrap(), "polkadot");
+        assert!(test_args.filter.is_none());
+    }
 }
@@ -0,0 +1,1253 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use itertools::Itertools;
+use solang::sema::{
+    ast::{self, ArrayLength, Namespace, StructType, Symbol, Type},
+    builtin::{self, Prototype},
+    symtable,
+    tags::render,
+};
+use solang::Target;
+use solang_parser::pt;
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, Documentation};
+
+/// Keywords offered when completing a plain identifier
+static KEYWORDS: [&str; 62] = [
+    "abstract",
+    "address",
+    "anonymous",
+    "as",
+    "assembly",
+    "bool",
+    "break",
+    "bytes",
+    "bytes32",
+    "calldata",
+    "catch",
+    "constant",
+    "constructor",
+    "continue",
+    "contract",
+    "delete",
+    "do",
+    "else",
+    "emit",
+    "enum",
+    "error",
+    "event",
+    "external",
+    "fallback",
+    "false",
+    "for",
+    "function",
+    "if",
+    "immutable",
+    "import",
+    "indexed",
+    "int256",
+    "interface",
+    "internal",
+    "is",
+    "library",
+    "mapping",
+    "memory",
+    "modifier",
+    "new",
+    "override",
+    "payable",
+    "pragma",
+    "private",
+    "public",
+    "pure",
+    "receive",
+    "return",
+    "returns",
+    "revert",
+    "storage",
+    "string",
+    "struct",
+    "true",
+    "try",
+    "type",
+    "uint256",
+    "unchecked",
+    "using",
+    "view",
+    "virtual",
+    "while",
+];
+
+/// The result of the most recent successful parse of a file, kept around so that completions
+/// can be offered while the user is typing, when the file often does not parse.
+pub(crate) struct CompletionCache {
+    pub(crate) ns: Namespace,
+    pub(crate) file_no: usize,
+}
+
+/// A link in a chain of member accesses, e.g. `a.b(1)[2]` is `a`, `b`, call, index
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Ident(String),
+    Call,
+    Index,
+}
+
+/// What a member access chain refers to
+#[derive(Clone)]
+enum Item {
+    /// An expression of the given type
+    Value(Type),
+    /// A contract, library or interface name
+    Contract(usize),
+    /// An enum type name
+    Enum(usize),
+    /// A user defined type name
+    UserType(usize),
+    /// An elementary type name like `address`, which can be used as a cast
+    TypeName(Type),
+    /// A builtin namespace like `block`
+    Namespace(&'static str),
+    /// A builtin function, not called yet
+    Builtin(&'static Prototype),
+    /// Functions with the same name, not called yet
+    Functions(Vec<usize>),
+    /// A file imported with `import "file.sol" as foo;`
+    Import(usize),
+}
+
+/// The context of the completion request: where in the namespace is the cursor
+struct Context<'a> {
+    ns: &'a Namespace,
+    file_no: usize,
+    contract_no: Option<usize>,
+    function_no: Option<usize>,
+    offset: usize,
+}
+
+/// Convert an LSP position into a byte offset in the text. The character of the position counts
+/// UTF-16 code units, so the offset is always on a char boundary.
+pub(crate) fn position_to_offset(text: &str, line: usize, character: usize) -> Option<usize> {
+    let mut offset = 0;
+
+    for (no, l) in text.split_inclusive('\n').enumerate() {
+        if no == line {
+            let l = l.trim_end_matches(['\n', '\r']);
+            let mut units = 0;
+
+            for (pos, ch) in l.char_indices() {
+                if units >= character {
+                    return Some(offset + pos);
+                }
+
+                units += ch.len_utf16();
+            }
+
+            return Some(offset + l.len());
+        }
+
+        offset += l.len();
+    }
+
+    // the cursor may be on the last (empty) line
+    (line == text.split_inclusive('\n').count()).then_some(text.len())
+}
+
+/// Complete the path in an import directive, if the cursor is inside one. The `dir` is the directory
+/// of the file being edited, which relative imports are resolved against.
+pub(crate) fn import_completions(
+    line: &str,
+    dir: &Path,
+    import_paths: &[(Option<OsString>, PathBuf)],
+) -> Option<Vec<CompletionItem>> {
+    if !line.trim_start().starts_with("import") {
+        return None;
+    }
+
+    // the cursor should be inside a string literal, so the last quote must open one
+    let quote = line.rfind(['"', '\''])?;
+
+    if line[..quote].matches(['"', '\'']).count() % 2 == 1 {
+        return None;
+    }
+
+    let partial = &line[quote + 1..];
+
+    let (dir_part, name_prefix) = match partial.rfind('/') {
+        Some(pos) => (&partial[..pos + 1], &partial[pos + 1..]),
+        None => ("", partial),
+    };
+
+    let mut items = Vec::new();
+
+    if partial.starts_with("./") || partial.starts_with("../") {
+        dir_entries(&dir.join(dir_part), name_prefix, &mut items);
+    } else {
+        for (map, path) in import_paths {
+            if let Some(map) = map.as_ref().and_then(|map| map.to_str()) {
+                if let Some(rest) = dir_part.strip_prefix(&format!("{map}/")) {
+                    dir_entries(&path.join(rest), name_prefix, &mut items);
+                } else if dir_part.is_empty() && map.starts_with(name_prefix) {
+                    items.push(CompletionItem {
+                        label: format!("{map}/"),
+                        kind: Some(CompletionItemKind::FOLDER),
+                        detail: Some(path.display().to_string()),
+                        ..Default::default()
+                    });
+                }
+            } else {
+                dir_entries(&path.join(dir_part), name_prefix, &mut items);
+            }
+        }
+    }
+
+    Some(dedup(items))
+}
+
+/// Add the directories and Solidity files in the given directory
+fn dir_entries(dir: &Path, prefix: &str, items: &mut Vec<CompletionItem>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if !name.starts_with(prefix) || name.starts_with('.') {
+            continue;
+        }
+
+        let path = entry.path();
+
+        if path.is_dir() {
+            items.push(CompletionItem {
+                label: format!("{name}/"),
+                kind: Some(CompletionItemKind::FOLDER),
+                ..Default::default()
+            });
+        } else if path.extension().is_some_and(|ext| ext == "sol") {
+            items.push(CompletionItem {
+                label: name,
+                kind: Some(CompletionItemKind::FILE),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// Provide completions for the identifier or member access which ends at `offset` in `text`. The
+/// text may have changed since the namespace in the cache was resolved, so everything is looked up
+/// by name rather than by location.
+pub(crate) fn completions(
+    cache: &CompletionCache,
+    text: &str,
+    offset: usize,
+) -> Option<Vec<CompletionItem>> {
+    let before = text.get(..offset)?;
+
+    let word_start = word_start(before);
+
+    let (contract_no, function_no) = enclosing(&cache.ns, cache.file_no, offset);
+
+    let context = Context {
+        ns: &cache.ns,
+        file_no: cache.file_no,
+        contract_no,
+        function_no,
+        offset,
+    };
+
+    let items = if before[..word_start].ends_with('.') {
+        let chain = member_chain(&before[..word_start - 1])?;
+
+        let item = context.resolve_chain(&chain)?;
+
+        context.members(&item)
+    } else {
+        context.identifiers()
+    };
+
+    Some(dedup(items))
+}
+
+/// Remove completion items with identical labels; overloaded functions only need to be shown once
+fn dedup(items: Vec<CompletionItem>) -> Vec<CompletionItem> {
+    let mut seen = HashSet::new();
+
+    items
+        .into_iter()
+        .filter(|item| seen.insert(item.label.clone()))
+        .collect()
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+/// The offset of the identifier at the end of the text
+fn word_start(text: &str) -> usize {
+    text.char_indices()
+        .rev()
+        .find(|(_, c)| !is_identifier_char(*c))
+        .map_or(0, |(pos, c)| pos + c.len_utf8())
+}
+
+/// Find the contract and function the offset is in. The function is the last one whose prototype
+/// starts before the offset.
+fn enclosing(ns: &Namespace, file_no: usize, offset: usize) -> (Option<usize>, Option<usize>) {
+    let contract_no = ns.contracts.iter().position(|contract| {
+        contract.loc.try_file_no() == Some(file_no)
+            && contract.loc.start() <= offset
+            && offset <= contract.loc.end()
+    });
+
+    let function_no = ns
+        .functions
+        .iter()
+        .enumerate()
+        .filter(|(_, func)| {
+            func.contract_no == contract_no
+                && !func.is_accessor
+                && func.loc.try_file_no() == Some(file_no)
+                && func.loc.start() <= offset
+        })
+        .max_by_key(|(_, func)| func.loc.start())
+        .map(|(function_no, _)| function_no);
+
+    (contract_no, function_no)
+}
+
+/// Parse the member access chain which ends at the end of `text`, e.g. `foo.bar(1)[2]`. Returns
+/// `None` if the text does not end in something we can resolve by name.
+fn member_chain(text: &str) -> Option<Vec<Segment>> {
+    let bytes = text.as_bytes();
+    let mut pos = text.trim_end().len();
+    let mut segments = Vec::new();
+
+    loop {
+        if pos == 0 {
+            return None;
+        }
+
+        match bytes[pos - 1] {
+            close @ (b')' | b']') => {
+                let mut depth = 0;
+                let mut start = pos;
+
+                loop {
+                    if start == 0 {
+                        return None;
+                    }
+
+                    start -= 1;
+
+                    match bytes[start] {
+                        b')' | b']' => depth += 1,
+                        b'(' | b'[' => {
+                            depth -= 1;
+
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+
+                segments.push(if close == b')' {
+                    Segment::Call
+                } else {
+                    Segment::Index
+                });
+
+                pos = text[..start].trim_end().len();
+            }
+            c if is_identifier_char(c as char) => {
+                let start = word_start(&text[..pos]);
+
+                let name = &text[start..pos];
+
+                if name.starts_with(|c: char| c.is_ascii_digit()) {
+                    return None;
+                }
+
+                segments.push(Segment::Ident(name.to_owned()));
+
+                pos = text[..start].trim_end().len();
+
+                if pos > 0 && bytes[pos - 1] == b'.' {
+                    pos = text[..pos - 1].trim_end().len();
+                } else {
+                    break;
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    segments.reverse();
+
+    match segments.first() {
+        Some(Segment::Ident(_)) => Some(segments),
+        _ => None,
+    }
+}
+
+impl<'a> Context<'a> {
+    fn identifier(&self, name: &str) -> pt::Identifier {
+        pt::Identifier {
+            loc: pt::Loc::File(self.file_no, self.offset, self.offset),
+            name: name.to_owned(),
+        }
+    }
+
+    /// The local variables which are declared before the cursor
+    fn locals(&self) -> Vec<&'a symtable::Variable> {
+        let Some(function_no) = self.function_no else {
+            return Vec::new();
+        };
+
+        self.ns.functions[function_no]
+            .symtable
+            .vars
+            .values()
+            .filter(|var| {
+                !var.id.name.is_empty()
+                    && var.id.loc.try_file_no() == Some(self.file_no)
+                    && var.id.loc.start() < self.offset
+            })
+            .collect()
+    }
+
+    fn resolve_chain(&self, chain: &[Segment]) -> Option<Item> {
+        let Segment::Ident(name) = &chain[0] else {
+            return None;
+        };
+
+        let mut item = self.resolve_name(name)?;
+
+        for segment in &chain[1..] {
+            item = match segment {
+                Segment::Ident(name) => self.member(&item, name)?,
+                Segment::Call => self.call(&item)?,
+                Segment::Index => self.index(&item)?,
+            };
+        }
+
+        Some(item)
+    }
+
+    /// Resolve the first identifier in a chain
+    fn resolve_name(&self, name: &str) -> Option<Item> {
+        match name {
+            "this" => return self.contract_no.map(|no| Item::Value(Type::Contract(no))),
+            "super" => return self.contract_no.map(Item::Contract),
+            "address" => return Some(Item::TypeName(Type::Address(false))),
+            "payable" => return Some(Item::TypeName(Type::Address(true))),
+            _ => (),
+        }
+
+        // the last declaration wins if the name is reused in different blocks
+        if let Some(var) = self
+            .locals()
+            .into_iter()
+            .rev()
+            .find(|var| var.id.name == name)
+        {
+            return Some(Item::Value(var.ty.clone()));
+        }
+
+        if let Some(symbol) = self.ns.resolve_var(
+            self.file_no,
+            self.contract_no,
+            &self.identifier(name),
+            false,
+        ) {
+            return self.symbol(symbol);
+        }
+
+        builtin::namespaces()
+            .into_iter()
+            .find(|namespace| *namespace == name)
+            .map(Item::Namespace)
+    }
+
+    fn symbol(&self, symbol: &Symbol) -> Option<Item> {
+        match symbol {
+            Symbol::Variable(_, Some(contract_no), var_no) => Some(Item::Value(
+                self.ns.contracts[*contract_no].variables[*var_no]
+                    .ty
+                    .clone(),
+            )),
+            Symbol::Variable(_, None, var_no) => {
+                Some(Item::Value(self.ns.constants[*var_no].ty.clone()))
+            }
+            Symbol::Contract(_, contract_no) => Some(Item::Contract(*contract_no)),
+            Symbol::Enum(_, enum_no) => Some(Item::Enum(*enum_no)),
+            Symbol::UserType(_, type_no) => Some(Item::UserType(*type_no)),
+            Symbol::Struct(_, struct_ty) => Some(Item::TypeName(Type::Struct(*struct_ty))),
+            Symbol::Function(list) => Some(Item::Functions(
+                list.iter().map(|(_, function_no)| *function_no).collect(),
+            )),
+            Symbol::Import(_, file_no) => Some(Item::Import(*file_no)),
+            Symbol::Event(_) | Symbol::Error(..) => None,
+        }
+    }
+
+    /// Resolve `item.name`
+    fn member(&self, item: &Item, name: &str) -> Option<Item> {
+        match item {
+            Item::Namespace(namespace) => builtin::namespace_members(Some(namespace), self.ns)
+                .into_iter()
+                .find(|p| p.name == name)
+                .map(|p| {
+                    if p.params.is_empty() && self.is_variable(p) {
+                        Item::Value(p.ret[0].clone())
+                    } else {
+                        Item::Builtin(p)
+                    }
+                }),
+            Item::Contract(contract_no) => {
+                self.contract_symbols(*contract_no)
+                    .find_map(|(symbol_name, symbol)| {
+                        if symbol_name == name {
+                            self.symbol(symbol)
+                        } else {
+                            None
+                        }
+                    })
+            }
+            Item::Import(file_no) => self
+                .ns
+                .variable_symbols
+                .get(&(*file_no, None, name.to_owned()))
+                .or_else(|| {
+                    self.ns
+                        .function_symbols
+                        .get(&(*file_no, None, name.to_owned()))
+                })
+                .and_then(|symbol| self.symbol(symbol)),
+            Item::Enum(enum_no) => self.ns.enums[*enum_no]
+                .values
+                .contains_key(name)
+                .then_some(Item::Value(Type::Enum(*enum_no))),
+            Item::Value(ty) => self.value_member(ty, name),
+            Item::UserType(_) | Item::TypeName(_) | Item::Builtin(_) | Item::Functions(_) => None,
+        }
+    }
+
+    fn value_member(&self, ty: &Type, name: &str) -> Option<Item> {
+        match ty {
+            Type::Ref(ty) | Type::StorageRef(_, ty) => self.value_member(ty, name),
+            Type::Struct(struct_ty) => struct_ty
+                .definition(self.ns)
+                .fields
+                .iter()
+                .find(|field| field.name_as_str() == name)
+                .map(|field| Item::Value(field.ty.clone())),
+            Type::Array(elem_ty, _)
+                if matches!(**elem_ty, Type::Struct(StructType::AccountInfo)) =>
+            {
+                if name == "length" {
+                    Some(Item::Value(Type::Uint(32)))
+                } else {
+                    Some(Item::Value(Type::Struct(StructType::AccountInfo)))
+                }
+            }
+            Type::Array(..) | Type::DynamicBytes | Type::String | Type::Bytes(_)
+                if name == "length" =>
+            {
+                Some(Item::Value(Type::Uint(32)))
+            }
+            Type::Contract(contract_no) => {
+                let functions: Vec<usize> = self
+                    .external_functions(*contract_no)
+                    .filter(|function_no| self.ns.functions[*function_no].id.name == name)
+                    .collect();
+
+                (!functions.is_empty()).then_some(Item::Functions(functions))
+            }
+            _ => builtin::type_methods(ty, self.ns)
+                .into_iter()
+                .find(|p| p.name == name)
+                .map(Item::Builtin),
+        }
+    }
+
+    /// Resolve `item(...)`
+    fn call(&self, item: &Item) -> Option<Item> {
+        match item {
+            Item::Functions(functions) => {
+                let func = &self.ns.functions[*functions.first()?];
+
+                if func.returns.len() == 1 {
+                    Some(Item::Value(func.returns[0].ty.clone()))
+                } else {
+                    None
+                }
+            }
+            Item::Builtin(p) if p.ret.len() == 1 => Some(Item::Value(p.ret[0].clone())),
+            Item::Contract(contract_no) => Some(Item::Value(Type::Contract(*contract_no))),
+            Item::UserType(type_no) => Some(Item::Value(Type::UserType(*type_no))),
+            Item::TypeName(ty) => Some(Item::Value(ty.clone())),
+            _ => None,
+        }
+    }
+
+    /// Resolve `item[...]`
+    fn index(&self, item: &Item) -> Option<Item> {
+        let Item::Value(ty) = item else {
+            return None;
+        };
+
+        match ty.deref_any() {
+            Type::Array(elem_ty, dims) if dims.len() > 1 => Some(Item::Value(Type::Array(
+                elem_ty.clone(),
+                dims[..dims.len() - 1].to_vec(),
+            ))),
+            Type::Array(elem_ty, _) => Some(Item::Value(*elem_ty.clone())),
+            Type::Mapping(ast::Mapping { value, .. }) => Some(Item::Value(*value.clone())),
+            Type::DynamicBytes | Type::Bytes(_) => Some(Item::Value(Type::Bytes(1))),
+            _ => None,
+        }
+    }
+
+    /// Builtin variables have no parameters and are not functions, e.g. `block.number`
+    fn is_variable(&self, p: &Prototype) -> bool {
+        builtin::builtin_var(
+            &pt::Loc::Builtin,
+            p.namespace,
+            p.name,
+            self.ns,
+            &mut Default::default(),
+        )
+        .is_some()
+    }
+
+    /// All the symbols declared in a contract or its bases
+    fn contract_symbols(
+        &self,
+        contract_no: usize,
+    ) -> impl Iterator<Item = (&'a String, &'a Symbol)> {
+        let bases = self.ns.contract_bases(contract_no);
+
+        self.ns
+            .variable_symbols
+            .iter()
+            .chain(self.ns.function_symbols.iter())
+            .filter(move |((_, contract, _), _)| {
+                contract.is_some_and(|contract| bases.contains(&contract))
+            })
+            .map(|((_, _, name), symbol)| (name, symbol))
+    }
+
+    /// The functions which can be called externally on a contract
+    fn external_functions(&self, contract_no: usize) -> impl Iterator<Item = usize> + 'a {
+        let ns = self.ns;
+
+        ns.contracts[contract_no]
+            .all_functions
+            .keys()
+            .copied()
+            .filter(move |function_no| {
+                let func = &ns.functions[*function_no];
+
+                func.ty == pt::FunctionTy::Function
+                    && matches!(
+                        func.visibility,
+                        pt::Visibility::Public(_) | pt::Visibility::External(_)
+                    )
+            })
+    }
+
+    /// List the members of an item
+    fn members(&self, item: &Item) -> Vec<CompletionItem> {
+        match item {
+            Item::Namespace(namespace) => builtin::namespace_members(Some(namespace), self.ns)
+                .into_iter()
+                .map(|p| self.prototype_item(p))
+                .collect(),
+            Item::Contract(contract_no) => {
+                let mut items: Vec<CompletionItem> = self
+                    .contract_symbols(*contract_no)
+                    .filter_map(|(name, symbol)| self.symbol_item(name, symbol))
+                    .collect();
+
+                items.sort_by(|a, b| a.label.cmp(&b.label));
+
+                items
+            }
+            Item::Import(file_no) => {
+                let mut items: Vec<CompletionItem> = self
+                    .ns
+                    .variable_symbols
+                    .iter()
+                    .chain(self.ns.function_symbols.iter())
+                    .filter(|((symbol_file_no, contract, _), _)| {
+                        symbol_file_no == file_no && contract.is_none()
+                    })
+                    .filter_map(|((_, _, name), symbol)| self.symbol_item(name, symbol))
+                    .collect();
+
+                items.sort_by(|a, b| a.label.cmp(&b.label));
+
+                items
+            }
+            Item::Enum(enum_no) => self.ns.enums[*enum_no]
+                .values
+                .keys()
+                .map(|name| CompletionItem {
+                    label: name.clone(),
+                    kind: Some(CompletionItemKind::ENUM_MEMBER),
+                    detail: Some(self.ns.enums[*enum_no].to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+            Item::UserType(type_no) => {
+                let decl = &self.ns.user_types[*type_no];
+
+                ["wrap", "unwrap"]
+                    .into_iter()
+                    .map(|name| CompletionItem {
+                        label: name.to_owned(),
+                        kind: Some(CompletionItemKind::FUNCTION),
+                        detail: Some(format!("{} {}", decl, decl.ty.to_string(self.ns))),
+                        ..Default::default()
+                    })
+                    .collect()
+            }
+            Item::Functions(_) => vec![CompletionItem {
+                label: "selector".to_owned(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                detail: Some(Type::FunctionSelector.to_string(self.ns)),
+                ..Default::default()
+            }],
+            Item::Value(ty) => self.value_members(ty),
+            Item::TypeName(_) | Item::Builtin(_) => Vec::new(),
+        }
+    }
+
+    fn value_members(&self, ty: &Type) -> Vec<CompletionItem> {
+        let mut items = Vec::new();
+
+        let storage = matches!(ty, Type::StorageRef(..));
+
+        match ty.deref_any() {
+            Type::Struct(struct_ty) => {
+                for field in &struct_ty.definition(self.ns).fields {
+                    items.push(CompletionItem {
+                        label: field.name_as_str().to_owned(),
+                        kind: Some(CompletionItemKind::FIELD),
+                        detail: Some(field.ty.to_string(self.ns)),
+                        ..Default::default()
+                    });
+                }
+            }
+            Type::Array(elem_ty, dims) => {
+                items.push(self.property("length", &Type::Uint(32)));
+
+                if matches!(dims.last(), Some(ArrayLength::Dynamic)) {
+                    items.push(self.method("push", "push an element onto the array"));
+                    items.push(self.method("pop", "remove the last element of the array"));
+                }
+
+                if matches!(**elem_ty, Type::Struct(StructType::AccountInfo))
+                    && self.ns.target == Target::Solana
+                {
+                    items.extend(self.accounts());
+                }
+            }
+            Type::DynamicBytes => {
+                items.push(self.property("length", &Type::Uint(32)));
+                items.push(self.method("push", "push a byte onto the bytes array"));
+                items.push(self.method("pop", "remove the last byte of the bytes array"));
+            }
+            Type::String | Type::Bytes(_) | Type::Slice(_) => {
+                items.push(self.property("length", &Type::Uint(32)));
+            }
+            Type::Contract(contract_no) => {
+                for function_no in self.external_functions(*contract_no) {
+                    items.push(self.function_item(function_no));
+                }
+            }
+            Type::Address(payable) => {
+                if self.ns.target != Target::Solana {
+                    items.push(self.property("balance", &Type::Value));
+
+                    if *payable {
+                        items.push(self.method("transfer", "transfer value to the address"));
+                        items.push(self.method("send", "send value to the address"));
+                    }
+
+                    items.push(self.method("delegatecall", "delegate call to the address"));
+                }
+
+                if self.ns.target == Target::EVM {
+                    items.push(self.property("code", &Type::DynamicBytes));
+                    items.push(self.method("staticcall", "static call to the address"));
+                }
+
+                items.push(self.method("call", "call the address with raw calldata"));
+            }
+            Type::ExternalFunction { .. } => {
+                items.push(self.property("selector", &Type::FunctionSelector));
+                items.push(self.property("address", &Type::Address(false)));
+            }
+            _ => (),
+        }
+
+        // Storage variables have no builtin methods, just the length and push/pop
+        if !storage {
+            items.extend(
+                builtin::type_methods(ty, self.ns)
+                    .into_iter()
+                    .map(|p| self.prototype_item(p)),
+            );
+        }
+
+        items.extend(self.using_functions(ty.deref_any()));
+
+        items
+    }
+
+    /// The accounts which are available through `tx.accounts` in the current function
+    fn accounts(&self) -> Vec<CompletionItem> {
+        // the data account is always available, even if it is not used by the function
+        let mut names = vec![String::from("dataAccount")];
+
+        if let Some(function_no) = self.function_no {
+            names.extend(
+                self.ns.functions[function_no]
+                    .solana_accounts
+                    .borrow()
+                    .keys()
+                    .cloned(),
+            );
+        }
+
+        names
+            .into_iter()
+            .map(|name| CompletionItem {
+                label: name,
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(Type::Struct(StructType::AccountInfo).to_string(self.ns)),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Functions which are attached to the type with `using ... for`
+    fn using_functions(&self, ty: &Type) -> Vec<CompletionItem> {
+        let contract_using = self
+            .contract_no
+            .map(|contract_no| self.ns.contracts[contract_no].using.iter())
+            .into_iter()
+            .flatten();
+
+        let mut items = Vec::new();
+
+        for using in self.ns.using.iter().chain(contract_using) {
+            if using.ty.as_ref().is_some_and(|using_ty| using_ty != ty) {
+                continue;
+            }
+
+            let functions = match &using.list {
+                ast::UsingList::Library(library_no) => {
+                    self.ns.contracts[*library_no].functions.clone()
+                }
+                ast::UsingList::Functions(functions) => functions
+                    .iter()
+                    .filter(|func| func.oper.is_none())
+                    .map(|func| func.function_no)
+                    .collect(),
+            };
+
+            for function_no in functions {
+                let func = &self.ns.functions[function_no];
+
+                if func
+                    .params
+                    .first()
+                    .is_some_and(|param| param.ty.deref_any() == ty)
+                {
+                    items.push(self.function_item(function_no));
+                }
+            }
+        }
+
+        items
+    }
+
+    /// List everything which is in scope at the cursor
+    fn identifiers(&self) -> Vec<CompletionItem> {
+        let mut items: Vec<CompletionItem> = self
+            .locals()
+            .into_iter()
+            .rev()
+            .map(|var| CompletionItem {
+                label: var.id.name.clone(),
+                kind: Some(CompletionItemKind::VARIABLE),
+                detail: Some(var.ty.to_string(self.ns)),
+                ..Default::default()
+            })
+            .collect();
+
+        let bases = self
+            .contract_no
+            .map(|contract_no| self.ns.contract_bases(contract_no))
+            .unwrap_or_default();
+
+        let mut symbols: Vec<CompletionItem> = self
+            .ns
+            .variable_symbols
+            .iter()
+            .chain(self.ns.function_symbols.iter())
+            .filter(|((file_no, contract, _), _)| match contract {
+                Some(contract_no) => bases.contains(contract_no),
+                None => *file_no == self.file_no,
+            })
+            .filter_map(|((_, _, name), symbol)| self.symbol_item(name, symbol))
+            .collect();
+
+        symbols.sort_by(|a, b| a.label.cmp(&b.label));
+
+        items.extend(symbols);
+
+        if self.contract_no.is_some() {
+            items.push(CompletionItem {
+                label: "this".to_owned(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                ..Default::default()
+            });
+            items.push(CompletionItem {
+                label: "super".to_owned(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                ..Default::default()
+            });
+        }
+
+        items.extend(
+            builtin::namespace_members(None, self.ns)
+                .into_iter()
+                .map(|p| self.prototype_item(p)),
+        );
+
+        items.extend(
+            builtin::namespaces()
+                .into_iter()
+                .map(|name| CompletionItem {
+                    label: name.to_owned(),
+                    kind: Some(CompletionItemKind::MODULE),
+                    ..Default::default()
+                }),
+        );
+
+        items.extend(KEYWORDS.iter().map(|keyword| CompletionItem {
+            label: keyword.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            ..Default::default()
+        }));
+
+        items
+    }
+
+    fn symbol_item(&self, name: &str, symbol: &Symbol) -> Option<CompletionItem> {
+        let (kind, detail, tags) = match symbol {
+            Symbol::Variable(_, contract_no, var_no) => {
+                let var = match contract_no {
+                    Some(contract_no) => &self.ns.contracts[*contract_no].variables[*var_no],
+                    None => &self.ns.constants[*var_no],
+                };
+
+                let kind = if var.constant {
+                    CompletionItemKind::CONSTANT
+                } else {
+                    CompletionItemKind::FIELD
+                };
+
+                (kind, var.ty.to_string(self.ns), render(&var.tags))
+            }
+            Symbol::Function(list) => {
+                let function_no = list.first()?.1;
+
+                return Some(self.function_item(function_no));
+            }
+            Symbol::Contract(_, contract_no) => {
+                let contract = &self.ns.contracts[*contract_no];
+
+                (
+                    CompletionItemKind::CLASS,
+                    format!("{} {}", contract.ty, contract.id),
+                    render(&contract.tags),
+                )
+            }
+            Symbol::Struct(_, struct_ty) => {
+                let decl = struct_ty.definition(self.ns);
+
+                (
+                    CompletionItemKind::STRUCT,
+                    format!("struct {decl}"),
+                    render(&decl.tags),
+                )
+            }
+            Symbol::Enum(_, enum_no) => {
+                let decl = &self.ns.enums[*enum_no];
+
+                (
+                    CompletionItemKind::ENUM,
+                    format!("enum {decl}"),
+                    render(&decl.tags),
+                )
+            }
+            Symbol::Event(list) => {
+                let decl = &self.ns.events[list.first()?.1];
+
+                (
+                    CompletionItemKind::EVENT,
+                    format!("event {}", decl.symbol_name(self.ns)),
+                    render(&decl.tags),
+                )
+            }
+            Symbol::Error(_, error_no) => {
+                let decl = &self.ns.errors[*error_no];
+
+                (
+                    CompletionItemKind::STRUCT,
+                    format!("error {}", decl.name),
+                    render(&decl.tags),
+                )
+            }
+            Symbol::UserType(_, type_no) => {
+                let decl = &self.ns.user_types[*type_no];
+
+                (
+                    CompletionItemKind::TYPE_PARAMETER,
+                    format!("type {} is {}", decl, decl.ty.to_string(self.ns)),
+                    render(&decl.tags),
+                )
+            }
+            Symbol::Import(_, file_no) => (
+                CompletionItemKind::MODULE,
+                self.ns.files[*file_no].to_string(),
+                String::new(),
+            ),
+        };
+
+        Some(CompletionItem {
+            label: name.to_owned(),
+            kind: Some(kind),
+            detail: Some(detail),
+            documentation: (!tags.is_empty()).then_some(Documentation::String(tags)),
+            ..Default::default()
+        })
+    }
+
+    fn function_item(&self, function_no: usize) -> CompletionItem {
+        let func = &self.ns.functions[function_no];
+
+        let params = func
+            .params
+            .iter()
+            .map(|param| format!("{} {}", param.ty.to_string(self.ns), param.name_as_str()))
+            .join(", ");
+
+        let returns = func
+            .returns
+            .iter()
+            .map(|ret| ret.ty.to_string(self.ns))
+            .join(", ");
+
+        let detail = if returns.is_empty() {
+            format!("{} {}({})", func.ty, func.id, params)
+        } else {
+            format!("{} {}({}) returns ({})", func.ty, func.id, params, returns)
+        };
+
+        let tags = render(&func.tags);
+
+        CompletionItem {
+            label: func.id.name.clone(),
+            kind: Some(CompletionItemKind::FUNCTION),
+            detail: Some(detail),
+            documentation: (!tags.is_empty()).then_some(Documentation::String(tags)),
+            ..Default::default()
+        }
+    }
+
+    fn prototype_item(&self, p: &Prototype) -> CompletionItem {
+        let (kind, detail) = if self.is_variable(p) {
+            (CompletionItemKind::PROPERTY, p.ret[0].to_string(self.ns))
+        } else {
+            let params = p.params.iter().map(|ty| ty.to_string(self.ns)).join(", ");
+
+            let returns = p
+                .ret
+                .iter()
+                .filter(|ty| !matches!(ty, Type::Void | Type::Unreachable))
+                .map(|ty| ty.to_string(self.ns))
+                .join(", ");
+
+            let detail = if returns.is_empty() {
+                format!("{}({})", p.name, params)
+            } else {
+                format!("{}({}) returns ({})", p.name, params, returns)
+            };
+
+            (CompletionItemKind::FUNCTION, detail)
+        };
+
+        CompletionItem {
+            label: p.name.to_owned(),
+            kind: Some(kind),
+            detail: Some(detail),
+            documentation: Some(Documentation::String(p.doc.to_owned())),
+            ..Default::default()
+        }
+    }
+
+    fn property(&self, name: &str, ty: &Type) -> CompletionItem {
+        CompletionItem {
+            label: name.to_owned(),
+            kind: Some(CompletionItemKind::PROPERTY),
+            detail: Some(ty.to_string(self.ns)),
+            ..Default::default()
+        }
+    }
+
+    fn method(&self, name: &str, doc: &str) -> CompletionItem {
+        CompletionItem {
+            label: name.to_owned(),
+            kind: Some(CompletionItemKind::METHOD),
+            documentation: Some(Documentation::String(doc.to_owned())),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains() {
+        assert_eq!(
+            member_chain("x = tx.accounts.dataAccount"),
+            Some(vec![
+                Segment::Ident("tx".to_owned()),
+                Segment::Ident("accounts".to_owned()),
+                Segment::Ident("dataAccount".to_owned())
+            ])
+        );
+
+        assert_eq!(
+            member_chain("return foo(a, (b + 1)) [ i ]"),
+            Some(vec![
+                Segment::Ident("foo".to_owned()),
+                Segment::Call,
+                Segment::Index
+            ])
+        );
+
+        assert_eq!(
+            member_chain("bar.baz(1)[2]. qux"),
+            Some(vec![
+                Segment::Ident("bar".to_owned()),
+                Segment::Ident("baz".to_owned()),
+                Segment::Call,
+                Segment::Index,
+                Segment::Ident("qux".to_owned()),
+            ])
+        );
+
+        assert_eq!(member_chain("(a + b)"), None);
+        assert_eq!(member_chain("1"), None);
+        assert_eq!(member_chain(""), None);
+    }
+
+    #[test]
+    fn positions() {
+        let text = "contract c {\n  function f() {}\n}";
+
+        assert_eq!(position_to_offset(text, 0, 3), Some(3));
+        assert_eq!(position_to_offset(text, 1, 2), Some(15));
+        assert_eq!(position_to_offset(text, 1, 100), Some(30));
+        assert_eq!(position_to_offset(text, 2, 1), Some(32));
+        assert_eq!(position_to_offset(text, 5, 0), None);
+
+        // é is 2 bytes but 1 UTF-16 code unit, 🦀 is 4 bytes and 2 code units
+        let text = "// é🦀\nx.";
+
+        assert_eq!(position_to_offset(text, 0, 4), Some(5));
+        assert_eq!(position_to_offset(text, 0, 5), Some(9));
+        assert_eq!(position_to_offset(text, 0, 6), Some(9));
+        assert_eq!(position_to_offset(text, 0, 7), Some(9));
+        assert_eq!(position_to_offset(text, 1, 2), Some(12));
+    }
+
+    #[test]
+    fn word_starts() {
+        assert_eq!(word_start("foo.ba"), 4);
+        assert_eq!(word_start("ba"), 0);
+        assert_eq!(word_start("// é"), 5);
+        assert_eq!(word_start("éba"), 2);
+    }
+
+    #[test]
+    fn members() {
+        let src = r#"
+        struct S { uint64 f1; bool f2; }
+        enum E { One, Two }
+        contract c {
+            S s;
+            uint64[] arr;
+            function foo(S memory x) public returns (uint64) {
+                uint64 y = x.f1;
+                return y + s.f1 + arr.length;
+            }
+        }"#;
+
+        let mut resolver = solang::file_resolver::FileResolver::default();
+        resolver.set_file_contents("test.sol", src.to_owned());
+
+        let ns = solang::parse_and_resolve(
+            std::ffi::OsStr::new("test.sol"),
+            &mut resolver,
+            Target::Solana,
+        );
+
+        let file_no = ns.top_file_no();
+
+        let cache = CompletionCache { ns, file_no };
+
+        let labels = |text: &str| -> Vec<String> {
+            completions(&cache, text, text.len())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|item| item.label)
+                .collect()
+        };
+
+        let body = src.find("uint64 y").unwrap();
+        let text = &src[..body];
+
+        assert_eq!(labels(&format!("{text}x.")), vec!["f1", "f2"]);
+        assert_eq!(labels(&format!("{text}s.f")), vec!["f1", "f2"]);
+        assert_eq!(labels(&format!("{text}E.")), vec!["One", "Two"]);
+        assert!(labels(&format!("{text}arr.")).contains(&"push".to_owned()));
+        assert!(labels(&format!("{text}block.")).contains(&"timestamp".to_owned()));
+        assert!(labels(&format!("{text}tx.accounts.")).contains(&"dataAccount".to_owned()));
+        assert!(labels(&format!("{text}tx.accounts.dataAccount.")).contains(&"lamports".to_owned()));
+
+        let identifiers = labels(&format!("{text}y"));
+        assert!(identifiers.contains(&"x".to_owned()));
+        assert!(identifiers.contains(&"foo".to_owned()));
+        assert!(identifiers.contains(&"S".to_owned()));
+        assert!(identifiers.contains(&"require".to_owned()));
+        assert!(identifiers.contains(&"while".to_owned()));
+    }
+}
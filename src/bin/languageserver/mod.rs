@@ -21,7 +21,7 @@ use solang_parser::pt;
 use std::{
     collections::{HashMap, HashSet},
     ffi::OsString,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use tokio::sync::Mutex;
 use tower_lsp::{
@@ -49,6 +49,10 @@ use tower_lsp::{
 
 use crate::cli::{target_arg, LanguageServerCommand};
 
+use self::completion::{completions, import_completions, position_to_offset, CompletionCache};
+
+mod completion;
+
 /// Represents the type of the code object that a reference points to
 /// Here "code object" refers to contracts, functions, structs, enums etc., that are defined and used within a namespace.
 /// It is used along with the path of the file where the code object is defined to uniquely identify an code object.
@@ -118,6 +122,7 @@ type Declarations = HashMap<DefinitionIndex, Vec<DefinitionIndex>>;
 struct Files {
     caches: HashMap<PathBuf, FileCache>,
     text_buffers: HashMap<PathBuf, String>,
+    completions: HashMap<PathBuf, CompletionCache>,
 }
 
 #[derive(Debug)]
@@ -214,6 +219,7 @@ pub async fn start_server(language_args: &LanguageServerCommand) -> ! {
         files: Mutex::new(Files {
             caches: HashMap::new(),
             text_buffers: HashMap::new(),
+            completions: HashMap::new(),
         }),
         global_cache: Mutex::new(GlobalCache {
             definitions: HashMap::new(),
@@ -229,27 +235,35 @@ pub async fn start_server(language_args: &LanguageServerCommand) -> ! {
 }
 
 impl SolangServer {
-    /// Parse file
-    async fn parse_file(&self, uri: Url) {
+    /// Create a file resolver for a file in the given directory, with the configured import paths and maps
+    fn file_resolver(&self, dir: &Path) -> FileResolver {
         let mut resolver = FileResolver::default();
-        for (path, contents) in &self.files.lock().await.text_buffers {
-            resolver.set_file_contents(path.to_str().unwrap(), contents.clone());
+
+        resolver.add_import_path(dir);
+
+        for path in &self.importpaths {
+            resolver.add_import_path(path);
         }
-        if let Ok(path) = uri.to_file_path() {
-            let dir = path.parent().unwrap();
 
-            resolver.add_import_path(dir);
+        for (map, path) in &self.importmaps {
+            resolver.add_import_map(OsString::from(map), PathBuf::from(path));
+        }
 
-            let mut diags = Vec::new();
+        resolver
+    }
 
-            for path in &self.importpaths {
-                resolver.add_import_path(path);
-            }
+    /// Parse file
+    async fn parse_file(&self, uri: Url) {
+        if let Ok(path) = uri.to_file_path() {
+            let dir = path.parent().unwrap();
 
-            for (map, path) in &self.importmaps {
-                resolver.add_import_map(OsString::from(map), PathBuf::from(path));
+            let mut resolver = self.file_resolver(dir);
+            for (path, contents) in &self.files.lock().await.text_buffers {
+                resolver.set_file_contents(path.to_str().unwrap(), contents.clone());
             }
 
+            let mut diags = Vec::new();
+
             let os_str = path.file_name().unwrap();
 
             let mut ns = parse_and_resolve(os_str, &mut resolver, self.target);
@@ -312,6 +326,19 @@ impl SolangServer {
                 }
             }
 
+            // While typing, the file will often not parse. Keep the last namespace which did parse,
+            // so that completions can still be offered.
+            if !ns
+                .diagnostics
+                .iter()
+                .any(|diag| diag.ty == ast::ErrorType::ParserError)
+            {
+                let file_no = ns.top_file_no();
+                files
+                    .completions
+                    .insert(path, CompletionCache { ns, file_no });
+            }
+
             let mut gc = self.global_cache.lock().await;
             gc.extend(global_cache);
 
@@ -1993,13 +2020,54 @@ impl LanguageServer for SolangServer {
             let mut files = self.files.lock().await;
             files.caches.remove(&path);
             files.text_buffers.remove(&path);
+            files.completions.remove(&path);
         }
 
         self.client.publish_diagnostics(uri, vec![], None).await;
     }
 
-    async fn completion(&self, _: CompletionParams) -> Result<Option<CompletionResponse>> {
-        Ok(None)
+    /// Called when the client requests completions, e.g. after typing `.`
+    ///
+    /// Completes import paths inside import directives, members after `.`, and otherwise
+    /// any identifiers which are in scope, builtins and keywords.
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let uri = params.text_document_position.text_document.uri;
+        let pos = params.text_document_position.position;
+
+        let path = uri.to_file_path().map_err(|_| Error {
+            code: ErrorCode::InvalidRequest,
+            message: format!("Received invalid URI: {uri}").into(),
+            data: None,
+        })?;
+
+        let files = self.files.lock().await;
+
+        let Some(text) = files.text_buffers.get(&path) else {
+            return Ok(None);
+        };
+
+        let Some(offset) = position_to_offset(text, pos.line as usize, pos.character as usize)
+        else {
+            return Ok(None);
+        };
+
+        let line_start = text[..offset].rfind('\n').map_or(0, |pos| pos + 1);
+
+        if let Some(dir) = path.parent() {
+            let resolver = self.file_resolver(dir);
+
+            if let Some(items) =
+                import_completions(&text[line_start..offset], dir, resolver.get_import_paths())
+            {
+                return Ok(Some(CompletionResponse::Array(items)));
+            }
+        }
+
+        Ok(files
+            .completions
+            .get(&path)
+            .and_then(|cache| completions(cache, text, offset))
+            .map(CompletionResponse::Array))
     }
 
     async fn hover(&self, hverparam: HoverParams) -> Result<Option<Hover>> {
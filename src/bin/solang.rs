@@ -8,10 +8,11 @@ use itertools::Itertools;
 use solang::{
     abi,
     codegen::{codegen, Options},
+    compute_units,
     emit::Generate,
     file_resolver::FileResolver,
-    sema::{ast::Namespace, file::PathDisplay},
-    standard_json::{EwasmContract, JsonContract, JsonResult},
+    sema::{ast::Namespace, callgraph, file::PathDisplay, solidity_version::SOLIDITY_VERSION},
+    standard_json::{EvmBytecode, EvmContract, EwasmContract, JsonContract, JsonResult},
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -32,6 +33,9 @@ mod doc;
 mod idl;
 #[cfg(feature = "language_server")]
 mod languageserver;
+mod storage_diff;
+#[cfg(feature = "test_runner")]
+mod test_runner;
 
 fn main() {
     let matches = Cli::command().get_matches();
@@ -63,6 +67,19 @@ fn main() {
         Commands::LanguageServer(server_args) => languageserver::start_server(&server_args),
         Commands::Idl(idl_args) => idl::idl(&idl_args),
         Commands::New(new_arg) => new_command(new_arg),
+        Commands::StorageDiff(diff_args) => storage_diff::storage_diff(&diff_args),
+        #[cfg(feature = "test_runner")]
+        Commands::Test(mut test_args) => {
+            // Like compile, the project settings come from the configuration file if it exists
+            if let Some(conf_file) = test_args.configuration_file.clone() {
+                if PathBuf::from(&conf_file).exists() {
+                    eprintln!("info: reading default config from toml file");
+                    let matches = matches.subcommand_matches("test").unwrap();
+                    test_args.overwrite_with_config(read_toml_config(&conf_file), matches);
+                }
+            }
+            test_runner::run(&test_args)
+        }
     }
 }
 
@@ -96,10 +113,7 @@ fn new_command(args: New) {
     let flipper = match target {
         "solana" => include_str!("../../examples/solana/flipper.sol"),
         "polkadot" => include_str!("../../examples/polkadot/flipper.sol"),
-        "evm" => {
-            eprintln!("EVM target is not supported yet!");
-            exit(1);
-        }
+        "evm" => include_str!("../../examples/evm/flipper.sol"),
         _ => unreachable!(),
     };
 
@@ -113,6 +127,7 @@ fn new_command(args: New) {
     let toml_content = match target {
         "solana" => include_str!("../../examples/solana/solana_config.toml"),
         "polkadot" => include_str!("../../examples/polkadot/polkadot_config.toml"),
+        "evm" => include_str!("../../examples/evm/evm_config.toml"),
         _ => unreachable!(),
     };
     toml_file
@@ -169,10 +184,22 @@ fn compile(compile_args: &Compile) {
         eprintln!("info: Solang version {}", env!("SOLANG_VERSION"));
     }
 
+    if let Some("cu-report") = compile_args.compiler_output.emit.as_deref() {
+        if target != solang::Target::Solana {
+            eprintln!("error: compute unit report is only available for the solana target");
+            exit(1);
+        }
+    }
+
     let mut resolver = imports_arg(&compile_args.package);
 
     let opt = options_arg(&compile_args.debug_features, &compile_args.optimizations);
 
+    let solidity_version = match &compile_args.package.solidity_version {
+        Some(version) => semver::Version::parse(version).unwrap(),
+        None => SOLIDITY_VERSION,
+    };
+
     let mut namespaces = Vec::new();
 
     let mut errors = false;
@@ -190,6 +217,7 @@ fn compile(compile_args: &Compile) {
             filename,
             &mut resolver,
             target,
+            &solidity_version,
             &compile_args.compiler_output,
             &opt,
         );
@@ -277,6 +305,10 @@ fn compile(compile_args: &Compile) {
         }
     }
 
+    if let Some(timings) = &opt.time_passes {
+        eprint!("{}", timings.report());
+    }
+
     if std_json {
         println!("{}", serde_json::to_string(&json).unwrap());
         exit(0);
@@ -308,6 +340,7 @@ fn process_file(
     filename: &Path,
     resolver: &mut FileResolver,
     target: solang::Target,
+    solidity_version: &semver::Version,
     compiler_output: &CompilerOutput,
     opt: &Options,
 ) -> Namespace {
@@ -319,10 +352,23 @@ fn process_file(
     };
 
     // resolve phase
-    let mut ns = solang::parse_and_resolve(filepath.as_os_str(), resolver, target);
+    let mut ns = opt.time("sema", || {
+        solang::parse_and_resolve_with_solidity_version(
+            filepath.as_os_str(),
+            resolver,
+            target,
+            solidity_version.clone(),
+        )
+    });
 
     // codegen all the contracts; some additional errors/warnings will be detected here
-    codegen(&mut ns, opt);
+    opt.time("codegen", || codegen(&mut ns, opt));
+
+    for contract in &ns.contracts {
+        if !contract.pass_dumps.is_empty() {
+            eprint!("{}", contract.print_pass_dumps(&ns));
+        }
+    }
 
     if let Some("ast-dot") = compiler_output.emit.as_deref() {
         let stem = filepath.file_stem().unwrap().to_string_lossy();
@@ -389,6 +435,77 @@ fn contract_results(
         return;
     }
 
+    if let Some("cfg-dot") = compiler_output.emit.as_deref() {
+        println!(
+            "{}",
+            resolved_contract.print_cfg_dot(ns, compiler_output.cfg_dot_annotations)
+        );
+        return;
+    }
+
+    if let Some("removed-checks") = compiler_output.emit.as_deref() {
+        println!("{}", resolved_contract.print_removed_checks(ns));
+        return;
+    }
+
+    if let Some("storage-layout") = compiler_output.emit.as_deref() {
+        let layout = abi::storage_layout::gen_storage_layout(contract_no, ns);
+        let layout_filename = output_file(
+            compiler_output,
+            &resolved_contract.id.name,
+            "storage-layout.json",
+            true,
+        );
+
+        if verbose {
+            eprintln!(
+                "info: Saving storage layout {} for contract {}",
+                layout_filename.display(),
+                resolved_contract.id
+            );
+        }
+
+        let mut file = create_file(&layout_filename);
+        file.write_all(serde_json::to_string_pretty(&layout).unwrap().as_bytes())
+            .unwrap();
+        return;
+    }
+
+    if let Some("callgraph") = compiler_output.emit.as_deref() {
+        let graph = callgraph::call_graph(contract_no, ns);
+
+        for (ext, contents) in [
+            ("callgraph.dot", graph.dot()),
+            ("callgraph.json", graph.json()),
+        ] {
+            let filename = output_file(compiler_output, &resolved_contract.id.name, ext, true);
+
+            if verbose {
+                eprintln!(
+                    "info: Saving call graph {} for contract {}",
+                    filename.display(),
+                    resolved_contract.id
+                );
+            }
+
+            let mut file = create_file(&filename);
+            file.write_all(contents.as_bytes()).unwrap();
+        }
+        return;
+    }
+
+    if ns.target == solang::Target::EVM {
+        evm_contract_results(
+            contract_no,
+            compiler_output,
+            ns,
+            json_contracts,
+            default_authors,
+            version,
+        );
+        return;
+    }
+
     if verbose {
         if ns.target == solang::Target::Solana {
             eprintln!(
@@ -407,6 +524,36 @@ fn contract_results(
 
     let binary = resolved_contract.binary(ns, &context, opt);
 
+    if let Some("cu-report") = compiler_output.emit.as_deref() {
+        let code = binary.code(Generate::Linked).expect("llvm build");
+
+        let report = match compute_units::compute_unit_report(contract_no, ns, &code) {
+            Ok(report) => report,
+            Err(err) => {
+                eprintln!(
+                    "error: cannot estimate compute units of contract {}: {err}",
+                    resolved_contract.id
+                );
+                exit(1);
+            }
+        };
+
+        let report_filename = output_file(compiler_output, &binary.name, "cu-report.json", true);
+
+        if verbose {
+            eprintln!(
+                "info: Saving compute unit report {} for contract {}",
+                report_filename.display(),
+                binary.name
+            );
+        }
+
+        let mut file = create_file(&report_filename);
+        file.write_all(serde_json::to_string_pretty(&report).unwrap().as_bytes())
+            .unwrap();
+        return;
+    }
+
     if save_intermediates(&binary, compiler_output) {
         return;
     }
@@ -429,7 +576,9 @@ fn contract_results(
                 ewasm: Some(EwasmContract {
                     wasm: hex::encode_upper(code),
                 }),
+                evm: None,
                 minimum_space: None,
+                storage_layout: Some(abi::storage_layout::gen_storage_layout(contract_no, ns)),
             },
         );
     } else {
@@ -469,6 +618,88 @@ fn contract_results(
     }
 }
 
+fn evm_contract_results(
+    contract_no: usize,
+    compiler_output: &CompilerOutput,
+    ns: &Namespace,
+    json_contracts: &mut HashMap<String, JsonContract>,
+    default_authors: &[String],
+    version: &str,
+) {
+    let verbose = compiler_output.verbose;
+    let contract = &ns.contracts[contract_no];
+
+    if verbose {
+        eprintln!("info: Generating EVM bytecode for contract {}", contract.id);
+    }
+
+    let code = match contract.code.get() {
+        Some(code) => code.clone(),
+        None => match solang::evm::compile(contract, ns) {
+            Ok(code) => code,
+            Err(diagnostic) => {
+                eprintln!(
+                    "{}: error: {}",
+                    ns.loc_to_string(PathDisplay::FullPath, &diagnostic.loc),
+                    diagnostic.message
+                );
+                exit(1);
+            }
+        },
+    };
+
+    if compiler_output.std_json_output {
+        json_contracts.insert(
+            contract.id.name.clone(),
+            JsonContract {
+                abi: abi::ethereum::gen_abi(contract_no, ns),
+                ewasm: None,
+                evm: Some(EvmContract {
+                    bytecode: EvmBytecode {
+                        object: hex::encode(code),
+                    },
+                }),
+                minimum_space: None,
+                storage_layout: Some(abi::storage_layout::gen_storage_layout(contract_no, ns)),
+            },
+        );
+    } else {
+        let bin_filename = output_file(
+            compiler_output,
+            &contract.id.name,
+            ns.target.file_extension(),
+            false,
+        );
+
+        if verbose {
+            eprintln!(
+                "info: Saving binary {} for contract {}",
+                bin_filename.display(),
+                contract.id
+            );
+        }
+
+        let mut file = create_file(&bin_filename);
+
+        file.write_all(hex::encode(&code).as_bytes()).unwrap();
+
+        let (metadata, meta_ext) =
+            abi::generate_abi(contract_no, ns, &code, verbose, default_authors, version);
+        let meta_filename = output_file(compiler_output, &contract.id.name, meta_ext, true);
+
+        if verbose {
+            eprintln!(
+                "info: Saving metadata {} for contract {}",
+                meta_filename.display(),
+                contract.id
+            );
+        }
+
+        let mut file = create_file(&meta_filename);
+        file.write_all(metadata.as_bytes()).unwrap();
+    }
+}
+
 fn save_intermediates(
     binary: &solang::emit::binary::Binary,
     compiler_output: &CompilerOutput,
@@ -555,7 +786,11 @@ fn save_intermediates(
             true
         }
         Some("cfg") => true,
+        Some("cfg-dot") => true,
         Some("ast-dot") => true,
+        Some("storage-layout") => true,
+        Some("callgraph") => true,
+        Some("removed-checks") => true,
         _ => false,
     }
 }
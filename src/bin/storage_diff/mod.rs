@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::cli::{imports_arg, target_arg, StorageDiff};
+use solang::{
+    abi::{
+        storage_diff::compare_storage_layouts,
+        storage_layout::{gen_storage_layout, StorageLayout},
+    },
+    codegen::{codegen, Options},
+    sema::ast::Namespace,
+};
+use std::{fs::File, process::exit};
+
+/// This subcommand compiles the new version of a contract, and compares its storage layout with
+/// the layout of the deployed version. Any variable which has been removed, moved or changed to
+/// an incompatible type is reported, since upgrading the contract would corrupt its storage.
+pub fn storage_diff(diff_args: &StorageDiff) {
+    let old_layout = read_layout(diff_args);
+
+    let target = target_arg(&diff_args.target);
+    let mut resolver = imports_arg(&diff_args.package);
+
+    let mut namespaces = Vec::new();
+
+    for filename in &diff_args.package.input {
+        let mut ns = solang::parse_and_resolve(filename.as_os_str(), &mut resolver, target);
+
+        if !ns.diagnostics.any_errors() {
+            codegen(&mut ns, &Options::default());
+        }
+
+        ns.print_diagnostics(&resolver, false);
+
+        if ns.diagnostics.any_errors() {
+            exit(1);
+        }
+
+        namespaces.push(ns);
+    }
+
+    let contracts: Vec<(&Namespace, usize)> = namespaces
+        .iter()
+        .flat_map(|ns| {
+            ns.contracts
+                .iter()
+                .enumerate()
+                .filter(|(_, contract)| {
+                    contract.instantiable
+                        && contract.loc.file_no() == ns.top_file_no()
+                        && diff_args
+                            .package
+                            .contracts
+                            .as_ref()
+                            .map_or(true, |names| names.contains(&contract.id.name))
+                })
+                .map(move |(contract_no, _)| (ns, contract_no))
+        })
+        .collect();
+
+    let (ns, contract_no) = match contracts.as_slice() {
+        [contract] => *contract,
+        [] => {
+            eprintln!("error: no contract found");
+            exit(1);
+        }
+        _ => {
+            eprintln!("error: more than one contract found, select one with --contract");
+            exit(1);
+        }
+    };
+
+    let contract = &ns.contracts[contract_no];
+    let new_layout = gen_storage_layout(contract_no, ns);
+
+    let changes = compare_storage_layouts(&old_layout, &new_layout);
+
+    if changes.is_empty() {
+        println!(
+            "info: storage layout of contract {} is compatible with '{}'",
+            contract.id,
+            diff_args.old_layout.display()
+        );
+    } else {
+        for change in &changes {
+            eprintln!("error: contract {}: {}", contract.id, change);
+        }
+
+        exit(1);
+    }
+}
+
+fn read_layout(diff_args: &StorageDiff) -> StorageLayout {
+    let path = &diff_args.old_layout;
+
+    let f = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}: error: {}", path.display(), e);
+            exit(1);
+        }
+    };
+
+    match serde_json::from_reader(f) {
+        Ok(layout) => layout,
+        Err(e) => {
+            eprintln!("{}: error: {}", path.display(), e);
+            exit(1);
+        }
+    }
+}
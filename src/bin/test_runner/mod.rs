@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! `solang test` compiles the project and runs its unit tests in an embedded virtual machine,
+//! so contracts can be tested without a validator or node.
+//!
+//! Every public function whose name starts with `test` and which takes no arguments is a unit
+//! test. For each test, the contract is deployed in a fresh runtime using its constructor, then
+//! `setUp()` is called if the contract has one, and finally the test function itself. The test
+//! passes if none of these calls revert.
+
+use crate::cli::{imports_arg, options_arg, target_arg, PackageTrait, Test};
+use parity_scale_codec::Decode;
+use solang::{
+    codegen::{
+        codegen,
+        revert::{SolidityError, ERROR_SELECTOR, PANIC_SELECTOR},
+    },
+    sema::{ast::Namespace, solidity_version::SOLIDITY_VERSION},
+    Target,
+};
+use solang_parser::pt;
+use std::process::exit;
+
+mod polkadot;
+mod solana;
+
+/// A unit test of a contract
+pub(super) struct UnitTest {
+    pub contract_no: usize,
+    pub function_no: usize,
+    /// The `setUp()` function of the contract, if any
+    pub set_up: Option<usize>,
+}
+
+/// The result of running a single unit test
+#[derive(Default)]
+pub(super) struct TestOutcome {
+    /// `None` if the test passed, otherwise why it failed
+    pub failure: Option<String>,
+    /// The messages logged by the contracts during the test, in order of execution
+    pub logs: Vec<String>,
+}
+
+pub fn run(test_args: &Test) {
+    let target = target_arg(&test_args.target_arg);
+
+    if target == Target::EVM {
+        eprintln!("error: unit tests cannot be run for the evm target");
+        exit(1);
+    }
+
+    let mut resolver = imports_arg(&test_args.package);
+
+    let opt = options_arg(&test_args.debug_features, &test_args.optimizations);
+
+    let solidity_version = match &test_args.package.solidity_version {
+        Some(version) => semver::Version::parse(version).unwrap(),
+        None => SOLIDITY_VERSION,
+    };
+
+    let mut errors = false;
+    let mut found = false;
+    let mut passed = 0;
+    let mut failed = Vec::new();
+
+    for filename in test_args.package.get_input() {
+        let filepath = match filename.canonicalize() {
+            Ok(filename) => filename,
+            Err(_) => filename.to_path_buf(),
+        };
+
+        let mut ns = solang::parse_and_resolve_with_solidity_version(
+            filepath.as_os_str(),
+            &mut resolver,
+            target,
+            solidity_version.clone(),
+        );
+
+        codegen(&mut ns, &opt);
+
+        ns.print_diagnostics(&resolver, false);
+
+        if ns.diagnostics.any_errors() {
+            errors = true;
+            continue;
+        }
+
+        let tests = unit_tests(&ns, test_args);
+
+        if tests.is_empty() {
+            continue;
+        }
+
+        found = true;
+
+        // Tests may create or call any other contract, so build all of them
+        let code: Vec<Option<Vec<u8>>> = ns
+            .contracts
+            .iter()
+            .map(|contract| {
+                if contract.instantiable {
+                    Some(contract.emit(&ns, &opt).expect("llvm build"))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        for test in &tests {
+            let name = format!(
+                "{}::{}",
+                ns.contracts[test.contract_no].id, ns.functions[test.function_no].id
+            );
+
+            let outcome = if target == Target::Solana {
+                solana::run_test(&ns, &code, test)
+            } else {
+                polkadot::run_test(&ns, &code, test)
+            };
+
+            if let Some(failure) = &outcome.failure {
+                println!("test {name} ... FAILED");
+                println!("    {failure}");
+                failed.push(name);
+            } else {
+                println!("test {name} ... ok");
+                passed += 1;
+            }
+
+            if outcome.failure.is_some() || test_args.verbose {
+                for log in &outcome.logs {
+                    println!("    log: {log}");
+                }
+            }
+        }
+    }
+
+    if errors {
+        exit(1);
+    }
+
+    if !found {
+        eprintln!("warning: no unit tests found");
+    }
+
+    if failed.is_empty() {
+        println!("\ntest result: ok. {passed} passed; 0 failed");
+    } else {
+        println!("\nfailures:");
+        for name in &failed {
+            println!("    {name}");
+        }
+
+        println!(
+            "\ntest result: FAILED. {passed} passed; {} failed",
+            failed.len()
+        );
+
+        exit(1);
+    }
+}
+
+/// Find the unit tests of the contracts defined in the top level file
+fn unit_tests(ns: &Namespace, test_args: &Test) -> Vec<UnitTest> {
+    let mut tests = Vec::new();
+
+    for (contract_no, contract) in ns.contracts.iter().enumerate() {
+        if !contract.instantiable || contract.loc.file_no() != ns.top_file_no() {
+            continue;
+        }
+
+        if let Some(names) = &test_args.package.contracts {
+            if !names.contains(&contract.id.name) {
+                continue;
+            }
+        }
+
+        let callable = |function_no: usize| {
+            let func = &ns.functions[function_no];
+
+            func.ty == pt::FunctionTy::Function
+                && func.params.is_empty()
+                && ns.function_externally_callable(contract_no, Some(function_no))
+        };
+
+        let set_up = contract.all_functions.keys().copied().find(|function_no| {
+            ns.functions[*function_no].id.name == "setUp" && callable(*function_no)
+        });
+
+        for function_no in contract.all_functions.keys().copied() {
+            let name = &ns.functions[function_no].id.name;
+
+            if !name.starts_with("test") || !callable(function_no) {
+                continue;
+            }
+
+            if let Some(filter) = &test_args.filter {
+                if !name.contains(filter.as_str()) {
+                    continue;
+                }
+            }
+
+            tests.push(UnitTest {
+                contract_no,
+                function_no,
+                set_up,
+            });
+        }
+    }
+
+    tests
+}
+
+/// Describe the revert data of a failed call. Error data is encoded with the ABI encoding of
+/// the target, so strings are Borsh encoded on Solana and SCALE encoded on Polkadot.
+pub(super) fn decode_revert(ns: &Namespace, data: &[u8]) -> Option<String> {
+    if data.len() < 4 {
+        return None;
+    }
+
+    let (selector, args) = data.split_at(4);
+
+    if selector == ERROR_SELECTOR {
+        let reason = if ns.target == Target::Solana {
+            args.get(..4)
+                .map(|len| u32::from_le_bytes(len.try_into().unwrap()) as usize)
+                .and_then(|len| args.get(4..4 + len))
+                .map(|reason| String::from_utf8_lossy(reason).to_string())
+        } else {
+            String::decode(&mut &args[..]).ok()
+        };
+
+        return Some(match reason {
+            Some(reason) => format!("revert: {reason}"),
+            None => format!("revert with malformed reason {}", hex::encode(args)),
+        });
+    }
+
+    if selector == PANIC_SELECTOR && args.len() == 32 {
+        // uint256 is encoded little endian on both Solana and Polkadot
+        let code = u128::from_le_bytes(args[..16].try_into().unwrap());
+
+        return Some(format!("panic: {} (0x{code:02x})", panic_description(code)));
+    }
+
+    for (error_no, error) in ns.errors.iter().enumerate() {
+        let custom = SolidityError::Custom {
+            error_no,
+            exprs: Vec::new(),
+        };
+
+        if custom.selector(ns) == selector {
+            return Some(format!(
+                "revert with custom error {} {}",
+                error.symbol_name(ns),
+                hex::encode(args)
+            ));
+        }
+    }
+
+    Some(format!("revert with data {}", hex::encode(data)))
+}
+
+fn panic_description(code: u128) -> &'static str {
+    match code {
+        0x01 => "assertion failed",
+        0x11 => "math overflow",
+        0x12 => "division by zero",
+        0x21 => "enum conversion out of bounds",
+        0x22 => "incorrectly encoded storage byte array",
+        0x31 => "pop from empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to uninitialized internal function",
+        _ => "generic panic",
+    }
+}
@@ -0,0 +1,887 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! An in-process mock of the contracts pallet, executing contracts with `wasmi`. Only the host
+//! functions which Solang generates code for are provided.
+
+use super::{decode_revert, TestOutcome, UnitTest};
+use blake2_rfc::blake2b::blake2b;
+use parity_scale_codec::Decode;
+use sha2::{Digest, Sha256};
+use solang::sema::ast::Namespace;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+use tiny_keccak::{Hasher, Keccak};
+use wasmi::core::{HostError, Trap, TrapCode};
+use wasmi::{Caller, Engine, Error, Func, Instance, Linker, Memory, MemoryType, Module, Store};
+
+type StorageKey = [u8; 32];
+type Address = [u8; 32];
+type Hash = [u8; 32];
+
+/// Balance of the account which calls the test functions
+const CALLER_BALANCE: u128 = 1_000_000_000_000_000;
+
+#[derive(Clone, Copy)]
+enum CallFlags {
+    ForwardInput = 1,
+    CloneInput = 2,
+    TailCall = 4,
+    AllowReentry = 8,
+}
+
+impl CallFlags {
+    /// Returns true if this flag is set in the given `flags`.
+    fn set(&self, flags: u32) -> bool {
+        flags & *self as u32 != 0
+    }
+}
+
+/// Reason for halting execution. Same as in pallet contracts.
+#[derive(Default, Debug, Clone)]
+enum HostReturn {
+    /// The contract was terminated (deleted).
+    #[default]
+    Terminate,
+    /// Flags and data returned by the contract.
+    Data(u32, Vec<u8>),
+}
+
+impl HostReturn {
+    fn as_data(&self) -> (u32, Vec<u8>) {
+        match self {
+            HostReturn::Data(flags, data) => (*flags, data.to_vec()),
+            HostReturn::Terminate => (0, vec![]),
+        }
+    }
+}
+
+impl fmt::Display for HostReturn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::Terminate => write!(f, "return: terminate"),
+            Self::Data(flags, data) => write!(f, "return {flags} {data:?}"),
+        }
+    }
+}
+
+impl HostError for HostReturn {}
+
+/// The Wasm code of a contract
+#[derive(Clone)]
+struct WasmCode {
+    hash: Hash,
+    blob: Vec<u8>,
+}
+
+/// Deployed Wasm code with its storage
+#[derive(Clone)]
+struct Contract {
+    code: WasmCode,
+    storage: HashMap<StorageKey, Vec<u8>>,
+}
+
+impl Contract {
+    /// Instantiate this contract as a Wasm module for execution.
+    fn instantiate(&self, runtime: Runtime) -> Result<(Store<Runtime>, Instance), Error> {
+        let engine = Engine::default();
+        let mut store = Store::new(&engine, runtime);
+
+        let mut linker = <Linker<Runtime>>::new(&engine);
+        Runtime::define(&mut store, &mut linker);
+        let memory = Memory::new(&mut store, MemoryType::new(16, Some(16)).unwrap()).unwrap();
+        linker.define("env", "memory", memory).unwrap();
+        store.data_mut().memory = Some(memory);
+
+        let instance = linker
+            .instantiate(&mut store, &Module::new(&engine, &mut &self.code.blob[..])?)?
+            .ensure_no_start(&mut store)
+            .expect("we never emit a start function");
+
+        Ok((store, instance))
+    }
+
+    /// Execute this contract at the exported function `name` in the given `runtime` context.
+    ///
+    /// On success, returns the Wasm store including the runtime state.
+    /// On failure, returns the Wasm execution error together with the logs.
+    #[allow(clippy::result_large_err)]
+    fn execute(
+        &self,
+        name: &str,
+        runtime: Runtime,
+    ) -> Result<Store<Runtime>, (Error, Vec<String>)> {
+        let logs = runtime.logs.clone();
+
+        let (mut store, instance) = self.instantiate(runtime).map_err(|e| (e, logs))?;
+
+        let func = match instance
+            .get_export(&store, name)
+            .and_then(|export| export.into_func())
+        {
+            Some(func) => func,
+            None => {
+                let err = Error::Trap(Trap::new(format!("contract does not export '{name}'")));
+                return Err((err, store.data().logs.clone()));
+            }
+        };
+
+        match func.call(&mut store, &[], &mut []) {
+            Err(Error::Trap(trap)) => match trap.downcast_ref::<HostReturn>() {
+                Some(HostReturn::Data(flags, data)) => {
+                    store.data_mut().output = HostReturn::Data(*flags, data.clone());
+                    Ok(store)
+                }
+                Some(HostReturn::Terminate) => Ok(store),
+                None => Err((Error::Trap(trap), store.data().logs.clone())),
+            },
+            Err(e) => Err((e, store.data().logs.clone())),
+            Ok(_) => Ok(store),
+        }
+    }
+}
+
+/// If contract is `Some`, this is considered to be a "contract account".
+#[derive(Default, Clone)]
+struct Account {
+    address: Address,
+    value: u128,
+    contract: Option<Contract>,
+}
+
+impl PartialEq for Account {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl Account {
+    /// Create a new contract account.
+    /// The account address is derived based of the provided `salt`.
+    fn with_contract(salt: &[u8], code: &WasmCode) -> Self {
+        Self {
+            address: Address::try_from(blake2b(32, &[], salt).as_bytes()).unwrap(),
+            contract: Some(Contract {
+                code: code.clone(),
+                storage: HashMap::new(),
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// The runtime provides the state of the mocked node during contract execution.
+#[derive(Default, Clone)]
+struct Runtime {
+    /// A list of "existing" accounts.
+    accounts: Vec<Account>,
+    /// A list of known ("uploaded") Wasm contract blobs.
+    blobs: Vec<WasmCode>,
+    /// Index into accounts pointing the account that is being executed.
+    account: usize,
+    /// Index into accounts pointing to the calling account.
+    caller_account: usize,
+    /// Will hold the memory reference after a successful execution.
+    memory: Option<Memory>,
+    /// The input for the contract execution.
+    input: Option<Vec<u8>>,
+    /// The output of the contract execution.
+    output: HostReturn,
+    /// Describes how much value was given to the contract call.
+    transferred_value: u128,
+    /// The `seal_debug_message` messages and emitted events, in order of execution
+    logs: Vec<String>,
+    /// The set of called accounts, needed for reentrancy protection.
+    called_accounts: HashSet<usize>,
+}
+
+/// Deploy the contract of the test, and run its `setUp()` and test function.
+pub(super) fn run_test(ns: &Namespace, code: &[Option<Vec<u8>>], test: &UnitTest) -> TestOutcome {
+    let mut runtime = Runtime::default();
+
+    let blob = code[test.contract_no].as_ref().unwrap();
+
+    runtime.blobs = code
+        .iter()
+        .flatten()
+        .map(|blob| WasmCode {
+            hash: blake2b(32, &[], blob).as_bytes().try_into().unwrap(),
+            blob: blob.clone(),
+        })
+        .collect();
+
+    // The externally owned account which deploys the contract and calls the tests
+    runtime.accounts.push(Account {
+        address: [0xff; 32],
+        value: CALLER_BALANCE,
+        contract: None,
+    });
+
+    let hash: Hash = blake2b(32, &[], blob).as_bytes().try_into().unwrap();
+
+    let contract = &ns.contracts[test.contract_no];
+
+    let res = match contract.constructors(ns).first() {
+        Some(function_no) if !ns.functions[*function_no].params.is_empty() => Err(format!(
+            "cannot deploy contract '{}' since its constructor has arguments",
+            contract.id
+        )),
+        Some(function_no) => Ok(ns.functions[*function_no].selector(ns, &test.contract_no)),
+        None => Ok(contract
+            .default_constructor
+            .as_ref()
+            .unwrap()
+            .0
+            .selector(ns, &test.contract_no)),
+    }
+    .and_then(|selector| runtime.deploy_contract(ns, hash, selector))
+    .and_then(|callee| {
+        for function_no in test.set_up.into_iter().chain([test.function_no]) {
+            let selector = ns.functions[function_no].selector(ns, &test.contract_no);
+
+            runtime.invoke(ns, "call", callee, selector)?;
+        }
+
+        Ok(())
+    });
+
+    TestOutcome {
+        failure: res.err(),
+        logs: runtime.logs,
+    }
+}
+
+impl Runtime {
+    /// Create a suitable runtime context based on the current one.
+    ///
+    /// Each contract execution must live within it's own runtime context.
+    /// When calling into another contract, we must:
+    /// * switch out the caller and callee account
+    /// * populate the input and the transferred balance
+    /// * clear the output
+    fn new_context(&self, callee: usize, input: Vec<u8>, value: u128) -> Self {
+        let mut runtime = self.clone();
+        runtime.caller_account = self.account;
+        runtime.account = callee;
+        runtime.transferred_value = value;
+        runtime.accounts[callee].value += value;
+        runtime.input = Some(input);
+        runtime.output = Default::default();
+        runtime.called_accounts.insert(self.caller_account);
+        runtime
+    }
+
+    /// After a successful contract execution, merge the runtime context of the callee back.
+    ///
+    /// We take over accounts (the callee might deploy new ones) and logs.
+    /// The transferred balance will now be deducted from the caller.
+    fn accept_state(&mut self, callee_state: Self, transferred_value: u128) {
+        self.logs = callee_state.logs;
+        self.accounts = callee_state.accounts;
+        self.accounts[self.caller_account].value -= transferred_value;
+    }
+
+    /// Access the contract that is currently being executed.
+    fn contract(&mut self) -> &mut Contract {
+        self.accounts[self.account].contract.as_mut().unwrap()
+    }
+
+    /// Call an exported function under the account found at index `callee`.
+    ///
+    /// Returns `None` if the account has no contract.
+    fn call(
+        &mut self,
+        export: &str,
+        callee: usize,
+        input: Vec<u8>,
+        value: u128,
+    ) -> Option<Result<Store<Runtime>, Error>> {
+        self.accounts[callee]
+            .contract
+            .as_ref()?
+            .execute(export, self.new_context(callee, input, value))
+            .map_err(|(err, logs)| {
+                self.logs = logs;
+                err
+            })
+            .into()
+    }
+
+    /// Add a new contract account and call its "deploy" function accordingly.
+    ///
+    /// Returns `None` if there is no contract corresponding to the given `code_hash`.
+    fn deploy(
+        &mut self,
+        code_hash: Hash,
+        value: u128,
+        salt: &[u8],
+        input: Vec<u8>,
+    ) -> Option<Result<Store<Runtime>, Error>> {
+        let account = self
+            .blobs
+            .iter()
+            .find(|code| code.hash == code_hash)
+            .map(|code| Account::with_contract(salt, code))?;
+
+        if self.accounts.contains(&account) {
+            return Some(Err(Error::Trap(TrapCode::UnreachableCodeReached.into())));
+        }
+
+        self.accounts.push(account);
+        self.call("deploy", self.accounts.len() - 1, input, value)
+    }
+
+    /// Deploy the contract from the externally owned account, and return its account index.
+    fn deploy_contract(
+        &mut self,
+        ns: &Namespace,
+        code_hash: Hash,
+        input: Vec<u8>,
+    ) -> Result<usize, String> {
+        self.account = 0;
+
+        let res = self.deploy(code_hash, 0, &code_hash, input).unwrap();
+
+        self.accept(ns, res)?;
+
+        Ok(self.accounts.len() - 1)
+    }
+
+    /// Call the exported function of the contract at `callee` from the externally owned account.
+    fn invoke(
+        &mut self,
+        ns: &Namespace,
+        export: &str,
+        callee: usize,
+        input: Vec<u8>,
+    ) -> Result<(), String> {
+        self.account = 0;
+        self.called_accounts.clear();
+
+        let res = self.call(export, callee, input, 0).unwrap();
+
+        self.accept(ns, res)
+    }
+
+    /// Take over the state of a successful execution, or describe why it failed.
+    fn accept(&mut self, ns: &Namespace, res: Result<Store<Runtime>, Error>) -> Result<(), String> {
+        match res {
+            Ok(store) => {
+                let (flags, data) = store.data().output.as_data();
+
+                if flags == 0 {
+                    self.accept_state(store.into_data(), 0);
+                    Ok(())
+                } else {
+                    self.logs = store.into_data().logs;
+
+                    Err(decode_revert(ns, &data).unwrap_or_else(|| "revert".to_string()))
+                }
+            }
+            Err(Error::Trap(trap))
+                if matches!(trap.trap_code(), Some(TrapCode::UnreachableCodeReached)) =>
+            {
+                Err("contract trapped".to_string())
+            }
+            Err(err) => Err(format!("contract execution failed: {err}")),
+        }
+    }
+
+    /// Define the host functions on the linker. They mock the original implementation, refer
+    /// to the [pallet docs][1] for more information.
+    ///
+    /// [1]: https://docs.rs/pallet-contracts/latest/pallet_contracts/api_doc/index.html
+    fn define(mut store: &mut Store<Runtime>, linker: &mut Linker<Runtime>) {
+        /// Wrap a host function so that `mem` is the memory of the contract, and `vm`
+        /// is the runtime state.
+        macro_rules! host {
+            ($module:literal, $name:literal, |$mem:ident, $vm:ident $(, $arg:ident: $ty:ty)*| -> $ret:ty $body:block) => {
+                linker
+                    .define(
+                        $module,
+                        $name,
+                        Func::wrap(&mut store, |mut caller: Caller<Runtime>, $($arg: $ty),*| -> $ret {
+                            let memory = caller.data().memory.unwrap();
+                            #[allow(unused_variables)]
+                            let ($mem, $vm) = memory.data_and_store_mut(&mut caller);
+                            $body
+                        }),
+                    )
+                    .unwrap();
+            };
+        }
+
+        host!("seal0", "input", |mem,
+                                 vm,
+                                 dest_ptr: u32,
+                                 len_ptr: u32|
+         -> Result<(), Trap> {
+            let data = match vm.input.as_ref() {
+                Some(data) => data,
+                None => return Err(Trap::new("input was forwarded")),
+            };
+
+            if read_len(mem, len_ptr) < data.len() {
+                return Err(TrapCode::MemoryOutOfBounds.into());
+            }
+
+            write_buf(mem, dest_ptr, data);
+            write_buf(mem, len_ptr, &(data.len() as u32).to_le_bytes());
+
+            Ok(())
+        });
+
+        host!("seal0", "seal_return", |mem,
+                                       vm,
+                                       flags: u32,
+                                       data_ptr: u32,
+                                       data_len: u32|
+         -> Result<(), Trap> {
+            let output = read_buf(mem, data_ptr, data_len);
+
+            Err(HostReturn::Data(flags, output).into())
+        });
+
+        host!("seal0", "value_transferred", |mem,
+                                             vm,
+                                             dest_ptr: u32,
+                                             out_len_ptr: u32|
+         -> Result<(), Trap> {
+            write_output(
+                mem,
+                dest_ptr,
+                out_len_ptr,
+                &vm.transferred_value.to_le_bytes(),
+            )
+        });
+
+        host!("seal0", "debug_message", |mem,
+                                         vm,
+                                         data_ptr: u32,
+                                         len: u32|
+         -> Result<u32, Trap> {
+            let buf = read_buf(mem, data_ptr, len);
+            let msg = String::from_utf8_lossy(&buf);
+
+            vm.logs.extend(
+                msg.lines()
+                    .filter(|line| !line.is_empty())
+                    .map(String::from),
+            );
+
+            Ok(0)
+        });
+
+        host!("seal1", "get_storage", |mem,
+                                       vm,
+                                       key_ptr: u32,
+                                       key_len: u32,
+                                       out_ptr: u32,
+                                       out_len_ptr: u32|
+         -> Result<u32, Trap> {
+            let key = read_storage_key(mem, key_ptr, key_len)?;
+
+            let value = match vm.contract().storage.get(&key) {
+                Some(value) => value,
+                _ => return Ok(3), // In pallet-contracts, ReturnCode::KeyNotFound == 3
+            };
+
+            write_output(mem, out_ptr, out_len_ptr, value)?;
+
+            Ok(0)
+        });
+
+        host!("seal2", "set_storage", |mem,
+                                       vm,
+                                       key_ptr: u32,
+                                       key_len: u32,
+                                       value_ptr: u32,
+                                       value_len: u32|
+         -> Result<u32, Trap> {
+            let key = read_storage_key(mem, key_ptr, key_len)?;
+            let value = read_buf(mem, value_ptr, value_len);
+
+            match vm.contract().storage.insert(key, value) {
+                Some(value) => Ok(value.len() as u32),
+                _ => Ok(u32::MAX), // In pallets contract, u32::MAX is the "none sentinel"
+            }
+        });
+
+        host!("seal1", "clear_storage", |mem,
+                                         vm,
+                                         key_ptr: u32,
+                                         key_len: u32|
+         -> Result<u32, Trap> {
+            let key = read_storage_key(mem, key_ptr, key_len)?;
+
+            match vm.contract().storage.remove(&key) {
+                Some(value) => Ok(value.len() as u32),
+                _ => Ok(u32::MAX), // In pallets contract, u32::MAX is the "none sentinel"
+            }
+        });
+
+        host!("seal0", "hash_keccak_256", |mem,
+                                           vm,
+                                           input_ptr: u32,
+                                           input_len: u32,
+                                           output_ptr: u32|
+         -> Result<(), Trap> {
+            let mut hasher = Keccak::v256();
+            let mut hash = [0u8; 32];
+            hasher.update(&read_buf(mem, input_ptr, input_len));
+            hasher.finalize(&mut hash);
+            write_buf(mem, output_ptr, &hash);
+            Ok(())
+        });
+
+        host!("seal0", "hash_sha2_256", |mem,
+                                         vm,
+                                         input_ptr: u32,
+                                         input_len: u32,
+                                         output_ptr: u32|
+         -> Result<(), Trap> {
+            let mut hasher = Sha256::new();
+            hasher.update(read_buf(mem, input_ptr, input_len));
+            write_buf(mem, output_ptr, &hasher.finalize());
+            Ok(())
+        });
+
+        host!("seal0", "hash_blake2_128", |mem,
+                                           vm,
+                                           input_ptr: u32,
+                                           input_len: u32,
+                                           output_ptr: u32|
+         -> Result<(), Trap> {
+            let data = read_buf(mem, input_ptr, input_len);
+            write_buf(mem, output_ptr, blake2b(16, &[], &data).as_bytes());
+            Ok(())
+        });
+
+        host!("seal0", "hash_blake2_256", |mem,
+                                           vm,
+                                           input_ptr: u32,
+                                           input_len: u32,
+                                           output_ptr: u32|
+         -> Result<(), Trap> {
+            let data = read_buf(mem, input_ptr, input_len);
+            write_buf(mem, output_ptr, blake2b(32, &[], &data).as_bytes());
+            Ok(())
+        });
+
+        host!("seal1", "seal_call", |mem,
+                                     vm,
+                                     flags: u32,
+                                     callee_ptr: u32,
+                                     _gas: u64,
+                                     value_ptr: u32,
+                                     input_ptr: u32,
+                                     input_len: u32,
+                                     output_ptr: u32,
+                                     output_len_ptr: u32|
+         -> Result<u32, Trap> {
+            let input = if CallFlags::ForwardInput.set(flags) {
+                match vm.input.take() {
+                    Some(input) => input,
+                    None => return Ok(1),
+                }
+            } else if CallFlags::CloneInput.set(flags) {
+                match vm.input.clone() {
+                    Some(input) => input,
+                    None => return Ok(1),
+                }
+            } else {
+                read_buf(mem, input_ptr, input_len)
+            };
+            let value = read_value(mem, value_ptr);
+            let callee_address = read_account(mem, callee_ptr);
+
+            let callee = match vm
+                .accounts
+                .iter()
+                .position(|account| account.address == callee_address)
+            {
+                Some(index) => index,
+                None => return Ok(8), // ReturnCode::NotCallable
+            };
+
+            if vm.called_accounts.contains(&callee) && !CallFlags::AllowReentry.set(flags) {
+                return Ok(1);
+            }
+
+            if value > vm.accounts[vm.account].value {
+                return Ok(5); // ReturnCode::TransferFailed
+            }
+
+            let ((ret, data), state) = match vm.call("call", callee, input, value) {
+                Some(Ok(state)) => ((state.data().output.as_data()), state),
+                Some(Err(_)) => return Ok(1), // ReturnCode::CalleeTrapped
+                None => return Ok(8),
+            };
+
+            if CallFlags::TailCall.set(flags) {
+                return Err(HostReturn::Data(ret, data).into());
+            }
+
+            if output_len_ptr != u32::MAX {
+                write_output(mem, output_ptr, output_len_ptr, &data)?;
+            }
+
+            if ret == 0 {
+                vm.accept_state(state.into_data(), value);
+                return Ok(0);
+            }
+
+            // Keep the logs of the callee, even though it reverted
+            vm.logs = state.into_data().logs;
+
+            Ok(2) // Callee reverted
+        });
+
+        host!(
+            "seal0",
+            "instantiation_nonce",
+            |mem, vm| -> Result<u64, Trap> { Ok(vm.accounts.len() as u64) }
+        );
+
+        host!("seal0", "minimum_balance", |mem,
+                                           vm,
+                                           out_ptr: u32,
+                                           out_len_ptr: u32|
+         -> Result<(), Trap> {
+            write_output(mem, out_ptr, out_len_ptr, &500u128.to_le_bytes())
+        });
+
+        host!("seal1", "instantiate", |mem,
+                                       vm,
+                                       code_hash_ptr: u32,
+                                       _gas: u64,
+                                       value_ptr: u32,
+                                       input_data_ptr: u32,
+                                       input_data_len: u32,
+                                       address_ptr: u32,
+                                       address_len_ptr: u32,
+                                       output_ptr: u32,
+                                       output_len_ptr: u32,
+                                       salt_ptr: u32,
+                                       salt_len: u32|
+         -> Result<u32, Trap> {
+            let code_hash = read_account(mem, code_hash_ptr);
+            let salt = read_buf(mem, salt_ptr, salt_len);
+            let input = read_buf(mem, input_data_ptr, input_data_len);
+            let value = read_value(mem, value_ptr);
+
+            if value > vm.accounts[vm.account].value {
+                return Ok(5); // ReturnCode::TransferFailed
+            }
+
+            let ((flags, data), state) = match vm.deploy(code_hash, value, &salt, input) {
+                Some(Ok(state)) => ((state.data().output.as_data()), state),
+                Some(Err(_)) => return Ok(1), // ReturnCode::CalleeTrapped
+                None => return Ok(7),         // ReturnCode::CodeNotFound
+            };
+
+            if output_len_ptr != u32::MAX {
+                write_output(mem, output_ptr, output_len_ptr, &data)?;
+            }
+
+            let address = state.data().accounts.last().unwrap().address;
+            write_output(mem, address_ptr, address_len_ptr, &address)?;
+
+            if flags == 0 {
+                vm.accept_state(state.into_data(), value);
+                return Ok(0);
+            }
+
+            // The new account does not exist, since the constructor reverted
+            vm.accounts.pop();
+            vm.logs = state.into_data().logs;
+
+            Ok(2) // Callee reverted
+        });
+
+        host!("seal0", "transfer", |mem,
+                                    vm,
+                                    account_ptr: u32,
+                                    _account_len: u32,
+                                    value_ptr: u32,
+                                    _value_len: u32|
+         -> Result<u32, Trap> {
+            let value = read_value(mem, value_ptr);
+            if value > vm.accounts[vm.account].value {
+                return Ok(5); // ReturnCode::TransferFailed
+            }
+
+            let account = read_account(mem, account_ptr);
+            let current = vm.account;
+            if let Some(to) = vm.accounts.iter_mut().find(|c| c.address == account) {
+                to.value += value;
+                vm.accounts[current].value -= value;
+                return Ok(0);
+            }
+
+            Ok(5)
+        });
+
+        host!("seal0", "address", |mem,
+                                   vm,
+                                   out_ptr: u32,
+                                   out_len_ptr: u32|
+         -> Result<(), Trap> {
+            let address = vm.accounts[vm.account].address;
+            write_output(mem, out_ptr, out_len_ptr, &address)
+        });
+
+        host!("seal0", "caller", |mem,
+                                  vm,
+                                  out_ptr: u32,
+                                  out_len_ptr: u32|
+         -> Result<(), Trap> {
+            let address = vm.accounts[vm.caller_account].address;
+            write_output(mem, out_ptr, out_len_ptr, &address)
+        });
+
+        host!("seal0", "balance", |mem,
+                                   vm,
+                                   out_ptr: u32,
+                                   out_len_ptr: u32|
+         -> Result<(), Trap> {
+            let balance = vm.accounts[vm.account].value.to_le_bytes();
+            write_output(mem, out_ptr, out_len_ptr, &balance)
+        });
+
+        host!("seal0", "block_number", |mem,
+                                        vm,
+                                        out_ptr: u32,
+                                        out_len_ptr: u32|
+         -> Result<(), Trap> {
+            write_output(mem, out_ptr, out_len_ptr, &950_119_597u32.to_le_bytes())
+        });
+
+        host!("seal0", "now", |mem,
+                               vm,
+                               out_ptr: u32,
+                               out_len_ptr: u32|
+         -> Result<(), Trap> {
+            write_output(mem, out_ptr, out_len_ptr, &1594035638000u64.to_le_bytes())
+        });
+
+        host!("seal0", "gas_left", |mem,
+                                    vm,
+                                    out_ptr: u32,
+                                    out_len_ptr: u32|
+         -> Result<(), Trap> {
+            write_output(mem, out_ptr, out_len_ptr, &2_224_097_461u64.to_le_bytes())
+        });
+
+        host!("seal0", "weight_to_fee", |mem,
+                                         vm,
+                                         gas: u64,
+                                         out_ptr: u32,
+                                         out_len_ptr: u32|
+         -> Result<(), Trap> {
+            let price = (59_541_253_813_967 * gas as u128).to_le_bytes();
+            write_output(mem, out_ptr, out_len_ptr, &price)
+        });
+
+        host!("seal1", "terminate", |mem,
+                                     vm,
+                                     beneficiary_ptr: u32|
+         -> Result<(), Trap> {
+            let free = vm.accounts.remove(vm.account).value;
+            let address = read_account(mem, beneficiary_ptr);
+
+            if let Some(to) = vm.accounts.iter_mut().find(|a| a.address == address) {
+                to.value += free;
+            }
+
+            Err(HostReturn::Terminate.into())
+        });
+
+        host!("seal0", "deposit_event", |mem,
+                                         vm,
+                                         topics_ptr: u32,
+                                         topics_len: u32,
+                                         data_ptr: u32,
+                                         data_len: u32|
+         -> Result<(), Trap> {
+            let data = read_buf(mem, data_ptr, data_len);
+            let topics = if topics_len > 0 {
+                <Vec<Hash>>::decode(&mut &read_buf(mem, topics_ptr, topics_len)[..])
+                    .map_err(|_| Trap::new("invalid event topics"))?
+            } else {
+                vec![]
+            };
+
+            vm.logs.push(format!(
+                "event data: {} topics: {}",
+                hex::encode(data),
+                topics.iter().map(hex::encode).collect::<Vec<_>>().join(" ")
+            ));
+
+            Ok(())
+        });
+
+        host!("seal0", "is_contract", |mem,
+                                       vm,
+                                       input_ptr: u32|
+         -> Result<u32, Trap> {
+            let address = read_account(mem, input_ptr);
+            Ok(vm
+                .accounts
+                .iter()
+                .any(|account| account.contract.is_some() && account.address == address)
+                .into())
+        });
+
+        host!("seal0", "set_code_hash", |mem,
+                                         vm,
+                                         code_hash_ptr: u32|
+         -> Result<u32, Trap> {
+            let hash = read_account(mem, code_hash_ptr);
+            if let Some(code) = vm.blobs.iter().find(|code| code.hash == hash) {
+                vm.contract().code = code.clone();
+                return Ok(0);
+            }
+            Ok(7) // ReturnCode::CodeNotFound
+        });
+    }
+}
+
+fn read_len(mem: &[u8], ptr: u32) -> usize {
+    u32::from_le_bytes(mem[ptr as usize..ptr as usize + 4].try_into().unwrap()) as usize
+}
+
+fn write_buf(mem: &mut [u8], ptr: u32, buf: &[u8]) {
+    mem[ptr as usize..ptr as usize + buf.len()].copy_from_slice(buf);
+}
+
+/// Write `buf` to the output buffer at `out_ptr`, and its length to `out_len_ptr`. The output
+/// buffer must be large enough.
+fn write_output(mem: &mut [u8], out_ptr: u32, out_len_ptr: u32, buf: &[u8]) -> Result<(), Trap> {
+    if read_len(mem, out_len_ptr) < buf.len() {
+        return Err(TrapCode::MemoryOutOfBounds.into());
+    }
+
+    write_buf(mem, out_ptr, buf);
+    write_buf(mem, out_len_ptr, &(buf.len() as u32).to_le_bytes());
+
+    Ok(())
+}
+
+fn read_buf(mem: &[u8], ptr: u32, len: u32) -> Vec<u8> {
+    mem[ptr as usize..(ptr + len) as usize].to_vec()
+}
+
+fn read_value(mem: &[u8], ptr: u32) -> u128 {
+    u128::from_le_bytes(read_buf(mem, ptr, 16).try_into().unwrap())
+}
+
+fn read_account(mem: &[u8], ptr: u32) -> Address {
+    Address::try_from(&mem[ptr as usize..(ptr + 32) as usize]).unwrap()
+}
+
+fn read_storage_key(mem: &[u8], key_ptr: u32, key_len: u32) -> Result<StorageKey, Trap> {
+    StorageKey::try_from(read_buf(mem, key_ptr, key_len))
+        .map_err(|_| Trap::new("storage key size must be 32 bytes"))
+}
@@ -0,0 +1,1334 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! An in-process Solana runtime built around the `solana_rbpf` virtual machine. Only the
+//! syscalls and system program instructions which Solang generates code for are provided.
+
+use super::{decode_revert, TestOutcome, UnitTest};
+use anchor_syn::idl::types::{IdlAccountItem, IdlInstruction};
+use base58::FromBase58;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use solana_rbpf::{
+    aligned_memory::AlignedMemory,
+    ebpf,
+    elf::{Executable, SBPFVersion},
+    memory_region::{AccessType, MemoryMapping, MemoryRegion},
+    verifier::{RequisiteVerifier, TautologyVerifier},
+    vm::{BuiltinProgram, Config, ContextObject, EbpfVm, ProgramResult, StableResult},
+};
+use solang::{
+    abi::anchor::{function_discriminator, generate_anchor_idl},
+    codegen::revert::SOLANA_CUSTOM_ERROR_OFFSET,
+    sema::ast::Namespace,
+};
+use std::{cell::RefCell, collections::HashMap, mem::size_of, rc::Rc, sync::Arc};
+use tiny_keccak::{Hasher, Keccak};
+
+type Error = Box<dyn std::error::Error>;
+
+type Account = [u8; 32];
+
+/// Error handling for syscall methods
+macro_rules! question_mark {
+    ( $value:expr, $result:ident ) => {{
+        let value = $value;
+        match value {
+            Err(err) => {
+                *$result = ProgramResult::Err(err);
+                return;
+            }
+            Ok(value) => value,
+        }
+    }};
+}
+
+/// Compute units available to a single transaction
+const COMPUTE_BUDGET: u64 = 1_400_000;
+
+const DEFAULT_HEAP_SIZE: usize = 32 * 1024;
+
+const MAX_PERMITTED_DATA_INCREASE: usize = 10 * 1024;
+
+/// Lamports given to every new account, so it can pay for the creation of other accounts
+const DEFAULT_LAMPORTS: u64 = 1_000_000_000;
+
+const SYSTEM_PROGRAM: Account = [0; 32];
+
+#[derive(Default)]
+struct AccountState {
+    data: Vec<u8>,
+    owner: Option<Account>,
+    lamports: u64,
+}
+
+struct VirtualMachine {
+    account_data: HashMap<Account, AccountState>,
+    /// The program id of each contract, indexed by contract number
+    programs: Vec<Option<Account>>,
+    /// The programs being executed, the current one first
+    stack: Vec<Account>,
+    logs: Vec<String>,
+    return_data: Option<(Account, Vec<u8>)>,
+}
+
+/// Deploy the contract of the test, and run its `setUp()` and test function.
+pub(super) fn run_test(ns: &Namespace, code: &[Option<Vec<u8>>], test: &UnitTest) -> TestOutcome {
+    let mut vm = VirtualMachine::new(ns, code);
+
+    let res = vm.deploy(ns, test.contract_no).and_then(|data_account| {
+        if let Some(function_no) = test.set_up {
+            vm.call(ns, test.contract_no, function_no, data_account)?;
+        }
+
+        vm.call(ns, test.contract_no, test.function_no, data_account)
+    });
+
+    TestOutcome {
+        failure: res.err(),
+        logs: vm.logs,
+    }
+}
+
+fn account_new() -> Account {
+    let mut rng = rand::thread_rng();
+
+    let mut a = [0u8; 32];
+
+    rng.fill(&mut a[..]);
+
+    a
+}
+
+fn sysvar(name: &str) -> Account {
+    name.from_base58().unwrap().try_into().unwrap()
+}
+
+impl VirtualMachine {
+    fn new(ns: &Namespace, code: &[Option<Vec<u8>>]) -> Self {
+        let mut account_data = HashMap::new();
+
+        let programs = ns
+            .contracts
+            .iter()
+            .zip(code)
+            .map(|(contract, code)| {
+                let code = code.as_ref()?;
+
+                let program = match &contract.program_id {
+                    Some(program_id) => program_id.clone().try_into().unwrap(),
+                    None => account_new(),
+                };
+
+                account_data.insert(
+                    program,
+                    AccountState {
+                        data: code.clone(),
+                        owner: None,
+                        lamports: 0,
+                    },
+                );
+
+                Some(program)
+            })
+            .collect();
+
+        // The clock sysvar has the fields slot, epoch_start_timestamp, epoch,
+        // leader_schedule_epoch and unix_timestamp
+        let clock = [70818331u64, 946684800, 102, 1231231312, 1620656423]
+            .iter()
+            .flat_map(|field| field.to_le_bytes())
+            .collect();
+
+        account_data.insert(
+            sysvar("SysvarC1ock11111111111111111111111111111111"),
+            AccountState {
+                data: clock,
+                owner: None,
+                lamports: 0,
+            },
+        );
+
+        for name in [
+            "SysvarRent111111111111111111111111111111111",
+            "Sysvar1nstructions1111111111111111111111111",
+        ] {
+            account_data.insert(sysvar(name), AccountState::default());
+        }
+
+        account_data.insert(SYSTEM_PROGRAM, AccountState::default());
+
+        VirtualMachine {
+            account_data,
+            programs,
+            stack: Vec::new(),
+            logs: Vec::new(),
+            return_data: None,
+        }
+    }
+
+    /// Create an account owned by the system program, without any data.
+    fn new_account(&mut self) -> Account {
+        let address = account_new();
+
+        self.account_data.insert(
+            address,
+            AccountState {
+                data: Vec::new(),
+                owner: Some(SYSTEM_PROGRAM),
+                lamports: DEFAULT_LAMPORTS,
+            },
+        );
+
+        address
+    }
+
+    /// Deploy a contract by calling its constructor, and return its data account.
+    fn deploy(&mut self, ns: &Namespace, contract_no: usize) -> Result<Account, String> {
+        let contract = &ns.contracts[contract_no];
+        let program = self.programs[contract_no].unwrap();
+        let instruction = instruction(ns, contract_no, "new");
+
+        if !instruction.args.is_empty() {
+            return Err(format!(
+                "cannot deploy contract '{}' since its constructor has arguments",
+                contract.id
+            ));
+        }
+
+        // If the constructor has a payer, it creates the data account itself
+        let data_account = if has_payer(&instruction) {
+            self.new_account()
+        } else {
+            let address = account_new();
+            let space = contract
+                .fixed_layout_size
+                .to_string()
+                .parse::<usize>()
+                .unwrap();
+
+            self.account_data.insert(
+                address,
+                AccountState {
+                    data: vec![0; space + 4096],
+                    owner: Some(program),
+                    lamports: DEFAULT_LAMPORTS,
+                },
+            );
+
+            address
+        };
+
+        let metas = self.account_metas(ns, &instruction, data_account)?;
+
+        let selector = match contract.constructors(ns).first() {
+            Some(function_no) => ns.functions[*function_no].selector(ns, &contract_no),
+            None => function_discriminator("new"),
+        };
+
+        self.invoke(ns, program, &metas, &selector)?;
+
+        Ok(data_account)
+    }
+
+    /// Call a function without arguments on a deployed contract.
+    fn call(
+        &mut self,
+        ns: &Namespace,
+        contract_no: usize,
+        function_no: usize,
+        data_account: Account,
+    ) -> Result<(), String> {
+        let func = &ns.functions[function_no];
+        let name = if func.mangled_name_contracts.contains(&contract_no) {
+            &func.mangled_name
+        } else {
+            &func.id.name
+        };
+
+        let instruction = instruction(ns, contract_no, name);
+        let metas = self.account_metas(ns, &instruction, data_account)?;
+
+        self.invoke(
+            ns,
+            self.programs[contract_no].unwrap(),
+            &metas,
+            &func.selector(ns, &contract_no),
+        )
+    }
+
+    /// Provide the accounts an instruction needs. Contracts called by the instruction are
+    /// deployed first, and any other account is created empty.
+    fn account_metas(
+        &mut self,
+        ns: &Namespace,
+        instruction: &IdlInstruction,
+        data_account: Account,
+    ) -> Result<Vec<AccountMeta>, String> {
+        let mut metas = Vec::new();
+
+        for account in &instruction.accounts {
+            let account = match account {
+                IdlAccountItem::IdlAccount(account) => account,
+                IdlAccountItem::IdlAccounts(_) => unreachable!("Solang does not use IdlAccounts"),
+            };
+
+            let name = account.name.as_str();
+
+            let pubkey = match name {
+                "dataAccount" => data_account,
+                "systemProgram" => SYSTEM_PROGRAM,
+                "clock" => sysvar("SysvarC1ock11111111111111111111111111111111"),
+                "rent" => sysvar("SysvarRent111111111111111111111111111111111"),
+                "SysvarInstruction" => sysvar("Sysvar1nstructions1111111111111111111111111"),
+                _ => {
+                    if let Some(contract_name) = name.strip_suffix("_programId") {
+                        let contract_no = find_contract(ns, contract_name)?;
+
+                        self.programs[contract_no].unwrap()
+                    } else if let Some(contract_name) = name.strip_suffix("_dataAccount") {
+                        if account.is_signer {
+                            // The contract will be created by the instruction
+                            self.new_account()
+                        } else {
+                            let contract_no = find_contract(ns, contract_name)?;
+
+                            self.deploy(ns, contract_no)?
+                        }
+                    } else {
+                        self.new_account()
+                    }
+                }
+            };
+
+            metas.push(AccountMeta {
+                pubkey: Pubkey(pubkey),
+                is_writable: account.is_mut,
+                is_signer: account.is_signer,
+            });
+        }
+
+        Ok(metas)
+    }
+
+    /// Run a transaction with a single instruction.
+    fn invoke(
+        &mut self,
+        ns: &Namespace,
+        program: Account,
+        metas: &[AccountMeta],
+        calldata: &[u8],
+    ) -> Result<(), String> {
+        self.return_data = None;
+        self.stack = vec![program];
+
+        match self.execute(metas, calldata) {
+            StableResult::Ok(0) => Ok(()),
+            StableResult::Ok(code) => {
+                let reason = self
+                    .return_data
+                    .as_ref()
+                    .and_then(|(_, data)| decode_revert(ns, data));
+
+                let contract_no = self.programs.iter().position(|p| *p == Some(program));
+
+                Err(reason.unwrap_or_else(|| program_error(ns, contract_no, code)))
+            }
+            StableResult::Err(err) => Err(format!("program aborted: {err}")),
+        }
+    }
+
+    fn execute(&mut self, metas: &[AccountMeta], calldata: &[u8]) -> ProgramResult {
+        let (mut parameter_bytes, mut refs) = serialize_parameters(calldata, metas, self);
+        let mut heap = vec![0_u8; DEFAULT_HEAP_SIZE];
+
+        let program = self.stack[0];
+
+        let code = match self.account_data.get(&program) {
+            Some(account) if !account.data.is_empty() => &account.data,
+            _ => return ProgramResult::Err("program has no code".into()),
+        };
+
+        let config = Config {
+            enable_sbpf_v1: true,
+            enable_symbol_and_section_labels: false,
+            ..Config::default()
+        };
+
+        let mut loader = BuiltinProgram::new_loader(config);
+
+        loader.register_function(b"sol_panic_", sol_panic_).unwrap();
+
+        loader.register_function(b"sol_log_", sol_log).unwrap();
+
+        loader
+            .register_function(b"sol_log_pubkey", sol_log_pubkey)
+            .unwrap();
+
+        loader
+            .register_function(b"sol_log_64_", sol_log_u64)
+            .unwrap();
+
+        loader.register_function(b"sol_sha256", sol_sha256).unwrap();
+
+        loader
+            .register_function(b"sol_keccak256", sol_keccak256)
+            .unwrap();
+
+        loader
+            .register_function(b"sol_create_program_address", sol_create_program_address)
+            .unwrap();
+
+        loader
+            .register_function(
+                b"sol_try_find_program_address",
+                sol_try_find_program_address,
+            )
+            .unwrap();
+
+        loader
+            .register_function(b"sol_invoke_signed_c", sol_invoke_signed_c)
+            .unwrap();
+
+        loader
+            .register_function(b"sol_set_return_data", sol_set_return_data)
+            .unwrap();
+
+        loader
+            .register_function(b"sol_get_return_data", sol_get_return_data)
+            .unwrap();
+
+        loader
+            .register_function(b"sol_log_data", sol_log_data)
+            .unwrap();
+
+        let executable =
+            match Executable::<TautologyVerifier, SyscallContext>::from_elf(code, Arc::new(loader))
+            {
+                Ok(executable) => executable,
+                Err(err) => return ProgramResult::Err(Box::new(err)),
+            };
+        let config = *executable.get_config();
+        let text = executable.get_ro_region();
+
+        let verified_executable =
+            match Executable::<RequisiteVerifier, SyscallContext>::verified(executable) {
+                Ok(executable) => executable,
+                Err(err) => return ProgramResult::Err(Box::new(err)),
+            };
+
+        let mut context = SyscallContext {
+            vm: Rc::new(RefCell::new(self)),
+            input_len: parameter_bytes.len(),
+            refs: Rc::new(RefCell::new(&mut refs)),
+            remaining: COMPUTE_BUDGET,
+        };
+
+        let mut stack = AlignedMemory::<{ ebpf::HOST_ALIGN }>::zero_filled(config.stack_size());
+
+        let parameter_region = vec![
+            text,
+            MemoryRegion::new_writable(&mut parameter_bytes, ebpf::MM_INPUT_START),
+            MemoryRegion::new_writable(&mut heap, ebpf::MM_HEAP_START),
+            MemoryRegion::new_writable(stack.as_slice_mut(), ebpf::MM_STACK_START),
+        ];
+
+        let memory_mapping =
+            MemoryMapping::new(parameter_region, &config, &SBPFVersion::V1).unwrap();
+
+        let mut vm = EbpfVm::new(
+            &config,
+            &SBPFVersion::V1,
+            &mut context,
+            memory_mapping,
+            4196,
+        );
+
+        let (_, res) = vm.execute_program(&verified_executable, true);
+
+        deserialize_parameters(&parameter_bytes, &refs, &mut self.account_data);
+
+        res
+    }
+}
+
+/// Find the IDL instruction with the given name
+fn instruction(ns: &Namespace, contract_no: usize, name: &str) -> IdlInstruction {
+    generate_anchor_idl(contract_no, ns, "0.0.1")
+        .instructions
+        .into_iter()
+        .find(|instruction| instruction.name == name)
+        .unwrap()
+}
+
+/// Does the constructor create the data account itself
+fn has_payer(instruction: &IdlInstruction) -> bool {
+    instruction.accounts.iter().any(|account| {
+        matches!(account, IdlAccountItem::IdlAccount(account)
+            if account.name == "dataAccount" && account.is_signer)
+    })
+}
+
+fn find_contract(ns: &Namespace, name: &str) -> Result<usize, String> {
+    ns.contracts
+        .iter()
+        .position(|contract| contract.id.name == name && contract.instantiable)
+        .ok_or_else(|| format!("cannot provide account for unknown contract '{name}'"))
+}
+
+/// Describe the error code returned by the program of a contract. The custom errors of the
+/// contract are numbered from `SOLANA_CUSTOM_ERROR_OFFSET`, and the builtin errors are shifted
+/// left by 32 bits.
+fn program_error(ns: &Namespace, contract_no: Option<usize>, code: u64) -> String {
+    if code < 1 << 32 {
+        let error_no = contract_no.and_then(|contract_no| {
+            let pos = code.checked_sub(SOLANA_CUSTOM_ERROR_OFFSET as u64)?;
+
+            ns.contracts[contract_no].custom_errors.get(pos as usize)
+        });
+
+        return match error_no {
+            Some(error_no) => format!("custom error {}", ns.errors[*error_no].symbol_name(ns)),
+            None => format!("custom program error: {code:#x}"),
+        };
+    }
+
+    match code >> 32 {
+        1 => "custom program error: 0x0".into(),
+        2 => "invalid argument".into(),
+        3 => "invalid instruction data".into(),
+        4 => "invalid account data".into(),
+        5 => "account data too small".into(),
+        6 => "insufficient funds".into(),
+        7 => "incorrect program id".into(),
+        8 => "missing required signature".into(),
+        9 => "account already initialized".into(),
+        10 => "uninitialized account".into(),
+        11 => "not enough account keys".into(),
+        _ => format!("program error {code:#x}"),
+    }
+}
+
+struct AccountRef {
+    account: Account,
+    owner_offset: usize,
+    data_offset: usize,
+    length: usize,
+}
+
+enum SerializableAccount {
+    Unique(AccountMeta),
+    Duplicate(usize),
+}
+
+fn remove_duplicates(metas: &[AccountMeta]) -> Vec<SerializableAccount> {
+    let mut serializable_format: Vec<SerializableAccount> = Vec::new();
+    let mut inserted: HashMap<AccountMeta, usize> = HashMap::new();
+
+    for (idx, account) in metas.iter().enumerate() {
+        if let Some(idx) = inserted.get(account) {
+            serializable_format.push(SerializableAccount::Duplicate(*idx));
+        } else {
+            serializable_format.push(SerializableAccount::Unique(account.clone()));
+            inserted.insert(account.clone(), idx);
+        }
+    }
+    serializable_format
+}
+
+/// Serialize the accounts and instruction data into the input region, in the format the
+/// BPF loader uses.
+fn serialize_parameters(
+    input: &[u8],
+    metas: &[AccountMeta],
+    vm: &VirtualMachine,
+) -> (Vec<u8>, Vec<AccountRef>) {
+    let mut refs = Vec::new();
+    let mut v: Vec<u8> = Vec::new();
+
+    fn serialize_account(
+        v: &mut Vec<u8>,
+        refs: &mut Vec<AccountRef>,
+        meta: &AccountMeta,
+        acc: &AccountState,
+    ) {
+        // dup_info
+        v.push(0xff);
+        // signer
+        v.push(meta.is_signer.into());
+        // is_writable
+        v.push(meta.is_writable.into());
+        // executable
+        v.push(1);
+        // padding
+        v.extend_from_slice(&[0u8; 4]);
+        // key
+        v.extend_from_slice(&meta.pubkey.0);
+        // owner
+        let owner_offset = v.len();
+
+        v.extend_from_slice(&acc.owner.unwrap_or([0u8; 32]));
+        // lamports
+        v.extend_from_slice(&acc.lamports.to_le_bytes());
+
+        // account data
+        v.extend_from_slice(&(acc.data.len() as u64).to_le_bytes());
+
+        refs.push(AccountRef {
+            account: meta.pubkey.0,
+            owner_offset,
+            data_offset: v.len(),
+            length: acc.data.len(),
+        });
+
+        v.extend_from_slice(&acc.data);
+        v.extend_from_slice(&[0u8; MAX_PERMITTED_DATA_INCREASE]);
+
+        let padding = v.len() % 8;
+        if padding != 0 {
+            v.extend_from_slice(&vec![0; 8 - padding]);
+        }
+        // rent epoch
+        v.extend_from_slice(&0u64.to_le_bytes());
+    }
+
+    let no_duplicates_meta = remove_duplicates(metas);
+    // ka_num
+    v.extend_from_slice(&(no_duplicates_meta.len() as u64).to_le_bytes());
+
+    let empty = AccountState::default();
+
+    for account_item in &no_duplicates_meta {
+        match account_item {
+            SerializableAccount::Unique(account) => {
+                serialize_account(
+                    &mut v,
+                    &mut refs,
+                    account,
+                    vm.account_data.get(&account.pubkey.0).unwrap_or(&empty),
+                );
+            }
+            SerializableAccount::Duplicate(idx) => {
+                v.extend_from_slice(&(*idx as u64).to_le_bytes());
+            }
+        }
+    }
+
+    // calldata
+    v.extend_from_slice(&(input.len() as u64).to_le_bytes());
+    v.extend_from_slice(input);
+
+    // program id
+    v.extend_from_slice(&vm.stack[0]);
+
+    (v, refs)
+}
+
+/// Copy the account data and lamports back from the input region after execution.
+fn deserialize_parameters(
+    input: &[u8],
+    refs: &[AccountRef],
+    accounts_data: &mut HashMap<Account, AccountState>,
+) {
+    for r in refs {
+        if let Some(entry) = accounts_data.get_mut(&r.account) {
+            entry.data = input[r.data_offset..r.data_offset + r.length].to_vec();
+            entry.lamports = u64::from_le_bytes(
+                input[r.data_offset - 16..r.data_offset - 8]
+                    .try_into()
+                    .unwrap(),
+            );
+        }
+    }
+}
+
+/// After a cross program invocation, the caller must see the account changes made by the callee.
+fn update_parameters(
+    input: &mut [u8],
+    refs: &mut [AccountRef],
+    accounts_data: &HashMap<Account, AccountState>,
+) {
+    for r in refs.iter_mut() {
+        if let Some(entry) = accounts_data.get(&r.account) {
+            r.length = entry.data.len();
+
+            input[r.data_offset - 8..r.data_offset].copy_from_slice(&r.length.to_le_bytes());
+            input[r.data_offset..r.data_offset + r.length].copy_from_slice(&entry.data);
+
+            if let Some(owner) = &entry.owner {
+                input[r.owner_offset..r.owner_offset + 32].copy_from_slice(owner);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct SyscallContext<'a> {
+    vm: Rc<RefCell<&'a mut VirtualMachine>>,
+    input_len: usize,
+    refs: Rc<RefCell<&'a mut Vec<AccountRef>>>,
+    remaining: u64,
+}
+
+impl<'a> ContextObject for SyscallContext<'a> {
+    fn trace(&mut self, _state: [u64; 12]) {}
+
+    fn consume(&mut self, amount: u64) {
+        self.remaining = self.remaining.saturating_sub(amount);
+    }
+
+    fn get_remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+impl<'a> SyscallContext<'a> {
+    fn log(&self, message: String) {
+        if let Ok(mut vm) = self.vm.try_borrow_mut() {
+            vm.logs.push(message);
+        }
+    }
+}
+
+fn sol_panic_(
+    _context: &mut SyscallContext,
+    _src: u64,
+    _len: u64,
+    _dest: u64,
+    _arg4: u64,
+    _arg5: u64,
+    _memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    *result = ProgramResult::Err("sol_panic_() called".into());
+}
+
+fn sol_log(
+    context: &mut SyscallContext,
+    vm_addr: u64,
+    len: u64,
+    _arg3: u64,
+    _arg4: u64,
+    _arg5: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    let buf = question_mark!(translate_slice::<u8>(memory_mapping, vm_addr, len), result);
+
+    context.log(String::from_utf8_lossy(buf).to_string());
+
+    *result = ProgramResult::Ok(0)
+}
+
+fn sol_log_pubkey(
+    context: &mut SyscallContext,
+    pubkey_addr: u64,
+    _arg2: u64,
+    _arg3: u64,
+    _arg4: u64,
+    _arg5: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    use base58::ToBase58;
+
+    let account = question_mark!(
+        translate_type::<Account>(memory_mapping, pubkey_addr),
+        result
+    );
+
+    context.log(account.to_base58());
+
+    *result = ProgramResult::Ok(0)
+}
+
+fn sol_log_u64(
+    context: &mut SyscallContext,
+    arg1: u64,
+    arg2: u64,
+    arg3: u64,
+    arg4: u64,
+    arg5: u64,
+    _memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    context.log(format!(
+        "{arg1:#x}, {arg2:#x}, {arg3:#x}, {arg4:#x}, {arg5:#x}"
+    ));
+
+    *result = ProgramResult::Ok(0)
+}
+
+fn sol_sha256(
+    _context: &mut SyscallContext,
+    src: u64,
+    len: u64,
+    dest: u64,
+    _arg4: u64,
+    _arg5: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    let arrays = question_mark!(
+        translate_slice::<(u64, u64)>(memory_mapping, src, len),
+        result
+    );
+
+    let mut hasher = Sha256::new();
+    for (addr, len) in arrays {
+        let buf = question_mark!(translate_slice::<u8>(memory_mapping, *addr, *len), result);
+        hasher.update(buf);
+    }
+
+    let hash = hasher.finalize();
+
+    let hash_result = question_mark!(
+        translate_slice_mut::<u8>(memory_mapping, dest, hash.len() as u64),
+        result
+    );
+
+    hash_result.copy_from_slice(&hash);
+
+    *result = ProgramResult::Ok(0)
+}
+
+fn sol_keccak256(
+    _context: &mut SyscallContext,
+    src: u64,
+    len: u64,
+    dest: u64,
+    _arg4: u64,
+    _arg5: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    let arrays = question_mark!(
+        translate_slice::<(u64, u64)>(memory_mapping, src, len),
+        result
+    );
+
+    let mut hasher = Keccak::v256();
+    let mut hash = [0u8; 32];
+    for (addr, len) in arrays {
+        let buf = question_mark!(translate_slice::<u8>(memory_mapping, *addr, *len), result);
+        hasher.update(buf);
+    }
+    hasher.finalize(&mut hash);
+
+    let hash_result = question_mark!(
+        translate_slice_mut::<u8>(memory_mapping, dest, hash.len() as u64),
+        result
+    );
+
+    hash_result.copy_from_slice(&hash);
+
+    *result = ProgramResult::Ok(0)
+}
+
+/// Read the seeds for a program derived address
+fn translate_seeds<'a>(
+    memory_mapping: &MemoryMapping,
+    seed_ptr: u64,
+    seed_len: u64,
+) -> Result<Vec<&'a [u8]>, Error> {
+    if seed_len > 16 {
+        return Err("too many seeds".into());
+    }
+
+    translate_slice::<(u64, u64)>(memory_mapping, seed_ptr, seed_len)?
+        .iter()
+        .map(|(addr, len)| {
+            if *len > 32 {
+                Err("seed too long".into())
+            } else {
+                translate_slice::<u8>(memory_mapping, *addr, *len)
+            }
+        })
+        .collect()
+}
+
+fn sol_create_program_address(
+    _context: &mut SyscallContext,
+    seed_ptr: u64,
+    seed_len: u64,
+    program_id: u64,
+    dest: u64,
+    _arg5: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    let seeds = question_mark!(translate_seeds(memory_mapping, seed_ptr, seed_len), result);
+
+    let program_id = question_mark!(
+        translate_type::<Account>(memory_mapping, program_id),
+        result
+    );
+
+    let pda = create_program_address(program_id, &seeds);
+
+    let hash_result = question_mark!(translate_slice_mut::<u8>(memory_mapping, dest, 32), result);
+
+    hash_result.copy_from_slice(&pda.0);
+
+    *result = ProgramResult::Ok(0)
+}
+
+fn sol_try_find_program_address(
+    _context: &mut SyscallContext,
+    seed_ptr: u64,
+    seed_len: u64,
+    program_id: u64,
+    dest: u64,
+    bump: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    let mut seeds = question_mark!(translate_seeds(memory_mapping, seed_ptr, seed_len), result);
+
+    let program_id = question_mark!(
+        translate_type::<Account>(memory_mapping, program_id),
+        result
+    );
+
+    // the real runtime searches for the first bump seed which gives an address off the
+    // ed25519 curve; we do not check the curve, so the first bump seed is always used
+    let bump_seed = [u8::MAX];
+    seeds.push(&bump_seed);
+
+    let pda = create_program_address(program_id, &seeds);
+
+    let hash_result = question_mark!(translate_slice_mut::<u8>(memory_mapping, dest, 32), result);
+
+    hash_result.copy_from_slice(&pda.0);
+
+    let bump_result = question_mark!(translate_slice_mut::<u8>(memory_mapping, bump, 1), result);
+
+    bump_result.copy_from_slice(&bump_seed);
+
+    *result = ProgramResult::Ok(0)
+}
+
+fn sol_set_return_data(
+    context: &mut SyscallContext,
+    addr: u64,
+    len: u64,
+    _arg3: u64,
+    _arg4: u64,
+    _arg5: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    if len > 1024 {
+        *result = ProgramResult::Err(format!("return data too large: {len}").into());
+        return;
+    }
+
+    let buf = question_mark!(translate_slice::<u8>(memory_mapping, addr, len), result);
+
+    if let Ok(mut vm) = context.vm.try_borrow_mut() {
+        if len == 0 {
+            vm.return_data = None;
+        } else {
+            vm.return_data = Some((vm.stack[0], buf.to_vec()));
+        }
+    }
+
+    *result = ProgramResult::Ok(0);
+}
+
+fn sol_get_return_data(
+    context: &mut SyscallContext,
+    addr: u64,
+    len: u64,
+    program_id_addr: u64,
+    _arg4: u64,
+    _arg5: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    let vm = context.vm.borrow();
+
+    if let Some((program_id, return_data)) = &vm.return_data {
+        let length = std::cmp::min(len, return_data.len() as u64);
+
+        if len > 0 {
+            let set_result = question_mark!(
+                translate_slice_mut::<u8>(memory_mapping, addr, length),
+                result
+            );
+
+            set_result.copy_from_slice(&return_data[..length as usize]);
+
+            let program_id_result = question_mark!(
+                translate_slice_mut::<u8>(memory_mapping, program_id_addr, 32),
+                result
+            );
+
+            program_id_result.copy_from_slice(program_id);
+        }
+
+        *result = ProgramResult::Ok(return_data.len() as u64);
+    } else {
+        *result = ProgramResult::Ok(0);
+    }
+}
+
+fn sol_log_data(
+    context: &mut SyscallContext,
+    addr: u64,
+    len: u64,
+    _arg3: u64,
+    _arg4: u64,
+    _arg5: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    let untranslated_fields =
+        question_mark!(translate_slice::<&[u8]>(memory_mapping, addr, len), result);
+
+    let mut fields = Vec::with_capacity(untranslated_fields.len());
+
+    for untranslated_field in untranslated_fields {
+        let field = question_mark!(
+            translate_slice::<u8>(
+                memory_mapping,
+                untranslated_field.as_ptr() as u64,
+                untranslated_field.len() as u64,
+            ),
+            result
+        );
+
+        fields.push(hex::encode(field));
+    }
+
+    context.log(format!("data: {}", fields.join(" ")));
+
+    *result = ProgramResult::Ok(0);
+}
+
+/// Rust representation of C's SolInstruction
+struct SolInstruction {
+    program_id_addr: u64,
+    accounts_addr: u64,
+    accounts_len: usize,
+    data_addr: u64,
+    data_len: usize,
+}
+
+/// Rust representation of C's SolAccountMeta
+struct SolAccountMeta {
+    pubkey_addr: u64,
+    is_writable: bool,
+    is_signer: bool,
+}
+
+/// Rust representation of C's SolSignerSeed
+struct SolSignerSeedC {
+    addr: u64,
+    len: u64,
+}
+
+/// Rust representation of C's SolSignerSeeds
+struct SolSignerSeedsC {
+    addr: u64,
+    len: u64,
+}
+
+struct Instruction {
+    /// Pubkey of the instruction processor that executes this instruction
+    program_id: Pubkey,
+    /// Metadata for what accounts should be passed to the instruction processor
+    accounts: Vec<AccountMeta>,
+    /// Opaque data passed to the instruction processor
+    data: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+struct Pubkey(Account);
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+struct AccountMeta {
+    /// An account's public key
+    pubkey: Pubkey,
+    /// True if the `pubkey` can be loaded as a read-write account.
+    is_writable: bool,
+    /// True if an Instruction requires a Transaction signature matching `pubkey`.
+    is_signer: bool,
+}
+
+fn translate(
+    memory_mapping: &MemoryMapping,
+    access_type: AccessType,
+    vm_addr: u64,
+    len: u64,
+) -> Result<u64, Error> {
+    memory_mapping.map(access_type, vm_addr, len, 0).into()
+}
+
+fn translate_type<'a, T>(memory_mapping: &MemoryMapping, vm_addr: u64) -> Result<&'a T, Error> {
+    let host_addr = translate(
+        memory_mapping,
+        AccessType::Load,
+        vm_addr,
+        size_of::<T>() as u64,
+    )?;
+
+    // host_addr is in our address space, cast
+    Ok(unsafe { &*(host_addr as *const T) })
+}
+
+fn translate_slice<'a, T>(
+    memory_mapping: &MemoryMapping,
+    vm_addr: u64,
+    len: u64,
+) -> Result<&'a [T], Error> {
+    translate_slice_inner::<T>(memory_mapping, AccessType::Load, vm_addr, len).map(|value| &*value)
+}
+
+fn translate_slice_mut<'a, T>(
+    memory_mapping: &MemoryMapping,
+    vm_addr: u64,
+    len: u64,
+) -> Result<&'a mut [T], Error> {
+    translate_slice_inner::<T>(memory_mapping, AccessType::Store, vm_addr, len)
+}
+
+fn translate_slice_inner<'a, T>(
+    memory_mapping: &MemoryMapping,
+    access_type: AccessType,
+    vm_addr: u64,
+    len: u64,
+) -> Result<&'a mut [T], Error> {
+    if len == 0 {
+        return Ok(&mut []);
+    }
+
+    let total_size = len.saturating_mul(size_of::<T>() as u64);
+
+    let host_addr = translate(memory_mapping, access_type, vm_addr, total_size)?;
+
+    // host_addr is in our address space, cast
+    Ok(unsafe { std::slice::from_raw_parts_mut(host_addr as *mut T, len as usize) })
+}
+
+fn translate_instruction(addr: u64, memory_mapping: &MemoryMapping) -> Result<Instruction, Error> {
+    let ix_c = translate_type::<SolInstruction>(memory_mapping, addr)?;
+
+    let program_id = translate_type::<Pubkey>(memory_mapping, ix_c.program_id_addr)?;
+    let meta_cs = translate_slice::<SolAccountMeta>(
+        memory_mapping,
+        ix_c.accounts_addr,
+        ix_c.accounts_len as u64,
+    )?;
+    let data =
+        translate_slice::<u8>(memory_mapping, ix_c.data_addr, ix_c.data_len as u64)?.to_vec();
+    let accounts = meta_cs
+        .iter()
+        .map(|meta_c| {
+            let pubkey = translate_type::<Pubkey>(memory_mapping, meta_c.pubkey_addr)?;
+            Ok(AccountMeta {
+                pubkey: pubkey.clone(),
+                is_signer: meta_c.is_signer,
+                is_writable: meta_c.is_writable,
+            })
+        })
+        .collect::<Result<Vec<AccountMeta>, Error>>()?;
+
+    Ok(Instruction {
+        program_id: program_id.clone(),
+        accounts,
+        data,
+    })
+}
+
+fn create_program_address(program_id: &Account, seeds: &[&[u8]]) -> Pubkey {
+    let mut hasher = Sha256::new();
+
+    for seed in seeds {
+        hasher.update(seed);
+    }
+
+    hasher.update(program_id);
+    hasher.update(b"ProgramDerivedAddress");
+
+    let hash = hasher.finalize();
+
+    // the real runtime does checks if this address exists on the ed25519 curve
+    Pubkey(hash.into())
+}
+
+/// Read little endian integers from the instruction data of the system program
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| "system instruction too short".into())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, Error> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| "system instruction too short".into())
+}
+
+fn read_account(data: &[u8], offset: usize) -> Result<Account, Error> {
+    data.get(offset..offset + 32)
+        .map(|bytes| bytes.try_into().unwrap())
+        .ok_or_else(|| "system instruction too short".into())
+}
+
+/// Execute an instruction of the system program. The instruction data is bincode encoded,
+/// starting with the instruction number as `u32`.
+fn system_instruction(
+    vm: &mut VirtualMachine,
+    refs: &mut [AccountRef],
+    instruction: &Instruction,
+    signers: &[Pubkey],
+) -> Result<(), Error> {
+    let data = &instruction.data;
+    let account = |no: usize| -> Result<Account, Error> {
+        instruction
+            .accounts
+            .get(no)
+            .map(|meta| meta.pubkey.0)
+            .ok_or_else(|| "not enough account keys".into())
+    };
+    let is_signer = |address: &Account| {
+        instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey.0 == *address && meta.is_signer)
+            || signers.iter().any(|signer| signer.0 == *address)
+    };
+
+    let (address, space, owner) = match read_u32(data, 0)? {
+        // CreateAccount { lamports, space, owner }
+        0 => (
+            account(1)?,
+            Some(read_u64(data, 12)?),
+            Some(read_account(data, 20)?),
+        ),
+        // Assign { owner }
+        1 => (account(0)?, None, Some(read_account(data, 4)?)),
+        // Allocate { space }
+        8 => (account(0)?, Some(read_u64(data, 4)?), None),
+        instruction => return Err(format!("system instruction {instruction} not supported").into()),
+    };
+
+    if !is_signer(&address) {
+        return Err("missing required signature".into());
+    }
+
+    let entry = vm.account_data.entry(address).or_default();
+
+    if let Some(space) = space {
+        if !entry.data.is_empty() {
+            return Err("account already in use".into());
+        }
+
+        entry.data = vec![0; space as usize];
+
+        for r in refs.iter_mut() {
+            if r.account == address {
+                r.length = space as usize;
+            }
+        }
+    }
+
+    if owner.is_some() {
+        entry.owner = owner;
+    }
+
+    Ok(())
+}
+
+fn sol_invoke_signed_c(
+    context: &mut SyscallContext,
+    instruction_addr: u64,
+    _account_infos_addr: u64,
+    _account_infos_len: u64,
+    signers_seeds_addr: u64,
+    signers_seeds_len: u64,
+    memory_mapping: &mut MemoryMapping,
+    result: &mut ProgramResult,
+) {
+    let instruction = question_mark!(
+        translate_instruction(instruction_addr, memory_mapping),
+        result
+    );
+
+    let seeds = question_mark!(
+        translate_slice::<SolSignerSeedsC>(memory_mapping, signers_seeds_addr, signers_seeds_len),
+        result
+    );
+
+    let mut vm = context.vm.borrow_mut();
+
+    let mut signers = Vec::new();
+
+    for seed in seeds {
+        let seeds = question_mark!(
+            translate_slice::<SolSignerSeedC>(memory_mapping, seed.addr, seed.len),
+            result
+        );
+
+        let seeds = question_mark!(
+            seeds
+                .iter()
+                .map(|seed| translate_slice::<u8>(memory_mapping, seed.addr, seed.len))
+                .collect::<Result<Vec<&[u8]>, Error>>(),
+            result
+        );
+
+        signers.push(create_program_address(&vm.stack[0], &seeds));
+    }
+
+    vm.return_data = None;
+
+    if instruction.program_id.0 == SYSTEM_PROGRAM {
+        let mut refs = context.refs.borrow_mut();
+
+        question_mark!(
+            system_instruction(&mut vm, &mut refs, &instruction, &signers),
+            result
+        );
+    } else {
+        if !vm.programs.contains(&Some(instruction.program_id.0)) {
+            *result = ProgramResult::Err("cross program invocation of unknown program".into());
+            return;
+        }
+
+        vm.stack.insert(0, instruction.program_id.0);
+
+        let res = vm.execute(&instruction.accounts, &instruction.data);
+
+        vm.stack.remove(0);
+
+        // Return the error code of the callee to the caller, so it can be handled with
+        // try-catch. The return data set by the callee remains available.
+        match res {
+            StableResult::Ok(0) => (),
+            StableResult::Ok(code) => {
+                *result = ProgramResult::Ok(code);
+                return;
+            }
+            StableResult::Err(err) => {
+                *result = ProgramResult::Err(err);
+                return;
+            }
+        }
+
+        let mut refs = context.refs.borrow_mut();
+
+        let input = question_mark!(
+            translate_slice_mut::<u8>(
+                memory_mapping,
+                ebpf::MM_INPUT_START,
+                context.input_len as u64,
+            ),
+            result
+        );
+
+        update_parameters(input, &mut refs, &vm.account_data);
+    }
+
+    *result = ProgramResult::Ok(0)
+}
@@ -4,17 +4,21 @@ use super::statements::{statement, LoopScopes};
 use super::{
     constant_folding, dead_storage,
     expression::expression,
-    reaching_definitions, strength_reduce,
+    licm,
+    passes::Pass,
+    reaching_definitions, storage_cache, strength_reduce,
     vartable::{Vars, Vartable},
-    vector_to_slice, Options,
+    vector_to_slice, verify, Options,
 };
 use crate::codegen::subexpression_elimination::common_sub_expression_elimination;
 use crate::codegen::{undefined_variable, Expression, LLVMName};
 use crate::sema::ast::{
-    CallTy, Contract, ExternalCallAccounts, FunctionAttributes, Namespace, Parameter, RetrieveType,
-    Statement, StringLocation, StructType, Type,
+    CallTy, Contract, Diagnostic, ExternalCallAccounts, FunctionAttributes, Layout, Namespace,
+    Note, Parameter, RetrieveType, Statement, StringLocation, StructType, Type,
+};
+use crate::sema::{
+    contracts::collect_base_args, diagnostics::Diagnostics, file::PathDisplay, Recurse,
 };
-use crate::sema::{contracts::collect_base_args, diagnostics::Diagnostics, Recurse};
 use crate::{sema::ast, Target};
 use indexmap::IndexMap;
 use num_bigint::BigInt;
@@ -60,8 +64,11 @@ pub enum Instr {
     },
     /// Set array element in memory
     Store { dest: Expression, data: Expression },
-    /// Abort execution
-    AssertFailure { encoded_args: Option<Expression> },
+    /// Abort execution. On Solana, the error code is returned as `ProgramError::Custom`
+    AssertFailure {
+        encoded_args: Option<Expression>,
+        error_code: Option<u32>,
+    },
     /// Print to log message
     Print { expr: Expression },
     /// Load storage (this is an instruction rather than an expression
@@ -226,6 +233,7 @@ impl Instr {
             | Instr::Print { expr }
             | Instr::AssertFailure {
                 encoded_args: Some(expr),
+                ..
             }
             | Instr::PopStorage { storage: expr, .. }
             | Instr::SelfDestruct { recipient: expr }
@@ -353,7 +361,9 @@ impl Instr {
                 }
             }
 
-            Instr::AssertFailure { encoded_args: None }
+            Instr::AssertFailure {
+                encoded_args: None, ..
+            }
             | Instr::Nop
             | Instr::ReturnCode { .. }
             | Instr::Branch { .. }
@@ -362,6 +372,98 @@ impl Instr {
             | Instr::Unimplemented { .. } => {}
         }
     }
+
+    /// All the expressions directly used by an instruction
+    pub(crate) fn expressions_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Instr::Set { expr, .. }
+            | Instr::BranchCond { cond: expr, .. }
+            | Instr::Print { expr }
+            | Instr::LoadStorage { storage: expr, .. }
+            | Instr::ClearStorage { storage: expr, .. }
+            | Instr::PopStorage { storage: expr, .. }
+            | Instr::SelfDestruct { recipient: expr } => vec![expr],
+            Instr::PushMemory { value, .. } => vec![value.as_mut()],
+            Instr::Call { call, args, .. } => {
+                let mut exprs: Vec<&mut Expression> = args.iter_mut().collect();
+                if let InternalCallTy::Dynamic(expr) = call {
+                    exprs.push(expr);
+                }
+                exprs
+            }
+            Instr::Return { value } => value.iter_mut().collect(),
+            Instr::Store { dest, data } => vec![dest, data],
+            Instr::AssertFailure { encoded_args, .. } => encoded_args.iter_mut().collect(),
+            Instr::SetStorage { value, storage, .. } => vec![value, storage],
+            Instr::SetStorageBytes {
+                value,
+                storage,
+                offset,
+            } => vec![value, storage, offset],
+            Instr::PushStorage { value, storage, .. } => {
+                value.iter_mut().chain(std::iter::once(storage)).collect()
+            }
+            Instr::Constructor {
+                encoded_args,
+                value,
+                gas,
+                salt,
+                address,
+                seeds,
+                accounts,
+                ..
+            } => {
+                let mut exprs = vec![encoded_args, gas];
+                exprs.extend(value.iter_mut());
+                exprs.extend(salt.iter_mut());
+                exprs.extend(address.iter_mut());
+                exprs.extend(seeds.iter_mut());
+                if let ExternalCallAccounts::Present(expr) = accounts {
+                    exprs.push(expr);
+                }
+                exprs
+            }
+            Instr::ExternalCall {
+                address,
+                accounts,
+                seeds,
+                payload,
+                value,
+                gas,
+                flags,
+                ..
+            } => {
+                let mut exprs = vec![payload, value, gas];
+                exprs.extend(address.iter_mut());
+                exprs.extend(seeds.iter_mut());
+                exprs.extend(flags.iter_mut());
+                if let ExternalCallAccounts::Present(expr) = accounts {
+                    exprs.push(expr);
+                }
+                exprs
+            }
+            Instr::ValueTransfer { address, value, .. } => vec![address, value],
+            Instr::EmitEvent { data, topics, .. } => {
+                std::iter::once(data).chain(topics.iter_mut()).collect()
+            }
+            Instr::WriteBuffer { buf, offset, value } => vec![buf, offset, value],
+            Instr::MemCopy {
+                source,
+                destination,
+                bytes,
+            } => vec![source, destination, bytes],
+            Instr::Switch { cond, cases, .. } => std::iter::once(cond)
+                .chain(cases.iter_mut().map(|(expr, _)| expr))
+                .collect(),
+            Instr::ReturnData { data, data_len } => vec![data, data_len],
+            Instr::Branch { .. }
+            | Instr::PopMemory { .. }
+            | Instr::Nop
+            | Instr::ReturnCode { .. }
+            | Instr::Unimplemented { .. }
+            | Instr::AccountAccess { .. } => Vec::new(),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -420,6 +522,25 @@ pub struct ControlFlowGraph {
     pub array_lengths_temps: ArrayLengthVars,
     /// Is this a modifier dispatch for which function number?
     pub modifier: Option<usize>,
+    /// Runtime checks which were proven unnecessary and removed, for `--emit removed-checks`
+    pub removed_checks: Vec<RemovedCheck>,
+}
+
+/// A runtime check which was removed by the optimizer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovedCheck {
+    pub loc: Loc,
+    pub kind: CheckKind,
+    /// The condition which implies the check always passes, or None if it follows from the
+    /// known range of the values
+    pub implied_by: Option<Loc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[display(style = "lowercase")]
+pub enum CheckKind {
+    Overflow,
+    Bounds,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -492,6 +613,7 @@ impl ControlFlowGraph {
             current: 0,
             array_lengths_temps: IndexMap::new(),
             modifier: None,
+            removed_checks: Vec::new(),
         };
 
         cfg.new_basic_block("entry".to_string());
@@ -515,6 +637,7 @@ impl ControlFlowGraph {
             current: 0,
             array_lengths_temps: IndexMap::new(),
             modifier: None,
+            removed_checks: Vec::new(),
         }
     }
 
@@ -879,6 +1002,9 @@ impl ControlFlowGraph {
             Expression::Negate { expr, .. } => {
                 format!("-{}", self.expr_to_string(contract, ns, expr))
             }
+            Expression::PackedStorageSlot {
+                ty, slot, offset, ..
+            } => format!("{} {} offset {}", ty.to_string(ns), slot, offset),
             Expression::Poison => "☠".to_string(),
             Expression::AllocDynamicBytes {
                 ty,
@@ -1113,12 +1239,20 @@ impl ControlFlowGraph {
                 self.vars[array].id.name,
                 ty.to_string(ns),
             ),
-            Instr::AssertFailure { encoded_args: None } => "assert-failure".to_string(),
-            Instr::AssertFailure { encoded_args: Some(expr) } => {
+            Instr::AssertFailure { encoded_args: None, error_code: None } => "assert-failure".to_string(),
+            Instr::AssertFailure { encoded_args: None, error_code: Some(code) } => {
+                format!("assert-failure: error code: {code}")
+            }
+            Instr::AssertFailure { encoded_args: Some(expr), error_code: None } => {
                 format!("assert-failure: buffer: {}",
                         self.expr_to_string(contract, ns, expr),
                 )
             }
+            Instr::AssertFailure { encoded_args: Some(expr), error_code: Some(code) } => {
+                format!("assert-failure: buffer: {} error code: {code}",
+                        self.expr_to_string(contract, ns, expr),
+                )
+            }
             Instr::Call {
                 res,
                 call: InternalCallTy::Builtin { ast_func_no },
@@ -1367,40 +1501,56 @@ impl ControlFlowGraph {
         }
     }
 
-    pub fn basic_block_to_string(&self, contract: &Contract, ns: &Namespace, pos: usize) -> String {
-        let mut s = format!("block{}: # {}\n", pos, self.blocks[pos].name);
+    /// The phis and reaching definitions of a basic block, as computed by the last
+    /// reaching definitions pass
+    pub fn basic_block_annotations(&self, pos: usize) -> Vec<String> {
+        let mut annotations = Vec::new();
 
         if let Some(ref phis) = self.blocks[pos].phis {
-            writeln!(
-                s,
-                "\t# phis: {}",
+            annotations.push(format!(
+                "phis: {}",
                 phis.iter()
                     .map(|p| -> &str { &self.vars[p].id.name })
                     .collect::<Vec<&str>>()
                     .join(",")
-            )
-            .unwrap();
+            ));
         }
 
-        let defs = &self.blocks[pos].defs;
+        // sort the definitions, so that the output does not depend on the order in which
+        // they were found
+        let mut defs: Vec<_> = self.blocks[pos].defs.iter().collect();
+        defs.sort_by_key(|(var_no, _)| **var_no);
 
         if !defs.is_empty() {
-            writeln!(
-                s,
-                "\t# reaching:{}",
+            annotations.push(format!(
+                "reaching:{}",
                 defs.iter()
-                    .map(|(var_no, defs)| format!(
-                        " {}:[{}]",
-                        &self.vars[var_no].id.name,
-                        defs.keys()
-                            .map(|d| format!("{}:{}", d.block_no, d.instr_no))
-                            .collect::<Vec<String>>()
-                            .join(", ")
-                    ))
+                    .map(|(var_no, defs)| {
+                        let mut defs: Vec<_> = defs.keys().collect();
+                        defs.sort_by_key(|d| (d.block_no, d.instr_no));
+
+                        format!(
+                            " {}:[{}]",
+                            &self.vars[*var_no].id.name,
+                            defs.iter()
+                                .map(|d| format!("{}:{}", d.block_no, d.instr_no))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        )
+                    })
                     .collect::<Vec<String>>()
                     .join(", ")
-            )
-            .unwrap();
+            ));
+        }
+
+        annotations
+    }
+
+    pub fn basic_block_to_string(&self, contract: &Contract, ns: &Namespace, pos: usize) -> String {
+        let mut s = format!("block{}: # {}\n", pos, self.blocks[pos].name);
+
+        for annotation in self.basic_block_annotations(pos) {
+            writeln!(s, "\t# {annotation}").unwrap();
         }
 
         for ins in &self.blocks[pos].instr {
@@ -1480,7 +1630,7 @@ pub fn generate_cfg(
     let ast_fn = function_no
         .map(ASTFunction::SolidityFunction)
         .unwrap_or(ASTFunction::None);
-    optimize_and_check_cfg(&mut cfg, ns, ast_fn, opt);
+    optimize_and_check_cfg(&mut cfg, contract_no, ns, ast_fn, opt);
 
     if let Some(func_no) = function_no {
         let func = &ns.functions[func_no];
@@ -1506,13 +1656,13 @@ pub fn generate_cfg(
                     ns,
                     opt,
                 );
-                optimize_and_check_cfg(&mut cfg, ns, ast_fn, opt);
+                cfg.modifier = Some(func_no);
+                optimize_and_check_cfg(&mut cfg, contract_no, ns, ast_fn, opt);
             }
 
             cfg.public = public;
             cfg.nonpayable = nonpayable;
             cfg.selector = ns.functions[func_no].selector(ns, &contract_no);
-            cfg.modifier = Some(func_no);
         }
     }
 
@@ -1551,11 +1701,12 @@ fn resolve_modifier_call<'a>(
 /// Detect undefined variables and run codegen optimizer passess
 pub fn optimize_and_check_cfg(
     cfg: &mut ControlFlowGraph,
+    contract_no: usize,
     ns: &mut Namespace,
     func_no: ASTFunction,
     opt: &Options,
 ) {
-    reaching_definitions::find(cfg);
+    opt.time("reaching-definitions", || reaching_definitions::find(cfg));
     if func_no != ASTFunction::None {
         // If there are undefined variables, we raise an error and don't run optimizations
         if undefined_variable::find_undefined_variables(cfg, ns, func_no) {
@@ -1563,24 +1714,133 @@ pub fn optimize_and_check_cfg(
         }
     }
 
+    if !verify_cfg("codegen", cfg, ns, opt) {
+        return;
+    }
+
     // constant folding generates diagnostics, so always run it. This means that the diagnostics
     // do not depend which passes are enabled. If the constant_folding is not enabled, run it
     // dry mode.
-    constant_folding::constant_folding(cfg, !opt.constant_folding, ns);
+    opt.time(Pass::ConstantFolding.name(), || {
+        constant_folding::constant_folding(cfg, !opt.constant_folding, ns)
+    });
+    print_after(Pass::ConstantFolding, cfg, contract_no, ns, opt);
+    if !verify_cfg(Pass::ConstantFolding.name(), cfg, ns, opt) {
+        return;
+    }
+
     if opt.vector_to_slice {
-        vector_to_slice::vector_to_slice(cfg, ns);
+        opt.time(Pass::VectorToSlice.name(), || {
+            vector_to_slice::vector_to_slice(cfg, ns)
+        });
+        print_after(Pass::VectorToSlice, cfg, contract_no, ns, opt);
+        if !verify_cfg(Pass::VectorToSlice.name(), cfg, ns, opt) {
+            return;
+        }
     }
     if opt.strength_reduce {
-        strength_reduce::strength_reduce(cfg, ns);
+        opt.time(Pass::StrengthReduce.name(), || {
+            strength_reduce::strength_reduce(cfg, ns)
+        });
+        print_after(Pass::StrengthReduce, cfg, contract_no, ns, opt);
+        if !verify_cfg(Pass::StrengthReduce.name(), cfg, ns, opt) {
+            return;
+        }
     }
     if opt.dead_storage {
-        dead_storage::dead_storage(cfg, ns);
+        opt.time(Pass::DeadStorage.name(), || {
+            dead_storage::dead_storage(cfg, ns)
+        });
+        print_after(Pass::DeadStorage, cfg, contract_no, ns, opt);
+        if !verify_cfg(Pass::DeadStorage.name(), cfg, ns, opt) {
+            return;
+        }
+    }
+    if opt.storage_cache {
+        opt.time(Pass::StorageCache.name(), || {
+            storage_cache::storage_cache(cfg, ns)
+        });
+        print_after(Pass::StorageCache, cfg, contract_no, ns, opt);
+        if !verify_cfg(Pass::StorageCache.name(), cfg, ns, opt) {
+            return;
+        }
     }
 
     // If the function is a default constructor, there is nothing to optimize.
     if opt.common_subexpression_elimination && func_no != ASTFunction::None {
-        common_sub_expression_elimination(cfg, ns);
+        opt.time(Pass::CommonSubexpressionElimination.name(), || {
+            common_sub_expression_elimination(cfg, ns)
+        });
+        print_after(
+            Pass::CommonSubexpressionElimination,
+            cfg,
+            contract_no,
+            ns,
+            opt,
+        );
+        if !verify_cfg(Pass::CommonSubexpressionElimination.name(), cfg, ns, opt) {
+            return;
+        }
+    }
+
+    if opt.licm {
+        opt.time(Pass::LoopInvariantCodeMotion.name(), || {
+            licm::loop_invariant_code_motion(cfg, ns)
+        });
+        print_after(Pass::LoopInvariantCodeMotion, cfg, contract_no, ns, opt);
+        verify_cfg(Pass::LoopInvariantCodeMotion.name(), cfg, ns, opt);
+    }
+}
+
+/// Keep a copy of the CFG if it should be printed after the pass. The CFG cannot be printed
+/// yet, since the functions it calls do not have a CFG yet.
+fn print_after(
+    pass: Pass,
+    cfg: &ControlFlowGraph,
+    contract_no: usize,
+    ns: &mut Namespace,
+    opt: &Options,
+) {
+    if opt.print_after.contains(&pass) {
+        ns.contracts[contract_no]
+            .pass_dumps
+            .push((pass, cfg.clone()));
+    }
+}
+
+/// With `--verify-cfg` or in debug builds, check that the CFG is still well formed after the
+/// pass. An invalid CFG is reported as an error, and false is returned so that no further
+/// passes are run on it.
+fn verify_cfg(after: &str, cfg: &ControlFlowGraph, ns: &mut Namespace, opt: &Options) -> bool {
+    if !opt.verify_cfg {
+        return true;
+    }
+
+    let errors = opt.time("verify-cfg", || verify::verify_cfg(cfg, ns));
+
+    if errors.is_empty() {
+        return true;
     }
+
+    let loc = match cfg.function_no {
+        ASTFunction::SolidityFunction(function_no) => ns.functions[function_no].loc,
+        ASTFunction::YulFunction(function_no) => ns.yul_functions[function_no].loc,
+        ASTFunction::None => pt::Loc::Codegen,
+    };
+
+    ns.diagnostics.push(Diagnostic::error_with_notes(
+        loc,
+        format!(
+            "internal compiler error: CFG '{}' is invalid after {}",
+            cfg.name, after
+        ),
+        errors
+            .into_iter()
+            .map(|message| Note { loc, message })
+            .collect(),
+    ));
+
+    false
 }
 
 /// Generate the CFG for a function. If function_no is None, generate the implicit default
@@ -2087,6 +2347,69 @@ impl Contract {
         out
     }
 
+    /// Print the runtime checks which the optimizer removed, for `--emit removed-checks`
+    pub fn print_removed_checks(&self, ns: &Namespace) -> String {
+        let mut out = format!("#\n# Contract: {}\n#\n", self.id);
+
+        for cfg in &self.cfg {
+            if cfg.is_placeholder() || cfg.removed_checks.is_empty() {
+                continue;
+            }
+
+            writeln!(out, "\n# {} {}", cfg.ty, cfg.name).unwrap();
+
+            let mut checks: Vec<&RemovedCheck> = cfg.removed_checks.iter().collect();
+            checks.sort_by_key(|check| check.loc);
+
+            for check in checks {
+                let reason = match &check.implied_by {
+                    Some(loc) => format!(
+                        "implied by condition at {}",
+                        ns.loc_to_string(PathDisplay::FullPath, loc)
+                    ),
+                    None => String::from("value range"),
+                };
+
+                writeln!(
+                    out,
+                    "{}: {} check removed: {}",
+                    ns.loc_to_string(PathDisplay::FullPath, &check.loc),
+                    check.kind,
+                    reason
+                )
+                .unwrap();
+            }
+        }
+
+        out
+    }
+
+    /// Print the CFGs which were kept for `--print-after`, in the order the passes were run
+    pub fn print_pass_dumps(&self, ns: &Namespace) -> String {
+        let mut out = String::new();
+
+        for (pass, cfg) in &self.pass_dumps {
+            writeln!(
+                out,
+                "# *** CFG after {} ***\n# {} {}",
+                pass, cfg.ty, cfg.name
+            )
+            .unwrap();
+
+            out += &cfg.to_string(self, ns);
+        }
+
+        out
+    }
+
+    /// Get the storage layout for a variable, possibly from base contract
+    pub fn get_storage_layout(&self, var_contract_no: usize, var_no: usize) -> &Layout {
+        self.layout
+            .iter()
+            .find(|l| l.contract_no == var_contract_no && l.var_no == var_no)
+            .expect("get_storage_layout called on non-storage variable")
+    }
+
     /// Get the storage slot for a variable, possibly from base contract
     pub fn get_storage_slot(
         &self,
@@ -2096,18 +2419,21 @@ impl Contract {
         ns: &Namespace,
         ty: Option<Type>,
     ) -> Expression {
-        if let Some(layout) = self
-            .layout
-            .iter()
-            .find(|l| l.contract_no == var_contract_no && l.var_no == var_no)
-        {
+        let layout = self.get_storage_layout(var_contract_no, var_no);
+
+        if let Some(offset) = layout.offset {
+            Expression::PackedStorageSlot {
+                loc,
+                ty: ty.unwrap_or_else(|| ns.storage_type()),
+                slot: layout.slot.clone(),
+                offset,
+            }
+        } else {
             Expression::NumberLiteral {
                 loc,
                 ty: ty.unwrap_or_else(|| ns.storage_type()),
                 value: layout.slot.clone(),
             }
-        } else {
-            panic!("get_storage_slot called on non-storage variable");
         }
     }
 }
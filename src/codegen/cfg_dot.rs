@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::cfg::{ControlFlowGraph, Instr};
+use crate::sema::ast::{Contract, Namespace};
+use std::fmt::Write;
+
+impl Contract {
+    /// Print the CFGs of the contract as a graphviz dot file. Each CFG is a cluster, with its
+    /// basic blocks as nodes. Optionally, the phis and reaching definitions of each block
+    /// are included.
+    pub fn print_cfg_dot(&self, ns: &Namespace, annotations: bool) -> String {
+        let mut out = format!(
+            "digraph \"{}\" {{\n\tnode [shape=box fontname=\"monospace\"]\n",
+            escape(&self.id.name)
+        );
+
+        for (cfg_no, cfg) in self.cfg.iter().enumerate() {
+            if !cfg.is_placeholder() {
+                cfg_dot(&mut out, self, ns, cfg_no, cfg, annotations);
+            }
+        }
+
+        out.push_str("}\n");
+
+        out
+    }
+}
+
+fn cfg_dot(
+    out: &mut String,
+    contract: &Contract,
+    ns: &Namespace,
+    cfg_no: usize,
+    cfg: &ControlFlowGraph,
+    annotations: bool,
+) {
+    writeln!(out, "\tsubgraph cluster_{cfg_no} {{").unwrap();
+    writeln!(
+        out,
+        "\t\tlabel=\"{} {} public:{}\"",
+        cfg.ty,
+        escape(&cfg.name),
+        cfg.public
+    )
+    .unwrap();
+
+    for (block_no, block) in cfg.blocks.iter().enumerate() {
+        let mut label = format!("block{}: # {}\\l", block_no, escape(&block.name));
+
+        if annotations {
+            for annotation in cfg.basic_block_annotations(block_no) {
+                write!(label, "# {}\\l", escape(&annotation)).unwrap();
+            }
+        }
+
+        for instr in &block.instr {
+            write!(
+                label,
+                "{}\\l",
+                escape(&cfg.instr_to_string(contract, ns, instr))
+            )
+            .unwrap();
+        }
+
+        writeln!(out, "\t\tcfg{cfg_no}_block{block_no} [label=\"{label}\"]").unwrap();
+    }
+
+    for (block_no, block) in cfg.blocks.iter().enumerate() {
+        let edges = match block.instr.last() {
+            Some(Instr::Branch { block }) => vec![(*block, None)],
+            Some(Instr::BranchCond {
+                true_block,
+                false_block,
+                ..
+            }) => vec![
+                (*true_block, Some("true".to_owned())),
+                (*false_block, Some("false".to_owned())),
+            ],
+            Some(Instr::Switch { cases, default, .. }) => cases
+                .iter()
+                .map(|(expr, block)| (*block, Some(cfg.expr_to_string(contract, ns, expr))))
+                .chain([(*default, Some("default".to_owned()))])
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        for (to, label) in edges {
+            write!(
+                out,
+                "\t\tcfg{cfg_no}_block{block_no} -> cfg{cfg_no}_block{to}"
+            )
+            .unwrap();
+
+            if let Some(label) = label {
+                write!(out, " [label=\"{}\"]", escape(&label)).unwrap();
+            }
+
+            out.push('\n');
+        }
+    }
+
+    out.push_str("\t}\n");
+}
+
+/// Escape a string for use in a quoted dot label
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\l")
+        .replace('\t', "  ")
+}
@@ -0,0 +1,938 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parser for the textual form of a CFG, as printed by `--emit cfg`. This makes it possible to
+//! write tests for the codegen optimization passes as IR in, IR out.
+//!
+//! Only the instructions and expressions which the optimization passes work on are supported:
+//! arithmetic, comparisons, casts, storage loads and stores, branches, switches, prints and
+//! returns. The types of the variables are taken from the `ty:` of the instructions which set
+//! them, and the types of the function arguments from the `# params:` line.
+
+use super::cfg::{BasicBlock, ControlFlowGraph, Instr};
+use super::vartable::{Storage, Variable, Vartable};
+use super::Expression;
+use crate::sema::ast::{ArrayLength, Namespace, Parameter, RetrieveType, Type};
+use num_bigint::BigInt;
+use solang_parser::pt;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// Parse a single CFG from its textual form. The variables are numbered from `ns.next_id`,
+/// which is updated like when a CFG is generated.
+pub fn parse_cfg(src: &str, ns: &mut Namespace) -> Result<ControlFlowGraph, String> {
+    let mut parser = CfgParser {
+        cfg: ControlFlowGraph::placeholder(),
+        vartab: Vartable::new(ns.next_id),
+        names: HashMap::new(),
+        params: Vec::new(),
+        phis: Vec::new(),
+    };
+
+    // The variables can be used before the instruction which sets them, e.g. in a loop, so
+    // collect their types first
+    for (line_no, line) in src.lines().enumerate() {
+        parser
+            .declaration(line.trim())
+            .map_err(|err| format!("line {}: {}", line_no + 1, err))?;
+    }
+
+    for (line_no, line) in src.lines().enumerate() {
+        parser
+            .line(line.trim())
+            .map_err(|err| format!("line {}: {}", line_no + 1, err))?;
+    }
+
+    parser.finish(ns)
+}
+
+struct CfgParser {
+    cfg: ControlFlowGraph,
+    vartab: Vartable,
+    names: HashMap<String, usize>,
+    params: Vec<Parameter>,
+    /// The names of the phis for each block
+    phis: Vec<(usize, Vec<String>)>,
+}
+
+impl CfgParser {
+    /// If the line sets a variable, declare the variable with its type
+    fn declaration(&mut self, line: &str) -> Result<(), String> {
+        if line.starts_with('#') || line.starts_with("block") {
+            return Ok(());
+        }
+
+        let mut tokens = Tokens::new(line)?;
+
+        let (name, ty) = match tokens.peek() {
+            // ty:uint64 %a = ...
+            Some(Token::Ident(ident)) if ident == "ty" => {
+                tokens.next();
+                tokens.expect(":")?;
+                let ty = tokens.ty()?;
+                (tokens.var()?, ty)
+            }
+            // %a = load storage slot(...) ty:uint64
+            Some(Token::Var(_)) => {
+                let name = tokens.var()?;
+
+                let Some(pos) = tokens.tokens.iter().rposition(|t| t.is_ident("ty")) else {
+                    return Ok(());
+                };
+
+                tokens.pos = pos + 1;
+                tokens.expect(":")?;
+                (name, tokens.ty()?)
+            }
+            _ => return Ok(()),
+        };
+
+        if let Some(var_no) = self.names.get(&name) {
+            let existing = &self.vartab.vars[var_no].ty;
+
+            if *existing != ty {
+                return Err(format!("variable %{name} has type {existing:?} and {ty:?}"));
+            }
+        } else {
+            let var_no = self.vartab.next_id;
+            self.vartab.next_id += 1;
+
+            self.vartab.vars.insert(
+                var_no,
+                Variable {
+                    id: pt::Identifier {
+                        name: name.clone(),
+                        loc: pt::Loc::Codegen,
+                    },
+                    ty,
+                    storage: Storage::Local,
+                },
+            );
+
+            self.names.insert(name, var_no);
+        }
+
+        Ok(())
+    }
+
+    fn line(&mut self, line: &str) -> Result<(), String> {
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            return self.comment(comment.trim());
+        }
+
+        if let Some((block, name)) = line.split_once(": #") {
+            if let Some(block_no) = block.strip_prefix("block") {
+                if block_no.parse() != Ok(self.cfg.blocks.len()) {
+                    return Err(format!("expected block{}", self.cfg.blocks.len()));
+                }
+
+                self.cfg.blocks.push(BasicBlock {
+                    name: name.trim().to_owned(),
+                    ..Default::default()
+                });
+
+                return Ok(());
+            }
+        }
+
+        let mut tokens = Tokens::new(line)?;
+
+        let instr = self.instr(&mut tokens)?;
+
+        if let Some(token) = tokens.next() {
+            return Err(format!("unexpected '{token}'"));
+        }
+
+        if let Some(instr) = instr {
+            let Some(block) = self.cfg.blocks.last_mut() else {
+                return Err("instruction outside of a block".into());
+            };
+
+            block.instr.push(instr);
+        }
+
+        Ok(())
+    }
+
+    fn comment(&mut self, comment: &str) -> Result<(), String> {
+        if let Some(phis) = comment.strip_prefix("phis:") {
+            let block_no = self.cfg.blocks.len().wrapping_sub(1);
+
+            self.phis.push((
+                block_no,
+                phis.split(',')
+                    .map(|name| name.trim().to_owned())
+                    .filter(|name| !name.is_empty())
+                    .collect(),
+            ));
+        } else if let Some(params) = comment.strip_prefix("params:") {
+            self.params = parameters(params)?;
+            self.cfg.params = Arc::new(self.params.clone());
+        } else if let Some(returns) = comment.strip_prefix("returns:") {
+            self.cfg.returns = Arc::new(parameters(returns)?);
+        } else {
+            // header of the function, e.g. "function c::c::function::f public:true ..."
+            let mut words = comment.split_whitespace();
+
+            let ty = match words.next() {
+                Some("function") => pt::FunctionTy::Function,
+                Some("constructor") => pt::FunctionTy::Constructor,
+                Some("fallback") => pt::FunctionTy::Fallback,
+                Some("receive") => pt::FunctionTy::Receive,
+                Some("modifier") => pt::FunctionTy::Modifier,
+                // any other comment, e.g. reaching definitions, is ignored
+                _ => return Ok(()),
+            };
+
+            if let Some(name) = words.next() {
+                self.cfg.ty = ty;
+                self.cfg.name = name.to_owned();
+
+                for word in words {
+                    match word {
+                        "public:true" => self.cfg.public = true,
+                        "nonpayable:true" => self.cfg.nonpayable = true,
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse an instruction. The lines with the cases of a switch are added to the switch
+    /// instruction, so there is no instruction to return.
+    fn instr(&mut self, tokens: &mut Tokens) -> Result<Option<Instr>, String> {
+        let Some(token) = tokens.next() else {
+            return Ok(None);
+        };
+
+        let instr = match token {
+            Token::Ident(ident) => match ident.as_str() {
+                "ty" => {
+                    tokens.expect(":")?;
+                    tokens.ty()?;
+                    let res = self.var_no(&tokens.var()?)?;
+                    tokens.expect("=")?;
+
+                    Instr::Set {
+                        loc: pt::Loc::Codegen,
+                        res,
+                        expr: self.expression(tokens)?,
+                    }
+                }
+                "branch" => Instr::Branch {
+                    block: tokens.block()?,
+                },
+                "branchcond" => {
+                    let cond = self.expression(tokens)?;
+                    tokens.expect(",")?;
+                    let true_block = tokens.block()?;
+                    tokens.expect(",")?;
+                    let false_block = tokens.block()?;
+
+                    Instr::BranchCond {
+                        cond,
+                        true_block,
+                        false_block,
+                    }
+                }
+                "return" => {
+                    let mut value = Vec::new();
+
+                    if tokens.peek().is_some() {
+                        value.push(self.expression(tokens)?);
+
+                        while tokens.eat(",") {
+                            value.push(self.expression(tokens)?);
+                        }
+                    }
+
+                    Instr::Return { value }
+                }
+                "store" => {
+                    let storage = self.storage_slot(tokens)?;
+                    tokens.expect("ty")?;
+                    tokens.expect(":")?;
+                    let ty = tokens.ty()?;
+                    tokens.expect("=")?;
+
+                    Instr::SetStorage {
+                        ty,
+                        value: self.expression(tokens)?,
+                        storage,
+                    }
+                }
+                "clear" => {
+                    let storage = self.storage_slot(tokens)?;
+                    tokens.expect("ty")?;
+                    tokens.expect(":")?;
+
+                    Instr::ClearStorage {
+                        ty: tokens.ty()?,
+                        storage,
+                    }
+                }
+                "switch" => {
+                    let cond = self.expression(tokens)?;
+                    tokens.expect(":")?;
+
+                    Instr::Switch {
+                        cond,
+                        cases: Vec::new(),
+                        default: usize::MAX,
+                    }
+                }
+                "case" => {
+                    let value = self.expression(tokens)?;
+                    tokens.expect(":")?;
+                    let block = tokens.goto_block()?;
+
+                    self.switch()?.push((value, block));
+
+                    return Ok(None);
+                }
+                "default" => {
+                    tokens.expect(":")?;
+                    let block = tokens.goto_block()?;
+
+                    match self.cfg.blocks.last_mut().and_then(|b| b.instr.last_mut()) {
+                        Some(Instr::Switch { default, .. }) => *default = block,
+                        _ => return Err("default outside of a switch".into()),
+                    }
+
+                    return Ok(None);
+                }
+                "print" => Instr::Print {
+                    expr: self.expression(tokens)?,
+                },
+                "assert" => {
+                    tokens.expect("-")?;
+                    tokens.expect("failure")?;
+
+                    Instr::AssertFailure {
+                        encoded_args: None,
+                        error_code: None,
+                    }
+                }
+                "unimplemented" => Instr::Unimplemented { reachable: true },
+                "nop" => Instr::Nop,
+                _ => return Err(format!("unsupported instruction '{ident}'")),
+            },
+            Token::Var(name) => {
+                let res = self.var_no(&name)?;
+                tokens.expect("=")?;
+                tokens.expect("load")?;
+                let storage = self.storage_slot(tokens)?;
+                tokens.expect("ty")?;
+                tokens.expect(":")?;
+
+                Instr::LoadStorage {
+                    res,
+                    ty: tokens.ty()?,
+                    storage,
+                }
+            }
+            token => return Err(format!("unexpected '{token}'")),
+        };
+
+        Ok(Some(instr))
+    }
+
+    /// Parse `storage slot(expr)`
+    fn storage_slot(&self, tokens: &mut Tokens) -> Result<Expression, String> {
+        tokens.expect("storage")?;
+        tokens.expect("slot")?;
+        tokens.expect("(")?;
+        let storage = self.expression(tokens)?;
+        tokens.expect(")")?;
+
+        Ok(storage)
+    }
+
+    fn switch(&mut self) -> Result<&mut Vec<(Expression, usize)>, String> {
+        match self.cfg.blocks.last_mut().and_then(|b| b.instr.last_mut()) {
+            Some(Instr::Switch { cases, .. }) => Ok(cases),
+            _ => Err("case outside of a switch".into()),
+        }
+    }
+
+    fn var_no(&self, name: &str) -> Result<usize, String> {
+        self.names
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("variable %{name} is never set, so its type is unknown"))
+    }
+
+    fn expression(&self, tokens: &mut Tokens) -> Result<Expression, String> {
+        let loc = pt::Loc::Codegen;
+
+        match tokens.next() {
+            Some(Token::Punct("(")) => {
+                let expr = self.parenthesized(tokens)?;
+                tokens.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(ident)) if ident == "true" || ident == "false" => {
+                Ok(Expression::BoolLiteral {
+                    loc,
+                    value: ident == "true",
+                })
+            }
+            Some(Token::Var(name)) => {
+                let var_no = self.var_no(&name)?;
+
+                Ok(Expression::Variable {
+                    loc,
+                    ty: self.vartab.vars[&var_no].ty.clone(),
+                    var_no,
+                })
+            }
+            Some(Token::Punct("!")) => Ok(Expression::Not {
+                loc,
+                expr: self.expression(tokens)?.into(),
+            }),
+            Some(Token::Punct("~")) => {
+                let expr = self.expression(tokens)?;
+
+                Ok(Expression::BitwiseNot {
+                    loc,
+                    ty: expr.ty(),
+                    expr: expr.into(),
+                })
+            }
+            Some(Token::Punct("-")) => {
+                let expr = self.expression(tokens)?;
+
+                Ok(Expression::Negate {
+                    loc,
+                    ty: expr.ty(),
+                    overflowing: false,
+                    expr: expr.into(),
+                })
+            }
+            Some(Token::Hex(value)) => Ok(Expression::BytesLiteral {
+                loc,
+                ty: Type::DynamicBytes,
+                value,
+            }),
+            Some(Token::Ident(_)) => {
+                tokens.pos -= 1;
+
+                let ty = tokens.ty()?;
+
+                if tokens.eat("(") {
+                    let expr = self.expression(tokens)?;
+                    tokens.expect(")")?;
+
+                    return Ok(Expression::Cast {
+                        loc,
+                        ty,
+                        expr: expr.into(),
+                    });
+                }
+
+                let negative = tokens.eat("-");
+
+                let value = tokens.number()?;
+
+                Ok(Expression::NumberLiteral {
+                    loc,
+                    ty,
+                    value: if negative { -value } else { value },
+                })
+            }
+            Some(token) => Err(format!("unexpected '{token}'")),
+            None => Err("expression expected".into()),
+        }
+    }
+
+    /// Parse an expression in parentheses, without the parentheses
+    fn parenthesized(&self, tokens: &mut Tokens) -> Result<Expression, String> {
+        let loc = pt::Loc::Codegen;
+
+        if tokens.eat("arg") {
+            tokens.expect("#")?;
+            let arg_no = tokens.usize()?;
+
+            let Some(param) = self.params.get(arg_no) else {
+                return Err(format!(
+                    "type of argument #{arg_no} is unknown, add it to '# params:'"
+                ));
+            };
+
+            return Ok(Expression::FunctionArg {
+                loc,
+                ty: param.ty.clone(),
+                arg_no,
+            });
+        }
+
+        for (kind, ext) in ["zext", "sext", "trunc"].into_iter().enumerate() {
+            if tokens.eat(ext) {
+                let ty = tokens.ty()?;
+                let expr = self.expression(tokens)?.into();
+
+                return Ok(match kind {
+                    0 => Expression::ZeroExt { loc, ty, expr },
+                    1 => Expression::SignExt { loc, ty, expr },
+                    _ => Expression::Trunc { loc, ty, expr },
+                });
+            }
+        }
+
+        let signed = if tokens.eat("signed") {
+            Some(true)
+        } else if tokens.eat("unsigned") {
+            Some(false)
+        } else {
+            None
+        };
+
+        if let Some(signed) = signed {
+            let kind = ["divide", "modulo", "more", "less"]
+                .into_iter()
+                .find(|kind| tokens.eat(kind));
+
+            let left = Box::new(self.expression(tokens)?);
+
+            let op = match kind {
+                Some("divide") => "/",
+                Some("modulo") => "%",
+                Some("more") => ">",
+                Some("less") => "<",
+                _ if tokens.peek() == Some(&Token::Punct(">=")) => ">=",
+                _ => "<=",
+            };
+
+            tokens.expect(op)?;
+
+            let right = Box::new(self.expression(tokens)?);
+            let ty = left.ty();
+
+            return Ok(match (op, signed) {
+                ("/", true) => Expression::SignedDivide {
+                    loc,
+                    ty,
+                    left,
+                    right,
+                },
+                ("/", false) => Expression::UnsignedDivide {
+                    loc,
+                    ty,
+                    left,
+                    right,
+                },
+                ("%", true) => Expression::SignedModulo {
+                    loc,
+                    ty,
+                    left,
+                    right,
+                },
+                ("%", false) => Expression::UnsignedModulo {
+                    loc,
+                    ty,
+                    left,
+                    right,
+                },
+                (">", _) => Expression::More {
+                    loc,
+                    signed,
+                    left,
+                    right,
+                },
+                ("<", _) => Expression::Less {
+                    loc,
+                    signed,
+                    left,
+                    right,
+                },
+                (">=", _) => Expression::MoreEqual {
+                    loc,
+                    signed,
+                    left,
+                    right,
+                },
+                _ => Expression::LessEqual {
+                    loc,
+                    signed,
+                    left,
+                    right,
+                },
+            });
+        }
+
+        let overflowing = tokens.eat("overflowing");
+
+        let left = Box::new(self.expression(tokens)?);
+
+        let op = match tokens.next() {
+            Some(Token::Punct(op)) => op,
+            Some(token) => return Err(format!("unexpected '{token}'")),
+            None => return Err("operator expected".into()),
+        };
+
+        let right = Box::new(self.expression(tokens)?);
+        let ty = left.ty();
+
+        Ok(match op {
+            "+" => Expression::Add {
+                loc,
+                ty,
+                overflowing,
+                left,
+                right,
+            },
+            "-" => Expression::Subtract {
+                loc,
+                ty,
+                overflowing,
+                left,
+                right,
+            },
+            "*" => Expression::Multiply {
+                loc,
+                ty,
+                overflowing,
+                left,
+                right,
+            },
+            "**" => Expression::Power {
+                loc,
+                ty,
+                overflowing,
+                base: left,
+                exp: right,
+            },
+            "|" => Expression::BitwiseOr {
+                loc,
+                ty,
+                left,
+                right,
+            },
+            "&" => Expression::BitwiseAnd {
+                loc,
+                ty,
+                left,
+                right,
+            },
+            "^" => Expression::BitwiseXor {
+                loc,
+                ty,
+                left,
+                right,
+            },
+            "<<" => Expression::ShiftLeft {
+                loc,
+                ty,
+                left,
+                right,
+            },
+            ">>" => Expression::ShiftRight {
+                loc,
+                signed: matches!(ty, Type::Int(_)),
+                ty,
+                left,
+                right,
+            },
+            "==" => Expression::Equal { loc, left, right },
+            "!=" => Expression::NotEqual { loc, left, right },
+            _ => return Err(format!("unsupported operator '{op}'")),
+        })
+    }
+
+    fn finish(mut self, ns: &mut Namespace) -> Result<ControlFlowGraph, String> {
+        if self.cfg.blocks.is_empty() {
+            return Err("no blocks found".into());
+        }
+
+        for (block_no, names) in std::mem::take(&mut self.phis) {
+            let phis = names
+                .iter()
+                .map(|name| self.var_no(name))
+                .collect::<Result<BTreeSet<usize>, String>>()?;
+
+            let Some(block) = self.cfg.blocks.get_mut(block_no) else {
+                return Err("phis outside of a block".into());
+            };
+
+            block.phis = Some(phis);
+        }
+
+        // Whether the blocks are well formed is left to verify_cfg
+        for (block_no, block) in self.cfg.blocks.iter_mut().enumerate() {
+            if block.instr.iter().any(|instr| {
+                matches!(
+                    instr,
+                    Instr::Switch {
+                        default: usize::MAX,
+                        ..
+                    }
+                )
+            }) {
+                return Err(format!("switch in block{block_no} has no default"));
+            }
+
+            if let Some(Instr::Unimplemented { reachable }) = block.instr.last_mut() {
+                *reachable = false;
+            }
+        }
+
+        let mut cfg = self.cfg;
+
+        self.vartab.finalize(ns, &mut cfg);
+
+        Ok(cfg)
+    }
+}
+
+/// Parse the parameters on a `# params:` or `# returns:` line
+fn parameters(list: &str) -> Result<Vec<Parameter>, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let mut tokens = Tokens::new(param)?;
+            let ty = tokens.ty()?;
+
+            let mut param = Parameter::new_default(ty);
+
+            if let Some(Token::Ident(name)) = tokens.next() {
+                param.id = Some(pt::Identifier {
+                    name,
+                    loc: pt::Loc::Codegen,
+                });
+            }
+
+            Ok(param)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    /// A variable, like `%a`
+    Var(String),
+    /// A bytes literal, like `hex"0102"`
+    Hex(Vec<u8>),
+    Punct(&'static str),
+}
+
+impl Token {
+    fn is_ident(&self, s: &str) -> bool {
+        matches!(self, Token::Ident(ident) if ident == s)
+    }
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(s) | Token::Number(s) => write!(f, "{s}"),
+            Token::Var(s) => write!(f, "%{s}"),
+            Token::Hex(v) => write!(f, "hex\"{}\"", hex::encode(v)),
+            Token::Punct(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+const PUNCTUATION: [&str; 26] = [
+    "**", "<<", ">>", "==", "!=", ">=", "<=", "(", ")", "[", "]", ",", ":", "#", "+", "-", "*",
+    "/", "%", "|", "&", "^", "<", ">", "!", "~",
+];
+
+struct Tokens {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Tokens {
+    fn new(line: &str) -> Result<Self, String> {
+        let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$';
+
+        let mut tokens = Vec::new();
+        let mut rest = line;
+
+        while let Some(c) = rest.chars().next() {
+            if c.is_whitespace() {
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+
+            if let Some(hex) = rest.strip_prefix("hex\"") {
+                let Some((digits, tail)) = hex.split_once('"') else {
+                    return Err("unterminated hex literal".into());
+                };
+
+                tokens.push(Token::Hex(hex::decode(digits).map_err(|e| e.to_string())?));
+                rest = tail;
+            } else if c == '%' && rest[1..].starts_with(is_ident) {
+                let len = rest[1..].find(|c| !is_ident(c)).unwrap_or(rest.len() - 1);
+
+                tokens.push(Token::Var(rest[1..=len].to_owned()));
+                rest = &rest[len + 1..];
+            } else if c.is_ascii_digit() {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .unwrap_or(rest.len());
+
+                tokens.push(Token::Number(rest[..len].to_owned()));
+                rest = &rest[len..];
+            } else if is_ident(c) {
+                let len = rest.find(|c| !is_ident(c)).unwrap_or(rest.len());
+
+                tokens.push(Token::Ident(rest[..len].to_owned()));
+                rest = &rest[len..];
+            } else if c == '=' {
+                // '==' is in the punctuation list, so a single '=' is an assignment
+                let punct = if rest.starts_with("==") { "==" } else { "=" };
+
+                tokens.push(Token::Punct(punct));
+                rest = &rest[punct.len()..];
+            } else if let Some(punct) = PUNCTUATION.iter().find(|p| rest.starts_with(**p)) {
+                tokens.push(Token::Punct(punct));
+                rest = &rest[punct.len()..];
+            } else {
+                return Err(format!("unexpected character '{c}'"));
+            }
+        }
+
+        Ok(Tokens { tokens, pos: 0 })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+
+        if token.is_some() {
+            self.pos += 1;
+        }
+
+        token
+    }
+
+    /// Skip the next token if it is the given punctuation or identifier
+    fn eat(&mut self, s: &str) -> bool {
+        let found = match self.peek() {
+            Some(Token::Punct(p)) => *p == s,
+            Some(Token::Ident(ident)) => ident == s,
+            _ => false,
+        };
+
+        if found {
+            self.pos += 1;
+        }
+
+        found
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), String> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            match self.peek() {
+                Some(token) => Err(format!("expected '{s}', found '{token}'")),
+                None => Err(format!("expected '{s}'")),
+            }
+        }
+    }
+
+    fn var(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Var(name)) => Ok(name),
+            _ => Err("variable expected".into()),
+        }
+    }
+
+    fn number(&mut self) -> Result<BigInt, String> {
+        let value = match self.next() {
+            Some(Token::Number(n)) => match n.strip_prefix("0x") {
+                Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+                None => BigInt::parse_bytes(n.as_bytes(), 10),
+            },
+            _ => None,
+        };
+
+        value.ok_or_else(|| "number expected".into())
+    }
+
+    fn usize(&mut self) -> Result<usize, String> {
+        match self.next() {
+            Some(Token::Number(n)) => n.parse().map_err(|_| format!("invalid number '{n}'")),
+            _ => Err("number expected".into()),
+        }
+    }
+
+    /// Parse `blockN`
+    fn block(&mut self) -> Result<usize, String> {
+        match self.next() {
+            Some(Token::Ident(ident)) => ident
+                .strip_prefix("block")
+                .and_then(|no| no.parse().ok())
+                .ok_or_else(|| format!("block expected, found '{ident}'")),
+            _ => Err("block expected".into()),
+        }
+    }
+
+    /// Parse `goto block #N`, as printed for switch cases
+    fn goto_block(&mut self) -> Result<usize, String> {
+        self.expect("goto")?;
+        self.expect("block")?;
+        self.expect("#")?;
+        self.usize()
+    }
+
+    fn ty(&mut self) -> Result<Type, String> {
+        let Some(Token::Ident(name)) = self.next() else {
+            return Err("type expected".into());
+        };
+
+        let bits = |prefix: &str| {
+            name.strip_prefix(prefix)
+                .and_then(|n| n.parse::<u16>().ok())
+        };
+
+        let ty = match name.as_str() {
+            "bool" => Type::Bool,
+            "string" => Type::String,
+            "bytes" => Type::DynamicBytes,
+            "address" => Type::Address(self.eat("payable")),
+            _ => {
+                if let Some(bits) = bits("uint") {
+                    Type::Uint(bits)
+                } else if let Some(bits) = bits("int") {
+                    Type::Int(bits)
+                } else if let Some(len) = bits("bytes") {
+                    Type::Bytes(len as u8)
+                } else {
+                    return Err(format!("unsupported type '{name}'"));
+                }
+            }
+        };
+
+        let mut dims = Vec::new();
+
+        while self.eat("[") {
+            if self.eat("]") {
+                dims.push(ArrayLength::Dynamic);
+            } else {
+                dims.push(ArrayLength::Fixed(self.number()?));
+                self.expect("]")?;
+            }
+        }
+
+        if dims.is_empty() {
+            Ok(ty)
+        } else {
+            Ok(Type::Array(ty.into(), dims))
+        }
+    }
+}
@@ -103,12 +103,14 @@ pub fn constant_folding(cfg: &mut ControlFlowGraph, dry_run: bool, ns: &mut Name
                 }
                 Instr::AssertFailure {
                     encoded_args: Some(expr),
+                    error_code,
                 } => {
                     let (buf, _) = expression(expr, Some(&vars), cfg, ns);
 
                     if !dry_run {
                         cfg.blocks[block_no].instr[instr_no] = Instr::AssertFailure {
                             encoded_args: Some(buf),
+                            error_code: *error_code,
                         };
                     }
                 }
@@ -651,6 +653,7 @@ fn expression(
 
         Expression::ReturnData { .. }
         | Expression::Undefined { .. }
+        | Expression::PackedStorageSlot { .. }
         | Expression::FormatString { .. }
         | Expression::GetRef { .. }
         | Expression::InternalFunctionCfg { .. } => (expr.clone(), false),
@@ -12,7 +12,7 @@ use crate::Target;
 use num_bigint::{BigInt, Sign};
 use solang_parser::pt::Loc;
 
-use super::encoding::abi_encode;
+use super::encoding::{abi_encode, abi_encode_with_selector};
 
 /// This function encodes the constructor arguments and place an instruction in the CFG to
 /// call the constructor of a contract.
@@ -76,7 +76,7 @@ pub(super) fn call_constructor(
         .accounts
         .map(|expr| expression(expr, cfg, contract_no, func, ns, vartab, opt));
 
-    let mut constructor_args = constructor_args
+    let constructor_args = constructor_args
         .iter()
         .map(|e| expression(e, cfg, callee_contract_no, func, ns, vartab, opt))
         .collect::<Vec<Expression>>();
@@ -91,15 +91,17 @@ pub(super) fn call_constructor(
             .selector(ns, &contract_no),
     };
 
-    let mut args = vec![Expression::BytesLiteral {
-        loc: *loc,
-        ty: Type::FunctionSelector,
-        value: selector,
-    }];
-
-    args.append(&mut constructor_args);
-
-    let (encoded_args, _) = abi_encode(loc, args, ns, vartab, cfg, false);
+    // On EVM, the constructor arguments follow the code of the contract without a selector
+    let (encoded_args, _) = if ns.target == Target::EVM {
+        abi_encode(loc, constructor_args, ns, vartab, cfg, false)
+    } else {
+        let selector = Expression::BytesLiteral {
+            loc: *loc,
+            ty: Type::FunctionSelector,
+            value: selector,
+        };
+        abi_encode_with_selector(loc, selector, constructor_args, ns, vartab, cfg)
+    };
     cfg.add(
         vartab,
         Instr::Constructor {
@@ -697,6 +697,24 @@ fn expression_compare(
                 ExpressionCmp::NotEqual
             }
         }
+        (
+            Expression::PackedStorageSlot {
+                slot: left_slot,
+                offset: left_offset,
+                ..
+            },
+            Expression::PackedStorageSlot {
+                slot: right_slot,
+                offset: right_offset,
+                ..
+            },
+        ) => {
+            if left_slot == right_slot && left_offset == right_offset {
+                ExpressionCmp::Equal
+            } else {
+                ExpressionCmp::NotEqual
+            }
+        }
         (Expression::Keccak256 { exprs: left, .. }, Expression::Keccak256 { exprs: right, .. }) => {
             // This could be written with fold_first() rather than collect(), but that is an unstable feature.
             // Also fold first does not short circuit
@@ -1,10 +1,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{cfg::ControlFlowGraph, Options};
-use crate::{sema::ast::Namespace, Target};
+use super::{
+    cfg::{ASTFunction, ControlFlowGraph, Instr},
+    vartable::Vartable,
+    DispatchStrategy, Expression, Options,
+};
+use crate::{
+    sema::ast::{Namespace, Type},
+    Target,
+};
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use solang_parser::pt::Loc::Codegen;
+use std::{cmp::Reverse, collections::HashSet};
 
 pub(crate) mod polkadot;
-pub(super) mod solana;
+pub(crate) mod solana;
 
 pub(super) fn function_dispatch(
     contract_no: usize,
@@ -19,3 +30,335 @@ pub(super) fn function_dispatch(
         }
     }
 }
+
+/// A function which can be called through the dispatcher
+struct Case {
+    selector: BigInt,
+    block: usize,
+    hot: Option<u32>,
+}
+
+/// The cases of the dispatcher, and how the selector is matched against them
+pub(super) struct Cases {
+    cases: Vec<Case>,
+    strategy: DispatchStrategy,
+}
+
+/// Candidates which are left for the binary search are matched with a switch
+const BINARY_SEARCH_LEAF: usize = 2;
+
+/// How many multipliers are tried for each table size when looking for a perfect hash
+const PERFECT_HASH_ATTEMPTS: usize = 4096;
+
+impl Cases {
+    pub(super) fn new(opt: &Options) -> Self {
+        Cases {
+            cases: Vec::new(),
+            strategy: opt.dispatch,
+        }
+    }
+
+    /// Add the function of `cfg`, whose dispatch code is in `block`
+    pub(super) fn push(
+        &mut self,
+        selector: BigInt,
+        block: usize,
+        cfg: &ControlFlowGraph,
+        ns: &Namespace,
+    ) {
+        let hot = match cfg.function_no {
+            ASTFunction::SolidityFunction(function_no) => {
+                ns.functions[function_no].hot.map(|(_, hot)| hot)
+            }
+            _ => None,
+        };
+
+        self.cases.push(Case {
+            selector,
+            block,
+            hot,
+        });
+    }
+
+    /// Jump to the block of the case which matches the selector, or the default block if none
+    /// do. The selector is of type `ty`. Functions annotated with `@hot` are matched first, the
+    /// most frequently called first, and the rest are matched using the dispatch strategy.
+    pub(super) fn build(
+        self,
+        selector: Expression,
+        ty: &Type,
+        default: usize,
+        cfg: &mut ControlFlowGraph,
+        vartab: &mut Vartable,
+        ns: &Namespace,
+    ) {
+        let (mut hot, mut cold): (Vec<Case>, Vec<Case>) =
+            self.cases.into_iter().partition(|case| case.hot.is_some());
+
+        // the selector is compared more than once, so it should be read only once
+        let selector = match selector {
+            Expression::Variable { .. } => selector,
+            _ if hot.is_empty() && self.strategy == DispatchStrategy::Linear => selector,
+            _ => {
+                let var_no = vartab.temp_name("selector", ty);
+
+                cfg.add(
+                    vartab,
+                    Instr::Set {
+                        loc: Codegen,
+                        res: var_no,
+                        expr: selector,
+                    },
+                );
+
+                Expression::Variable {
+                    loc: Codegen,
+                    ty: ty.clone(),
+                    var_no,
+                }
+            }
+        };
+
+        // sort_by_key is stable, so functions with the same frequency stay in declaration order
+        hot.sort_by_key(|case| Reverse(case.hot));
+
+        for case in hot {
+            let next = cfg.new_basic_block("not_hot".into());
+
+            cfg.add(
+                vartab,
+                Instr::BranchCond {
+                    cond: Expression::Equal {
+                        loc: Codegen,
+                        left: selector.clone().into(),
+                        right: number_literal(ty, case.selector).into(),
+                    },
+                    true_block: case.block,
+                    false_block: next,
+                },
+            );
+
+            cfg.set_basic_block(next);
+        }
+
+        match self.strategy {
+            DispatchStrategy::Linear => switch(&selector, ty, &cold, default, cfg, vartab),
+            DispatchStrategy::BinarySearch => {
+                cold.sort_by(|a, b| a.selector.cmp(&b.selector));
+
+                binary_search(&selector, ty, &cold, default, cfg, vartab);
+            }
+            DispatchStrategy::PerfectHash => {
+                if let Some(hash) = perfect_hash(&cold, ty.bits(ns)) {
+                    hash_table(&selector, ty, &cold, hash, default, cfg, vartab);
+                } else {
+                    // no perfect hash found; this is unlikely unless there are very many functions
+                    cold.sort_by(|a, b| a.selector.cmp(&b.selector));
+
+                    binary_search(&selector, ty, &cold, default, cfg, vartab);
+                }
+            }
+        }
+    }
+}
+
+fn number_literal(ty: &Type, value: BigInt) -> Expression {
+    Expression::NumberLiteral {
+        loc: Codegen,
+        ty: ty.clone(),
+        value,
+    }
+}
+
+/// Match the selector with a single switch
+fn switch(
+    selector: &Expression,
+    ty: &Type,
+    cases: &[Case],
+    default: usize,
+    cfg: &mut ControlFlowGraph,
+    vartab: &mut Vartable,
+) {
+    cfg.add(
+        vartab,
+        Instr::Switch {
+            cond: selector.clone(),
+            cases: cases
+                .iter()
+                .map(|case| (number_literal(ty, case.selector.clone()), case.block))
+                .collect(),
+            default,
+        },
+    );
+}
+
+/// Match the selector with the sorted cases, by comparing it with the middle case and recursing
+/// into the half which may contain it
+fn binary_search(
+    selector: &Expression,
+    ty: &Type,
+    cases: &[Case],
+    default: usize,
+    cfg: &mut ControlFlowGraph,
+    vartab: &mut Vartable,
+) {
+    if cases.len() <= BINARY_SEARCH_LEAF {
+        switch(selector, ty, cases, default, cfg, vartab);
+        return;
+    }
+
+    let mid = cases.len() / 2;
+    let lower = cfg.new_basic_block("selector_lower".into());
+    let upper = cfg.new_basic_block("selector_upper".into());
+
+    cfg.add(
+        vartab,
+        Instr::BranchCond {
+            cond: Expression::Less {
+                loc: Codegen,
+                signed: false,
+                left: selector.clone().into(),
+                right: number_literal(ty, cases[mid].selector.clone()).into(),
+            },
+            true_block: lower,
+            false_block: upper,
+        },
+    );
+
+    cfg.set_basic_block(lower);
+    binary_search(selector, ty, &cases[..mid], default, cfg, vartab);
+
+    cfg.set_basic_block(upper);
+    binary_search(selector, ty, &cases[mid..], default, cfg, vartab);
+}
+
+/// The hash `(selector * multiplier) >> (width - bits)`, where the multiply wraps around at `width`
+/// bits
+struct Hash {
+    multiplier: u64,
+    width: u16,
+    bits: u16,
+}
+
+impl Hash {
+    fn slot(&self, selector: u64) -> u64 {
+        let mask = u64::MAX >> (64 - self.width);
+
+        (selector.wrapping_mul(self.multiplier) & mask) >> (self.width - self.bits)
+    }
+}
+
+/// Find a multiplier for which the hash is different for each case, so that the cases can be
+/// looked up in a table with `2^bits` entries. The smallest table which fits the cases is tried
+/// first, up to a table eight times that size.
+fn perfect_hash(cases: &[Case], width: u16) -> Option<Hash> {
+    if cases.len() < 2 || width > 64 {
+        return None;
+    }
+
+    let selectors = cases
+        .iter()
+        .map(|case| case.selector.to_u64())
+        .collect::<Option<Vec<u64>>>()?;
+
+    let mask = u64::MAX >> (64 - width);
+    let min_bits = (cases.len() - 1).ilog2() as u16 + 1;
+
+    for bits in min_bits..(min_bits + 4).min(width) {
+        // a fixed sequence of odd multipliers, so that the output is reproducible
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+
+        for _ in 0..PERFECT_HASH_ATTEMPTS {
+            let hash = Hash {
+                multiplier: (state & mask) | 1,
+                width,
+                bits,
+            };
+            let mut slots = HashSet::new();
+
+            if selectors
+                .iter()
+                .all(|selector| slots.insert(hash.slot(*selector)))
+            {
+                return Some(hash);
+            }
+
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+        }
+    }
+
+    None
+}
+
+/// Switch on the perfect hash of the selector, and then check that the selector is the one of the
+/// case in the table entry
+fn hash_table(
+    selector: &Expression,
+    ty: &Type,
+    cases: &[Case],
+    hash: Hash,
+    default: usize,
+    cfg: &mut ControlFlowGraph,
+    vartab: &mut Vartable,
+) {
+    let hash_expr = Expression::ShiftRight {
+        loc: Codegen,
+        ty: ty.clone(),
+        left: Expression::Multiply {
+            loc: Codegen,
+            ty: ty.clone(),
+            overflowing: true,
+            left: selector.clone().into(),
+            right: number_literal(ty, hash.multiplier.into()).into(),
+        }
+        .into(),
+        right: number_literal(ty, (hash.width - hash.bits).into()).into(),
+        signed: false,
+    };
+
+    let mut slots = cases
+        .iter()
+        .map(|case| {
+            let slot = hash.slot(case.selector.to_u64().unwrap());
+
+            (slot, case)
+        })
+        .collect::<Vec<_>>();
+
+    slots.sort_by_key(|(slot, _)| *slot);
+
+    let switch_block = cfg.current_block();
+    let mut table = Vec::new();
+
+    for (slot, case) in slots {
+        let block = cfg.new_basic_block(format!("selector_slot_{slot}"));
+
+        cfg.set_basic_block(block);
+        cfg.add(
+            vartab,
+            Instr::BranchCond {
+                cond: Expression::Equal {
+                    loc: Codegen,
+                    left: selector.clone().into(),
+                    right: number_literal(ty, case.selector.clone()).into(),
+                },
+                true_block: case.block,
+                false_block: default,
+            },
+        );
+
+        table.push((number_literal(ty, slot.into()), block));
+    }
+
+    cfg.set_basic_block(switch_block);
+    cfg.add(
+        vartab,
+        Instr::Switch {
+            cond: hash_expr,
+            cases: table,
+            default,
+        },
+    );
+}
@@ -3,12 +3,14 @@
 use crate::{
     codegen::{
         cfg::{ASTFunction, ControlFlowGraph, Instr, InternalCallTy, ReturnCode},
+        dispatch::Cases,
         encoding::{abi_decode, abi_encode},
         revert::log_runtime_error,
         vartable::Vartable,
         Builtin, Expression, Options,
     },
     sema::ast::{Namespace, Parameter, Type, Type::Uint},
+    Target,
 };
 use num_bigint::{BigInt, Sign};
 use solang_parser::pt::{FunctionTy, Loc::Codegen};
@@ -20,7 +22,7 @@ use solang_parser::pt::{FunctionTy, Loc::Codegen};
 /// On Ethereum, constructors do not exist on-chain; they are only executed once.
 /// To cope with that model, we emit different code for the dispatcher,
 /// depending on the exported function:
-/// * On `deploy`, match only on constructor selectors
+/// * On `deploy`, match only on constructor selectors; on EVM, call the constructor directly
 /// * On `call`, match only on selectors of externally callable functions
 pub enum DispatchType {
     Deploy,
@@ -168,10 +170,16 @@ impl<'a> Dispatch<'a> {
             ty: Type::BufferPointer,
             var_no: input_ptr_var,
         };
+        // On EVM, the constructor arguments are not preceded by a selector
+        let selector_len = if ns.target == Target::EVM && ty == FunctionTy::Constructor {
+            0
+        } else {
+            ns.target.selector_length()
+        };
         let selector_len: Box<Expression> = Expression::NumberLiteral {
             loc: Codegen,
             ty: Uint(32),
-            value: ns.target.selector_length().into(),
+            value: selector_len.into(),
         }
         .into();
         let input_ptr = Expression::AdvancePointer {
@@ -196,6 +204,10 @@ impl<'a> Dispatch<'a> {
 
     /// Build the dispatch logic into the returned control flow graph.
     fn build(mut self) -> ControlFlowGraph {
+        if self.ns.target == Target::EVM && self.ty == FunctionTy::Constructor {
+            return self.build_evm_deploy();
+        }
+
         // Go to fallback or receive if there is no selector in the call input
         let cond = Expression::Less {
             loc: Codegen,
@@ -217,24 +229,14 @@ impl<'a> Dispatch<'a> {
 
         // Build all cases
         let selector_ty = Uint(8 * self.ns.target.selector_length() as u16);
-        let cases = self
-            .all_cfg
-            .iter()
-            .enumerate()
-            .filter_map(|(func_no, func_cfg)| {
-                if func_cfg.ty == self.ty && func_cfg.public {
-                    let selector = BigInt::from_bytes_le(Sign::Plus, &func_cfg.selector);
-                    let case = Expression::NumberLiteral {
-                        loc: Codegen,
-                        ty: selector_ty.clone(),
-                        value: selector,
-                    };
-                    Some((case, self.dispatch_case(func_no)))
-                } else {
-                    None
-                }
-            })
-            .collect();
+        let mut cases = Cases::new(self.opt);
+        for (func_no, func_cfg) in self.all_cfg.iter().enumerate() {
+            if func_cfg.ty == self.ty && func_cfg.public {
+                let selector = BigInt::from_bytes_le(Sign::Plus, &func_cfg.selector);
+                let block = self.dispatch_case(func_no);
+                cases.push(selector, block, func_cfg, self.ns);
+            }
+        }
 
         // Read selector
         self.cfg.set_basic_block(self.start);
@@ -273,11 +275,14 @@ impl<'a> Dispatch<'a> {
             },
             data: selector.clone(),
         });
-        self.add(Instr::Switch {
-            cond: selector,
-            cases,
+        cases.build(
+            selector,
+            &selector_ty,
             default,
-        });
+            &mut self.cfg,
+            &mut self.vartab,
+            self.ns,
+        );
 
         // Handle fallback or receive case
         self.cfg.set_basic_block(default);
@@ -287,6 +292,31 @@ impl<'a> Dispatch<'a> {
         self.cfg
     }
 
+    /// On EVM, a contract has a single constructor, which is called without a selector.
+    /// Build the dispatch logic which calls it directly into the returned control flow graph.
+    fn build_evm_deploy(mut self) -> ControlFlowGraph {
+        self.add(Instr::Branch { block: self.start });
+
+        let constructor = self
+            .all_cfg
+            .iter()
+            .position(|cfg| cfg.ty == FunctionTy::Constructor && cfg.public);
+        match constructor {
+            Some(func_no) => {
+                let block = self.dispatch_case(func_no);
+                self.cfg.set_basic_block(self.start);
+                self.add(Instr::Branch { block });
+            }
+            None => {
+                self.cfg.set_basic_block(self.start);
+                self.selector_invalid();
+            }
+        }
+
+        self.vartab.finalize(self.ns, &mut self.cfg);
+        self.cfg
+    }
+
     /// Insert the dispatch logic for `func_no`. `func_no` may be a function or constructor.
     /// Returns the basic block number in which the dispatch logic was inserted.
     fn dispatch_case(&mut self, func_no: usize) -> usize {
@@ -416,7 +446,10 @@ impl<'a> Dispatch<'a> {
             &mut self.vartab,
             self.ns,
         );
-        self.add(Instr::AssertFailure { encoded_args: None });
+        self.add(Instr::AssertFailure {
+            encoded_args: None,
+            error_code: None,
+        });
 
         self.cfg.set_basic_block(false_block);
     }
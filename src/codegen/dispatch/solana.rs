@@ -2,6 +2,7 @@
 
 use crate::codegen::{
     cfg::{ASTFunction, ControlFlowGraph, Instr, InternalCallTy, ReturnCode},
+    dispatch::Cases,
     solana_deploy::solana_deploy,
     vartable::Vartable,
     Builtin, Expression, Options,
@@ -146,7 +147,7 @@ pub(crate) fn function_dispatch(
         }),
     };
 
-    let mut cases = Vec::new();
+    let mut cases = Cases::new(opt);
 
     for (cfg_no, func_cfg) in all_cfg.iter().enumerate() {
         if !func_cfg.public {
@@ -180,25 +181,23 @@ pub(crate) fn function_dispatch(
             continue;
         };
 
-        cases.push((
-            Expression::NumberLiteral {
-                loc: Loc::Codegen,
-                ty: Type::Uint(64),
-                value: BigInt::from_bytes_le(Sign::Plus, &func_cfg.selector),
-            },
+        cases.push(
+            BigInt::from_bytes_le(Sign::Plus, &func_cfg.selector),
             entry,
-        ));
+            func_cfg,
+            ns,
+        );
     }
 
     cfg.set_basic_block(switch_block);
 
-    cfg.add(
+    cases.build(
+        fid,
+        &Type::Uint(64),
+        no_function_matched,
+        &mut cfg,
         &mut vartab,
-        Instr::Switch {
-            cond: fid,
-            cases,
-            default: no_function_matched,
-        },
+        ns,
     );
 
     cfg.set_basic_block(no_function_matched);
@@ -83,6 +83,11 @@ impl BufferValidator<'_> {
         self.build_out_of_bounds_fail_branch(offset, ns, vartab, cfg);
     }
 
+    /// The length of the buffer being validated
+    pub(super) fn buffer_length(&self) -> &Expression {
+        &self.buffer_length
+    }
+
     /// Checks if a buffer validation is necessary
     pub(super) fn validation_necessary(&self) -> bool {
         self.verified_until.is_none() || self.current_arg > self.verified_until.unwrap()
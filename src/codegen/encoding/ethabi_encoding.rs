@@ -0,0 +1,1481 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::codegen::cfg::{ControlFlowGraph, Instr};
+use crate::codegen::encoding::{
+    allocate_array, array_outer_length, finish_array_loop, index_array, load_struct_member,
+    set_array_loop, AbiEncoding,
+};
+use crate::codegen::expression::load_storage;
+use crate::codegen::revert::{assert_failure, PanicCode, SolidityError};
+use crate::codegen::vartable::Vartable;
+use crate::codegen::{Builtin, Expression};
+use crate::sema::ast::{ArrayLength, Namespace, RetrieveType, StructType, Type, Type::Uint};
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+use solang_parser::pt::Loc::Codegen;
+use std::collections::HashMap;
+
+use super::buffer_validator::BufferValidator;
+
+/// The Ethereum ABI encoding, which is what contracts compiled with solc expect.
+///
+/// Every value is encoded in one or more 32 byte words. The items of a tuple, like the arguments
+/// of a function or the fields of a struct, are encoded as a head followed by a tail: static items
+/// are encoded in place in the head, whereas the head of a dynamic item is the offset of its
+/// encoding in the tail, relative to the start of the tuple.
+/// More information can found in the [Solidity documentation](https://docs.soliditylang.org/en/latest/abi-spec.html).
+pub(super) struct EthAbiEncoding {
+    storage_cache: HashMap<usize, Expression>,
+    packed_encoder: bool,
+}
+
+impl EthAbiEncoding {
+    pub fn new(packed: bool) -> Self {
+        Self {
+            storage_cache: HashMap::new(),
+            packed_encoder: packed,
+        }
+    }
+
+    /// Calculate the size of the `items` encoded as a tuple. The items are the arguments
+    /// themselves if `arg_no` is `None`, or fields of the argument `arg_no` otherwise.
+    pub(super) fn tuple_size(
+        &mut self,
+        items: &[Expression],
+        arg_no: Option<usize>,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let mut size = uint32(0);
+        for (item_no, item) in items.iter().enumerate() {
+            // Storage arguments are loaded here, even if they are static
+            let item_size = self.get_expr_size(arg_no.unwrap_or(item_no), item, ns, vartab, cfg);
+            size = if item.ty().is_dynamic(ns) {
+                size.add_u32(uint32(32)).add_u32(item_size)
+            } else {
+                size.add_u32(item_size)
+            };
+        }
+        size
+    }
+
+    /// Encode the `items` as a tuple into `buffer` at `offset`. The items are the arguments
+    /// themselves if `arg_no` is `None`, or fields of the argument `arg_no` otherwise.
+    /// Returns the encoded size.
+    pub(super) fn encode_tuple(
+        &mut self,
+        items: &[Expression],
+        arg_no: Option<usize>,
+        buffer: &Expression,
+        offset: &Expression,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let heads_size: BigInt = items.iter().map(|item| head_size(&item.ty(), ns)).sum();
+
+        if !items.iter().any(|item| item.ty().is_dynamic(ns)) {
+            let mut head = BigInt::zero();
+            for (item_no, item) in items.iter().enumerate() {
+                let head_offset = offset.clone().add_u32(uint32(head.clone()));
+                let arg_no = arg_no.unwrap_or(item_no);
+                self.encode(item, buffer, &head_offset, arg_no, ns, vartab, cfg);
+                head += head_size(&item.ty(), ns);
+            }
+            return uint32(heads_size);
+        }
+
+        let tail_var = vartab.temp_name("tail", &Uint(32));
+        cfg.add(
+            vartab,
+            Instr::Set {
+                loc: Codegen,
+                res: tail_var,
+                expr: uint32(heads_size),
+            },
+        );
+        let tail = Expression::Variable {
+            loc: Codegen,
+            ty: Uint(32),
+            var_no: tail_var,
+        };
+
+        let mut head = BigInt::zero();
+        for (item_no, item) in items.iter().enumerate() {
+            let head_offset = offset.clone().add_u32(uint32(head.clone()));
+            let arg_no = arg_no.unwrap_or(item_no);
+            if item.ty().is_dynamic(ns) {
+                self.encode_directly(
+                    &word(&tail, ns),
+                    buffer,
+                    &head_offset,
+                    vartab,
+                    cfg,
+                    32.into(),
+                );
+                let tail_offset = offset.clone().add_u32(tail.clone());
+                let size = self.encode(item, buffer, &tail_offset, arg_no, ns, vartab, cfg);
+                cfg.add(
+                    vartab,
+                    Instr::Set {
+                        loc: Codegen,
+                        res: tail_var,
+                        expr: tail.clone().add_u32(size),
+                    },
+                );
+            } else {
+                self.encode(item, buffer, &head_offset, arg_no, ns, vartab, cfg);
+            }
+            head += head_size(&item.ty(), ns);
+        }
+
+        tail
+    }
+
+    /// Decode a tuple of the given `types` from `buffer` at `offset`.
+    /// Returns the decoded values.
+    pub(super) fn decode_tuple(
+        &self,
+        buffer: &Expression,
+        offset: &Expression,
+        types: &[Type],
+        validator: &mut BufferValidator,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Vec<Expression> {
+        let mut values = Vec::with_capacity(types.len());
+        let mut head = BigInt::zero();
+
+        for ty in types {
+            let head_offset = offset.clone().add_u32(uint32(head.clone()));
+            let item_offset = if ty.is_dynamic(ns) {
+                let tail = self.read_length(buffer, &head_offset, validator, ns, vartab, cfg);
+                offset.clone().add_u32(tail)
+            } else {
+                head_offset
+            };
+            let (value, _) =
+                self.read_from_buffer(buffer, &item_offset, ty, validator, ns, vartab, cfg);
+            values.push(value);
+            head += head_size(ty, ns);
+        }
+
+        values
+    }
+
+    /// Read the 32 byte word at `offset` as a 256 bit unsigned integer.
+    fn read_word(
+        &self,
+        buffer: &Expression,
+        offset: &Expression,
+        validator: &mut BufferValidator,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        validator.validate_offset_plus_size(offset, &uint32(32), ns, vartab, cfg);
+
+        let word_var = vartab.temp_anonymous(&Uint(256));
+        cfg.add(
+            vartab,
+            Instr::Set {
+                loc: Codegen,
+                res: word_var,
+                expr: Expression::Cast {
+                    loc: Codegen,
+                    ty: Uint(256),
+                    expr: Expression::Builtin {
+                        loc: Codegen,
+                        tys: vec![Type::Bytes(32)],
+                        kind: Builtin::ReadFromBuffer,
+                        args: vec![buffer.clone(), offset.clone()],
+                    }
+                    .into(),
+                },
+            },
+        );
+
+        Expression::Variable {
+            loc: Codegen,
+            ty: Uint(256),
+            var_no: word_var,
+        }
+    }
+
+    /// Read an offset or a length at `offset`. Either cannot be larger than the buffer,
+    /// which also guarantees that it fits into 32 bits. Returns the value as a 32 bit integer.
+    fn read_length(
+        &self,
+        buffer: &Expression,
+        offset: &Expression,
+        validator: &mut BufferValidator,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let word = self.read_word(buffer, offset, validator, ns, vartab, cfg);
+        let in_bounds = Expression::LessEqual {
+            loc: Codegen,
+            signed: false,
+            left: word.clone().into(),
+            right: Expression::ZeroExt {
+                loc: Codegen,
+                ty: Uint(256),
+                expr: validator.buffer_length().clone().into(),
+            }
+            .into(),
+        };
+        assert_valid(in_bounds, ns, vartab, cfg);
+
+        let length_var = vartab.temp_anonymous(&Uint(32));
+        cfg.add(
+            vartab,
+            Instr::Set {
+                loc: Codegen,
+                res: length_var,
+                expr: Expression::Trunc {
+                    loc: Codegen,
+                    ty: Uint(32),
+                    expr: word.into(),
+                },
+            },
+        );
+
+        Expression::Variable {
+            loc: Codegen,
+            ty: Uint(32),
+            var_no: length_var,
+        }
+    }
+
+    /// Decode a value type from the word at `offset`. The word must be a valid encoding of
+    /// the type, i.e. any bits which are not part of the value must be zero (or the sign).
+    fn decode_value(
+        &self,
+        buffer: &Expression,
+        offset: &Expression,
+        ty: &Type,
+        validator: &mut BufferValidator,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let word = self.read_word(buffer, offset, validator, ns, vartab, cfg);
+
+        let value = match ty {
+            Type::Uint(256) => word,
+            Type::Int(256) => Expression::Cast {
+                loc: Codegen,
+                ty: ty.clone(),
+                expr: word.into(),
+            },
+            Type::Int(_) => {
+                let value = Expression::Trunc {
+                    loc: Codegen,
+                    ty: ty.clone(),
+                    expr: word.clone().into(),
+                };
+                let sign_extended = Expression::Equal {
+                    loc: Codegen,
+                    left: Expression::SignExt {
+                        loc: Codegen,
+                        ty: Type::Int(256),
+                        expr: value.clone().into(),
+                    }
+                    .into(),
+                    right: Expression::Cast {
+                        loc: Codegen,
+                        ty: Type::Int(256),
+                        expr: word.into(),
+                    }
+                    .into(),
+                };
+                assert_valid(sign_extended, ns, vartab, cfg);
+                value
+            }
+            Type::Bytes(length) => {
+                if *length < 32 {
+                    let padding = Expression::BitwiseAnd {
+                        loc: Codegen,
+                        ty: Uint(256),
+                        left: word.into(),
+                        right: uint256(low_bits(256 - *length as u16 * 8)).into(),
+                    };
+                    let no_padding = Expression::Equal {
+                        loc: Codegen,
+                        left: padding.into(),
+                        right: uint256(0).into(),
+                    };
+                    assert_valid(no_padding, ns, vartab, cfg);
+                }
+                // The bytes are left aligned, which is how they are read from a buffer
+                Expression::Builtin {
+                    loc: Codegen,
+                    tys: vec![ty.clone()],
+                    kind: Builtin::ReadFromBuffer,
+                    args: vec![buffer.clone(), offset.clone()],
+                }
+            }
+            _ => {
+                let max = match ty {
+                    Type::Bool => BigInt::one(),
+                    Type::Enum(enum_no) => BigInt::from(ns.enums[*enum_no].values.len() - 1),
+                    Type::Address(_) | Type::Contract(_) => low_bits(ns.address_length as u16 * 8),
+                    Type::Value => low_bits(ns.value_length as u16 * 8),
+                    _ => low_bits(ty.bits(ns)),
+                };
+                let in_range = Expression::LessEqual {
+                    loc: Codegen,
+                    signed: false,
+                    left: word.clone().into(),
+                    right: uint256(max).into(),
+                };
+                assert_valid(in_range, ns, vartab, cfg);
+
+                match ty {
+                    Type::Bool => Expression::NotEqual {
+                        loc: Codegen,
+                        left: word.into(),
+                        right: uint256(0).into(),
+                    },
+                    Type::Address(_) | Type::Contract(_) => Expression::Cast {
+                        loc: Codegen,
+                        ty: ty.clone(),
+                        expr: Expression::Trunc {
+                            loc: Codegen,
+                            ty: Uint(ns.address_length as u16 * 8),
+                            expr: word.into(),
+                        }
+                        .into(),
+                    },
+                    _ => Expression::Trunc {
+                        loc: Codegen,
+                        ty: ty.clone(),
+                        expr: word.into(),
+                    },
+                }
+            }
+        };
+
+        let value_var = vartab.temp_anonymous(ty);
+        cfg.add(
+            vartab,
+            Instr::Set {
+                loc: Codegen,
+                res: value_var,
+                expr: value,
+            },
+        );
+
+        Expression::Variable {
+            loc: Codegen,
+            ty: ty.clone(),
+            var_no: value_var,
+        }
+    }
+}
+
+impl AbiEncoding for EthAbiEncoding {
+    fn size_width(
+        &self,
+        _size: &Expression,
+        _vartab: &mut Vartable,
+        _cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        uint32(32)
+    }
+
+    fn encode(
+        &mut self,
+        expr: &Expression,
+        buffer: &Expression,
+        offset: &Expression,
+        arg_no: usize,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let expr_ty = &expr.ty().unwrap_user_type(ns);
+        match expr_ty {
+            Type::Uint(width) | Type::Int(width) => {
+                self.encode_int(expr, buffer, offset, ns, vartab, cfg, *width)
+            }
+            Type::Value => {
+                let width = ns.value_length as u16 * 8;
+                self.encode_int(expr, buffer, offset, ns, vartab, cfg, width)
+            }
+            Type::Contract(_) | Type::Address(_) | Type::Bool | Type::Enum(_) => {
+                if self.is_packed() {
+                    let size = expr_ty.memory_size_of(ns);
+                    self.encode_directly(expr, buffer, offset, vartab, cfg, size)
+                } else {
+                    self.encode_directly(&word(expr, ns), buffer, offset, vartab, cfg, 32.into())
+                }
+            }
+            Type::Bytes(_) | Type::FunctionSelector => {
+                // Bytes are left aligned, so they are written as they are
+                let size = if self.is_packed() {
+                    expr_ty.bytes(ns).into()
+                } else {
+                    32.into()
+                };
+                self.encode_directly(expr, buffer, offset, vartab, cfg, size)
+            }
+            Type::String | Type::DynamicBytes | Type::Slice(_) => {
+                self.encode_bytes(expr, buffer, offset, ns, vartab, cfg)
+            }
+            Type::Struct(ty) => {
+                self.encode_struct(expr, buffer, offset.clone(), ty, arg_no, ns, vartab, cfg)
+            }
+            Type::Array(ty, dims) => self.encode_array(
+                expr, expr_ty, ty, dims, arg_no, buffer, offset, ns, vartab, cfg,
+            ),
+            Type::ExternalFunction { .. } => {
+                self.encode_external_function(expr, buffer, offset, ns, vartab, cfg)
+            }
+            Type::Ref(r) => {
+                if let Type::Struct(ty) = &**r {
+                    // Structs references should not be dereferenced
+                    return self.encode_struct(
+                        expr,
+                        buffer,
+                        offset.clone(),
+                        ty,
+                        arg_no,
+                        ns,
+                        vartab,
+                        cfg,
+                    );
+                }
+                let loaded = Expression::Load {
+                    loc: Codegen,
+                    ty: *r.clone(),
+                    expr: expr.clone().into(),
+                };
+                self.encode(&loaded, buffer, offset, arg_no, ns, vartab, cfg)
+            }
+            Type::StorageRef(..) => {
+                let loaded = self.storage_cache_remove(arg_no).unwrap();
+                self.encode(&loaded, buffer, offset, arg_no, ns, vartab, cfg)
+            }
+            Type::UserType(_) | Type::Unresolved | Type::Rational | Type::Unreachable => {
+                unreachable!("Type should not exist in codegen")
+            }
+            Type::InternalFunction { .. }
+            | Type::Void
+            | Type::BufferPointer
+            | Type::Mapping(..) => unreachable!("This type cannot be encoded"),
+        }
+    }
+
+    /// Integers are big endian; they are sign extended to a whole word, unless packed.
+    fn encode_int(
+        &mut self,
+        expr: &Expression,
+        buffer: &Expression,
+        offset: &Expression,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+        width: u16,
+    ) -> Expression {
+        if self.is_packed() {
+            let bytes = Expression::Cast {
+                loc: Codegen,
+                ty: Type::Bytes((width / 8) as u8),
+                expr: expr.clone().into(),
+            };
+            self.encode_directly(&bytes, buffer, offset, vartab, cfg, (width / 8).into())
+        } else {
+            self.encode_directly(&word(expr, ns), buffer, offset, vartab, cfg, 32.into())
+        }
+    }
+
+    fn encode_size(
+        &mut self,
+        expr: &Expression,
+        buffer: &Expression,
+        offset: &Expression,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        self.encode_directly(&word(expr, ns), buffer, offset, vartab, cfg, 32.into())
+    }
+
+    /// Bytes and strings are a length word followed by the data, padded to a multiple of 32 bytes.
+    fn encode_bytes(
+        &mut self,
+        expr: &Expression,
+        buffer: &Expression,
+        offset: &Expression,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let len = array_outer_length(expr, vartab, cfg);
+        let data_offset = if self.is_packed() {
+            offset.clone()
+        } else {
+            let size = self.encode_size(&len, buffer, offset, ns, vartab, cfg);
+            offset.clone().add_u32(size)
+        };
+        cfg.add(
+            vartab,
+            Instr::MemCopy {
+                source: expr.clone(),
+                destination: Expression::AdvancePointer {
+                    pointer: buffer.clone().into(),
+                    bytes_offset: data_offset.into(),
+                },
+                bytes: len.clone(),
+            },
+        );
+
+        if self.is_packed() {
+            len
+        } else {
+            uint32(32).add_u32(padded(len))
+        }
+    }
+
+    /// Structs are encoded as a tuple of their fields.
+    fn encode_struct(
+        &mut self,
+        expr: &Expression,
+        buffer: &Expression,
+        offset: Expression,
+        struct_ty: &StructType,
+        arg_no: usize,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let fields = struct_fields(expr, struct_ty, ns);
+        self.encode_tuple(&fields, Some(arg_no), buffer, &offset, ns, vartab, cfg)
+    }
+
+    /// Arrays are encoded as a tuple of their elements. Dynamic arrays are preceded by their
+    /// length. When packed, there is no length and the elements are padded to 32 bytes.
+    fn encode_array(
+        &mut self,
+        array: &Expression,
+        _array_ty: &Type,
+        elem_ty: &Type,
+        dims: &[ArrayLength],
+        arg_no: usize,
+        buffer: &Expression,
+        offset: &Expression,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let (length, elements_offset, length_size) = match dims.last() {
+            Some(ArrayLength::Dynamic) => {
+                let length = array_outer_length(array, vartab, cfg);
+                if self.is_packed() {
+                    (length, offset.clone(), uint32(0))
+                } else {
+                    let size = self.encode_size(&length, buffer, offset, ns, vartab, cfg);
+                    (length, offset.clone().add_u32(size.clone()), size)
+                }
+            }
+            Some(ArrayLength::Fixed(length)) => (uint32(length.clone()), offset.clone(), uint32(0)),
+            _ => unreachable!("array must have a length"),
+        };
+
+        let inner_ty = inner_type(elem_ty, dims);
+        let dynamic_elements = inner_ty.is_dynamic(ns);
+
+        // The elements of a packed array are not packed
+        let mut unpacked;
+        let encoder = if self.is_packed() {
+            unpacked = EthAbiEncoding::new(false);
+            &mut unpacked
+        } else {
+            self
+        };
+
+        let tail_var = dynamic_elements.then(|| {
+            let tail_var = vartab.temp_name("tail", &Uint(32));
+            cfg.add(
+                vartab,
+                Instr::Set {
+                    loc: Codegen,
+                    res: tail_var,
+                    expr: multiply(length.clone(), uint32(32)),
+                },
+            );
+            tail_var
+        });
+
+        let mut indexes = Vec::new();
+        let for_loop = set_array_loop(array, dims, dims.len() - 1, &mut indexes, vartab, cfg);
+        cfg.set_basic_block(for_loop.body_block);
+        let index = Expression::Variable {
+            loc: Codegen,
+            ty: Uint(32),
+            var_no: for_loop.index,
+        };
+        let elem = index_array(array.clone(), dims, &indexes, false);
+
+        if let Some(tail_var) = tail_var {
+            let tail = Expression::Variable {
+                loc: Codegen,
+                ty: Uint(32),
+                var_no: tail_var,
+            };
+            let head_offset = elements_offset.clone().add_u32(multiply(index, uint32(32)));
+            encoder.encode_directly(
+                &word(&tail, ns),
+                buffer,
+                &head_offset,
+                vartab,
+                cfg,
+                32.into(),
+            );
+            let tail_offset = elements_offset.clone().add_u32(tail.clone());
+            let size = encoder.encode(&elem, buffer, &tail_offset, arg_no, ns, vartab, cfg);
+            cfg.add(
+                vartab,
+                Instr::Set {
+                    loc: Codegen,
+                    res: tail_var,
+                    expr: tail.add_u32(size),
+                },
+            );
+        } else {
+            let elem_size = uint32(static_size(&inner_ty, ns));
+            let elem_offset = elements_offset.add_u32(multiply(index, elem_size));
+            encoder.encode(&elem, buffer, &elem_offset, arg_no, ns, vartab, cfg);
+        }
+
+        finish_array_loop(&for_loop, vartab, cfg);
+
+        let elements_size = match tail_var {
+            Some(tail_var) => Expression::Variable {
+                loc: Codegen,
+                ty: Uint(32),
+                var_no: tail_var,
+            },
+            None => multiply(length, uint32(static_size(&inner_ty, ns))),
+        };
+
+        length_size.add_u32(elements_size)
+    }
+
+    /// An external function is the address followed by the selector, left aligned.
+    fn encode_external_function(
+        &mut self,
+        expr: &Expression,
+        buffer: &Expression,
+        offset: &Expression,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let addr_len = ns.address_length.into();
+        let address = expr.external_function_address();
+        let size = self.encode_directly(&address, buffer, offset, vartab, cfg, addr_len);
+        let offset = offset.clone().add_u32(size);
+        let selector = expr.external_function_selector();
+        self.encode_directly(&selector, buffer, &offset, vartab, cfg, 4.into());
+
+        if self.is_packed() {
+            uint32(ns.address_length + 4)
+        } else {
+            uint32(32)
+        }
+    }
+
+    fn read_from_buffer(
+        &self,
+        buffer: &Expression,
+        offset: &Expression,
+        ty: &Type,
+        validator: &mut BufferValidator,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> (Expression, Expression) {
+        match ty {
+            Type::Uint(_)
+            | Type::Int(_)
+            | Type::Bool
+            | Type::Address(_)
+            | Type::Contract(_)
+            | Type::Enum(_)
+            | Type::Value
+            | Type::Bytes(_) => (
+                self.decode_value(buffer, offset, ty, validator, ns, vartab, cfg),
+                uint32(32),
+            ),
+
+            Type::DynamicBytes | Type::String => {
+                let length = self.read_length(buffer, offset, validator, ns, vartab, cfg);
+                let data_offset = offset.clone().add_u32(uint32(32));
+                validator.validate_offset(
+                    data_offset.clone().add_u32(length.clone()),
+                    ns,
+                    vartab,
+                    cfg,
+                );
+
+                let length_var = match length {
+                    Expression::Variable { var_no, .. } => var_no,
+                    _ => unreachable!("length must be a variable"),
+                };
+                let allocated_array = allocate_array(ty, length_var, vartab, cfg);
+                let array = Expression::Variable {
+                    loc: Codegen,
+                    ty: ty.clone(),
+                    var_no: allocated_array,
+                };
+                cfg.add(
+                    vartab,
+                    Instr::MemCopy {
+                        source: Expression::AdvancePointer {
+                            pointer: buffer.clone().into(),
+                            bytes_offset: data_offset.into(),
+                        },
+                        destination: array.clone(),
+                        bytes: length.clone(),
+                    },
+                );
+
+                (array, uint32(32).add_u32(length))
+            }
+
+            Type::UserType(type_no) => {
+                let usr_type = ns.user_types[*type_no].ty.clone();
+                self.read_from_buffer(buffer, offset, &usr_type, validator, ns, vartab, cfg)
+            }
+
+            Type::ExternalFunction { .. } => {
+                self.decode_external_function(buffer, offset, ty, validator, ns, vartab, cfg)
+            }
+
+            Type::Array(elem_ty, dims) => self.decode_array(
+                buffer, offset, ty, elem_ty, dims, validator, ns, vartab, cfg,
+            ),
+
+            Type::Struct(struct_ty) => self.decode_struct(
+                buffer,
+                offset.clone(),
+                ty,
+                struct_ty,
+                validator,
+                ns,
+                vartab,
+                cfg,
+            ),
+
+            Type::Rational
+            | Type::Ref(_)
+            | Type::StorageRef(..)
+            | Type::BufferPointer
+            | Type::Unresolved
+            | Type::InternalFunction { .. }
+            | Type::Unreachable
+            | Type::Void
+            | Type::Slice(_)
+            | Type::FunctionSelector
+            | Type::Mapping(..) => unreachable!("Type should not appear on an encoded buffer"),
+        }
+    }
+
+    fn retrieve_array_length(
+        &self,
+        buffer: &Expression,
+        offset: &Expression,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> (usize, Expression) {
+        // This does not check whether the length fits into 32 bits; see `read_length()`
+        let length_var = vartab.temp_anonymous(&Uint(32));
+        cfg.add(
+            vartab,
+            Instr::Set {
+                loc: Codegen,
+                res: length_var,
+                expr: Expression::Trunc {
+                    loc: Codegen,
+                    ty: Uint(32),
+                    expr: Expression::Cast {
+                        loc: Codegen,
+                        ty: Uint(256),
+                        expr: Expression::Builtin {
+                            loc: Codegen,
+                            tys: vec![Type::Bytes(32)],
+                            kind: Builtin::ReadFromBuffer,
+                            args: vec![buffer.clone(), offset.clone()],
+                        }
+                        .into(),
+                    }
+                    .into(),
+                },
+            },
+        );
+        (length_var, uint32(32))
+    }
+
+    /// For dynamic arrays, the returned size does not include the tails of dynamic elements.
+    fn decode_array(
+        &self,
+        buffer: &Expression,
+        offset: &Expression,
+        array_ty: &Type,
+        elem_ty: &Type,
+        dims: &[ArrayLength],
+        validator: &mut BufferValidator,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> (Expression, Expression) {
+        let inner_ty = inner_type(elem_ty, dims);
+
+        let (array_var, elements_offset, size) = match dims.last() {
+            Some(ArrayLength::Dynamic) => {
+                let length = self.read_length(buffer, offset, validator, ns, vartab, cfg);
+                let length_var = match &length {
+                    Expression::Variable { var_no, .. } => *var_no,
+                    _ => unreachable!("length must be a variable"),
+                };
+                let size = uint32(32).add_u32(multiply(length, uint32(head_size(&inner_ty, ns))));
+                (
+                    allocate_array(array_ty, length_var, vartab, cfg),
+                    offset.clone().add_u32(uint32(32)),
+                    size,
+                )
+            }
+            Some(ArrayLength::Fixed(_)) => {
+                let array_var = vartab.temp_anonymous(array_ty);
+                cfg.add(
+                    vartab,
+                    Instr::Set {
+                        loc: Codegen,
+                        res: array_var,
+                        expr: Expression::ArrayLiteral {
+                            loc: Codegen,
+                            ty: array_ty.clone(),
+                            dimensions: vec![],
+                            values: vec![],
+                        },
+                    },
+                );
+                let size = uint32(head_size(array_ty, ns));
+                (array_var, offset.clone(), size)
+            }
+            _ => unreachable!("array must have a length"),
+        };
+
+        let array = Expression::Variable {
+            loc: Codegen,
+            ty: array_ty.clone(),
+            var_no: array_var,
+        };
+
+        let mut indexes = Vec::new();
+        let for_loop = set_array_loop(&array, dims, dims.len() - 1, &mut indexes, vartab, cfg);
+        cfg.set_basic_block(for_loop.body_block);
+        let index = Expression::Variable {
+            loc: Codegen,
+            ty: Uint(32),
+            var_no: for_loop.index,
+        };
+
+        let elem_offset = if inner_ty.is_dynamic(ns) {
+            let head_offset = elements_offset.clone().add_u32(multiply(index, uint32(32)));
+            let tail = self.read_length(buffer, &head_offset, validator, ns, vartab, cfg);
+            elements_offset.add_u32(tail)
+        } else {
+            let elem_size = uint32(static_size(&inner_ty, ns));
+            elements_offset.add_u32(multiply(index, elem_size))
+        };
+        let (read_expr, _) =
+            self.read_from_buffer(buffer, &elem_offset, &inner_ty, validator, ns, vartab, cfg);
+        let ptr = index_array(array.clone(), dims, &indexes, true);
+        cfg.add(
+            vartab,
+            Instr::Store {
+                dest: ptr,
+                data: if matches!(read_expr.ty(), Type::Struct(_)) {
+                    // Type::Struct is a pointer to a struct. We need to dereference the
+                    // pointer before storing it at a given array index.
+                    Expression::Load {
+                        loc: Codegen,
+                        ty: read_expr.ty(),
+                        expr: Box::new(read_expr),
+                    }
+                } else {
+                    read_expr
+                },
+            },
+        );
+
+        finish_array_loop(&for_loop, vartab, cfg);
+
+        (array, size)
+    }
+
+    /// For dynamic structs, the returned size does not include the tails of dynamic fields.
+    fn decode_struct(
+        &self,
+        buffer: &Expression,
+        offset: Expression,
+        expr_ty: &Type,
+        struct_ty: &StructType,
+        validator: &mut BufferValidator,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> (Expression, Expression) {
+        let types = struct_ty
+            .definition(ns)
+            .fields
+            .iter()
+            .map(|field| field.ty.clone())
+            .collect::<Vec<Type>>();
+        let values = self.decode_tuple(buffer, &offset, &types, validator, ns, vartab, cfg);
+
+        let allocated_struct = vartab.temp_anonymous(expr_ty);
+        cfg.add(
+            vartab,
+            Instr::Set {
+                loc: Codegen,
+                res: allocated_struct,
+                expr: Expression::StructLiteral {
+                    loc: Codegen,
+                    ty: expr_ty.clone(),
+                    values,
+                },
+            },
+        );
+
+        let size = types.iter().map(|ty| head_size(ty, ns)).sum::<BigInt>();
+        let struct_var = Expression::Variable {
+            loc: Codegen,
+            ty: expr_ty.clone(),
+            var_no: allocated_struct,
+        };
+        (struct_var, uint32(size))
+    }
+
+    fn get_expr_size(
+        &mut self,
+        arg_no: usize,
+        expr: &Expression,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let ty = expr.ty().unwrap_user_type(ns);
+        match &ty {
+            Type::Uint(n) | Type::Int(n) if self.is_packed() => uint32(n / 8),
+            Type::Value if self.is_packed() => uint32(ns.value_length),
+            Type::ExternalFunction { .. } if self.is_packed() => uint32(ns.address_length + 4),
+            Type::Enum(_)
+            | Type::Contract(_)
+            | Type::Bool
+            | Type::Address(_)
+            | Type::Bytes(_)
+            | Type::FunctionSelector
+                if self.is_packed() =>
+            {
+                uint32(ty.bytes(ns))
+            }
+            Type::Uint(_)
+            | Type::Int(_)
+            | Type::Value
+            | Type::ExternalFunction { .. }
+            | Type::Enum(_)
+            | Type::Contract(_)
+            | Type::Bool
+            | Type::Address(_)
+            | Type::Bytes(_)
+            | Type::FunctionSelector => uint32(32),
+            Type::Struct(struct_ty) => {
+                self.calculate_struct_size(arg_no, expr, struct_ty, ns, vartab, cfg)
+            }
+            Type::Array(ty, dims) => {
+                self.calculate_array_size(expr, ty, dims, arg_no, ns, vartab, cfg)
+            }
+            Type::Ref(r) => {
+                if let Type::Struct(struct_ty) = &**r {
+                    return self.calculate_struct_size(arg_no, expr, struct_ty, ns, vartab, cfg);
+                }
+                let loaded = Expression::Load {
+                    loc: Codegen,
+                    ty: *r.clone(),
+                    expr: expr.clone().into(),
+                };
+                self.get_expr_size(arg_no, &loaded, ns, vartab, cfg)
+            }
+            Type::StorageRef(_, r) => {
+                let var = load_storage(&Codegen, r, expr.clone(), cfg, vartab);
+                let size = self.get_expr_size(arg_no, &var, ns, vartab, cfg);
+                self.storage_cache_insert(arg_no, var.clone());
+                size
+            }
+            Type::String | Type::DynamicBytes | Type::Slice(_) => {
+                self.calculate_string_size(expr, vartab, cfg)
+            }
+            Type::InternalFunction { .. }
+            | Type::Void
+            | Type::Unreachable
+            | Type::BufferPointer
+            | Type::Mapping(..) => unreachable!("This type cannot be encoded"),
+            Type::UserType(_) | Type::Unresolved | Type::Rational => {
+                unreachable!("Type should not exist in codegen")
+            }
+        }
+    }
+
+    fn decode_external_function(
+        &self,
+        buffer: &Expression,
+        offset: &Expression,
+        ty: &Type,
+        validator: &mut BufferValidator,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> (Expression, Expression) {
+        let word = self.read_word(buffer, offset, validator, ns, vartab, cfg);
+        let padding = Expression::BitwiseAnd {
+            loc: Codegen,
+            ty: Uint(256),
+            left: word.into(),
+            right: uint256(low_bits(256 - (ns.address_length as u16 + 4) * 8)).into(),
+        };
+        let no_padding = Expression::Equal {
+            loc: Codegen,
+            left: padding.into(),
+            right: uint256(0).into(),
+        };
+        assert_valid(no_padding, ns, vartab, cfg);
+
+        let address = Expression::Builtin {
+            loc: Codegen,
+            tys: vec![Type::Address(false)],
+            kind: Builtin::ReadFromBuffer,
+            args: vec![buffer.clone(), offset.clone()],
+        };
+        let selector = Expression::Builtin {
+            loc: Codegen,
+            tys: vec![Type::FunctionSelector],
+            kind: Builtin::ReadFromBuffer,
+            args: vec![
+                buffer.clone(),
+                offset.clone().add_u32(uint32(ns.address_length)),
+            ],
+        };
+        let ext_func = Expression::StructLiteral {
+            loc: Codegen,
+            ty: Type::Struct(StructType::ExternalFunction),
+            values: vec![selector, address],
+        };
+        (
+            Expression::Cast {
+                loc: Codegen,
+                ty: ty.clone(),
+                expr: ext_func.into(),
+            },
+            uint32(32),
+        )
+    }
+
+    /// The elements of a packed array are padded to 32 bytes.
+    fn calculate_array_size(
+        &mut self,
+        array: &Expression,
+        elem_ty: &Type,
+        dims: &Vec<ArrayLength>,
+        arg_no: usize,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let (length, length_size) = match dims.last() {
+            Some(ArrayLength::Dynamic) => {
+                let length = array_outer_length(array, vartab, cfg);
+                let length_size = if self.is_packed() { 0 } else { 32 };
+                (length, uint32(length_size))
+            }
+            Some(ArrayLength::Fixed(length)) => (uint32(length.clone()), uint32(0)),
+            _ => unreachable!("array must have a length"),
+        };
+
+        let inner_ty = inner_type(elem_ty, dims);
+        if !inner_ty.is_dynamic(ns) {
+            let elements_size = multiply(length, uint32(static_size(&inner_ty, ns)));
+            return length_size.add_u32(elements_size);
+        }
+
+        // Each element has an offset in the head, and its encoding in the tail
+        let size_var = vartab.temp_name("array_size", &Uint(32));
+        cfg.add(
+            vartab,
+            Instr::Set {
+                loc: Codegen,
+                res: size_var,
+                expr: multiply(length, uint32(32)),
+            },
+        );
+        let size = Expression::Variable {
+            loc: Codegen,
+            ty: Uint(32),
+            var_no: size_var,
+        };
+
+        let mut indexes = Vec::new();
+        let for_loop = set_array_loop(array, dims, dims.len() - 1, &mut indexes, vartab, cfg);
+        cfg.set_basic_block(for_loop.body_block);
+        let elem = index_array(array.clone(), dims, &indexes, false);
+        let elem_size = self.get_expr_size(arg_no, &elem, ns, vartab, cfg);
+        cfg.add(
+            vartab,
+            Instr::Set {
+                loc: Codegen,
+                res: size_var,
+                expr: size.clone().add_u32(elem_size),
+            },
+        );
+        finish_array_loop(&for_loop, vartab, cfg);
+
+        length_size.add_u32(size)
+    }
+
+    fn calculate_struct_size(
+        &mut self,
+        arg_no: usize,
+        expr: &Expression,
+        struct_ty: &StructType,
+        ns: &Namespace,
+        vartab: &mut Vartable,
+        cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        if !Type::Struct(*struct_ty).is_dynamic(ns) {
+            return uint32(static_size(&Type::Struct(*struct_ty), ns));
+        }
+
+        let fields = struct_fields(expr, struct_ty, ns);
+        self.tuple_size(&fields, Some(arg_no), ns, vartab, cfg)
+    }
+
+    fn calculate_string_size(
+        &self,
+        expr: &Expression,
+        _vartab: &mut Vartable,
+        _cfg: &mut ControlFlowGraph,
+    ) -> Expression {
+        let length = Expression::Builtin {
+            loc: Codegen,
+            tys: vec![Uint(32)],
+            kind: Builtin::ArrayLength,
+            args: vec![expr.clone()],
+        };
+        if self.is_packed() {
+            length
+        } else {
+            uint32(32).add_u32(padded(length))
+        }
+    }
+
+    fn storage_cache_insert(&mut self, arg_no: usize, expr: Expression) {
+        self.storage_cache.insert(arg_no, expr);
+    }
+
+    fn storage_cache_remove(&mut self, arg_no: usize) -> Option<Expression> {
+        self.storage_cache.remove(&arg_no)
+    }
+
+    fn is_packed(&self) -> bool {
+        self.packed_encoder
+    }
+
+    /// The `args` are encoded as a tuple.
+    ///
+    /// TODO: This is used and tested for error data (Error and Panic) only.
+    fn const_encode(&self, args: &[Expression]) -> Option<Vec<u8>> {
+        if self.is_packed() {
+            return None;
+        }
+
+        let mut head = Vec::new();
+        let mut tail = Vec::new();
+        for arg in args {
+            match arg {
+                Expression::AllocDynamicBytes {
+                    initializer: Some(data),
+                    ty: Type::String | Type::DynamicBytes,
+                    ..
+                } => {
+                    head.extend_from_slice(&const_word(&BigInt::from(
+                        32 * args.len() + tail.len(),
+                    )));
+                    tail.extend_from_slice(&const_word(&BigInt::from(data.len())));
+                    tail.extend_from_slice(data);
+                    tail.resize((tail.len() + 31) & !31, 0);
+                }
+                Expression::NumberLiteral {
+                    ty: Type::Uint(256),
+                    value,
+                    ..
+                } => head.extend_from_slice(&const_word(value)),
+                Expression::NumberLiteral {
+                    ty: Type::Bytes(length),
+                    value,
+                    ..
+                } => {
+                    let mut bytes = const_word(value)[32 - *length as usize..].to_vec();
+                    bytes.resize(32, 0);
+                    head.extend_from_slice(&bytes);
+                }
+                _ => return None,
+            }
+        }
+        head.extend_from_slice(&tail);
+        head.into()
+    }
+}
+
+/// The type of the elements in the outer dimension of an array
+fn inner_type(elem_ty: &Type, dims: &[ArrayLength]) -> Type {
+    if dims.len() == 1 {
+        elem_ty.clone()
+    } else {
+        Type::Array(elem_ty.clone().into(), dims[..dims.len() - 1].to_vec())
+    }
+}
+
+/// Load the fields of a struct, which are encoded as a tuple
+fn struct_fields(expr: &Expression, struct_ty: &StructType, ns: &Namespace) -> Vec<Expression> {
+    struct_ty
+        .definition(ns)
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(member, field)| load_struct_member(field.ty.clone(), expr.clone(), member, ns))
+        .collect()
+}
+
+/// The encoded size of a static type
+fn static_size(ty: &Type, ns: &Namespace) -> BigInt {
+    match ty {
+        Type::Ref(ty) | Type::StorageRef(_, ty) => static_size(ty, ns),
+        Type::Struct(struct_ty) => struct_ty
+            .definition(ns)
+            .fields
+            .iter()
+            .map(|field| static_size(&field.ty, ns))
+            .sum(),
+        Type::Array(elem_ty, dims) => dims.iter().fold(static_size(elem_ty, ns), |size, dim| {
+            size * dim.array_length().expect("array must be static")
+        }),
+        _ => 32.into(),
+    }
+}
+
+/// The size of an item in the head of a tuple
+fn head_size(ty: &Type, ns: &Namespace) -> BigInt {
+    if ty.is_dynamic(ns) {
+        32.into()
+    } else {
+        static_size(ty, ns)
+    }
+}
+
+/// The 32 byte word which encodes a value type. Integers are sign or zero extended, and the
+/// word is stored big endian.
+fn word(expr: &Expression, ns: &Namespace) -> Expression {
+    let ty = expr.ty().unwrap_user_type(ns);
+    let value = if matches!(expr.ty(), Type::UserType(_)) {
+        Expression::Cast {
+            loc: Codegen,
+            ty: ty.clone(),
+            expr: expr.clone().into(),
+        }
+    } else {
+        expr.clone()
+    };
+    let value = match ty {
+        Type::Uint(256) | Type::Int(256) => value,
+        Type::Int(_) => Expression::SignExt {
+            loc: Codegen,
+            ty: Type::Int(256),
+            expr: value.into(),
+        },
+        Type::Address(_) | Type::Contract(_) => Expression::ZeroExt {
+            loc: Codegen,
+            ty: Uint(256),
+            expr: Expression::Cast {
+                loc: Codegen,
+                ty: Uint(ns.address_length as u16 * 8),
+                expr: value.into(),
+            }
+            .into(),
+        },
+        _ => Expression::ZeroExt {
+            loc: Codegen,
+            ty: Uint(256),
+            expr: value.into(),
+        },
+    };
+    Expression::Cast {
+        loc: Codegen,
+        ty: Type::Bytes(32),
+        expr: value.into(),
+    }
+}
+
+/// The 32 byte word which encodes a constant unsigned integer
+fn const_word(value: &BigInt) -> [u8; 32] {
+    let bytes = value.to_bytes_be().1;
+    let mut word = [0; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+/// Round `length` up to a multiple of 32
+fn padded(length: Expression) -> Expression {
+    Expression::BitwiseAnd {
+        loc: Codegen,
+        ty: Uint(32),
+        left: length.add_u32(uint32(31)).into(),
+        right: uint32(!31u32).into(),
+    }
+}
+
+fn multiply(left: Expression, right: Expression) -> Expression {
+    Expression::Multiply {
+        loc: Codegen,
+        ty: Uint(32),
+        overflowing: false,
+        left: left.into(),
+        right: right.into(),
+    }
+}
+
+fn low_bits(bits: u16) -> BigInt {
+    (BigInt::one() << bits) - 1
+}
+
+fn uint32(value: impl Into<BigInt>) -> Expression {
+    Expression::NumberLiteral {
+        loc: Codegen,
+        ty: Uint(32),
+        value: value.into(),
+    }
+}
+
+fn uint256(value: impl Into<BigInt>) -> Expression {
+    Expression::NumberLiteral {
+        loc: Codegen,
+        ty: Uint(256),
+        value: value.into(),
+    }
+}
+
+/// Fail if the encoded data is invalid, just like when reading past the end of the buffer
+fn assert_valid(
+    cond: Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) {
+    let valid = cfg.new_basic_block("valid_encoding".to_string());
+    let invalid = cfg.new_basic_block("invalid_encoding".to_string());
+    cfg.add(
+        vartab,
+        Instr::BranchCond {
+            cond,
+            true_block: valid,
+            false_block: invalid,
+        },
+    );
+
+    cfg.set_basic_block(invalid);
+    let error = SolidityError::Panic(PanicCode::Generic);
+    assert_failure(&Codegen, error, ns, cfg, vartab);
+    cfg.set_basic_block(valid);
+}
+
+#[cfg(test)]
+mod tests {
+    use ethabi::{encode, Token};
+    use num_bigint::{BigInt, Sign};
+
+    use crate::{
+        codegen::{
+            encoding::{ethabi_encoding::EthAbiEncoding, AbiEncoding},
+            Expression,
+        },
+        sema::ast::Type,
+    };
+
+    #[test]
+    fn const_encode_string() {
+        let encoder = EthAbiEncoding::new(false);
+        for data in [
+            "",
+            "failed",
+            "a string which is longer than one word of 32 bytes",
+        ] {
+            let expr = Expression::AllocDynamicBytes {
+                loc: Default::default(),
+                ty: Type::String,
+                size: Expression::Poison.into(),
+                initializer: data.as_bytes().to_vec().into(),
+            };
+            let encoded = encoder.const_encode(&[expr]).unwrap();
+            assert_eq!(encoded, encode(&[Token::String(data.into())]));
+        }
+    }
+
+    #[test]
+    fn const_encode_uint() {
+        let encoder = EthAbiEncoding::new(false);
+        for value in [0u64, 1, 0x11, u64::MAX] {
+            let expr = Expression::NumberLiteral {
+                loc: Default::default(),
+                ty: Type::Uint(256),
+                value: value.into(),
+            };
+            let encoded = encoder.const_encode(&[expr]).unwrap();
+            assert_eq!(encoded, encode(&[Token::Uint(value.into())]));
+        }
+    }
+
+    #[test]
+    fn const_encode_tuple() {
+        let encoder = EthAbiEncoding::new(false);
+        let args = [
+            Expression::NumberLiteral {
+                loc: Default::default(),
+                ty: Type::Bytes(4),
+                value: BigInt::from_bytes_be(Sign::Plus, &[0xde, 0xad, 0xbe, 0xef]),
+            },
+            Expression::AllocDynamicBytes {
+                loc: Default::default(),
+                ty: Type::DynamicBytes,
+                size: Expression::Poison.into(),
+                initializer: vec![0x41; 33].into(),
+            },
+            Expression::NumberLiteral {
+                loc: Default::default(),
+                ty: Type::Uint(256),
+                value: 7.into(),
+            },
+        ];
+        let encoded = encoder.const_encode(&args).unwrap();
+        let expected = encode(&[
+            Token::FixedBytes(vec![0xde, 0xad, 0xbe, 0xef]),
+            Token::Bytes(vec![0x41; 33]),
+            Token::Uint(7.into()),
+        ]);
+        assert_eq!(encoded, expected);
+    }
+}
@@ -10,10 +10,12 @@
 ///   Any such helper function should work fine regardless of the encoding scheme being used.
 mod borsh_encoding;
 mod buffer_validator;
+mod ethabi_encoding;
 pub(super) mod scale_encoding;
 
 use crate::codegen::cfg::{ControlFlowGraph, Instr};
 use crate::codegen::encoding::borsh_encoding::BorshEncoding;
+use crate::codegen::encoding::ethabi_encoding::EthAbiEncoding;
 use crate::codegen::encoding::scale_encoding::ScaleEncoding;
 use crate::codegen::expression::load_storage;
 use crate::codegen::vartable::Vartable;
@@ -38,34 +40,24 @@ pub(super) fn abi_encode(
     cfg: &mut ControlFlowGraph,
     packed: bool,
 ) -> (Expression, Expression) {
-    let mut encoder = create_encoder(ns, packed);
-    let size = calculate_size_args(&mut encoder, &args, ns, vartab, cfg);
-    let encoded_bytes = vartab.temp_name("abi_encoded", &Type::DynamicBytes);
-    let expr = Expression::AllocDynamicBytes {
-        loc: *loc,
-        ty: Type::DynamicBytes,
-        size: size.clone().into(),
-        initializer: None,
-    };
-    cfg.add(
-        vartab,
-        Instr::Set {
-            loc: *loc,
-            res: encoded_bytes,
-            expr,
-        },
-    );
-
     let mut offset = Expression::NumberLiteral {
         loc: *loc,
         ty: Uint(32),
         value: BigInt::zero(),
     };
-    let buffer = Expression::Variable {
-        loc: *loc,
-        ty: Type::DynamicBytes,
-        var_no: encoded_bytes,
-    };
+
+    if ns.target == Target::EVM && !packed {
+        // The arguments are encoded as a tuple, which also works for no arguments
+        let mut encoder = EthAbiEncoding::new(false);
+        let size = encoder.tuple_size(&args, None, ns, vartab, cfg);
+        let buffer = allocate_encoded(loc, &size, vartab, cfg);
+        encoder.encode_tuple(&args, None, &buffer, &offset, ns, vartab, cfg);
+        return (buffer, size);
+    }
+
+    let mut encoder = create_encoder(ns, packed);
+    let size = calculate_size_args(&mut encoder, &args, ns, vartab, cfg);
+    let buffer = allocate_encoded(loc, &size, vartab, cfg);
     for (arg_no, item) in args.iter().enumerate() {
         let advance = encoder.encode(item, &buffer, &offset, arg_no, ns, vartab, cfg);
         offset = Expression::Add {
@@ -118,6 +110,18 @@ pub(super) fn abi_decode(
 
     let mut validator = BufferValidator::new(buffer_size, types);
 
+    if ns.target == Target::EVM {
+        // The items are decoded as a tuple. Every offset and length in the encoded data is
+        // checked against the buffer size, so the trailing bytes are not checked.
+        let offset = Expression::NumberLiteral {
+            loc: *loc,
+            ty: Uint(32),
+            value: BigInt::zero(),
+        };
+        let encoder = EthAbiEncoding::new(false);
+        return encoder.decode_tuple(buffer, &offset, types, &mut validator, ns, vartab, cfg);
+    }
+
     let mut read_items: Vec<Expression> = vec![Expression::Poison; types.len()];
     let mut offset = Expression::NumberLiteral {
         loc: *loc,
@@ -148,6 +152,139 @@ pub(super) fn abi_decode(
     read_items
 }
 
+/// Encode the `args` preceded by a function `selector`, like for a call or for error data.
+/// Returns a pointer to the encoded data and the size as a 32bit integer.
+pub(super) fn abi_encode_with_selector(
+    loc: &Loc,
+    selector: Expression,
+    mut args: Vec<Expression>,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> (Expression, Expression) {
+    if ns.target != Target::EVM {
+        args.insert(0, selector);
+        return abi_encode(loc, args, ns, vartab, cfg, false);
+    }
+
+    // The offsets in the tuple of arguments are relative to the end of the selector
+    let selector_len = Expression::NumberLiteral {
+        loc: *loc,
+        ty: Uint(32),
+        value: 4.into(),
+    };
+    let mut encoder = EthAbiEncoding::new(false);
+    let size = selector_len
+        .clone()
+        .add_u32(encoder.tuple_size(&args, None, ns, vartab, cfg));
+    let buffer = allocate_encoded(loc, &size, vartab, cfg);
+    let offset = Expression::NumberLiteral {
+        loc: *loc,
+        ty: Uint(32),
+        value: BigInt::zero(),
+    };
+    encoder.encode_directly(&selector, &buffer, &offset, vartab, cfg, 4.into());
+    encoder.encode_tuple(&args, None, &buffer, &selector_len, ns, vartab, cfg);
+    (buffer, size)
+}
+
+/// Decode the `types` following a function selector in `buffer`, like for error data.
+/// The selector itself is not checked.
+pub(super) fn abi_decode_with_selector(
+    loc: &Loc,
+    buffer: &Expression,
+    types: &[Type],
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Vec<Expression> {
+    if ns.target != Target::EVM {
+        let mut types = types.to_vec();
+        types.insert(0, Type::Bytes(4));
+        let mut read_items = abi_decode(loc, buffer, &types, ns, vartab, cfg, None);
+        read_items.remove(0);
+        return read_items;
+    }
+
+    let selector_len = Expression::NumberLiteral {
+        loc: *loc,
+        ty: Uint(32),
+        value: 4.into(),
+    };
+    let data = Expression::AdvancePointer {
+        pointer: buffer.clone().into(),
+        bytes_offset: selector_len.clone().into(),
+    };
+    let data_size = Expression::Subtract {
+        loc: *loc,
+        ty: Uint(32),
+        overflowing: false,
+        left: Expression::Builtin {
+            loc: *loc,
+            tys: vec![Uint(32)],
+            kind: Builtin::ArrayLength,
+            args: vec![buffer.clone()],
+        }
+        .into(),
+        right: selector_len.into(),
+    };
+    abi_decode(loc, &data, types, ns, vartab, cfg, Some(data_size))
+}
+
+/// Encode the `args` preceded by a function `selector` at compile time.
+///
+/// Returns `None` if the data can not be encoded at compile time.
+pub(super) fn const_encode_with_selector(
+    selector: &[u8],
+    args: &[Expression],
+    ns: &Namespace,
+) -> Option<Vec<u8>> {
+    let encoder = create_encoder(ns, false);
+    if ns.target == Target::EVM {
+        let mut encoded = selector.to_vec();
+        encoded.extend(encoder.const_encode(args)?);
+        return Some(encoded);
+    }
+
+    let mut items = vec![Expression::NumberLiteral {
+        loc: Codegen,
+        ty: Type::Bytes(selector.len() as u8),
+        value: BigInt::from_bytes_be(num_bigint::Sign::Plus, selector),
+    }];
+    items.extend_from_slice(args);
+    encoder.const_encode(&items)
+}
+
+/// Allocate the buffer for `size` bytes of encoded data
+fn allocate_encoded(
+    loc: &Loc,
+    size: &Expression,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Expression {
+    let encoded_bytes = vartab.temp_name("abi_encoded", &Type::DynamicBytes);
+    let expr = Expression::AllocDynamicBytes {
+        loc: *loc,
+        ty: Type::DynamicBytes,
+        size: size.clone().into(),
+        initializer: None,
+    };
+    cfg.add(
+        vartab,
+        Instr::Set {
+            loc: *loc,
+            res: encoded_bytes,
+            expr,
+        },
+    );
+
+    Expression::Variable {
+        loc: *loc,
+        ty: Type::DynamicBytes,
+        var_no: encoded_bytes,
+    }
+}
+
 /// Calculate the size of a set of arguments to encoding functions
 fn calculate_size_args(
     encoder: &mut Box<dyn AbiEncoding>,
@@ -1746,12 +1883,8 @@ pub(crate) trait AbiEncoding {
 pub(crate) fn create_encoder(ns: &Namespace, packed: bool) -> Box<dyn AbiEncoding> {
     match &ns.target {
         Target::Solana => Box::new(BorshEncoding::new(packed)),
-        // Solana utilizes Borsh encoding and Polkadot, SCALE encoding.
-        // All other targets are using the SCALE encoding, because we have tests for a
-        // fake Ethereum target that checks the presence of Instr::AbiDecode and
-        // Expression::AbiEncode.
-        // If a new target is added, this piece of code needs to change.
-        _ => Box::new(ScaleEncoding::new(packed)),
+        Target::EVM => Box::new(EthAbiEncoding::new(packed)),
+        Target::Polkadot { .. } => Box::new(ScaleEncoding::new(packed)),
     }
 }
 
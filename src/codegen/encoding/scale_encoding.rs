@@ -118,7 +118,13 @@ fn decode_compact(
     // We will land in the default block for sizes of 2**30 (1GB) or larger.
     // Such big sizes are invalid for smart contracts and should never occur anyways.
     cfg.set_basic_block(default);
-    cfg.add(vartab, Instr::AssertFailure { encoded_args: None });
+    cfg.add(
+        vartab,
+        Instr::AssertFailure {
+            encoded_args: None,
+            error_code: None,
+        },
+    );
 
     cfg.set_basic_block(cases[0].1);
     let expr = Expression::ShiftRight {
@@ -269,7 +275,13 @@ fn encode_compact(
     );
 
     cfg.set_basic_block(fail);
-    cfg.add(vartab, Instr::AssertFailure { encoded_args: None });
+    cfg.add(
+        vartab,
+        Instr::AssertFailure {
+            encoded_args: None,
+            error_code: None,
+        },
+    );
 
     cfg.set_basic_block(prepare);
     let cmp_val = Expression::NumberLiteral {
@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::codegen::cfg::{ControlFlowGraph, Instr};
+use crate::codegen::encoding::abi_encode;
+use crate::codegen::events::EventEmitter;
+use crate::codegen::expression::expression;
+use crate::codegen::vartable::Vartable;
+use crate::codegen::{Builtin, Expression, Options};
+use crate::sema::ast::{self, Function, Namespace, RetrieveType, Type};
+use solang_parser::pt;
+use tiny_keccak::{Hasher, Keccak};
+
+/// This struct implements the trait 'EventEmitter' in order to handle the emission of events
+/// for EVM, the way solc does.
+///
+/// The first topic is the keccak256 hash of the event signature, unless the event is anonymous.
+/// Indexed fields of value types are topics as they are ABI encoded, whereas strings and bytes are
+/// hashed. Indexed structs and arrays are hashed too, but note that solc hashes their encoding
+/// without padding and offsets, so those topics differ. The remaining fields are ABI encoded as
+/// the data of the event.
+pub(super) struct EvmEventEmitter<'a> {
+    /// Arguments passed to the event
+    pub(super) args: &'a [ast::Expression],
+    pub(super) ns: &'a Namespace,
+    pub(super) event_no: usize,
+}
+
+impl EventEmitter for EvmEventEmitter<'_> {
+    fn selector(&self, _emitting_contract_no: usize) -> Vec<u8> {
+        let event = &self.ns.events[self.event_no];
+
+        let mut buf = [0; 32];
+        let mut hasher = Keccak::v256();
+        hasher.update(event.signature.as_bytes());
+        hasher.finalize(&mut buf);
+        buf.into()
+    }
+
+    fn emit(
+        &self,
+        contract_no: usize,
+        func: &Function,
+        cfg: &mut ControlFlowGraph,
+        vartab: &mut Vartable,
+        opt: &Options,
+    ) {
+        let loc = pt::Loc::Builtin;
+        let event = &self.ns.events[self.event_no];
+        let mut data = vec![];
+        let mut topics = vec![];
+
+        // Events that are not anonymous always have their selector as the first topic.
+        // This is static and can be calculated at compile time.
+        if !event.anonymous {
+            topics.push(Expression::AllocDynamicBytes {
+                loc,
+                ty: Type::Slice(Type::Uint(8).into()),
+                size: Expression::NumberLiteral {
+                    loc,
+                    ty: Type::Uint(32),
+                    value: 32.into(),
+                }
+                .into(),
+                initializer: Some(self.selector(contract_no)),
+            });
+        }
+
+        for (ast_exp, field) in self.args.iter().zip(event.fields.iter()) {
+            let value_exp = expression(ast_exp, cfg, contract_no, Some(func), self.ns, vartab, opt);
+            let value_var = vartab.temp_anonymous(&value_exp.ty());
+            let value = Expression::Variable {
+                loc,
+                ty: value_exp.ty(),
+                var_no: value_var,
+            };
+            cfg.add(
+                vartab,
+                Instr::Set {
+                    loc,
+                    res: value_var,
+                    expr: value_exp,
+                },
+            );
+
+            if !field.indexed {
+                data.push(value);
+                continue;
+            }
+
+            let topic = match field.ty {
+                Type::String | Type::DynamicBytes => Expression::Builtin {
+                    loc,
+                    tys: vec![Type::Bytes(32)],
+                    kind: Builtin::Keccak256,
+                    args: vec![value],
+                },
+                Type::Struct(_) | Type::Array(..) => {
+                    let encoded = abi_encode(&loc, vec![value], self.ns, vartab, cfg, false).0;
+                    Expression::Builtin {
+                        loc,
+                        tys: vec![Type::Bytes(32)],
+                        kind: Builtin::Keccak256,
+                        args: vec![encoded],
+                    }
+                }
+                _ => value,
+            };
+            topics.push(abi_encode(&loc, vec![topic], self.ns, vartab, cfg, false).0);
+        }
+
+        let data = abi_encode(&loc, data, self.ns, vartab, cfg, false).0;
+        cfg.add(
+            vartab,
+            Instr::EmitEvent {
+                event_no: self.event_no,
+                data,
+                topics,
+            },
+        );
+    }
+}
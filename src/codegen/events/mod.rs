@@ -1,9 +1,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
+mod evm;
 mod polkadot;
 mod solana;
 
 use crate::codegen::cfg::ControlFlowGraph;
+use crate::codegen::events::evm::EvmEventEmitter;
 use crate::codegen::events::polkadot::PolkadotEventEmitter;
 use crate::codegen::events::solana::SolanaEventEmitter;
 use crate::codegen::vartable::Vartable;
@@ -40,9 +42,9 @@ pub(super) fn new_event_emitter<'a>(
     ns: &'a Namespace,
 ) -> Box<dyn EventEmitter + 'a> {
     match ns.target {
-        Target::Polkadot { .. } | Target::EVM => {
-            Box::new(PolkadotEventEmitter { args, ns, event_no })
-        }
+        Target::Polkadot { .. } => Box::new(PolkadotEventEmitter { args, ns, event_no }),
+
+        Target::EVM => Box::new(EvmEventEmitter { args, ns, event_no }),
 
         Target::Solana => Box::new(SolanaEventEmitter {
             loc: *loc,
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use super::encoding::{abi_decode, abi_encode};
+use super::encoding::{self, abi_decode, abi_encode};
 use super::revert::{
     assert_failure, expr_assert, log_runtime_error, require, PanicCode, SolidityError,
 };
@@ -660,6 +660,25 @@ pub fn expression(
             vartab,
             opt,
         ),
+        ast::Expression::ArraySlice {
+            loc,
+            ty,
+            array,
+            start,
+            end,
+        } => array_slice(
+            loc,
+            ty,
+            array,
+            start.as_deref(),
+            end.as_deref(),
+            cfg,
+            contract_no,
+            func,
+            ns,
+            vartab,
+            opt,
+        ),
         ast::Expression::StructMember {
             loc,
             ty,
@@ -742,6 +761,23 @@ pub fn expression(
         ast::Expression::CheckingTrunc { loc, to, expr } => {
             checking_trunc(loc, expr, to, cfg, contract_no, func, ns, vartab, opt)
         }
+        ast::Expression::Trunc {
+            loc,
+            to: Type::Enum(enum_no),
+            expr,
+        }
+        | ast::Expression::ZeroExt {
+            loc,
+            to: Type::Enum(enum_no),
+            expr,
+        }
+        | ast::Expression::Cast {
+            loc,
+            to: Type::Enum(enum_no),
+            expr,
+        } if matches!(expr.ty(), Type::Uint(_) | Type::Int(_)) => {
+            enum_cast(loc, expr, *enum_no, cfg, contract_no, func, ns, vartab, opt)
+        }
         ast::Expression::Trunc { loc, to, expr } => Expression::Trunc {
             loc: *loc,
             ty: to.clone(),
@@ -1147,6 +1183,9 @@ pub fn expression(
         }
 
         ast::Expression::List { .. } => unreachable!("List shall not appear in the CFG"),
+        ast::Expression::CustomError { .. } => {
+            unreachable!("CustomError shall only appear in require()")
+        }
     }
 }
 
@@ -1738,15 +1777,12 @@ fn abi_encode_packed(
 fn encode_many_with_selector(
     loc: &pt::Loc,
     selector: Expression,
-    mut args: Vec<Expression>,
+    args: Vec<Expression>,
     ns: &Namespace,
     vartab: &mut Vartable,
     cfg: &mut ControlFlowGraph,
 ) -> Expression {
-    let mut encoder_args: Vec<Expression> = Vec::with_capacity(args.len() + 1);
-    encoder_args.push(selector);
-    encoder_args.append(&mut args);
-    abi_encode(loc, encoder_args, ns, vartab, cfg, false).0
+    encoding::abi_encode_with_selector(loc, selector, args, ns, vartab, cfg).0
 }
 
 fn abi_encode_with_selector(
@@ -2234,7 +2270,7 @@ fn expr_builtin(
 
             Expression::NumberLiteral {
                 loc: *loc,
-                ty: Type::Bool,
+                ty: Type::Address(false),
                 value: 0.into(),
             }
         }
@@ -2424,6 +2460,125 @@ fn checking_trunc(
     }
 }
 
+/// Convert an integer to an enum, checking that the value is a valid ordinal of the enum
+fn enum_cast(
+    loc: &pt::Loc,
+    expr: &ast::Expression,
+    enum_no: usize,
+    cfg: &mut ControlFlowGraph,
+    contract_no: usize,
+    func: Option<&Function>,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    opt: &Options,
+) -> Expression {
+    let source_ty = expr.ty();
+    let ty = Type::Enum(enum_no);
+    let values = ns.enums[enum_no].values.len();
+
+    let pos = vartab.temp(
+        &pt::Identifier {
+            name: "value".to_owned(),
+            loc: *loc,
+        },
+        &source_ty,
+    );
+
+    let expr = expression(expr, cfg, contract_no, func, ns, vartab, opt);
+
+    cfg.add(
+        vartab,
+        Instr::Set {
+            loc: expr.loc(),
+            res: pos,
+            expr,
+        },
+    );
+
+    let value = Expression::Variable {
+        loc: *loc,
+        ty: source_ty.clone(),
+        var_no: pos,
+    };
+
+    // Negative values are huge when compared unsigned, so a single comparison covers them
+    // too. An unsigned type which cannot hold the number of values never overflows.
+    if matches!(source_ty, Type::Int(_))
+        || BigInt::from(values) < BigInt::one() << source_ty.bits(ns)
+    {
+        let out_of_range = cfg.new_basic_block("enum_out_of_range".to_string());
+        let in_range = cfg.new_basic_block("enum_in_range".to_string());
+
+        // If the number of values does not fit in the signed type, every non-negative
+        // value is in range, so only negative values are out of range
+        let cond = match source_ty {
+            Type::Int(bits) if BigInt::from(values) >= BigInt::one() << (bits - 1) => {
+                Expression::Less {
+                    loc: *loc,
+                    signed: true,
+                    left: Box::new(value.clone()),
+                    right: Box::new(Expression::NumberLiteral {
+                        loc: *loc,
+                        ty: source_ty.clone(),
+                        value: BigInt::zero(),
+                    }),
+                }
+            }
+            _ => Expression::MoreEqual {
+                loc: *loc,
+                signed: false,
+                left: Box::new(value.clone()),
+                right: Box::new(Expression::NumberLiteral {
+                    loc: *loc,
+                    ty: source_ty.clone(),
+                    value: values.into(),
+                }),
+            },
+        };
+
+        cfg.add(
+            vartab,
+            Instr::BranchCond {
+                cond,
+                true_block: out_of_range,
+                false_block: in_range,
+            },
+        );
+
+        cfg.set_basic_block(out_of_range);
+        log_runtime_error(
+            opt.log_runtime_errors,
+            "enum conversion out of range",
+            *loc,
+            cfg,
+            vartab,
+            ns,
+        );
+        let error = SolidityError::Panic(PanicCode::EnumCastOob);
+        assert_failure(loc, error, ns, cfg, vartab);
+
+        cfg.set_basic_block(in_range);
+    }
+
+    match source_ty.bits(ns).cmp(&ty.bits(ns)) {
+        Ordering::Greater => Expression::Trunc {
+            loc: *loc,
+            ty,
+            expr: Box::new(value),
+        },
+        Ordering::Less => Expression::ZeroExt {
+            loc: *loc,
+            ty,
+            expr: Box::new(value),
+        },
+        Ordering::Equal => Expression::Cast {
+            loc: *loc,
+            ty,
+            expr: Box::new(value),
+        },
+    }
+}
+
 fn format_string(
     args: &[(FormatArg, ast::Expression)],
     cfg: &mut ControlFlowGraph,
@@ -2916,8 +3071,7 @@ pub fn emit_function_call(
                     .contract_no
                     .map(|contract_no| (contract_no, *function_no));
 
-                let mut tys: Vec<Type> = args.iter().map(|a| a.ty()).collect();
-                let mut args: Vec<Expression> = args
+                let args: Vec<Expression> = args
                     .iter()
                     .map(|a| expression(a, cfg, caller_contract_no, func, ns, vartab, opt))
                     .collect();
@@ -2946,19 +3100,14 @@ pub fn emit_function_call(
                 };
 
                 let selector = dest_func.selector(ns, &caller_contract_no);
+                let selector = Expression::BytesLiteral {
+                    loc: *loc,
+                    ty: Type::Bytes(selector.len() as u8),
+                    value: selector,
+                };
 
-                tys.insert(0, Type::Bytes(selector.len() as u8));
-
-                args.insert(
-                    0,
-                    Expression::BytesLiteral {
-                        loc: *loc,
-                        ty: Type::Bytes(selector.len() as u8),
-                        value: selector,
-                    },
-                );
-
-                let (payload, _) = abi_encode(loc, args, ns, vartab, cfg, false);
+                let (payload, _) =
+                    encoding::abi_encode_with_selector(loc, selector, args, ns, vartab, cfg);
 
                 let flags = call_args
                     .flags
@@ -3019,8 +3168,7 @@ pub fn emit_function_call(
                 ..
             } = function.ty()
             {
-                let mut tys: Vec<Type> = args.iter().map(|a| a.ty()).collect();
-                let mut args = args
+                let args = args
                     .iter()
                     .map(|a| expression(a, cfg, caller_contract_no, func, ns, vartab, opt))
                     .collect::<Vec<Expression>>();
@@ -3043,10 +3191,8 @@ pub fn emit_function_call(
                 let selector = function.external_function_selector();
                 let address = function.external_function_address();
 
-                tys.insert(0, Type::Bytes(ns.target.selector_length()));
-                args.insert(0, selector);
-
-                let (payload, _) = abi_encode(loc, args, ns, vartab, cfg, false);
+                let (payload, _) =
+                    encoding::abi_encode_with_selector(loc, selector, args, ns, vartab, cfg);
 
                 let flags = call_args
                     .flags
@@ -3125,6 +3271,150 @@ pub fn default_gas(ns: &Namespace) -> Expression {
     }
 }
 
+/// Slice a memory array. The slice points into the data of the array, so nothing is copied.
+/// If the slice bounds are not `start <= end <= array.length`, execution reverts.
+fn array_slice(
+    loc: &pt::Loc,
+    ty: &Type,
+    array: &ast::Expression,
+    start: Option<&ast::Expression>,
+    end: Option<&ast::Expression>,
+    cfg: &mut ControlFlowGraph,
+    contract_no: usize,
+    func: Option<&Function>,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    opt: &Options,
+) -> Expression {
+    let array = expression(array, cfg, contract_no, func, ns, vartab, opt);
+    let array_ty = array.ty();
+
+    let array_var = vartab.temp_anonymous(&array_ty);
+    cfg.add(
+        vartab,
+        Instr::Set {
+            loc: array.loc(),
+            res: array_var,
+            expr: array,
+        },
+    );
+    let array = Expression::Variable {
+        loc: *loc,
+        ty: array_ty,
+        var_no: array_var,
+    };
+
+    let array_length = Expression::Builtin {
+        loc: *loc,
+        tys: vec![Type::Uint(32)],
+        kind: Builtin::ArrayLength,
+        args: vec![array.clone()],
+    };
+
+    let start = start.map(|start| expression(start, cfg, contract_no, func, ns, vartab, opt));
+    let end = end.map(|end| expression(end, cfg, contract_no, func, ns, vartab, opt));
+
+    // The bounds may be wider than the array length, so compare with the widest type
+    let width = [&start, &end]
+        .into_iter()
+        .flatten()
+        .map(|bound| bound.ty().bits(ns))
+        .fold(32, std::cmp::max);
+    let coerced_ty = Type::Uint(width);
+
+    let start = start.unwrap_or_else(|| Expression::NumberLiteral {
+        loc: *loc,
+        ty: coerced_ty.clone(),
+        value: BigInt::zero(),
+    });
+    let end = end.unwrap_or_else(|| array_length.clone());
+
+    let mut bound_var = |name: &str, expr: Expression| {
+        let var_no = vartab.temp(
+            &pt::Identifier {
+                name: name.to_owned(),
+                loc: *loc,
+            },
+            &coerced_ty,
+        );
+        let expr = expr.cast(&coerced_ty, ns);
+        cfg.add(
+            vartab,
+            Instr::Set {
+                loc: expr.loc(),
+                res: var_no,
+                expr,
+            },
+        );
+        Expression::Variable {
+            loc: *loc,
+            ty: coerced_ty.clone(),
+            var_no,
+        }
+    };
+
+    let start = bound_var("start", start);
+    let end = bound_var("end", end);
+
+    let out_of_bounds = cfg.new_basic_block("slice_out_of_bounds".to_string());
+    let end_in_bounds = cfg.new_basic_block("slice_end_in_bounds".to_string());
+    let in_bounds = cfg.new_basic_block("slice_in_bounds".to_string());
+
+    cfg.add(
+        vartab,
+        Instr::BranchCond {
+            cond: Expression::More {
+                loc: *loc,
+                signed: false,
+                left: Box::new(end.clone()),
+                right: Box::new(array_length.cast(&coerced_ty, ns)),
+            },
+            true_block: out_of_bounds,
+            false_block: end_in_bounds,
+        },
+    );
+
+    cfg.set_basic_block(end_in_bounds);
+    cfg.add(
+        vartab,
+        Instr::BranchCond {
+            cond: Expression::More {
+                loc: *loc,
+                signed: false,
+                left: Box::new(start.clone()),
+                right: Box::new(end.clone()),
+            },
+            true_block: out_of_bounds,
+            false_block: in_bounds,
+        },
+    );
+
+    cfg.set_basic_block(out_of_bounds);
+    log_runtime_error(
+        opt.log_runtime_errors,
+        "array slice out of bounds",
+        *loc,
+        cfg,
+        vartab,
+        ns,
+    );
+    assert_failure(loc, SolidityError::Empty, ns, cfg, vartab);
+
+    cfg.set_basic_block(in_bounds);
+
+    // Both bounds are now known to fit into 32 bits
+    Expression::Builtin {
+        loc: *loc,
+        tys: vec![ty.clone()],
+        kind: Builtin::ArraySlice,
+        args: vec![
+            array,
+            start.cast(&Type::Uint(32), ns),
+            end.cast(&Type::Uint(32), ns),
+        ],
+    }
+}
+
 /// Codegen for an array subscript expression
 fn array_subscript(
     loc: &pt::Loc,
@@ -3651,7 +3941,7 @@ fn array_literal_to_memory_array(
 fn code(loc: &Loc, contract_no: usize, ns: &Namespace, opt: &Options) -> Expression {
     let contract = &ns.contracts[contract_no];
 
-    let code = contract.emit(ns, opt);
+    let code = contract.emit(ns, opt).expect("llvm build");
 
     let size = Expression::NumberLiteral {
         loc: *loc,
@@ -103,6 +103,27 @@ pub fn add_external_functions(contract_no: usize, ns: &mut Namespace) {
     }
 
     ns.contracts[contract_no].emits_events = emits_events;
+
+    // and the custom errors it may revert with. These are sorted in order of declaration, so that
+    // the error codes on Solana do not depend on the order of the functions
+    let mut custom_errors = Vec::new();
+
+    for function_no in ns.contracts[contract_no].all_functions.keys() {
+        for error_no in &ns.functions[*function_no].custom_errors {
+            if !custom_errors.contains(error_no) {
+                custom_errors.push(*error_no);
+            }
+        }
+    }
+
+    custom_errors.sort_unstable();
+
+    ns.contracts[contract_no].custom_errors = custom_errors;
+
+    ns.contracts[contract_no].yul_memory = ns.contracts[contract_no]
+        .all_functions
+        .keys()
+        .any(|function_no| ns.functions[*function_no].yul_memory);
 }
 
 fn check_expression(expr: &Expression, call_list: &mut CallList) -> bool {
@@ -0,0 +1,351 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::cfg::{ASTFunction, BasicBlock, ControlFlowGraph, Instr, InternalCallTy};
+use super::reaching_definitions;
+use super::vartable::{Storage, Variable};
+use super::Expression;
+use crate::sema::ast::Namespace;
+use crate::Target;
+use indexmap::IndexMap;
+use solang_parser::pt;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Functions with more instructions than this are not inlined. Tiny getters and helpers are
+/// well below this, so they no longer pay for the call overhead.
+const INLINE_COST_THRESHOLD: usize = 24;
+
+/// Inlining a function into its caller can turn the caller into a leaf function, which can then
+/// be inlined too. This limits how many times we repeat this.
+const MAX_INLINE_ROUNDS: usize = 4;
+
+/// Inline static calls to small leaf functions in all the cfgs of a contract. Only functions which
+/// do not call any other function are inlined, so recursive functions are never inlined. Returns
+/// the cfgs which had calls inlined, so that they can be optimized again.
+pub(super) fn inline(all_cfg: &mut [ControlFlowGraph], ns: &mut Namespace) -> BTreeSet<usize> {
+    let mut inlined = BTreeSet::new();
+
+    for _ in 0..MAX_INLINE_ROUNDS {
+        let callees: HashMap<usize, ControlFlowGraph> = all_cfg
+            .iter()
+            .enumerate()
+            .filter(|(_, cfg)| can_inline(cfg, ns))
+            .map(|(cfg_no, cfg)| (cfg_no, cfg.clone()))
+            .collect();
+
+        if callees.is_empty() {
+            break;
+        }
+
+        let mut changed = false;
+
+        for (cfg_no, cfg) in all_cfg.iter_mut().enumerate() {
+            if inline_calls(cfg, &callees, ns) {
+                inlined.insert(cfg_no);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    inlined
+}
+
+/// Does the cost model allow this function to be inlined
+fn can_inline(cfg: &ControlFlowGraph, ns: &Namespace) -> bool {
+    if cfg.is_placeholder() {
+        return false;
+    }
+
+    // The cfgs of a modifier chain do not have a function of their own
+    if cfg.function_no == ASTFunction::None && cfg.modifier.is_none() {
+        return false;
+    }
+
+    let mut cost = 0;
+
+    for instr in cfg.blocks.iter().flat_map(|block| block.instr.iter()) {
+        match instr {
+            Instr::Call {
+                call: InternalCallTy::Static { .. } | InternalCallTy::Dynamic(_),
+                ..
+            }
+            | Instr::Unimplemented { .. } => return false,
+            // On Solana, these instructions use the accounts collected for the function they are in
+            Instr::Constructor { .. }
+            | Instr::ExternalCall { .. }
+            | Instr::AccountAccess { .. }
+                if ns.target == Target::Solana =>
+            {
+                return false
+            }
+            Instr::Nop => (),
+            _ => cost += 1,
+        }
+    }
+
+    cost <= INLINE_COST_THRESHOLD
+}
+
+/// Inline all calls to the given callees. Returns true if anything was inlined.
+fn inline_calls(
+    cfg: &mut ControlFlowGraph,
+    callees: &HashMap<usize, ControlFlowGraph>,
+    ns: &mut Namespace,
+) -> bool {
+    let mut changed = false;
+    let mut block_no = 0;
+
+    // Inlining appends blocks to the cfg, so the remainder of a block after an inlined call is
+    // visited later on
+    while block_no < cfg.blocks.len() {
+        let call = cfg.blocks[block_no].instr.iter().position(|instr| {
+            matches!(instr, Instr::Call {
+                call: InternalCallTy::Static { cfg_no },
+                ..
+            } if callees.contains_key(cfg_no))
+        });
+
+        if let Some(instr_no) = call {
+            inline_call(cfg, block_no, instr_no, callees, ns);
+            changed = true;
+        }
+
+        block_no += 1;
+    }
+
+    if changed {
+        // the reaching definitions are stale now
+        for block in &mut cfg.blocks {
+            block.defs = IndexMap::new();
+        }
+
+        reaching_definitions::find(cfg);
+    }
+
+    changed
+}
+
+/// Replace the call at the given instruction with the body of the callee. The block is split
+/// at the call; the callee blocks are appended to the cfg and return to a new block which holds
+/// the instructions after the call.
+fn inline_call(
+    cfg: &mut ControlFlowGraph,
+    block_no: usize,
+    instr_no: usize,
+    callees: &HashMap<usize, ControlFlowGraph>,
+    ns: &mut Namespace,
+) {
+    let mut rest = cfg.blocks[block_no].instr.split_off(instr_no);
+
+    let (res, cfg_no, args) = match rest.remove(0) {
+        Instr::Call {
+            res,
+            call: InternalCallTy::Static { cfg_no },
+            args,
+            ..
+        } => (res, cfg_no, args),
+        _ => unreachable!(),
+    };
+
+    let callee = &callees[&cfg_no];
+
+    let callee_name = match callee.function_no {
+        ASTFunction::SolidityFunction(function_no) => ns.functions[function_no].id.name.clone(),
+        ASTFunction::YulFunction(function_no) => ns.yul_functions[function_no].name.clone(),
+        // the name of a modifier chain cfg ends with the modifier symbol
+        ASTFunction::None => callee.name.rsplit("::").next().unwrap().to_owned(),
+    };
+
+    // variable numbers are unique within a cfg, so give the callee variables new numbers
+    let mut vars = HashMap::new();
+
+    for (var_no, var) in &callee.vars {
+        let new_var_no = ns.next_id;
+        ns.next_id += 1;
+
+        vars.insert(*var_no, new_var_no);
+
+        cfg.vars.insert(
+            new_var_no,
+            Variable {
+                id: pt::Identifier {
+                    name: format!("{}.inline.{}", var.id.name, new_var_no),
+                    loc: var.id.loc,
+                },
+                ty: var.ty.clone(),
+                storage: var.storage.clone(),
+            },
+        );
+    }
+
+    // the arguments are evaluated once in the caller, like they are for a call
+    let mut params = Vec::new();
+
+    for (param, arg) in callee.params.iter().zip(args) {
+        let var_no = ns.next_id;
+        ns.next_id += 1;
+
+        let name = match param.name_as_str() {
+            "" => "arg",
+            name => name,
+        };
+
+        cfg.vars.insert(
+            var_no,
+            Variable {
+                id: pt::Identifier {
+                    name: format!("{name}.inline.{var_no}"),
+                    loc: pt::Loc::Codegen,
+                },
+                ty: param.ty.clone(),
+                storage: Storage::Local,
+            },
+        );
+
+        cfg.blocks[block_no].instr.push(Instr::Set {
+            loc: pt::Loc::Codegen,
+            res: var_no,
+            expr: arg,
+        });
+
+        params.push(var_no);
+    }
+
+    let first_block = cfg.blocks.len();
+    let return_block = first_block + callee.blocks.len();
+
+    cfg.blocks[block_no]
+        .instr
+        .push(Instr::Branch { block: first_block });
+
+    for block in &callee.blocks {
+        let mut instrs = Vec::new();
+
+        for instr in &block.instr {
+            let mut instr = instr.clone();
+
+            rename_instr(&mut instr, &vars, &params, first_block);
+
+            if let Instr::Return { value } = instr {
+                for (res, expr) in res.iter().zip(value) {
+                    instrs.push(Instr::Set {
+                        loc: pt::Loc::Codegen,
+                        res: *res,
+                        expr,
+                    });
+                }
+
+                instrs.push(Instr::Branch {
+                    block: return_block,
+                });
+            } else {
+                instrs.push(instr);
+            }
+        }
+
+        cfg.blocks.push(BasicBlock {
+            name: format!("{}.{}", callee_name, block.name),
+            instr: instrs,
+            phis: block
+                .phis
+                .as_ref()
+                .map(|phis| phis.iter().map(|var_no| vars[var_no]).collect()),
+            transfers: Vec::new(),
+            defs: IndexMap::new(),
+            loop_reaching_variables: HashSet::new(),
+        });
+    }
+
+    // The callee may return from more than one block, each setting the return values
+    cfg.blocks.push(BasicBlock {
+        name: format!("{callee_name}.return"),
+        instr: rest,
+        phis: if res.is_empty() {
+            None
+        } else {
+            Some(res.iter().copied().collect::<BTreeSet<usize>>())
+        },
+        transfers: Vec::new(),
+        defs: IndexMap::new(),
+        loop_reaching_variables: HashSet::new(),
+    });
+}
+
+/// Move an instruction from the callee into the caller: renumber the variables and blocks, and
+/// replace the function arguments with the variables holding them
+fn rename_instr(
+    instr: &mut Instr,
+    vars: &HashMap<usize, usize>,
+    params: &[usize],
+    first_block: usize,
+) {
+    match instr {
+        Instr::Set { res, .. }
+        | Instr::LoadStorage { res, .. }
+        | Instr::PushStorage { res, .. }
+        | Instr::AccountAccess { var_no: res, .. } => *res = vars[res],
+        Instr::Call { res, .. } => {
+            for res in res {
+                *res = vars[res];
+            }
+        }
+        Instr::PopStorage { res, .. }
+        | Instr::ExternalCall { success: res, .. }
+        | Instr::ValueTransfer { success: res, .. } => {
+            if let Some(res) = res {
+                *res = vars[res];
+            }
+        }
+        Instr::PushMemory { res, array, .. } | Instr::PopMemory { res, array, .. } => {
+            *res = vars[res];
+            *array = vars[array];
+        }
+        Instr::Constructor { success, res, .. } => {
+            if let Some(success) = success {
+                *success = vars[success];
+            }
+            *res = vars[res];
+        }
+        Instr::Branch { block } => *block += first_block,
+        Instr::BranchCond {
+            true_block,
+            false_block,
+            ..
+        } => {
+            *true_block += first_block;
+            *false_block += first_block;
+        }
+        Instr::Switch { cases, default, .. } => {
+            for (_, block) in cases {
+                *block += first_block;
+            }
+            *default += first_block;
+        }
+        _ => (),
+    }
+
+    for expr in instr.expressions_mut() {
+        rename_expr(expr, vars, params);
+    }
+}
+
+fn rename_expr(expr: &mut Expression, vars: &HashMap<usize, usize>, params: &[usize]) {
+    match expr {
+        Expression::Variable { var_no, .. } => *var_no = vars[var_no],
+        Expression::FunctionArg { loc, ty, arg_no } => {
+            *expr = Expression::Variable {
+                loc: *loc,
+                ty: ty.clone(),
+                var_no: params[*arg_no],
+            };
+        }
+        _ => {
+            for expr in expr.subexpressions_mut() {
+                rename_expr(expr, vars, params);
+            }
+        }
+    }
+}
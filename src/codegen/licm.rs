@@ -0,0 +1,680 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::cfg::{ControlFlowGraph, Instr};
+use super::reaching_definitions::{self, Transfer};
+use super::vartable::{Storage, Variable};
+use crate::codegen::{Builtin, Expression};
+use crate::sema::ast::{Namespace, RetrieveType, Type};
+use crate::sema::Recurse;
+use crate::Target;
+use indexmap::IndexMap;
+use solang_parser::pt::{self, CodeLocation};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/*
+  Loop-invariant code motion moves computations which give the same result on every iteration
+  of a loop to the block before the loop, the preheader. For example:
+
+    for (uint i = 0; i < arr.length; i++) {
+        s += m[x] + i;
+    }
+
+  Here the length of the storage array is loaded in the loop condition, and the storage slot
+  of m[x] is calculated with keccak256 on every iteration. The loop does not write to storage,
+  so the length can be loaded once before the loop, and the slot can be calculated once.
+
+  Loops are found using the dominator tree: an edge from a block to a block which dominates it
+  is a back edge, and the target of the back edge is the loop header. Only loops which are
+  entered from a single block, which ends with an unconditional branch to the header, are
+  optimized. That block is the preheader, and the hoisted instructions are inserted there.
+
+  Instructions which are hoisted as a whole are assignments to variables and storage loads.
+  The transfers of the reaching definitions tell us which variables are defined in the loop; an
+  expression is invariant if none of the variables it uses are defined in the loop. Any other
+  instructions in the loop have their invariant subexpressions replaced with a new variable,
+  which is set in the preheader.
+
+  The hoisted code is executed even if the loop body is not, so only expressions which cannot
+  fail are hoisted from the loop body. Checked arithmetic and division may only be hoisted from
+  the loop header, which is always executed when the loop is entered.
+*/
+
+/// Move loop-invariant computations out of the loops
+pub fn loop_invariant_code_motion(cfg: &mut ControlFlowGraph, ns: &mut Namespace) {
+    if cfg.blocks.is_empty() {
+        return;
+    }
+
+    let dominators = Dominators::new(cfg);
+    let loops = find_loops(cfg, &dominators);
+
+    if loops.is_empty() {
+        return;
+    }
+
+    // the previous passes may have changed the instructions, so the transfers are stale
+    refresh_definitions(cfg);
+
+    for lp in loops {
+        if hoist(cfg, &lp, &dominators, ns) {
+            refresh_definitions(cfg);
+        }
+    }
+}
+
+pub(super) fn refresh_definitions(cfg: &mut ControlFlowGraph) {
+    for block in &mut cfg.blocks {
+        block.defs = IndexMap::new();
+    }
+
+    reaching_definitions::find(cfg);
+}
+
+/// A natural loop with a preheader
+struct Loop {
+    header: usize,
+    preheader: usize,
+    blocks: BTreeSet<usize>,
+}
+
+/// The immediate dominator of each reachable block
+pub(super) struct Dominators {
+    idom: Vec<Option<usize>>,
+    pub(super) predecessors: Vec<Vec<usize>>,
+}
+
+impl Dominators {
+    /// Calculate the dominator tree using the algorithm from "A Simple, Fast Dominance
+    /// Algorithm" by Cooper, Harvey and Kennedy.
+    pub(super) fn new(cfg: &ControlFlowGraph) -> Self {
+        let mut predecessors = vec![Vec::new(); cfg.blocks.len()];
+
+        // reverse postorder of the reachable blocks
+        let mut postorder = Vec::new();
+        let mut visited = HashSet::from([0]);
+        let mut stack = vec![(0, cfg.blocks[0].successors(), 0)];
+
+        while let Some((block_no, successors, next)) = stack.last_mut() {
+            if let Some(succ) = successors.get(*next).copied() {
+                *next += 1;
+
+                predecessors[succ].push(*block_no);
+
+                if visited.insert(succ) {
+                    stack.push((succ, cfg.blocks[succ].successors(), 0));
+                }
+            } else {
+                postorder.push(*block_no);
+                stack.pop();
+            }
+        }
+
+        let mut order = vec![usize::MAX; cfg.blocks.len()];
+
+        for (no, block_no) in postorder.iter().enumerate() {
+            order[*block_no] = no;
+        }
+
+        let mut idom: Vec<Option<usize>> = vec![None; cfg.blocks.len()];
+        idom[0] = Some(0);
+
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for block_no in postorder.iter().rev().skip(1) {
+                let mut new_idom: Option<usize> = None;
+
+                for pred in &predecessors[*block_no] {
+                    if idom[*pred].is_none() {
+                        continue;
+                    }
+
+                    new_idom = Some(match new_idom {
+                        None => *pred,
+                        Some(mut other) => {
+                            let mut pred = *pred;
+
+                            while pred != other {
+                                while order[pred] < order[other] {
+                                    pred = idom[pred].unwrap();
+                                }
+                                while order[other] < order[pred] {
+                                    other = idom[other].unwrap();
+                                }
+                            }
+
+                            pred
+                        }
+                    });
+                }
+
+                if idom[*block_no] != new_idom {
+                    idom[*block_no] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+
+        Dominators { idom, predecessors }
+    }
+
+    /// Does block a dominate block b
+    pub(super) fn dominates(&self, a: usize, mut b: usize) -> bool {
+        loop {
+            if a == b {
+                return true;
+            }
+
+            match self.idom[b] {
+                Some(idom) if idom != b => b = idom,
+                _ => return false,
+            }
+        }
+    }
+}
+
+/// Find the loops which have a preheader, innermost loops first
+fn find_loops(cfg: &ControlFlowGraph, dominators: &Dominators) -> Vec<Loop> {
+    let mut loops: Vec<Loop> = Vec::new();
+
+    for (block_no, block) in cfg.blocks.iter().enumerate() {
+        if dominators.idom[block_no].is_none() {
+            continue;
+        }
+
+        for header in block.successors() {
+            if !dominators.dominates(header, block_no) {
+                continue;
+            }
+
+            // the loop body are the blocks from which the back edge can be reached without
+            // passing through the header
+            let mut blocks = BTreeSet::from([header]);
+            let mut todo = vec![block_no];
+
+            while let Some(block_no) = todo.pop() {
+                if blocks.insert(block_no) {
+                    todo.extend(&dominators.predecessors[block_no]);
+                }
+            }
+
+            if let Some(lp) = loops.iter_mut().find(|lp| lp.header == header) {
+                lp.blocks.extend(blocks);
+            } else {
+                loops.push(Loop {
+                    header,
+                    preheader: usize::MAX,
+                    blocks,
+                });
+            }
+        }
+    }
+
+    loops.retain_mut(|lp| {
+        let mut entries = dominators.predecessors[lp.header]
+            .iter()
+            .filter(|pred| !lp.blocks.contains(pred));
+
+        match (entries.next(), entries.next()) {
+            (Some(preheader), None)
+                if matches!(cfg.blocks[*preheader].instr.last(),
+                    Some(Instr::Branch { block }) if *block == lp.header) =>
+            {
+                lp.preheader = *preheader;
+                true
+            }
+            _ => false,
+        }
+    });
+
+    loops.sort_by_key(|lp| lp.blocks.len());
+
+    loops
+}
+
+/// What the loop does, which limits what can be hoisted
+struct LoopInfo {
+    /// The number of definitions of each variable in the loop
+    defs: HashMap<usize, usize>,
+    /// Storage is written or a function is called
+    writes_storage: bool,
+    /// Memory arrays may change length
+    resizes_memory: bool,
+}
+
+/// Hoist the loop-invariant instructions and expressions of a loop into its preheader. Returns
+/// true if anything was hoisted.
+fn hoist(
+    cfg: &mut ControlFlowGraph,
+    lp: &Loop,
+    dominators: &Dominators,
+    ns: &mut Namespace,
+) -> bool {
+    let mut info = LoopInfo {
+        defs: HashMap::new(),
+        writes_storage: false,
+        resizes_memory: false,
+    };
+
+    for block_no in &lp.blocks {
+        let block = &cfg.blocks[*block_no];
+
+        for transfer in block.transfers.iter().flatten() {
+            if let Transfer::Gen { var_no, .. }
+            | Transfer::Copy { var_no, .. }
+            | Transfer::Mod { var_no } = transfer
+            {
+                *info.defs.entry(*var_no).or_default() += 1;
+            }
+        }
+
+        for instr in &block.instr {
+            match instr {
+                Instr::SetStorage { .. }
+                | Instr::ClearStorage { .. }
+                | Instr::SetStorageBytes { .. }
+                | Instr::PopStorage { .. }
+                | Instr::ExternalCall { .. }
+                | Instr::Constructor { .. }
+                | Instr::ValueTransfer { .. }
+                | Instr::SelfDestruct { .. } => {
+                    info.writes_storage = true;
+                }
+                // On Solana, storage is account data, which can be written through memory
+                Instr::Store { .. } | Instr::MemCopy { .. } if ns.target == Target::Solana => {
+                    info.writes_storage = true;
+                }
+                Instr::PushStorage { res, .. } => {
+                    info.writes_storage = true;
+                    *info.defs.entry(*res).or_default() += 1;
+                }
+                Instr::Call { .. } => {
+                    info.writes_storage = true;
+                    info.resizes_memory = true;
+                }
+                Instr::PushMemory { .. } => {
+                    info.resizes_memory = true;
+                }
+                // These instructions set a variable, but have no transfer
+                Instr::PopMemory { res, .. } => {
+                    info.resizes_memory = true;
+                    *info.defs.entry(*res).or_default() += 1;
+                }
+                Instr::AccountAccess { var_no, .. } => {
+                    *info.defs.entry(*var_no).or_default() += 1;
+                }
+                _ => (),
+            }
+
+            if writes_raw_memory(instr) {
+                info.resizes_memory = true;
+            }
+        }
+    }
+
+    // First hoist whole instructions. Once an instruction is hoisted, the variable it sets is no
+    // longer defined in the loop, so repeat until nothing changes.
+    let mut hoisted = Vec::new();
+    let mut removed = HashSet::new();
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        for block_no in &lp.blocks {
+            for (instr_no, instr) in cfg.blocks[*block_no].instr.iter().enumerate() {
+                if removed.contains(&(*block_no, instr_no)) {
+                    continue;
+                }
+
+                let res = match instr {
+                    Instr::Set { res, expr, .. }
+                        if info.invariant(expr) && (*block_no == lp.header || !may_fail(expr)) =>
+                    {
+                        *res
+                    }
+                    Instr::LoadStorage { res, ty, storage }
+                        if *block_no == lp.header
+                            && !info.writes_storage
+                            && is_value_type(ty)
+                            && info.invariant(storage) =>
+                    {
+                        *res
+                    }
+                    _ => continue,
+                };
+
+                if info.defs.get(&res) != Some(&1)
+                    || !cfg.vars.get(&res).is_some_and(|var| is_value_type(&var.ty))
+                    || !uses_dominated(cfg, lp, dominators, &removed, res, (*block_no, instr_no))
+                {
+                    continue;
+                }
+
+                hoisted.push(instr.clone());
+                removed.insert((*block_no, instr_no));
+                info.defs.remove(&res);
+                changed = true;
+            }
+        }
+    }
+
+    // Now replace the invariant subexpressions of the remaining instructions
+    let mut temps: Vec<(Expression, usize)> = Vec::new();
+
+    for block_no in &lp.blocks {
+        let mut instrs = std::mem::take(&mut cfg.blocks[*block_no].instr);
+
+        for (instr_no, instr) in instrs.iter_mut().enumerate() {
+            if removed.contains(&(*block_no, instr_no)) {
+                continue;
+            }
+
+            for expr in instr.expressions_mut() {
+                extract(expr, *block_no == lp.header, &info, &mut temps, ns);
+            }
+        }
+
+        // remove the hoisted instructions, last first so the instruction numbers stay valid
+        for instr_no in (0..instrs.len()).rev() {
+            if removed.contains(&(*block_no, instr_no)) {
+                instrs.remove(instr_no);
+            }
+        }
+
+        cfg.blocks[*block_no].instr = instrs;
+    }
+
+    if hoisted.is_empty() && temps.is_empty() {
+        return false;
+    }
+
+    for (expr, var_no) in temps {
+        cfg.vars.insert(
+            var_no,
+            Variable {
+                id: pt::Identifier {
+                    loc: pt::Loc::Codegen,
+                    name: format!("licm.temp.{var_no}"),
+                },
+                ty: expr.ty(),
+                storage: Storage::Local,
+            },
+        );
+
+        hoisted.push(Instr::Set {
+            loc: pt::Loc::Codegen,
+            res: var_no,
+            expr,
+        });
+    }
+
+    let preheader = &mut cfg.blocks[lp.preheader].instr;
+    let branch = preheader.len() - 1;
+    preheader.splice(branch..branch, hoisted);
+
+    true
+}
+
+/// Check that the definition of a variable at the given instruction is executed before all its
+/// uses in the loop. If the definition is not in the loop header, the loop body might not be
+/// executed at all, so the variable should not be used after the loop.
+fn uses_dominated(
+    cfg: &ControlFlowGraph,
+    lp: &Loop,
+    dominators: &Dominators,
+    removed: &HashSet<(usize, usize)>,
+    var_no: usize,
+    def: (usize, usize),
+) -> bool {
+    for (block_no, block) in cfg.blocks.iter().enumerate() {
+        let in_loop = lp.blocks.contains(&block_no);
+
+        for (instr_no, instr) in block.instr.iter().enumerate() {
+            if removed.contains(&(block_no, instr_no)) || !uses_var(instr, var_no) {
+                continue;
+            }
+
+            if in_loop {
+                if !dominators.dominates(def.0, block_no)
+                    || (block_no == def.0 && instr_no <= def.1)
+                {
+                    return false;
+                }
+            } else if def.0 != lp.header {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Does the instruction write to memory through a raw pointer, like `mstore` or `calldatacopy` in
+/// inline assembly does. This can overwrite the length of any memory array. Writes into a memory
+/// array itself, like abi encoding into a new buffer, do not change its length.
+pub(super) fn writes_raw_memory(instr: &Instr) -> bool {
+    let mut dest = match instr {
+        Instr::WriteBuffer { buf, .. } => buf,
+        Instr::MemCopy { destination, .. } => destination,
+        _ => return false,
+    };
+
+    while let Expression::AdvancePointer { pointer, .. } = dest {
+        dest = pointer;
+    }
+
+    !matches!(dest, Expression::Variable { ty, .. } if ty.is_dynamic_memory())
+}
+
+/// Does the instruction read the variable
+fn uses_var(instr: &Instr, var_no: usize) -> bool {
+    if let Instr::PushMemory { array, .. } | Instr::PopMemory { array, .. } = instr {
+        if *array == var_no {
+            return true;
+        }
+    }
+
+    let mut uses = (var_no, false);
+
+    instr.recurse_expressions(&mut uses, |expr, uses| {
+        if matches!(expr, Expression::Variable { var_no, .. } if *var_no == uses.0) {
+            uses.1 = true;
+        }
+        true
+    });
+
+    uses.1
+}
+
+impl LoopInfo {
+    /// Does the expression give the same result on each iteration of the loop, without side
+    /// effects
+    fn invariant(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::NumberLiteral { .. } | Expression::BoolLiteral { .. } => true,
+            Expression::BytesLiteral { ty, .. } | Expression::FunctionArg { ty, .. } => {
+                is_value_type(ty)
+            }
+            Expression::Variable { ty, var_no, .. } => {
+                is_value_type(ty) && !self.defs.contains_key(var_no)
+            }
+            Expression::Add {
+                ty, left, right, ..
+            }
+            | Expression::Subtract {
+                ty, left, right, ..
+            }
+            | Expression::Multiply {
+                ty, left, right, ..
+            }
+            | Expression::Power {
+                ty,
+                base: left,
+                exp: right,
+                ..
+            }
+            | Expression::UnsignedDivide {
+                ty, left, right, ..
+            }
+            | Expression::SignedDivide {
+                ty, left, right, ..
+            }
+            | Expression::UnsignedModulo {
+                ty, left, right, ..
+            }
+            | Expression::SignedModulo {
+                ty, left, right, ..
+            }
+            | Expression::BitwiseAnd {
+                ty, left, right, ..
+            }
+            | Expression::BitwiseOr {
+                ty, left, right, ..
+            }
+            | Expression::BitwiseXor {
+                ty, left, right, ..
+            }
+            | Expression::ShiftLeft {
+                ty, left, right, ..
+            }
+            | Expression::ShiftRight {
+                ty, left, right, ..
+            } => is_value_type(ty) && self.invariant(left) && self.invariant(right),
+            Expression::Equal { left, right, .. }
+            | Expression::NotEqual { left, right, .. }
+            | Expression::Less { left, right, .. }
+            | Expression::LessEqual { left, right, .. }
+            | Expression::More { left, right, .. }
+            | Expression::MoreEqual { left, right, .. } => {
+                self.invariant(left) && self.invariant(right)
+            }
+            Expression::ZeroExt { ty, expr, .. }
+            | Expression::SignExt { ty, expr, .. }
+            | Expression::Trunc { ty, expr, .. }
+            | Expression::Cast { ty, expr, .. }
+            | Expression::BitwiseNot { ty, expr, .. }
+            | Expression::Negate { ty, expr, .. } => is_value_type(ty) && self.invariant(expr),
+            Expression::Not { expr, .. } => self.invariant(expr),
+            Expression::Keccak256 { exprs, .. } => exprs.iter().all(|expr| self.invariant(expr)),
+            // The length of a memory array only changes when it is pushed or popped
+            Expression::Builtin {
+                kind: Builtin::ArrayLength,
+                args,
+                ..
+            } if !self.resizes_memory && args.len() == 1 => match &args[0] {
+                Expression::Variable { ty, var_no, .. } => {
+                    ty.is_dynamic_memory() && !self.defs.contains_key(var_no)
+                }
+                Expression::FunctionArg { .. } => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Replace the largest invariant subexpressions with variables which are set in the preheader
+fn extract(
+    expr: &mut Expression,
+    in_header: bool,
+    info: &LoopInfo,
+    temps: &mut Vec<(Expression, usize)>,
+    ns: &mut Namespace,
+) {
+    if !is_trivial(expr) && info.invariant(expr) && (in_header || !may_fail(expr)) {
+        let var_no = match temps.iter().find(|(temp, _)| temp == expr) {
+            Some((_, var_no)) => *var_no,
+            None => {
+                let var_no = ns.next_id;
+                ns.next_id += 1;
+                temps.push((expr.clone(), var_no));
+                var_no
+            }
+        };
+
+        *expr = Expression::Variable {
+            loc: expr.loc(),
+            ty: expr.ty(),
+            var_no,
+        };
+
+        return;
+    }
+
+    for expr in expr.subexpressions_mut() {
+        extract(expr, in_header, info, temps, ns);
+    }
+}
+
+/// Is this a variable, constant or cast of those, which is not worth hoisting. Expressions
+/// which only use constants are left to constant folding.
+fn is_trivial(expr: &Expression) -> bool {
+    match expr {
+        Expression::ZeroExt { expr, .. }
+        | Expression::SignExt { expr, .. }
+        | Expression::Trunc { expr, .. }
+        | Expression::Cast { expr, .. } => is_trivial(expr),
+        Expression::NumberLiteral { .. }
+        | Expression::BoolLiteral { .. }
+        | Expression::BytesLiteral { .. }
+        | Expression::FunctionArg { .. }
+        | Expression::Variable { .. } => true,
+        _ => {
+            let mut constant = true;
+
+            expr.recurse(&mut constant, |expr, constant| {
+                if matches!(
+                    expr,
+                    Expression::Variable { .. } | Expression::FunctionArg { .. }
+                ) {
+                    *constant = false;
+                }
+                *constant
+            });
+
+            constant
+        }
+    }
+}
+
+/// Can evaluating the expression fail, because of an overflow or division by zero
+fn may_fail(expr: &Expression) -> bool {
+    let mut fails = false;
+
+    expr.recurse(&mut fails, |expr, fails| {
+        match expr {
+            Expression::Add { overflowing, .. }
+            | Expression::Subtract { overflowing, .. }
+            | Expression::Multiply { overflowing, .. }
+            | Expression::Power { overflowing, .. }
+            | Expression::Negate { overflowing, .. } => *fails |= !overflowing,
+            Expression::UnsignedDivide { .. }
+            | Expression::SignedDivide { .. }
+            | Expression::UnsignedModulo { .. }
+            | Expression::SignedModulo { .. } => *fails = true,
+            _ => (),
+        }
+        !*fails
+    });
+
+    fails
+}
+
+/// Only values which fit in a register are hoisted
+fn is_value_type(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Bool
+            | Type::Int(_)
+            | Type::Uint(_)
+            | Type::Bytes(_)
+            | Type::Address(_)
+            | Type::Contract(_)
+            | Type::Enum(_)
+            | Type::UserType(_)
+            | Type::StorageRef(..)
+    )
+}
@@ -2,6 +2,8 @@
 
 mod array_boundary;
 pub mod cfg;
+mod cfg_dot;
+pub mod cfg_parser;
 mod constant_folding;
 mod constructor;
 mod dead_storage;
@@ -10,6 +12,9 @@ pub(crate) mod encoding;
 mod events;
 mod expression;
 mod external_functions;
+mod inline;
+mod licm;
+pub mod passes;
 pub(super) mod polkadot;
 mod reaching_definitions;
 pub mod revert;
@@ -17,13 +22,16 @@ mod solana_accounts;
 mod solana_deploy;
 mod statements;
 mod storage;
+mod storage_cache;
 mod strength_reduce;
 pub(crate) mod subexpression_elimination;
 mod tests;
 mod undefined_variable;
+mod unreachable_functions;
 mod unused_variable;
 pub(crate) mod vartable;
 mod vector_to_slice;
+pub mod verify;
 mod yul;
 
 use self::{
@@ -38,8 +46,10 @@ use crate::sema::ast::{
 };
 use crate::{sema::ast, Target};
 use std::cmp::Ordering;
+use std::sync::Arc;
 
 use crate::codegen::cfg::ASTFunction;
+use crate::codegen::passes::{Pass, PassTimings};
 use crate::codegen::solana_accounts::account_management::manage_contract_accounts;
 use crate::codegen::yul::generate_yul_function_cfg;
 use crate::sema::diagnostics::Diagnostics;
@@ -53,6 +63,8 @@ use num_traits::{FromPrimitive, Zero};
 use solang_parser::diagnostics::Diagnostic;
 use solang_parser::{pt, pt::CodeLocation};
 
+pub(crate) use yul::YUL_MEMORY_SIZE;
+
 // The sizeof(struct account_data_header)
 pub const SOLANA_FIRST_OFFSET: u64 = 16;
 
@@ -95,6 +107,17 @@ impl From<inkwell::OptimizationLevel> for OptimizationLevel {
     }
 }
 
+/// How the dispatcher finds the function for the selector in the call data
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DispatchStrategy {
+    /// A single switch over all the selectors
+    Linear,
+    /// Compare with the middle of the sorted selectors, halving the candidates each time
+    BinarySearch,
+    /// Hash the selector into a jump table without collisions
+    PerfectHash,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Options {
     pub dead_storage: bool,
@@ -102,13 +125,23 @@ pub struct Options {
     pub strength_reduce: bool,
     pub vector_to_slice: bool,
     pub common_subexpression_elimination: bool,
+    pub inline: bool,
+    pub licm: bool,
+    pub storage_cache: bool,
     pub generate_debug_information: bool,
     pub opt_level: OptimizationLevel,
+    pub dispatch: DispatchStrategy,
     pub log_api_return_codes: bool,
     pub log_runtime_errors: bool,
     pub log_prints: bool,
     #[cfg(feature = "wasm_opt")]
     pub wasm_opt: Option<OptimizationPasses>,
+    /// Keep a copy of the CFGs after these passes, so they can be printed
+    pub print_after: Vec<Pass>,
+    /// Collect the time spent in each pass
+    pub time_passes: Option<Arc<PassTimings>>,
+    /// Check that the CFG is well formed after each pass
+    pub verify_cfg: bool,
 }
 
 impl Default for Options {
@@ -119,13 +152,30 @@ impl Default for Options {
             strength_reduce: true,
             vector_to_slice: true,
             common_subexpression_elimination: true,
+            inline: true,
+            licm: true,
+            storage_cache: true,
             generate_debug_information: false,
             opt_level: OptimizationLevel::Default,
+            dispatch: DispatchStrategy::Linear,
             log_api_return_codes: false,
             log_runtime_errors: false,
             log_prints: true,
             #[cfg(feature = "wasm_opt")]
             wasm_opt: None,
+            print_after: Vec::new(),
+            time_passes: None,
+            verify_cfg: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl Options {
+    /// Run the function, adding the time it took to the phase if `--time-passes` is enabled
+    pub fn time<T>(&self, phase: &str, f: impl FnOnce() -> T) -> T {
+        match &self.time_passes {
+            Some(timings) => timings.time(phase, f),
+            None => f(),
         }
     }
 }
@@ -252,11 +302,48 @@ fn contract(contract_no: usize, ns: &mut Namespace, opt: &Options) {
             ns.contracts[contract_no].default_constructor = Some((func, cfg_no));
         }
 
-        for mut dispatch_cfg in function_dispatch(contract_no, &all_cfg, ns, opt) {
-            optimize_and_check_cfg(&mut dispatch_cfg, ns, ASTFunction::None, opt);
+        unreachable_functions::report_unreachable_functions(contract_no, &all_cfg, ns);
+
+        if opt.inline {
+            let inlined = opt.time("inline", || inline::inline(&mut all_cfg, ns));
+
+            // the inlined function bodies can now be optimized together with their caller. The
+            // diagnostics have already been reported for the functions as written. An inlined body
+            // is specialized to its call site, so it may fold into errors which never occur at
+            // runtime, like a division by zero in a branch which is not taken. In that case, keep
+            // the cfg as it was, so that the runtime checks are not folded away.
+            let diagnostics = std::mem::take(&mut ns.diagnostics);
+            let var_constants = std::mem::take(&mut ns.var_constants);
+
+            for cfg_no in inlined {
+                let func_no = match all_cfg[cfg_no].modifier {
+                    Some(func_no) => ASTFunction::SolidityFunction(func_no),
+                    None => all_cfg[cfg_no].function_no,
+                };
+                let mut cfg = all_cfg[cfg_no].clone();
+
+                optimize_and_check_cfg(&mut cfg, contract_no, ns, func_no, opt);
+
+                if !std::mem::take(&mut ns.diagnostics).any_errors() {
+                    all_cfg[cfg_no] = cfg;
+                }
+            }
+
+            ns.diagnostics = diagnostics;
+            ns.var_constants = var_constants;
+        }
+
+        let dispatch_cfgs = opt.time("function-dispatch", || {
+            function_dispatch(contract_no, &all_cfg, ns, opt)
+        });
+
+        for mut dispatch_cfg in dispatch_cfgs {
+            optimize_and_check_cfg(&mut dispatch_cfg, contract_no, ns, ASTFunction::None, opt);
             all_cfg.push(dispatch_cfg);
         }
 
+        unreachable_functions::remove_unreachable_cfgs(&mut all_cfg);
+
         ns.contracts[contract_no].cfg = all_cfg;
     }
 }
@@ -296,7 +383,7 @@ fn storage_initializer(contract_no: usize, ns: &mut Namespace, opt: &Options) ->
 
     vartab.finalize(ns, &mut cfg);
 
-    optimize_and_check_cfg(&mut cfg, ns, ASTFunction::None, opt);
+    optimize_and_check_cfg(&mut cfg, contract_no, ns, ASTFunction::None, opt);
 
     cfg
 }
@@ -309,10 +396,15 @@ fn layout(contract_no: usize, ns: &mut Namespace) {
         BigInt::zero()
     };
 
+    // On Polkadot, consecutive small values are packed into one storage slot. This is the number
+    // of bytes used in the current slot.
+    let mut packed_bytes = 0;
+
     for base_contract_no in ns.contract_bases(contract_no) {
         for var_no in 0..ns.contracts[base_contract_no].variables.len() {
             if !ns.contracts[base_contract_no].variables[var_no].constant {
                 let ty = ns.contracts[base_contract_no].variables[var_no].ty.clone();
+                let mut offset = None;
 
                 if ns.target == Target::Solana {
                     // elements need to be aligned on solana
@@ -323,16 +415,48 @@ fn layout(contract_no: usize, ns: &mut Namespace) {
                     if offset > BigInt::zero() {
                         slot += alignment - offset;
                     }
+                } else if let Some(size) = packed_size(&ty, ns) {
+                    if packed_bytes + size > POLKADOT_SLOT_SIZE {
+                        slot += 1;
+                        packed_bytes = 0;
+                    }
+
+                    offset = Some(packed_bytes);
+                    packed_bytes += size;
+                } else if packed_bytes > 0 {
+                    slot += 1;
+                    packed_bytes = 0;
                 }
 
                 ns.contracts[contract_no].layout.push(Layout {
                     slot: slot.clone(),
+                    offset,
                     contract_no: base_contract_no,
                     var_no,
                     ty: ty.clone(),
                 });
 
-                slot += ty.storage_slots(ns);
+                if offset.is_none() {
+                    slot += ty.storage_slots(ns);
+                }
+            }
+        }
+    }
+
+    if packed_bytes > 0 {
+        slot += 1;
+    }
+
+    // A small value which has a storage slot to itself is stored as usual
+    let layout = &mut ns.contracts[contract_no].layout;
+
+    for i in 0..layout.len() {
+        if layout[i].offset.is_some() {
+            let shared = (i > 0 && layout[i - 1].slot == layout[i].slot)
+                || (i + 1 < layout.len() && layout[i + 1].slot == layout[i].slot);
+
+            if !shared {
+                layout[i].offset = None;
             }
         }
     }
@@ -361,6 +485,31 @@ fn layout(contract_no: usize, ns: &mut Namespace) {
     ns.contracts[contract_no].fixed_layout_size = slot;
 }
 
+/// The size of a storage slot on Polkadot, in bytes
+const POLKADOT_SLOT_SIZE: usize = 32;
+
+/// If values of this type can be packed into a storage slot with other values on Polkadot, return
+/// its size in bytes
+fn packed_size(ty: &Type, ns: &Namespace) -> Option<usize> {
+    if !ns.target.is_polkadot() {
+        return None;
+    }
+
+    match ty {
+        Type::Bool | Type::Int(_) | Type::Uint(_) | Type::Bytes(_) | Type::Enum(_) => {
+            let size = ty.bytes(ns) as usize;
+
+            if size < POLKADOT_SLOT_SIZE {
+                Some(size)
+            } else {
+                None
+            }
+        }
+        Type::UserType(no) => packed_size(&ns.user_types[*no].ty, ns),
+        _ => None,
+    }
+}
+
 trait LLVMName {
     fn llvm_symbol(&self, ns: &Namespace) -> String;
 }
@@ -564,6 +713,13 @@ pub enum Expression {
         ty: Type,
         value: BigInt,
     },
+    /// A small storage variable which shares its storage slot with other variables
+    PackedStorageSlot {
+        loc: pt::Loc,
+        ty: Type,
+        slot: BigInt,
+        offset: usize,
+    },
     Poison,
     Power {
         loc: pt::Loc,
@@ -671,6 +827,7 @@ impl CodeLocation for Expression {
             | Expression::Builtin { loc, .. }
             | Expression::Cast { loc, .. }
             | Expression::NumberLiteral { loc, .. }
+            | Expression::PackedStorageSlot { loc, .. }
             | Expression::Keccak256 { loc, .. }
             | Expression::MoreEqual { loc, .. }
             | Expression::ReturnData { loc }
@@ -832,6 +989,7 @@ impl RetrieveType for Expression {
             | Expression::BytesLiteral { ty, .. }
             | Expression::Add { ty, .. }
             | Expression::NumberLiteral { ty, .. }
+            | Expression::PackedStorageSlot { ty, .. }
             | Expression::Multiply { ty, .. }
             | Expression::Subtract { ty, .. }
             | Expression::SignedDivide { ty, .. }
@@ -876,6 +1034,88 @@ impl RetrieveType for Expression {
 }
 
 impl Expression {
+    /// The operands of an expression
+    pub(crate) fn subexpressions_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Expression::Add { left, right, .. }
+            | Expression::BitwiseAnd { left, right, .. }
+            | Expression::BitwiseOr { left, right, .. }
+            | Expression::BitwiseXor { left, right, .. }
+            | Expression::UnsignedDivide { left, right, .. }
+            | Expression::SignedDivide { left, right, .. }
+            | Expression::Equal { left, right, .. }
+            | Expression::Less { left, right, .. }
+            | Expression::LessEqual { left, right, .. }
+            | Expression::UnsignedModulo { left, right, .. }
+            | Expression::SignedModulo { left, right, .. }
+            | Expression::More { left, right, .. }
+            | Expression::MoreEqual { left, right, .. }
+            | Expression::Multiply { left, right, .. }
+            | Expression::NotEqual { left, right, .. }
+            | Expression::ShiftLeft { left, right, .. }
+            | Expression::ShiftRight { left, right, .. }
+            | Expression::Subtract { left, right, .. }
+            | Expression::Power {
+                base: left,
+                exp: right,
+                ..
+            }
+            | Expression::Subscript {
+                expr: left,
+                index: right,
+                ..
+            }
+            | Expression::AdvancePointer {
+                pointer: left,
+                bytes_offset: right,
+            } => vec![left.as_mut(), right.as_mut()],
+
+            Expression::AllocDynamicBytes { size: expr, .. }
+            | Expression::BytesCast { expr, .. }
+            | Expression::Cast { expr, .. }
+            | Expression::BitwiseNot { expr, .. }
+            | Expression::GetRef { expr, .. }
+            | Expression::Load { expr, .. }
+            | Expression::Not { expr, .. }
+            | Expression::SignExt { expr, .. }
+            | Expression::StorageArrayLength { array: expr, .. }
+            | Expression::StructMember { expr, .. }
+            | Expression::Trunc { expr, .. }
+            | Expression::Negate { expr, .. }
+            | Expression::ZeroExt { expr, .. } => vec![expr.as_mut()],
+
+            Expression::ArrayLiteral { values, .. }
+            | Expression::ConstArrayLiteral { values, .. }
+            | Expression::StructLiteral { values, .. }
+            | Expression::Builtin { args: values, .. }
+            | Expression::Keccak256 { exprs: values, .. } => values.iter_mut().collect(),
+
+            Expression::FormatString { args, .. } => {
+                args.iter_mut().map(|(_, expr)| expr).collect()
+            }
+
+            Expression::StringCompare { left, right, .. } => [left, right]
+                .into_iter()
+                .filter_map(|string| match string {
+                    StringLocation::RunTime(expr) => Some(expr.as_mut()),
+                    StringLocation::CompileTime(_) => None,
+                })
+                .collect(),
+
+            Expression::BoolLiteral { .. }
+            | Expression::BytesLiteral { .. }
+            | Expression::FunctionArg { .. }
+            | Expression::InternalFunctionCfg { .. }
+            | Expression::NumberLiteral { .. }
+            | Expression::PackedStorageSlot { .. }
+            | Expression::Poison
+            | Expression::RationalNumberLiteral { .. }
+            | Expression::ReturnData { .. }
+            | Expression::Undefined { .. }
+            | Expression::Variable { .. } => Vec::new(),
+        }
+    }
+
     /// Increment an expression by some value.
     pub(crate) fn add_u32(self, other: Expression) -> Self {
         Expression::Add {
@@ -1716,6 +1956,9 @@ pub enum Builtin {
     Accounts,
     AddMod,
     ArrayLength,
+    /// A slice of a memory array, with the arguments array, start and end. The bounds must
+    /// have been checked already.
+    ArraySlice,
     Balance,
     Blake2_128,
     Blake2_256,
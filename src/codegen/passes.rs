@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The codegen optimization passes, whose output can be printed with `--print-after`
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Pass {
+    ConstantFolding,
+    VectorToSlice,
+    StrengthReduce,
+    DeadStorage,
+    StorageCache,
+    CommonSubexpressionElimination,
+    LoopInvariantCodeMotion,
+}
+
+impl Pass {
+    /// All the passes, in the order they are run
+    pub const ALL: [Pass; 7] = [
+        Pass::ConstantFolding,
+        Pass::VectorToSlice,
+        Pass::StrengthReduce,
+        Pass::DeadStorage,
+        Pass::StorageCache,
+        Pass::CommonSubexpressionElimination,
+        Pass::LoopInvariantCodeMotion,
+    ];
+
+    /// The name of the pass, as used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pass::ConstantFolding => "constant-folding",
+            Pass::VectorToSlice => "vector-to-slice",
+            Pass::StrengthReduce => "strength-reduce",
+            Pass::DeadStorage => "dead-storage",
+            Pass::StorageCache => "storage-cache",
+            Pass::CommonSubexpressionElimination => "cse",
+            Pass::LoopInvariantCodeMotion => "licm",
+        }
+    }
+}
+
+impl fmt::Display for Pass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Pass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pass::ALL
+            .into_iter()
+            .find(|pass| pass.name() == s)
+            .ok_or_else(|| format!("unknown pass '{s}'"))
+    }
+}
+
+/// Time spent in each phase of the compiler, for `--time-passes`. Phases which are started
+/// while another one is running are nested under it in the report.
+#[derive(Debug, Default)]
+pub struct PassTimings {
+    state: Mutex<TimingState>,
+}
+
+#[derive(Debug, Default)]
+struct TimingState {
+    /// The phases in the order they were first started, with their nesting depth
+    phases: Vec<(String, usize, Duration)>,
+    running: HashSet<String>,
+}
+
+impl PassTimings {
+    /// Run the function and add the time it took to the phase. If the phase is already running,
+    /// e.g. when a contract is emitted while emitting the contract which creates it, the
+    /// time is already accounted for.
+    pub fn time<T>(&self, phase: &str, f: impl FnOnce() -> T) -> T {
+        let index = {
+            let mut state = self.state.lock().unwrap();
+
+            if !state.running.insert(phase.to_owned()) {
+                drop(state);
+                return f();
+            }
+
+            match state.phases.iter().position(|(name, ..)| name == phase) {
+                Some(index) => index,
+                None => {
+                    let depth = state.running.len() - 1;
+                    state.phases.push((phase.to_owned(), depth, Duration::ZERO));
+                    state.phases.len() - 1
+                }
+            }
+        };
+
+        let start = Instant::now();
+
+        let res = f();
+
+        let mut state = self.state.lock().unwrap();
+
+        state.phases[index].2 += start.elapsed();
+        state.running.remove(phase);
+
+        res
+    }
+
+    /// The time spent in each phase, with the nested phases indented
+    pub fn report(&self) -> String {
+        let state = self.state.lock().unwrap();
+
+        let mut out = String::from("time-passes:\n");
+
+        for (name, depth, duration) in &state.phases {
+            writeln!(
+                out,
+                "{:>12.3}ms  {}{}",
+                duration.as_secs_f64() * 1000.0,
+                "  ".repeat(*depth),
+                name
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+/// Only the same instance is equal, so that options with timing enabled can be compared
+impl PartialEq for PassTimings {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pass, PassTimings};
+
+    #[test]
+    fn pass_names() {
+        for pass in Pass::ALL {
+            assert_eq!(pass.name().parse::<Pass>(), Ok(pass));
+        }
+
+        assert_eq!(
+            "inline".parse::<Pass>(),
+            Err("unknown pass 'inline'".to_owned())
+        );
+    }
+
+    #[test]
+    fn nested_timings() {
+        let timings = PassTimings::default();
+
+        timings.time("codegen", || {
+            timings.time("constant-folding", || ());
+            // a phase which is already running is not timed twice
+            timings.time("codegen", || ());
+            timings.time("constant-folding", || ());
+        });
+        timings.time("emit", || ());
+
+        let report = timings.report();
+        let phases: Vec<&str> = report
+            .lines()
+            .skip(1)
+            .map(|line| line.split_once("ms  ").unwrap().1)
+            .collect();
+
+        assert_eq!(phases, ["codegen", "  constant-folding", "emit"]);
+    }
+}
@@ -107,12 +107,24 @@ impl RetCodeCheck {
     ) {
         cfg.set_basic_block(self.error_no_data);
         log_runtime_error(opt.log_runtime_errors, self.msg, self.loc, cfg, vartab, ns);
-        cfg.add(vartab, Instr::AssertFailure { encoded_args: None });
+        cfg.add(
+            vartab,
+            Instr::AssertFailure {
+                encoded_args: None,
+                error_code: None,
+            },
+        );
 
         cfg.set_basic_block(self.revert);
         log_runtime_error(opt.log_runtime_errors, self.msg, self.loc, cfg, vartab, ns);
         let encoded_args = Expression::ReturnData { loc: self.loc }.into();
-        cfg.add(vartab, Instr::AssertFailure { encoded_args });
+        cfg.add(
+            vartab,
+            Instr::AssertFailure {
+                encoded_args,
+                error_code: None,
+            },
+        );
 
         cfg.set_basic_block(self.success);
     }
@@ -165,7 +177,13 @@ pub(super) fn check_transfer_ret(
     cfg.set_basic_block(fail_block);
     let msg = "value transfer failure";
     log_runtime_error(opt.log_runtime_errors, msg, *loc, cfg, vartab, ns);
-    cfg.add(vartab, Instr::AssertFailure { encoded_args: None });
+    cfg.add(
+        vartab,
+        Instr::AssertFailure {
+            encoded_args: None,
+            error_code: None,
+        },
+    );
 
     cfg.set_basic_block(success_block);
 
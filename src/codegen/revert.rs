@@ -3,7 +3,7 @@
 //! Releated to code that ultimately compiles to the target
 //! equivalent instruction of EVM revert (0xfd).
 
-use super::encoding::{abi_encode, create_encoder};
+use super::encoding::{abi_encode_with_selector, const_encode_with_selector};
 use super::expression::expression;
 use super::Options;
 use super::{
@@ -24,9 +24,11 @@ use solang_parser::pt::{CodeLocation, Loc, Loc::Codegen};
 use tiny_keccak::{Hasher, Keccak};
 
 /// Signature of `Keccak256('Error(string)')[:4]`
-pub(crate) const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
 /// Signature of `Keccak256('Panic(uint256)')[:4]`
-pub(crate) const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+/// Custom errors on Solana are numbered from here, like Anchor does for `#[error_code]`
+pub const SOLANA_CUSTOM_ERROR_OFFSET: u32 = 6000;
 
 /// Corresponds to the error types from the Solidity language.
 ///
@@ -85,82 +87,49 @@ impl SolidityError {
         vartab: &mut Vartable,
         cfg: &mut ControlFlowGraph,
     ) -> Option<Expression> {
-        match self {
-            Self::Empty => None,
-            Self::String(expr) => {
-                let args = vec![self.selector_expression(ns), expr.clone()];
-                create_encoder(ns, false)
-                    .const_encode(&args)
-                    .map(|bytes| {
-                        let size = Expression::NumberLiteral {
-                            loc: Codegen,
-                            ty: Type::Uint(32),
-                            value: bytes.len().into(),
-                        };
-                        Expression::AllocDynamicBytes {
-                            loc: Codegen,
-                            ty: Type::Slice(Type::Bytes(1).into()),
-                            size: size.into(),
-                            initializer: bytes.into(),
-                        }
-                    })
-                    .or_else(|| abi_encode(loc, args, ns, vartab, cfg, false).0.into())
-            }
-            Self::Custom { exprs, .. } => {
-                let mut args = exprs.to_owned();
-                args.insert(0, self.selector_expression(ns));
-                create_encoder(ns, false)
-                    .const_encode(&args)
-                    .map(|bytes| {
-                        let size = Expression::NumberLiteral {
-                            loc: Codegen,
-                            ty: Type::Uint(32),
-                            value: bytes.len().into(),
-                        };
-                        Expression::AllocDynamicBytes {
-                            loc: Codegen,
-                            ty: Type::Slice(Type::Bytes(1).into()),
-                            size: size.into(),
-                            initializer: bytes.into(),
-                        }
-                    })
-                    .or_else(|| abi_encode(loc, args, ns, vartab, cfg, false).0.into())
-            }
-            Self::Panic(code) => {
-                let code = Expression::NumberLiteral {
-                    loc: Codegen,
-                    ty: Type::Uint(256),
-                    value: (*code as u8).into(),
-                };
-                create_encoder(ns, false)
-                    .const_encode(&[self.selector_expression(ns), code])
-                    .map(|bytes| {
-                        let size = Expression::NumberLiteral {
-                            loc: Codegen,
-                            ty: Type::Uint(32),
-                            value: bytes.len().into(),
-                        };
-                        Expression::AllocDynamicBytes {
-                            loc: Codegen,
-                            ty: Type::Slice(Type::Bytes(1).into()),
-                            size: size.into(),
-                            initializer: bytes.into(),
-                        }
-                    })
+        let args = match self {
+            Self::Empty => return None,
+            Self::String(expr) => vec![expr.clone()],
+            Self::Custom { exprs, .. } => exprs.to_owned(),
+            Self::Panic(code) => vec![Expression::NumberLiteral {
+                loc: Codegen,
+                ty: Type::Uint(256),
+                value: (*code as u8).into(),
+            }],
+        };
+        let encoded = const_encode_with_selector(&self.selector(ns), &args, ns).map(|bytes| {
+            let size = Expression::NumberLiteral {
+                loc: Codegen,
+                ty: Type::Uint(32),
+                value: bytes.len().into(),
+            };
+            Expression::AllocDynamicBytes {
+                loc: Codegen,
+                ty: Type::Slice(Type::Bytes(1).into()),
+                size: size.into(),
+                initializer: bytes.into(),
             }
+        });
+
+        // Panics are only reported if their data can be encoded at compile time
+        if matches!(self, Self::Panic(_)) {
+            return encoded;
         }
+
+        encoded.or_else(|| {
+            let selector = self.selector_expression(ns);
+            abi_encode_with_selector(loc, selector, args, ns, vartab, cfg)
+                .0
+                .into()
+        })
     }
 }
 
 /// Solidity `Panic` Codes. Source:
 /// https://docs.soliditylang.org/en/v0.8.20/control-structures.html#panic-via-assert-and-error-via-require
 ///
-/// FIXME: Currently, not all panic variants are wired up yet in Solang:
-/// * EnumCastOob
-/// * StorageBytesEncodingIncorrect
-/// * OutOfMemory
-///
-/// Tracking issue: <https://github.com/hyperledger/solang/issues/1477>
+/// `OutOfMemory` is raised by the heap allocator in `stdlib/heap.c`. On Solana, the allocator
+/// cannot return an error code, so running out of heap aborts the transaction instead.
 #[derive(Display, Debug, PartialEq, Clone, Copy)]
 #[non_exhaustive]
 #[repr(u8)]
@@ -186,14 +155,53 @@ pub(super) fn assert_failure(
     cfg: &mut ControlFlowGraph,
     vartab: &mut Vartable,
 ) {
-    // On Solana, returning the encoded arguments has no effect
-    if ns.target == Target::Solana {
-        cfg.add(vartab, Instr::AssertFailure { encoded_args: None });
-        return;
-    }
-
     let encoded_args = error.abi_encode(loc, ns, vartab, cfg);
-    cfg.add(vartab, Instr::AssertFailure { encoded_args })
+    cfg.add(
+        vartab,
+        Instr::AssertFailure {
+            encoded_args,
+            error_code: None,
+        },
+    )
+}
+
+/// Revert with the custom error `error_no`. On Solana, the program also fails with the
+/// error code of the custom error in the contract.
+fn custom_error_failure(
+    loc: &Loc,
+    error_no: usize,
+    exprs: Vec<Expression>,
+    contract_no: usize,
+    ns: &Namespace,
+    cfg: &mut ControlFlowGraph,
+    vartab: &mut Vartable,
+) {
+    let error_code = if ns.target == Target::Solana {
+        Some(solana_error_code(contract_no, error_no, ns))
+    } else {
+        None
+    };
+    let encoded_args = SolidityError::Custom { error_no, exprs }.abi_encode(loc, ns, vartab, cfg);
+    cfg.add(
+        vartab,
+        Instr::AssertFailure {
+            encoded_args,
+            error_code,
+        },
+    )
+}
+
+/// The `ProgramError::Custom` code of the custom error `error_no` in the contract on Solana.
+/// The errors a contract may revert with are numbered in order of declaration, so the code
+/// is stable between compilations.
+pub fn solana_error_code(contract_no: usize, error_no: usize, ns: &Namespace) -> u32 {
+    let pos = ns.contracts[contract_no]
+        .custom_errors
+        .iter()
+        .position(|no| *no == error_no)
+        .expect("contract should list the custom errors it reverts with");
+
+    SOLANA_CUSTOM_ERROR_OFFSET + pos as u32
 }
 
 pub(super) fn expr_assert(
@@ -253,6 +261,28 @@ pub(super) fn require(
         },
     );
     cfg.set_basic_block(false_);
+
+    // require(cond, CustomError(...)) reverts with the custom error
+    if let Some(ast::Expression::CustomError {
+        error_no,
+        args: error_args,
+        ..
+    }) = args.get(1)
+    {
+        let exprs = error_args
+            .iter()
+            .map(|s| expression(s, cfg, contract_no, func, ns, vartab, opt))
+            .collect::<Vec<_>>();
+
+        let reason = format!("{} require condition failed", ns.errors[*error_no].name);
+        log_runtime_error(opt.log_runtime_errors, &reason, loc, cfg, vartab, ns);
+
+        custom_error_failure(&Codegen, *error_no, exprs, contract_no, ns, cfg, vartab);
+
+        cfg.set_basic_block(true_);
+        return Expression::Poison;
+    }
+
     let expr = args
         .get(1)
         .map(|s| expression(s, cfg, contract_no, func, ns, vartab, opt));
@@ -371,7 +401,10 @@ pub(super) fn revert(
 
     let error = match (*error_no, exprs.get(0)) {
         // Having an error number requires a custom error
-        (Some(error_no), _) => SolidityError::Custom { error_no, exprs },
+        (Some(error_no), _) => {
+            custom_error_failure(&Codegen, error_no, exprs, contract_no, ns, cfg, vartab);
+            return;
+        }
         // No error number but an expression requires Error(String)
         (None, Some(expr)) => SolidityError::String(expr.clone()),
         // No error number and no data means just "revert();" without any reason
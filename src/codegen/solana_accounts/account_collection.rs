@@ -148,11 +148,8 @@ pub(crate) fn collect_accounts_from_contract(contract_no: usize, ns: &Namespace)
 
             recurse_data.contract_no = *contract_no;
             recurse_data.cfg_func_no = *func_no;
-            match &ns.contracts[*contract_no].cfg[*func_no].function_no {
-                ASTFunction::SolidityFunction(ast_no) | ASTFunction::YulFunction(ast_no) => {
-                    recurse_data.ast_no = *ast_no;
-                }
-                _ => (),
+            if let Some(ast_no) = ast_function_no(&ns.contracts[*contract_no].cfg[*func_no]) {
+                recurse_data.ast_no = ast_no;
             }
             check_function(&ns.contracts[*contract_no].cfg[*func_no], &mut recurse_data);
         }
@@ -173,6 +170,15 @@ pub(crate) fn collect_accounts_from_contract(contract_no: usize, ns: &Namespace)
     diagnostics
 }
 
+/// The AST function which holds the accounts of a cfg. The cfgs of a modifier chain use the
+/// accounts of the function the modifiers are applied to.
+fn ast_function_no(cfg: &ControlFlowGraph) -> Option<usize> {
+    match cfg.function_no {
+        ASTFunction::SolidityFunction(ast_no) | ASTFunction::YulFunction(ast_no) => Some(ast_no),
+        ASTFunction::None => cfg.modifier,
+    }
+}
+
 /// Collect the accounts in a function
 fn check_function(cfg: &ControlFlowGraph, data: &mut RecurseData) {
     if cfg.blocks.is_empty() {
@@ -218,15 +224,13 @@ fn check_instruction(instr: &Instr, data: &mut RecurseData) {
                 // recursive function calls
                 data.next_queue.insert((data.contract_no, *cfg_no));
                 data.next_queue.insert((data.contract_no, data.cfg_func_no));
-                match &data.contracts[data.contract_no].cfg[*cfg_no].function_no {
-                    ASTFunction::SolidityFunction(ast_no) | ASTFunction::YulFunction(ast_no) => {
-                        let accounts_to_add =
-                            data.functions[*ast_no].solana_accounts.borrow().clone();
-                        for (account_name, account) in accounts_to_add {
-                            data.add_account(account_name, &account);
-                        }
+                if let Some(ast_no) =
+                    ast_function_no(&data.contracts[data.contract_no].cfg[*cfg_no])
+                {
+                    let accounts_to_add = data.functions[ast_no].solana_accounts.borrow().clone();
+                    for (account_name, account) in accounts_to_add {
+                        data.add_account(account_name, &account);
                     }
-                    _ => (),
                 }
             } else if let InternalCallTy::Builtin { ast_func_no } = call {
                 let name = &data.functions[*ast_func_no].id.name;
@@ -257,7 +261,7 @@ fn check_instruction(instr: &Instr, data: &mut RecurseData) {
             store_data.recurse(data, check_expression);
         }
 
-        Instr::AssertFailure { encoded_args } => {
+        Instr::AssertFailure { encoded_args, .. } => {
             if let Some(args) = encoded_args {
                 args.recurse(data, check_expression);
             }
@@ -4,7 +4,7 @@ use super::{statement, Builtin, LoopScopes, Options};
 use crate::codegen::{
     cfg::{ControlFlowGraph, Instr},
     constructor::call_constructor,
-    encoding::{abi_decode, abi_encode},
+    encoding::{abi_decode, abi_decode_with_selector, abi_encode_with_selector},
     expression::{default_gas, expression},
     polkadot,
     revert::{ERROR_SELECTOR, PANIC_SELECTOR},
@@ -12,9 +12,9 @@ use crate::codegen::{
     Expression,
 };
 use crate::sema::ast::{
-    self, CallTy, ExternalCallAccounts, Function, Namespace, RetrieveType, TryCatch, Type,
-    Type::Uint,
+    self, CallTy, Function, Namespace, RetrieveType, TryCatch, Type, Type::Uint,
 };
+use crate::Target;
 use num_bigint::{BigInt, Sign};
 use num_traits::Zero;
 use solang_parser::pt::{self, CodeLocation, Loc::Codegen};
@@ -32,10 +32,6 @@ pub(super) fn try_catch(
     return_override: Option<&Instr>,
     opt: &Options,
 ) {
-    if !ns.target.is_polkadot() {
-        unimplemented!()
-    }
-
     let ok_block = cfg.new_basic_block("ok".to_string());
     let catch_block = cfg.new_basic_block("catch".to_string());
     let finally_block = cfg.new_basic_block("finally".to_string());
@@ -123,26 +119,28 @@ fn insert_try_expression(
 
     vartab.new_dirty_tracker();
 
-    cfg.set_basic_block(cases.error_no_data);
-    cfg.add(
-        vartab,
-        Instr::Set {
-            loc: Codegen,
-            res: error_ret_data_var,
-            expr: Expression::AllocDynamicBytes {
+    if let Some(error_no_data) = cases.error_no_data {
+        cfg.set_basic_block(error_no_data);
+        cfg.add(
+            vartab,
+            Instr::Set {
                 loc: Codegen,
-                ty: Type::DynamicBytes,
-                size: Expression::NumberLiteral {
+                res: error_ret_data_var,
+                expr: Expression::AllocDynamicBytes {
                     loc: Codegen,
-                    ty: Uint(32),
-                    value: 0.into(),
-                }
-                .into(),
-                initializer: Some(vec![]),
+                    ty: Type::DynamicBytes,
+                    size: Expression::NumberLiteral {
+                        loc: Codegen,
+                        ty: Uint(32),
+                        value: 0.into(),
+                    }
+                    .into(),
+                    initializer: Some(vec![]),
+                },
             },
-        },
-    );
-    cfg.add(vartab, Instr::Branch { block: catch_block });
+        );
+        cfg.add(vartab, Instr::Branch { block: catch_block });
+    }
 
     cfg.set_basic_block(cases.revert);
     cfg.add(
@@ -194,7 +192,7 @@ fn exec_try(
     ns: &Namespace,
     vartab: &mut Vartable,
     opt: &Options,
-) -> (polkadot::RetCodeCheck, Vec<Type>) {
+) -> (TryCases, Vec<Type>) {
     let success = vartab.temp(
         &pt::Identifier {
             loc: try_stmt.expr.loc(),
@@ -215,6 +213,17 @@ fn exec_try(
                 ..
             } = function.ty()
             {
+                // On Solana, the accounts for the call can be derived from the called function
+                let contract_function_no = match function.as_ref() {
+                    ast::Expression::ExternalFunction { function_no, .. }
+                        if ns.target == Target::Solana =>
+                    {
+                        ns.functions[*function_no]
+                            .contract_no
+                            .map(|contract_no| (contract_no, *function_no))
+                    }
+                    _ => None,
+                };
                 let value = if let Some(value) = &call_args.value {
                     expression(value, cfg, callee_contract_no, Some(func), ns, vartab, opt)
                 } else {
@@ -239,7 +248,7 @@ fn exec_try(
                     opt,
                 );
 
-                let mut args = args
+                let args = args
                     .iter()
                     .map(|a| expression(a, cfg, callee_contract_no, Some(func), ns, vartab, opt))
                     .collect::<Vec<Expression>>();
@@ -248,12 +257,17 @@ fn exec_try(
 
                 let address = function.external_function_address();
 
-                args.insert(0, selector);
-                let (payload, _) = abi_encode(loc, args, ns, vartab, cfg, false);
+                let (payload, _) = abi_encode_with_selector(loc, selector, args, ns, vartab, cfg);
 
                 let flags = call_args.flags.as_ref().map(|expr| {
                     expression(expr, cfg, callee_contract_no, Some(func), ns, vartab, opt)
                 });
+                let accounts = call_args.accounts.map(|expr| {
+                    expression(expr, cfg, callee_contract_no, Some(func), ns, vartab, opt)
+                });
+                let seeds = call_args.seeds.as_ref().map(|expr| {
+                    expression(expr, cfg, callee_contract_no, Some(func), ns, vartab, opt)
+                });
 
                 cfg.add(
                     vartab,
@@ -261,21 +275,18 @@ fn exec_try(
                         loc: *loc,
                         success: Some(success),
                         address: Some(address),
-                        accounts: ExternalCallAccounts::AbsentArgument,
-                        seeds: None,
+                        accounts,
+                        seeds,
                         payload,
                         value,
                         gas,
                         callty: CallTy::Regular,
-                        contract_function_no: None,
+                        contract_function_no,
                         flags,
                     },
                 );
 
-                let cases = polkadot::RetCodeCheckBuilder::default()
-                    .loc(*loc)
-                    .success_var(success)
-                    .insert(cfg, vartab);
+                let cases = check_success(*loc, success, ns, cfg, vartab);
                 (cases, func_returns)
             } else {
                 // dynamic dispatch
@@ -311,16 +322,72 @@ fn exec_try(
                 opt,
             );
 
-            let cases = polkadot::RetCodeCheckBuilder::default()
-                .loc(*loc)
-                .success_var(success)
-                .insert(cfg, vartab);
+            let cases = check_success(*loc, success, ns, cfg, vartab);
             (cases, vec![])
         }
         _ => unreachable!(),
     }
 }
 
+/// The blocks where execution continues after the call in the `try` expression
+struct TryCases {
+    /// The call succeeded
+    success: usize,
+    /// The call failed, and the error data is in the return data buffer
+    revert: usize,
+    /// The call failed without error data
+    error_no_data: Option<usize>,
+}
+
+/// Branch on the outcome of the call in the `try` expression.
+///
+/// On Polkadot, the success variable holds the return code of the call. On Solana,
+/// it is true if `sol_invoke_signed_c` succeeded; the callee may have set return data
+/// with the error, which will be empty otherwise. On EVM, it is the result of the `CALL`
+/// or `CREATE` instruction, and the return data holds the revert data.
+fn check_success(
+    loc: pt::Loc,
+    success: usize,
+    ns: &Namespace,
+    cfg: &mut ControlFlowGraph,
+    vartab: &mut Vartable,
+) -> TryCases {
+    if !ns.target.is_polkadot() {
+        let success_block = cfg.new_basic_block("call_success".into());
+        let revert_block = cfg.new_basic_block("call_failed".into());
+
+        cfg.add(
+            vartab,
+            Instr::BranchCond {
+                cond: Expression::Variable {
+                    loc,
+                    ty: Type::Bool,
+                    var_no: success,
+                },
+                true_block: success_block,
+                false_block: revert_block,
+            },
+        );
+
+        TryCases {
+            success: success_block,
+            revert: revert_block,
+            error_no_data: None,
+        }
+    } else {
+        let cases = polkadot::RetCodeCheckBuilder::default()
+            .loc(loc)
+            .success_var(success)
+            .insert(cfg, vartab);
+
+        TryCases {
+            success: cases.success,
+            revert: cases.revert,
+            error_no_data: Some(cases.error_no_data),
+        }
+    }
+}
+
 /// Insert the success code into the CFG.
 fn insert_success_code_block(
     try_stmt: &TryCatch,
@@ -449,13 +516,14 @@ fn insert_catch_clauses(
             let clause_body_block = cfg.new_basic_block(format!("catch_error_{}", n));
 
             cfg.set_basic_block(clause_body_block);
-            let types = &[Type::Bytes(4), clause.param.as_ref().unwrap().ty.clone()];
+            let types = &[clause.param.as_ref().unwrap().ty.clone()];
             let instruction = Instr::Set {
                 loc: Codegen,
                 res: clause
                     .param_pos
                     .unwrap_or_else(|| vartab.temp_anonymous(&clause.param.as_ref().unwrap().ty)),
-                expr: abi_decode(&Codegen, &buffer, types, ns, vartab, cfg, None)[1].clone(),
+                expr: abi_decode_with_selector(&Codegen, &buffer, types, ns, vartab, cfg)[0]
+                    .clone(),
             };
             cfg.add(vartab, instruction);
 
@@ -523,7 +591,13 @@ fn insert_catch_clauses(
     cfg.set_basic_block(no_match_err_id);
     if try_stmt.catch_all.is_none() {
         let encoded_args = Some(buffer);
-        cfg.add(vartab, Instr::AssertFailure { encoded_args });
+        cfg.add(
+            vartab,
+            Instr::AssertFailure {
+                encoded_args,
+                error_code: None,
+            },
+        );
     } else {
         insert_catchall_clause_code_block(
             try_stmt,
@@ -0,0 +1,878 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::cfg::{ControlFlowGraph, Instr};
+use super::licm::{refresh_definitions, Dominators};
+use super::vartable::{Storage, Variable};
+use super::Expression;
+use crate::sema::ast::{Namespace, Type};
+use crate::Target;
+use num_bigint::BigInt;
+use solang_parser::pt;
+use std::collections::HashSet;
+
+/*
+  Storage caching keeps the value of a storage variable in a local variable for the duration of
+  a function, so that it is read from storage at most once, and written back to storage once.
+  For example:
+
+    function add(uint x) public {
+        total += x;
+        if (x > 100) {
+            total += 1;
+        }
+        last = total;
+    }
+
+  Without this pass, total is loaded from storage three times and stored twice. With this pass,
+  total is loaded once, and stored once before the function returns.
+
+  The storage locations which are cached are those with a fixed slot, like state variables and
+  the fields of state variables which are structs, and mapping entries whose key only depends on
+  the function arguments. For each of those, a forward data flow analysis tracks whether the
+  local variable holds the current value (valid), and whether it holds a value which is not
+  written to storage yet (dirty). A dirty value is always valid.
+
+  - A load of the slot uses the local variable if it is valid, else the loaded value is kept in it
+  - A store to the slot only sets the local variable, which becomes dirty
+  - Before any instruction which may read the slot, the dirty value is written back. This
+    includes returning from the function, calling another function, and external calls, since
+    the called contract may call us back and read our storage.
+  - After any instruction which may write the slot, the local variable is no longer valid. After
+    an external call, the value has to be loaded again, since our storage may have been
+    modified by a reentrant call.
+
+  Reverting does not write back the value, since any changes to storage are discarded anyway.
+
+  Where control flow joins, the local variable is only valid if it is valid on all the incoming
+  edges. If a loop is entered without a valid value, but the value is valid at the end of each
+  iteration, the value is loaded before the loop. Otherwise, the dirty value is written back at
+  the end of the incoming blocks.
+
+  A location is only cached if this means fewer loads and stores, counting the ones in loops as
+  more expensive. Straight-line code which loads and stores a value once is left as it is.
+
+  On Solana, contract storage is account data which is mapped into memory, so there is nothing to
+  gain.
+*/
+
+/// Keep storage values in local variables, and write them back when needed
+pub fn storage_cache(cfg: &mut ControlFlowGraph, ns: &mut Namespace) {
+    if ns.target == Target::Solana || cfg.blocks.is_empty() {
+        return;
+    }
+
+    let dominators = Dominators::new(cfg);
+    let in_loop = in_loop(cfg);
+    let mut changed = false;
+
+    for (slot, storage, ty) in candidates(cfg) {
+        let cache = Cache {
+            slot,
+            storage,
+            ty,
+            var_no: ns.next_id,
+        };
+
+        if cache.promote(cfg, &dominators, &in_loop, ns) {
+            ns.next_id += 1;
+            changed = true;
+
+            cfg.vars.insert(
+                cache.var_no,
+                Variable {
+                    id: pt::Identifier {
+                        loc: pt::Loc::Codegen,
+                        name: format!("storage.temp.{}", cache.var_no),
+                    },
+                    ty: cache.ty,
+                    storage: Storage::Local,
+                },
+            );
+        }
+    }
+
+    if changed {
+        refresh_definitions(cfg);
+    }
+}
+
+/// Storage accesses in loops are assumed to happen this many times
+const LOOP_COST: usize = 10;
+
+/// The blocks which are part of a cycle
+fn in_loop(cfg: &ControlFlowGraph) -> Vec<bool> {
+    (0..cfg.blocks.len())
+        .map(|block_no| {
+            let mut visited = HashSet::new();
+            let mut todo = cfg.blocks[block_no].successors();
+
+            while let Some(next) = todo.pop() {
+                if next == block_no {
+                    return true;
+                }
+
+                if visited.insert(next) {
+                    todo.extend(cfg.blocks[next].successors());
+                }
+            }
+
+            false
+        })
+        .collect()
+}
+
+/// A storage location
+#[derive(Clone, Debug)]
+enum Slot {
+    /// A fixed slot, and the offset in the slot if the variable is packed
+    Fixed(BigInt, Option<usize>),
+    /// A slot calculated with keccak256, like a mapping entry or an element of a dynamic array. The
+    /// slot of the mapping or array is known if it is a constant.
+    Hashed {
+        base: Option<BigInt>,
+        expr: Box<Expression>,
+    },
+    Unknown,
+}
+
+impl Slot {
+    fn new(expr: &Expression) -> Self {
+        match expr {
+            Expression::NumberLiteral { value, .. } => Slot::Fixed(value.clone(), None),
+            Expression::PackedStorageSlot { slot, offset, .. } => {
+                Slot::Fixed(slot.clone(), Some(*offset))
+            }
+            _ => match hash_base(expr) {
+                Some(base) => Slot::Hashed {
+                    base,
+                    expr: Box::new(expr.clone()),
+                },
+                None => Slot::Unknown,
+            },
+        }
+    }
+
+    /// Is this exactly the same location, which can be cached
+    fn same(&self, other: &Slot) -> bool {
+        match (self, other) {
+            (Slot::Fixed(left, left_offset), Slot::Fixed(right, right_offset)) => {
+                left == right && left_offset == right_offset
+            }
+            (Slot::Hashed { expr: left, .. }, Slot::Hashed { expr: right, .. }) => {
+                invariant(left) && same_expression(left, right)
+            }
+            _ => false,
+        }
+    }
+
+    /// Can an access of this type to this location overlap with the cached location
+    fn overlaps(&self, ty: &Type, cached: &Slot, ns: &Namespace) -> bool {
+        match (self, cached) {
+            (Slot::Fixed(slot, _), Slot::Fixed(cached, _)) => {
+                // a struct or fixed length array may span more than one slot
+                let end = if is_cacheable(ty) {
+                    slot + 1
+                } else {
+                    slot + ty.storage_slots(ns).max(BigInt::from(1))
+                };
+
+                slot <= cached && *cached < end
+            }
+            // the result of keccak256 is never a small number
+            (Slot::Fixed(slot, _), Slot::Hashed { .. })
+            | (Slot::Hashed { .. }, Slot::Fixed(slot, _)) => slot.bits() > 64,
+            // different mappings and arrays never share entries
+            (
+                Slot::Hashed {
+                    base: Some(left), ..
+                },
+                Slot::Hashed {
+                    base: Some(right), ..
+                },
+            ) => left == right,
+            _ => true,
+        }
+    }
+}
+
+/// A storage location which is cached in a local variable
+struct Cache {
+    slot: Slot,
+    /// The storage slot expression, for loading and storing the value
+    storage: Expression,
+    ty: Type,
+    var_no: usize,
+}
+
+/// What an instruction does with the cached storage location
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Access {
+    None,
+    /// Load the cached location
+    Load,
+    /// Store to the cached location
+    Store,
+    /// The location may be read, so the value should be written back first
+    Observe,
+    /// The location may be written, so the value should be written back first and loaded again
+    /// when it is needed
+    Clobber,
+}
+
+/// The state of the local variable
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+struct State {
+    /// The local variable holds the current value
+    valid: bool,
+    /// The value of the local variable is not written to storage yet
+    dirty: bool,
+}
+
+impl State {
+    const EMPTY: State = State {
+        valid: false,
+        dirty: false,
+    };
+
+    fn join(self, other: State) -> State {
+        State {
+            valid: self.valid && other.valid,
+            dirty: self.dirty || other.dirty,
+        }
+    }
+
+    fn apply(&mut self, access: Access) {
+        match access {
+            Access::Load => {
+                self.valid = true;
+            }
+            Access::Store => {
+                self.valid = true;
+                self.dirty = true;
+            }
+            Access::Observe => {
+                self.dirty = false;
+            }
+            Access::Clobber => {
+                *self = State::EMPTY;
+            }
+            Access::None => (),
+        }
+    }
+}
+
+/// The instructions which are inserted at the end of blocks, before the branch, so that the
+/// state of the local variable is the same on all incoming edges of a block
+#[derive(Default)]
+struct EdgeFixes {
+    /// Load the value in these blocks, if it is not valid yet
+    load: HashSet<usize>,
+    /// Write back the value in these blocks, if it is dirty
+    flush: HashSet<usize>,
+}
+
+/// The storage locations of value types which are loaded or stored at least twice. All the
+/// accesses should be of the same type.
+fn candidates(cfg: &ControlFlowGraph) -> Vec<(Slot, Expression, Type)> {
+    let mut accesses: Vec<(Slot, Expression, Type, usize)> = Vec::new();
+    let mut mixed = Vec::new();
+
+    for instr in cfg.blocks.iter().flat_map(|block| block.instr.iter()) {
+        let (storage, ty) = match instr {
+            Instr::LoadStorage { storage, ty, .. } | Instr::SetStorage { storage, ty, .. } => {
+                (storage, ty)
+            }
+            _ => continue,
+        };
+
+        let slot = Slot::new(storage);
+
+        if matches!(slot, Slot::Unknown)
+            || (matches!(slot, Slot::Hashed { .. }) && !invariant(storage))
+        {
+            continue;
+        }
+
+        match accesses.iter_mut().find(|(other, ..)| other.same(&slot)) {
+            Some((_, _, other_ty, count)) if other_ty == ty => *count += 1,
+            Some((slot, ..)) => mixed.push(slot.clone()),
+            None => accesses.push((slot, storage.clone(), ty.clone(), 1)),
+        }
+    }
+
+    accesses
+        .into_iter()
+        .filter(|(slot, _, ty, count)| {
+            *count > 1 && is_cacheable(ty) && !mixed.iter().any(|other| other.same(slot))
+        })
+        .map(|(slot, storage, ty, _)| (slot, storage, ty))
+        .collect()
+}
+
+impl Cache {
+    /// Replace the loads and stores of the cached location with the local variable, if this
+    /// means fewer storage accesses. Accesses in loops are counted as more expensive.
+    fn promote(
+        &self,
+        cfg: &mut ControlFlowGraph,
+        dominators: &Dominators,
+        in_loop: &[bool],
+        ns: &Namespace,
+    ) -> bool {
+        let mut fixes = EdgeFixes::default();
+
+        // Fixing the state on one edge may change the state elsewhere, so repeat until the
+        // state at the start of each block agrees with all its incoming edges
+        let states = loop {
+            let (states, exits) = self.analyse(cfg, dominators, &fixes, ns);
+
+            if !self.fix_joins(dominators, &exits, &mut fixes) {
+                break states;
+            }
+        };
+
+        let mut before = 0;
+        let mut after = 0;
+        let mut blocks = Vec::new();
+
+        for (block_no, state) in states.iter().enumerate() {
+            let Some(mut state) = *state else {
+                continue;
+            };
+
+            let cost = if in_loop[block_no] { LOOP_COST } else { 1 };
+            let instrs = &cfg.blocks[block_no].instr;
+            let mut new_instrs = Vec::with_capacity(instrs.len());
+            let last = instrs.len().saturating_sub(1);
+
+            for (instr_no, instr) in instrs.iter().enumerate() {
+                if instr_no == last && is_branch(instr) {
+                    if fixes.flush.contains(&block_no) && state.dirty {
+                        new_instrs.push(self.write_back());
+                        state.dirty = false;
+                        after += cost;
+                    }
+
+                    if fixes.load.contains(&block_no) && !state.valid {
+                        new_instrs.push(self.load(self.var_no));
+                        state.valid = true;
+                        after += cost;
+                    }
+                }
+
+                let access = self.access(instr, ns);
+
+                match access {
+                    Access::Load => {
+                        let Instr::LoadStorage { res, .. } = instr else {
+                            unreachable!();
+                        };
+
+                        before += cost;
+
+                        if state.valid {
+                            new_instrs.push(Instr::Set {
+                                loc: pt::Loc::Codegen,
+                                res: *res,
+                                expr: self.variable(),
+                            });
+                        } else {
+                            new_instrs.push(instr.clone());
+                            new_instrs.push(Instr::Set {
+                                loc: pt::Loc::Codegen,
+                                res: self.var_no,
+                                expr: Expression::Variable {
+                                    loc: pt::Loc::Codegen,
+                                    ty: self.ty.clone(),
+                                    var_no: *res,
+                                },
+                            });
+                            after += cost;
+                        }
+                    }
+                    Access::Store => {
+                        let Instr::SetStorage { value, .. } = instr else {
+                            unreachable!();
+                        };
+
+                        before += cost;
+
+                        new_instrs.push(Instr::Set {
+                            loc: pt::Loc::Codegen,
+                            res: self.var_no,
+                            expr: value.clone(),
+                        });
+                    }
+                    Access::Observe | Access::Clobber => {
+                        if state.dirty {
+                            new_instrs.push(self.write_back());
+                            after += cost;
+                        }
+                        new_instrs.push(instr.clone());
+                    }
+                    Access::None => new_instrs.push(instr.clone()),
+                }
+
+                state.apply(access);
+            }
+
+            blocks.push((block_no, new_instrs));
+        }
+
+        if after >= before {
+            return false;
+        }
+
+        for (block_no, instrs) in blocks {
+            cfg.blocks[block_no].instr = instrs;
+        }
+
+        // The value of the local variable may be different on each incoming edge
+        for (block_no, state) in states.iter().enumerate() {
+            if state.is_some_and(|state| state.valid)
+                && (dominators.predecessors[block_no].len() > 1
+                    || (block_no == 0 && !dominators.predecessors[0].is_empty()))
+            {
+                cfg.blocks[block_no]
+                    .phis
+                    .get_or_insert_with(Default::default)
+                    .insert(self.var_no);
+            }
+        }
+
+        true
+    }
+
+    /// Calculate the state at the start of each reachable block, and at the end of each
+    /// reachable block before its branch
+    #[allow(clippy::type_complexity)]
+    fn analyse(
+        &self,
+        cfg: &ControlFlowGraph,
+        dominators: &Dominators,
+        fixes: &EdgeFixes,
+        ns: &Namespace,
+    ) -> (Vec<Option<State>>, Vec<Option<State>>) {
+        let mut states: Vec<Option<State>> = vec![None; cfg.blocks.len()];
+        let mut exits: Vec<Option<State>> = vec![None; cfg.blocks.len()];
+        let mut todo = vec![0];
+
+        while let Some(block_no) = todo.pop() {
+            // Predecessors which have not been visited yet do not count, since their state
+            // can only become less valid and more dirty
+            let mut state = dominators.predecessors[block_no]
+                .iter()
+                .filter_map(|pred| exits[*pred])
+                .fold(None, |acc: Option<State>, exit| {
+                    Some(acc.map_or(exit, |acc| acc.join(exit)))
+                });
+
+            if block_no == 0 {
+                state = Some(state.map_or(State::EMPTY, |state| state.join(State::EMPTY)));
+            }
+
+            let mut state = state.unwrap();
+
+            states[block_no] = Some(state);
+
+            let block = &cfg.blocks[block_no];
+            let mut branch = None;
+
+            for instr in &block.instr {
+                if is_branch(instr) {
+                    branch = Some(instr);
+                    break;
+                }
+
+                state.apply(self.access(instr, ns));
+            }
+
+            if fixes.flush.contains(&block_no) {
+                state.dirty = false;
+            }
+
+            if fixes.load.contains(&block_no) {
+                state.valid = true;
+            }
+
+            if let Some(branch) = branch {
+                state.apply(self.access(branch, ns));
+            }
+
+            if exits[block_no] != Some(state) {
+                exits[block_no] = Some(state);
+
+                todo.extend(block.successors());
+            }
+        }
+
+        // the state at the start of each block, now that the state at the end of all the
+        // predecessors is known
+        for (block_no, entry) in states.iter_mut().enumerate() {
+            if entry.is_none() {
+                continue;
+            }
+
+            let joined = dominators.predecessors[block_no]
+                .iter()
+                .filter_map(|pred| exits[*pred])
+                .chain((block_no == 0).then_some(State::EMPTY))
+                .reduce(State::join);
+
+            *entry = joined;
+        }
+
+        (states, exits)
+    }
+
+    /// Where the local variable is valid on some incoming edges of a block but not others, it
+    /// cannot be used in the block. Either load the value before entering a loop, or write back
+    /// a dirty value on the incoming edges. Returns true if anything changed.
+    fn fix_joins(
+        &self,
+        dominators: &Dominators,
+        exits: &[Option<State>],
+        fixes: &mut EdgeFixes,
+    ) -> bool {
+        let mut changed = false;
+
+        for (block_no, preds) in dominators.predecessors.iter().enumerate() {
+            let incoming: Vec<(usize, State)> = preds
+                .iter()
+                .filter_map(|pred| exits[*pred].map(|state| (*pred, state)))
+                .collect();
+
+            let entry_invalid = block_no == 0 && !incoming.is_empty();
+
+            if incoming.iter().all(|(_, state)| state.valid) && !entry_invalid
+                || incoming.iter().all(|(_, state)| !state.valid)
+            {
+                continue;
+            }
+
+            // the back edges of a loop go to the block which dominates them
+            let is_loop = preds
+                .iter()
+                .any(|pred| dominators.dominates(block_no, *pred));
+
+            let invalid_on_entry = incoming
+                .iter()
+                .filter(|(_, state)| !state.valid)
+                .all(|(pred, _)| !dominators.dominates(block_no, *pred));
+
+            if is_loop && invalid_on_entry && !entry_invalid {
+                for (pred, state) in &incoming {
+                    if !state.valid {
+                        changed |= fixes.load.insert(*pred);
+                    }
+                }
+            } else {
+                for (pred, state) in &incoming {
+                    if state.dirty {
+                        changed |= fixes.flush.insert(*pred);
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+
+    /// How does the instruction access the cached location
+    fn access(&self, instr: &Instr, ns: &Namespace) -> Access {
+        match instr {
+            Instr::LoadStorage { storage, ty, .. } => {
+                let slot = Slot::new(storage);
+
+                if *ty == self.ty && slot.same(&self.slot) {
+                    Access::Load
+                } else if slot.overlaps(ty, &self.slot, ns) {
+                    Access::Observe
+                } else {
+                    self.expressions_access(instr, ns)
+                }
+            }
+            Instr::SetStorage { storage, ty, .. } => {
+                let slot = Slot::new(storage);
+
+                if *ty == self.ty && slot.same(&self.slot) {
+                    Access::Store
+                } else if slot.overlaps(ty, &self.slot, ns) {
+                    Access::Clobber
+                } else {
+                    self.expressions_access(instr, ns)
+                }
+            }
+            Instr::ClearStorage { storage, ty }
+            | Instr::PushStorage { storage, ty, .. }
+            | Instr::PopStorage { storage, ty, .. } => {
+                if Slot::new(storage).overlaps(ty, &self.slot, ns) {
+                    Access::Clobber
+                } else {
+                    self.expressions_access(instr, ns)
+                }
+            }
+            Instr::SetStorageBytes { storage, .. } => {
+                if Slot::new(storage).overlaps(&Type::DynamicBytes, &self.slot, ns) {
+                    Access::Clobber
+                } else {
+                    self.expressions_access(instr, ns)
+                }
+            }
+            Instr::Call { .. }
+            | Instr::ExternalCall { .. }
+            | Instr::Constructor { .. }
+            | Instr::ValueTransfer { .. }
+            | Instr::Unimplemented { .. } => Access::Clobber,
+            // The function is done; a revert discards any changes to storage
+            Instr::Return { .. }
+            | Instr::ReturnData { .. }
+            | Instr::ReturnCode { .. }
+            | Instr::SelfDestruct { .. } => Access::Observe,
+            _ => self.expressions_access(instr, ns),
+        }
+    }
+
+    /// Some expressions read storage
+    fn expressions_access(&self, instr: &Instr, ns: &Namespace) -> Access {
+        let mut reads: Vec<(Slot, Type)> = Vec::new();
+
+        instr.recurse_expressions(&mut reads, |expr, reads| {
+            match expr {
+                Expression::StorageArrayLength { array, elem_ty, .. } => {
+                    reads.push((Slot::new(array), elem_ty.clone()));
+                }
+                Expression::Subscript {
+                    array_ty,
+                    expr: array,
+                    ..
+                } if array_ty.is_contract_storage() => {
+                    reads.push((Slot::new(array), array_ty.clone()));
+                }
+                _ => (),
+            }
+            true
+        });
+
+        if reads
+            .iter()
+            .any(|(slot, ty)| slot.overlaps(ty, &self.slot, ns))
+        {
+            Access::Observe
+        } else {
+            Access::None
+        }
+    }
+
+    fn variable(&self) -> Expression {
+        Expression::Variable {
+            loc: pt::Loc::Codegen,
+            ty: self.ty.clone(),
+            var_no: self.var_no,
+        }
+    }
+
+    fn load(&self, res: usize) -> Instr {
+        Instr::LoadStorage {
+            res,
+            ty: self.ty.clone(),
+            storage: self.storage.clone(),
+        }
+    }
+
+    fn write_back(&self) -> Instr {
+        Instr::SetStorage {
+            ty: self.ty.clone(),
+            value: self.variable(),
+            storage: self.storage.clone(),
+        }
+    }
+}
+
+/// If the slot is calculated with keccak256, the slot of the mapping or array it is in
+fn hash_base(expr: &Expression) -> Option<Option<BigInt>> {
+    match expr {
+        Expression::Keccak256 { exprs, .. } => Some(match exprs.first() {
+            Some(Expression::NumberLiteral { value, .. }) => Some(value.clone()),
+            Some(first) => hash_base(first).flatten(),
+            None => None,
+        }),
+        Expression::Add { left, right, .. } => hash_base(left).or_else(|| hash_base(right)),
+        Expression::ZeroExt { expr, .. }
+        | Expression::Trunc { expr, .. }
+        | Expression::Cast { expr, .. } => hash_base(expr),
+        _ => None,
+    }
+}
+
+/// Does the expression have the same value anywhere in the function, without side effects
+fn invariant(expr: &Expression) -> bool {
+    match expr {
+        Expression::NumberLiteral { .. } | Expression::FunctionArg { .. } => true,
+        Expression::Keccak256 { exprs, .. } => exprs.iter().all(invariant),
+        Expression::Add {
+            overflowing: true,
+            left,
+            right,
+            ..
+        }
+        | Expression::Subtract {
+            overflowing: true,
+            left,
+            right,
+            ..
+        }
+        | Expression::Multiply {
+            overflowing: true,
+            left,
+            right,
+            ..
+        } => invariant(left) && invariant(right),
+        Expression::ZeroExt { expr, .. }
+        | Expression::SignExt { expr, .. }
+        | Expression::Trunc { expr, .. }
+        | Expression::Cast { expr, .. } => invariant(expr),
+        _ => false,
+    }
+}
+
+/// Compare two invariant expressions, ignoring their locations
+fn same_expression(left: &Expression, right: &Expression) -> bool {
+    match (left, right) {
+        (
+            Expression::NumberLiteral {
+                ty: left_ty,
+                value: left,
+                ..
+            },
+            Expression::NumberLiteral {
+                ty: right_ty,
+                value: right,
+                ..
+            },
+        ) => left_ty == right_ty && left == right,
+        (
+            Expression::FunctionArg { arg_no: left, .. },
+            Expression::FunctionArg { arg_no: right, .. },
+        ) => left == right,
+        (Expression::Keccak256 { exprs: left, .. }, Expression::Keccak256 { exprs: right, .. }) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(left, right)| same_expression(left, right))
+        }
+        (
+            Expression::Add {
+                ty: left_ty,
+                left: l1,
+                right: r1,
+                ..
+            },
+            Expression::Add {
+                ty: right_ty,
+                left: l2,
+                right: r2,
+                ..
+            },
+        )
+        | (
+            Expression::Subtract {
+                ty: left_ty,
+                left: l1,
+                right: r1,
+                ..
+            },
+            Expression::Subtract {
+                ty: right_ty,
+                left: l2,
+                right: r2,
+                ..
+            },
+        )
+        | (
+            Expression::Multiply {
+                ty: left_ty,
+                left: l1,
+                right: r1,
+                ..
+            },
+            Expression::Multiply {
+                ty: right_ty,
+                left: l2,
+                right: r2,
+                ..
+            },
+        ) => left_ty == right_ty && same_expression(l1, l2) && same_expression(r1, r2),
+        (
+            Expression::ZeroExt {
+                ty: left_ty,
+                expr: left,
+                ..
+            },
+            Expression::ZeroExt {
+                ty: right_ty,
+                expr: right,
+                ..
+            },
+        )
+        | (
+            Expression::SignExt {
+                ty: left_ty,
+                expr: left,
+                ..
+            },
+            Expression::SignExt {
+                ty: right_ty,
+                expr: right,
+                ..
+            },
+        )
+        | (
+            Expression::Trunc {
+                ty: left_ty,
+                expr: left,
+                ..
+            },
+            Expression::Trunc {
+                ty: right_ty,
+                expr: right,
+                ..
+            },
+        )
+        | (
+            Expression::Cast {
+                ty: left_ty,
+                expr: left,
+                ..
+            },
+            Expression::Cast {
+                ty: right_ty,
+                expr: right,
+                ..
+            },
+        ) => left_ty == right_ty && same_expression(left, right),
+        _ => false,
+    }
+}
+
+/// Only values which are stored in a single slot are cached
+fn is_cacheable(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Bool
+            | Type::Int(_)
+            | Type::Uint(_)
+            | Type::Bytes(_)
+            | Type::Address(_)
+            | Type::Contract(_)
+            | Type::Enum(_)
+    )
+}
+
+fn is_branch(instr: &Instr) -> bool {
+    matches!(
+        instr,
+        Instr::Branch { .. } | Instr::BranchCond { .. } | Instr::Switch { .. }
+    )
+}
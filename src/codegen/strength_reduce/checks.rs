@@ -0,0 +1,655 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::expression_values::expression_values;
+use super::reaching_values::transfer;
+use super::value::{get_max_unsigned, get_min_unsigned};
+use super::Variables;
+use crate::codegen::cfg::{CheckKind, ControlFlowGraph, Instr, RemovedCheck};
+use crate::codegen::licm::{refresh_definitions, writes_raw_memory, Dominators};
+use crate::codegen::reaching_definitions::Transfer;
+use crate::codegen::{Builtin, Expression};
+use crate::sema::ast::{Namespace, Type};
+use crate::sema::Recurse;
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+use solang_parser::pt::{self, CodeLocation};
+use std::collections::HashSet;
+
+/*
+  Checked arithmetic and bounds checks which can never fail are removed. For example:
+
+    function f(uint[] memory a) public pure returns (uint sum) {
+        for (uint i = 0; i < a.length; i++) {
+            sum += a[i] & 0xff;
+        }
+    }
+
+  The index check of a[i] compares i with a.length. This is implied by the loop condition, so the
+  check is removed. The same condition tells us that i is less than a.length, so i++ cannot
+  overflow and is done without overflow check.
+
+  Two sources of information are used:
+  - The reaching values of variables, which give the maximum and minimum value an expression can
+    have. This is the same information which is used for strength reduction.
+  - Comparisons of a variable with a bound in a branch condition. The comparison holds in the blocks
+    which are dominated by the target of the branch, as long as neither the variable nor the bound
+    are modified between the branch and the use.
+
+  Only unsigned comparisons and unsigned arithmetic are considered. Any check which is removed is
+  recorded in the cfg, so that it can be listed with --emit removed-checks.
+*/
+
+/// A comparison which is known to hold in the blocks dominated by `block`: `var_no < bound` if the
+/// bound is an upper bound, or `bound < var_no` if not. If it is not strict, the comparison is
+/// `<=` rather than `<`.
+struct Fact {
+    block: usize,
+    var_no: usize,
+    bound: Expression,
+    upper: bool,
+    strict: bool,
+    loc: pt::Loc,
+}
+
+/// The comparisons made in branch conditions
+pub(super) struct Conditions {
+    facts: Vec<Fact>,
+    dominators: Dominators,
+}
+
+impl Conditions {
+    pub(super) fn new(cfg: &mut ControlFlowGraph) -> Self {
+        // the transfers are needed to find modifications of variables
+        refresh_definitions(cfg);
+
+        let dominators = Dominators::new(cfg);
+        let mut facts = Vec::new();
+
+        for (block_no, block) in cfg.blocks.iter().enumerate() {
+            let Some(Instr::BranchCond {
+                cond,
+                true_block,
+                false_block,
+            }) = block.instr.last()
+            else {
+                continue;
+            };
+
+            for (target, value) in [(*true_block, true), (*false_block, false)] {
+                // if the block can be entered another way, the comparison may not hold there
+                if dominators.predecessors[target] != [block_no] {
+                    continue;
+                }
+
+                let Some((smaller, bigger, strict)) = relation(cond, value) else {
+                    continue;
+                };
+
+                for (var, bound, upper) in [(smaller, bigger, true), (bigger, smaller, false)] {
+                    if let Expression::Variable { var_no, .. } = strip(var) {
+                        if is_stable(bound) {
+                            facts.push(Fact {
+                                block: target,
+                                var_no: *var_no,
+                                bound: bound.clone(),
+                                upper,
+                                strict,
+                                loc: cond.loc(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Conditions { facts, dominators }
+    }
+}
+
+/// Remove the checks which cannot fail in a block, given the reaching values at the start of the
+/// block
+pub(super) fn remove_checks(
+    block_no: usize,
+    cfg: &mut ControlFlowGraph,
+    mut vars: Variables,
+    conditions: &Conditions,
+    ns: &Namespace,
+) {
+    for instr_no in 0..cfg.blocks[block_no].instr.len() {
+        let mut instr = cfg.blocks[block_no].instr[instr_no].clone();
+        let mut removed = Vec::new();
+
+        let point = Point {
+            cfg,
+            block_no,
+            instr_no,
+            vars: &vars,
+            conditions,
+            ns,
+        };
+
+        for expr in instr.expressions_mut() {
+            point.arithmetic(expr, &mut removed);
+        }
+
+        if let Instr::BranchCond {
+            cond,
+            true_block,
+            false_block,
+        } = &instr
+        {
+            if let Some((block, check)) = point.bounds_check(cond, *true_block, *false_block) {
+                instr = Instr::Branch { block };
+                removed.push(check);
+            }
+        }
+
+        transfer(&instr, &mut vars, ns);
+
+        cfg.blocks[block_no].instr[instr_no] = instr;
+        cfg.removed_checks.extend(removed);
+    }
+}
+
+/// An instruction in the cfg, and what is known about the values there
+struct Point<'a> {
+    cfg: &'a ControlFlowGraph,
+    block_no: usize,
+    instr_no: usize,
+    vars: &'a Variables,
+    conditions: &'a Conditions,
+    ns: &'a Namespace,
+}
+
+impl Point<'_> {
+    /// Remove the overflow checks from unsigned arithmetic which cannot overflow
+    fn arithmetic(&self, expr: &mut Expression, removed: &mut Vec<RemovedCheck>) {
+        for expr in expr.subexpressions_mut() {
+            self.arithmetic(expr, removed);
+        }
+
+        let (loc, overflowing, implied_by) = match expr {
+            Expression::Add {
+                loc,
+                ty: Type::Uint(bits),
+                overflowing,
+                left,
+                right,
+            } if !*overflowing => {
+                let Some(((left, left_by), (right, right_by))) =
+                    self.upper(left).zip(self.upper(right))
+                else {
+                    return;
+                };
+
+                if left + right > max_unsigned(*bits) {
+                    return;
+                }
+
+                (*loc, overflowing, left_by.or(right_by))
+            }
+            Expression::Multiply {
+                loc,
+                ty: Type::Uint(bits),
+                overflowing,
+                left,
+                right,
+            } if !*overflowing => {
+                let Some(((left, left_by), (right, right_by))) =
+                    self.upper(left).zip(self.upper(right))
+                else {
+                    return;
+                };
+
+                if left * right > max_unsigned(*bits) {
+                    return;
+                }
+
+                (*loc, overflowing, left_by.or(right_by))
+            }
+            Expression::Subtract {
+                loc,
+                ty: Type::Uint(_),
+                overflowing,
+                left,
+                right,
+            } if !*overflowing => match self.at_most(right, left, false) {
+                Some(implied_by) => (*loc, overflowing, implied_by),
+                None => return,
+            },
+            _ => return,
+        };
+
+        *overflowing = true;
+
+        removed.push(RemovedCheck {
+            loc,
+            kind: CheckKind::Overflow,
+            implied_by,
+        });
+    }
+
+    /// If the branch is a bounds check which cannot fail, return the in bounds block
+    fn bounds_check(
+        &self,
+        cond: &Expression,
+        true_block: usize,
+        false_block: usize,
+    ) -> Option<(usize, RemovedCheck)> {
+        let out_of_bounds = |block_no: usize| self.cfg.blocks[block_no].name == "out_of_bounds";
+
+        // the value of the condition if the check passes
+        let (in_bounds, passes) = match (out_of_bounds(true_block), out_of_bounds(false_block)) {
+            (true, false) => (false_block, false),
+            (false, true) => (true_block, true),
+            _ => return None,
+        };
+
+        let values = expression_values(cond, self.vars, self.ns);
+
+        let implied_by = match values.iter().next() {
+            Some(v) if values.len() == 1 && v.known_bits[0] && v.value[0] == passes => None,
+            _ => {
+                let (left, right, strict) = relation(cond, passes)?;
+
+                self.at_most(left, right, strict)?
+            }
+        };
+
+        // checking_trunc() also uses an out of bounds block, for the truncation overflow check
+        let kind = match cond {
+            Expression::MoreEqual { left, .. }
+                if matches!(left.as_ref(), Expression::Variable { var_no, .. }
+                    if self.cfg.vars[var_no].id.name == "value") =>
+            {
+                CheckKind::Overflow
+            }
+            _ => CheckKind::Bounds,
+        };
+
+        Some((
+            in_bounds,
+            RemovedCheck {
+                loc: cond.loc(),
+                kind,
+                implied_by,
+            },
+        ))
+    }
+
+    /// Is `left < right` (if strict) or `left <= right` always true? On success, returns the
+    /// condition which implies it, if any.
+    fn at_most(
+        &self,
+        left: &Expression,
+        right: &Expression,
+        strict: bool,
+    ) -> Option<Option<pt::Loc>> {
+        let facts = [(left, right, true), (right, left, false)];
+
+        for (var, bound, upper) in facts {
+            for var_no in self.var_candidates(var) {
+                if let Some(fact) = self.facts(var_no).find(|fact| {
+                    fact.upper == upper
+                        && (fact.strict || !strict)
+                        && same_value(&fact.bound, bound)
+                }) {
+                    return Some(Some(fact.loc));
+                }
+            }
+        }
+
+        let (max, max_by) = self.upper(left)?;
+        let (min, min_by) = self.lower(right)?;
+
+        if max < min || (!strict && max == min) {
+            Some(max_by.or(min_by))
+        } else {
+            None
+        }
+    }
+
+    /// The maximum unsigned value of an expression, and the condition which limits it
+    fn upper(&self, expr: &Expression) -> Option<(BigInt, Option<pt::Loc>)> {
+        let values = expression_values(expr, self.vars, self.ns);
+
+        if values.is_empty() {
+            return None;
+        }
+
+        let mut max = (get_max_unsigned(&values), None);
+
+        for var_no in self.var_candidates(expr) {
+            for fact in self.facts(var_no).filter(|fact| fact.upper) {
+                let bound = expression_values(&fact.bound, self.vars, self.ns);
+
+                if bound.is_empty() {
+                    continue;
+                }
+
+                let mut bound = get_max_unsigned(&bound);
+
+                if fact.strict {
+                    if bound.is_zero() {
+                        continue;
+                    }
+
+                    bound -= 1;
+                }
+
+                if bound < max.0 {
+                    max = (bound, Some(fact.loc));
+                }
+            }
+        }
+
+        Some(max)
+    }
+
+    /// The minimum unsigned value of an expression, and the condition which limits it
+    fn lower(&self, expr: &Expression) -> Option<(BigInt, Option<pt::Loc>)> {
+        let mut min = (
+            get_min_unsigned(&expression_values(expr, self.vars, self.ns))?,
+            None,
+        );
+
+        for var_no in self.var_candidates(expr) {
+            for fact in self.facts(var_no).filter(|fact| !fact.upper) {
+                let Some(mut bound) =
+                    get_min_unsigned(&expression_values(&fact.bound, self.vars, self.ns))
+                else {
+                    continue;
+                };
+
+                if fact.strict {
+                    bound += 1;
+                }
+
+                if bound > min.0 {
+                    min = (bound, Some(fact.loc));
+                }
+            }
+        }
+
+        Some(min)
+    }
+
+    /// The variables which hold the same value as the expression. An array index is copied into
+    /// a temporary before the bounds check, so follow the copy to the original variable.
+    fn var_candidates(&self, expr: &Expression) -> Vec<usize> {
+        let Expression::Variable { var_no, .. } = strip(expr) else {
+            return Vec::new();
+        };
+
+        let block = &self.cfg.blocks[self.block_no];
+
+        let Some(def) = (0..self.instr_no)
+            .rev()
+            .find(|instr_no| modifies(block, *instr_no, &HashSet::from([*var_no]), false))
+        else {
+            return vec![*var_no];
+        };
+
+        if let Instr::Set { res, expr, .. } = &block.instr[def] {
+            if let Expression::Variable { var_no: copied, .. } = strip(expr) {
+                let vars = HashSet::from([*copied]);
+
+                if *res == *var_no
+                    && !(def + 1..self.instr_no)
+                        .any(|instr_no| modifies(block, instr_no, &vars, false))
+                {
+                    return vec![*var_no, *copied];
+                }
+            }
+        }
+
+        vec![*var_no]
+    }
+
+    /// The facts about the variable which hold at this point
+    fn facts(&self, var_no: usize) -> impl Iterator<Item = &Fact> + '_ {
+        self.conditions
+            .facts
+            .iter()
+            .filter(move |fact| fact.var_no == var_no && self.holds(fact))
+    }
+
+    /// Does the fact hold at this point. The block of the fact must dominate this point, and the
+    /// variable and the bound must not be modified between the start of the block and this point.
+    fn holds(&self, fact: &Fact) -> bool {
+        let dominators = &self.conditions.dominators;
+
+        if !dominators.dominates(fact.block, self.block_no) {
+            return false;
+        }
+
+        // the variables used by the fact, and whether it depends on the length of an array
+        let mut used = (HashSet::from([fact.var_no]), false);
+
+        fact.bound.recurse(&mut used, |expr, (vars, memory)| {
+            match expr {
+                Expression::Variable { var_no, .. } => {
+                    vars.insert(*var_no);
+                }
+                Expression::Builtin {
+                    kind: Builtin::ArrayLength,
+                    ..
+                } => {
+                    *memory = true;
+                }
+                _ => (),
+            }
+            true
+        });
+
+        let (vars, memory) = used;
+
+        // the blocks which may be executed between the block of the fact and this point
+        let mut blocks = HashSet::new();
+        let mut todo = if self.block_no == fact.block {
+            Vec::new()
+        } else {
+            dominators.predecessors[self.block_no].clone()
+        };
+
+        while let Some(block_no) = todo.pop() {
+            if blocks.insert(block_no) && block_no != fact.block {
+                todo.extend(&dominators.predecessors[block_no]);
+            }
+        }
+
+        // if this block is part of a loop which does not contain the block of the fact, all of
+        // it may be executed before this point
+        let block = &self.cfg.blocks[self.block_no];
+        let instrs = if blocks.remove(&self.block_no) {
+            block.instr.len()
+        } else {
+            self.instr_no
+        };
+
+        !(0..instrs).any(|instr_no| modifies(block, instr_no, &vars, memory))
+            && !blocks.iter().any(|block_no| {
+                let block = &self.cfg.blocks[*block_no];
+
+                (0..block.instr.len()).any(|instr_no| modifies(block, instr_no, &vars, memory))
+            })
+    }
+}
+
+/// Does the instruction modify any of the variables, or the length of a memory array
+fn modifies(
+    block: &crate::codegen::cfg::BasicBlock,
+    instr_no: usize,
+    vars: &HashSet<usize>,
+    memory: bool,
+) -> bool {
+    let defined = block.transfers.get(instr_no).is_some_and(|transfers| {
+        transfers.iter().any(|transfer| match transfer {
+            Transfer::Gen { var_no, .. }
+            | Transfer::Copy { var_no, .. }
+            | Transfer::Mod { var_no } => vars.contains(var_no),
+            _ => false,
+        })
+    });
+
+    defined
+        || match &block.instr[instr_no] {
+            // These instructions set a variable, but have no transfer
+            Instr::PushStorage { res, .. } | Instr::AccountAccess { var_no: res, .. } => {
+                vars.contains(res)
+            }
+            Instr::PopMemory { res, .. } => memory || vars.contains(res),
+            Instr::PushMemory { .. } | Instr::Call { .. } => memory,
+            instr => memory && writes_raw_memory(instr),
+        }
+}
+
+/// The unsigned comparison `left < right` (if strict) or `left <= right` which holds if the
+/// condition has the given value
+fn relation(cond: &Expression, value: bool) -> Option<(&Expression, &Expression, bool)> {
+    match (cond, value) {
+        (
+            Expression::Less {
+                signed: false,
+                left,
+                right,
+                ..
+            },
+            true,
+        )
+        | (
+            Expression::MoreEqual {
+                signed: false,
+                left,
+                right,
+                ..
+            },
+            false,
+        ) => Some((left, right, true)),
+        (
+            Expression::LessEqual {
+                signed: false,
+                left,
+                right,
+                ..
+            },
+            true,
+        )
+        | (
+            Expression::More {
+                signed: false,
+                left,
+                right,
+                ..
+            },
+            false,
+        ) => Some((left, right, false)),
+        (
+            Expression::More {
+                signed: false,
+                left,
+                right,
+                ..
+            },
+            true,
+        )
+        | (
+            Expression::LessEqual {
+                signed: false,
+                left,
+                right,
+                ..
+            },
+            false,
+        ) => Some((right, left, true)),
+        (
+            Expression::MoreEqual {
+                signed: false,
+                left,
+                right,
+                ..
+            },
+            true,
+        )
+        | (
+            Expression::Less {
+                signed: false,
+                left,
+                right,
+                ..
+            },
+            false,
+        ) => Some((right, left, false)),
+        _ => None,
+    }
+}
+
+/// Zero extension does not change the unsigned value
+fn strip(mut expr: &Expression) -> &Expression {
+    while let Expression::ZeroExt { expr: inner, .. } = expr {
+        expr = inner;
+    }
+
+    expr
+}
+
+/// Can the bound of a fact be used after the branch. Only variables, arguments, constants and
+/// array lengths are used, so we can check whether they are modified.
+fn is_stable(expr: &Expression) -> bool {
+    match strip(expr) {
+        Expression::Variable { .. }
+        | Expression::FunctionArg { .. }
+        | Expression::NumberLiteral { .. } => true,
+        Expression::Builtin {
+            kind: Builtin::ArrayLength,
+            args,
+            ..
+        } => args.iter().all(|arg| {
+            matches!(
+                arg,
+                Expression::Variable { .. } | Expression::FunctionArg { .. }
+            )
+        }),
+        _ => false,
+    }
+}
+
+/// Do the expressions have the same unsigned value. The locations are ignored.
+fn same_value(left: &Expression, right: &Expression) -> bool {
+    match (strip(left), strip(right)) {
+        (Expression::Variable { var_no: left, .. }, Expression::Variable { var_no: right, .. }) => {
+            left == right
+        }
+        (
+            Expression::FunctionArg { arg_no: left, .. },
+            Expression::FunctionArg { arg_no: right, .. },
+        ) => left == right,
+        (
+            Expression::NumberLiteral { value: left, .. },
+            Expression::NumberLiteral { value: right, .. },
+        ) => left == right,
+        (
+            Expression::Builtin {
+                kind: Builtin::ArrayLength,
+                args: left,
+                ..
+            },
+            Expression::Builtin {
+                kind: Builtin::ArrayLength,
+                args: right,
+                ..
+            },
+        ) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(left, right)| same_value(left, right))
+        }
+        _ => false,
+    }
+}
+
+fn max_unsigned(bits: u16) -> BigInt {
+    (BigInt::one() << bits) - 1
+}
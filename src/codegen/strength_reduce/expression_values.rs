@@ -493,11 +493,11 @@ fn more_values(
             let mut value = BitArray::new([0u8; 32]);
 
             let is_true = if signed {
-                BigInt::from_signed_bytes_le(&l.get_signed_max_value().into_inner())
-                    > BigInt::from_signed_bytes_le(&r.get_signed_min_value().into_inner())
+                BigInt::from_signed_bytes_le(&l.get_signed_min_value().into_inner())
+                    > BigInt::from_signed_bytes_le(&r.get_signed_max_value().into_inner())
             } else {
-                BigInt::from_bytes_le(Sign::Plus, &l.get_unsigned_max_value().into_inner())
-                    > BigInt::from_bytes_le(Sign::Plus, &r.get_unsigned_min_value().into_inner())
+                BigInt::from_bytes_le(Sign::Plus, &l.get_unsigned_min_value().into_inner())
+                    > BigInt::from_bytes_le(Sign::Plus, &r.get_unsigned_max_value().into_inner())
             };
 
             if is_true {
@@ -507,13 +507,13 @@ fn more_values(
             } else {
                 // maybe the comparison is always false
                 let is_false = if signed {
-                    BigInt::from_signed_bytes_le(&l.get_signed_min_value().into_inner())
-                        <= BigInt::from_signed_bytes_le(&r.get_signed_max_value().into_inner())
+                    BigInt::from_signed_bytes_le(&l.get_signed_max_value().into_inner())
+                        <= BigInt::from_signed_bytes_le(&r.get_signed_min_value().into_inner())
                 } else {
-                    BigInt::from_bytes_le(Sign::Plus, &l.get_unsigned_min_value().into_inner())
+                    BigInt::from_bytes_le(Sign::Plus, &l.get_unsigned_max_value().into_inner())
                         <= BigInt::from_bytes_le(
                             Sign::Plus,
-                            &r.get_unsigned_max_value().into_inner(),
+                            &r.get_unsigned_min_value().into_inner(),
                         )
                 };
 
@@ -550,11 +550,11 @@ fn more_equal_values(
             let mut value = BitArray::new([0u8; 32]);
 
             let is_true = if signed {
-                BigInt::from_signed_bytes_le(&l.get_signed_max_value().into_inner())
-                    >= BigInt::from_signed_bytes_le(&r.get_signed_min_value().into_inner())
+                BigInt::from_signed_bytes_le(&l.get_signed_min_value().into_inner())
+                    >= BigInt::from_signed_bytes_le(&r.get_signed_max_value().into_inner())
             } else {
-                BigInt::from_bytes_le(Sign::Plus, &l.get_unsigned_max_value().into_inner())
-                    >= BigInt::from_bytes_le(Sign::Plus, &r.get_unsigned_min_value().into_inner())
+                BigInt::from_bytes_le(Sign::Plus, &l.get_unsigned_min_value().into_inner())
+                    >= BigInt::from_bytes_le(Sign::Plus, &r.get_unsigned_max_value().into_inner())
             };
 
             if is_true {
@@ -564,13 +564,13 @@ fn more_equal_values(
             } else {
                 // maybe the comparison is always false
                 let is_false = if signed {
-                    BigInt::from_signed_bytes_le(&l.get_signed_min_value().into_inner())
-                        < BigInt::from_signed_bytes_le(&r.get_signed_max_value().into_inner())
+                    BigInt::from_signed_bytes_le(&l.get_signed_max_value().into_inner())
+                        < BigInt::from_signed_bytes_le(&r.get_signed_min_value().into_inner())
                 } else {
-                    BigInt::from_bytes_le(Sign::Plus, &l.get_unsigned_min_value().into_inner())
+                    BigInt::from_bytes_le(Sign::Plus, &l.get_unsigned_max_value().into_inner())
                         < BigInt::from_bytes_le(
                             Sign::Plus,
-                            &r.get_unsigned_max_value().into_inner(),
+                            &r.get_unsigned_min_value().into_inner(),
                         )
                 };
 
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
+mod checks;
 mod expression_values;
 mod reaching_values;
 mod tests;
@@ -22,6 +23,7 @@ use value::{get_max_signed, get_max_unsigned, is_single_constant, Value};
 
   Currently implemented:
   - Replace 256/128 bit multiply/divide/modulo with smaller width operations
+  - Remove overflow and bounds checks which cannot fail (see checks.rs)
 
 */
 
@@ -86,6 +88,22 @@ pub fn strength_reduce(cfg: &mut ControlFlowGraph, ns: &mut Namespace) {
 
     reaching_values(0, cfg, &mut vars, &mut block_vars, ns);
 
+    // remove the overflow and bounds checks which cannot fail, in block order so that the
+    // removed checks are listed in a stable order
+    let conditions = checks::Conditions::new(cfg);
+    let mut blocks: Vec<usize> = block_vars.keys().copied().collect();
+    blocks.sort_unstable();
+
+    for block_no in blocks {
+        checks::remove_checks(
+            block_no,
+            cfg,
+            block_vars[&block_no].clone(),
+            &conditions,
+            ns,
+        );
+    }
+
     // now we have all the reaching values for the top of each block
     // we can now step through each block and do any strength reduction where possible
     for (block_no, vars) in block_vars {
@@ -124,6 +142,7 @@ fn block_reduce(
             }
             Instr::AssertFailure {
                 encoded_args: Some(expr),
+                ..
             } => {
                 *expr = expression_reduce(expr, &vars, ns);
             }
@@ -104,6 +104,13 @@ pub(super) fn get_max_unsigned(set: &HashSet<Value>) -> BigInt {
     m
 }
 
+/// Get the minimum unsigned value in a set
+pub(super) fn get_min_unsigned(set: &HashSet<Value>) -> Option<BigInt> {
+    set.iter()
+        .map(|v| BigInt::from_bytes_le(Sign::Plus, &v.get_unsigned_min_value().into_inner()))
+        .min()
+}
+
 impl Value {
     /// Calculate the unsigned min value. Higher bits than the type are 0
     pub(super) fn get_unsigned_min_value(&self) -> Bits {
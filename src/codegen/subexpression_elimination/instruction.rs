@@ -22,6 +22,7 @@ impl<'a, 'b: 'a> AvailableExpressionSet<'a> {
             | Instr::Print { expr }
             | Instr::AssertFailure {
                 encoded_args: Some(expr),
+                ..
             }
             | Instr::PopStorage { storage: expr, .. }
             | Instr::SelfDestruct { recipient: expr } => {
@@ -192,7 +193,9 @@ impl<'a, 'b: 'a> AvailableExpressionSet<'a> {
                 }
             }
 
-            Instr::AssertFailure { encoded_args: None }
+            Instr::AssertFailure {
+                encoded_args: None, ..
+            }
             | Instr::Nop
             | Instr::ReturnCode { .. }
             | Instr::Branch { .. }
@@ -259,8 +262,10 @@ impl<'a, 'b: 'a> AvailableExpressionSet<'a> {
 
             Instr::AssertFailure {
                 encoded_args: Some(exp),
+                error_code,
             } => Instr::AssertFailure {
                 encoded_args: Some(self.regenerate_expression(exp, ave, cst).1),
+                error_code: *error_code,
             },
 
             Instr::Print { expr } => Instr::Print {
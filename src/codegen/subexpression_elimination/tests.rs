@@ -158,6 +158,7 @@ fn non_commutative() {
 
     let instr = Instr::AssertFailure {
         encoded_args: Some(sub.clone()),
+        error_code: None,
     };
 
     let mut ave = AvailableExpression::default();
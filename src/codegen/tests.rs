@@ -2,6 +2,10 @@
 
 #[cfg(test)]
 use crate::{codegen, sema::ast};
+#[cfg(test)]
+use crate::{parse_and_resolve, FileResolver, Target};
+#[cfg(test)]
+use std::{ffi::OsStr, fs, path::Path};
 
 #[test]
 fn test_builtin_conversion() {
@@ -121,3 +125,246 @@ fn test_builtin_conversion() {
         assert_eq!(codegen::Builtin::from(item), output[i]);
     }
 }
+
+/// Run the passes on the CFGs in `tests/cfg_ir_testcases`. Each file starts with a
+/// `# RUN:` line listing the passes, followed by the input CFG, an `# EXPECT:` line and the
+/// CFG as printed after the passes.
+#[test]
+fn cfg_ir_testcases() {
+    let mut files: Vec<_> = fs::read_dir("tests/cfg_ir_testcases")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some(OsStr::new("cfg")))
+        .collect();
+
+    files.sort();
+
+    assert!(!files.is_empty());
+
+    for path in files {
+        cfg_ir_testcase(&path);
+    }
+}
+
+#[cfg(test)]
+fn cfg_ir_testcase(path: &Path) {
+    use codegen::passes::Pass;
+
+    let src = fs::read_to_string(path).unwrap();
+
+    let (input, expected) = src
+        .split_once("# EXPECT:")
+        .unwrap_or_else(|| panic!("{}: missing '# EXPECT:'", path.display()));
+
+    let passes: Vec<Pass> = input
+        .lines()
+        .find_map(|line| line.strip_prefix("# RUN:"))
+        .unwrap_or_else(|| panic!("{}: missing '# RUN:'", path.display()))
+        .split_whitespace()
+        .map(|pass| pass.parse().unwrap())
+        .collect();
+
+    let mut cache = FileResolver::default();
+    cache.set_file_contents("test.sol", "contract c {}".to_string());
+    let mut ns = parse_and_resolve(OsStr::new("test.sol"), &mut cache, Target::EVM);
+
+    let mut cfg = codegen::cfg_parser::parse_cfg(input, &mut ns)
+        .unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+
+    for pass in passes {
+        codegen::reaching_definitions::find(&mut cfg);
+
+        match pass {
+            Pass::ConstantFolding => {
+                codegen::constant_folding::constant_folding(&mut cfg, false, &mut ns)
+            }
+            Pass::VectorToSlice => codegen::vector_to_slice::vector_to_slice(&mut cfg, &mut ns),
+            Pass::StrengthReduce => codegen::strength_reduce::strength_reduce(&mut cfg, &mut ns),
+            Pass::DeadStorage => codegen::dead_storage::dead_storage(&mut cfg, &mut ns),
+            Pass::StorageCache => codegen::storage_cache::storage_cache(&mut cfg, &mut ns),
+            Pass::CommonSubexpressionElimination => {
+                codegen::subexpression_elimination::common_sub_expression_elimination(
+                    &mut cfg, &mut ns,
+                )
+            }
+            Pass::LoopInvariantCodeMotion => {
+                codegen::licm::loop_invariant_code_motion(&mut cfg, &mut ns)
+            }
+        }
+
+        assert_eq!(
+            codegen::verify::verify_cfg(&cfg, &ns),
+            Vec::<String>::new(),
+            "{}: invalid CFG after {}",
+            path.display(),
+            pass
+        );
+    }
+
+    // the reaching definitions are not part of the expected output
+    for block in &mut cfg.blocks {
+        block.defs.clear();
+    }
+
+    let output = cfg.to_string(&ns.contracts[0], &ns);
+
+    let lines = |s: &str| -> Vec<String> {
+        s.lines()
+            .map(|line| line.trim().to_owned())
+            .filter(|line| !line.is_empty())
+            .collect()
+    };
+
+    assert_eq!(
+        lines(&output),
+        lines(expected),
+        "{}: output:\n{}",
+        path.display(),
+        output
+    );
+}
+
+#[test]
+fn cfg_parser_roundtrip() {
+    let src = r#"
+# function c::c::function::f public:true selector:00000000 nonpayable:true
+# params: uint64 a,int32 b
+# returns: uint64
+block0: # entry
+    ty:uint64 %i = uint64 0
+    branch block1
+block1: # cond
+    # phis: i
+    branchcond (unsigned less %i < (arg #0)), block2, block3
+block2: # body
+    ty:uint64 %i = (overflowing %i + uint64 1)
+    ty:int32 %s = (signed modulo (arg #1) % int32 -3)
+    store storage slot(uint256 42) ty:uint64 = (trunc uint64 (zext uint256 %i))
+    switch (%s >> int32 1):
+        case int32 1: goto block #1
+        default: goto block #3
+block3: # end
+    %v = load storage slot(uint256 42) ty:uint64
+    ty:address %w = address 0x2a
+    print hex"0102"
+    return (unsigned divide %v / uint64 2)
+"#;
+
+    let mut cache = FileResolver::default();
+    cache.set_file_contents("test.sol", "contract c {}".to_string());
+    let mut ns = parse_and_resolve(OsStr::new("test.sol"), &mut cache, Target::EVM);
+
+    let next_id = ns.next_id;
+
+    let cfg = codegen::cfg_parser::parse_cfg(src, &mut ns).unwrap();
+
+    assert_eq!(cfg.name, "c::c::function::f");
+    assert!(cfg.public && cfg.nonpayable);
+    assert_eq!(cfg.params.len(), 2);
+    assert_eq!(ns.next_id, next_id + 4);
+
+    let output = cfg.to_string(&ns.contracts[0], &ns);
+    let header_end = src.find("block0").unwrap();
+
+    assert_eq!(
+        output.lines().map(str::trim).collect::<Vec<_>>(),
+        src[header_end..].lines().map(str::trim).collect::<Vec<_>>()
+    );
+
+    let err =
+        codegen::cfg_parser::parse_cfg("block0: # entry\n\treturn %x\n", &mut ns).unwrap_err();
+
+    assert_eq!(
+        err,
+        "line 2: variable %x is never set, so its type is unknown"
+    );
+}
+
+#[test]
+fn verify_cfg() {
+    let mut cache = FileResolver::default();
+    cache.set_file_contents("test.sol", "contract c {}".to_string());
+    let mut ns = parse_and_resolve(OsStr::new("test.sol"), &mut cache, Target::EVM);
+
+    let mut verify = |src: &str| {
+        let cfg = codegen::cfg_parser::parse_cfg(src, &mut ns).unwrap();
+
+        codegen::verify::verify_cfg(&cfg, &ns)
+    };
+
+    let valid = r#"
+# params: uint64 a
+block0: # entry
+    ty:uint64 %x = (arg #0)
+    branchcond (unsigned more %x > uint64 5), block1, block2
+block1: # then
+    ty:uint64 %x = uint64 5
+    branch block2
+block2: # endif
+    # phis: x
+    return %x
+block3: # unreachable
+"#;
+
+    assert_eq!(verify(valid), Vec::<String>::new());
+
+    let invalid = r#"
+# params: uint64 a
+block0: # entry
+    ty:uint32 %y = (arg #0)
+    return (overflowing %x + (arg #0))
+    branch block1
+block1: # loop
+    ty:uint64 %x = (zext uint64 (trunc uint128 (arg #0)))
+    branchcond %y, block1, block5
+"#;
+
+    assert_eq!(
+        verify(invalid),
+        [
+            "block0 instruction 1: terminator is not the last instruction in the block",
+            "block1 instruction 1: branch to non-existing block5",
+        ]
+    );
+
+    let invalid = r#"
+# params: uint64 a
+block0: # entry
+    ty:uint32 %y = (arg #0)
+    branchcond (arg #0), block1, block2
+block1: # then
+    ty:uint64 %x = (zext uint64 (trunc uint128 (arg #0)))
+    branch block2
+block2: # endif
+    return (%x + uint32 1)
+"#;
+
+    assert_eq!(
+        verify(invalid),
+        [
+            "block0 instruction 0: variable 'y' of type uint32 set to value of type uint64",
+            "block0 instruction 1: branch condition has type uint64",
+            "block1 instruction 0: extension from uint128 to smaller type uint64",
+            "block1 instruction 0: truncation from uint64 to larger type uint128",
+            "block2 instruction 0: operand of type uint32 in arithmetic of type uint64",
+        ]
+    );
+
+    let invalid = r#"
+block0: # entry
+    branchcond true, block1, block2
+block1: # then
+    ty:uint64 %x = uint64 1
+    branch block2
+block2: # endif
+    return %x, %y
+block3: # never
+    ty:uint64 %y = uint64 2
+    return %y
+"#;
+
+    assert_eq!(
+        verify(invalid),
+        ["block2 instruction 0: variable 'y' is used before it is defined"]
+    );
+}
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::cfg::{ASTFunction, ControlFlowGraph, Instr, InternalCallTy};
+use super::Expression;
+use crate::sema::ast::{Diagnostic, Namespace, Note};
+use crate::sema::Recurse;
+use solang_parser::pt::FunctionTy;
+
+/// Report the functions of a contract which cannot be reached from any public function,
+/// constructor, fallback, receive or the storage initializer. This should be called before
+/// inlining, so that functions which are inlined everywhere are not reported.
+pub(super) fn report_unreachable_functions(
+    contract_no: usize,
+    all_cfg: &[ControlFlowGraph],
+    ns: &mut Namespace,
+) {
+    let roots = all_cfg.iter().enumerate().filter_map(|(cfg_no, cfg)| {
+        if cfg.public || cfg.ty == FunctionTy::Constructor || cfg.function_no == ASTFunction::None {
+            Some(cfg_no)
+        } else {
+            None
+        }
+    });
+
+    let reachable = reachable_cfgs(all_cfg, roots);
+
+    let contract = &ns.contracts[contract_no];
+
+    let notes: Vec<Note> = contract
+        .all_functions
+        .iter()
+        .filter(|(function_no, cfg_no)| {
+            let func = &ns.functions[**function_no];
+
+            // functions which are overridden are never called, that is not worth reporting
+            let overridden = contract
+                .virtual_functions
+                .get(&func.signature)
+                .is_some_and(|list| list.last() != Some(function_no));
+
+            func.ty == FunctionTy::Function
+                && !overridden
+                && !all_cfg[**cfg_no].is_placeholder()
+                && !reachable[**cfg_no]
+        })
+        .map(|(function_no, _)| {
+            let func = &ns.functions[*function_no];
+
+            Note {
+                loc: func.loc,
+                message: format!("function '{}' is never called", func.id),
+            }
+        })
+        .collect();
+
+    if !notes.is_empty() {
+        let mut diagnostic = Diagnostic::info(
+            contract.id.loc,
+            format!(
+                "contract '{}' has unreachable functions which are not included in the output",
+                contract.id
+            ),
+        );
+
+        diagnostic.notes = notes;
+
+        ns.diagnostics.push(diagnostic);
+    }
+}
+
+/// Replace the cfgs which cannot be reached from the dispatch cfgs, the constructors or the storage
+/// initializer with placeholders, so that they are not emitted. The cfg numbers stay the same.
+pub(super) fn remove_unreachable_cfgs(all_cfg: &mut [ControlFlowGraph]) {
+    let roots = all_cfg.iter().enumerate().filter_map(|(cfg_no, cfg)| {
+        if cfg.ty == FunctionTy::Constructor || cfg.function_no == ASTFunction::None {
+            Some(cfg_no)
+        } else {
+            None
+        }
+    });
+
+    let reachable = reachable_cfgs(all_cfg, roots);
+
+    for (cfg_no, cfg) in all_cfg.iter_mut().enumerate() {
+        if !reachable[cfg_no] {
+            *cfg = ControlFlowGraph::placeholder();
+        }
+    }
+}
+
+/// Walk the call graph from the given roots. Internal function pointers are followed too,
+/// since they can be called dynamically.
+fn reachable_cfgs(all_cfg: &[ControlFlowGraph], roots: impl Iterator<Item = usize>) -> Vec<bool> {
+    let mut reachable = vec![false; all_cfg.len()];
+    let mut todo: Vec<usize> = roots.collect();
+
+    while let Some(cfg_no) = todo.pop() {
+        if reachable[cfg_no] {
+            continue;
+        }
+
+        reachable[cfg_no] = true;
+
+        for instr in all_cfg[cfg_no]
+            .blocks
+            .iter()
+            .flat_map(|block| block.instr.iter())
+        {
+            match instr {
+                Instr::Call {
+                    call: InternalCallTy::Static { cfg_no },
+                    ..
+                } => todo.push(*cfg_no),
+                Instr::Call {
+                    call: InternalCallTy::Dynamic(expr),
+                    ..
+                } => expr.recurse(&mut todo, function_pointers),
+                _ => (),
+            }
+
+            instr.recurse_expressions(&mut todo, function_pointers);
+        }
+    }
+
+    reachable
+}
+
+fn function_pointers(expr: &Expression, todo: &mut Vec<usize>) -> bool {
+    if let Expression::InternalFunctionCfg { cfg_no, .. } = expr {
+        todo.push(*cfg_no);
+    }
+
+    true
+}
@@ -37,6 +37,17 @@ fn find_writable_vectors(
     writable: &mut HashSet<Def>,
 ) {
     for instr_no in 0..block.instr.len() {
+        // inline assembly can write to a vector through its address
+        let mut exposed = Vec::new();
+
+        block.instr[instr_no].recurse_expressions(&mut exposed, find_pointer_casts);
+
+        for var_no in exposed {
+            if let Some(entry) = vars.get(&var_no) {
+                writable.extend(entry.keys());
+            }
+        }
+
         match &block.instr[instr_no] {
             Instr::Set {
                 res,
@@ -126,6 +137,22 @@ fn find_writable_vectors(
     }
 }
 
+/// Collect the variables which are converted to an address
+fn find_pointer_casts(expr: &Expression, exposed: &mut Vec<usize>) -> bool {
+    if let Expression::Cast {
+        ty: Type::Uint(_),
+        expr: inner,
+        ..
+    } = expr
+    {
+        if let Expression::Variable { var_no, .. } = inner.as_ref() {
+            exposed.push(*var_no);
+        }
+    }
+
+    true
+}
+
 fn apply_transfers(
     transfers: &[Transfer],
     vars: &mut IndexMap<usize, IndexMap<Def, bool>>,
@@ -0,0 +1,477 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::cfg::{BasicBlock, ControlFlowGraph, Instr};
+use super::reaching_definitions::{self, Transfer};
+use super::Expression;
+use crate::sema::ast::{Namespace, RetrieveType, Type};
+use std::collections::HashSet;
+
+/// Check that the CFG is well formed: every block ends in a terminator, all branches go to
+/// existing blocks, the types of the variables and expressions are consistent, and variables
+/// are defined before they are used. Any problems are returned as messages; an empty list
+/// means the CFG is valid.
+///
+/// The emitter assumes all of these hold, so a pass which breaks any of them would otherwise
+/// result in a panic or invalid code much later.
+pub fn verify_cfg(cfg: &ControlFlowGraph, ns: &Namespace) -> Vec<String> {
+    if cfg.is_placeholder() {
+        return Vec::new();
+    }
+
+    let mut verifier = Verifier {
+        cfg,
+        ns,
+        errors: Vec::new(),
+        position: String::new(),
+    };
+
+    verifier.structure();
+
+    // The reaching definitions cannot be calculated if the blocks are malformed
+    if verifier.errors.is_empty() {
+        verifier.types();
+        verifier.definitions();
+    }
+
+    verifier.errors
+}
+
+struct Verifier<'a> {
+    cfg: &'a ControlFlowGraph,
+    ns: &'a Namespace,
+    errors: Vec<String>,
+    /// The instruction being verified, for the messages
+    position: String,
+}
+
+impl<'a> Verifier<'a> {
+    fn error(&mut self, message: String) {
+        self.errors.push(format!("{}: {}", self.position, message));
+    }
+
+    /// Every block should end with exactly one terminator, and the branches should go to
+    /// existing blocks
+    fn structure(&mut self) {
+        let cfg = self.cfg;
+
+        // Blocks which are not reachable are not emitted, so they may be incomplete
+        let mut reachable = HashSet::from([0]);
+        let mut todo = vec![0];
+
+        while let Some(block_no) = todo.pop() {
+            for target in cfg.blocks[block_no]
+                .instr
+                .last()
+                .map_or(vec![], branch_targets)
+            {
+                if target < cfg.blocks.len() && reachable.insert(target) {
+                    todo.push(target);
+                }
+            }
+        }
+
+        for (block_no, block) in cfg.blocks.iter().enumerate() {
+            if !reachable.contains(&block_no) {
+                continue;
+            }
+
+            self.position = format!("block{block_no}");
+
+            if block.instr.is_empty() {
+                self.error("block is empty".into());
+                continue;
+            }
+
+            let last = block.instr.len() - 1;
+
+            for (instr_no, instr) in block.instr.iter().enumerate() {
+                self.position = format!("block{block_no} instruction {instr_no}");
+
+                if !matches!(
+                    instr,
+                    Instr::Branch { .. } | Instr::BranchCond { .. } | Instr::Switch { .. }
+                ) {
+                    if is_terminator(instr) != (instr_no == last) {
+                        self.error(if instr_no == last {
+                            "block does not end with a terminator".into()
+                        } else {
+                            "terminator is not the last instruction in the block".into()
+                        });
+                    }
+
+                    continue;
+                }
+
+                if instr_no != last {
+                    self.error("branch is not the last instruction in the block".into());
+                }
+
+                for target in branch_targets(instr) {
+                    if target >= cfg.blocks.len() {
+                        self.error(format!("branch to non-existing block{target}"));
+                    }
+                }
+            }
+
+            if let Some(phis) = &block.phis {
+                self.position = format!("block{block_no}");
+
+                for var_no in phis {
+                    if !cfg.vars.contains_key(var_no) {
+                        self.error(format!("phi for non-existing variable {var_no}"));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The variables which are set should exist, and have the type of the value they are
+    /// set to. The operands of expressions should have the types the expression expects.
+    fn types(&mut self) {
+        let cfg = self.cfg;
+
+        for (block_no, block) in cfg.blocks.iter().enumerate() {
+            for (instr_no, instr) in block.instr.iter().enumerate() {
+                self.position = format!("block{block_no} instruction {instr_no}");
+
+                match instr {
+                    Instr::Set { res, expr, .. } => {
+                        if let (Some(var_ty), Some(ty)) = (self.var_ty(*res), expr_ty(expr)) {
+                            if !self.same_type(&var_ty, &ty) {
+                                self.error(format!(
+                                    "variable '{}' of type {} set to value of type {}",
+                                    cfg.vars[res].id.name,
+                                    var_ty.to_string(self.ns),
+                                    ty.to_string(self.ns)
+                                ));
+                            }
+                        }
+                    }
+                    Instr::BranchCond { cond, .. } => {
+                        if let Some(ty) = expr_ty(cond) {
+                            if !self.same_type(&ty, &Type::Bool) {
+                                self.error(format!(
+                                    "branch condition has type {}",
+                                    ty.to_string(self.ns)
+                                ));
+                            }
+                        }
+                    }
+                    Instr::Call { res, .. } => {
+                        for res in res {
+                            self.var_ty(*res);
+                        }
+                    }
+                    Instr::LoadStorage { res, .. } | Instr::PushMemory { res, .. } => {
+                        self.var_ty(*res);
+                    }
+                    _ => (),
+                }
+
+                instr.recurse_expressions(self, check_expression);
+            }
+        }
+    }
+
+    /// A variable which is set somewhere should be set on some path to each of its uses.
+    /// Variables which are never set are fine, since they are zero initialized.
+    fn definitions(&mut self) {
+        let mut cfg = self.cfg.clone();
+
+        // This gives us the transfers for each instruction
+        reaching_definitions::find(&mut cfg);
+
+        let sets = |block: &BasicBlock, instr_no: usize, defined: &mut HashSet<usize>| {
+            for transfer in &block.transfers[instr_no] {
+                if let Transfer::Gen { var_no, .. } | Transfer::Copy { var_no, .. } = transfer {
+                    defined.insert(*var_no);
+                }
+            }
+
+            // These instructions set a variable, but have no transfer
+            match &block.instr[instr_no] {
+                Instr::PushStorage { res, .. } | Instr::PopMemory { res, .. } => {
+                    defined.insert(*res);
+                }
+                Instr::AccountAccess { var_no, .. } => {
+                    defined.insert(*var_no);
+                }
+                _ => (),
+            }
+        };
+
+        let mut set_anywhere = HashSet::new();
+
+        for block in &cfg.blocks {
+            for instr_no in 0..block.instr.len() {
+                sets(block, instr_no, &mut set_anywhere);
+            }
+        }
+
+        // The variables which are set on some path to the start of each block. Blocks which
+        // are not reachable stay None.
+        let mut block_defined: Vec<Option<HashSet<usize>>> = vec![None; cfg.blocks.len()];
+        block_defined[0] = Some(HashSet::new());
+
+        let mut todo = vec![0];
+
+        while let Some(block_no) = todo.pop() {
+            let block = &cfg.blocks[block_no];
+            let mut defined = block_defined[block_no].clone().unwrap();
+
+            for instr_no in 0..block.instr.len() {
+                sets(block, instr_no, &mut defined);
+            }
+
+            for edge in block.successors() {
+                let changed = match &mut block_defined[edge] {
+                    Some(entry) => {
+                        let len = entry.len();
+                        entry.extend(defined.iter().copied());
+                        entry.len() != len
+                    }
+                    entry => {
+                        *entry = Some(defined.clone());
+                        true
+                    }
+                };
+
+                if changed {
+                    todo.push(edge);
+                }
+            }
+        }
+
+        // Only report each variable once
+        let mut reported = HashSet::new();
+
+        for (block_no, block) in cfg.blocks.iter().enumerate() {
+            let Some(mut defined) = block_defined[block_no].clone() else {
+                continue;
+            };
+
+            for (instr_no, instr) in block.instr.iter().enumerate() {
+                let mut used = Vec::new();
+
+                instr.recurse_expressions(&mut used, |expr, used: &mut Vec<usize>| {
+                    if let Expression::Variable { var_no, .. } = expr {
+                        used.push(*var_no);
+                    }
+                    true
+                });
+
+                for var_no in used {
+                    if set_anywhere.contains(&var_no)
+                        && !defined.contains(&var_no)
+                        && reported.insert(var_no)
+                    {
+                        self.position = format!("block{block_no} instruction {instr_no}");
+
+                        let name = cfg
+                            .vars
+                            .get(&var_no)
+                            .map(|var| var.id.name.as_str())
+                            .unwrap_or_default();
+
+                        self.error(format!("variable '{name}' is used before it is defined"));
+                    }
+                }
+
+                sets(block, instr_no, &mut defined);
+            }
+        }
+    }
+
+    /// The type of the variable, or an error if it does not exist
+    fn var_ty(&mut self, var_no: usize) -> Option<Type> {
+        match self.cfg.vars.get(&var_no) {
+            Some(var) => Some(var.ty.clone()),
+            None => {
+                self.error(format!("variable {var_no} does not exist"));
+                None
+            }
+        }
+    }
+
+    /// Do the two types have the same representation. Codegen freely mixes types with the
+    /// same representation, e.g. `string` and `bytes`, signed and unsigned integers, or
+    /// storage references and storage slots.
+    fn same_type(&self, left: &Type, right: &Type) -> bool {
+        match (self.representation(left), self.representation(right)) {
+            // Inline assembly uses uint256 for storage slots on every target
+            (Type::StorageRef(..), Type::Uint(_)) | (Type::Uint(_), Type::StorageRef(..)) => true,
+            (left, right) => left == right,
+        }
+    }
+
+    fn representation(&self, ty: &Type) -> Type {
+        match ty {
+            Type::UserType(no) => self.representation(&self.ns.user_types[*no].ty),
+            Type::Enum(no) => self.ns.enums[*no].ty.clone(),
+            Type::Int(bits) => Type::Uint(*bits),
+            Type::Bytes(bytes) => Type::Uint(*bytes as u16 * 8),
+            Type::StorageRef(..) => Type::StorageRef(false, Type::Void.into()),
+            Type::Contract(_) | Type::Address(_) => Type::Address(false),
+            // All of these are pointers
+            Type::String
+            | Type::DynamicBytes
+            | Type::Slice(_)
+            | Type::Struct(_)
+            | Type::Array(..)
+            | Type::Ref(_)
+            | Type::BufferPointer => Type::BufferPointer,
+            _ => ty.clone(),
+        }
+    }
+
+    /// The number of bits in an integer type, if it is one
+    fn int_bits(&self, ty: &Type) -> Option<u16> {
+        match self.representation(ty) {
+            Type::Uint(bits) => Some(bits),
+            ty @ (Type::Bool | Type::Address(_) | Type::Value | Type::FunctionSelector) => {
+                Some(ty.bits(self.ns))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn check_expression(expr: &Expression, verifier: &mut Verifier) -> bool {
+    let ns = verifier.ns;
+
+    match expr {
+        Expression::Variable { var_no, .. } => {
+            verifier.var_ty(*var_no);
+        }
+        Expression::Add {
+            ty, left, right, ..
+        }
+        | Expression::Subtract {
+            ty, left, right, ..
+        }
+        | Expression::Multiply {
+            ty, left, right, ..
+        }
+        | Expression::SignedDivide {
+            ty, left, right, ..
+        }
+        | Expression::UnsignedDivide {
+            ty, left, right, ..
+        }
+        | Expression::SignedModulo {
+            ty, left, right, ..
+        }
+        | Expression::UnsignedModulo {
+            ty, left, right, ..
+        } => {
+            for operand in [left, right] {
+                if let Some(operand_ty) = expr_ty(operand) {
+                    if !verifier.same_type(ty, &operand_ty) {
+                        verifier.error(format!(
+                            "operand of type {} in arithmetic of type {}",
+                            operand_ty.to_string(ns),
+                            ty.to_string(ns)
+                        ));
+                    }
+                }
+            }
+        }
+        Expression::More { left, right, .. }
+        | Expression::Less { left, right, .. }
+        | Expression::MoreEqual { left, right, .. }
+        | Expression::LessEqual { left, right, .. } => {
+            if let (Some(left), Some(right)) = (expr_ty(left), expr_ty(right)) {
+                if !verifier.same_type(&left, &right) {
+                    verifier.error(format!(
+                        "comparison of {} with {}",
+                        left.to_string(ns),
+                        right.to_string(ns)
+                    ));
+                }
+            }
+        }
+        Expression::Not { expr, .. } => {
+            if let Some(ty) = expr_ty(expr) {
+                if !verifier.same_type(&ty, &Type::Bool) {
+                    verifier.error(format!("logical not of type {}", ty.to_string(ns)));
+                }
+            }
+        }
+        Expression::ZeroExt { ty, expr, .. } | Expression::SignExt { ty, expr, .. } => {
+            if let Some(from) = expr_ty(expr) {
+                if let (Some(to_bits), Some(from_bits)) =
+                    (verifier.int_bits(ty), verifier.int_bits(&from))
+                {
+                    if to_bits < from_bits {
+                        verifier.error(format!(
+                            "extension from {} to smaller type {}",
+                            from.to_string(ns),
+                            ty.to_string(ns)
+                        ));
+                    }
+                }
+            }
+        }
+        Expression::Trunc { ty, expr, .. } => {
+            if let Some(from) = expr_ty(expr) {
+                if let (Some(to_bits), Some(from_bits)) =
+                    (verifier.int_bits(ty), verifier.int_bits(&from))
+                {
+                    if to_bits > from_bits {
+                        verifier.error(format!(
+                            "truncation from {} to larger type {}",
+                            from.to_string(ns),
+                            ty.to_string(ns)
+                        ));
+                    }
+                }
+            }
+        }
+        Expression::Poison => {
+            verifier.error("poison expression".into());
+        }
+        _ => (),
+    }
+
+    true
+}
+
+/// The type of the expression, if it has a single type
+fn expr_ty(expr: &Expression) -> Option<Type> {
+    match expr {
+        Expression::Poison => None,
+        Expression::Builtin { tys, .. } if tys.len() != 1 => None,
+        _ => Some(expr.ty()),
+    }
+}
+
+/// The blocks the instruction branches to
+fn branch_targets(instr: &Instr) -> Vec<usize> {
+    match instr {
+        Instr::Branch { block } => vec![*block],
+        Instr::BranchCond {
+            true_block,
+            false_block,
+            ..
+        } => vec![*true_block, *false_block],
+        Instr::Switch { cases, default, .. } => cases
+            .iter()
+            .map(|(_, block)| *block)
+            .chain([*default])
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Does the instruction end a block without branching
+fn is_terminator(instr: &Instr) -> bool {
+    matches!(
+        instr,
+        Instr::AssertFailure { .. }
+            | Instr::SelfDestruct { .. }
+            | Instr::ReturnCode { .. }
+            | Instr::ReturnData { .. }
+            | Instr::Return { .. }
+            | Instr::Unimplemented { reachable: false }
+    )
+}
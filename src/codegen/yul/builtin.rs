@@ -5,7 +5,7 @@ use crate::{
         cfg::{ControlFlowGraph, Instr},
         revert::{assert_failure, log_runtime_error, PanicCode, SolidityError},
         vartable::Vartable,
-        yul::expression::expression,
+        yul::{expression::expression, memory},
         {Builtin, Expression, Options},
     },
     sema::{
@@ -92,13 +92,26 @@ pub(crate) fn process_builtin(
             byte_builtin(loc, args, contract_no, ns, cfg, vartab, opt)
         }
 
+        YulBuiltInFunction::Keccak256
+        | YulBuiltInFunction::MLoad
+        | YulBuiltInFunction::MStore
+        | YulBuiltInFunction::MStore8
+        | YulBuiltInFunction::MSize
+        | YulBuiltInFunction::CallDataLoad
+        | YulBuiltInFunction::CallDataSize
+        | YulBuiltInFunction::CallDataCopy
+        | YulBuiltInFunction::ReturnDataSize
+        | YulBuiltInFunction::ReturnDataCopy if ns.target != Target::EVM => {
+            process_memory_builtin(loc, builtin_ty, args, contract_no, ns, vartab, cfg, opt)
+        }
+
         YulBuiltInFunction::SignExtend
         | YulBuiltInFunction::Keccak256
         | YulBuiltInFunction::Pop
         | YulBuiltInFunction::Pc
         | YulBuiltInFunction::ChainId
         | YulBuiltInFunction::BaseFee
-        // Memory functions: need to convert between number to pointer type
+        // Memory functions are only implemented for Solana and Polkadot
         | YulBuiltInFunction::MLoad
         | YulBuiltInFunction::MStore
         | YulBuiltInFunction::MStore8
@@ -106,7 +119,7 @@ pub(crate) fn process_builtin(
         // Storage function: need to think about how to deal with pointer size and the size of chunk to load
         | YulBuiltInFunction::SStore
         | YulBuiltInFunction::SLoad
-        // Calldata functions: the same problems with other memory functions
+        // Calldata functions
         | YulBuiltInFunction::CallDataLoad
         | YulBuiltInFunction::CallDataSize
         | YulBuiltInFunction::CallDataCopy
@@ -412,6 +425,83 @@ fn process_arithmetic(
     }
 }
 
+/// Process the builtins which access memory, call data and return data
+fn process_memory_builtin(
+    loc: &pt::Loc,
+    builtin_ty: YulBuiltInFunction,
+    args: &[ast::YulExpression],
+    contract_no: usize,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+    opt: &Options,
+) -> Expression {
+    let args: Vec<Expression> = args
+        .iter()
+        .map(|arg| {
+            let expr = expression(arg, contract_no, ns, vartab, cfg, opt);
+            memory_argument(expr, ns)
+        })
+        .collect();
+
+    match builtin_ty {
+        YulBuiltInFunction::MLoad => memory::mload(loc, args[0].clone(), ns, vartab, cfg),
+        YulBuiltInFunction::MStore => {
+            memory::mstore(loc, args[0].clone(), args[1].clone(), ns, vartab, cfg);
+            Expression::Poison
+        }
+        YulBuiltInFunction::MStore8 => {
+            memory::mstore8(loc, args[0].clone(), args[1].clone(), ns, vartab, cfg);
+            Expression::Poison
+        }
+        YulBuiltInFunction::MSize => memory::msize(loc, ns, vartab, cfg),
+        YulBuiltInFunction::Keccak256 => {
+            memory::keccak256(loc, args[0].clone(), args[1].clone(), ns, vartab, cfg)
+        }
+        YulBuiltInFunction::CallDataLoad => {
+            memory::calldataload(loc, args[0].clone(), ns, vartab, cfg)
+        }
+        YulBuiltInFunction::CallDataSize | YulBuiltInFunction::ReturnDataSize => {
+            let return_data = builtin_ty == YulBuiltInFunction::ReturnDataSize;
+            let buf = memory::data_buffer(loc, return_data, vartab, cfg);
+            memory::data_size(buf, ns)
+        }
+        YulBuiltInFunction::CallDataCopy | YulBuiltInFunction::ReturnDataCopy => {
+            let return_data = builtin_ty == YulBuiltInFunction::ReturnDataCopy;
+            memory::data_copy(
+                loc,
+                return_data,
+                args[0].clone(),
+                args[1].clone(),
+                args[2].clone(),
+                ns,
+                vartab,
+                cfg,
+            );
+            Expression::Poison
+        }
+        _ => unreachable!("not a memory builtin"),
+    }
+}
+
+/// Memory builtins take 256 bit words; pointers to memory objects are their addresses
+fn memory_argument(expr: Expression, ns: &Namespace) -> Expression {
+    let expr = match expr {
+        Expression::BoolLiteral { .. } => expr.to_number_literal(),
+        _ => cast_to_number(expr, ns),
+    };
+
+    match expr.ty() {
+        Type::Bytes(n) => Expression::Cast {
+            loc: pt::Loc::Codegen,
+            ty: Type::Uint(n as u16 * 8),
+            expr: expr.into(),
+        }
+        .cast(&Type::Uint(256), ns),
+        _ => expr.cast(&Type::Uint(256), ns),
+    }
+}
+
 /// Arithmetic operations work on numbers, so addresses and pointers need to be
 /// converted to integers
 fn cast_to_number(expr: Expression, ns: &Namespace) -> Expression {
@@ -524,6 +614,21 @@ fn branch_if_zero(
     );
     cfg.add(vartab, Instr::Branch { block: endif });
 
+    // The operation is done in the type of the operands, but the result is always uint256
+    let codegen_expr = match codegen_expr.ty() {
+        Type::Uint(bits) if bits < 256 => Expression::ZeroExt {
+            loc: pt::Loc::Codegen,
+            ty: Type::Uint(256),
+            expr: Box::new(codegen_expr),
+        },
+        Type::Int(bits) if bits < 256 => Expression::SignExt {
+            loc: pt::Loc::Codegen,
+            ty: Type::Uint(256),
+            expr: Box::new(codegen_expr),
+        },
+        _ => codegen_expr,
+    };
+
     cfg.set_basic_block(else_);
     cfg.add(
         vartab,
@@ -120,13 +120,14 @@ fn process_suffix_access(
     match suffix {
         YulSuffix::Slot => match expr {
             ast::YulExpression::StorageVariable(loc, _, contract_no, var_no) => {
-                return ns.contracts[*contract_no].get_storage_slot(
-                    *loc,
-                    *contract_no,
-                    *var_no,
-                    ns,
-                    Some(Type::Uint(256)),
-                );
+                return Expression::NumberLiteral {
+                    loc: *loc,
+                    ty: Type::Uint(256),
+                    value: ns.contracts[*contract_no]
+                        .get_storage_layout(*contract_no, *var_no)
+                        .slot
+                        .clone(),
+                };
             }
             ast::YulExpression::SolidityLocalVariable(
                 loc,
@@ -134,16 +135,42 @@ fn process_suffix_access(
                 Some(StorageLocation::Storage(_)),
                 var_no,
             ) => {
-                return Expression::Variable {
+                let slot = Expression::Variable {
                     loc: *loc,
-                    ty: Type::Uint(256),
+                    ty: ns.storage_type(),
                     var_no: *var_no,
                 };
+
+                // On Solana, storage slots are smaller than yul's uint256
+                return if ns.storage_type() == Type::Uint(256) {
+                    slot
+                } else {
+                    Expression::ZeroExt {
+                        loc: *loc,
+                        ty: Type::Uint(256),
+                        expr: Box::new(slot),
+                    }
+                };
             }
 
             _ => (),
         },
         YulSuffix::Offset => match expr {
+            // storage is only packed on Polkadot
+            ast::YulExpression::StorageVariable(_, _, contract_no, var_no)
+                if ns.target.is_polkadot() =>
+            {
+                let offset = ns.contracts[*contract_no]
+                    .get_storage_layout(*contract_no, *var_no)
+                    .offset
+                    .unwrap_or_default();
+
+                return Expression::NumberLiteral {
+                    loc: Loc::Codegen,
+                    ty: Type::Uint(256),
+                    value: BigInt::from(offset),
+                };
+            }
             ast::YulExpression::StorageVariable(..)
             | ast::YulExpression::SolidityLocalVariable(
                 _,
@@ -0,0 +1,622 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Memory for inline assembly on Solana and Polkadot.
+//!
+//! Contracts whose inline assembly accesses memory reserve an area of 0x880 bytes at the end of
+//! the heap. Addresses below 0x880 in assembly are relative to this area: 0x00 to 0x7f are the
+//! scratch space, the free memory pointer (0x40) and the zero slot (0x60), as on EVM, and the
+//! free memory pointer initially points to the arena at 0x80 which follows. Any other address is
+//! an address in the linear memory of the contract, so a Solidity memory variable evaluates to
+//! the address of its object, and an address assigned to a memory variable is used as is.
+//! Every access must be within the heap, else the contract reverts. The runtime reserves the
+//! area in `stdlib/heap.c`.
+
+use crate::codegen::{
+    cfg::{ControlFlowGraph, Instr},
+    revert::{assert_failure, PanicCode, SolidityError},
+    vartable::Vartable,
+    Builtin, Expression,
+};
+use crate::sema::ast::{Namespace, RetrieveType, Type};
+use crate::Target;
+use num_bigint::BigInt;
+use num_traits::Zero;
+use solang_parser::pt;
+
+/// Size of the scratch space, free memory pointer and zero slot
+const SCRATCH_SIZE: u64 = 0x80;
+
+/// Size of the arena the free memory pointer initially points to
+const ARENA_SIZE: u64 = 0x800;
+
+/// Size of the memory reserved for inline assembly at the end of the heap
+pub(crate) const YUL_MEMORY_SIZE: u64 = SCRATCH_SIZE + ARENA_SIZE;
+
+/// Address of the free memory pointer
+const FREE_MEMORY_POINTER: u64 = 0x40;
+
+/// The start and the size of the heap. On Polkadot, the heap extends to the end of the linear
+/// memory, which the linker fixes at 1 MiB.
+fn heap(ns: &Namespace) -> (u64, u64) {
+    match ns.target {
+        Target::Solana => (0x3_0000_0000, 32 * 1024),
+        Target::Polkadot { .. } => (0x10000, 0x10_0000 - 0x10000),
+        Target::EVM => unreachable!("EVM has its own memory"),
+    }
+}
+
+fn number(value: u64, bits: u16) -> Expression {
+    Expression::NumberLiteral {
+        loc: pt::Loc::Codegen,
+        ty: Type::Uint(bits),
+        value: BigInt::from(value),
+    }
+}
+
+/// Convert an address to a pointer into linear memory, reverting if `size` bytes at the address
+/// are not within the heap. Addresses below 0x880 are moved to the memory for inline assembly at
+/// the end of the heap.
+fn pointer(
+    loc: &pt::Loc,
+    address: &Expression,
+    size: &Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Expression {
+    let (heap_start, heap_size) = heap(ns);
+
+    let in_yul_memory = Expression::Less {
+        loc: pt::Loc::Codegen,
+        signed: false,
+        left: Box::new(address.clone()),
+        right: Box::new(number(YUL_MEMORY_SIZE, 256)),
+    };
+
+    let offset = Expression::Multiply {
+        loc: pt::Loc::Codegen,
+        ty: Type::Uint(256),
+        overflowing: true,
+        left: Box::new(in_yul_memory.cast(&Type::Uint(256), ns)),
+        right: Box::new(number(heap_start + heap_size - YUL_MEMORY_SIZE, 256)),
+    };
+
+    let address = temporary(
+        loc,
+        Expression::Add {
+            loc: pt::Loc::Codegen,
+            ty: Type::Uint(256),
+            overflowing: true,
+            left: Box::new(address.clone()),
+            right: Box::new(offset),
+        },
+        vartab,
+        cfg,
+    );
+
+    // an address below the start of the heap wraps around to a large offset
+    let heap_offset = Expression::Subtract {
+        loc: pt::Loc::Codegen,
+        ty: Type::Uint(256),
+        overflowing: true,
+        left: Box::new(address.clone()),
+        right: Box::new(number(heap_start, 256)),
+    };
+
+    let in_bounds = cfg.new_basic_block("in_bounds".into());
+    let out_of_bounds = cfg.new_basic_block("out_of_bounds".into());
+
+    if !matches!(size, Expression::NumberLiteral { .. }) {
+        let size_in_bounds = cfg.new_basic_block("size_in_bounds".into());
+
+        cfg.add(
+            vartab,
+            Instr::BranchCond {
+                cond: Expression::LessEqual {
+                    loc: pt::Loc::Codegen,
+                    signed: false,
+                    left: Box::new(size.clone()),
+                    right: Box::new(number(heap_size, 256)),
+                },
+                true_block: size_in_bounds,
+                false_block: out_of_bounds,
+            },
+        );
+
+        cfg.set_basic_block(size_in_bounds);
+    }
+
+    cfg.add(
+        vartab,
+        Instr::BranchCond {
+            cond: Expression::LessEqual {
+                loc: pt::Loc::Codegen,
+                signed: false,
+                left: Box::new(heap_offset),
+                right: Box::new(Expression::Subtract {
+                    loc: pt::Loc::Codegen,
+                    ty: Type::Uint(256),
+                    overflowing: true,
+                    left: Box::new(number(heap_size, 256)),
+                    right: Box::new(size.clone()),
+                }),
+            },
+            true_block: in_bounds,
+            false_block: out_of_bounds,
+        },
+    );
+
+    cfg.set_basic_block(out_of_bounds);
+    assert_failure(
+        loc,
+        SolidityError::Panic(PanicCode::OutOfMemory),
+        ns,
+        cfg,
+        vartab,
+    );
+
+    cfg.set_basic_block(in_bounds);
+
+    Expression::Cast {
+        loc: pt::Loc::Codegen,
+        ty: Type::BufferPointer,
+        expr: Box::new(address.cast(&Type::Uint(ns.target.ptr_size()), ns)),
+    }
+}
+
+/// Read a 256 bit big endian word from a buffer
+fn read_word(loc: &pt::Loc, buf: Expression, offset: Expression) -> Expression {
+    Expression::Cast {
+        loc: *loc,
+        ty: Type::Uint(256),
+        expr: Box::new(Expression::Builtin {
+            loc: *loc,
+            tys: vec![Type::Bytes(32)],
+            kind: Builtin::ReadFromBuffer,
+            args: vec![buf, offset],
+        }),
+    }
+}
+
+/// Store an expression in a new temporary, so that it is evaluated before any following
+/// memory writes
+fn temporary(
+    loc: &pt::Loc,
+    expr: Expression,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Expression {
+    let ty = expr.ty();
+    let res = vartab.temp_anonymous(&ty);
+
+    cfg.add(
+        vartab,
+        Instr::Set {
+            loc: *loc,
+            res,
+            expr,
+        },
+    );
+
+    Expression::Variable {
+        loc: *loc,
+        ty,
+        var_no: res,
+    }
+}
+
+/// mload(p)
+pub(super) fn mload(
+    loc: &pt::Loc,
+    address: Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Expression {
+    let buf = pointer(loc, &address, &number(32, 256), ns, vartab, cfg);
+    let word = read_word(loc, buf, number(0, 32));
+
+    temporary(loc, word, vartab, cfg)
+}
+
+/// mstore(p, v)
+pub(super) fn mstore(
+    loc: &pt::Loc,
+    address: Expression,
+    value: Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) {
+    let buf = pointer(loc, &address, &number(32, 256), ns, vartab, cfg);
+
+    cfg.add(
+        vartab,
+        Instr::WriteBuffer {
+            buf,
+            offset: number(0, 32),
+            value: Expression::Cast {
+                loc: *loc,
+                ty: Type::Bytes(32),
+                expr: Box::new(value),
+            },
+        },
+    );
+}
+
+/// mstore8(p, v)
+pub(super) fn mstore8(
+    loc: &pt::Loc,
+    address: Expression,
+    value: Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) {
+    let buf = pointer(loc, &address, &number(1, 256), ns, vartab, cfg);
+
+    cfg.add(
+        vartab,
+        Instr::WriteBuffer {
+            buf,
+            offset: number(0, 32),
+            value: value.cast(&Type::Uint(8), ns),
+        },
+    );
+}
+
+/// msize() is the free memory pointer; memory beyond it is not in use
+pub(super) fn msize(
+    loc: &pt::Loc,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Expression {
+    mload(loc, number(FREE_MEMORY_POINTER, 256), ns, vartab, cfg)
+}
+
+/// keccak256(p, n)
+pub(super) fn keccak256(
+    loc: &pt::Loc,
+    address: Expression,
+    size: Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Expression {
+    let source = pointer(loc, &address, &size, ns, vartab, cfg);
+    let size = size.cast(&Type::Uint(32), ns);
+    let buf = vartab.temp_anonymous(&Type::DynamicBytes);
+
+    cfg.add(
+        vartab,
+        Instr::Set {
+            loc: *loc,
+            res: buf,
+            expr: Expression::AllocDynamicBytes {
+                loc: *loc,
+                ty: Type::DynamicBytes,
+                size: Box::new(size.clone()),
+                initializer: None,
+            },
+        },
+    );
+
+    let buf = Expression::Variable {
+        loc: *loc,
+        ty: Type::DynamicBytes,
+        var_no: buf,
+    };
+
+    cfg.add(
+        vartab,
+        Instr::MemCopy {
+            source,
+            destination: buf.clone(),
+            bytes: size,
+        },
+    );
+
+    let hash = Expression::Cast {
+        loc: *loc,
+        ty: Type::Uint(256),
+        expr: Box::new(Expression::Builtin {
+            loc: *loc,
+            tys: vec![Type::Bytes(32)],
+            kind: Builtin::Keccak256,
+            args: vec![buf],
+        }),
+    };
+
+    temporary(loc, hash, vartab, cfg)
+}
+
+/// The call data, or the return data of the last call
+pub(super) fn data_buffer(
+    loc: &pt::Loc,
+    return_data: bool,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Expression {
+    let data = if return_data {
+        Expression::ReturnData { loc: *loc }
+    } else {
+        Expression::Builtin {
+            loc: *loc,
+            tys: vec![Type::DynamicBytes],
+            kind: Builtin::Calldata,
+            args: vec![],
+        }
+    };
+
+    temporary(loc, data, vartab, cfg)
+}
+
+/// calldatasize() or returndatasize()
+pub(super) fn data_size(buf: Expression, ns: &Namespace) -> Expression {
+    Expression::Builtin {
+        loc: pt::Loc::Codegen,
+        tys: vec![Type::Uint(32)],
+        kind: Builtin::ArrayLength,
+        args: vec![buf],
+    }
+    .cast(&Type::Uint(256), ns)
+}
+
+/// The number of bytes in a buffer from the offset to the end, or zero if the offset is out
+/// of bounds
+fn bytes_available(
+    buf: &Expression,
+    offset: &Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Expression {
+    let len = data_size(buf.clone(), ns);
+    let available = vartab.temp_anonymous(&Type::Uint(256));
+
+    let in_bounds = cfg.new_basic_block("in_bounds".into());
+    let out_of_bounds = cfg.new_basic_block("out_of_bounds".into());
+    let done = cfg.new_basic_block("done".into());
+
+    cfg.add(
+        vartab,
+        Instr::BranchCond {
+            cond: Expression::Less {
+                loc: pt::Loc::Codegen,
+                signed: false,
+                left: Box::new(offset.clone()),
+                right: Box::new(len.clone()),
+            },
+            true_block: in_bounds,
+            false_block: out_of_bounds,
+        },
+    );
+
+    vartab.new_dirty_tracker();
+
+    cfg.set_basic_block(in_bounds);
+    cfg.add(
+        vartab,
+        Instr::Set {
+            loc: pt::Loc::Codegen,
+            res: available,
+            expr: Expression::Subtract {
+                loc: pt::Loc::Codegen,
+                ty: Type::Uint(256),
+                overflowing: true,
+                left: Box::new(len),
+                right: Box::new(offset.clone()),
+            },
+        },
+    );
+    cfg.add(vartab, Instr::Branch { block: done });
+
+    cfg.set_basic_block(out_of_bounds);
+    cfg.add(
+        vartab,
+        Instr::Set {
+            loc: pt::Loc::Codegen,
+            res: available,
+            expr: Expression::NumberLiteral {
+                loc: pt::Loc::Codegen,
+                ty: Type::Uint(256),
+                value: BigInt::zero(),
+            },
+        },
+    );
+    cfg.add(vartab, Instr::Branch { block: done });
+
+    cfg.set_phis(done, vartab.pop_dirty_tracker());
+    cfg.set_basic_block(done);
+
+    Expression::Variable {
+        loc: pt::Loc::Codegen,
+        ty: Type::Uint(256),
+        var_no: available,
+    }
+}
+
+/// Branch on whether `size` bytes are available at `offset` in the buffer. Returns the
+/// number of available bytes, and the blocks for the in range and out of range cases.
+fn branch_on_range(
+    buf: &Expression,
+    offset: &Expression,
+    size: &Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> (Expression, usize, usize) {
+    let available = bytes_available(buf, offset, ns, vartab, cfg);
+
+    let in_range = cfg.new_basic_block("in_range".into());
+    let out_of_range = cfg.new_basic_block("out_of_range".into());
+
+    cfg.add(
+        vartab,
+        Instr::BranchCond {
+            cond: Expression::LessEqual {
+                loc: pt::Loc::Codegen,
+                signed: false,
+                left: Box::new(size.clone()),
+                right: Box::new(available.clone()),
+            },
+            true_block: in_range,
+            false_block: out_of_range,
+        },
+    );
+
+    (available, in_range, out_of_range)
+}
+
+/// calldataload(p); call data beyond the end reads as zero
+pub(super) fn calldataload(
+    loc: &pt::Loc,
+    offset: Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) -> Expression {
+    let calldata = data_buffer(loc, false, vartab, cfg);
+    let word = vartab.temp_anonymous(&Type::Uint(256));
+
+    let (available, in_range, out_of_range) =
+        branch_on_range(&calldata, &offset, &number(32, 256), ns, vartab, cfg);
+
+    let done = cfg.new_basic_block("done".into());
+
+    vartab.new_dirty_tracker();
+
+    cfg.set_basic_block(in_range);
+    cfg.add(
+        vartab,
+        Instr::Set {
+            loc: *loc,
+            res: word,
+            expr: read_word(loc, calldata.clone(), offset.cast(&Type::Uint(32), ns)),
+        },
+    );
+    cfg.add(vartab, Instr::Branch { block: done });
+
+    // copy what there is into a zeroed word
+    cfg.set_basic_block(out_of_range);
+    let padded = temporary(
+        loc,
+        Expression::AllocDynamicBytes {
+            loc: *loc,
+            ty: Type::DynamicBytes,
+            size: Box::new(number(32, 32)),
+            initializer: None,
+        },
+        vartab,
+        cfg,
+    );
+    cfg.add(
+        vartab,
+        Instr::MemCopy {
+            source: Expression::AdvancePointer {
+                pointer: Box::new(calldata),
+                bytes_offset: Box::new(offset.cast(&Type::Uint(32), ns)),
+            },
+            destination: padded.clone(),
+            bytes: available.cast(&Type::Uint(32), ns),
+        },
+    );
+    cfg.add(
+        vartab,
+        Instr::Set {
+            loc: *loc,
+            res: word,
+            expr: read_word(loc, padded, number(0, 32)),
+        },
+    );
+    cfg.add(vartab, Instr::Branch { block: done });
+
+    cfg.set_phis(done, vartab.pop_dirty_tracker());
+    cfg.set_basic_block(done);
+
+    Expression::Variable {
+        loc: *loc,
+        ty: Type::Uint(256),
+        var_no: word,
+    }
+}
+
+/// calldatacopy(t, f, s) or returndatacopy(t, f, s). Copying beyond the end of the call data
+/// writes zeros; copying beyond the end of the return data reverts.
+pub(super) fn data_copy(
+    loc: &pt::Loc,
+    return_data: bool,
+    destination: Expression,
+    offset: Expression,
+    size: Expression,
+    ns: &Namespace,
+    vartab: &mut Vartable,
+    cfg: &mut ControlFlowGraph,
+) {
+    let destination = pointer(loc, &destination, &size, ns, vartab, cfg);
+    let buf = data_buffer(loc, return_data, vartab, cfg);
+
+    let (available, in_range, out_of_range) =
+        branch_on_range(&buf, &offset, &size, ns, vartab, cfg);
+
+    let done = cfg.new_basic_block("done".into());
+
+    let source = Expression::AdvancePointer {
+        pointer: Box::new(buf),
+        bytes_offset: Box::new(offset.cast(&Type::Uint(32), ns)),
+    };
+
+    vartab.new_dirty_tracker();
+
+    cfg.set_basic_block(in_range);
+    cfg.add(
+        vartab,
+        Instr::MemCopy {
+            source: source.clone(),
+            destination: destination.clone(),
+            bytes: size.cast(&Type::Uint(32), ns),
+        },
+    );
+    cfg.add(vartab, Instr::Branch { block: done });
+
+    cfg.set_basic_block(out_of_range);
+
+    if return_data {
+        assert_failure(loc, SolidityError::Empty, ns, cfg, vartab);
+    } else {
+        // copy what there is into zeroed memory
+        let size = size.cast(&Type::Uint(32), ns);
+        let padded = temporary(
+            loc,
+            Expression::AllocDynamicBytes {
+                loc: *loc,
+                ty: Type::DynamicBytes,
+                size: Box::new(size.clone()),
+                initializer: None,
+            },
+            vartab,
+            cfg,
+        );
+        cfg.add(
+            vartab,
+            Instr::MemCopy {
+                source,
+                destination: padded.clone(),
+                bytes: available.cast(&Type::Uint(32), ns),
+            },
+        );
+        cfg.add(
+            vartab,
+            Instr::MemCopy {
+                source: padded,
+                destination,
+                bytes: size,
+            },
+        );
+        cfg.add(vartab, Instr::Branch { block: done });
+    }
+
+    cfg.set_phis(done, vartab.pop_dirty_tracker());
+    cfg.set_basic_block(done);
+}
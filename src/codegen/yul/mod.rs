@@ -15,9 +15,12 @@ use solang_parser::pt::FunctionTy;
 
 mod builtin;
 mod expression;
+mod memory;
 mod statements;
 mod tests;
 
+pub(crate) use memory::YUL_MEMORY_SIZE;
+
 /// Create the CFG instructions for inline assembly statements
 pub fn inline_assembly_cfg(
     inline_assembly: &InlineAssembly,
@@ -43,7 +46,13 @@ pub(crate) fn generate_yul_function_cfg(
 ) {
     let mut cfg = yul_function_cfg(contract_no, function_no, ns, opt);
 
-    optimize_and_check_cfg(&mut cfg, ns, ASTFunction::YulFunction(function_no), opt);
+    optimize_and_check_cfg(
+        &mut cfg,
+        contract_no,
+        ns,
+        ASTFunction::YulFunction(function_no),
+        opt,
+    );
     all_cfgs[ns.yul_functions[function_no].cfg_no] = cfg;
 }
 
@@ -248,12 +248,13 @@ fn cfg_single_assigment(
             match &**member {
                 ast::YulExpression::SolidityLocalVariable(
                     _,
-                    _,
+                    ty,
                     Some(StorageLocation::Calldata(_)),
                     var_no,
                 ) => match suffix {
                     YulSuffix::Offset => {
-                        let rhs = rhs.cast(&lhs.ty(), ns);
+                        // The offset is the pointer itself, so the rhs replaces the variable
+                        let rhs = rhs.cast(ty, ns);
                         cfg.add(
                             vartab,
                             Instr::Set {
@@ -154,9 +154,12 @@ fn contract_constant_variable() {
         variables: vec![var],
         creates: vec![],
         emits_events: vec![],
+        custom_errors: vec![],
+        yul_memory: false,
         initializer: None,
         default_constructor: None,
         cfg: vec![],
+        pass_dumps: vec![],
         code: OnceCell::new(),
         instantiable: true,
         program_id: None,
@@ -269,6 +272,7 @@ fn slot_suffix() {
     let loc = Loc::File(1, 2, 3);
     let layout = Layout {
         slot: BigInt::from(2),
+        offset: None,
         contract_no: 0,
         var_no: 0,
         ty: Type::Uint(256),
@@ -292,9 +296,12 @@ fn slot_suffix() {
         variables: vec![],
         creates: vec![],
         emits_events: vec![],
+        custom_errors: vec![],
+        yul_memory: false,
         initializer: None,
         default_constructor: None,
         cfg: vec![],
+        pass_dumps: vec![],
         code: OnceCell::new(),
         instantiable: true,
         program_id: None,
@@ -338,10 +345,14 @@ fn slot_suffix() {
     let res = expression(&expr, 0, &ns, &mut vartab, &mut cfg, &opt);
     assert_eq!(
         res,
-        Expression::Variable {
+        Expression::ZeroExt {
             loc,
             ty: Type::Uint(256),
-            var_no: 0
+            expr: Box::new(Expression::Variable {
+                loc,
+                ty: Type::Uint(32),
+                var_no: 0
+            })
         }
     );
 }
@@ -0,0 +1,586 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Static estimate of the compute units used by the public functions of a Solana program. This
+//! works on the SBF instructions of the emitted ELF file: every instruction costs one compute
+//! unit, and a call to a syscall costs the base amount the Solana runtime charges for it.
+//!
+//! The longest path through each function is found after removing the back edges of loops, so
+//! the estimate is an upper bound only if every loop runs at most once. Loops, recursion and calls
+//! into other programs are listed with the estimate, since their cost is not known. The part of
+//! the syscall cost which depends on the length of the arguments is not included either.
+
+use crate::abi::anchor::instruction_name;
+use crate::sema::ast::Namespace;
+use goblin::elf::{sym::STT_FUNC, Elf};
+use serde::Serialize;
+use solang_parser::pt::FunctionTy;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The cost of a syscall which is not known
+const DEFAULT_SYSCALL_COST: u64 = 100;
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ComputeUnitReport {
+    /// The compute units used by the entrypoint and the dispatcher, except for the functions
+    /// which are called by them
+    pub entrypoint: u64,
+    pub functions: Vec<FunctionEstimate>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct FunctionEstimate {
+    /// The name of the function, as used in the Anchor IDL
+    pub name: String,
+    /// The estimated upper bound, including the entrypoint and dispatcher
+    #[serde(rename = "computeUnits")]
+    pub compute_units: u64,
+    /// The function has been inlined into the dispatcher, so its cost is included in the
+    /// entrypoint
+    pub inlined: bool,
+    /// Why the estimate may be exceeded
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unbounded: Vec<String>,
+}
+
+/// Estimate the compute units of each public function of the contract, from its ELF file
+pub fn compute_unit_report(
+    contract_no: usize,
+    ns: &Namespace,
+    code: &[u8],
+) -> Result<ComputeUnitReport, String> {
+    let program = Program::parse(code)?;
+    let contract = &ns.contracts[contract_no];
+
+    let functions: Vec<(String, Option<usize>)> = contract
+        .all_functions
+        .iter()
+        .filter(|(func_no, _)| {
+            ns.function_externally_callable(contract_no, Some(**func_no))
+                && !matches!(
+                    ns.functions[**func_no].ty,
+                    FunctionTy::Fallback | FunctionTy::Receive | FunctionTy::Modifier
+                )
+        })
+        .map(|(func_no, cfg_no)| {
+            (
+                instruction_name(contract_no, *func_no, ns),
+                program.function_by_name(&contract.cfg[*cfg_no].name),
+            )
+        })
+        .collect();
+
+    let entrypoint = program
+        .function_by_name("entrypoint")
+        .ok_or("no entrypoint found")?;
+
+    // The cost of the entrypoint does not include the functions which are reported separately
+    let mut analysis = Analysis::new(
+        &program,
+        functions.iter().filter_map(|(_, func)| *func).collect(),
+    );
+
+    let overhead = analysis.function_cost(entrypoint);
+
+    let mut analysis = Analysis::new(&program, HashSet::new());
+
+    let functions = functions
+        .into_iter()
+        .map(|(name, func)| {
+            let mut unbounded = overhead.unbounded.clone();
+            let mut compute_units = overhead.units;
+
+            if let Some(func) = func {
+                let cost = analysis.function_cost(func);
+
+                compute_units += cost.units;
+                unbounded.extend(cost.unbounded);
+            }
+
+            FunctionEstimate {
+                name,
+                compute_units,
+                inlined: func.is_none(),
+                unbounded: unbounded.into_iter().collect(),
+            }
+        })
+        .collect();
+
+    Ok(ComputeUnitReport {
+        entrypoint: overhead.units,
+        functions,
+    })
+}
+
+/// The base cost of the syscalls, as charged by the Solana runtime
+fn syscall_cost(name: &str) -> u64 {
+    match name {
+        "sol_sha256" | "sol_keccak256" | "sol_blake3" => 85,
+        "sol_invoke_signed_c" | "sol_invoke_signed_rust" => 1_000,
+        "sol_create_program_address" | "sol_try_find_program_address" => 1_500,
+        "sol_secp256k1_recover" => 25_000,
+        "sol_memcpy_" | "sol_memmove_" | "sol_memset_" | "sol_memcmp_" => 10,
+        _ => DEFAULT_SYSCALL_COST,
+    }
+}
+
+const BPF_JMP: u8 = 0x05;
+const BPF_JA: u8 = 0x00;
+const BPF_CALL: u8 = 0x80;
+const BPF_EXIT: u8 = 0x90;
+const BPF_X: u8 = 0x08;
+const BPF_LD_DW_IMM: u8 = 0x18;
+
+#[derive(Debug, Clone, Copy)]
+struct Insn {
+    opcode: u8,
+    off: i16,
+    imm: i32,
+}
+
+impl Insn {
+    fn decode(bytes: &[u8]) -> Self {
+        Insn {
+            opcode: bytes[0],
+            off: i16::from_le_bytes([bytes[2], bytes[3]]),
+            imm: i32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        }
+    }
+
+    /// Number of 8 byte slots this instruction occupies
+    fn len(&self) -> usize {
+        if self.opcode == BPF_LD_DW_IMM {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn is_jump(&self) -> bool {
+        self.opcode & 0x07 == BPF_JMP && !matches!(self.opcode & 0xf0, BPF_CALL | BPF_EXIT)
+    }
+
+    fn is_unconditional_jump(&self) -> bool {
+        self.opcode == BPF_JMP | BPF_JA
+    }
+
+    fn is_call(&self) -> bool {
+        self.opcode == BPF_JMP | BPF_CALL
+    }
+
+    fn is_indirect_call(&self) -> bool {
+        self.opcode == BPF_JMP | BPF_CALL | BPF_X
+    }
+
+    fn is_exit(&self) -> bool {
+        self.opcode == BPF_JMP | BPF_EXIT
+    }
+}
+
+#[derive(Debug)]
+struct Function {
+    name: String,
+    start: usize,
+    end: usize,
+}
+
+/// The instructions and functions of the text section. Instructions are indexed by their 8 byte
+/// slot in the section.
+struct Program {
+    insns: Vec<Insn>,
+    functions: Vec<Function>,
+    /// The symbol called by the call instruction at the slot, from the relocations
+    calls: HashMap<usize, String>,
+}
+
+impl Program {
+    fn parse(code: &[u8]) -> Result<Self, String> {
+        let elf = Elf::parse(code).map_err(|e| e.to_string())?;
+
+        let (text_no, text) = elf
+            .section_headers
+            .iter()
+            .enumerate()
+            .find(|(_, sh)| elf.shdr_strtab.get_at(sh.sh_name) == Some(".text"))
+            .ok_or("no .text section found")?;
+
+        let bytes = code
+            .get(text.sh_offset as usize..(text.sh_offset + text.sh_size) as usize)
+            .ok_or(".text section is out of bounds")?;
+
+        let insns: Vec<Insn> = bytes.chunks_exact(8).map(Insn::decode).collect();
+
+        let addr = text.sh_addr;
+        let slot = |offset: u64| ((offset - addr) / 8) as usize;
+        let in_text = |offset: u64| offset >= addr && offset < addr + text.sh_size;
+
+        let mut functions: Vec<Function> = elf
+            .syms
+            .iter()
+            .filter(|sym| {
+                sym.st_type() == STT_FUNC && sym.st_shndx == text_no && in_text(sym.st_value)
+            })
+            .filter_map(|sym| {
+                Some(Function {
+                    name: elf.strtab.get_at(sym.st_name)?.to_owned(),
+                    start: slot(sym.st_value),
+                    end: 0,
+                })
+            })
+            .collect();
+
+        functions.sort_by_key(|func| func.start);
+        functions.dedup_by_key(|func| func.start);
+
+        for i in 0..functions.len() {
+            functions[i].end = functions.get(i + 1).map_or(insns.len(), |next| next.start);
+        }
+
+        let mut calls = HashMap::new();
+
+        // relocations of an object file
+        for (reloc_no, relocs) in &elf.shdr_relocs {
+            if elf.section_headers[*reloc_no].sh_info as usize != text_no {
+                continue;
+            }
+
+            for reloc in relocs.iter() {
+                if let Some(name) = elf
+                    .syms
+                    .get(reloc.r_sym)
+                    .and_then(|sym| elf.strtab.get_at(sym.st_name))
+                    .filter(|name| !name.is_empty())
+                {
+                    calls.insert(slot(reloc.r_offset + addr), name.to_owned());
+                }
+            }
+        }
+
+        // relocations of a shared object
+        for reloc in elf.dynrels.iter().filter(|reloc| in_text(reloc.r_offset)) {
+            if let Some(name) = elf
+                .dynsyms
+                .get(reloc.r_sym)
+                .and_then(|sym| elf.dynstrtab.get_at(sym.st_name))
+                .filter(|name| !name.is_empty())
+            {
+                calls.insert(slot(reloc.r_offset), name.to_owned());
+            }
+        }
+
+        Ok(Program {
+            insns,
+            functions,
+            calls,
+        })
+    }
+
+    fn function_by_name(&self, name: &str) -> Option<usize> {
+        self.functions.iter().position(|func| func.name == name)
+    }
+
+    fn function_at(&self, slot: usize) -> Option<usize> {
+        self.functions.iter().position(|func| func.start == slot)
+    }
+}
+
+#[derive(Clone, Default)]
+struct Cost {
+    units: u64,
+    unbounded: BTreeSet<String>,
+}
+
+struct Analysis<'a> {
+    program: &'a Program,
+    /// These functions are not included in the cost
+    excluded: HashSet<usize>,
+    done: HashMap<usize, Cost>,
+    in_progress: HashSet<usize>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(program: &'a Program, excluded: HashSet<usize>) -> Self {
+        Analysis {
+            program,
+            excluded,
+            done: HashMap::new(),
+            in_progress: HashSet::new(),
+        }
+    }
+
+    /// The cost of the longest path through the function, including the functions it calls
+    fn function_cost(&mut self, func_no: usize) -> Cost {
+        if let Some(cost) = self.done.get(&func_no) {
+            return cost.clone();
+        }
+
+        let program = self.program;
+        let func = &program.functions[func_no];
+
+        if !self.in_progress.insert(func_no) {
+            return Cost {
+                units: 0,
+                unbounded: BTreeSet::from([format!("recursive call to '{}'", func.name)]),
+            };
+        }
+
+        let cost = self.longest_path(func);
+
+        self.in_progress.remove(&func_no);
+        self.done.insert(func_no, cost.clone());
+
+        cost
+    }
+
+    fn longest_path(&mut self, func: &Function) -> Cost {
+        let program = self.program;
+        let insns = &program.insns;
+        let mut unbounded = BTreeSet::new();
+
+        // find the start of each basic block
+        let mut leaders = BTreeSet::from([func.start]);
+        let mut slot = func.start;
+
+        while slot < func.end {
+            let insn = &insns[slot];
+            let next = slot + insn.len();
+
+            if insn.is_jump() {
+                leaders.insert(jump_target(slot, insn));
+                leaders.insert(next);
+            } else if insn.is_exit() {
+                leaders.insert(next);
+            }
+
+            slot = next;
+        }
+
+        let leaders: Vec<usize> = leaders
+            .into_iter()
+            .filter(|slot| *slot >= func.start && *slot < func.end)
+            .collect();
+
+        if leaders.is_empty() {
+            return Cost::default();
+        }
+
+        let block_at = |slot: usize| leaders.binary_search(&slot).ok();
+
+        let mut units = Vec::with_capacity(leaders.len());
+        let mut successors = Vec::with_capacity(leaders.len());
+
+        for (block_no, start) in leaders.iter().enumerate() {
+            let end = leaders.get(block_no + 1).copied().unwrap_or(func.end);
+            let mut block_units = 0;
+            let mut block_successors = Vec::new();
+            let mut slot = *start;
+            let mut falls_through = true;
+
+            while slot < end {
+                let insn = insns[slot];
+
+                block_units += 1;
+
+                if insn.is_call() || insn.is_indirect_call() {
+                    let cost = self.call_cost(slot, &insn, func);
+                    block_units += cost.units;
+                    unbounded.extend(cost.unbounded);
+                } else if insn.is_jump() {
+                    block_successors.extend(block_at(jump_target(slot, &insn)));
+                    falls_through = !insn.is_unconditional_jump();
+                } else if insn.is_exit() {
+                    falls_through = false;
+                }
+
+                slot += insn.len();
+            }
+
+            if falls_through {
+                block_successors.extend(block_at(end));
+            }
+
+            units.push(block_units);
+            successors.push(block_successors);
+        }
+
+        // depth first search for the back edges, which form loops
+        let mut post_order = Vec::new();
+        let mut back_edges = HashSet::new();
+        let mut visited = vec![false; leaders.len()];
+        let mut on_stack = vec![false; leaders.len()];
+        let mut stack = vec![(0, 0)];
+
+        visited[0] = true;
+        on_stack[0] = true;
+
+        while let Some((block_no, succ_no)) = stack.pop() {
+            if let Some(succ) = successors[block_no].get(succ_no).copied() {
+                stack.push((block_no, succ_no + 1));
+
+                if on_stack[succ] {
+                    back_edges.insert((block_no, succ));
+                } else if !visited[succ] {
+                    visited[succ] = true;
+                    on_stack[succ] = true;
+                    stack.push((succ, 0));
+                }
+            } else {
+                on_stack[block_no] = false;
+                post_order.push(block_no);
+            }
+        }
+
+        if !back_edges.is_empty() {
+            unbounded.insert(format!("loop in '{}'", func.name));
+        }
+
+        // longest path through the acyclic graph, in topological order
+        let mut longest = vec![0; leaders.len()];
+
+        longest[0] = units[0];
+
+        for block_no in post_order.iter().rev() {
+            for succ in &successors[*block_no] {
+                if !back_edges.contains(&(*block_no, *succ)) {
+                    longest[*succ] =
+                        std::cmp::max(longest[*succ], longest[*block_no] + units[*succ]);
+                }
+            }
+        }
+
+        Cost {
+            units: longest.into_iter().max().unwrap_or_default(),
+            unbounded,
+        }
+    }
+
+    fn call_cost(&mut self, slot: usize, insn: &Insn, func: &Function) -> Cost {
+        if insn.is_indirect_call() {
+            return Cost {
+                units: 0,
+                unbounded: BTreeSet::from([format!("indirect call in '{}'", func.name)]),
+            };
+        }
+
+        let callee = if let Some(name) = self.program.calls.get(&slot) {
+            match self.program.function_by_name(name) {
+                Some(callee) => callee,
+                None => {
+                    let mut cost = Cost {
+                        units: syscall_cost(name),
+                        ..Default::default()
+                    };
+
+                    if name.starts_with("sol_invoke_signed") {
+                        cost.unbounded
+                            .insert(format!("'{}' invokes another program", func.name));
+                    }
+
+                    return cost;
+                }
+            }
+        } else {
+            // a relative call within the text section, or else a syscall by hash
+            match self.program.function_at(jump_target(slot, insn)) {
+                Some(callee) => callee,
+                None => {
+                    return Cost {
+                        units: DEFAULT_SYSCALL_COST,
+                        ..Default::default()
+                    }
+                }
+            }
+        };
+
+        if self.excluded.contains(&callee) {
+            Cost::default()
+        } else {
+            self.function_cost(callee)
+        }
+    }
+}
+
+/// The slot which a relative jump or call goes to
+fn jump_target(slot: usize, insn: &Insn) -> usize {
+    let offset = if insn.is_call() {
+        insn.imm as i64
+    } else {
+        insn.off as i64
+    };
+
+    (slot as i64 + 1 + offset) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Analysis, Function, Insn, Program};
+    use std::collections::{BTreeSet, HashMap, HashSet};
+
+    fn insn(opcode: u8, off: i16, imm: i32) -> Insn {
+        Insn { opcode, off, imm }
+    }
+
+    fn program() -> Program {
+        let insns = vec![
+            // entrypoint
+            insn(0xb7, 0, 1),
+            insn(0x85, 0, -1),
+            insn(0x85, 0, -1),
+            insn(0x95, 0, 0),
+            // foo
+            insn(0xb7, 0, 0),
+            insn(0x15, 2, 0),
+            insn(0x07, 0, 1),
+            insn(0x05, -3, 0),
+            insn(0x18, 0, 0),
+            insn(0x00, 0, 0),
+            insn(0x95, 0, 0),
+            // bar
+            insn(0x85, 0, -1),
+            insn(0x95, 0, 0),
+        ];
+
+        let function = |name: &str, start, end| Function {
+            name: name.to_owned(),
+            start,
+            end,
+        };
+
+        Program {
+            insns,
+            functions: vec![
+                function("entrypoint", 0, 4),
+                function("foo", 4, 11),
+                function("bar", 11, 13),
+            ],
+            calls: HashMap::from([(1, "foo".to_owned()), (2, "sol_log_".to_owned())]),
+        }
+    }
+
+    #[test]
+    fn longest_path() {
+        let program = program();
+
+        let mut analysis = Analysis::new(&program, HashSet::new());
+
+        let foo = analysis.function_cost(1);
+        assert_eq!(foo.units, 4);
+        assert_eq!(foo.unbounded, BTreeSet::from(["loop in 'foo'".to_owned()]));
+
+        let entrypoint = analysis.function_cost(0);
+        assert_eq!(entrypoint.units, 108);
+        assert_eq!(entrypoint.unbounded, foo.unbounded);
+
+        let bar = analysis.function_cost(2);
+        assert_eq!(bar.units, 2);
+        assert_eq!(
+            bar.unbounded,
+            BTreeSet::from(["recursive call to 'bar'".to_owned()])
+        );
+
+        let mut analysis = Analysis::new(&program, HashSet::from([1]));
+
+        let entrypoint = analysis.function_cost(0);
+        assert_eq!(entrypoint.units, 104);
+        assert!(entrypoint.unbounded.is_empty());
+    }
+}
@@ -175,7 +175,7 @@ impl<'a> Binary<'a> {
                 polkadot::PolkadotTarget::build(context, &std_lib, contract, ns, opt)
             }
             Target::Solana => solana::SolanaTarget::build(context, &std_lib, contract, ns, opt),
-            Target::EVM => unimplemented!(),
+            Target::EVM => unreachable!("EVM bytecode is generated by the evm module"),
         }
     }
 
@@ -198,7 +198,8 @@ impl<'a> Binary<'a> {
                 pass_manager.add_global_dce_pass();
                 pass_manager.add_constant_merge_pass();
 
-                pass_manager.run_on(&self.module);
+                self.options
+                    .time("llvm-optimization", || pass_manager.run_on(&self.module));
             }
             _ => {}
         }
@@ -216,20 +217,25 @@ impl<'a> Binary<'a> {
             )
             .unwrap();
 
-        let code = target_machine
-            .write_to_memory_buffer(
-                &self.module,
-                if generate == Generate::Assembly {
-                    FileType::Assembly
-                } else {
-                    FileType::Object
-                },
-            )
+        let code = self
+            .options
+            .time("llvm-codegen", || {
+                target_machine.write_to_memory_buffer(
+                    &self.module,
+                    if generate == Generate::Assembly {
+                        FileType::Assembly
+                    } else {
+                        FileType::Object
+                    },
+                )
+            })
             .map(|out| {
                 let slice = out.as_slice();
 
                 if generate == Generate::Linked {
-                    link(slice, &self.name, self.target).to_vec()
+                    self.options
+                        .time("link", || link(slice, &self.name, self.target))
+                        .to_vec()
                 } else {
                     slice.to_vec()
                 }
@@ -245,11 +251,14 @@ impl<'a> Binary<'a> {
 
             // Using the same config as cargo contract:
             // https://github.com/paritytech/cargo-contract/blob/71a8a42096e2df36d54a695d099aecfb1e394b78/crates/build/src/wasm_opt.rs#L67
-            OptimizationOptions::from(level)
-                .mvp_features_only()
-                .zero_filled_memory(true)
-                .debug_info(self.options.generate_debug_information)
-                .run(&infile, &outfile)
+            self.options
+                .time("wasm-opt", || {
+                    OptimizationOptions::from(level)
+                        .mvp_features_only()
+                        .zero_filled_memory(true)
+                        .debug_info(self.options.generate_debug_information)
+                        .run(&infile, &outfile)
+                })
                 .map_err(|err| format!("wasm-opt for binary {} failed: {}", self.name, err))?;
 
             return std::fs::read(&outfile).map_err(|e| e.to_string());
@@ -1108,7 +1108,7 @@ pub(super) fn expression<'a, T: TargetRuntime<'a> + ?Sized>(
         Expression::BytesCast {
             loc,
             ty: Type::Bytes(n),
-            from: Type::DynamicBytes,
+            from: Type::DynamicBytes | Type::Slice(_),
             expr: e,
         } => {
             let array = expression(target, bin, e, vartab, function, ns);
@@ -1437,6 +1437,59 @@ pub(super) fn expression<'a, T: TargetRuntime<'a> + ?Sized>(
 
             bin.vector_len(array).into()
         }
+        Expression::Builtin {
+            tys,
+            kind: Builtin::ArraySlice,
+            args,
+            ..
+        } => {
+            let array = expression(target, bin, &args[0], vartab, function, ns);
+            let start = expression(target, bin, &args[1], vartab, function, ns).into_int_value();
+            let end = expression(target, bin, &args[2], vartab, function, ns).into_int_value();
+
+            let elem_size = bin
+                .llvm_type(&tys[0].array_elem(), ns)
+                .size_of()
+                .unwrap()
+                .const_cast(bin.context.i32_type(), false);
+
+            let offset = bin.builder.build_int_mul(start, elem_size, "offset");
+
+            let data = unsafe {
+                bin.builder.build_gep(
+                    bin.context.i8_type(),
+                    bin.vector_bytes(array),
+                    &[offset],
+                    "slice_data",
+                )
+            };
+
+            let slice_ty = bin.llvm_type(&tys[0], ns).into_struct_type();
+            let slice = bin.build_alloca(function, slice_ty, "slice");
+
+            let data_ptr = bin
+                .builder
+                .build_struct_gep(slice_ty, slice, 0, "data")
+                .unwrap();
+
+            bin.builder.build_store(data_ptr, data);
+
+            let len = bin.builder.build_int_sub(end, start, "len");
+            let len = bin.builder.build_int_z_extend_or_bit_cast(
+                len,
+                slice_ty.get_field_type_at_index(1).unwrap().into_int_type(),
+                "len",
+            );
+
+            let len_ptr = bin
+                .builder
+                .build_struct_gep(slice_ty, slice, 1, "len")
+                .unwrap();
+
+            bin.builder.build_store(len_ptr, len);
+
+            bin.builder.build_load(slice_ty, slice, "slice")
+        }
         Expression::Builtin {
             tys: returns,
             kind: Builtin::ReadFromBuffer,
@@ -1910,6 +1963,8 @@ pub(super) fn expression<'a, T: TargetRuntime<'a> + ?Sized>(
             advanced.into()
         }
 
+        Expression::PackedStorageSlot { slot, .. } => bin.number_literal(256, slot, ns).into(),
+
         Expression::RationalNumberLiteral { .. }
         | Expression::Undefined { .. }
         | Expression::Poison
@@ -2074,6 +2129,14 @@ fn runtime_cast<'a>(
                 "ptr_to_int",
             )
             .into(),
+        (Type::Uint(_), Type::BufferPointer) => bin
+            .builder
+            .build_int_to_ptr(
+                val.into_int_value(),
+                bin.llvm_type(to, ns).into_pointer_type(),
+                "int_to_ptr",
+            )
+            .into(),
         (Type::Uint(_), _) if to.is_reference_type(ns) => bin
             .builder
             .build_int_to_ptr(
@@ -2082,6 +2145,28 @@ fn runtime_cast<'a>(
                 "int_to_ptr",
             )
             .into(),
+        (Type::Slice(elem), Type::DynamicBytes | Type::Array(..)) => {
+            // copy the contents of the slice into a new vector
+            let elem_size = bin
+                .llvm_type(elem, ns)
+                .size_of()
+                .unwrap()
+                .const_cast(bin.context.i32_type(), false);
+
+            bin.builder
+                .build_call(
+                    bin.module.get_function("vector_new").unwrap(),
+                    &[
+                        bin.vector_len(val).into(),
+                        elem_size.into(),
+                        bin.vector_bytes(val).into(),
+                    ],
+                    "",
+                )
+                .try_as_basic_value()
+                .left()
+                .unwrap()
+        }
         (Type::DynamicBytes | Type::String, Type::Slice(_)) => {
             let slice_ty = bin.llvm_type(to, ns);
             let slice = bin.build_alloca(function, slice_ty, "slice");
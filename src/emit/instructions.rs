@@ -102,19 +102,41 @@ pub(super) fn process_instruction<'a, T: TargetRuntime<'a> + ?Sized>(
             let mut slot = expression(target, bin, storage, &w.vars, function, ns).into_int_value();
 
             w.vars.get_mut(res).unwrap().value =
-                target.storage_load(bin, ty, &mut slot, function, ns);
+                if let Expression::PackedStorageSlot { offset, .. } = storage {
+                    target.storage_load_packed(bin, ty, slot, *offset, function, ns)
+                } else {
+                    target.storage_load(bin, ty, &mut slot, function, ns)
+                };
         }
         Instr::ClearStorage { ty, storage } => {
             let mut slot = expression(target, bin, storage, &w.vars, function, ns).into_int_value();
 
-            target.storage_delete(bin, ty, &mut slot, function, ns);
+            if let Expression::PackedStorageSlot { offset, .. } = storage {
+                let zero = bin.llvm_type(ty, ns).into_int_type().const_zero();
+
+                target.storage_store_packed(bin, ty, slot, *offset, zero, function, ns);
+            } else {
+                target.storage_delete(bin, ty, &mut slot, function, ns);
+            }
         }
         Instr::SetStorage { ty, value, storage } => {
             let value = expression(target, bin, value, &w.vars, function, ns);
 
             let mut slot = expression(target, bin, storage, &w.vars, function, ns).into_int_value();
 
-            target.storage_store(bin, ty, true, &mut slot, value, function, ns);
+            if let Expression::PackedStorageSlot { offset, .. } = storage {
+                target.storage_store_packed(
+                    bin,
+                    ty,
+                    slot,
+                    *offset,
+                    value.into_int_value(),
+                    function,
+                    ns,
+                );
+            } else {
+                target.storage_store(bin, ty, true, &mut slot, value, function, ns);
+            }
         }
         Instr::SetStorageBytes {
             storage,
@@ -385,7 +407,9 @@ pub(super) fn process_instruction<'a, T: TargetRuntime<'a> + ?Sized>(
             };
             bin.builder.build_store(size_ptr, new_len);
         }
-        Instr::AssertFailure { encoded_args: None } => {
+        Instr::AssertFailure {
+            encoded_args: None, ..
+        } => {
             target.assert_failure(
                 bin,
                 bin.context
@@ -397,12 +421,17 @@ pub(super) fn process_instruction<'a, T: TargetRuntime<'a> + ?Sized>(
         }
         Instr::AssertFailure {
             encoded_args: Some(expr),
+            error_code,
         } => {
             let data = expression(target, bin, expr, &w.vars, function, ns);
             let vector_bytes = bin.vector_bytes(data);
             let len = bin.vector_len(data);
 
-            target.assert_failure(bin, vector_bytes, len);
+            if let Some(error_code) = error_code {
+                target.custom_error_failure(bin, vector_bytes, len, *error_code);
+            } else {
+                target.assert_failure(bin, vector_bytes, len);
+            }
         }
         Instr::Print { expr } => {
             let expr = expression(target, bin, expr, &w.vars, function, ns);
@@ -915,7 +944,11 @@ pub(super) fn process_instruction<'a, T: TargetRuntime<'a> + ?Sized>(
         }
         Instr::WriteBuffer { buf, offset, value } => {
             let v = expression(target, bin, buf, &w.vars, function, ns);
-            let data = bin.vector_bytes(v);
+            let data = if buf.ty() == Type::BufferPointer {
+                v.into_pointer_value()
+            } else {
+                bin.vector_bytes(v)
+            };
 
             let offset = expression(target, bin, offset, &w.vars, function, ns).into_int_value();
             let emit_value = expression(target, bin, value, &w.vars, function, ns);
@@ -12,6 +12,7 @@ use inkwell::types::{BasicTypeEnum, IntType};
 use inkwell::values::{
     ArrayValue, BasicMetadataValueEnum, BasicValueEnum, FunctionValue, IntValue, PointerValue,
 };
+use solang_parser::diagnostics::Diagnostic;
 use solang_parser::pt::Loc;
 
 pub mod binary;
@@ -96,6 +97,29 @@ pub trait TargetRuntime<'a> {
         ns: &ast::Namespace,
     );
 
+    /// Load a small value which shares its storage slot with other values
+    fn storage_load_packed(
+        &self,
+        bin: &Binary<'a>,
+        ty: &Type,
+        slot: IntValue<'a>,
+        offset: usize,
+        function: FunctionValue<'a>,
+        ns: &Namespace,
+    ) -> BasicValueEnum<'a>;
+
+    /// Store a small value into a storage slot which is shared with other values
+    fn storage_store_packed(
+        &self,
+        bin: &Binary<'a>,
+        ty: &Type,
+        slot: IntValue<'a>,
+        offset: usize,
+        value: IntValue<'a>,
+        function: FunctionValue<'a>,
+        ns: &Namespace,
+    );
+
     /// Recursively clear storage. The default implementation is for slot-based storage
     fn storage_delete(
         &self,
@@ -222,6 +246,18 @@ pub trait TargetRuntime<'a> {
     /// Return failure without any result
     fn assert_failure(&self, bin: &Binary, data: PointerValue, length: IntValue);
 
+    /// Return failure with the error code of a custom error. Only Solana reports
+    /// the code, the other targets abort like `assert_failure`
+    fn custom_error_failure(
+        &self,
+        bin: &Binary,
+        data: PointerValue,
+        length: IntValue,
+        _error_code: u32,
+    ) {
+        self.assert_failure(bin, data, length);
+    }
+
     fn builtin_function(
         &self,
         binary: &Binary<'a>,
@@ -367,21 +403,27 @@ impl ast::Contract {
         context: &'a inkwell::context::Context,
         opt: &'a Options,
     ) -> binary::Binary {
-        binary::Binary::build(context, self, ns, opt)
+        opt.time("emit", || binary::Binary::build(context, self, ns, opt))
     }
 
-    /// Generate the final program code for the contract
-    pub fn emit(&self, ns: &ast::Namespace, opt: &Options) -> Vec<u8> {
+    /// Generate the final program code for the contract. On EVM, this fails for
+    /// constructs which the backend does not support.
+    pub fn emit(&self, ns: &ast::Namespace, opt: &Options) -> Result<Vec<u8>, Diagnostic> {
         if ns.target == Target::EVM {
-            return vec![];
+            if let Some(code) = self.code.get() {
+                return Ok(code.to_vec());
+            }
+            let code = crate::evm::compile(self, ns)?;
+            return Ok(self.code.get_or_init(|| code).to_vec());
         }
 
-        self.code
+        Ok(self
+            .code
             .get_or_init(move || {
                 let context = inkwell::context::Context::create();
                 let binary = self.binary(ns, &context, opt);
                 binary.code(Generate::Linked).expect("llvm build")
             })
-            .to_vec()
+            .to_vec())
     }
 }
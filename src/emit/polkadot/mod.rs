@@ -2,7 +2,7 @@
 
 use std::ffi::CString;
 
-use crate::codegen::{Options, STORAGE_INITIALIZER};
+use crate::codegen::{Options, STORAGE_INITIALIZER, YUL_MEMORY_SIZE};
 use crate::sema::ast::{Contract, Namespace};
 use inkwell::context::Context;
 use inkwell::module::{Linkage, Module};
@@ -83,8 +83,8 @@ impl PolkadotTarget {
             .expect("storage initializer is always present");
         assert!(storage_initializers.next().is_none());
 
-        target.emit_dispatch(Some(storage_initializer), &mut binary, ns);
-        target.emit_dispatch(None, &mut binary, ns);
+        target.emit_dispatch(Some(storage_initializer), &mut binary, contract, ns);
+        target.emit_dispatch(None, &mut binary, contract, ns);
 
         binary.internalize(&[
             "deploy",
@@ -129,6 +129,7 @@ impl PolkadotTarget {
         binary: &Binary<'a>,
         function: FunctionValue<'a>,
         storage_initializer: Option<FunctionValue>,
+        contract: &Contract,
     ) -> (PointerValue<'a>, IntValue<'a>) {
         let entry = binary.context.append_basic_block(function, "entry");
 
@@ -139,6 +140,19 @@ impl PolkadotTarget {
             .builder
             .build_call(binary.module.get_function("__init_heap").unwrap(), &[], "");
 
+        // reserve memory for inline assembly
+        if contract.yul_memory {
+            binary.builder.build_call(
+                binary.module.get_function("__init_yul_memory").unwrap(),
+                &[binary
+                    .context
+                    .i32_type()
+                    .const_int(YUL_MEMORY_SIZE, false)
+                    .into()],
+                "",
+            );
+        }
+
         // Call the storage initializers on deploy
         if let Some(initializer) = storage_initializer {
             binary.builder.build_call(initializer, &[], "");
@@ -274,6 +288,7 @@ impl PolkadotTarget {
         &mut self,
         storage_initializer: Option<FunctionValue>,
         bin: &mut Binary,
+        contract: &Contract,
         ns: &Namespace,
     ) {
         let ty = bin.context.void_type().fn_type(&[], false);
@@ -283,7 +298,8 @@ impl PolkadotTarget {
             "call"
         };
         let func = bin.module.add_function(export_name, ty, None);
-        let (input, input_length) = self.public_function_prelude(bin, func, storage_initializer);
+        let (input, input_length) =
+            self.public_function_prelude(bin, func, storage_initializer, contract);
         let args = vec![
             BasicMetadataValueEnum::PointerValue(input),
             BasicMetadataValueEnum::IntValue(input_length),
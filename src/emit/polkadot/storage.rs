@@ -671,6 +671,74 @@ impl StorageSlot for PolkadotTarget {
         }
     }
 
+    fn storage_load_packed_slot<'a>(
+        &self,
+        bin: &Binary<'a>,
+        ty: &Type,
+        slot_ptr: PointerValue<'a>,
+        offset: usize,
+        function: FunctionValue,
+        ns: &Namespace,
+    ) -> BasicValueEnum<'a> {
+        let slot_ty = bin.context.custom_width_int_type(256);
+        let llvm_ty = bin.llvm_type(ty.deref_any(), ns).into_int_type();
+
+        // the whole slot is read, and the value is found at its offset
+        let word = self.get_storage_int(bin, function, slot_ptr, slot_ty);
+
+        let value = bin.builder.build_right_shift(
+            word,
+            slot_ty.const_int(offset as u64 * 8, false),
+            false,
+            "packed",
+        );
+
+        bin.builder
+            .build_int_truncate(value, llvm_ty, "value")
+            .into()
+    }
+
+    fn storage_store_packed_slot<'a>(
+        &self,
+        bin: &Binary<'a>,
+        slot_ptr: PointerValue<'a>,
+        offset: usize,
+        value: IntValue<'a>,
+        function: FunctionValue<'a>,
+        ns: &Namespace,
+    ) {
+        let slot_ty = bin.context.custom_width_int_type(256);
+        let shift = slot_ty.const_int(offset as u64 * 8, false);
+
+        let word = self.get_storage_int(bin, function, slot_ptr, slot_ty);
+
+        // clear the bits of our value, keeping the other values in the slot
+        let mask = bin.builder.build_left_shift(
+            bin.builder
+                .build_int_z_extend(value.get_type().const_all_ones(), slot_ty, ""),
+            shift,
+            "mask",
+        );
+
+        let word = bin
+            .builder
+            .build_and(word, bin.builder.build_not(mask, ""), "cleared");
+
+        let value = bin.builder.build_left_shift(
+            bin.builder.build_int_z_extend(value, slot_ty, ""),
+            shift,
+            "value",
+        );
+
+        let word = bin.builder.build_or(word, value, "packed");
+
+        let dest = bin.build_alloca(function, slot_ty, "packed");
+
+        bin.builder.build_store(dest, word);
+
+        self.set_storage(bin, slot_ptr, dest, bin.llvm_type(&Type::Uint(256), ns));
+    }
+
     fn storage_delete_slot<'a>(
         &self,
         bin: &Binary<'a>,
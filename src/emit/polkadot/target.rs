@@ -815,7 +815,9 @@ impl<'a> TargetRuntime<'a> for PolkadotTarget {
 
         let created_contract = &ns.contracts[contract_no];
 
-        let code = created_contract.emit(ns, binary.options);
+        let code = created_contract
+            .emit(ns, binary.options)
+            .expect("llvm build");
 
         let (scratch_buf, scratch_len) = scratch_buf!();
 
@@ -1534,6 +1536,39 @@ impl<'a> TargetRuntime<'a> for PolkadotTarget {
         self.storage_delete_slot(bin, ty, slot, slot_ptr, function, ns);
     }
 
+    fn storage_load_packed(
+        &self,
+        bin: &Binary<'a>,
+        ty: &Type,
+        slot: IntValue<'a>,
+        offset: usize,
+        function: FunctionValue<'a>,
+        ns: &Namespace,
+    ) -> BasicValueEnum<'a> {
+        let slot_ptr = bin.builder.build_alloca(slot.get_type(), "slot");
+
+        bin.builder.build_store(slot_ptr, slot);
+
+        self.storage_load_packed_slot(bin, ty, slot_ptr, offset, function, ns)
+    }
+
+    fn storage_store_packed(
+        &self,
+        bin: &Binary<'a>,
+        _ty: &Type,
+        slot: IntValue<'a>,
+        offset: usize,
+        value: IntValue<'a>,
+        function: FunctionValue<'a>,
+        ns: &Namespace,
+    ) {
+        let slot_ptr = bin.builder.build_alloca(slot.get_type(), "slot");
+
+        bin.builder.build_store(slot_ptr, slot);
+
+        self.storage_store_packed_slot(bin, slot_ptr, offset, value, function, ns);
+    }
+
     fn builtin_function(
         &self,
         binary: &Binary<'a>,
@@ -6,7 +6,10 @@ use crate::sema::ast;
 use crate::Target;
 use std::cmp::Ordering;
 
-use crate::codegen::{cfg::ReturnCode, Options};
+use crate::codegen::{
+    cfg::ReturnCode, dispatch::solana::SOLANA_DISPATCH_CFG_NAME, revert::PanicCode, Options,
+    YUL_MEMORY_SIZE,
+};
 use crate::sema::ast::{Namespace, StructType, Type};
 use inkwell::module::{Linkage, Module};
 use inkwell::types::BasicType;
@@ -72,6 +75,10 @@ impl SolanaTarget {
 
         emit_functions(&mut target, &mut binary, contract, ns);
 
+        if contract.yul_memory {
+            target.init_yul_memory(&binary);
+        }
+
         binary.internalize(&[
             "entrypoint",
             "sol_log_",
@@ -90,6 +97,29 @@ impl SolanaTarget {
         binary
     }
 
+    /// Reserve memory for inline assembly when dispatching, after the heap is initialized
+    fn init_yul_memory(&self, binary: &Binary) {
+        let dispatch = binary
+            .module
+            .get_function(SOLANA_DISPATCH_CFG_NAME)
+            .unwrap();
+        let entry = dispatch.get_first_basic_block().unwrap();
+
+        binary
+            .builder
+            .position_before(&entry.get_first_instruction().unwrap());
+
+        binary.builder.build_call(
+            binary.module.get_function("__init_yul_memory").unwrap(),
+            &[binary
+                .context
+                .i32_type()
+                .const_int(YUL_MEMORY_SIZE, false)
+                .into()],
+            "",
+        );
+    }
+
     fn declare_externals(&self, binary: &mut Binary, ns: &ast::Namespace) {
         let void_ty = binary.context.void_type();
         let u8_ptr = binary.context.i8_type().ptr_type(AddressSpace::default());
@@ -1077,11 +1107,14 @@ impl SolanaTarget {
             .as_basic_value_enum()
     }
 
-    /// Construct the LLVM-IR to call 'sol_invoke_signed_c'.
+    /// Construct the LLVM-IR to call 'sol_invoke_signed_c'. If the call is made from a try-catch
+    /// statement, the success variable is set from the result code. Otherwise, a failing
+    /// call returns its result code to our caller.
     fn build_invoke_signed_c<'b>(
         &self,
         binary: &Binary<'b>,
         function: FunctionValue<'b>,
+        success: Option<&mut BasicValueEnum<'b>>,
         payload: PointerValue<'b>,
         payload_len: IntValue<'b>,
         contract_args: ContractArgs<'b>,
@@ -1224,16 +1257,160 @@ impl SolanaTarget {
             )
         };
 
+        let ret = binary
+            .builder
+            .build_call(
+                external_call,
+                &[
+                    instruction.into(),
+                    account_infos.into(),
+                    account_infos_len.into(),
+                    signer_seeds.into(),
+                    signer_seeds_len.into(),
+                ],
+                "",
+            )
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value();
+
+        let is_success = binary.builder.build_int_compare(
+            IntPredicate::EQ,
+            ret,
+            binary.context.i64_type().const_zero(),
+            "success",
+        );
+
+        if let Some(success) = success {
+            *success = is_success.into();
+        } else {
+            let success_block = binary.context.append_basic_block(function, "success");
+            let bail_block = binary.context.append_basic_block(function, "bail");
+            binary
+                .builder
+                .build_conditional_branch(is_success, success_block, bail_block);
+
+            binary.builder.position_at_end(bail_block);
+            binary.builder.build_return(Some(&ret));
+
+            binary.builder.position_at_end(success_block);
+        }
+    }
+
+    /// Set the error data as return data and return the given failure code
+    fn return_failure(&self, binary: &Binary, data: PointerValue, length: IntValue, code: u64) {
         binary.builder.build_call(
-            external_call,
+            binary.module.get_function("sol_set_return_data").unwrap(),
             &[
-                instruction.into(),
-                account_infos.into(),
-                account_infos_len.into(),
-                signer_seeds.into(),
-                signer_seeds_len.into(),
+                data.into(),
+                binary
+                    .builder
+                    .build_int_z_extend(length, binary.context.i64_type(), "length")
+                    .into(),
             ],
             "",
         );
+
+        binary
+            .builder
+            .build_return(Some(&binary.context.i64_type().const_int(code, false)));
+    }
+
+    /// Return the length of the storage `bytes` or `string` at `offset` in the account data.
+    /// If the offset or the length points outside the account data, the encoding is corrupt
+    /// and we abort with `Panic(0x22)`.
+    fn storage_bytes_length<'b>(
+        &self,
+        binary: &Binary<'b>,
+        function: FunctionValue,
+        data: PointerValue<'b>,
+        offset: IntValue<'b>,
+        ns: &ast::Namespace,
+    ) -> IntValue<'b> {
+        let account = self.contract_storage_account(binary);
+        let account_info_ty = binary
+            .module
+            .get_struct_type("struct.SolAccountInfo")
+            .unwrap();
+
+        let data_len = binary
+            .builder
+            .build_load(
+                binary.context.i64_type(),
+                binary
+                    .builder
+                    .build_struct_gep(account_info_ty, account, 2, "data_len")
+                    .unwrap(),
+                "data_len",
+            )
+            .into_int_value();
+
+        let offset64 =
+            binary
+                .builder
+                .build_int_z_extend(offset, binary.context.i64_type(), "offset");
+
+        let offset_valid = binary.context.append_basic_block(function, "offset_valid");
+        let length_valid = binary.context.append_basic_block(function, "length_valid");
+        let bang_block = binary.context.append_basic_block(function, "bang_block");
+
+        let in_range = binary.builder.build_int_compare(
+            IntPredicate::ULE,
+            offset64,
+            data_len,
+            "offset_in_range",
+        );
+
+        binary
+            .builder
+            .build_conditional_branch(in_range, offset_valid, bang_block);
+
+        binary.builder.position_at_end(offset_valid);
+
+        let length = binary
+            .builder
+            .build_call(
+                binary.module.get_function("account_data_len").unwrap(),
+                &[data.into(), offset.into()],
+                "length",
+            )
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value();
+
+        let end = binary.builder.build_int_add(
+            offset64,
+            binary
+                .builder
+                .build_int_z_extend(length, binary.context.i64_type(), "length"),
+            "end",
+        );
+
+        let in_range =
+            binary
+                .builder
+                .build_int_compare(IntPredicate::ULE, end, data_len, "end_in_range");
+
+        binary
+            .builder
+            .build_conditional_branch(in_range, length_valid, bang_block);
+
+        binary.builder.position_at_end(bang_block);
+
+        binary.log_runtime_error(
+            self,
+            "storage bytes encoding incorrect".to_string(),
+            None,
+            ns,
+        );
+        let (revert_out, revert_out_len) =
+            binary.panic_data_const(ns, PanicCode::StorageBytesEncodingIncorrect);
+        self.assert_failure(binary, revert_out, revert_out_len);
+
+        binary.builder.position_at_end(length_valid);
+
+        length
     }
 }
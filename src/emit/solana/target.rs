@@ -33,6 +33,31 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
         self.storage_free(binary, ty, data, *slot, function, true, ns);
     }
 
+    fn storage_load_packed(
+        &self,
+        _bin: &Binary<'a>,
+        _ty: &ast::Type,
+        _slot: IntValue<'a>,
+        _offset: usize,
+        _function: FunctionValue<'a>,
+        _ns: &ast::Namespace,
+    ) -> BasicValueEnum<'a> {
+        unreachable!("storage is only packed on Polkadot");
+    }
+
+    fn storage_store_packed(
+        &self,
+        _bin: &Binary<'a>,
+        _ty: &ast::Type,
+        _slot: IntValue<'a>,
+        _offset: usize,
+        _value: IntValue<'a>,
+        _function: FunctionValue<'a>,
+        _ns: &ast::Namespace,
+    ) {
+        unreachable!("storage is only packed on Polkadot");
+    }
+
     fn set_storage_extfunc(
         &self,
         _binary: &Binary,
@@ -96,17 +121,7 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
             .build_load(binary.context.i32_type(), member, "offset")
             .into_int_value();
 
-        let length = binary
-            .builder
-            .build_call(
-                binary.module.get_function("account_data_len").unwrap(),
-                &[data.into(), offset.into()],
-                "length",
-            )
-            .try_as_basic_value()
-            .left()
-            .unwrap()
-            .into_int_value();
+        let length = self.storage_bytes_length(binary, function, data, offset, ns);
 
         // do bounds check on index
         let in_range =
@@ -157,11 +172,11 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
 
     fn set_storage_bytes_subscript(
         &self,
-        binary: &Binary,
+        binary: &Binary<'a>,
         function: FunctionValue,
-        slot: IntValue,
-        index: IntValue,
-        val: IntValue,
+        slot: IntValue<'a>,
+        index: IntValue<'a>,
+        val: IntValue<'a>,
         ns: &Namespace,
         loc: Loc,
     ) {
@@ -178,17 +193,7 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
             .build_load(binary.context.i32_type(), member, "offset")
             .into_int_value();
 
-        let length = binary
-            .builder
-            .build_call(
-                binary.module.get_function("account_data_len").unwrap(),
-                &[data.into(), offset.into()],
-                "length",
-            )
-            .try_as_basic_value()
-            .left()
-            .unwrap()
-            .into_int_value();
+        let length = self.storage_bytes_length(binary, function, data, offset, ns);
 
         // do bounds check on index
         let in_range =
@@ -598,17 +603,7 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
                     .build_load(binary.context.i32_type(), member, "offset")
                     .into_int_value();
 
-                let string_length = binary
-                    .builder
-                    .build_call(
-                        binary.module.get_function("account_data_len").unwrap(),
-                        &[data.into(), offset.into()],
-                        "free",
-                    )
-                    .try_as_basic_value()
-                    .left()
-                    .unwrap()
-                    .into_int_value();
+                let string_length = self.storage_bytes_length(binary, function, data, offset, ns);
 
                 let string_data = unsafe {
                     binary.builder.build_gep(
@@ -1189,23 +1184,19 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
     }
 
     fn assert_failure(&self, binary: &Binary, data: PointerValue, length: IntValue) {
-        // the reason code should be null (and already printed)
-        binary.builder.build_call(
-            binary.module.get_function("sol_set_return_data").unwrap(),
-            &[
-                data.into(),
-                binary
-                    .builder
-                    .build_int_z_extend(length, binary.context.i64_type(), "length")
-                    .into(),
-            ],
-            "",
-        );
-
         // return 1 for failure
-        binary.builder.build_return(Some(
-            &binary.context.i64_type().const_int(1u64 << 32, false),
-        ));
+        self.return_failure(binary, data, length, 1u64 << 32);
+    }
+
+    fn custom_error_failure(
+        &self,
+        binary: &Binary,
+        data: PointerValue,
+        length: IntValue,
+        error_code: u32,
+    ) {
+        // ProgramError::Custom(n) is returned as n itself
+        self.return_failure(binary, data, length, error_code as u64);
     }
 
     fn print(&self, binary: &Binary, string_ptr: PointerValue, string_len: IntValue) {
@@ -1226,7 +1217,7 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
         &mut self,
         binary: &Binary<'b>,
         function: FunctionValue<'b>,
-        _success: Option<&mut BasicValueEnum<'b>>,
+        success: Option<&mut BasicValueEnum<'b>>,
         _contract_no: usize,
         address: PointerValue<'b>,
         encoded_args: BasicValueEnum<'b>,
@@ -1242,7 +1233,15 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
 
         assert!(contract_args.accounts.is_some());
         // The AccountMeta array is always present for Solana contracts
-        self.build_invoke_signed_c(binary, function, payload, payload_len, contract_args, ns);
+        self.build_invoke_signed_c(
+            binary,
+            function,
+            success,
+            payload,
+            payload_len,
+            contract_args,
+            ns,
+        );
     }
 
     fn builtin_function(
@@ -1333,7 +1332,7 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
         &self,
         binary: &Binary<'b>,
         function: FunctionValue<'b>,
-        _success: Option<&mut BasicValueEnum<'b>>,
+        success: Option<&mut BasicValueEnum<'b>>,
         payload: PointerValue<'b>,
         payload_len: IntValue<'b>,
         address: Option<PointerValue<'b>>,
@@ -1356,7 +1355,15 @@ impl<'a> TargetRuntime<'a> for SolanaTarget {
         };
 
         contract_args.program_id = Some(address);
-        self.build_invoke_signed_c(binary, function, payload, payload_len, contract_args, ns);
+        self.build_invoke_signed_c(
+            binary,
+            function,
+            success,
+            payload,
+            payload_len,
+            contract_args,
+            ns,
+        );
     }
 
     /// Get return buffer for external call
@@ -48,6 +48,29 @@ pub(super) trait StorageSlot {
         ns: &Namespace,
     );
 
+    /// Load a small value at the byte offset within a storage slot which is shared with other values
+    fn storage_load_packed_slot<'a>(
+        &self,
+        bin: &Binary<'a>,
+        ty: &Type,
+        slot_ptr: PointerValue<'a>,
+        offset: usize,
+        function: FunctionValue,
+        ns: &Namespace,
+    ) -> BasicValueEnum<'a>;
+
+    /// Store a small value at the byte offset within a storage slot which is shared with other
+    /// values, leaving the other values in the slot untouched
+    fn storage_store_packed_slot<'a>(
+        &self,
+        bin: &Binary<'a>,
+        slot_ptr: PointerValue<'a>,
+        offset: usize,
+        value: IntValue<'a>,
+        function: FunctionValue<'a>,
+        ns: &Namespace,
+    );
+
     /// Recursively clear bin storage for slot-based storage
     fn storage_delete_slot<'a>(
         &self,
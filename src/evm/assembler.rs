@@ -0,0 +1,294 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use num_bigint::{BigInt, Sign};
+use num_traits::Zero;
+
+/// The subset of EVM opcodes the backend emits
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub(super) enum Op {
+    Stop = 0x00,
+    Add = 0x01,
+    Mul = 0x02,
+    Sub = 0x03,
+    Div = 0x04,
+    SDiv = 0x05,
+    Mod = 0x06,
+    SMod = 0x07,
+    AddMod = 0x08,
+    MulMod = 0x09,
+    Exp = 0x0a,
+    SignExtend = 0x0b,
+    Lt = 0x10,
+    Gt = 0x11,
+    SLt = 0x12,
+    SGt = 0x13,
+    Eq = 0x14,
+    IsZero = 0x15,
+    And = 0x16,
+    Or = 0x17,
+    Xor = 0x18,
+    Not = 0x19,
+    Byte = 0x1a,
+    Shl = 0x1b,
+    Shr = 0x1c,
+    Sar = 0x1d,
+    Keccak256 = 0x20,
+    Address = 0x30,
+    Balance = 0x31,
+    Origin = 0x32,
+    Caller = 0x33,
+    CallValue = 0x34,
+    CallDataSize = 0x36,
+    CallDataCopy = 0x37,
+    CodeSize = 0x38,
+    CodeCopy = 0x39,
+    GasPrice = 0x3a,
+    ExtCodeSize = 0x3b,
+    ExtCodeCopy = 0x3c,
+    ReturnDataSize = 0x3d,
+    ReturnDataCopy = 0x3e,
+    BlockHash = 0x40,
+    Coinbase = 0x41,
+    Timestamp = 0x42,
+    Number = 0x43,
+    PrevRandao = 0x44,
+    GasLimit = 0x45,
+    ChainId = 0x46,
+    BaseFee = 0x48,
+    Pop = 0x50,
+    MLoad = 0x51,
+    MStore = 0x52,
+    MStore8 = 0x53,
+    SLoad = 0x54,
+    SStore = 0x55,
+    Jump = 0x56,
+    JumpI = 0x57,
+    Gas = 0x5a,
+    JumpDest = 0x5b,
+    Dup1 = 0x80,
+    Swap1 = 0x90,
+    Log0 = 0xa0,
+    Create = 0xf0,
+    Call = 0xf1,
+    Return = 0xf3,
+    DelegateCall = 0xf4,
+    Create2 = 0xf5,
+    StaticCall = 0xfa,
+    Revert = 0xfd,
+    Invalid = 0xfe,
+    SelfDestruct = 0xff,
+}
+
+const PUSH1: u8 = 0x60;
+const PUSH2: u8 = 0x61;
+
+/// A label is a 16 bit value which is only known once assembly is complete; either the
+/// position of a jump destination, the offset of a data item, or a constant such as the
+/// size of a stack frame.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub(super) struct Label(usize);
+
+enum Definition {
+    Undefined,
+    Code(usize),
+    Data(usize),
+    Value(usize),
+}
+
+/// Assembles EVM bytecode. Any reference to a label is emitted as PUSH2, which is patched
+/// once all labels are defined.
+pub(super) struct Assembler {
+    code: Vec<u8>,
+    data: Vec<u8>,
+    labels: Vec<Definition>,
+    fixups: Vec<(usize, Label)>,
+}
+
+impl Assembler {
+    pub fn new() -> Self {
+        Assembler {
+            code: Vec::new(),
+            data: Vec::new(),
+            labels: Vec::new(),
+            fixups: Vec::new(),
+        }
+    }
+
+    pub fn op(&mut self, op: Op) {
+        self.code.push(op as u8);
+    }
+
+    pub fn ops(&mut self, ops: &[Op]) {
+        for op in ops {
+            self.op(*op);
+        }
+    }
+
+    /// Push a value with the shortest PUSHn instruction
+    pub fn push_u64(&mut self, value: u64) {
+        let bytes = value.to_be_bytes();
+        let first = bytes.iter().position(|b| *b != 0).unwrap_or(7);
+
+        self.push_bytes(&bytes[first..]);
+    }
+
+    /// Push an arbitrary integer; negative values are pushed in two's complement
+    pub fn push_bigint(&mut self, value: &BigInt) {
+        let mut bytes = if value.sign() == Sign::Minus {
+            let mut bs = value.to_signed_bytes_be();
+            while bs.len() < 32 {
+                bs.insert(0, 0xff);
+            }
+            bs
+        } else if value.is_zero() {
+            vec![0]
+        } else {
+            value.to_bytes_be().1
+        };
+
+        if bytes.len() > 32 {
+            bytes.drain(0..bytes.len() - 32);
+        }
+
+        self.push_word(&bytes);
+    }
+
+    /// Push a big-endian value of at most 32 bytes, using a shorter sequence for values which
+    /// are mostly ones or end in many zero bytes.
+    pub fn push_word(&mut self, bytes: &[u8]) {
+        let first = bytes
+            .iter()
+            .position(|b| *b != 0)
+            .unwrap_or(bytes.len() - 1);
+        let bytes = &bytes[first..];
+
+        if bytes.len() > 4 {
+            let trailing_zeros = bytes.iter().rev().take_while(|b| **b == 0).count();
+
+            if trailing_zeros > 2 {
+                self.push_word(&bytes[..bytes.len() - trailing_zeros]);
+                self.push_u64(trailing_zeros as u64 * 8);
+                self.op(Op::Shl);
+                return;
+            }
+
+            if bytes.len() == 32 && bytes.iter().all(|b| *b == 0xff) {
+                self.push_u64(0);
+                self.op(Op::Not);
+                return;
+            }
+
+            if bytes.len() == 32 {
+                let inverted: Vec<u8> = bytes.iter().map(|b| !b).collect();
+                let leading = inverted.iter().take_while(|b| **b == 0).count();
+
+                if leading > 3 {
+                    self.push_word(&inverted);
+                    self.op(Op::Not);
+                    return;
+                }
+            }
+        }
+
+        self.push_bytes(bytes);
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        debug_assert!(!bytes.is_empty() && bytes.len() <= 32);
+
+        self.code.push(PUSH1 + bytes.len() as u8 - 1);
+        self.code.extend_from_slice(bytes);
+    }
+
+    pub fn dup(&mut self, n: u8) {
+        debug_assert!((1..=16).contains(&n));
+        self.code.push(Op::Dup1 as u8 + n - 1);
+    }
+
+    pub fn swap(&mut self, n: u8) {
+        debug_assert!((1..=16).contains(&n));
+        self.code.push(Op::Swap1 as u8 + n - 1);
+    }
+
+    pub fn log(&mut self, topics: usize) {
+        debug_assert!(topics <= 4);
+        self.code.push(Op::Log0 as u8 + topics as u8);
+    }
+
+    pub fn new_label(&mut self) -> Label {
+        self.labels.push(Definition::Undefined);
+        Label(self.labels.len() - 1)
+    }
+
+    /// Place a jump destination here
+    pub fn set_label(&mut self, label: Label) {
+        debug_assert!(matches!(self.labels[label.0], Definition::Undefined));
+
+        self.labels[label.0] = Definition::Code(self.code.len());
+        self.op(Op::JumpDest);
+    }
+
+    /// Define the value of a label which is not a code position
+    pub fn define(&mut self, label: Label, value: usize) {
+        self.labels[label.0] = Definition::Value(value);
+    }
+
+    /// Add a data item which is appended after the code. The returned label is its offset
+    /// in the code.
+    pub fn data(&mut self, bytes: &[u8]) -> Label {
+        let label = self.new_label();
+        self.labels[label.0] = Definition::Data(self.data.len());
+        self.data.extend_from_slice(bytes);
+        label
+    }
+
+    /// Label which resolves to the end of the code and data, i.e. the code size
+    pub fn end_label(&mut self) -> Label {
+        let label = self.new_label();
+        self.labels[label.0] = Definition::Data(usize::MAX);
+        label
+    }
+
+    pub fn push_label(&mut self, label: Label) {
+        self.fixups.push((self.code.len() + 1, label));
+        self.code.extend_from_slice(&[PUSH2, 0, 0]);
+    }
+
+    pub fn jump(&mut self, label: Label) {
+        self.push_label(label);
+        self.op(Op::Jump);
+    }
+
+    pub fn jumpi(&mut self, label: Label) {
+        self.push_label(label);
+        self.op(Op::JumpI);
+    }
+
+    /// Resolve all labels and return the bytecode including the data section
+    pub fn finish(mut self) -> Result<Vec<u8>, String> {
+        let code_len = self.code.len();
+        let total_len = code_len + self.data.len();
+
+        for (pos, label) in &self.fixups {
+            let value = match self.labels[label.0] {
+                Definition::Code(offset) | Definition::Value(offset) => offset,
+                Definition::Data(usize::MAX) => total_len,
+                Definition::Data(offset) => code_len + offset,
+                Definition::Undefined => unreachable!("label {} not defined", label.0),
+            };
+
+            if value > u16::MAX as usize {
+                return Err(format!(
+                    "contract code is {total_len} bytes, which exceeds the 65535 byte limit"
+                ));
+            }
+
+            self.code[*pos..*pos + 2].copy_from_slice(&(value as u16).to_be_bytes());
+        }
+
+        self.code.extend_from_slice(&self.data);
+
+        Ok(self.code)
+    }
+}
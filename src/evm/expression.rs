@@ -0,0 +1,1252 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::assembler::Op;
+use super::{
+    array_elem_size, elem_size, field_offset, inline_size, object_size, repr, Emitter, Repr,
+    ADDRESS, CALLDATA, SELECTOR,
+};
+use crate::codegen::revert::PanicCode;
+use crate::codegen::{Builtin, Expression};
+use crate::sema::ast::{RetrieveType, StringLocation, Type};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::One;
+use solang_parser::diagnostics::Diagnostic;
+use solang_parser::pt::{CodeLocation, Loc};
+
+/// Mask with the lower `bits` bits set
+fn low_bits(bits: u16) -> BigInt {
+    (BigInt::one() << bits) - 1
+}
+
+/// Mask with the upper `bytes` bytes of a word set
+fn high_bytes(bytes: u8) -> BigInt {
+    low_bits(bytes as u16 * 8) << (256 - bytes as u16 * 8)
+}
+
+/// How a value is written to an ABI encoding buffer. Fixed length byte arrays and selectors
+/// are encoded as bytes, whereas integers are little endian.
+fn buffer_repr(ty: &Type, ns: &crate::sema::ast::Namespace) -> Repr {
+    match ty {
+        Type::Bytes(n) if *n > 1 => Repr::Be(*n),
+        Type::FunctionSelector => Repr::Be(ty.bytes(ns)),
+        Type::UserType(no) => buffer_repr(&ns.user_types[*no].ty, ns),
+        _ => repr(ty, ns),
+    }
+}
+
+fn not_supported(loc: Loc, what: &str) -> Diagnostic {
+    Diagnostic::error(loc, format!("{what} is not supported on EVM"))
+}
+
+impl<'a> Emitter<'a> {
+    /// Evaluate an expression, leaving its value on the stack. Value types are kept in
+    /// canonical form: signed integers are sign extended to 256 bits, and all other values
+    /// have their unused upper bits cleared. Reference types are memory pointers, and
+    /// storage references are slot numbers.
+    pub(super) fn expression(&mut self, expr: &Expression) -> Result<(), Diagnostic> {
+        match expr {
+            Expression::FunctionArg { arg_no, .. } => {
+                self.frame_address(arg_no * 32);
+                self.asm.op(Op::MLoad);
+            }
+            Expression::Variable { var_no, .. } => self.load_var(*var_no),
+            Expression::BoolLiteral { value, .. } => self.asm.push_u64(*value as u64),
+            Expression::NumberLiteral { loc, ty, value } => self.number_literal(loc, ty, value)?,
+            Expression::BytesLiteral { ty, value, .. } => {
+                if matches!(repr(ty, self.ns), Repr::Word) {
+                    self.bytes_object(value);
+                } else {
+                    let mut word = value.clone();
+                    word.truncate(32);
+                    if word.is_empty() {
+                        word.push(0);
+                    }
+                    self.asm.push_word(&word);
+                }
+            }
+            Expression::AllocDynamicBytes {
+                ty,
+                size,
+                initializer,
+                ..
+            } => {
+                let elem_size = array_elem_size(ty, self.ns) as u64;
+
+                self.expression(size)?;
+                self.asm.dup(1);
+                if elem_size != 1 {
+                    self.asm.push_u64(elem_size);
+                    self.asm.op(Op::Mul);
+                }
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.alloc();
+                // stack: [len, ptr]
+                self.asm.swap(1);
+                self.asm.dup(2);
+                self.asm.op(Op::MStore);
+
+                if let Some(initializer) = initializer {
+                    if !initializer.is_empty() {
+                        let data = self.asm.data(initializer);
+                        self.asm.push_u64(initializer.len() as u64);
+                        self.asm.push_label(data);
+                        self.asm.dup(3);
+                        self.asm.push_u64(32);
+                        self.asm.op(Op::Add);
+                        self.asm.op(Op::CodeCopy);
+                    }
+                }
+            }
+            Expression::ArrayLiteral {
+                ty,
+                dimensions,
+                values,
+                ..
+            }
+            | Expression::ConstArrayLiteral {
+                ty,
+                dimensions,
+                values,
+                ..
+            } => {
+                let mut leaf = ty.clone();
+                for _ in 0..dimensions.len() {
+                    leaf = leaf.array_elem();
+                }
+                let leaf_size = elem_size(&leaf, self.ns);
+                let leaf_repr = repr(&leaf, self.ns);
+                let dynamic = ty.is_dynamic_memory();
+                let header = if dynamic { 32 } else { 0 };
+
+                // a fixed array literal without values is an all zero array
+                let size = if dynamic {
+                    header + values.len() * leaf_size
+                } else {
+                    inline_size(ty, self.ns)
+                };
+
+                self.asm.push_u64(size as u64);
+                self.alloc();
+
+                if dynamic {
+                    self.asm.push_u64(values.len() as u64);
+                    self.asm.dup(2);
+                    self.asm.op(Op::MStore);
+                }
+
+                for (i, value) in values.iter().enumerate() {
+                    self.expression(value)?;
+                    self.asm.dup(2);
+                    let offset = header + i * leaf_size;
+                    if offset > 0 {
+                        self.asm.push_u64(offset as u64);
+                        self.asm.op(Op::Add);
+                    }
+                    self.store(leaf_repr);
+                }
+            }
+            Expression::StructLiteral { ty, values, .. } => {
+                self.asm.push_u64(inline_size(ty, self.ns) as u64);
+                self.alloc();
+
+                if let Type::Struct(str_ty) = ty {
+                    let fields = &str_ty.definition(self.ns).fields;
+
+                    for (member, value) in values.iter().enumerate() {
+                        self.expression(value)?;
+                        self.asm.dup(2);
+                        let offset = field_offset(ty, member, self.ns);
+                        if offset > 0 {
+                            self.asm.push_u64(offset as u64);
+                            self.asm.op(Op::Add);
+                        }
+                        let field_repr = if fields[member].infinite_size {
+                            Repr::Word
+                        } else {
+                            repr(&fields[member].ty, self.ns)
+                        };
+                        self.store(field_repr);
+                    }
+                }
+            }
+            Expression::StructMember {
+                expr: inner,
+                member,
+                ..
+            } => {
+                self.expression(inner)?;
+                let offset = field_offset(&inner.ty(), *member, self.ns);
+                if offset > 0 {
+                    self.asm.push_u64(offset as u64);
+                    self.asm.op(Op::Add);
+                }
+            }
+            Expression::Subscript {
+                loc,
+                array_ty,
+                expr: array,
+                index,
+                ..
+            } => {
+                if array_ty.is_storage_bytes() {
+                    self.expression(index)?;
+                    self.expression(array)?;
+                    self.storage_bytes_get();
+                } else if array_ty.is_contract_storage() {
+                    return Err(Diagnostic::error(
+                        *loc,
+                        "unexpected storage subscript on EVM".into(),
+                    ));
+                } else {
+                    let elem_size = array_elem_size(array_ty, self.ns) as u64;
+
+                    self.expression(array)?;
+                    self.data_pointer(array_ty);
+                    self.expression(index)?;
+                    if elem_size != 1 {
+                        self.asm.push_u64(elem_size);
+                        self.asm.op(Op::Mul);
+                    }
+                    self.asm.op(Op::Add);
+                }
+            }
+            Expression::Load { ty, expr: ptr, .. } => {
+                self.expression(ptr)?;
+
+                if ty.is_reference_type(self.ns) && !ty.is_fixed_reference_type(self.ns) {
+                    // a null pointer means the object has not been allocated yet
+                    let done = self.asm.new_label();
+                    self.asm.dup(1);
+                    self.asm.op(Op::MLoad);
+                    self.asm.dup(1);
+                    self.asm.jumpi(done);
+                    self.asm.op(Op::Pop);
+                    self.asm.push_u64(object_size(ty, self.ns) as u64);
+                    self.alloc();
+                    self.asm.dup(1);
+                    self.asm.dup(3);
+                    self.asm.op(Op::MStore);
+                    self.asm.set_label(done);
+                    self.asm.swap(1);
+                    self.asm.op(Op::Pop);
+                } else if !ty.is_fixed_reference_type(self.ns) {
+                    self.load(repr(ty, self.ns));
+                }
+            }
+            Expression::GetRef { expr: value, .. } => {
+                self.expression(value)?;
+                self.asm.push_u64(32);
+                self.alloc();
+                self.asm.swap(1);
+                self.asm.dup(2);
+                self.store(repr(&value.ty(), self.ns));
+            }
+            Expression::Cast {
+                ty, expr: value, ..
+            }
+            | Expression::Trunc {
+                ty, expr: value, ..
+            } => {
+                self.expression(value)?;
+                self.normalize(ty);
+            }
+            Expression::ZeroExt {
+                ty, expr: value, ..
+            } => {
+                self.expression(value)?;
+                let from = value.ty();
+                if from.is_signed_int(self.ns) {
+                    self.asm.push_bigint(&low_bits(from.bits(self.ns)));
+                    self.asm.op(Op::And);
+                }
+                self.normalize(ty);
+            }
+            Expression::SignExt {
+                ty, expr: value, ..
+            } => {
+                self.expression(value)?;
+                let bytes = value.ty().bytes(self.ns);
+                if bytes < 32 {
+                    self.asm.push_u64(bytes as u64 - 1);
+                    self.asm.op(Op::SignExtend);
+                }
+                self.normalize(ty);
+            }
+            Expression::BytesCast {
+                ty: Type::DynamicBytes,
+                from: Type::Bytes(n),
+                expr: value,
+                ..
+            } => {
+                self.expression(value)?;
+                self.asm.push_u64(32 + *n as u64);
+                self.alloc();
+                self.asm.push_u64(*n as u64);
+                self.asm.dup(2);
+                self.asm.op(Op::MStore);
+                // stack: [value, ptr]
+                self.asm.swap(1);
+                self.asm.push_u64(256 - *n as u64 * 8);
+                self.asm.op(Op::Shl);
+                self.asm.dup(2);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.asm.op(Op::MStore);
+            }
+            Expression::BytesCast {
+                ty: Type::Bytes(n),
+                from: Type::DynamicBytes,
+                expr: value,
+                ..
+            } => {
+                self.expression(value)?;
+                self.asm.dup(1);
+                self.asm.op(Op::MLoad);
+                self.asm.push_u64(*n as u64);
+                self.asm.op(Op::Eq);
+                self.asm.op(Op::IsZero);
+                self.jump_to_panic(PanicCode::Generic);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.load(Repr::Be(*n));
+            }
+            Expression::BytesCast { loc, .. } => {
+                return Err(Diagnostic::error(*loc, "unexpected bytes cast".into()));
+            }
+            Expression::Add {
+                ty,
+                overflowing,
+                left,
+                right,
+                ..
+            } => {
+                self.operands(left, right)?;
+                self.add(ty, *overflowing);
+            }
+            Expression::Subtract {
+                ty,
+                overflowing,
+                left,
+                right,
+                ..
+            } => {
+                self.operands(left, right)?;
+                self.subtract(ty, *overflowing);
+            }
+            Expression::Multiply {
+                ty,
+                overflowing,
+                left,
+                right,
+                ..
+            } => {
+                self.operands(left, right)?;
+                self.multiply(ty, *overflowing);
+            }
+            Expression::UnsignedDivide {
+                ty, left, right, ..
+            }
+            | Expression::SignedDivide {
+                ty, left, right, ..
+            }
+            | Expression::UnsignedModulo {
+                ty, left, right, ..
+            }
+            | Expression::SignedModulo {
+                ty, left, right, ..
+            } => {
+                self.operands(left, right)?;
+                self.asm.dup(2);
+                self.asm.op(Op::IsZero);
+                self.jump_to_panic(PanicCode::DivisionByZero);
+                self.asm.op(match expr {
+                    Expression::UnsignedDivide { .. } => Op::Div,
+                    Expression::SignedDivide { .. } => Op::SDiv,
+                    Expression::UnsignedModulo { .. } => Op::Mod,
+                    _ => Op::SMod,
+                });
+                self.normalize(ty);
+            }
+            Expression::Power {
+                ty,
+                overflowing,
+                base,
+                exp,
+                ..
+            } => {
+                if *overflowing {
+                    self.operands(base, exp)?;
+                    self.asm.op(Op::Exp);
+                    self.normalize(ty);
+                } else {
+                    self.checked_power(ty, base, exp)?;
+                }
+            }
+            Expression::Negate {
+                ty,
+                overflowing,
+                expr: value,
+                ..
+            } => {
+                self.expression(value)?;
+                if !*overflowing && ty.is_signed_int(self.ns) {
+                    let bits = ty.bits(self.ns);
+                    self.asm.dup(1);
+                    self.asm.push_bigint(&-(BigInt::one() << (bits - 1)));
+                    self.asm.op(Op::Eq);
+                    self.jump_to_panic(PanicCode::MathOverflow);
+                }
+                self.asm.push_u64(0);
+                self.asm.op(Op::Sub);
+                self.normalize(ty);
+            }
+            Expression::BitwiseAnd { left, right, .. } => {
+                self.operands(left, right)?;
+                self.asm.op(Op::And);
+            }
+            Expression::BitwiseOr { left, right, .. } => {
+                self.operands(left, right)?;
+                self.asm.op(Op::Or);
+            }
+            Expression::BitwiseXor { left, right, .. } => {
+                self.operands(left, right)?;
+                self.asm.op(Op::Xor);
+            }
+            Expression::BitwiseNot {
+                ty, expr: value, ..
+            } => {
+                self.expression(value)?;
+                self.asm.op(Op::Not);
+                self.normalize(ty);
+            }
+            Expression::ShiftLeft {
+                ty, left, right, ..
+            } => {
+                self.expression(left)?;
+                self.expression(right)?;
+                self.asm.op(Op::Shl);
+                self.normalize(ty);
+            }
+            Expression::ShiftRight {
+                ty,
+                left,
+                right,
+                signed,
+                ..
+            } => {
+                self.expression(left)?;
+                self.expression(right)?;
+                self.asm.op(if *signed { Op::Sar } else { Op::Shr });
+                self.normalize(ty);
+            }
+            Expression::Not { expr: value, .. } => {
+                self.expression(value)?;
+                self.asm.op(Op::IsZero);
+            }
+            Expression::Equal { left, right, .. } => {
+                self.operands(left, right)?;
+                self.asm.op(Op::Eq);
+            }
+            Expression::NotEqual { left, right, .. } => {
+                self.operands(left, right)?;
+                self.asm.ops(&[Op::Eq, Op::IsZero]);
+            }
+            Expression::Less {
+                signed,
+                left,
+                right,
+                ..
+            } => {
+                self.operands(left, right)?;
+                self.asm.op(if *signed { Op::SLt } else { Op::Lt });
+            }
+            Expression::More {
+                signed,
+                left,
+                right,
+                ..
+            } => {
+                self.operands(left, right)?;
+                self.asm.op(if *signed { Op::SGt } else { Op::Gt });
+            }
+            Expression::LessEqual {
+                signed,
+                left,
+                right,
+                ..
+            } => {
+                self.operands(left, right)?;
+                self.asm.op(if *signed { Op::SGt } else { Op::Gt });
+                self.asm.op(Op::IsZero);
+            }
+            Expression::MoreEqual {
+                signed,
+                left,
+                right,
+                ..
+            } => {
+                self.operands(left, right)?;
+                self.asm.op(if *signed { Op::SLt } else { Op::Lt });
+                self.asm.op(Op::IsZero);
+            }
+            Expression::Keccak256 { exprs, .. } => self.keccak256(exprs)?,
+            Expression::StringCompare { left, right, .. } => {
+                self.string_hash(left)?;
+                self.string_hash(right)?;
+                self.asm.op(Op::Eq);
+            }
+            Expression::ReturnData { .. } => {
+                self.asm.op(Op::ReturnDataSize);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.alloc();
+                self.asm.op(Op::ReturnDataSize);
+                self.asm.dup(2);
+                self.asm.op(Op::MStore);
+                self.asm.op(Op::ReturnDataSize);
+                self.asm.push_u64(0);
+                self.asm.dup(3);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.asm.op(Op::ReturnDataCopy);
+            }
+            Expression::StorageArrayLength { array, .. } => {
+                self.expression(array)?;
+                self.asm.op(Op::SLoad);
+            }
+            Expression::InternalFunctionCfg { cfg_no, .. } => {
+                self.asm.push_label(self.functions[cfg_no]);
+            }
+            Expression::AdvancePointer {
+                pointer,
+                bytes_offset,
+            } => {
+                self.expression(pointer)?;
+                self.data_pointer(&pointer.ty());
+                self.expression(bytes_offset)?;
+                self.asm.op(Op::Add);
+            }
+            Expression::Undefined { .. } => self.asm.push_u64(0),
+            Expression::Builtin {
+                loc,
+                tys,
+                kind,
+                args,
+            } => self.builtin(loc, tys, kind, args)?,
+            Expression::FormatString { loc, .. } => {
+                return Err(not_supported(*loc, "string formatting"));
+            }
+            Expression::RationalNumberLiteral { loc, .. } => {
+                return Err(not_supported(*loc, "rational number"));
+            }
+            Expression::PackedStorageSlot { .. } => {
+                unreachable!("storage is only packed on Polkadot")
+            }
+            Expression::Poison => unreachable!("poison in cfg"),
+        }
+
+        Ok(())
+    }
+
+    /// Evaluate the operands of a binary operator: [] -> [right, left]
+    fn operands(&mut self, left: &Expression, right: &Expression) -> Result<(), Diagnostic> {
+        self.expression(right)?;
+        self.expression(left)
+    }
+
+    fn number_literal(&mut self, loc: &Loc, ty: &Type, value: &BigInt) -> Result<(), Diagnostic> {
+        if ty.is_signed_int(self.ns) {
+            if ty.bits(self.ns) > 256 {
+                return Err(Diagnostic::error(*loc, "integer too large for EVM".into()));
+            }
+            self.asm.push_bigint(value);
+        } else {
+            let bits = match ty.deref_any() {
+                Type::Bool => 1,
+                Type::Uint(_)
+                | Type::Address(_)
+                | Type::Contract(_)
+                | Type::Bytes(_)
+                | Type::Enum(_)
+                | Type::Value
+                | Type::FunctionSelector
+                | Type::UserType(_) => ty.deref_any().bits(self.ns),
+                _ => 256,
+            };
+
+            if bits > 256 {
+                return Err(Diagnostic::error(*loc, "integer too large for EVM".into()));
+            }
+
+            let modulus = BigInt::one() << bits;
+            self.asm.push_bigint(&value.mod_floor(&modulus));
+        }
+
+        Ok(())
+    }
+
+    /// Create a memory bytes object from a constant
+    fn bytes_object(&mut self, value: &[u8]) {
+        self.asm.push_u64(32 + value.len() as u64);
+        self.alloc();
+        self.asm.push_u64(value.len() as u64);
+        self.asm.dup(2);
+        self.asm.op(Op::MStore);
+
+        if !value.is_empty() {
+            let data = self.asm.data(value);
+            self.asm.push_u64(value.len() as u64);
+            self.asm.push_label(data);
+            self.asm.dup(3);
+            self.asm.push_u64(32);
+            self.asm.op(Op::Add);
+            self.asm.op(Op::CodeCopy);
+        }
+    }
+
+    /// Bring a value on the stack into canonical form for its type
+    pub(super) fn normalize(&mut self, ty: &Type) {
+        match ty {
+            Type::UserType(no) => self.normalize(&self.ns.user_types[*no].ty.clone()),
+            Type::Int(bits) if *bits < 256 => {
+                self.asm.push_u64(*bits as u64 / 8 - 1);
+                self.asm.op(Op::SignExtend);
+            }
+            Type::Uint(_)
+            | Type::Address(_)
+            | Type::Contract(_)
+            | Type::Bytes(_)
+            | Type::Enum(_)
+            | Type::Value
+            | Type::FunctionSelector => {
+                let bits = ty.bits(self.ns);
+                if bits < 256 {
+                    self.asm.push_bigint(&low_bits(bits));
+                    self.asm.op(Op::And);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Panic if the value on the stack does not fit in the signed type: [value] -> [value]
+    fn check_signed_range(&mut self, bits: u16) {
+        if bits < 256 {
+            self.asm.dup(1);
+            self.asm.dup(1);
+            self.asm.push_u64(bits as u64 / 8 - 1);
+            self.asm.op(Op::SignExtend);
+            self.asm.ops(&[Op::Eq, Op::IsZero]);
+            self.jump_to_panic(PanicCode::MathOverflow);
+        }
+    }
+
+    /// Panic if the value on the stack does not fit in the unsigned type: [value] -> [value]
+    fn check_unsigned_range(&mut self, bits: u16) {
+        if bits < 256 {
+            self.asm.push_bigint(&low_bits(bits));
+            self.asm.dup(2);
+            self.asm.op(Op::Gt);
+            self.jump_to_panic(PanicCode::MathOverflow);
+        }
+    }
+
+    /// [right, left] -> [left + right]
+    fn add(&mut self, ty: &Type, overflowing: bool) {
+        let bits = ty.bits(self.ns);
+        let signed = ty.is_signed_int(self.ns);
+
+        if overflowing {
+            self.asm.op(Op::Add);
+            self.normalize(ty);
+        } else if bits < 256 {
+            self.asm.op(Op::Add);
+            if signed {
+                self.check_signed_range(bits);
+            } else {
+                self.check_unsigned_range(bits);
+            }
+        } else if signed {
+            // overflow if (sum < left) != (right < 0)
+            self.asm.dup(2);
+            self.asm.dup(2);
+            self.asm.op(Op::Add);
+            self.asm.swap(1);
+            self.asm.dup(2);
+            self.asm.op(Op::SLt);
+            self.asm.swap(2);
+            self.asm.push_u64(0);
+            self.asm.swap(1);
+            self.asm.op(Op::SLt);
+            self.asm.swap(1);
+            self.asm.swap(2);
+            self.asm.op(Op::Xor);
+            self.jump_to_panic(PanicCode::MathOverflow);
+        } else {
+            // overflow if sum < right
+            self.asm.dup(2);
+            self.asm.op(Op::Add);
+            self.asm.swap(1);
+            self.asm.dup(2);
+            self.asm.op(Op::Lt);
+            self.jump_to_panic(PanicCode::MathOverflow);
+        }
+    }
+
+    /// [right, left] -> [left - right]
+    fn subtract(&mut self, ty: &Type, overflowing: bool) {
+        let bits = ty.bits(self.ns);
+        let signed = ty.is_signed_int(self.ns);
+
+        if overflowing {
+            self.asm.op(Op::Sub);
+            self.normalize(ty);
+        } else if !signed {
+            self.asm.dup(2);
+            self.asm.dup(2);
+            self.asm.op(Op::Lt);
+            self.jump_to_panic(PanicCode::MathOverflow);
+            self.asm.op(Op::Sub);
+        } else if bits < 256 {
+            self.asm.op(Op::Sub);
+            self.check_signed_range(bits);
+        } else {
+            // overflow if (difference > left) != (right < 0)
+            self.asm.dup(2);
+            self.asm.dup(2);
+            self.asm.op(Op::Sub);
+            self.asm.swap(1);
+            self.asm.dup(2);
+            self.asm.op(Op::SGt);
+            self.asm.swap(2);
+            self.asm.push_u64(0);
+            self.asm.swap(1);
+            self.asm.op(Op::SLt);
+            self.asm.swap(1);
+            self.asm.swap(2);
+            self.asm.op(Op::Xor);
+            self.jump_to_panic(PanicCode::MathOverflow);
+        }
+    }
+
+    /// [right, left] -> [left * right]
+    fn multiply(&mut self, ty: &Type, overflowing: bool) {
+        let bits = ty.bits(self.ns);
+        let signed = ty.is_signed_int(self.ns);
+
+        if overflowing {
+            self.asm.op(Op::Mul);
+            self.normalize(ty);
+        } else if bits <= 128 {
+            // the product of two values of at most 128 bits fits in 256 bits
+            self.asm.op(Op::Mul);
+            if signed {
+                self.check_signed_range(bits);
+            } else {
+                self.check_unsigned_range(bits);
+            }
+        } else {
+            // overflow if left != 0 and product / left != right
+            self.asm.dup(2);
+            self.asm.dup(2);
+            self.asm.op(Op::Mul);
+            // stack: [right, left, product]
+            self.asm.dup(2);
+            self.asm.dup(2);
+            self.asm.op(if signed { Op::SDiv } else { Op::Div });
+            self.asm.dup(4);
+            self.asm.ops(&[Op::Eq, Op::IsZero]);
+            self.asm.dup(3);
+            self.asm.ops(&[Op::IsZero, Op::IsZero, Op::And]);
+
+            if signed && bits == 256 {
+                // -1 * MIN is not detected by the division
+                self.asm.dup(3);
+                self.asm.ops(&[Op::Not, Op::IsZero]);
+                self.asm.dup(5);
+                self.asm.push_bigint(&-(BigInt::one() << 255u16));
+                self.asm.ops(&[Op::Eq, Op::And, Op::Or]);
+            }
+
+            self.jump_to_panic(PanicCode::MathOverflow);
+            self.asm.swap(2);
+            self.asm.ops(&[Op::Pop, Op::Pop]);
+
+            if signed {
+                self.check_signed_range(bits);
+            } else {
+                self.check_unsigned_range(bits);
+            }
+        }
+    }
+
+    /// Exponentiation by squaring, checking each multiplication for overflow
+    fn checked_power(
+        &mut self,
+        ty: &Type,
+        base: &Expression,
+        exp: &Expression,
+    ) -> Result<(), Diagnostic> {
+        let base_temp = self.temp();
+        let exp_temp = self.temp();
+        let result_temp = self.temp();
+
+        self.expression(base)?;
+        self.store_temp(base_temp);
+        self.expression(exp)?;
+        self.store_temp(exp_temp);
+        self.asm.push_u64(1);
+        self.store_temp(result_temp);
+
+        let looping = self.asm.new_label();
+        let skip = self.asm.new_label();
+        let done = self.asm.new_label();
+
+        self.asm.set_label(looping);
+        self.load_temp(exp_temp);
+        self.asm.op(Op::IsZero);
+        self.asm.jumpi(done);
+
+        self.load_temp(exp_temp);
+        self.asm.push_u64(1);
+        self.asm.op(Op::And);
+        self.asm.op(Op::IsZero);
+        self.asm.jumpi(skip);
+        self.load_temp(base_temp);
+        self.load_temp(result_temp);
+        self.multiply(ty, false);
+        self.store_temp(result_temp);
+
+        self.asm.set_label(skip);
+        self.load_temp(exp_temp);
+        self.asm.push_u64(1);
+        self.asm.op(Op::Shr);
+        self.asm.dup(1);
+        self.store_temp(exp_temp);
+        self.asm.op(Op::IsZero);
+        self.asm.jumpi(done);
+        self.load_temp(base_temp);
+        self.load_temp(base_temp);
+        self.multiply(ty, false);
+        self.store_temp(base_temp);
+        self.asm.jump(looping);
+
+        self.asm.set_label(done);
+        self.load_temp(result_temp);
+
+        self.free_temp();
+        self.free_temp();
+        self.free_temp();
+
+        Ok(())
+    }
+
+    /// Load a value from memory: [pointer] -> [value]
+    pub(super) fn load(&mut self, repr: Repr) {
+        match repr {
+            Repr::Le { bytes: 1, signed } => {
+                self.asm.op(Op::MLoad);
+                self.asm.push_u64(248);
+                self.asm.op(Op::Shr);
+                if signed {
+                    self.asm.push_u64(0);
+                    self.asm.op(Op::SignExtend);
+                }
+            }
+            Repr::Le { bytes, signed } => {
+                self.asm.op(Op::MLoad);
+                self.bswap();
+                if bytes < 32 {
+                    if signed {
+                        self.asm.push_u64(bytes as u64 - 1);
+                        self.asm.op(Op::SignExtend);
+                    } else {
+                        self.asm.push_bigint(&low_bits(bytes as u16 * 8));
+                        self.asm.op(Op::And);
+                    }
+                }
+            }
+            Repr::Be(bytes) => {
+                self.asm.op(Op::MLoad);
+                if bytes < 32 {
+                    self.asm.push_u64(256 - bytes as u64 * 8);
+                    self.asm.op(Op::Shr);
+                }
+            }
+            Repr::Word => self.asm.op(Op::MLoad),
+            // the value of a fixed size struct or array is its address
+            Repr::Inline(_) => (),
+        }
+    }
+
+    /// Store a value to memory: [value, pointer] -> []
+    pub(super) fn store(&mut self, repr: Repr) {
+        match repr {
+            Repr::Le { bytes: 1, .. } => self.asm.op(Op::MStore8),
+            Repr::Le { bytes: 32, .. } | Repr::Be(32) | Repr::Word => {
+                if matches!(repr, Repr::Le { .. }) {
+                    self.asm.swap(1);
+                    self.bswap();
+                    self.asm.swap(1);
+                }
+                self.asm.op(Op::MStore);
+            }
+            Repr::Le { bytes, .. } | Repr::Be(bytes) => {
+                self.asm.swap(1);
+                if matches!(repr, Repr::Le { .. }) {
+                    self.bswap();
+                    self.asm.push_bigint(&high_bytes(bytes));
+                    self.asm.op(Op::And);
+                } else {
+                    self.asm.push_u64(256 - bytes as u64 * 8);
+                    self.asm.op(Op::Shl);
+                }
+                // stack: [pointer, shifted value]; keep the bytes which follow
+                self.asm.dup(2);
+                self.asm.op(Op::MLoad);
+                self.asm.push_bigint(&low_bits(256 - bytes as u16 * 8));
+                self.asm.op(Op::And);
+                self.asm.op(Op::Or);
+                self.asm.swap(1);
+                self.asm.op(Op::MStore);
+            }
+            Repr::Inline(size) => {
+                // [source, destination] -> [destination, source, size]
+                self.asm.swap(1);
+                self.asm.push_u64(size as u64);
+                self.memcpy();
+            }
+        }
+    }
+
+    /// Compute the address of a field in an ABI encoding buffer: [] -> [address]
+    pub(super) fn buffer_address(
+        &mut self,
+        buf: &Expression,
+        offset: &Expression,
+    ) -> Result<(), Diagnostic> {
+        self.expression(buf)?;
+        self.data_pointer(&buf.ty());
+        self.expression(offset)?;
+        self.asm.op(Op::Add);
+        Ok(())
+    }
+
+    /// Write a value to an ABI encoding buffer: [value, address] -> []
+    pub(super) fn buffer_store(&mut self, ty: &Type) {
+        self.store(buffer_repr(ty, self.ns));
+    }
+
+    /// Read a value from an ABI encoding buffer: [address] -> [value]
+    fn buffer_load(&mut self, ty: &Type) {
+        let repr = buffer_repr(ty, self.ns);
+
+        self.load(repr);
+
+        // a fixed size struct or array is copied out of the buffer
+        if let Repr::Inline(size) = repr {
+            self.asm.push_u64(size as u64);
+            self.alloc();
+            self.asm.dup(1);
+            self.asm.swap(2);
+            self.asm.push_u64(size as u64);
+            self.memcpy();
+        }
+    }
+
+    /// Hash the concatenation of the values, like the other targets do for mapping keys. The
+    /// result is a little endian integer.
+    fn keccak256(&mut self, exprs: &[Expression]) -> Result<(), Diagnostic> {
+        let tys: Vec<Type> = exprs.iter().map(|e| e.ty()).collect();
+        let sizes: Vec<Option<usize>> = tys
+            .iter()
+            .map(|ty| match ty {
+                Type::DynamicBytes | Type::String => None,
+                ty => Some(elem_size(ty, self.ns)),
+            })
+            .collect();
+
+        if sizes.iter().all(Option::is_some) && sizes.iter().flatten().sum::<usize>() <= 64 {
+            // the values fit in the scratch space; store them back to front so that the
+            // read-modify-write of a narrow value does not clobber the next one
+            let mut offsets = Vec::new();
+            let mut offset = 0;
+
+            for (expr, size) in exprs.iter().zip(&sizes) {
+                self.expression(expr)?;
+                offsets.push(offset);
+                offset += size.unwrap();
+            }
+
+            for (ty, offset) in tys.iter().zip(offsets).rev() {
+                self.asm.push_u64(offset as u64);
+                self.store(repr(ty, self.ns));
+            }
+
+            self.asm.push_u64(offset as u64);
+            self.asm.push_u64(0);
+        } else {
+            let temps: Vec<usize> = exprs.iter().map(|_| self.temp()).collect();
+
+            self.asm
+                .push_u64(sizes.iter().flatten().sum::<usize>() as u64);
+            for ((expr, size), temp) in exprs.iter().zip(&sizes).zip(&temps) {
+                self.expression(expr)?;
+                self.asm.dup(1);
+                self.store_temp(*temp);
+                if size.is_none() {
+                    self.asm.op(Op::MLoad);
+                    self.asm.op(Op::Add);
+                } else {
+                    self.asm.op(Op::Pop);
+                }
+            }
+
+            // stack: [length]
+            self.asm.dup(1);
+            self.alloc();
+            self.asm.dup(1);
+            // stack: [length, buffer, cursor]
+            for ((ty, size), temp) in tys.iter().zip(&sizes).zip(&temps) {
+                match size {
+                    Some(size) => {
+                        self.load_temp(*temp);
+                        self.asm.dup(2);
+                        self.store(repr(ty, self.ns));
+                        self.asm.push_u64(*size as u64);
+                    }
+                    None => {
+                        self.asm.dup(1);
+                        self.load_temp(*temp);
+                        self.asm.push_u64(32);
+                        self.asm.op(Op::Add);
+                        self.load_temp(*temp);
+                        self.asm.op(Op::MLoad);
+                        self.memcpy();
+                        self.load_temp(*temp);
+                        self.asm.op(Op::MLoad);
+                    }
+                }
+                self.asm.op(Op::Add);
+            }
+            self.asm.op(Op::Pop);
+
+            for _ in &temps {
+                self.free_temp();
+            }
+        }
+
+        self.asm.op(Op::Keccak256);
+        self.bswap();
+
+        Ok(())
+    }
+
+    /// Push the keccak256 hash of a string, for comparing strings
+    fn string_hash(&mut self, string: &StringLocation<Expression>) -> Result<(), Diagnostic> {
+        match string {
+            StringLocation::CompileTime(value) => {
+                let mut hasher = tiny_keccak::Keccak::v256();
+                let mut hash = [0u8; 32];
+                tiny_keccak::Hasher::update(&mut hasher, value);
+                tiny_keccak::Hasher::finalize(hasher, &mut hash);
+                self.asm.push_word(&hash);
+            }
+            StringLocation::RunTime(expr) => {
+                self.expression(expr)?;
+                self.bytes_and_length();
+                self.asm.op(Op::Keccak256);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// [bytes] -> [length, data]
+    fn bytes_and_length(&mut self) {
+        self.asm.dup(1);
+        self.asm.op(Op::MLoad);
+        self.asm.swap(1);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+    }
+
+    fn builtin(
+        &mut self,
+        loc: &Loc,
+        tys: &[Type],
+        kind: &Builtin,
+        args: &[Expression],
+    ) -> Result<(), Diagnostic> {
+        let opcode = match kind {
+            Builtin::Value => Some(Op::CallValue),
+            Builtin::Sender => Some(Op::Caller),
+            Builtin::Origin => Some(Op::Origin),
+            Builtin::Gasprice => Some(Op::GasPrice),
+            Builtin::Gasleft => Some(Op::Gas),
+            Builtin::GasLimit => Some(Op::GasLimit),
+            Builtin::BlockNumber => Some(Op::Number),
+            Builtin::Timestamp => Some(Op::Timestamp),
+            Builtin::ChainId => Some(Op::ChainId),
+            Builtin::BlockCoinbase => Some(Op::Coinbase),
+            Builtin::BlockDifficulty | Builtin::PrevRandao => Some(Op::PrevRandao),
+            Builtin::BaseFee => Some(Op::BaseFee),
+            Builtin::BlockHash => Some(Op::BlockHash),
+            Builtin::Balance => Some(Op::Balance),
+            Builtin::ExtCodeSize => Some(Op::ExtCodeSize),
+            _ => None,
+        };
+
+        if let Some(opcode) = opcode {
+            for arg in args.iter().rev() {
+                self.expression(arg)?;
+            }
+            self.asm.op(opcode);
+            if let Some(ty) = tys.first() {
+                self.normalize(ty);
+            }
+            return Ok(());
+        }
+
+        match kind {
+            Builtin::GetAddress => {
+                self.asm.op(Op::Address);
+                self.asm.push_u64(96);
+                self.asm.op(Op::Shl);
+                self.asm.push_u64(ADDRESS);
+                self.asm.op(Op::MStore);
+                self.asm.push_u64(ADDRESS);
+            }
+            Builtin::Calldata => {
+                self.asm.push_u64(CALLDATA);
+                self.asm.op(Op::MLoad);
+            }
+            Builtin::Signature => {
+                self.asm.push_u64(SELECTOR);
+                self.asm.op(Op::MLoad);
+                self.asm.push_u64(224);
+                self.asm.op(Op::Shr);
+            }
+            Builtin::ContractCode => {
+                self.expression(&args[0])?;
+                self.asm.dup(1);
+                self.asm.op(Op::ExtCodeSize);
+                self.asm.dup(1);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.alloc();
+                // stack: [address, size, ptr]
+                self.asm.dup(2);
+                self.asm.dup(2);
+                self.asm.op(Op::MStore);
+                self.asm.swap(1);
+                self.asm.push_u64(0);
+                self.asm.dup(3);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.asm.dup(5);
+                self.asm.op(Op::ExtCodeCopy);
+                self.asm.swap(1);
+                self.asm.op(Op::Pop);
+            }
+            Builtin::AddMod | Builtin::MulMod => {
+                for arg in args.iter().rev() {
+                    self.expression(arg)?;
+                }
+                self.asm.op(if *kind == Builtin::AddMod {
+                    Op::AddMod
+                } else {
+                    Op::MulMod
+                });
+            }
+            Builtin::Keccak256 => {
+                self.expression(&args[0])?;
+                self.bytes_and_length();
+                self.asm.op(Op::Keccak256);
+            }
+            Builtin::Sha256 | Builtin::Ripemd160 => {
+                self.asm.push_u64(32);
+                self.asm.push_u64(0);
+                self.expression(&args[0])?;
+                self.bytes_and_length();
+                self.asm
+                    .push_u64(if *kind == Builtin::Sha256 { 2 } else { 3 });
+                self.asm.op(Op::Gas);
+                self.asm.op(Op::StaticCall);
+                self.asm.op(Op::Pop);
+                self.asm.push_u64(0);
+                self.asm.op(Op::MLoad);
+            }
+            Builtin::ArrayLength => {
+                self.expression(&args[0])?;
+                self.asm.op(Op::MLoad);
+            }
+            Builtin::ReadFromBuffer => {
+                self.buffer_address(&args[0], &args[1])?;
+                self.buffer_load(&tys[0]);
+            }
+            Builtin::Concat => {
+                let temps: Vec<usize> = args.iter().map(|_| self.temp()).collect();
+
+                self.asm.push_u64(0);
+                for (arg, temp) in args.iter().zip(&temps) {
+                    self.expression(arg)?;
+                    self.asm.dup(1);
+                    self.store_temp(*temp);
+                    self.asm.op(Op::MLoad);
+                    self.asm.op(Op::Add);
+                }
+
+                // stack: [length]
+                self.asm.dup(1);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.alloc();
+                self.asm.swap(1);
+                self.asm.dup(2);
+                self.asm.op(Op::MStore);
+                self.asm.dup(1);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                // stack: [result, cursor]
+                for temp in &temps {
+                    self.asm.dup(1);
+                    self.load_temp(*temp);
+                    self.bytes_and_length();
+                    self.asm.swap(1);
+                    self.memcpy();
+                    self.load_temp(*temp);
+                    self.asm.op(Op::MLoad);
+                    self.asm.op(Op::Add);
+                }
+                self.asm.op(Op::Pop);
+
+                for _ in &temps {
+                    self.free_temp();
+                }
+            }
+            Builtin::Blake2_128 | Builtin::Blake2_256 => {
+                return Err(not_supported(*loc, "blake2 hashing"));
+            }
+            Builtin::SignatureVerify => {
+                return Err(not_supported(*loc, "signature verification"));
+            }
+            Builtin::MinimumBalance => {
+                return Err(not_supported(*loc, "'minimum_balance'"));
+            }
+            Builtin::Accounts => return Err(not_supported(*loc, "'tx.accounts'")),
+            Builtin::Slot => return Err(not_supported(*loc, "'block.slot'")),
+            Builtin::ArraySlice => return Err(not_supported(*loc, "array slices")),
+            _ => {
+                return Err(Diagnostic::error(
+                    args.first().map(|arg| arg.loc()).unwrap_or(*loc),
+                    format!("unexpected builtin {kind:?} on EVM"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,783 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::assembler::{Label, Op};
+use super::{compile, repr, Emitter, Function, FRAME_POINTER, FREE_POINTER};
+use crate::codegen::cfg::{Instr, InternalCallTy, ReturnCode};
+use crate::codegen::revert::PanicCode;
+use crate::codegen::Expression;
+use crate::sema::ast::{CallTy, RetrieveType, Type};
+use solang_parser::diagnostics::Diagnostic;
+use solang_parser::pt::{CodeLocation, Loc};
+use std::collections::HashMap;
+
+impl<'a> Emitter<'a> {
+    /// Emit a function. On entry, the return address is on the stack and the frame pointer
+    /// points to the frame, which holds the arguments.
+    pub(super) fn function(&mut self, cfg_no: usize) -> Result<(), Diagnostic> {
+        let cfg = &self.contract.cfg[cfg_no];
+
+        let slots = std::cmp::max(cfg.params.len(), cfg.returns.len());
+        let vars: HashMap<usize, usize> = cfg
+            .vars
+            .keys()
+            .enumerate()
+            .map(|(i, var_no)| (*var_no, (slots + i) * 32))
+            .collect();
+
+        let blocks = cfg.blocks.iter().map(|_| self.asm.new_label()).collect();
+        let frame_size = self.asm.new_label();
+
+        self.func = Some(Function {
+            blocks,
+            temps_start: slots + vars.len(),
+            vars,
+            temps: 0,
+            max_temps: 0,
+            frame_size,
+        });
+
+        self.asm.set_label(self.functions[&cfg_no]);
+
+        if !self.bounded_frames {
+            // make sure the frame does not run into the heap
+            self.asm.push_label(self.frames_end);
+            self.asm.push_label(frame_size);
+            self.frame_pointer();
+            self.asm.op(Op::Add);
+            self.asm.op(Op::Gt);
+            self.jump_to_panic(PanicCode::OutOfMemory);
+        }
+
+        for (block_no, block) in cfg.blocks.iter().enumerate() {
+            self.asm.set_label(self.func().blocks[block_no]);
+
+            for instr in &block.instr {
+                self.instruction(instr)?;
+            }
+        }
+
+        let func = self.func.take().unwrap();
+        let size = (func.temps_start + func.max_temps) * 32;
+
+        self.asm.define(func.frame_size, size);
+        self.frame_sizes.insert(cfg_no, size);
+
+        Ok(())
+    }
+
+    fn func(&self) -> &Function {
+        self.func.as_ref().unwrap()
+    }
+
+    fn instruction(&mut self, instr: &Instr) -> Result<(), Diagnostic> {
+        match instr {
+            Instr::Nop | Instr::Print { .. } => (),
+            Instr::Set { res, expr, .. } => {
+                self.expression(expr)?;
+                self.store_var(*res);
+            }
+            Instr::Branch { block } => {
+                self.asm.jump(self.func().blocks[*block]);
+            }
+            Instr::BranchCond {
+                cond,
+                true_block,
+                false_block,
+            } => {
+                self.expression(cond)?;
+                self.asm.jumpi(self.func().blocks[*true_block]);
+                self.asm.jump(self.func().blocks[*false_block]);
+            }
+            Instr::Switch {
+                cond,
+                cases,
+                default,
+            } => {
+                let temp = self.temp();
+                self.expression(cond)?;
+                self.store_temp(temp);
+
+                for (case, block) in cases {
+                    self.expression(case)?;
+                    self.load_temp(temp);
+                    self.asm.op(Op::Eq);
+                    self.asm.jumpi(self.func().blocks[*block]);
+                }
+
+                self.free_temp();
+                self.asm.jump(self.func().blocks[*default]);
+            }
+            Instr::Call {
+                res, call, args, ..
+            } => self.call(res, call, args)?,
+            Instr::Return { value } => {
+                for v in value {
+                    self.expression(v)?;
+                }
+
+                for slot in (0..value.len()).rev() {
+                    self.frame_address(slot * 32);
+                    self.asm.op(Op::MStore);
+                }
+
+                // the return address is on the stack
+                self.asm.op(Op::Jump);
+            }
+            Instr::Store { dest, data } => {
+                self.expression(data)?;
+                self.expression(dest)?;
+                self.store(repr(&data.ty(), self.ns));
+            }
+            Instr::LoadStorage { res, ty, storage } => {
+                self.expression(storage)?;
+                self.storage_load(ty)?;
+                self.store_var(*res);
+            }
+            Instr::ClearStorage { ty, storage } => {
+                self.expression(storage)?;
+                self.storage_clear(ty)?;
+            }
+            Instr::SetStorage { ty, value, storage } => {
+                self.expression(value)?;
+                self.expression(storage)?;
+                self.storage_store(ty)?;
+            }
+            Instr::SetStorageBytes {
+                value,
+                storage,
+                offset,
+            } => {
+                self.expression(value)?;
+                self.expression(offset)?;
+                self.expression(storage)?;
+                self.storage_bytes_set();
+            }
+            Instr::PushStorage {
+                res,
+                value,
+                storage,
+                ..
+            } => {
+                match value {
+                    Some(value) => self.expression(value)?,
+                    None => self.asm.push_u64(0),
+                }
+                self.asm.dup(1);
+                self.store_var(*res);
+                self.expression(storage)?;
+                self.storage_bytes_push();
+            }
+            Instr::PopStorage { res, storage, .. } => {
+                self.expression(storage)?;
+                self.storage_bytes_pop();
+                match res {
+                    Some(res) => self.store_var(*res),
+                    None => self.asm.op(Op::Pop),
+                }
+            }
+            Instr::PushMemory {
+                res,
+                ty,
+                array,
+                value,
+            } => self.push_memory(*res, ty, *array, value)?,
+            Instr::PopMemory { res, ty, array, .. } => self.pop_memory(*res, ty, *array),
+            Instr::WriteBuffer { buf, offset, value } => {
+                self.expression(value)?;
+                self.buffer_address(buf, offset)?;
+                self.buffer_store(&value.ty());
+            }
+            Instr::MemCopy {
+                source,
+                destination,
+                bytes,
+            } => {
+                self.expression(destination)?;
+                self.data_pointer(&destination.ty());
+                self.expression(source)?;
+                self.data_pointer(&source.ty());
+                self.expression(bytes)?;
+                self.memcpy();
+            }
+            Instr::AssertFailure { encoded_args, .. } => {
+                match encoded_args {
+                    Some(args) => {
+                        self.expression(args)?;
+                        self.asm.dup(1);
+                        self.asm.op(Op::MLoad);
+                        self.asm.swap(1);
+                        self.asm.push_u64(32);
+                        self.asm.op(Op::Add);
+                    }
+                    None => {
+                        self.asm.push_u64(0);
+                        self.asm.push_u64(0);
+                    }
+                }
+                self.asm.op(Op::Revert);
+            }
+            Instr::ReturnData { data, data_len } => {
+                if self.deploy() {
+                    self.return_runtime_code();
+                } else {
+                    self.expression(data_len)?;
+                    self.expression(data)?;
+                    self.data_pointer(&data.ty());
+                    self.asm.op(Op::Return);
+                }
+            }
+            Instr::ReturnCode { code } => match code {
+                ReturnCode::Success if self.deploy() => self.return_runtime_code(),
+                ReturnCode::Success => self.asm.op(Op::Stop),
+                _ => {
+                    self.asm.push_u64(0);
+                    self.asm.push_u64(0);
+                    self.asm.op(Op::Revert);
+                }
+            },
+            Instr::Unimplemented { .. } => self.asm.op(Op::Invalid),
+            Instr::EmitEvent { data, topics, .. } => {
+                if topics.len() > 4 {
+                    return Err(Diagnostic::error(
+                        data.loc(),
+                        "events can have at most 4 topics on EVM".into(),
+                    ));
+                }
+
+                for topic in topics.iter().rev() {
+                    self.expression(topic)?;
+                    self.asm.push_u64(32);
+                    self.asm.op(Op::Add);
+                    self.asm.op(Op::MLoad);
+                }
+
+                self.expression(data)?;
+                self.asm.dup(1);
+                self.asm.op(Op::MLoad);
+                self.asm.swap(1);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.asm.log(topics.len());
+            }
+            Instr::ExternalCall {
+                loc,
+                success,
+                address,
+                payload,
+                value,
+                gas,
+                callty,
+                flags,
+                ..
+            } => {
+                let Some(address) = address else {
+                    return Err(Diagnostic::error(
+                        *loc,
+                        "external call without address is not supported on EVM".into(),
+                    ));
+                };
+
+                if flags.is_some() {
+                    return Err(Diagnostic::error(
+                        *loc,
+                        "call flags are not supported on EVM".into(),
+                    ));
+                }
+
+                // return data is retrieved with Expression::ReturnData
+                self.asm.push_u64(0);
+                self.asm.push_u64(0);
+                self.expression(payload)?;
+                self.asm.dup(1);
+                self.asm.op(Op::MLoad);
+                self.asm.swap(1);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                if *callty == CallTy::Regular {
+                    self.expression(value)?;
+                }
+                self.expression(address)?;
+                self.expression(gas)?;
+                self.asm.op(match callty {
+                    CallTy::Regular => Op::Call,
+                    CallTy::Delegate => Op::DelegateCall,
+                    CallTy::Static => Op::StaticCall,
+                });
+
+                self.call_success(*success);
+            }
+            Instr::ValueTransfer {
+                success,
+                address,
+                value,
+            } => {
+                for _ in 0..4 {
+                    self.asm.push_u64(0);
+                }
+                self.expression(value)?;
+                self.expression(address)?;
+                // only the stipend is passed on
+                self.asm.push_u64(0);
+                self.asm.op(Op::Call);
+
+                self.call_success(*success);
+            }
+            Instr::Constructor {
+                success,
+                res,
+                contract_no,
+                encoded_args,
+                value,
+                salt,
+                loc,
+                ..
+            } => self.constructor(*success, *res, *contract_no, encoded_args, value, salt, loc)?,
+            Instr::SelfDestruct { recipient } => {
+                self.expression(recipient)?;
+                self.asm.op(Op::SelfDestruct);
+            }
+            Instr::AccountAccess { loc, .. } => {
+                return Err(Diagnostic::error(
+                    *loc,
+                    "accounts are not supported on EVM".into(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Call an internal function. The arguments are written to the frame of the callee, which
+    /// starts where the frame of the caller ends.
+    fn call(
+        &mut self,
+        res: &[usize],
+        call: &InternalCallTy,
+        args: &[Expression],
+    ) -> Result<(), Diagnostic> {
+        let frame_size = self.func().frame_size;
+
+        for (arg_no, arg) in args.iter().enumerate() {
+            self.expression(arg)?;
+            self.callee_frame_address(arg_no);
+            self.asm.op(Op::MStore);
+        }
+
+        let ret = self.asm.new_label();
+        self.asm.push_label(ret);
+
+        match call {
+            InternalCallTy::Static { cfg_no } => {
+                self.adjust_frame_pointer(Op::Add);
+                self.asm.jump(self.functions[cfg_no]);
+            }
+            InternalCallTy::Dynamic(function) => {
+                self.expression(function)?;
+                self.adjust_frame_pointer(Op::Add);
+                self.asm.op(Op::Jump);
+            }
+            InternalCallTy::Builtin { .. } => {
+                return Err(Diagnostic::error(
+                    args.first().map(|arg| arg.loc()).unwrap_or(Loc::Codegen),
+                    "builtin functions are not supported on EVM".into(),
+                ));
+            }
+        }
+
+        self.asm.set_label(ret);
+        self.asm.push_label(frame_size);
+        self.frame_pointer();
+        self.asm.op(Op::Sub);
+        self.asm.push_u64(FRAME_POINTER);
+        self.asm.op(Op::MStore);
+
+        for (slot, var_no) in res.iter().enumerate() {
+            self.callee_frame_address(slot);
+            self.asm.op(Op::MLoad);
+            self.store_var(*var_no);
+        }
+
+        Ok(())
+    }
+
+    /// Move the frame pointer past the frame of the current function, or back
+    fn adjust_frame_pointer(&mut self, op: Op) {
+        self.frame_pointer();
+        self.asm.push_label(self.func().frame_size);
+        self.asm.op(op);
+        self.asm.push_u64(FRAME_POINTER);
+        self.asm.op(Op::MStore);
+    }
+
+    fn callee_frame_address(&mut self, slot: usize) {
+        self.frame_pointer();
+        self.asm.push_label(self.func().frame_size);
+        self.asm.op(Op::Add);
+        if slot > 0 {
+            self.asm.push_u64(slot as u64 * 32);
+            self.asm.op(Op::Add);
+        }
+    }
+
+    /// Handle the success flag of a call or create on the stack; if there is no variable
+    /// to store it in, revert with the return data of the callee.
+    fn call_success(&mut self, success: Option<usize>) {
+        match success {
+            Some(success) => self.store_var(success),
+            None => {
+                self.asm.op(Op::IsZero);
+                let label = self.bubble_revert();
+                self.asm.jumpi(label);
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn constructor(
+        &mut self,
+        success: Option<usize>,
+        res: usize,
+        contract_no: usize,
+        encoded_args: &Expression,
+        value: &Option<Expression>,
+        salt: &Option<Expression>,
+        loc: &Loc,
+    ) -> Result<(), Diagnostic> {
+        let (code, code_len) = self.creation_code(contract_no, loc)?;
+
+        // the constructor arguments are appended to the creation code
+        self.expression(encoded_args)?;
+        self.asm.dup(1);
+        self.asm.op(Op::MLoad);
+        self.asm.push_u64(code_len as u64);
+        self.asm.op(Op::Add);
+        self.asm.dup(1);
+        self.alloc();
+        // stack: [args, size, ptr]
+        self.asm.push_u64(code_len as u64);
+        self.asm.push_label(code);
+        self.asm.dup(3);
+        self.asm.op(Op::CodeCopy);
+        self.asm.dup(1);
+        self.asm.push_u64(code_len as u64);
+        self.asm.op(Op::Add);
+        self.asm.dup(4);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        self.asm.dup(5);
+        self.asm.op(Op::MLoad);
+        self.memcpy();
+        self.asm.swap(2);
+        self.asm.op(Op::Pop);
+        self.asm.swap(1);
+        // stack: [size, ptr]
+
+        if let Some(salt) = salt {
+            self.expression(salt)?;
+            self.asm.swap(2);
+            self.asm.swap(1);
+        }
+
+        match value {
+            Some(value) => self.expression(value)?,
+            None => self.asm.push_u64(0),
+        }
+
+        self.asm.op(if salt.is_some() {
+            Op::Create2
+        } else {
+            Op::Create
+        });
+
+        self.asm.dup(1);
+        self.store_var(res);
+
+        if success.is_some() {
+            self.asm.op(Op::IsZero);
+            self.asm.op(Op::IsZero);
+        }
+
+        self.call_success(success);
+
+        Ok(())
+    }
+
+    /// Embed the creation code of another contract
+    fn creation_code(
+        &mut self,
+        contract_no: usize,
+        loc: &Loc,
+    ) -> Result<(Label, usize), Diagnostic> {
+        if let Some(code) = self.creation_code.get(&contract_no) {
+            return Ok(*code);
+        }
+
+        let contract = &self.ns.contracts[contract_no];
+
+        let code = match contract.code.get() {
+            Some(code) => code.clone(),
+            None => {
+                let code = compile(contract, self.ns).map_err(|diag| {
+                    Diagnostic::error(
+                        *loc,
+                        format!(
+                            "cannot create contract '{}': {}",
+                            contract.id.name, diag.message
+                        ),
+                    )
+                })?;
+                let _ = contract.code.set(code.clone());
+                code
+            }
+        };
+
+        let label = self.asm.data(&code);
+
+        self.creation_code.insert(contract_no, (label, code.len()));
+
+        Ok((label, code.len()))
+    }
+
+    /// Push an element on a memory array. Memory arrays cannot grow in place, so the array is
+    /// copied to a new allocation.
+    fn push_memory(
+        &mut self,
+        res: usize,
+        ty: &Type,
+        array: usize,
+        value: &Expression,
+    ) -> Result<(), Diagnostic> {
+        let elem_ty = ty.array_elem();
+        let elem_size = super::elem_size(&elem_ty, self.ns) as u64;
+
+        self.load_var(array);
+        self.asm.dup(1);
+        self.asm.op(Op::MLoad);
+        // stack: [old, len]
+        self.asm.dup(1);
+        self.asm.push_u64(1);
+        self.asm.op(Op::Add);
+        self.asm.dup(1);
+        self.asm.push_u64(elem_size);
+        self.asm.op(Op::Mul);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        self.alloc();
+        // stack: [old, len, len + 1, new]
+        self.asm.swap(1);
+        self.asm.dup(2);
+        self.asm.op(Op::MStore);
+        // stack: [old, len, new]
+        self.asm.dup(1);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        self.asm.dup(4);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        self.asm.dup(4);
+        self.asm.push_u64(elem_size);
+        self.asm.op(Op::Mul);
+        self.memcpy();
+        self.asm.dup(1);
+        self.store_var(array);
+        // stack: [old, len, new]
+        self.asm.swap(2);
+        self.asm.op(Op::Pop);
+        self.asm.push_u64(elem_size);
+        self.asm.op(Op::Mul);
+        self.asm.op(Op::Add);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        // stack: [elem]
+        self.expression(value)?;
+        self.asm.dup(2);
+
+        let repr = repr(&elem_ty, self.ns);
+        self.store(repr);
+        // for structs, the result is a reference to the new element
+        self.load(repr);
+        self.store_var(res);
+
+        Ok(())
+    }
+
+    fn pop_memory(&mut self, res: usize, ty: &Type, array: usize) {
+        let elem_ty = ty.array_elem();
+        let elem_size = super::elem_size(&elem_ty, self.ns) as u64;
+
+        self.load_var(array);
+        self.asm.dup(1);
+        self.asm.op(Op::MLoad);
+        // stack: [array, len]
+        self.asm.dup(1);
+        self.asm.op(Op::IsZero);
+        self.jump_to_panic(PanicCode::EmptyArrayPop);
+        self.asm.push_u64(1);
+        self.asm.swap(1);
+        self.asm.op(Op::Sub);
+        self.asm.dup(1);
+        self.asm.dup(3);
+        self.asm.op(Op::MStore);
+        // stack: [array, len - 1]
+        self.asm.push_u64(elem_size);
+        self.asm.op(Op::Mul);
+        self.asm.op(Op::Add);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        self.load(repr(&elem_ty, self.ns));
+        self.store_var(res);
+    }
+
+    fn return_runtime_code(&mut self) {
+        let runtime = self.runtime.as_ref().unwrap();
+        let len = runtime.len() as u64;
+
+        let label = match self.runtime_label {
+            Some(label) => label,
+            None => {
+                let label = self.asm.data(runtime);
+                self.runtime_label = Some(label);
+                label
+            }
+        };
+
+        self.asm.push_u64(len);
+        self.asm.push_label(label);
+        self.asm.push_u64(0);
+        self.asm.op(Op::CodeCopy);
+        self.asm.push_u64(len);
+        self.asm.push_u64(0);
+        self.asm.op(Op::Return);
+    }
+
+    /// Convert a pointer to a memory array to a pointer to its data
+    pub(super) fn data_pointer(&mut self, ty: &Type) {
+        if ty.is_dynamic_memory() {
+            self.asm.push_u64(32);
+            self.asm.op(Op::Add);
+        }
+    }
+
+    pub(super) fn frame_pointer(&mut self) {
+        self.asm.push_u64(FRAME_POINTER);
+        self.asm.op(Op::MLoad);
+    }
+
+    pub(super) fn frame_address(&mut self, offset: usize) {
+        self.frame_pointer();
+        if offset > 0 {
+            self.asm.push_u64(offset as u64);
+            self.asm.op(Op::Add);
+        }
+    }
+
+    pub(super) fn load_var(&mut self, var_no: usize) {
+        let offset = self.func().vars[&var_no];
+        self.frame_address(offset);
+        self.asm.op(Op::MLoad);
+    }
+
+    pub(super) fn store_var(&mut self, var_no: usize) {
+        let offset = self.func().vars[&var_no];
+        self.frame_address(offset);
+        self.asm.op(Op::MStore);
+    }
+
+    /// Reserve a frame slot for a temporary value. Temporaries are freed in reverse order.
+    pub(super) fn temp(&mut self) -> usize {
+        let func = self.func.as_mut().unwrap();
+        let offset = (func.temps_start + func.temps) * 32;
+
+        func.temps += 1;
+        func.max_temps = std::cmp::max(func.max_temps, func.temps);
+
+        offset
+    }
+
+    pub(super) fn free_temp(&mut self) {
+        self.func.as_mut().unwrap().temps -= 1;
+    }
+
+    pub(super) fn load_temp(&mut self, offset: usize) {
+        self.frame_address(offset);
+        self.asm.op(Op::MLoad);
+    }
+
+    pub(super) fn store_temp(&mut self, offset: usize) {
+        self.frame_address(offset);
+        self.asm.op(Op::MStore);
+    }
+
+    /// Allocate memory: [size] -> [pointer]. The memory is never freed, so it is always zero.
+    pub(super) fn alloc(&mut self) {
+        self.asm.push_u64(31);
+        self.asm.op(Op::Add);
+        self.asm.push_u64(5);
+        self.asm.op(Op::Shr);
+        self.asm.push_u64(5);
+        self.asm.op(Op::Shl);
+        self.asm.push_u64(FREE_POINTER);
+        self.asm.op(Op::MLoad);
+        self.asm.dup(1);
+        self.asm.swap(2);
+        self.asm.op(Op::Add);
+        self.asm.push_u64(FREE_POINTER);
+        self.asm.op(Op::MStore);
+    }
+
+    /// Copy memory using the identity precompile: [dest, src, len] -> []
+    pub(super) fn memcpy(&mut self) {
+        self.asm.dup(1);
+        self.asm.dup(4);
+        self.asm.dup(3);
+        self.asm.dup(5);
+        self.asm.push_u64(4);
+        self.asm.op(Op::Gas);
+        self.asm.op(Op::StaticCall);
+        self.asm.ops(&[Op::Pop, Op::Pop, Op::Pop, Op::Pop]);
+    }
+
+    /// Reverse the byte order of the word on the stack
+    pub(super) fn bswap(&mut self) {
+        let label = match self.bswap {
+            Some(label) => label,
+            None => {
+                let label = self.asm.new_label();
+                self.bswap = Some(label);
+                label
+            }
+        };
+
+        let ret = self.asm.new_label();
+        self.asm.push_label(ret);
+        self.asm.swap(1);
+        self.asm.jump(label);
+        self.asm.set_label(ret);
+    }
+
+    /// Jump to a panic with the given code if the value on the stack is true
+    pub(super) fn jump_to_panic(&mut self, code: PanicCode) {
+        let code = code as u8;
+        let label = match self.panics.get(&code) {
+            Some(label) => *label,
+            None => {
+                let label = self.asm.new_label();
+                self.panics.insert(code, label);
+                label
+            }
+        };
+
+        self.asm.jumpi(label);
+    }
+
+    /// Label of code which reverts with the return data of the last call
+    fn bubble_revert(&mut self) -> Label {
+        if let Some(label) = self.bubble_revert {
+            return label;
+        }
+
+        let label = self.asm.new_label();
+        self.bubble_revert = Some(label);
+        label
+    }
+}
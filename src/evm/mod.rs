@@ -0,0 +1,566 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! EVM backend. This lowers the codegen control flow graphs of a contract directly to EVM
+//! bytecode, without going through LLVM.
+//!
+//! The generated code uses the same dispatcher as Polkadot, with the Ethereum ABI encoding
+//! which solc uses: the first four bytes of the call data are the function selector, followed
+//! by the ABI encoded arguments. The constructor arguments follow the creation code, without
+//! a selector. Storage uses the slot numbering from the contract's `layout`.
+//!
+//! Memory is laid out as follows:
+//!
+//! | Offset | Contents                                                    |
+//! |--------|-------------------------------------------------------------|
+//! | 0x00   | scratch space for hashing                                   |
+//! | 0x40   | free memory pointer                                         |
+//! | 0x60   | frame pointer                                               |
+//! | 0x80   | function selector of the current call                       |
+//! | 0xa0   | address of the contract, for `Builtin::GetAddress`          |
+//! | 0xc0   | pointer to the call data, for `Builtin::Calldata`           |
+//! | 0xe0   | always zero, used in place of null pointers                 |
+//! | 0x100  | stack frames, followed by the heap                          |
+//!
+//! Every function has a stack frame in memory, in which each parameter, return value and
+//! variable has a 32 byte slot. The EVM stack is only used for evaluating expressions and for
+//! holding the return address of the current function.
+//!
+//! Values in memory objects (arrays, structs and buffers) use the same layout as the other
+//! targets: integers are little endian, addresses are stored as bytes. Dynamic arrays and
+//! strings are a 32 byte length followed by the elements.
+
+mod assembler;
+mod expression;
+mod instructions;
+mod storage;
+
+use crate::codegen::cfg::{Instr, InternalCallTy};
+use crate::codegen::dispatch::polkadot::DispatchType;
+use crate::codegen::Expression;
+use crate::sema::ast::{ArrayLength, Contract, Namespace, Type};
+use assembler::{Assembler, Label, Op};
+use solang_parser::diagnostics::Diagnostic;
+use std::collections::{HashMap, HashSet};
+
+const FREE_POINTER: u64 = 0x40;
+const FRAME_POINTER: u64 = 0x60;
+const SELECTOR: u64 = 0x80;
+const ADDRESS: u64 = 0xa0;
+const CALLDATA: u64 = 0xc0;
+const ZERO: u64 = 0xe0;
+const FRAMES_START: usize = 0x100;
+
+/// If the depth of the call graph cannot be determined, because of recursion or function
+/// pointers, this much memory is reserved for stack frames.
+const UNBOUNDED_FRAMES_SIZE: usize = 0x8000;
+
+/// Selector of `Panic(uint256)`
+const PANIC_SELECTOR: u32 = 0x4e48_7b71;
+
+/// Compile a contract to EVM bytecode. The result is the creation code, which returns the
+/// runtime code when executed. The constructor arguments should be appended to it.
+pub fn compile(contract: &Contract, ns: &Namespace) -> Result<Vec<u8>, Diagnostic> {
+    let find = |dispatch: DispatchType| {
+        let name = dispatch.to_string();
+
+        contract
+            .cfg
+            .iter()
+            .position(|cfg| cfg.name == name)
+            .ok_or_else(|| {
+                Diagnostic::error(
+                    contract.loc,
+                    format!("contract '{}' has no {name}", contract.id.name),
+                )
+            })
+    };
+
+    let call_dispatch = find(DispatchType::Call)?;
+    let deploy_dispatch = find(DispatchType::Deploy)?;
+
+    let runtime = Emitter::new(contract, ns, None).build(None, call_dispatch)?;
+
+    Emitter::new(contract, ns, Some(runtime)).build(contract.initializer, deploy_dispatch)
+}
+
+/// How a value is stored in memory
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Repr {
+    /// Little endian integer of the given number of bytes
+    Le { bytes: u8, signed: bool },
+    /// Big endian bytes, used for addresses
+    Be(u8),
+    /// 32 byte word, used for pointers and function references
+    Word,
+    /// Fixed size struct or array, which is stored inline in its containing object
+    Inline(usize),
+}
+
+fn repr(ty: &Type, ns: &Namespace) -> Repr {
+    match ty {
+        Type::Bool => Repr::Le {
+            bytes: 1,
+            signed: false,
+        },
+        Type::Enum(_) => Repr::Le {
+            bytes: ty.bytes(ns),
+            signed: false,
+        },
+        Type::Int(n) => Repr::Le {
+            bytes: (*n / 8) as u8,
+            signed: true,
+        },
+        Type::Uint(n) => Repr::Le {
+            bytes: (*n / 8) as u8,
+            signed: false,
+        },
+        Type::Bytes(n) => Repr::Le {
+            bytes: *n,
+            signed: false,
+        },
+        Type::Value | Type::FunctionSelector => Repr::Le {
+            bytes: ty.bytes(ns),
+            signed: false,
+        },
+        Type::Address(_) | Type::Contract(_) => Repr::Be(ns.address_length as u8),
+        Type::UserType(no) => repr(&ns.user_types[*no].ty, ns),
+        Type::Struct(_) | Type::Array(..) if ty.is_fixed_reference_type(ns) => {
+            Repr::Inline(inline_size(ty, ns))
+        }
+        _ => Repr::Word,
+    }
+}
+
+/// The number of bytes a value of this type occupies inside a struct or array
+fn elem_size(ty: &Type, ns: &Namespace) -> usize {
+    match repr(ty, ns) {
+        Repr::Le { bytes, .. } | Repr::Be(bytes) => bytes as usize,
+        Repr::Word => 32,
+        Repr::Inline(size) => size,
+    }
+}
+
+/// The size of a struct or an array whose outer dimension is fixed
+fn inline_size(ty: &Type, ns: &Namespace) -> usize {
+    match ty {
+        Type::Struct(str_ty) => str_ty
+            .definition(ns)
+            .fields
+            .iter()
+            .map(|field| field_size(&field.ty, field.infinite_size, ns))
+            .sum(),
+        Type::Array(_, dims) => match dims.last() {
+            Some(ArrayLength::Fixed(len)) => {
+                let len: usize = len.try_into().expect("array too large");
+                len * elem_size(&ty.array_elem(), ns)
+            }
+            _ => 32,
+        },
+        Type::UserType(no) => inline_size(&ns.user_types[*no].ty, ns),
+        _ => elem_size(ty, ns),
+    }
+}
+
+fn field_size(ty: &Type, infinite_size: bool, ns: &Namespace) -> usize {
+    if infinite_size {
+        32
+    } else {
+        elem_size(ty, ns)
+    }
+}
+
+/// Offset of a field in a memory struct
+fn field_offset(ty: &Type, member: usize, ns: &Namespace) -> usize {
+    match ty.deref_memory() {
+        Type::Struct(str_ty) => str_ty.definition(ns).fields[..member]
+            .iter()
+            .map(|field| field_size(&field.ty, field.infinite_size, ns))
+            .sum(),
+        // the selector is followed by the address
+        Type::ExternalFunction { .. } => member * 4,
+        _ => unreachable!("not a struct"),
+    }
+}
+
+/// The size of the memory object a pointer of this type refers to, when it is created empty
+fn object_size(ty: &Type, ns: &Namespace) -> usize {
+    if ty.is_dynamic_memory() {
+        32
+    } else {
+        inline_size(ty.deref_memory(), ns)
+    }
+}
+
+/// The size of an element of a memory array, or 1 for strings
+fn array_elem_size(array_ty: &Type, ns: &Namespace) -> usize {
+    match array_ty.deref_memory() {
+        Type::String | Type::DynamicBytes => 1,
+        Type::Slice(elem) => elem_size(elem, ns),
+        ty => elem_size(&ty.array_elem(), ns),
+    }
+}
+
+/// Mask which selects the lower half of every group of `2 * bits` bits
+fn swap_mask(bits: u64) -> [u8; 32] {
+    let group = bits as usize / 8;
+    let mut mask = [0u8; 32];
+
+    for (i, byte) in mask.iter_mut().enumerate() {
+        if (i / group) % 2 == 1 {
+            *byte = 0xff;
+        }
+    }
+
+    mask
+}
+
+/// The functions reachable from the roots, and whether the depth of the calls is bounded
+struct CallGraph {
+    functions: Vec<usize>,
+    callees: HashMap<usize, HashSet<usize>>,
+    bounded: bool,
+}
+
+fn call_graph(contract: &Contract, roots: &[usize]) -> CallGraph {
+    struct Scan {
+        callees: HashSet<usize>,
+        pointers: HashSet<usize>,
+        dynamic: bool,
+    }
+
+    let mut graph = CallGraph {
+        functions: Vec::new(),
+        callees: HashMap::new(),
+        bounded: true,
+    };
+    let mut worklist = roots.to_vec();
+
+    while let Some(cfg_no) = worklist.pop() {
+        if graph.callees.contains_key(&cfg_no) {
+            continue;
+        }
+
+        let mut scan = Scan {
+            callees: HashSet::new(),
+            pointers: HashSet::new(),
+            dynamic: false,
+        };
+
+        for instr in contract.cfg[cfg_no].blocks.iter().flat_map(|b| &b.instr) {
+            match instr {
+                Instr::Call {
+                    call: InternalCallTy::Static { cfg_no },
+                    ..
+                } => {
+                    scan.callees.insert(*cfg_no);
+                }
+                Instr::Call {
+                    call: InternalCallTy::Dynamic(_),
+                    ..
+                } => {
+                    scan.dynamic = true;
+                }
+                _ => (),
+            }
+
+            instr.recurse_expressions(&mut scan, |expr, scan| {
+                if let Expression::InternalFunctionCfg { cfg_no, .. } = expr {
+                    scan.pointers.insert(*cfg_no);
+                }
+                true
+            });
+        }
+
+        if scan.dynamic || !scan.pointers.is_empty() {
+            graph.bounded = false;
+        }
+
+        worklist.extend(scan.callees.iter().chain(scan.pointers.iter()));
+        graph.functions.push(cfg_no);
+        graph.callees.insert(cfg_no, scan.callees);
+    }
+
+    // recursion means we cannot know how deep the stack will get
+    fn has_cycle(
+        cfg_no: usize,
+        callees: &HashMap<usize, HashSet<usize>>,
+        visiting: &mut HashSet<usize>,
+        done: &mut HashSet<usize>,
+    ) -> bool {
+        if done.contains(&cfg_no) {
+            return false;
+        }
+        if !visiting.insert(cfg_no) {
+            return true;
+        }
+        if callees[&cfg_no]
+            .iter()
+            .any(|callee| has_cycle(*callee, callees, visiting, done))
+        {
+            return true;
+        }
+        visiting.remove(&cfg_no);
+        done.insert(cfg_no);
+        false
+    }
+
+    let mut done = HashSet::new();
+
+    if graph.bounded
+        && roots
+            .iter()
+            .any(|root| has_cycle(*root, &graph.callees, &mut HashSet::new(), &mut done))
+    {
+        graph.bounded = false;
+    }
+
+    graph.functions.sort_unstable();
+
+    graph
+}
+
+/// State for the function currently being emitted
+struct Function {
+    blocks: Vec<Label>,
+    /// Frame offset for each variable
+    vars: HashMap<usize, usize>,
+    temps_start: usize,
+    temps: usize,
+    max_temps: usize,
+    frame_size: Label,
+}
+
+struct Emitter<'a> {
+    contract: &'a Contract,
+    ns: &'a Namespace,
+    asm: Assembler,
+    /// When emitting the creation code, this is the runtime code
+    runtime: Option<Vec<u8>>,
+    runtime_label: Option<Label>,
+    functions: HashMap<usize, Label>,
+    frame_sizes: HashMap<usize, usize>,
+    func: Option<Function>,
+    heap_start: Label,
+    frames_end: Label,
+    bounded_frames: bool,
+    bswap: Option<Label>,
+    panics: HashMap<u8, Label>,
+    bubble_revert: Option<Label>,
+    /// The creation code of contracts created with `new`
+    creation_code: HashMap<usize, (Label, usize)>,
+}
+
+impl<'a> Emitter<'a> {
+    fn new(contract: &'a Contract, ns: &'a Namespace, runtime: Option<Vec<u8>>) -> Self {
+        let mut asm = Assembler::new();
+        let heap_start = asm.new_label();
+        let frames_end = asm.new_label();
+
+        Emitter {
+            contract,
+            ns,
+            asm,
+            runtime,
+            runtime_label: None,
+            functions: HashMap::new(),
+            frame_sizes: HashMap::new(),
+            func: None,
+            heap_start,
+            frames_end,
+            bounded_frames: true,
+            bswap: None,
+            panics: HashMap::new(),
+            bubble_revert: None,
+            creation_code: HashMap::new(),
+        }
+    }
+
+    fn deploy(&self) -> bool {
+        self.runtime.is_some()
+    }
+
+    /// Emit the entry code, which calls the optional storage initializer followed by the
+    /// dispatcher, and all the functions reachable from there.
+    fn build(mut self, initializer: Option<usize>, dispatch: usize) -> Result<Vec<u8>, Diagnostic> {
+        let roots: Vec<usize> = initializer.into_iter().chain([dispatch]).collect();
+        let graph = call_graph(self.contract, &roots);
+
+        self.bounded_frames = graph.bounded;
+
+        for cfg_no in &graph.functions {
+            let label = self.asm.new_label();
+            self.functions.insert(*cfg_no, label);
+        }
+
+        self.asm.push_label(self.heap_start);
+        self.asm.push_u64(FREE_POINTER);
+        self.asm.op(Op::MStore);
+        self.asm.push_u64(FRAMES_START as u64);
+        self.asm.push_u64(FRAME_POINTER);
+        self.asm.op(Op::MStore);
+
+        if let Some(initializer) = initializer {
+            let done = self.asm.new_label();
+            self.asm.push_label(done);
+            self.asm.jump(self.functions[&initializer]);
+            self.asm.set_label(done);
+        }
+
+        // copy the input into a memory buffer; for deploy this is appended to the code
+        if self.deploy() {
+            let end = self.asm.end_label();
+            self.asm.push_label(end);
+            self.asm.op(Op::CodeSize);
+            self.asm.op(Op::Sub);
+        } else {
+            self.asm.op(Op::CallDataSize);
+        }
+
+        // stack: [len]
+        self.asm.dup(1);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        self.alloc();
+        // stack: [len, buf]
+        self.asm.dup(2);
+        self.asm.dup(2);
+        self.asm.op(Op::MStore);
+        self.asm.dup(2);
+        if self.deploy() {
+            let end = self.asm.end_label();
+            self.asm.push_label(end);
+        } else {
+            self.asm.push_u64(0);
+        }
+        self.asm.dup(3);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        self.asm.op(if self.deploy() {
+            Op::CodeCopy
+        } else {
+            Op::CallDataCopy
+        });
+        self.asm.dup(1);
+        self.asm.push_u64(CALLDATA);
+        self.asm.op(Op::MStore);
+
+        // dispatcher arguments: input pointer, input length, value, selector pointer
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        self.asm.push_u64(FRAMES_START as u64);
+        self.asm.op(Op::MStore);
+        self.asm.push_u64(FRAMES_START as u64 + 32);
+        self.asm.op(Op::MStore);
+        self.asm.op(Op::CallValue);
+        self.asm.push_u64(FRAMES_START as u64 + 64);
+        self.asm.op(Op::MStore);
+        self.asm.push_u64(SELECTOR);
+        self.asm.push_u64(FRAMES_START as u64 + 96);
+        self.asm.op(Op::MStore);
+
+        // the dispatcher does not return
+        let done = self.asm.new_label();
+        self.asm.push_label(done);
+        self.asm.jump(self.functions[&dispatch]);
+        self.asm.set_label(done);
+        self.asm.op(Op::Invalid);
+
+        for cfg_no in &graph.functions {
+            self.function(*cfg_no)?;
+        }
+
+        self.emit_helpers();
+
+        let frames_size = if self.bounded_frames {
+            roots
+                .iter()
+                .map(|root| self.frames_depth(*root, &graph.callees))
+                .max()
+                .unwrap_or_default()
+        } else {
+            UNBOUNDED_FRAMES_SIZE
+        };
+
+        self.asm.define(self.frames_end, FRAMES_START + frames_size);
+        self.asm.define(self.heap_start, FRAMES_START + frames_size);
+
+        self.asm
+            .finish()
+            .map_err(|msg| Diagnostic::error(self.contract.loc, msg))
+    }
+
+    /// The maximum amount of memory used for stack frames when calling this function
+    fn frames_depth(&self, cfg_no: usize, callees: &HashMap<usize, HashSet<usize>>) -> usize {
+        self.frame_sizes[&cfg_no]
+            + callees[&cfg_no]
+                .iter()
+                .map(|callee| self.frames_depth(*callee, callees))
+                .max()
+                .unwrap_or_default()
+    }
+
+    /// Subroutines which are shared by all functions are emitted after the functions
+    fn emit_helpers(&mut self) {
+        if let Some(label) = self.bswap {
+            self.asm.set_label(label);
+
+            // stack: [return, value]
+            for bits in [8, 16, 32, 64] {
+                // ((v >> bits) & mask) | ((v & mask) << bits)
+                self.asm.dup(1);
+                self.asm.push_u64(bits);
+                self.asm.op(Op::Shr);
+                self.asm.push_word(&swap_mask(bits));
+                self.asm.op(Op::And);
+                self.asm.swap(1);
+                self.asm.push_word(&swap_mask(bits));
+                self.asm.op(Op::And);
+                self.asm.push_u64(bits);
+                self.asm.op(Op::Shl);
+                self.asm.op(Op::Or);
+            }
+
+            self.asm.dup(1);
+            self.asm.push_u64(128);
+            self.asm.op(Op::Shr);
+            self.asm.swap(1);
+            self.asm.push_u64(128);
+            self.asm.op(Op::Shl);
+            self.asm.op(Op::Or);
+
+            self.asm.swap(1);
+            self.asm.op(Op::Jump);
+        }
+
+        if let Some(label) = self.bubble_revert {
+            self.asm.set_label(label);
+            self.asm.op(Op::ReturnDataSize);
+            self.asm.push_u64(0);
+            self.asm.push_u64(0);
+            self.asm.op(Op::ReturnDataCopy);
+            self.asm.op(Op::ReturnDataSize);
+            self.asm.push_u64(0);
+            self.asm.op(Op::Revert);
+        }
+
+        let mut panics: Vec<(u8, Label)> = self.panics.iter().map(|(c, l)| (*c, *l)).collect();
+        panics.sort_unstable();
+
+        for (code, label) in panics {
+            self.asm.set_label(label);
+
+            // selector followed by the ABI encoded uint256 code
+            let mut word = [0u8; 32];
+            word[..4].copy_from_slice(&PANIC_SELECTOR.to_be_bytes());
+            self.asm.push_word(&word);
+            self.asm.push_u64(0);
+            self.asm.op(Op::MStore);
+            self.asm.push_u64(code.into());
+            self.asm.push_u64(4);
+            self.asm.op(Op::MStore);
+            self.asm.push_u64(36);
+            self.asm.push_u64(0);
+            self.asm.op(Op::Revert);
+        }
+    }
+}
@@ -0,0 +1,788 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::assembler::Op;
+use super::{elem_size, field_offset, inline_size, repr, Emitter, Repr, ZERO};
+use crate::codegen::revert::PanicCode;
+use crate::sema::ast::{ArrayLength, Type};
+use num_bigint::BigInt;
+use solang_parser::diagnostics::Diagnostic;
+use solang_parser::pt::Loc;
+
+/// How a type is laid out in storage
+enum Layout<'t> {
+    /// A single slot holding the value
+    Word,
+    /// External functions are a pointer to a selector and address in memory, and packed
+    /// into one slot in storage
+    ExternalFunction,
+    /// Length in the slot, and the content in 32 byte chunks from `keccak256(slot)`
+    Bytes,
+    /// Struct fields in consecutive slots
+    Struct,
+    /// Fixed length array
+    FixedArray(u64),
+    /// Length in the slot, and the elements from `keccak256(slot)`
+    DynamicArray,
+    Mapping,
+    /// Same layout as the underlying type of a user type or storage reference
+    Alias(&'t Type),
+}
+
+fn layout<'t>(ty: &'t Type, ns: &'t crate::sema::ast::Namespace) -> Layout<'t> {
+    match ty {
+        Type::String | Type::DynamicBytes => Layout::Bytes,
+        Type::ExternalFunction { .. } => Layout::ExternalFunction,
+        Type::Struct(_) => Layout::Struct,
+        Type::Array(_, dims) => match dims.last() {
+            Some(ArrayLength::Fixed(len)) => {
+                Layout::FixedArray(len.try_into().expect("array too large"))
+            }
+            _ => Layout::DynamicArray,
+        },
+        Type::Mapping(..) => Layout::Mapping,
+        Type::UserType(no) => Layout::Alias(&ns.user_types[*no].ty),
+        Type::StorageRef(_, ty) => Layout::Alias(ty),
+        _ => Layout::Word,
+    }
+}
+
+impl<'a> Emitter<'a> {
+    /// Load a value from storage into memory: [slot] -> [value]
+    pub(super) fn storage_load(&mut self, ty: &Type) -> Result<(), Diagnostic> {
+        match layout(ty, self.ns) {
+            Layout::Word => self.asm.op(Op::SLoad),
+            Layout::Alias(ty) => self.storage_load(ty)?,
+            Layout::ExternalFunction => {
+                self.asm.op(Op::SLoad);
+                self.asm.push_u64(4 + self.ns.address_length as u64);
+                self.alloc();
+                // stack: [word, ptr]; the selector is above the address
+                self.asm.dup(2);
+                self.asm.push_u64(160);
+                self.asm.op(Op::Shr);
+                self.asm.dup(2);
+                self.store(Repr::Le {
+                    bytes: 4,
+                    signed: false,
+                });
+                self.asm.swap(1);
+                self.normalize(&Type::Address(false));
+                self.asm.dup(2);
+                self.asm.push_u64(4);
+                self.asm.op(Op::Add);
+                self.store(repr(&Type::Address(false), self.ns));
+            }
+            Layout::Bytes => {
+                self.asm.dup(1);
+                self.asm.op(Op::SLoad);
+                self.asm.dup(1);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.alloc();
+                self.asm.dup(2);
+                self.asm.dup(2);
+                self.asm.op(Op::MStore);
+                // stack: [slot, length, ptr]
+                self.asm.swap(2);
+                self.data_slot();
+                self.asm.swap(1);
+                self.chunks();
+                self.asm.dup(3);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                // stack: [ptr, data slot, chunks, dest]
+                let looping = self.asm.new_label();
+                let done = self.asm.new_label();
+                self.asm.set_label(looping);
+                self.asm.dup(2);
+                self.asm.op(Op::IsZero);
+                self.asm.jumpi(done);
+                self.asm.dup(3);
+                self.asm.op(Op::SLoad);
+                self.asm.dup(2);
+                self.asm.op(Op::MStore);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.asm.swap(1);
+                self.asm.push_u64(1);
+                self.asm.swap(1);
+                self.asm.op(Op::Sub);
+                self.asm.swap(2);
+                self.asm.push_u64(1);
+                self.asm.op(Op::Add);
+                self.asm.swap(2);
+                self.asm.swap(1);
+                self.asm.jump(looping);
+                self.asm.set_label(done);
+                self.asm.ops(&[Op::Pop, Op::Pop, Op::Pop]);
+            }
+            Layout::Struct => {
+                let Type::Struct(str_ty) = ty else {
+                    unreachable!()
+                };
+
+                self.asm.push_u64(inline_size(ty, self.ns) as u64);
+                self.alloc();
+
+                let mut slot = BigInt::from(0);
+
+                for (member, field) in str_ty.definition(self.ns).fields.iter().enumerate() {
+                    if field.infinite_size {
+                        continue;
+                    }
+
+                    // stack: [slot, ptr]
+                    self.asm.dup(2);
+                    self.add_bigint(&slot);
+                    self.storage_load(&field.ty)?;
+                    self.asm.dup(2);
+                    self.add_offset(field_offset(ty, member, self.ns));
+                    self.store(repr(&field.ty, self.ns));
+
+                    slot += field.ty.storage_slots(self.ns);
+                }
+
+                self.asm.swap(1);
+                self.asm.op(Op::Pop);
+            }
+            Layout::FixedArray(len) => {
+                let elem_ty = ty.array_elem();
+                let elem_size = elem_size(&elem_ty, self.ns);
+
+                self.asm.push_u64(len * elem_size as u64);
+                self.alloc();
+                self.asm.push_u64(len);
+                self.load_elements(&elem_ty)?;
+            }
+            Layout::DynamicArray => {
+                let elem_ty = ty.array_elem();
+                let elem_size = elem_size(&elem_ty, self.ns);
+
+                self.asm.dup(1);
+                self.asm.op(Op::SLoad);
+                self.asm.dup(1);
+                self.asm.push_u64(elem_size as u64);
+                self.asm.op(Op::Mul);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.alloc();
+                self.asm.dup(2);
+                self.asm.dup(2);
+                self.asm.op(Op::MStore);
+                // stack: [slot, length, ptr]
+                self.asm.swap(2);
+                self.data_slot();
+                self.asm.swap(2);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.asm.swap(1);
+                self.load_elements(&elem_ty)?;
+                self.asm.push_u64(32);
+                self.asm.swap(1);
+                self.asm.op(Op::Sub);
+            }
+            Layout::Mapping => {
+                return Err(Diagnostic::error(
+                    Loc::Codegen,
+                    "mapping cannot be loaded from storage".into(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy array elements from storage to memory: [slot, data, length] -> [data]
+    fn load_elements(&mut self, elem_ty: &Type) -> Result<(), Diagnostic> {
+        let elem_size = elem_size(elem_ty, self.ns) as u64;
+        let elem_slots = elem_ty.storage_slots(self.ns);
+        let length = self.temp();
+
+        self.store_temp(length);
+        self.asm.push_u64(0);
+
+        // stack: [slot, data, index]
+        let looping = self.asm.new_label();
+        let done = self.asm.new_label();
+        self.asm.set_label(looping);
+        self.load_temp(length);
+        self.asm.dup(2);
+        self.asm.op(Op::Eq);
+        self.asm.jumpi(done);
+        self.asm.dup(1);
+        self.mul_bigint(&elem_slots);
+        self.asm.dup(4);
+        self.asm.op(Op::Add);
+        self.storage_load(elem_ty)?;
+        self.asm.dup(2);
+        self.asm.push_u64(elem_size);
+        self.asm.op(Op::Mul);
+        self.asm.dup(4);
+        self.asm.op(Op::Add);
+        self.store(repr(elem_ty, self.ns));
+        self.asm.push_u64(1);
+        self.asm.op(Op::Add);
+        self.asm.jump(looping);
+        self.asm.set_label(done);
+        self.asm.op(Op::Pop);
+        self.asm.swap(1);
+        self.asm.op(Op::Pop);
+
+        self.free_temp();
+
+        Ok(())
+    }
+
+    /// Store a value to storage: [value, slot] -> []
+    pub(super) fn storage_store(&mut self, ty: &Type) -> Result<(), Diagnostic> {
+        match layout(ty, self.ns) {
+            Layout::Word => self.asm.op(Op::SStore),
+            Layout::Alias(ty) => self.storage_store(ty)?,
+            Layout::ExternalFunction => {
+                self.asm.swap(1);
+                self.asm.dup(1);
+                self.load(Repr::Le {
+                    bytes: 4,
+                    signed: false,
+                });
+                self.asm.push_u64(160);
+                self.asm.op(Op::Shl);
+                self.asm.swap(1);
+                self.asm.push_u64(4);
+                self.asm.op(Op::Add);
+                self.load(repr(&Type::Address(false), self.ns));
+                self.asm.op(Op::Or);
+                self.asm.swap(1);
+                self.asm.op(Op::SStore);
+            }
+            Layout::Bytes => self.storage_bytes_store(),
+            Layout::Struct => {
+                let Type::Struct(str_ty) = ty else {
+                    unreachable!()
+                };
+
+                let mut slot = BigInt::from(0);
+
+                for (member, field) in str_ty.definition(self.ns).fields.iter().enumerate() {
+                    if field.infinite_size {
+                        continue;
+                    }
+
+                    // stack: [ptr, slot]
+                    self.asm.dup(2);
+                    self.add_offset(field_offset(ty, member, self.ns));
+                    self.load(repr(&field.ty, self.ns));
+                    self.asm.dup(2);
+                    self.add_bigint(&slot);
+                    self.storage_store(&field.ty)?;
+
+                    slot += field.ty.storage_slots(self.ns);
+                }
+
+                self.asm.ops(&[Op::Pop, Op::Pop]);
+            }
+            Layout::FixedArray(len) => {
+                self.asm.swap(1);
+                self.asm.push_u64(len);
+                self.store_elements(&ty.array_elem(), false)?;
+            }
+            Layout::DynamicArray => {
+                // stack: [ptr, slot]
+                self.asm.swap(1);
+                self.null_to_empty();
+                self.asm.dup(1);
+                self.asm.op(Op::MLoad);
+                self.asm.dup(3);
+                self.asm.op(Op::SLoad);
+                // stack: [slot, ptr, length, old length]; clear any elements past the end
+                let old_length = self.temp();
+                self.store_temp(old_length);
+                self.asm.dup(1);
+                self.asm.dup(4);
+                self.asm.op(Op::SStore);
+                self.asm.swap(2);
+                self.data_slot();
+                self.asm.swap(2);
+                self.asm.swap(1);
+                self.asm.push_u64(32);
+                self.asm.op(Op::Add);
+                self.asm.swap(1);
+                self.store_elements(&ty.array_elem(), true)?;
+                self.clear_elements(&ty.array_elem(), old_length)?;
+                self.free_temp();
+            }
+            Layout::Mapping => {
+                return Err(Diagnostic::error(
+                    Loc::Codegen,
+                    "mapping cannot be stored to storage".into(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy array elements from memory to storage: [slot, data, length] -> []. If `keep` is
+    /// set, the slot and length are left on the stack: [slot, data, length] -> [slot, length]
+    fn store_elements(&mut self, elem_ty: &Type, keep: bool) -> Result<(), Diagnostic> {
+        let elem_size = elem_size(elem_ty, self.ns) as u64;
+        let elem_slots = elem_ty.storage_slots(self.ns);
+
+        self.asm.push_u64(0);
+
+        // stack: [slot, data, length, index]
+        let looping = self.asm.new_label();
+        let done = self.asm.new_label();
+        self.asm.set_label(looping);
+        self.asm.dup(2);
+        self.asm.dup(2);
+        self.asm.op(Op::Eq);
+        self.asm.jumpi(done);
+        self.asm.dup(1);
+        self.asm.push_u64(elem_size);
+        self.asm.op(Op::Mul);
+        self.asm.dup(4);
+        self.asm.op(Op::Add);
+        self.load(repr(elem_ty, self.ns));
+        self.asm.dup(2);
+        self.mul_bigint(&elem_slots);
+        self.asm.dup(6);
+        self.asm.op(Op::Add);
+        self.storage_store(elem_ty)?;
+        self.asm.push_u64(1);
+        self.asm.op(Op::Add);
+        self.asm.jump(looping);
+        self.asm.set_label(done);
+        self.asm.op(Op::Pop);
+
+        if keep {
+            self.asm.swap(1);
+            self.asm.op(Op::Pop);
+        } else {
+            self.asm.ops(&[Op::Pop, Op::Pop, Op::Pop]);
+        }
+
+        Ok(())
+    }
+
+    /// Clear the elements of a storage array from the index on the stack up to the length in
+    /// the temporary: [slot, index] -> []
+    fn clear_elements(&mut self, elem_ty: &Type, length: usize) -> Result<(), Diagnostic> {
+        let elem_slots = elem_ty.storage_slots(self.ns);
+
+        let looping = self.asm.new_label();
+        let done = self.asm.new_label();
+        self.asm.set_label(looping);
+        self.load_temp(length);
+        self.asm.dup(2);
+        self.asm.op(Op::Lt);
+        self.asm.op(Op::IsZero);
+        self.asm.jumpi(done);
+        self.asm.dup(1);
+        self.mul_bigint(&elem_slots);
+        self.asm.dup(3);
+        self.asm.op(Op::Add);
+        self.storage_clear(elem_ty)?;
+        self.asm.push_u64(1);
+        self.asm.op(Op::Add);
+        self.asm.jump(looping);
+        self.asm.set_label(done);
+        self.asm.ops(&[Op::Pop, Op::Pop]);
+
+        Ok(())
+    }
+
+    /// Store a string or bytes: [ptr, slot] -> []
+    fn storage_bytes_store(&mut self) {
+        let length = self.temp();
+        let data_slot = self.temp();
+        let source = self.temp();
+
+        self.asm.swap(1);
+        self.null_to_empty();
+        self.asm.swap(1);
+        self.asm.dup(1);
+        self.asm.op(Op::SLoad);
+        self.asm.dup(3);
+        self.asm.op(Op::MLoad);
+        // stack: [ptr, slot, old length, length]
+        self.asm.dup(1);
+        self.asm.dup(4);
+        self.asm.op(Op::SStore);
+        self.store_temp(length);
+        self.asm.swap(1);
+        self.data_slot();
+        self.store_temp(data_slot);
+        self.asm.swap(1);
+        self.asm.push_u64(32);
+        self.asm.op(Op::Add);
+        self.store_temp(source);
+        self.chunks();
+        self.load_temp(length);
+        self.chunks();
+        self.asm.push_u64(0);
+
+        // stack: [old chunks, chunks, index]
+        let looping = self.asm.new_label();
+        let done = self.asm.new_label();
+        self.asm.set_label(looping);
+        self.asm.dup(2);
+        self.asm.dup(2);
+        self.asm.op(Op::Lt);
+        self.asm.op(Op::IsZero);
+        self.asm.jumpi(done);
+        self.load_temp(source);
+        self.asm.dup(2);
+        self.asm.push_u64(5);
+        self.asm.op(Op::Shl);
+        self.asm.op(Op::Add);
+        self.asm.op(Op::MLoad);
+        self.load_temp(data_slot);
+        self.asm.dup(3);
+        self.asm.op(Op::Add);
+        self.asm.op(Op::SStore);
+        self.asm.push_u64(1);
+        self.asm.op(Op::Add);
+        self.asm.jump(looping);
+        self.asm.set_label(done);
+
+        // memory after the end of the string is not necessarily zero; clear it in the last chunk
+        let aligned = self.asm.new_label();
+        self.load_temp(length);
+        self.asm.push_u64(31);
+        self.asm.op(Op::And);
+        self.asm.dup(1);
+        self.asm.op(Op::IsZero);
+        self.asm.jumpi(aligned);
+        self.asm.push_u64(3);
+        self.asm.op(Op::Shl);
+        self.asm.push_u64(0);
+        self.asm.op(Op::Not);
+        self.asm.swap(1);
+        self.asm.op(Op::Shr);
+        self.asm.op(Op::Not);
+        self.asm.dup(3);
+        self.asm.push_u64(1);
+        self.asm.swap(1);
+        self.asm.op(Op::Sub);
+        self.load_temp(data_slot);
+        self.asm.op(Op::Add);
+        self.asm.dup(1);
+        self.asm.op(Op::SLoad);
+        self.asm.dup(3);
+        self.asm.op(Op::And);
+        self.asm.swap(1);
+        self.asm.op(Op::SStore);
+        self.asm.set_label(aligned);
+        self.asm.op(Op::Pop);
+
+        // clear the chunks of the old value past the end
+        let looping = self.asm.new_label();
+        let done = self.asm.new_label();
+        self.asm.set_label(looping);
+        self.asm.dup(3);
+        self.asm.dup(2);
+        self.asm.op(Op::Lt);
+        self.asm.op(Op::IsZero);
+        self.asm.jumpi(done);
+        self.asm.push_u64(0);
+        self.load_temp(data_slot);
+        self.asm.dup(3);
+        self.asm.op(Op::Add);
+        self.asm.op(Op::SStore);
+        self.asm.push_u64(1);
+        self.asm.op(Op::Add);
+        self.asm.jump(looping);
+        self.asm.set_label(done);
+        self.asm.ops(&[Op::Pop, Op::Pop, Op::Pop]);
+
+        self.free_temp();
+        self.free_temp();
+        self.free_temp();
+    }
+
+    /// Clear a value in storage: [slot] -> []
+    pub(super) fn storage_clear(&mut self, ty: &Type) -> Result<(), Diagnostic> {
+        match layout(ty, self.ns) {
+            Layout::Word | Layout::ExternalFunction => {
+                self.asm.push_u64(0);
+                self.asm.swap(1);
+                self.asm.op(Op::SStore);
+            }
+            Layout::Alias(ty) => self.storage_clear(ty)?,
+            Layout::Bytes => {
+                self.asm.dup(1);
+                self.asm.op(Op::SLoad);
+                self.asm.push_u64(0);
+                self.asm.dup(3);
+                self.asm.op(Op::SStore);
+                self.chunks();
+                self.asm.swap(1);
+                self.data_slot();
+                // stack: [chunks, data slot]
+                let looping = self.asm.new_label();
+                let done = self.asm.new_label();
+                self.asm.set_label(looping);
+                self.asm.dup(2);
+                self.asm.op(Op::IsZero);
+                self.asm.jumpi(done);
+                self.asm.push_u64(0);
+                self.asm.dup(2);
+                self.asm.op(Op::SStore);
+                self.asm.push_u64(1);
+                self.asm.op(Op::Add);
+                self.asm.swap(1);
+                self.asm.push_u64(1);
+                self.asm.swap(1);
+                self.asm.op(Op::Sub);
+                self.asm.swap(1);
+                self.asm.jump(looping);
+                self.asm.set_label(done);
+                self.asm.ops(&[Op::Pop, Op::Pop]);
+            }
+            Layout::Struct => {
+                let Type::Struct(str_ty) = ty else {
+                    unreachable!()
+                };
+
+                let mut slot = BigInt::from(0);
+
+                for field in &str_ty.definition(self.ns).fields {
+                    if field.infinite_size {
+                        continue;
+                    }
+
+                    self.asm.dup(1);
+                    self.add_bigint(&slot);
+                    self.storage_clear(&field.ty)?;
+
+                    slot += field.ty.storage_slots(self.ns);
+                }
+
+                self.asm.op(Op::Pop);
+            }
+            Layout::FixedArray(len) => {
+                let length = self.temp();
+                self.asm.push_u64(len);
+                self.store_temp(length);
+                self.asm.push_u64(0);
+                self.clear_elements(&ty.array_elem(), length)?;
+                self.free_temp();
+            }
+            Layout::DynamicArray => {
+                let length = self.temp();
+                self.asm.dup(1);
+                self.asm.op(Op::SLoad);
+                self.store_temp(length);
+                self.asm.push_u64(0);
+                self.asm.dup(2);
+                self.asm.op(Op::SStore);
+                self.data_slot();
+                self.asm.push_u64(0);
+                self.clear_elements(&ty.array_elem(), length)?;
+                self.free_temp();
+            }
+            // mappings cannot be cleared, as the keys are not known
+            Layout::Mapping => self.asm.op(Op::Pop),
+        }
+
+        Ok(())
+    }
+
+    /// Read a byte from a storage bytes: [index, slot] -> [byte]
+    pub(super) fn storage_bytes_get(&mut self) {
+        self.storage_bytes_bounds_check();
+        self.data_slot();
+        self.asm.dup(2);
+        self.asm.push_u64(5);
+        self.asm.op(Op::Shr);
+        self.asm.op(Op::Add);
+        self.asm.op(Op::SLoad);
+        self.asm.swap(1);
+        self.asm.push_u64(31);
+        self.asm.op(Op::And);
+        self.asm.op(Op::Byte);
+    }
+
+    /// Set a byte in a storage bytes: [value, index, slot] -> []
+    pub(super) fn storage_bytes_set(&mut self) {
+        self.storage_bytes_bounds_check();
+        self.data_slot();
+        // stack: [value, index, data slot]
+        self.asm.dup(2);
+        self.asm.push_u64(5);
+        self.asm.op(Op::Shr);
+        self.asm.op(Op::Add);
+        self.asm.swap(1);
+        self.byte_shift();
+        // stack: [value, chunk slot, shift]
+        self.asm.swap(2);
+        self.asm.dup(3);
+        self.asm.op(Op::Shl);
+        self.asm.swap(2);
+        self.asm.push_u64(0xff);
+        self.asm.swap(1);
+        self.asm.op(Op::Shl);
+        self.asm.op(Op::Not);
+        self.asm.dup(2);
+        self.asm.op(Op::SLoad);
+        self.asm.op(Op::And);
+        self.asm.dup(3);
+        self.asm.op(Op::Or);
+        self.asm.swap(1);
+        self.asm.op(Op::SStore);
+        self.asm.op(Op::Pop);
+    }
+
+    /// Append a byte to a storage bytes: [value, slot] -> []
+    pub(super) fn storage_bytes_push(&mut self) {
+        self.asm.dup(1);
+        self.asm.op(Op::SLoad);
+        self.asm.dup(1);
+        self.asm.push_u64(1);
+        self.asm.op(Op::Add);
+        self.asm.dup(3);
+        self.asm.op(Op::SStore);
+        // stack: [value, slot, length]
+        self.asm.swap(1);
+        self.data_slot();
+        self.asm.dup(2);
+        self.asm.push_u64(5);
+        self.asm.op(Op::Shr);
+        self.asm.op(Op::Add);
+        self.asm.swap(1);
+        self.byte_shift();
+        // stack: [value, chunk slot, shift]; the byte past the end is always zero
+        self.asm.swap(2);
+        self.asm.dup(3);
+        self.asm.op(Op::Shl);
+        self.asm.swap(2);
+        self.asm.op(Op::Pop);
+        self.asm.dup(1);
+        self.asm.op(Op::SLoad);
+        self.asm.dup(3);
+        self.asm.op(Op::Or);
+        self.asm.swap(1);
+        self.asm.op(Op::SStore);
+        self.asm.op(Op::Pop);
+    }
+
+    /// Remove the last byte of a storage bytes: [slot] -> [byte]
+    pub(super) fn storage_bytes_pop(&mut self) {
+        self.asm.dup(1);
+        self.asm.op(Op::SLoad);
+        self.asm.dup(1);
+        self.asm.op(Op::IsZero);
+        self.jump_to_panic(PanicCode::EmptyArrayPop);
+        self.asm.push_u64(1);
+        self.asm.swap(1);
+        self.asm.op(Op::Sub);
+        self.asm.dup(1);
+        self.asm.dup(3);
+        self.asm.op(Op::SStore);
+        // stack: [slot, index]
+        self.asm.swap(1);
+        self.data_slot();
+        self.asm.dup(2);
+        self.asm.push_u64(5);
+        self.asm.op(Op::Shr);
+        self.asm.op(Op::Add);
+        self.asm.swap(1);
+        self.asm.push_u64(31);
+        self.asm.op(Op::And);
+        // stack: [chunk slot, byte index]
+        self.asm.dup(2);
+        self.asm.op(Op::SLoad);
+        self.asm.dup(1);
+        self.asm.dup(3);
+        self.asm.op(Op::Byte);
+        // stack: [chunk slot, byte index, chunk, byte]
+        self.asm.swap(2);
+        self.asm.push_u64(31);
+        self.asm.op(Op::Sub);
+        self.asm.push_u64(3);
+        self.asm.op(Op::Shl);
+        self.asm.push_u64(0xff);
+        self.asm.swap(1);
+        self.asm.op(Op::Shl);
+        self.asm.op(Op::Not);
+        self.asm.op(Op::And);
+        self.asm.dup(3);
+        self.asm.op(Op::SStore);
+        self.asm.swap(1);
+        self.asm.op(Op::Pop);
+    }
+
+    /// Panic if the index is out of bounds: [index, slot] -> [index, slot]
+    fn storage_bytes_bounds_check(&mut self) {
+        self.asm.dup(1);
+        self.asm.op(Op::SLoad);
+        self.asm.dup(3);
+        self.asm.swap(1);
+        self.asm.op(Op::Gt);
+        self.asm.op(Op::IsZero);
+        self.jump_to_panic(PanicCode::ArrayIndexOob);
+    }
+
+    /// The bit position of a byte within its 32 byte chunk: [index] -> [shift]
+    fn byte_shift(&mut self) {
+        self.asm.push_u64(31);
+        self.asm.op(Op::And);
+        self.asm.push_u64(31);
+        self.asm.op(Op::Sub);
+        self.asm.push_u64(3);
+        self.asm.op(Op::Shl);
+    }
+
+    /// The slot where the contents of a dynamic array or bytes start, which is the same as
+    /// codegen uses for arrays: [slot] -> [keccak256(slot)]
+    fn data_slot(&mut self) {
+        self.asm.push_u64(0);
+        self.store(Repr::Le {
+            bytes: 32,
+            signed: false,
+        });
+        self.asm.push_u64(32);
+        self.asm.push_u64(0);
+        self.asm.op(Op::Keccak256);
+        self.bswap();
+    }
+
+    /// The number of 32 byte chunks for a length: [length] -> [chunks]
+    fn chunks(&mut self) {
+        self.asm.push_u64(31);
+        self.asm.op(Op::Add);
+        self.asm.push_u64(5);
+        self.asm.op(Op::Shr);
+    }
+
+    /// Replace a null pointer with a pointer to a zero word, which is an empty array
+    fn null_to_empty(&mut self) {
+        self.asm.dup(1);
+        self.asm.op(Op::IsZero);
+        self.asm.push_u64(ZERO);
+        self.asm.op(Op::Mul);
+        self.asm.op(Op::Add);
+    }
+
+    fn add_offset(&mut self, offset: usize) {
+        if offset > 0 {
+            self.asm.push_u64(offset as u64);
+            self.asm.op(Op::Add);
+        }
+    }
+
+    fn add_bigint(&mut self, value: &BigInt) {
+        if value.sign() != num_bigint::Sign::NoSign {
+            self.asm.push_bigint(value);
+            self.asm.op(Op::Add);
+        }
+    }
+
+    fn mul_bigint(&mut self, value: &BigInt) {
+        if *value != BigInt::from(1) {
+            self.asm.push_bigint(value);
+            self.asm.op(Op::Mul);
+        }
+    }
+}
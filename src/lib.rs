@@ -2,8 +2,10 @@
 
 pub mod abi;
 pub mod codegen;
+pub mod compute_units;
 #[cfg(feature = "llvm")]
 pub mod emit;
+pub mod evm;
 pub mod file_resolver;
 #[cfg(feature = "llvm")]
 mod linker;
@@ -84,6 +86,8 @@ impl Target {
         match self {
             // Solana uses ELF dynamic shared object (BPF)
             Target::Solana => "so",
+            // EVM bytecode is written hex encoded, like solc does
+            Target::EVM => "bin",
             // Everything else generates webassembly
             _ => "wasm",
         }
@@ -144,7 +148,13 @@ pub fn compile(
         let contract = &ns.contracts[contract_no];
 
         if contract.instantiable {
-            let code = contract.emit(&ns, opts);
+            let code = match contract.emit(&ns, opts) {
+                Ok(code) => code,
+                Err(diagnostic) => {
+                    ns.diagnostics.push(diagnostic);
+                    return (Vec::new(), ns);
+                }
+            };
 
             let (abistr, _) = abi::generate_abi(contract_no, &ns, &code, false, &authors, version);
 
@@ -164,8 +174,25 @@ pub fn parse_and_resolve(
     filename: &OsStr,
     resolver: &mut FileResolver,
     target: Target,
+) -> sema::ast::Namespace {
+    parse_and_resolve_with_solidity_version(
+        filename,
+        resolver,
+        target,
+        sema::solidity_version::SOLIDITY_VERSION,
+    )
+}
+
+/// Like [`parse_and_resolve`], but `pragma solidity` is checked against the given Solidity
+/// language level rather than the default one.
+pub fn parse_and_resolve_with_solidity_version(
+    filename: &OsStr,
+    resolver: &mut FileResolver,
+    target: Target,
+    solidity_version: semver::Version,
 ) -> sema::ast::Namespace {
     let mut ns = sema::ast::Namespace::new(target);
+    ns.solidity_version = solidity_version;
 
     match resolver.resolve_file(None, filename) {
         Err(message) => {
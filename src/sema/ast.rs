@@ -3,6 +3,7 @@
 use super::symtable::Symtable;
 use crate::abi::anchor::function_discriminator;
 use crate::codegen::cfg::{ControlFlowGraph, Instr};
+use crate::codegen::passes::Pass;
 use crate::diagnostics::Diagnostics;
 use crate::sema::ast::ExternalCallAccounts::{AbsentArgument, NoAccount};
 use crate::sema::yul::ast::{InlineAssembly, YulFunction};
@@ -338,6 +339,8 @@ pub struct Function {
     pub is_override: Option<(pt::Loc, Vec<usize>)>,
     /// The selector (known as discriminator on Solana/Anchor)
     pub selector: Option<(pt::Loc, Vec<u8>)>,
+    /// The relative call frequency given with the @hot annotation, used by the dispatcher
+    pub hot: Option<(pt::Loc, u32)>,
     /// Was the function declared with a body
     pub has_body: bool,
     /// The resolved body (if any)
@@ -345,6 +348,10 @@ pub struct Function {
     pub symtable: Symtable,
     /// What events are emitted by the body of this function
     pub emits_events: Vec<usize>,
+    /// What custom errors the body of this function may revert with
+    pub custom_errors: Vec<usize>,
+    /// Does inline assembly in the body of this function access memory
+    pub yul_memory: bool,
     /// For overloaded functions this is the mangled (unique) name.
     pub mangled_name: String,
     /// Solana constructors may have seeds specified using @seed tags
@@ -448,6 +455,7 @@ impl Function {
             bases: BTreeMap::new(),
             modifiers: Vec::new(),
             selector: None,
+            hot: None,
             is_virtual: false,
             is_accessor: false,
             has_body: false,
@@ -455,6 +463,8 @@ impl Function {
             body: Vec::new(),
             symtable: Symtable::new(),
             emits_events: Vec::new(),
+            custom_errors: Vec::new(),
+            yul_memory: false,
             mangled_name,
             annotations: ConstructorAnnotations::default(),
             mangled_name_contracts: HashSet::new(),
@@ -662,6 +672,8 @@ pub struct File {
 #[derive(Debug)]
 pub struct Namespace {
     pub target: Target,
+    /// The Solidity language level which `pragma solidity` is checked against
+    pub solidity_version: semver::Version,
     pub files: Vec<File>,
     pub enums: Vec<EnumDecl>,
     pub structs: Vec<StructDecl>,
@@ -699,6 +711,9 @@ pub struct Namespace {
 #[derive(Debug)]
 pub struct Layout {
     pub slot: BigInt,
+    /// On Polkadot, small values may share a storage slot. This is the byte offset of the value
+    /// within the slot, or `None` if the variable does not share its slot.
+    pub offset: Option<usize>,
     pub contract_no: usize,
     pub var_no: usize,
     pub ty: Type,
@@ -754,9 +769,15 @@ pub struct Contract {
     pub creates: Vec<usize>,
     /// List of events this contract may emit
     pub emits_events: Vec<usize>,
+    /// List of custom errors this contract may revert with, in order of declaration
+    pub custom_errors: Vec<usize>,
+    /// Does inline assembly in this contract access memory, so memory must be reserved for it
+    pub yul_memory: bool,
     pub initializer: Option<usize>,
     pub default_constructor: Option<(Function, usize)>,
     pub cfg: Vec<ControlFlowGraph>,
+    /// Copies of the CFGs after the passes selected with `--print-after`
+    pub pass_dumps: Vec<(Pass, ControlFlowGraph)>,
     /// Compiled program. Only available after emit.
     pub code: OnceCell<Vec<u8>>,
     /// Can the contract be instantiated, i.e. not abstract, no errors, etc.
@@ -1089,6 +1110,14 @@ pub enum Expression {
         array: Box<Expression>,
         index: Box<Expression>,
     },
+    /// A slice of a memory or calldata array, e.g. `data[4:]`. The bounds are optional.
+    ArraySlice {
+        loc: pt::Loc,
+        ty: Type,
+        array: Box<Expression>,
+        start: Option<Box<Expression>>,
+        end: Option<Box<Expression>>,
+    },
     NamedMember {
         loc: pt::Loc,
         ty: Type,
@@ -1200,6 +1229,12 @@ pub enum Expression {
         ty: Type,
         event_no: usize,
     },
+    /// A custom error with its arguments, only used as the reason of `require()`
+    CustomError {
+        loc: pt::Loc,
+        error_no: usize,
+        args: Vec<Expression>,
+    },
 }
 
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
@@ -1374,6 +1409,17 @@ impl Recurse for Expression {
                     left.recurse(cx, f);
                     right.recurse(cx, f);
                 }
+                Expression::ArraySlice {
+                    array, start, end, ..
+                } => {
+                    array.recurse(cx, f);
+                    if let Some(start) = start {
+                        start.recurse(cx, f);
+                    }
+                    if let Some(end) = end {
+                        end.recurse(cx, f);
+                    }
+                }
 
                 Expression::AllocDynamicBytes { length, .. } => length.recurse(cx, f),
                 Expression::StorageArrayLength { array, .. } => array.recurse(cx, f),
@@ -1427,6 +1473,7 @@ impl Recurse for Expression {
                 }
                 Expression::UserDefinedOperator { args: exprs, .. }
                 | Expression::Builtin { args: exprs, .. }
+                | Expression::CustomError { args: exprs, .. }
                 | Expression::List { list: exprs, .. } => {
                     for e in exprs {
                         e.recurse(cx, f);
@@ -1500,6 +1547,7 @@ impl CodeLocation for Expression {
             | Expression::Negate { loc, .. }
             | Expression::ConditionalOperator { loc, .. }
             | Expression::Subscript { loc, .. }
+            | Expression::ArraySlice { loc, .. }
             | Expression::StructMember { loc, .. }
             | Expression::Or { loc, .. }
             | Expression::AllocDynamicBytes { loc, .. }
@@ -1523,7 +1571,8 @@ impl CodeLocation for Expression {
             | Expression::And { loc, .. }
             | Expression::NamedMember { loc, .. }
             | Expression::UserDefinedOperator { loc, .. }
-            | Expression::EventSelector { loc, .. } => *loc,
+            | Expression::EventSelector { loc, .. }
+            | Expression::CustomError { loc, .. } => *loc,
         }
     }
 }
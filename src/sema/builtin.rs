@@ -7,6 +7,7 @@ use super::ast::{
 use super::diagnostics::Diagnostics;
 use super::eval::eval_const_number;
 use super::expression::{ExprContext, ResolveTo};
+use super::statements::{custom_error_named_args, custom_error_pos_args};
 use super::symtable::Symtable;
 use crate::sema::ast::{RetrieveType, Tag, UserTypeDecl};
 use crate::sema::expression::resolve_expression::expression;
@@ -916,6 +917,46 @@ pub fn builtin_namespace(namespace: &str) -> bool {
         .any(|p| p.namespace == Some(namespace))
 }
 
+/// List the builtin functions and variables which are available for the target, either
+/// global ones (if `namespace` is `None`) or those accessed through a namespace like `block`
+pub fn namespace_members(namespace: Option<&str>, ns: &Namespace) -> Vec<&'static Prototype> {
+    BUILTIN_FUNCTIONS
+        .iter()
+        .chain(BUILTIN_VARIABLE.iter())
+        .filter(|p| {
+            p.namespace == namespace
+                && p.method.is_empty()
+                && (p.target.is_empty() || p.target.contains(&ns.target))
+        })
+        .collect()
+}
+
+/// List the names of all the builtin namespaces, like `abi` or `msg`
+pub fn namespaces() -> Vec<&'static str> {
+    let mut namespaces: Vec<&'static str> = BUILTIN_FUNCTIONS
+        .iter()
+        .chain(BUILTIN_VARIABLE.iter())
+        .filter_map(|p| p.namespace)
+        .collect();
+
+    namespaces.sort_unstable();
+    namespaces.dedup();
+
+    namespaces
+}
+
+/// List the builtin methods which can be called on a value of the given type
+pub fn type_methods(ty: &Type, ns: &Namespace) -> Vec<&'static Prototype> {
+    let deref_ty = ty.deref_memory();
+
+    BUILTIN_METHODS
+        .iter()
+        .filter(|p| {
+            p.method.contains(deref_ty) && (p.target.is_empty() || p.target.contains(&ns.target))
+        })
+        .collect()
+}
+
 /// Is name reserved for builtins
 pub fn is_reserved(fname: &str) -> bool {
     if fname == "type" || fname == "super" || fname == "this" {
@@ -937,6 +978,61 @@ pub fn is_reserved(fname: &str) -> bool {
     })
 }
 
+/// If the expression is a custom error with its arguments, e.g. `NotEnoughBalance(addr)`,
+/// resolve it. Returns None if the expression does not name a custom error.
+fn custom_error(
+    expr: &pt::Expression,
+    context: &ExprContext,
+    ns: &mut Namespace,
+    symtable: &mut Symtable,
+    diagnostics: &mut Diagnostics,
+) -> Option<Expression> {
+    let (loc, ty) = match expr.remove_parenthesis() {
+        pt::Expression::FunctionCall(loc, ty, _)
+        | pt::Expression::NamedFunctionCall(loc, ty, _) => (loc, ty),
+        _ => return None,
+    };
+
+    let path = ns.expr_to_identifier_path(ty)?;
+
+    let error_no = ns
+        .resolve_error(
+            context.file_no,
+            context.contract_no,
+            &path,
+            &mut Diagnostics::default(),
+        )
+        .ok()?;
+
+    let args = match expr.remove_parenthesis() {
+        pt::Expression::FunctionCall(_, _, args) => custom_error_pos_args(
+            &path.loc,
+            error_no,
+            args,
+            context,
+            symtable,
+            diagnostics,
+            ns,
+        ),
+        pt::Expression::NamedFunctionCall(_, _, args) => custom_error_named_args(
+            &path.loc,
+            error_no,
+            args,
+            context,
+            symtable,
+            diagnostics,
+            ns,
+        ),
+        _ => unreachable!(),
+    };
+
+    Some(Expression::CustomError {
+        loc: *loc,
+        error_no,
+        args,
+    })
+}
+
 /// Resolve a builtin call
 pub(super) fn resolve_call(
     loc: &pt::Loc,
@@ -948,6 +1044,28 @@ pub(super) fn resolve_call(
     symtable: &mut Symtable,
     diagnostics: &mut Diagnostics,
 ) -> Result<Expression, ()> {
+    // require(cond, CustomError(...)) reverts with a custom error rather than a reason string
+    if namespace.is_none() && id == "require" && args.len() == 2 {
+        if let Some(error) = custom_error(&args[1], context, ns, symtable, diagnostics) {
+            let cond = expression(
+                &args[0],
+                context,
+                ns,
+                symtable,
+                diagnostics,
+                ResolveTo::Type(&Type::Bool),
+            )?;
+            let cond = cond.cast(&args[0].loc(), &Type::Bool, true, ns, diagnostics)?;
+
+            return Ok(Expression::Builtin {
+                loc: *loc,
+                tys: vec![Type::Void],
+                kind: Builtin::Require,
+                args: vec![cond, error],
+            });
+        }
+    }
+
     let funcs = BUILTIN_FUNCTIONS
         .iter()
         .filter(|p| p.name == id && p.namespace == namespace && p.method.is_empty())
@@ -1138,8 +1256,14 @@ pub(super) fn resolve_namespace_call(
             symtable,
             diagnostics,
             ResolveTo::Type(&Type::DynamicBytes),
-        )?
-        .cast(&args[0].loc(), &Type::DynamicBytes, true, ns, diagnostics)?;
+        )?;
+
+        // A slice of bytes can be decoded without copying it
+        let data = if data.ty() == Type::Slice(Box::new(Type::Bytes(1))) {
+            data
+        } else {
+            data.cast(&args[0].loc(), &Type::DynamicBytes, true, ns, diagnostics)?
+        };
 
         let mut tys = Vec::new();
         let mut broken = false;
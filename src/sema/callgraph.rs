@@ -0,0 +1,478 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The call graph of a contract. Every function of the contract, including the inherited ones,
+//! is a node. The functions they call internally are followed, so library and free functions are
+//! included too. External calls, contract creations, modifiers and yul function calls are edges
+//! to nodes of their own kind. The functions which can be called externally are the entry points.
+
+use super::ast::{DestructureField, Expression, Namespace, Statement};
+use super::yul::ast::{YulExpression, YulStatement};
+use super::Recurse;
+use petgraph::dot::Dot;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
+use solang_parser::pt::{self, FunctionTy};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Function,
+    Modifier,
+    /// A function of another contract which is called externally
+    ExternalFunction,
+    /// A contract which is created
+    Contract,
+    YulFunction,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    Internal,
+    External,
+    Create,
+    Modifier,
+    Yul,
+}
+
+impl fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdgeKind::Internal => write!(f, "call"),
+            EdgeKind::External => write!(f, "external call"),
+            EdgeKind::Create => write!(f, "new"),
+            EdgeKind::Modifier => write!(f, "modifier"),
+            EdgeKind::Yul => write!(f, "yul call"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CallGraphNode {
+    pub kind: NodeKind,
+    pub name: String,
+    /// Can this function be called externally
+    #[serde(rename = "entryPoint")]
+    pub entry_point: bool,
+}
+
+impl fmt::Display for CallGraphNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            NodeKind::Contract => write!(f, "new {}", self.name),
+            NodeKind::YulFunction => write!(f, "assembly {}", self.name),
+            _ => write!(f, "{}", self.name),
+        }
+    }
+}
+
+pub struct CallGraph {
+    pub contract: String,
+    pub graph: DiGraph<CallGraphNode, EdgeKind>,
+}
+
+#[derive(Serialize)]
+struct JsonEdge {
+    from: usize,
+    to: usize,
+    kind: EdgeKind,
+}
+
+#[derive(Serialize)]
+struct JsonGraph<'a> {
+    contract: &'a str,
+    nodes: Vec<&'a CallGraphNode>,
+    edges: Vec<JsonEdge>,
+}
+
+impl CallGraph {
+    /// Graphviz dot file of the call graph; the entry points are filled
+    pub fn dot(&self) -> String {
+        let edge_attributes = |_, _| String::new();
+        let node_attributes = |_, (_, node): (NodeIndex, &CallGraphNode)| {
+            let shape = match node.kind {
+                NodeKind::Function | NodeKind::Modifier => "box",
+                NodeKind::ExternalFunction => "cds",
+                NodeKind::Contract => "component",
+                NodeKind::YulFunction => "ellipse",
+            };
+
+            if node.entry_point {
+                format!("shape = {shape} style = filled fillcolor = lightblue")
+            } else {
+                format!("shape = {shape}")
+            }
+        };
+
+        format!(
+            "{}",
+            Dot::with_attr_getters(&self.graph, &[], &edge_attributes, &node_attributes)
+        )
+    }
+
+    /// The call graph as json, with the nodes identified by their index
+    pub fn json(&self) -> String {
+        let graph = JsonGraph {
+            contract: &self.contract,
+            nodes: self.graph.node_weights().collect(),
+            edges: self
+                .graph
+                .edge_references()
+                .map(|edge| JsonEdge {
+                    from: edge.source().index(),
+                    to: edge.target().index(),
+                    kind: *edge.weight(),
+                })
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&graph).unwrap()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Target {
+    Function(usize),
+    ExternalFunction(usize),
+    Contract(usize),
+    YulFunction(usize),
+}
+
+struct Builder<'a> {
+    contract_no: usize,
+    ns: &'a Namespace,
+    graph: DiGraph<CallGraphNode, EdgeKind>,
+    nodes: HashMap<Target, NodeIndex>,
+    edges: HashSet<(NodeIndex, NodeIndex, EdgeKind)>,
+    /// Functions and yul functions whose bodies have not been visited yet
+    todo: VecDeque<Target>,
+}
+
+/// Build the call graph for the given contract
+pub fn call_graph(contract_no: usize, ns: &Namespace) -> CallGraph {
+    let mut builder = Builder {
+        contract_no,
+        ns,
+        graph: DiGraph::new(),
+        nodes: HashMap::new(),
+        edges: HashSet::new(),
+        todo: VecDeque::new(),
+    };
+
+    for function_no in ns.contracts[contract_no].all_functions.keys() {
+        builder.node(Target::Function(*function_no));
+    }
+
+    while let Some(target) = builder.todo.pop_front() {
+        let from = builder.nodes[&target];
+
+        match target {
+            Target::Function(function_no) => {
+                let func = &ns.functions[function_no];
+
+                for modifier in &func.modifiers {
+                    if let Expression::InternalFunctionCall { function, args, .. } = modifier {
+                        builder.expression(from, function, EdgeKind::Modifier);
+
+                        for arg in args {
+                            builder.expression(from, arg, EdgeKind::Internal);
+                        }
+                    }
+                }
+
+                for (_, constructor_no, args) in func.bases.values() {
+                    builder.edge(from, Target::Function(*constructor_no), EdgeKind::Internal);
+
+                    for arg in args {
+                        builder.expression(from, arg, EdgeKind::Internal);
+                    }
+                }
+
+                builder.statements(from, &func.body);
+            }
+            Target::YulFunction(function_no) => {
+                builder.yul_statements(from, &ns.yul_functions[function_no].body.statements);
+            }
+            _ => (),
+        }
+    }
+
+    CallGraph {
+        contract: ns.contracts[contract_no].id.name.clone(),
+        graph: builder.graph,
+    }
+}
+
+impl Builder<'_> {
+    fn node(&mut self, target: Target) -> NodeIndex {
+        if let Some(node) = self.nodes.get(&target) {
+            return *node;
+        }
+
+        let ns = self.ns;
+
+        let node = match target {
+            Target::Function(function_no) | Target::ExternalFunction(function_no) => {
+                let func = &ns.functions[function_no];
+
+                let name = match func.ty {
+                    FunctionTy::Constructor => "constructor".to_owned(),
+                    FunctionTy::Fallback => "fallback".to_owned(),
+                    FunctionTy::Receive => "receive".to_owned(),
+                    _ => func.signature.clone(),
+                };
+
+                let name = match func.contract_no {
+                    Some(contract_no) => format!("{}.{}", ns.contracts[contract_no].id.name, name),
+                    None => name,
+                };
+
+                let kind = match (target, func.ty) {
+                    (Target::ExternalFunction(_), _) => NodeKind::ExternalFunction,
+                    (_, FunctionTy::Modifier) => NodeKind::Modifier,
+                    _ => NodeKind::Function,
+                };
+
+                CallGraphNode {
+                    kind,
+                    name,
+                    entry_point: matches!(target, Target::Function(_))
+                        && ns.function_externally_callable(self.contract_no, Some(function_no)),
+                }
+            }
+            Target::Contract(contract_no) => CallGraphNode {
+                kind: NodeKind::Contract,
+                name: ns.contracts[contract_no].id.name.clone(),
+                entry_point: false,
+            },
+            Target::YulFunction(function_no) => CallGraphNode {
+                kind: NodeKind::YulFunction,
+                name: ns.yul_functions[function_no].name.clone(),
+                entry_point: false,
+            },
+        };
+
+        let node = self.graph.add_node(node);
+
+        self.nodes.insert(target, node);
+
+        if matches!(target, Target::Function(_) | Target::YulFunction(_)) {
+            self.todo.push_back(target);
+        }
+
+        node
+    }
+
+    fn edge(&mut self, from: NodeIndex, to: Target, kind: EdgeKind) {
+        if let Target::Function(function_no) = to {
+            // builtin functions have no body
+            if self.ns.functions[function_no].loc == pt::Loc::Builtin {
+                return;
+            }
+        }
+
+        let to = self.node(to);
+
+        if self.edges.insert((from, to, kind)) {
+            self.graph.add_edge(from, to, kind);
+        }
+    }
+
+    fn statements(&mut self, from: NodeIndex, stmts: &[Statement]) {
+        for stmt in stmts {
+            match stmt {
+                Statement::Block { statements, .. } => self.statements(from, statements),
+                Statement::VariableDecl(_, _, _, Some(expr)) => {
+                    self.expression(from, expr, EdgeKind::Internal);
+                }
+                Statement::Expression(_, _, expr) | Statement::Return(_, Some(expr)) => {
+                    self.expression(from, expr, EdgeKind::Internal);
+                }
+                Statement::If(_, _, expr, then_, else_) => {
+                    self.expression(from, expr, EdgeKind::Internal);
+                    self.statements(from, then_);
+                    self.statements(from, else_);
+                }
+                Statement::DoWhile(_, _, body, expr) | Statement::While(_, _, expr, body) => {
+                    self.expression(from, expr, EdgeKind::Internal);
+                    self.statements(from, body);
+                }
+                Statement::For {
+                    init,
+                    cond,
+                    next,
+                    body,
+                    ..
+                } => {
+                    self.statements(from, init);
+                    if let Some(cond) = cond {
+                        self.expression(from, cond, EdgeKind::Internal);
+                    }
+                    if let Some(next) = next {
+                        self.expression(from, next, EdgeKind::Internal);
+                    }
+                    self.statements(from, body);
+                }
+                Statement::Destructure(_, fields, expr) => {
+                    self.expression(from, expr, EdgeKind::Internal);
+
+                    for field in fields {
+                        if let DestructureField::Expression(expr) = field {
+                            self.expression(from, expr, EdgeKind::Internal);
+                        }
+                    }
+                }
+                Statement::TryCatch(_, _, try_catch) => {
+                    self.expression(from, &try_catch.expr, EdgeKind::Internal);
+                    self.statements(from, &try_catch.ok_stmt);
+                    for clause in &try_catch.errors {
+                        self.statements(from, &clause.stmt);
+                    }
+                    if let Some(clause) = try_catch.catch_all.as_ref() {
+                        self.statements(from, &clause.stmt);
+                    }
+                }
+                Statement::Emit { args, .. } | Statement::Revert { args, .. } => {
+                    for arg in args {
+                        self.expression(from, arg, EdgeKind::Internal);
+                    }
+                }
+                Statement::Assembly(inline_assembly, _) => {
+                    self.yul_statements(from, &inline_assembly.body);
+                }
+                Statement::VariableDecl(_, _, _, None)
+                | Statement::Return(_, None)
+                | Statement::Delete(..)
+                | Statement::Break(_)
+                | Statement::Continue(_)
+                | Statement::Underscore(_) => (),
+            }
+        }
+    }
+
+    /// Add an edge for every function referenced in the expression. Internal functions are
+    /// added with the given edge kind, so that modifiers can be distinguished from calls.
+    fn expression(&mut self, from: NodeIndex, expr: &Expression, kind: EdgeKind) {
+        let mut targets = Vec::new();
+
+        expr.recurse(&mut targets, |expr, targets: &mut Vec<(Target, bool)>| {
+            match expr {
+                Expression::InternalFunction {
+                    function_no,
+                    signature,
+                    ..
+                } => targets.push((Target::Function(*function_no), signature.is_some())),
+                Expression::ExternalFunction { function_no, .. } => {
+                    targets.push((Target::ExternalFunction(*function_no), false))
+                }
+                Expression::Constructor { contract_no, .. } => {
+                    targets.push((Target::Contract(*contract_no), false))
+                }
+                _ => (),
+            }
+
+            true
+        });
+
+        for (target, is_virtual) in targets {
+            let (target, kind) = match target {
+                Target::Function(function_no) if is_virtual => {
+                    // virtual functions are resolved for the contract
+                    let signature = &self.ns.functions[function_no].signature;
+
+                    let function_no = self.ns.contracts[self.contract_no]
+                        .virtual_functions
+                        .get(signature)
+                        .and_then(|functions| functions.last().copied())
+                        .unwrap_or(function_no);
+
+                    (Target::Function(function_no), kind)
+                }
+                Target::Function(_) => (target, kind),
+                Target::ExternalFunction(_) => (target, EdgeKind::External),
+                Target::Contract(_) => (target, EdgeKind::Create),
+                Target::YulFunction(_) => (target, EdgeKind::Yul),
+            };
+
+            self.edge(from, target, kind);
+        }
+    }
+
+    fn yul_statements(&mut self, from: NodeIndex, stmts: &[YulStatement]) {
+        for stmt in stmts {
+            match stmt {
+                YulStatement::FunctionCall(_, _, function_no, args) => {
+                    self.edge(from, Target::YulFunction(*function_no), EdgeKind::Yul);
+
+                    for arg in args {
+                        self.yul_expression(from, arg);
+                    }
+                }
+                YulStatement::BuiltInCall(_, _, _, args) => {
+                    for arg in args {
+                        self.yul_expression(from, arg);
+                    }
+                }
+                YulStatement::Block(block) => self.yul_statements(from, &block.statements),
+                YulStatement::Assignment(_, _, _, value)
+                | YulStatement::VariableDeclaration(_, _, _, Some(value)) => {
+                    self.yul_expression(from, value);
+                }
+                YulStatement::IfBlock(_, _, condition, block) => {
+                    self.yul_expression(from, condition);
+                    self.yul_statements(from, &block.statements);
+                }
+                YulStatement::Switch {
+                    condition,
+                    cases,
+                    default,
+                    ..
+                } => {
+                    self.yul_expression(from, condition);
+                    for item in cases {
+                        self.yul_expression(from, &item.condition);
+                        self.yul_statements(from, &item.block.statements);
+                    }
+                    if let Some(block) = default {
+                        self.yul_statements(from, &block.statements);
+                    }
+                }
+                YulStatement::For {
+                    init_block,
+                    condition,
+                    post_block,
+                    execution_block,
+                    ..
+                } => {
+                    self.yul_statements(from, &init_block.statements);
+                    self.yul_expression(from, condition);
+                    self.yul_statements(from, &post_block.statements);
+                    self.yul_statements(from, &execution_block.statements);
+                }
+                YulStatement::VariableDeclaration(_, _, _, None)
+                | YulStatement::Leave(..)
+                | YulStatement::Break(..)
+                | YulStatement::Continue(..) => (),
+            }
+        }
+    }
+
+    fn yul_expression(&mut self, from: NodeIndex, expr: &YulExpression) {
+        let mut functions = Vec::new();
+
+        expr.recurse(&mut functions, |expr, functions: &mut Vec<usize>| {
+            if let YulExpression::FunctionCall(_, function_no, ..) = expr {
+                functions.push(*function_no);
+            }
+
+            true
+        });
+
+        for function_no in functions {
+            self.edge(from, Target::YulFunction(function_no), EdgeKind::Yul);
+        }
+    }
+}
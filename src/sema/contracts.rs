@@ -48,9 +48,12 @@ impl ast::Contract {
             variables: Vec::new(),
             creates: Vec::new(),
             emits_events: Vec::new(),
+            custom_errors: Vec::new(),
+            yul_memory: false,
             initializer: None,
             default_constructor: None,
             cfg: Vec::new(),
+            pass_dumps: Vec::new(),
             code: OnceCell::new(),
             instantiable,
             program_id: None,
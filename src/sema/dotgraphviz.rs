@@ -1066,6 +1066,33 @@ impl Dot {
                 self.add_expression(array, func, ns, node, String::from("array"));
                 self.add_expression(index, func, ns, node, String::from("index"));
             }
+            Expression::ArraySlice {
+                loc,
+                ty,
+                array,
+                start,
+                end,
+            } => {
+                let node = self.add_node(
+                    Node::new(
+                        "array_slice",
+                        vec![
+                            format!("array slice {}", ty.to_string(ns)),
+                            ns.loc_to_string(PathDisplay::FullPath, loc),
+                        ],
+                    ),
+                    Some(parent),
+                    Some(parent_rel),
+                );
+
+                self.add_expression(array, func, ns, node, String::from("array"));
+                if let Some(start) = start {
+                    self.add_expression(start, func, ns, node, String::from("start"));
+                }
+                if let Some(end) = end {
+                    self.add_expression(end, func, ns, node, String::from("end"));
+                }
+            }
             Expression::StructMember {
                 loc,
                 ty,
@@ -1447,6 +1474,28 @@ impl Dot {
                     Some(parent_rel),
                 );
             }
+            Expression::CustomError {
+                loc,
+                error_no,
+                args,
+            } => {
+                let error = &ns.errors[*error_no];
+
+                let labels = vec![
+                    format!("error {}", error.symbol_name(ns)),
+                    ns.loc_to_string(PathDisplay::FullPath, loc),
+                ];
+
+                let node = self.add_node(
+                    Node::new("custom_error", labels),
+                    Some(parent),
+                    Some(parent_rel),
+                );
+
+                for (no, arg) in args.iter().enumerate() {
+                    self.add_expression(arg, func, ns, node, format!("arg #{no}"));
+                }
+            }
         }
     }
 
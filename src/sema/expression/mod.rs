@@ -443,7 +443,7 @@ impl Expression {
 
                 let to_width = enum_ty.ty.bits(ns);
 
-                // TODO needs runtime checks
+                // codegen checks that the value is in range at runtime
                 match from_width.cmp(&to_width) {
                     Ordering::Greater => Ok(Expression::Trunc {
                         loc: *loc,
@@ -927,6 +927,14 @@ impl Expression {
                     expr: Box::new(self.clone()),
                 })
             }
+            (Type::Slice(elem), Type::Bytes(_)) if elem.as_ref() == &Type::Bytes(1) => {
+                Ok(Expression::BytesCast {
+                    loc: *loc,
+                    to: to.clone(),
+                    from: from.clone(),
+                    expr: Box::new(self.clone()),
+                })
+            }
             // Explicit conversion from bytesN to int/uint only allowed with expliciy
             // cast and if it is the same size (i.e. no conversion required)
             (Type::Bytes(from_len), Type::Uint(to_len))
@@ -1210,6 +1218,24 @@ impl Expression {
             {
                 Ok(self.clone())
             }
+            // slice bytes1 -> bytes, copies the slice into memory
+            (Type::Slice(elem), Type::DynamicBytes) if elem.as_ref() == &Type::Bytes(1) => {
+                Ok(Expression::Cast {
+                    loc: *loc,
+                    to: to.clone(),
+                    expr: Box::new(self.clone()),
+                })
+            }
+            // slice T -> T[], copies the slice into memory
+            (Type::Slice(from_elem), Type::Array(to_elem, dims))
+                if dims.len() == 1 && dims[0] == ArrayLength::Dynamic && from_elem == to_elem =>
+            {
+                Ok(Expression::Cast {
+                    loc: *loc,
+                    to: to.clone(),
+                    expr: Box::new(self.clone()),
+                })
+            }
             // bytes/address/bytesN -> slice bytes1
             (_, Type::Slice(ty)) if can_cast_to_slice(from) && ty.as_ref() == &Type::Bytes(1) => {
                 Ok(Expression::Cast {
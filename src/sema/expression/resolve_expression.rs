@@ -14,7 +14,7 @@ use crate::sema::expression::{
         rational_number_literal, string_literal, unit_literal,
     },
     member_access::member_access,
-    subscript::array_subscript,
+    subscript::{array_slice, array_subscript},
     variable::variable,
     {user_defined_operator, ExprContext, ResolveTo},
 };
@@ -332,13 +332,8 @@ pub fn expression(
 
             Err(())
         }
-        pt::Expression::ArraySlice(loc, ..) => {
-            diagnostics.push(Diagnostic::error(
-                *loc,
-                "slice not supported yet".to_string(),
-            ));
-
-            Err(())
+        pt::Expression::ArraySlice(loc, array, start, end) => {
+            array_slice(loc, array, start, end, context, ns, symtable, diagnostics)
         }
         pt::Expression::ArraySubscript(loc, array, Some(index)) => {
             array_subscript(loc, array, index, context, ns, symtable, diagnostics)
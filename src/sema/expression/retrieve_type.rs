@@ -51,6 +51,7 @@ impl RetrieveType for Expression {
             | Expression::PostDecrement { ty, .. }
             | Expression::Assign { ty, .. }
             | Expression::Subscript { ty, .. }
+            | Expression::ArraySlice { ty, .. }
             | Expression::ZeroExt { to: ty, .. }
             | Expression::SignExt { to: ty, .. }
             | Expression::Trunc { to: ty, .. }
@@ -80,6 +81,7 @@ impl RetrieveType for Expression {
             Expression::Constructor { contract_no, .. } => Type::Contract(*contract_no),
             Expression::InterfaceId { .. } => Type::FunctionSelector,
             Expression::FormatString { .. } => Type::String,
+            Expression::CustomError { .. } => Type::Void,
         }
     }
 }
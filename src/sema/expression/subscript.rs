@@ -1,7 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::sema::ast::{Expression, Mapping, Namespace, RetrieveType, Type};
+use crate::sema::ast::{ArrayLength, Expression, Mapping, Namespace, RetrieveType, Type};
 use crate::sema::diagnostics::Diagnostics;
+use crate::sema::eval::eval_const_number;
 use crate::sema::expression::resolve_expression::expression;
 use crate::sema::expression::{ExprContext, ResolveTo};
 use crate::sema::symtable::Symtable;
@@ -139,6 +140,143 @@ pub(super) fn array_subscript(
     }
 }
 
+/// Resolve an array slice expression, e.g. `data[4:8]`. Slices are only possible on
+/// memory and calldata arrays, and do not copy the underlying data.
+pub(super) fn array_slice(
+    loc: &pt::Loc,
+    array: &pt::Expression,
+    start: &Option<Box<pt::Expression>>,
+    end: &Option<Box<pt::Expression>>,
+    context: &ExprContext,
+    ns: &mut Namespace,
+    symtable: &mut Symtable,
+    diagnostics: &mut Diagnostics,
+) -> Result<Expression, ()> {
+    let array = expression(
+        array,
+        context,
+        ns,
+        symtable,
+        diagnostics,
+        ResolveTo::Unknown,
+    )?;
+    let array_ty = array.ty();
+
+    if array_ty.is_contract_storage() {
+        diagnostics.push(Diagnostic::error(
+            *loc,
+            "array slice is only supported on memory and calldata arrays".to_string(),
+        ));
+        return Err(());
+    }
+
+    let elem_ty = match array_ty.deref_any() {
+        Type::DynamicBytes => Type::Bytes(1),
+        Type::Array(_, dims) if dims.last() == Some(&ArrayLength::Dynamic) => {
+            array_ty.deref_any().array_elem()
+        }
+        Type::Slice(elem) => *elem.clone(),
+        Type::String => {
+            diagnostics.push(Diagnostic::error(
+                array.loc(),
+                "array slice is not permitted on string".to_string(),
+            ));
+            return Err(());
+        }
+        Type::Array(..) | Type::Bytes(_) => {
+            diagnostics.push(Diagnostic::error(
+                array.loc(),
+                format!(
+                    "array slice is only supported on dynamic arrays, not '{}'",
+                    array_ty.to_string(ns)
+                ),
+            ));
+            return Err(());
+        }
+        _ => {
+            diagnostics.push(Diagnostic::error(
+                array.loc(),
+                "expression is not an array".to_string(),
+            ));
+            return Err(());
+        }
+    };
+
+    let array = array.cast(&array.loc(), array_ty.deref_any(), true, ns, diagnostics)?;
+
+    let start = start
+        .as_ref()
+        .map(|start| slice_bound(start, context, ns, symtable, diagnostics))
+        .transpose()?;
+    let end = end
+        .as_ref()
+        .map(|end| slice_bound(end, context, ns, symtable, diagnostics))
+        .transpose()?;
+
+    if let (Some(start), Some(end)) = (&start, &end) {
+        let mut eval_diagnostics = Diagnostics::default();
+
+        if let (Ok((_, start_value)), Ok((_, end_value))) = (
+            eval_const_number(start, ns, &mut eval_diagnostics),
+            eval_const_number(end, ns, &mut eval_diagnostics),
+        ) {
+            if start_value > end_value {
+                diagnostics.push(Diagnostic::error(
+                    *loc,
+                    format!("array slice start {start_value} is greater than end {end_value}"),
+                ));
+                return Err(());
+            }
+        }
+    }
+
+    Ok(Expression::ArraySlice {
+        loc: *loc,
+        ty: Type::Slice(Box::new(elem_ty)),
+        array: Box::new(array),
+        start: start.map(Box::new),
+        end: end.map(Box::new),
+    })
+}
+
+/// Resolve the start or end of an array slice
+fn slice_bound(
+    bound: &pt::Expression,
+    context: &ExprContext,
+    ns: &mut Namespace,
+    symtable: &mut Symtable,
+    diagnostics: &mut Diagnostics,
+) -> Result<Expression, ()> {
+    let expr = expression(
+        bound,
+        context,
+        ns,
+        symtable,
+        diagnostics,
+        ResolveTo::Type(&Type::Uint(32)),
+    )?;
+
+    expr.check_constant_overflow(diagnostics);
+
+    let ty = expr.ty();
+
+    match ty.deref_any() {
+        Type::Uint(_) => (),
+        _ => {
+            diagnostics.push(Diagnostic::error(
+                bound.loc(),
+                format!(
+                    "array slice bound must be an unsigned integer, not '{}'",
+                    ty.to_string(ns)
+                ),
+            ));
+            return Err(());
+        }
+    }
+
+    expr.cast(&bound.loc(), ty.deref_any(), true, ns, diagnostics)
+}
+
 /// Calculate storage subscript
 fn mapping_subscript(
     loc: &pt::Loc,
@@ -35,7 +35,7 @@ pub(super) fn unexpected_parameter_annotation(loc: pt::Loc) -> Diagnostic {
     Diagnostic::error(loc, "unexpected parameter annotation".to_string())
 }
 
-/// Resolve the prototype annotation for functions (just the selector). These
+/// Resolve the prototype annotation for functions (the selector and call frequency). These
 /// annotations can be resolved for functions without a body. This means they
 /// do not need to access the symbol table, like `@seed(foo)` annotations do.
 pub fn function_prototype_annotations(
@@ -48,6 +48,7 @@ pub fn function_prototype_annotations(
     for annotation in annotations {
         match annotation.id.name.as_str() {
             "selector" => function_selector(func, annotation, &mut diagnostics, ns),
+            "hot" => function_hot(func, annotation, &mut diagnostics, ns),
             "account" | "signer" | "mutableAccount" | "mutableSigner"
                 if ns.target == Target::Solana =>
             {
@@ -186,6 +187,75 @@ fn function_selector(
     }
 }
 
+/// Parse the call frequency from a @hot annotation. The dispatcher matches the selectors of
+/// these functions before any other, the most frequently called first.
+fn function_hot(
+    func: &mut Function,
+    annotation: &pt::Annotation,
+    diagnostics: &mut Diagnostics,
+    ns: &mut Namespace,
+) {
+    if func.ty != pt::FunctionTy::Function {
+        diagnostics.push(Diagnostic::error(
+            annotation.loc,
+            format!("@hot not permitted on {}", func.ty),
+        ));
+        return;
+    }
+
+    if !func.is_public() {
+        diagnostics.push(Diagnostic::error(
+            annotation.loc,
+            format!(
+                "@hot only permitted on 'public' or 'external' function, not '{}'",
+                func.visibility
+            ),
+        ));
+        return;
+    }
+
+    if let Some((prev, _)) = &func.hot {
+        duplicate_annotation(diagnostics, "hot", annotation.loc, *prev, func.ty.as_str());
+        return;
+    }
+
+    let uint32 = Type::Uint(32);
+
+    let expr = match annotation.value.as_ref().unwrap() {
+        pt::Expression::HexNumberLiteral(loc, n, None) => {
+            hex_number_literal(loc, n, ns, diagnostics, ResolveTo::Type(&uint32))
+        }
+        pt::Expression::NumberLiteral(loc, base, exp, unit) => {
+            let unit = unit_literal(loc, unit, ns, diagnostics);
+
+            number_literal(
+                loc,
+                base,
+                exp,
+                ns,
+                &unit,
+                diagnostics,
+                ResolveTo::Type(&uint32),
+            )
+        }
+        expr => {
+            diagnostics.push(Diagnostic::error(
+                expr.loc(),
+                "literal number expected".into(),
+            ));
+            return;
+        }
+    };
+
+    if let Ok(Expression::NumberLiteral { loc, value, .. }) = &expr {
+        if let Some(diagnostic) = overflow_diagnostic(value, &uint32, loc) {
+            diagnostics.push(diagnostic);
+        } else {
+            func.hot = Some((annotation.loc, value.to_u32().unwrap()));
+        }
+    }
+}
+
 /// Collect the seeds, bump, payer, and space for constructors. This is a no-op on Polkadot/EVM since
 /// there should be no seed or bump annotations permitted on other targets.
 ///
@@ -213,8 +283,8 @@ pub(super) fn function_body_annotations(
 
     for note in body_annotations {
         match note.id.name.as_str() {
-            "selector" => {
-                // selectors already done in function_prototype_annotations
+            "selector" | "hot" => {
+                // selectors and call frequencies already done in function_prototype_annotations
                 // without using a symbol table
             }
             "seed" if is_solana_constructor => {
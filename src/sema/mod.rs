@@ -21,6 +21,7 @@ mod address;
 pub mod ast;
 pub mod builtin;
 mod builtin_structs;
+pub mod callgraph;
 pub(crate) mod contracts;
 pub mod diagnostics;
 mod dotgraphviz;
@@ -33,6 +34,7 @@ mod functions;
 mod mutability;
 mod namespace;
 pub(crate) mod solana_accounts;
+pub mod solidity_version;
 mod statements;
 pub mod symtable;
 pub mod tags;
@@ -416,7 +418,7 @@ fn resolve_import(
     }
 }
 
-/// Resolve pragma. We don't do anything with pragmas for now
+/// Resolve pragma. Only `pragma solidity` is checked, other known pragmas are ignored
 fn resolve_pragma(
     loc: &pt::Loc,
     name: &pt::Identifier,
@@ -424,10 +426,7 @@ fn resolve_pragma(
     ns: &mut ast::Namespace,
 ) {
     if name.name == "solidity" {
-        ns.diagnostics.push(ast::Diagnostic::debug(
-            *loc,
-            "pragma 'solidity' is ignored".to_string(),
-        ));
+        solidity_version::check_pragma_solidity(value, ns);
     } else if name.name == "experimental" && value.string == "ABIEncoderV2" {
         ns.diagnostics.push(ast::Diagnostic::debug(
             *loc,
@@ -10,6 +10,7 @@ use super::{
     eval::eval_const_number,
     expression::{ExprContext, ResolveTo},
     resolve_params, resolve_returns,
+    solidity_version::SOLIDITY_VERSION,
     symtable::Symtable,
     ArrayDimension,
 };
@@ -47,6 +48,7 @@ impl Namespace {
 
         let mut ns = Namespace {
             target,
+            solidity_version: SOLIDITY_VERSION,
             files: Vec::new(),
             enums: Vec::new(),
             structs: Vec::new(),
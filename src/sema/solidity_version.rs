@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::ast::{Diagnostic, Namespace};
+use semver::{Comparator, Op, Version, VersionReq};
+use solang_parser::pt;
+
+/// The Solidity language level which Solang implements, unless overridden with
+/// `--solidity-version`
+pub const SOLIDITY_VERSION: Version = Version::new(0, 8, 22);
+
+/// Check the version expression of `pragma solidity` against the Solidity language level
+/// of the namespace. Ranges which can never be satisfied are an error; ranges which are
+/// not satisfied by the language level give a warning.
+pub(super) fn check_pragma_solidity(value: &pt::StringLiteral, ns: &mut Namespace) {
+    let alternatives = match parse_version_expression(&value.string) {
+        Ok(alternatives) => alternatives,
+        Err(message) => {
+            ns.diagnostics.push(Diagnostic::error(
+                value.loc,
+                format!("invalid solidity version '{}': {}", value.string, message),
+            ));
+            return;
+        }
+    };
+
+    if alternatives.iter().all(|req| !satisfiable(req)) {
+        ns.diagnostics.push(Diagnostic::error(
+            value.loc,
+            format!(
+                "solidity version '{}' cannot be satisfied by any version",
+                value.string
+            ),
+        ));
+    } else if !alternatives
+        .iter()
+        .any(|req| req.matches(&ns.solidity_version))
+    {
+        ns.diagnostics.push(Diagnostic::warning(
+            value.loc,
+            format!(
+                "solidity version '{}' is not satisfied by solidity language level {}",
+                value.string, ns.solidity_version
+            ),
+        ));
+    }
+}
+
+/// Convert a Solidity version expression into a list of semver requirements, one for each
+/// alternative separated by `||`. Solidity differs from semver in that a version without an
+/// operator means exactly that version, comparators are separated by whitespace rather than
+/// commas, and `A - B` is a range.
+fn parse_version_expression(expr: &str) -> Result<Vec<VersionReq>, String> {
+    expr.split("||")
+        .map(|alternative| {
+            let tokens: Vec<&str> = alternative.split_whitespace().collect();
+
+            if tokens.is_empty() {
+                return Err("missing version".to_string());
+            }
+
+            let comparators = if let [from, "-", to] = tokens.as_slice() {
+                vec![format!(">={from}"), format!("<={to}")]
+            } else {
+                let mut comparators = Vec::new();
+                let mut operator = String::new();
+
+                for token in tokens {
+                    if token.chars().all(|ch| "<>=^~".contains(ch)) {
+                        if !operator.is_empty() {
+                            return Err(format!("unexpected operator '{token}'"));
+                        }
+                        operator = token.to_string();
+                    } else if operator.is_empty()
+                        && token.starts_with(|ch: char| ch.is_ascii_digit())
+                        && !token.contains(['*', 'x', 'X'])
+                    {
+                        comparators.push(format!("={token}"));
+                    } else {
+                        comparators.push(format!("{operator}{token}"));
+                        operator.clear();
+                    }
+                }
+
+                if !operator.is_empty() {
+                    return Err(format!("missing version after '{operator}'"));
+                }
+
+                comparators
+            };
+
+            VersionReq::parse(&comparators.join(", ")).map_err(|err| err.to_string())
+        })
+        .collect()
+}
+
+/// Is there any version which matches all the comparators
+fn satisfiable(req: &VersionReq) -> bool {
+    let mut lower = Version::new(0, 0, 0);
+    let mut upper = None;
+
+    for comparator in &req.comparators {
+        let (from, to) = bounds(comparator);
+
+        lower = lower.max(from);
+
+        upper = match (upper, to) {
+            (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    upper.map_or(true, |upper| lower < upper)
+}
+
+/// The range of versions matched by a comparator, as an inclusive lower bound and an
+/// exclusive upper bound. Pre-release versions are not used by Solidity and are ignored.
+fn bounds(comparator: &Comparator) -> (Version, Option<Version>) {
+    let major = comparator.major;
+    let minor = comparator.minor;
+    let patch = comparator.patch;
+
+    // the versions matched when there is no operator
+    let first = Version::new(major, minor.unwrap_or(0), patch.unwrap_or(0));
+    let next = match (minor, patch) {
+        (Some(minor), Some(patch)) => Version::new(major, minor, patch + 1),
+        (Some(minor), None) => Version::new(major, minor + 1, 0),
+        (None, _) => Version::new(major + 1, 0, 0),
+    };
+
+    match comparator.op {
+        Op::Exact | Op::Wildcard => (first, Some(next)),
+        Op::Greater => (next, None),
+        Op::GreaterEq => (first, None),
+        Op::Less => (Version::new(0, 0, 0), Some(first)),
+        Op::LessEq => (Version::new(0, 0, 0), Some(next)),
+        Op::Tilde => match minor {
+            Some(minor) => (first, Some(Version::new(major, minor + 1, 0))),
+            None => (first, Some(next)),
+        },
+        Op::Caret => match (major, minor, patch) {
+            (0, Some(0), Some(patch)) => (first, Some(Version::new(0, 0, patch + 1))),
+            (0, Some(minor), _) => (first, Some(Version::new(0, minor + 1, 0))),
+            (major, _, _) => (first, Some(Version::new(major + 1, 0, 0))),
+        },
+        _ => (Version::new(0, 0, 0), None),
+    }
+}
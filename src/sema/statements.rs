@@ -977,67 +977,15 @@ fn revert_pos_arg(
     if let Some(path) = path {
         let error_no = ns.resolve_error(context.file_no, context.contract_no, path, diagnostics)?;
 
-        let mut arguments = Vec::new();
-
-        for (pos, arg) in args.iter().enumerate() {
-            let error = &ns.errors[error_no];
-
-            let ty = if let Some(field) = error.fields.get(pos) {
-                field.ty.clone()
-            } else {
-                let _ = expression(arg, context, ns, symtable, diagnostics, ResolveTo::Unknown);
-
-                continue;
-            };
-
-            let arg = match expression(
-                arg,
-                context,
-                ns,
-                symtable,
-                diagnostics,
-                ResolveTo::Type(&ty),
-            ) {
-                Ok(e) => e,
-                Err(()) => {
-                    continue;
-                }
-            };
-
-            match arg.cast(&arg.loc(), &ty, true, ns, diagnostics) {
-                Ok(expr) => {
-                    arguments.push(expr);
-                }
-                Err(()) => {
-                    continue;
-                }
-            }
-        }
-
-        let error = &ns.errors[error_no];
-
-        if args.len() != error.fields.len() {
-            ns.diagnostics.push(Diagnostic::error_with_note(
-                path.loc,
-                format!(
-                    "error '{}' has {} fields, {} provided",
-                    error.name,
-                    error.fields.len(),
-                    args.len()
-                ),
-                error.loc,
-                format!("definition of '{}'", error.name),
-            ));
-        }
-
-        if ns.target == Target::Solana {
-            ns.diagnostics.push(Diagnostic::error(
-                *loc,
-                format!("revert with custom errors not supported on {}", ns.target),
-            ));
-        }
-
-        ns.errors[error_no].used = true;
+        let arguments = custom_error_pos_args(
+            &path.loc,
+            error_no,
+            args,
+            context,
+            symtable,
+            diagnostics,
+            ns,
+        );
 
         Ok(Statement::Revert {
             loc: *loc,
@@ -1106,90 +1054,159 @@ fn revert_named_arg(
     if let Some(path) = path {
         let error_no = ns.resolve_error(context.file_no, context.contract_no, path, diagnostics)?;
 
+        let args = custom_error_named_args(
+            &path.loc,
+            error_no,
+            args,
+            context,
+            symtable,
+            diagnostics,
+            ns,
+        );
+
+        Ok(Statement::Revert {
+            loc: *loc,
+            error_no: Some(error_no),
+            args,
+        })
+    } else {
+        ns.diagnostics.push(Diagnostic::error(
+            *loc,
+            "revert with named arguments requires error type".to_string(),
+        ));
+
+        Err(())
+    }
+}
+
+/// Resolve the positional arguments of a custom error, e.g. `NotEnoughBalance(address)` in
+/// `revert NotEnoughBalance(address)` or `require(cond, NotEnoughBalance(address))`
+pub(super) fn custom_error_pos_args(
+    path_loc: &pt::Loc,
+    error_no: usize,
+    args: &[pt::Expression],
+    context: &ExprContext,
+    symtable: &mut Symtable,
+    diagnostics: &mut Diagnostics,
+    ns: &mut Namespace,
+) -> Vec<Expression> {
+    let mut arguments = Vec::new();
+
+    for (pos, arg) in args.iter().enumerate() {
         let error = &ns.errors[error_no];
 
-        let unnamed_fields = error
-            .fields
-            .iter()
-            .filter(|param| param.id.is_none())
-            .count();
+        let ty = if let Some(field) = error.fields.get(pos) {
+            field.ty.clone()
+        } else {
+            let _ = expression(arg, context, ns, symtable, diagnostics, ResolveTo::Unknown);
 
-        if unnamed_fields > 0 {
-            ns.diagnostics.push(Diagnostic::error_with_note(
-                path.loc,
-                format!(
-                    "error '{}' has {} unnamed fields",
-                    error.name, unnamed_fields
-                ),
-                error.loc,
-                format!("definition of '{}'", error.name),
-            ));
+            continue;
+        };
+
+        let arg = match expression(
+            arg,
+            context,
+            ns,
+            symtable,
+            diagnostics,
+            ResolveTo::Type(&ty),
+        ) {
+            Ok(e) => e,
+            Err(()) => {
+                continue;
+            }
+        };
+
+        match arg.cast(&arg.loc(), &ty, true, ns, diagnostics) {
+            Ok(expr) => {
+                arguments.push(expr);
+            }
+            Err(()) => {
+                continue;
+            }
         }
+    }
 
-        let fields: HashMap<String, (usize, Type)> = error
-            .fields
-            .iter()
-            .enumerate()
-            .filter_map(|(i, p)| {
-                p.id.as_ref()
-                    .map(|id| (id.name.to_owned(), (i, p.ty.clone())))
-            })
-            .collect();
+    let error = &ns.errors[error_no];
 
-        let mut arguments = HashMap::new();
+    if args.len() != error.fields.len() {
+        ns.diagnostics.push(Diagnostic::error_with_note(
+            *path_loc,
+            format!(
+                "error '{}' has {} fields, {} provided",
+                error.name,
+                error.fields.len(),
+                args.len()
+            ),
+            error.loc,
+            format!("definition of '{}'", error.name),
+        ));
+    }
 
-        for arg in args {
-            let error = &ns.errors[error_no];
+    ns.errors[error_no].used = true;
 
-            let name = arg.name.name.as_str();
+    if let Some(function_no) = context.function_no {
+        if !ns.functions[function_no].custom_errors.contains(&error_no) {
+            ns.functions[function_no].custom_errors.push(error_no);
+        }
+    }
 
-            if let Some((pos, ty)) = fields.get(name) {
-                if arguments.contains_key(pos) {
-                    diagnostics.push(Diagnostic::error(
-                        arg.name.loc,
-                        format!("duplicate argument with name '{}'", arg.name.name),
-                    ));
+    arguments
+}
 
-                    let _ = expression(
-                        &arg.expr,
-                        context,
-                        ns,
-                        symtable,
-                        diagnostics,
-                        ResolveTo::Unknown,
-                    );
+/// Resolve the named arguments of a custom error, e.g. `NotEnoughBalance({user: address})` in
+/// `revert NotEnoughBalance({user: address})`
+pub(super) fn custom_error_named_args(
+    path_loc: &pt::Loc,
+    error_no: usize,
+    args: &[pt::NamedArgument],
+    context: &ExprContext,
+    symtable: &mut Symtable,
+    diagnostics: &mut Diagnostics,
+    ns: &mut Namespace,
+) -> Vec<Expression> {
+    let error = &ns.errors[error_no];
 
-                    continue;
-                }
+    let unnamed_fields = error
+        .fields
+        .iter()
+        .filter(|param| param.id.is_none())
+        .count();
 
-                let arg = match expression(
-                    &arg.expr,
-                    context,
-                    ns,
-                    symtable,
-                    diagnostics,
-                    ResolveTo::Type(ty),
-                ) {
-                    Ok(e) => e,
-                    Err(()) => {
-                        continue;
-                    }
-                };
+    if unnamed_fields > 0 {
+        ns.diagnostics.push(Diagnostic::error_with_note(
+            *path_loc,
+            format!(
+                "error '{}' has {} unnamed fields",
+                error.name, unnamed_fields
+            ),
+            error.loc,
+            format!("definition of '{}'", error.name),
+        ));
+    }
 
-                match arg.cast(&arg.loc(), ty, true, ns, diagnostics) {
-                    Ok(expr) => {
-                        arguments.insert(*pos, expr);
-                    }
-                    Err(()) => {
-                        continue;
-                    }
-                }
-            } else {
-                ns.diagnostics.push(Diagnostic::error_with_note(
+    let fields: HashMap<String, (usize, Type)> = error
+        .fields
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| {
+            p.id.as_ref()
+                .map(|id| (id.name.to_owned(), (i, p.ty.clone())))
+        })
+        .collect();
+
+    let mut arguments = HashMap::new();
+
+    for arg in args {
+        let error = &ns.errors[error_no];
+
+        let name = arg.name.name.as_str();
+
+        if let Some((pos, ty)) = fields.get(name) {
+            if arguments.contains_key(pos) {
+                diagnostics.push(Diagnostic::error(
                     arg.name.loc,
-                    format!("error '{}' has no field called '{}'", error.name, name),
-                    error.loc,
-                    format!("definition of '{}'", error.name),
+                    format!("duplicate argument with name '{}'", arg.name.name),
                 ));
 
                 let _ = expression(
@@ -1200,47 +1217,76 @@ fn revert_named_arg(
                     diagnostics,
                     ResolveTo::Unknown,
                 );
+
+                continue;
             }
-        }
 
-        if ns.target == Target::Solana {
-            ns.diagnostics.push(Diagnostic::error(
-                *loc,
-                format!("revert with custom errors not supported on {}", ns.target),
+            let arg = match expression(
+                &arg.expr,
+                context,
+                ns,
+                symtable,
+                diagnostics,
+                ResolveTo::Type(ty),
+            ) {
+                Ok(e) => e,
+                Err(()) => {
+                    continue;
+                }
+            };
+
+            match arg.cast(&arg.loc(), ty, true, ns, diagnostics) {
+                Ok(expr) => {
+                    arguments.insert(*pos, expr);
+                }
+                Err(()) => {
+                    continue;
+                }
+            }
+        } else {
+            ns.diagnostics.push(Diagnostic::error_with_note(
+                arg.name.loc,
+                format!("error '{}' has no field called '{}'", error.name, name),
+                error.loc,
+                format!("definition of '{}'", error.name),
             ));
+
+            let _ = expression(
+                &arg.expr,
+                context,
+                ns,
+                symtable,
+                diagnostics,
+                ResolveTo::Unknown,
+            );
         }
+    }
 
-        let mut args = Vec::new();
-        let error = &ns.errors[error_no];
+    let mut args = Vec::new();
+    let error = &ns.errors[error_no];
 
-        for pos in 0..error.fields.len() {
-            if let Some(arg) = arguments.remove(&pos) {
-                args.push(arg);
-            } else if let Some(id) = &error.fields[pos].id {
-                ns.diagnostics.push(Diagnostic::error_with_note(
-                    path.loc,
-                    format!("missing field '{}'", id.name),
-                    error.loc,
-                    format!("definition of '{}'", error.name),
-                ));
-            }
+    for pos in 0..error.fields.len() {
+        if let Some(arg) = arguments.remove(&pos) {
+            args.push(arg);
+        } else if let Some(id) = &error.fields[pos].id {
+            ns.diagnostics.push(Diagnostic::error_with_note(
+                *path_loc,
+                format!("missing field '{}'", id.name),
+                error.loc,
+                format!("definition of '{}'", error.name),
+            ));
         }
+    }
 
-        ns.errors[error_no].used = true;
+    ns.errors[error_no].used = true;
 
-        Ok(Statement::Revert {
-            loc: *loc,
-            error_no: Some(error_no),
-            args,
-        })
-    } else {
-        ns.diagnostics.push(Diagnostic::error(
-            *loc,
-            "revert with named arguments requires error type".to_string(),
-        ));
-
-        Err(())
+    if let Some(function_no) = context.function_no {
+        if !ns.functions[function_no].custom_errors.contains(&error_no) {
+            ns.functions[function_no].custom_errors.push(error_no);
+        }
     }
+
+    args
 }
 
 /// Resolve emit event
@@ -2169,17 +2215,6 @@ fn try_catch(
     ns: &mut Namespace,
     diagnostics: &mut Diagnostics,
 ) -> Result<(Statement, bool), ()> {
-    if ns.target == Target::Solana {
-        diagnostics.push(Diagnostic::error(
-            *loc,
-            "The try-catch statement is not supported on Solana. Please, go to \
-             https://solang.readthedocs.io/en/latest/language/statements.html#try-catch-statement \
-             for more information"
-                .to_string(),
-        ));
-        return Err(());
-    }
-
     let mut expr = expr.remove_parenthesis();
     let mut ok = None;
 
@@ -2462,6 +2497,15 @@ fn try_catch(
 
     symtable.leave_scope();
 
+    if ns.target == Target::Solana {
+        ns.diagnostics.push(Diagnostic::warning(
+            *loc,
+            "catch clauses are unreachable on Solana, as the runtime aborts the transaction when \
+             a cross-program invocation fails"
+                .to_string(),
+        ));
+    }
+
     let mut clauses_unique = HashSet::new();
     let mut errors_resolved = Vec::new();
 
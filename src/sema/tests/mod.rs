@@ -456,7 +456,7 @@ contract aborting {
 }
 
 contract runner {
-    function test() external pure {
+    function test() external {
 
         try aborting.abort() returns (int32 a, bool b) {
             // call succeeded; return values are in a and b
@@ -478,12 +478,16 @@ contract runner {
 
     let ns = parse_and_resolve(OsStr::new("test.sol"), &mut cache, Target::Solana);
 
-    assert_eq!(ns.diagnostics.len(), 3);
+    assert!(!ns.diagnostics.any_errors());
     assert!(ns.diagnostics.contains_message("found contract 'runner'"));
     assert!(ns.diagnostics.contains_message("found contract 'aborting'"));
-    assert!(ns.diagnostics.contains_message("The try-catch statement is not \
-     supported on Solana. Please, go to \
-     https://solang.readthedocs.io/en/latest/language/statements.html#try-catch-statement for more information"));
+    assert!(ns
+        .diagnostics
+        .contains_message("try-catch error bytes 'raw' has never been used"));
+    assert!(ns.diagnostics.contains_message(
+        "catch clauses are unreachable on Solana, as the runtime aborts the transaction when a \
+         cross-program invocation fails"
+    ));
 }
 
 #[test]
@@ -655,3 +659,120 @@ fn get_import_path() {
         assert_eq!(Some(&(None, examples.clone())), import_path);
     }
 }
+
+#[test]
+fn call_graph() {
+    use crate::sema::callgraph::{call_graph, EdgeKind, NodeKind};
+
+    let ns = parse(
+        r#"
+        interface I {
+            function get() external returns (uint);
+        }
+
+        contract Other {}
+
+        abstract contract Base {
+            modifier onlyOwner() {
+                check();
+                _;
+            }
+
+            function check() internal virtual {}
+        }
+
+        contract C is Base {
+            function run(I i) public onlyOwner returns (uint) {
+                new Other();
+                return helper(i.get());
+            }
+
+            function helper(uint a) private pure returns (uint r) {
+                assembly {
+                    function double(x) -> y { y := add(x, x) }
+                    r := double(a)
+                }
+            }
+
+            function check() internal override {}
+        }"#,
+    );
+
+    assert!(!ns.diagnostics.any_errors());
+
+    let contract_no = ns
+        .contracts
+        .iter()
+        .position(|contract| contract.id.name == "C")
+        .unwrap();
+
+    let graph = call_graph(contract_no, &ns);
+
+    let mut edges: Vec<_> = graph
+        .graph
+        .edge_indices()
+        .map(|edge| {
+            let (from, to) = graph.graph.edge_endpoints(edge).unwrap();
+            (
+                graph.graph[from].to_string(),
+                graph.graph[to].to_string(),
+                graph.graph[edge],
+            )
+        })
+        .collect();
+
+    edges.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+    assert_eq!(
+        edges,
+        vec![
+            (
+                "Base.onlyOwner()".to_owned(),
+                "C.check()".to_owned(),
+                EdgeKind::Internal
+            ),
+            (
+                "C.helper(uint256)".to_owned(),
+                "assembly double".to_owned(),
+                EdgeKind::Yul
+            ),
+            (
+                "C.run(address)".to_owned(),
+                "Base.onlyOwner()".to_owned(),
+                EdgeKind::Modifier
+            ),
+            (
+                "C.run(address)".to_owned(),
+                "C.helper(uint256)".to_owned(),
+                EdgeKind::Internal
+            ),
+            (
+                "C.run(address)".to_owned(),
+                "I.get()".to_owned(),
+                EdgeKind::External
+            ),
+            (
+                "C.run(address)".to_owned(),
+                "new Other".to_owned(),
+                EdgeKind::Create
+            ),
+        ]
+    );
+
+    let entry_points: Vec<_> = graph
+        .graph
+        .node_weights()
+        .filter(|node| node.entry_point)
+        .map(|node| node.name.as_str())
+        .collect();
+
+    assert_eq!(entry_points, vec!["C.run(address)"]);
+
+    assert!(graph
+        .graph
+        .node_weights()
+        .any(|node| node.kind == NodeKind::Modifier && node.name == "Base.onlyOwner()"));
+
+    let dot = graph.dot();
+    assert!(dot.contains("label = \"C.run(address)\" shape = box style = filled"));
+}
@@ -1149,8 +1149,11 @@ fn struct_offsets(ns: &mut Namespace) {
             let mut offset = BigInt::zero();
             let mut largest_alignment = BigInt::zero();
 
+            // there is an offset for each field, so that the offsets can be indexed by field number
             for field in &ns.structs[struct_no].fields {
-                if !field.infinite_size {
+                if field.infinite_size {
+                    storage_offsets.push(offset.clone());
+                } else {
                     let alignment = field.ty.storage_align(ns);
                     largest_alignment = std::cmp::max(alignment.clone(), largest_alignment.clone());
                     let remainder = offset.clone() % alignment.clone();
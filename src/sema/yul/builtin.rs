@@ -228,6 +228,20 @@ impl YulBuiltInFunction {
         )
     }
 
+    /// Does the builtin access memory, rather than call data or return data
+    pub(crate) fn accesses_memory(self) -> bool {
+        matches!(
+            self,
+            YulBuiltInFunction::Keccak256
+                | YulBuiltInFunction::MLoad
+                | YulBuiltInFunction::MStore
+                | YulBuiltInFunction::MStore8
+                | YulBuiltInFunction::MSize
+                | YulBuiltInFunction::CallDataCopy
+                | YulBuiltInFunction::ReturnDataCopy
+        )
+    }
+
     pub(crate) fn read_state(self) -> bool {
         matches!(
             self,
@@ -509,7 +523,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "keccak256(p, n) performs keccak(mem[p...(p+n)])",
             ty: YulBuiltInFunction::Keccak256,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "pc",
@@ -536,7 +550,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "mload(p) returns mem[p...(p+32)]",
             ty: YulBuiltInFunction::MLoad,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "mstore",
@@ -545,7 +559,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "mstore(p, v) stores v into mem[p...(p+32)]",
             ty: YulBuiltInFunction::MStore,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "mstore8",
@@ -554,7 +568,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "mstore8(p, v) stores (v & 0xff) into mem[p] (modified a single byte of v)",
             ty: YulBuiltInFunction::MStore8,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "sload",
@@ -581,7 +595,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "Returns the size of memory, i.e largest accessed memory index",
             ty: YulBuiltInFunction::MSize,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "gas",
@@ -644,7 +658,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "calldataload(p) returns call data starting from position p (32 bytes)",
             ty: YulBuiltInFunction::CallDataLoad,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "calldatasize",
@@ -653,7 +667,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "Returns the size of call data in bytes",
             ty: YulBuiltInFunction::CallDataSize,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "calldatacopy",
@@ -662,7 +676,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "calldatacopy(t, f, s) copies s bytes from calldata at position f to mem at position t",
             ty: YulBuiltInFunction::CallDataCopy,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "codesize",
@@ -707,7 +721,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "Returns the size of the last returndata",
             ty: YulBuiltInFunction::ReturnDataSize,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "returndatacopy",
@@ -716,7 +730,7 @@ static YUL_BUILTIN: [YulBuiltinPrototype; 77] =
             doc: "returndatacopy(t, f, s) copy s bytes from return data at position f to mem at position t",
             ty: YulBuiltInFunction::ReturnDataCopy,
             stops_execution: false,
-            availability: [true, false, false],
+            availability: [true, true, true],
         },
         YulBuiltinPrototype {
             name: "extcodehash",
@@ -11,6 +11,7 @@ use crate::sema::yul::types::{
     get_default_type_from_identifier, get_type_from_string, verify_type_from_expression,
 };
 use crate::sema::yul::unused_variable::{assigned_variable, used_variable};
+use crate::Target;
 use num_bigint::{BigInt, Sign};
 use num_rational::BigRational;
 use num_traits::{Num, Pow};
@@ -432,6 +433,13 @@ pub(crate) fn resolve_function_call(
             check_function_argument(&default_builtin_parameter, item, function_table, ns);
         }
 
+        // On Solana and Polkadot, memory for assembly is only reserved if it is used
+        if built_in.accesses_memory() && ns.target != Target::EVM {
+            if let Some(function_no) = context.function_no {
+                ns.functions[function_no].yul_memory = true;
+            }
+        }
+
         return Ok(YulExpression::BuiltInCall(
             func_call.loc,
             *built_in,
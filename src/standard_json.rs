@@ -3,6 +3,7 @@
 //! This module defines the json format for `solang compile --standard-json`.
 
 use crate::abi::ethereum::ABI;
+use crate::abi::storage_layout::StorageLayout;
 use serde::Serialize;
 use std::collections::HashMap;
 
@@ -11,13 +12,27 @@ pub struct EwasmContract {
     pub wasm: String,
 }
 
+#[derive(Serialize)]
+pub struct EvmBytecode {
+    pub object: String,
+}
+
+#[derive(Serialize)]
+pub struct EvmContract {
+    pub bytecode: EvmBytecode,
+}
+
 #[derive(Serialize)]
 pub struct JsonContract {
     pub abi: Vec<ABI>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ewasm: Option<EwasmContract>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub evm: Option<EvmContract>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub minimum_space: Option<u32>,
+    #[serde(rename = "storageLayout", skip_serializing_if = "Option::is_none")]
+    pub storage_layout: Option<StorageLayout>,
 }
 
 #[derive(Serialize)]
@@ -7,6 +7,11 @@
 
 #ifndef __wasm__
 #include "solana_sdk.h"
+#else
+extern void seal_return(uint32_t flags, const uint8_t *data, uint32_t length);
+
+// The SCALE encoded Panic(uint256) selector followed by the panic code 0x41 as uint256
+static const uint8_t out_of_memory_panic[36] = {0x4e, 0x48, 0x7b, 0x71, 0x41};
 #endif
 
 /*
@@ -47,6 +52,30 @@ void __init_heap()
 }
 #endif
 
+/*
+  Contracts with inline assembly which accesses memory reserve memory for it at the end of the
+  heap; this must be called right after __init_heap(). Addresses 0x00 to 0x7f in assembly refer
+  to the scratch space, the free memory pointer (0x40) and the zero slot (0x60), as on EVM. The
+  free memory pointer initially points to the arena which follows. This must match the memory
+  model in src/codegen/yul/memory.rs.
+*/
+#define YUL_SCRATCH_SIZE 0x80
+#define YUL_FREE_MEMORY_POINTER 0x40
+
+void __init_yul_memory(uint32_t size)
+{
+    struct chunk *first = HEAP_START;
+    first->length -= size;
+
+    uint8_t *memory = (uint8_t *)(first + 1) + first->length;
+
+    for (int i = 0; i < YUL_SCRATCH_SIZE; i++)
+        memory[i] = 0;
+
+    // the free memory pointer is a 256 bit big endian value
+    memory[YUL_FREE_MEMORY_POINTER + 31] = YUL_SCRATCH_SIZE;
+}
+
 void __attribute__((noinline)) __free(void *m)
 {
     struct chunk *cur = m;
@@ -112,8 +141,12 @@ void *__attribute__((noinline)) __malloc(uint32_t size)
     {
         // go bang
 #ifdef __wasm__
+        // revert with Panic(0x41), like solc does when memory runs out
+        seal_return(1, out_of_memory_panic, sizeof(out_of_memory_panic));
         __builtin_unreachable();
 #else
+        // there is no way to return an error code to the entrypoint from here, so unlike
+        // on Polkadot this does not raise Panic(0x41)
         sol_log("out of heap memory");
         sol_panic();
 #endif
@@ -130,3 +130,64 @@ fn basic_compilation_from_toml() {
 
     compile_cmd.current_dir(polkadot_test).assert().success();
 }
+
+#[test]
+#[cfg(feature = "test_runner")]
+fn run_unit_tests() {
+    let tmp = TempDir::new_in("tests").unwrap();
+
+    let source = tmp.path().join("counter.sol");
+
+    std::fs::write(
+        &source,
+        r#"
+        contract CounterTest {
+            uint64 count;
+
+            function setUp() public {
+                count = 1;
+            }
+
+            function testIncrement() public {
+                count += 1;
+                require(count == 2, "count should be 2");
+            }
+
+            function testBroken() public {
+                print("about to fail");
+                require(count == 0, "count should be 0");
+            }
+        }"#,
+    )
+    .unwrap();
+
+    for target in ["solana", "polkadot"] {
+        let mut cmd = Command::cargo_bin("solang").unwrap();
+
+        let assert = cmd
+            .args(["test", "--target", target])
+            .arg(&source)
+            .assert()
+            .failure();
+
+        let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+
+        assert!(stdout.contains("test CounterTest::testIncrement ... ok"));
+        assert!(stdout.contains("test CounterTest::testBroken ... FAILED"));
+        assert!(stdout.contains("revert: count should be 0"));
+        assert!(stdout.contains("about to fail"));
+        assert!(stdout.contains("test result: FAILED. 1 passed; 1 failed"));
+
+        let mut cmd = Command::cargo_bin("solang").unwrap();
+
+        let assert = cmd
+            .args(["test", "--target", target, "--filter", "Increment"])
+            .arg(&source)
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+
+        assert!(stdout.contains("test result: ok. 1 passed; 0 failed"));
+    }
+}
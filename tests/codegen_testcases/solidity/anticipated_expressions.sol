@@ -12,8 +12,9 @@ contract Test {
         int z = a-b;
         int p=0;
 
-        // CHECK: block1: # cond
+        // a % b is loop invariant, so it is hoisted out of the loop condition
         // CHECK: ty:int256 %2.cse_temp = (signed modulo (arg #0) % (arg #1))
+        // CHECK: block1: # cond
         while(x != 0) {
         // CHECK: block2: # body
         // CHECK: ty:int256 %z = (%z + int256 9)
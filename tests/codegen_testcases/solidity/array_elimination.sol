@@ -1,6 +1,7 @@
-// RUN: --target solana --emit cfg
+// RUN: --target solana --emit cfg --no-inline
 contract TestCase {
     int128[] st;
+    uint32[] stUint;
 
     // BEGIN-CHECK: TestCase::TestCase::function::myFuncStorage__uint32
     function myFuncStorage(uint32[] storage arr) private {
@@ -14,6 +15,15 @@ contract TestCase {
         arr.push(5);
     }
 
+    function callAll(uint32[] memory arr) public {
+        uint32[] storage ref = stUint;
+        myFuncStorage(ref);
+        myFuncStorage2(ref);
+        myFuncPointer(new int256[](2));
+        myFuncPointer2(new int256[](2));
+        arr.push(1);
+    }
+
     // BEGIN-CHECK: TestCase::TestCase::function::myPush__int128
     function myPush(int128 val) public {
         // CHECK: push storage ty:int128 slot:uint32 16 = (arg #0)
@@ -0,0 +1,29 @@
+// RUN: --target polkadot --emit cfg
+
+contract Slices {
+// BEGIN-CHECK: Slices::Slices::function::selector__bytes
+    function selector(bytes calldata data) public pure returns (bytes4) {
+        // CHECK: branchcond (unsigned more uint32 4 > (builtin ArrayLength ((arg #0)))), block1, block2
+        // CHECK: block1: # slice_out_of_bounds
+        // CHECK: assert-failure
+        // CHECK: block3: # slice_in_bounds
+        // CHECK: return bytes4 from:bytes ((builtin ArraySlice ((arg #0), uint32 0, uint32 4)))
+        return bytes4(data[:4]);
+    }
+
+// BEGIN-CHECK: Slices::Slices::function::decode__bytes
+    function decode(bytes calldata data) public pure returns (uint64, bool) {
+        // CHECK: (builtin ArrayLength ((arg #0)))
+        // CHECK: (builtin ReadFromBuffer ((builtin ArraySlice ((arg #0), uint32 4, %end.temp.
+        // NOT-CHECK: alloc bytes
+        return abi.decode(data[4:], (uint64, bool));
+    }
+
+// BEGIN-CHECK: Slices::Slices::function::copy__uint32:_uint64_uint64
+    function copy(uint32[] memory arr, uint64 start, uint64 end) public pure returns (uint32[] memory) {
+        // CHECK: branchcond (unsigned more (arg #2) > (zext uint64 (builtin ArrayLength ((arg #0))))), block1, block2
+        // CHECK: branchcond (unsigned more (arg #1) > (arg #2)), block1, block3
+        // CHECK: return uint32[]((builtin ArraySlice ((arg #0), (trunc uint32 (arg #1)), (trunc uint32 (arg #2)))))
+        return arr[start:end];
+    }
+}
@@ -42,8 +42,8 @@ contract Testing {
         // CHECK: branchcond (unsigned %1.cse_temp <= %temp.10), block7, block8
 
         // CHECK: block6: # end_for
-        // CHECK: ty:uint32 %temp.12 = (%temp.12 - uint32 0)
-        // CHECK: branchcond (unsigned less (uint32 0 + %temp.12) < %temp.10), block11, block12
+        // CHECK: ty:uint32 %temp.12 = (overflowing %temp.12 - uint32 0)
+        // CHECK: branchcond (unsigned less (overflowing uint32 0 + %temp.12) < %temp.10), block11, block12
         // CHECK: block7: # inbounds
 
         // CHECK: ty:uint32 %2.cse_temp = (%temp.12 + (%temp.16 + uint32 4))
@@ -108,7 +108,7 @@ contract Testing {
 
         // CHECK: block6: # next
         // CHECK: # phis: temp.23,for_i_0.temp.26
-        // CHECK: # reaching: buffer:[0:0],  temp.20:[0:1],  temp.21:[1:0],  temp.24:[1:2],  temp.28:[11:0],  temp.25:[3:1],  temp.22:[3:1],  for_i_0.temp.26:[3:3, 6:0],  temp.27:[7:0],  temp.23:[11:3]
+        // CHECK: # reaching: buffer:[0:0],  temp.20:[0:1],  temp.21:[1:0],  temp.22:[3:1],  temp.23:[9:0],  temp.24:[1:2],  temp.25:[3:1],  for_i_0.temp.26:[3:3, 6:0],  temp.27:[7:0],  temp.28:[11:0],  1.cse_temp:[7:1],  2.cse_temp:[9:0]
         // CHECK: ty:uint32 %for_i_0.temp.26 = (%for_i_0.temp.26 + uint32 1)
         // CHECK: branch block5
 
@@ -120,7 +120,7 @@ contract Testing {
         // CHECK: block8: # end_for
         // CHECK: ty:uint32 %temp.23 = (%temp.23 - uint32 8)
         // CHECK: ty:struct Testing.NonConstantStruct %temp.29 = struct { %temp.21, %temp.22 }
-        // CHECK: branchcond (unsigned less (uint32 0 + (uint32 8 + %temp.23)) < %temp.20), block13, block14
+        // CHECK: branchcond (unsigned less (overflowing uint32 0 + (uint32 8 + %temp.23)) < %temp.20), block13, block14
 
         // CHECK: block9: # inbounds
         // CHECK: ty:uint32 %2.cse_temp = (%temp.23 + (%temp.27 + uint32 4))
@@ -183,8 +183,8 @@ contract Testing {
         // CHECK: branchcond (unsigned %1.cse_temp <= %temp.32), block7, block8
 
         // CHECK: block6: # end_for
-        // CHECK: ty:uint32 %temp.34 = (%temp.34 - uint32 0)
-        // CHECK: branchcond (unsigned less (uint32 0 + %temp.34) < %temp.32), block19, block20
+        // CHECK: ty:uint32 %temp.34 = (overflowing %temp.34 - uint32 0)
+        // CHECK: branchcond (unsigned less (overflowing uint32 0 + %temp.34) < %temp.32), block19, block20
 
         // CHECK: block7: # inbounds
         // CHECK: ty:uint64 %temp.38 = (builtin ReadFromBuffer ((arg #0), %temp.34))
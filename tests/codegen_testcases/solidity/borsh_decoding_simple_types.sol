@@ -133,7 +133,7 @@ contract Testing {
 		// CHECK: branchcond (unsigned uint32 4 <= %temp.82), block1, block2
 
         // CHECK: block1: # inbounds
-        // CHECK: ty:uint32 %1.cse_temp = (uint32 0 + (%temp.83 + uint32 4))
+        // CHECK: ty:uint32 %1.cse_temp = (overflowing uint32 0 + (%temp.83 + uint32 4))
         // CHECK: branchcond (unsigned %1.cse_temp <= %temp.82), block3, block4
 
         // CHECK: block2: # out_of_bounds
@@ -142,7 +142,7 @@ contract Testing {
         // CHECK: block3: # inbounds
         // CHECK: ty:bytes %temp.84 = (alloc bytes len %temp.83)
         // CHECK: memcpy src: (advance ptr: %buffer, by: uint32 4), dest: %temp.84, bytes_len: %temp.83
-        // CHECK: ty:uint32 %temp.85 = (builtin ReadFromBuffer ((arg #0), (uint32 0 + (%temp.83 + uint32 4))))
+        // CHECK: ty:uint32 %temp.85 = (builtin ReadFromBuffer ((arg #0), (overflowing uint32 0 + (%temp.83 + uint32 4))))
         // CHECK: ty:uint32 %2.cse_temp = (%1.cse_temp + uint32 4)
         // CHECK: branchcond (unsigned %2.cse_temp <= %temp.82), block5, block6
 
@@ -0,0 +1,23 @@
+// RUN: --target solana --emit cfg-dot --cfg-dot-annotations
+
+contract c {
+    function test(uint64 a) public pure returns (uint64 r) {
+        if (a > 10) {
+            r = a * 2;
+        } else {
+            r = a + 1;
+        }
+    }
+}
+
+// CHECK: digraph "c" {
+// CHECK: subgraph cluster_0 {
+// CHECK: label="function c::c::function::test__uint64 public:true"
+// CHECK: cfg0_block0 [label="block0: # entry\lty:uint64 %a = (arg #0)\lty:uint64 %r = uint64 0\lbranchcond (unsigned more (arg #0) > uint64 10), block1, block2\l"]
+// CHECK: cfg0_block3 [label="block3: # endif\l# phis: r\l# reaching: a:[0:0],  r:[1:0, 2:0]\lreturn %r\l"]
+// CHECK: cfg0_block0 -> cfg0_block1 [label="true"]
+// CHECK: cfg0_block0 -> cfg0_block2 [label="false"]
+// CHECK: cfg0_block1 -> cfg0_block3
+// CHECK: cfg0_block2 -> cfg0_block3
+// CHECK: function solang_dispatch public:false
+// CHECK: [label="default"]
@@ -301,32 +301,41 @@ contract c1 {
         string bst = "from Solang";
         string cst = string.concat(ast, bst);
         // CHECK: ty:int256 %1.cse_temp = (signed divide (arg #0) / (int256 2 * (arg #1)))
-        // CHECK: call c1::c1::function::get__int256_int256 %1.cse_temp, (arg #1)
         int p = a + get(a/(2*b), b);
 
         bool e = (ast == bst) || p < 2;
-        // CHECK: ty:bool %2.cse_temp = (strcmp (%ast) (%bst))
-        // CHECK: branchcond %2.cse_temp, block2, block1
         bool e2 = e;
         // CHECK: branchcond (strcmp ((builtin Concat (%ast, %bst))) (%cst)), block3, block4
         if (string.concat(ast, bst) == cst) {
-            // CHECK: call c1::c1::function::get__int256_int256 %1.cse_temp, (arg #1)
+            // CHECK: block3: # then
+            // CHECK: = %1.cse_temp
             require(a + get(a/(2*b), b) < 0);
             emit testEvent(a + get(a/(2*b) -p, b), p, string.concat(ast, bst));
         }
 
+        // CHECK: block4: # endif
         // CHECK: branchcond %2.cse_temp, block21, block22
         if (ast == bst) {
             ast = string.concat(ast, "b");
         }
-        // CHECK: call c1::c1::function::get__int256_int256 (%1.cse_temp - %p), (arg #1)
+        // CHECK: = (%1.cse_temp - %p)
 
         // CHECK: branchcond (strcmp (%ast) (%bst)), block24, block25
         while (ast == bst) {
             ast = string.concat(ast, "a");
         }
 
-        // CHECK: call c1::c1::function::get__int256_int256 (arg #1), (signed divide (arg #0) / (arg #1))
+        // CHECK: = (signed divide (arg #0) / (arg #1))
+
+        // The calls to get are inlined, so the second one is the same expression as the first
+        // CHECK: block26: # get.entry
+        // CHECK: ty:int256 %.temp.206 = ((%1.cse_temp + (arg #1)) + int256 1)
+        // CHECK: ty:int256 %p = ((arg #0) + %.temp.206)
+        // CHECK: ty:bool %2.cse_temp = (strcmp (%ast) (%bst))
+        // CHECK: branchcond %2.cse_temp, block2, block1
+        // CHECK: block28: # get.entry
+        // CHECK: ty:int256 %.temp.208 = %.temp.206
+        // CHECK: return (((arg #1) + %b.inline.278) + int256 1)
         return get(b, a/b);
     }
 
@@ -376,11 +385,10 @@ contract c1 {
 
         // CHECK: = (int256 4 - %1.cse_temp)
         if(vec.length - (a+b) == 1) {
-            // CHECK:  call c1::c1::function::testing__bytes %c
+            // CHECK: block9: # then
+            // CHECK: = %c
             string k = testing(bytes(c));
             string p = string.concat("a", k);
-            // CHECK: ty:string %p = (builtin Concat ((alloc string uint32 1 "a"), %k))
-            // CHECK: branchcond ((builtin ArrayLength (%p)) == uint32 2), block11, block12
             if(p.length == 2) {
                 // CHECK: ty:string %p1 = (builtin Concat ((alloc string uint32 1 "a"), %k))
                 string p1 = string.concat("a", k);
@@ -406,11 +414,15 @@ contract c1 {
 
         for(int p=0; p<a; ++p) {
             b1.push();
-            // CHECK: = call c1::c1::function::testing__bytes %b1
+            // CHECK: = %b1
             string k1 = testing(bytes(string(b1)));
             string k2 = k1;
         }
 
+        // The calls to testing are inlined
+        // CHECK: block27: # testing.entry
+        // CHECK: ty:string %p = (builtin Concat ((alloc string uint32 1 "a"), %k))
+        // CHECK: branchcond ((builtin ArrayLength (%p)) == uint32 2), block11, block12
         return 2;
     }
 
@@ -1,4 +1,4 @@
-// RUN: --target polkadot --emit cfg
+// RUN: --target polkadot --emit cfg --no-storage-cache
 contract deadstorage {
     int a;
 
@@ -82,7 +82,7 @@ contract deadstorage {
     // CHECK: store storage slot(uint256 3)
 
     // BEGIN-CHECK: deadstorage::function::test7
-    // storage should be flushed before function call
+    // test6 is inlined and does not use test7var, so the first store is redundant
     int test7var;
 
     function test7() public returns (int) {
@@ -92,7 +92,9 @@ contract deadstorage {
         return test7var;
     }
 
-    // CHECK: store storage slot(uint256 4)
+    // CHECK: block0: # entry
+    // CHECK: nop
+    // CHECK: block1: # test6.entry
     // CHECK: store storage slot(uint256 4)
 
     // BEGIN-CHECK: deadstorage::function::test8
@@ -184,9 +186,10 @@ contract foo {
         S[] storage ptrArr = arr;
         ptrArr.push(S({f1: 1}));
         ptrArr.push(S({f1: 2}));
-        // CHECK: %.temp.119, %.temp.120 = call foo::foo::function::g
-        // CHECK: %temp.121 = load storage slot(%.temp.119) ty:struct foo.S
-        // CHECK: %temp.122 = load storage slot(%.temp.120) ty:struct foo.S
+        // CHECK: block1: # g.entry
+        // CHECK: block6: # g.return
+        // CHECK: %temp.127 = load storage slot(%.temp.125) ty:struct foo.S
+        // CHECK: %temp.128 = load storage slot(%.temp.126) ty:struct foo.S
         return g();
     }
 }
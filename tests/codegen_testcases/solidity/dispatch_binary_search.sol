@@ -0,0 +1,46 @@
+// RUN: --target polkadot --emit cfg --dispatch binary-search
+
+contract dispatch {
+	// BEGIN-CHECK: # function polkadot_call_dispatch public:false selector: nonpayable:false
+	// CHECK: block1: # start_dispatch
+	// CHECK: 	ty:uint32 %selector.temp.28 = (builtin ReadFromBuffer ((arg #0), uint32 0))
+	// the hot functions are matched first, the most frequently called one first
+	// CHECK: 	branchcond (%selector.temp.28 == uint32 5), block15, block24
+	// CHECK: block24: # not_hot
+	// CHECK: 	branchcond (%selector.temp.28 == uint32 3), block9, block25
+	// CHECK: block25: # not_hot
+	// CHECK: 	branchcond (unsigned less %selector.temp.28 < uint32 4), block26, block27
+	// CHECK: block26: # selector_lower
+	// CHECK: 	switch %selector.temp.28:
+	// CHECK: 		case uint32 1: goto block #3
+	// CHECK: 		case uint32 2: goto block #6
+	// CHECK: 		default: goto block #2
+	// CHECK: block27: # selector_upper
+	// CHECK: 	branchcond (unsigned less %selector.temp.28 < uint32 6), block28, block29
+	// CHECK: block28: # selector_lower
+	// CHECK: 	switch %selector.temp.28:
+	// CHECK: 		case uint32 4: goto block #12
+	// CHECK: 		default: goto block #2
+	// CHECK: block29: # selector_upper
+	// CHECK: 	switch %selector.temp.28:
+	// CHECK: 		case uint32 6: goto block #18
+	// CHECK: 		case uint32 7: goto block #21
+	// CHECK: 		default: goto block #2
+
+	@selector([1, 0, 0, 0])
+	function a() public pure returns (uint32) { return 1; }
+	@selector([2, 0, 0, 0])
+	function b() public pure returns (uint32) { return 2; }
+	@selector([3, 0, 0, 0])
+	@hot(10)
+	function c() public pure returns (uint32) { return 3; }
+	@selector([4, 0, 0, 0])
+	function d() public pure returns (uint32) { return 4; }
+	@selector([5, 0, 0, 0])
+	@hot(200)
+	function e() public pure returns (uint32) { return 5; }
+	@selector([6, 0, 0, 0])
+	function f() public pure returns (uint32) { return 6; }
+	@selector([7, 0, 0, 0])
+	function g() public pure returns (uint32) { return 7; }
+}
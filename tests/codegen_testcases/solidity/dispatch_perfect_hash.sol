@@ -0,0 +1,44 @@
+// RUN: --target solana --emit cfg --dispatch perfect-hash
+
+contract dispatch {
+	// BEGIN-CHECK: # function solang_dispatch public:false selector: nonpayable:false
+	// CHECK: block1: # switch
+	// CHECK: 	ty:uint64 %selector.temp.32 = (builtin ReadFromBuffer (%input.temp.7, uint64 0))
+	// CHECK: 	switch ((overflowing %selector.temp.32 * uint64 11400714819323198485) >> uint64 61):
+	// CHECK: 		case uint64 0: goto block #31
+	// CHECK: 		case uint64 7: goto block #38
+	// CHECK: 		default: goto block #2
+	// each entry in the table checks for the one selector which hashes to it
+	// CHECK: block31: # selector_slot_0
+	// CHECK: 	branchcond (%selector.temp.32 == uint64 5), block15, block2
+	// CHECK: block32: # selector_slot_1
+	// CHECK: 	branchcond (%selector.temp.32 == uint64 2), block6, block2
+	// CHECK: block33: # selector_slot_2
+	// CHECK: 	branchcond (%selector.temp.32 == uint64 7), block21, block2
+	// CHECK: block34: # selector_slot_3
+	// CHECK: 	branchcond (%selector.temp.32 == uint64 4), block12, block2
+	// CHECK: block35: # selector_slot_4
+	// CHECK: 	branchcond (%selector.temp.32 == uint64 1), block3, block2
+	// CHECK: block36: # selector_slot_5
+	// CHECK: 	branchcond (%selector.temp.32 == uint64 6), block18, block2
+	// CHECK: block37: # selector_slot_6
+	// CHECK: 	branchcond (%selector.temp.32 == uint64 3), block9, block2
+	// the constructor has a discriminator too
+	// CHECK: block38: # selector_slot_7
+	// CHECK: 	branchcond (%selector.temp.32 == uint64 13567095087674764423), block24, block2
+
+	@selector([1, 0, 0, 0, 0, 0, 0, 0])
+	function a() public pure returns (uint32) { return 1; }
+	@selector([2, 0, 0, 0, 0, 0, 0, 0])
+	function b() public pure returns (uint32) { return 2; }
+	@selector([3, 0, 0, 0, 0, 0, 0, 0])
+	function c() public pure returns (uint32) { return 3; }
+	@selector([4, 0, 0, 0, 0, 0, 0, 0])
+	function d() public pure returns (uint32) { return 4; }
+	@selector([5, 0, 0, 0, 0, 0, 0, 0])
+	function e() public pure returns (uint32) { return 5; }
+	@selector([6, 0, 0, 0, 0, 0, 0, 0])
+	function f() public pure returns (uint32) { return 6; }
+	@selector([7, 0, 0, 0, 0, 0, 0, 0])
+	function g() public pure returns (uint32) { return 7; }
+}
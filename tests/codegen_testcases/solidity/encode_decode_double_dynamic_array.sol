@@ -11,13 +11,14 @@ contract Testing {
 	    // CHECK: ty:uint32 %array_bytes_size_0.temp.7 = uint32 0
 	    // CHECK: ty:uint32 %array_bytes_size_0.temp.7 = uint32 4
 	    // CHECK: ty:uint32 %for_i_1.temp.8 = uint32 0
+	    // CHECK: ty:uint32 %licm.temp.14 = (builtin ArrayLength (%vec))
 	    // CHECK: branch block1
 
         // CHECK: block1: # cond
-	    // CHECK: branchcond (unsigned less %for_i_1.temp.8 < (builtin ArrayLength (%vec))), block3, block4
+	    // CHECK: branchcond (unsigned less %for_i_1.temp.8 < %licm.temp.14), block3, block4
 
         // CHECK: block2: # next
-	    // CHECK: ty:uint32 %for_i_1.temp.8 = (%for_i_1.temp.8 + uint32 1)
+	    // CHECK: ty:uint32 %for_i_1.temp.8 = (overflowing %for_i_1.temp.8 + uint32 1)
 	    // CHECK: branch block1
 
         // CHECK: block3: # body
@@ -31,6 +32,7 @@ contract Testing {
 	    // CHECK: writebuffer buffer:%abi_encoded.temp.10 offset:uint32 0 value:(builtin ArrayLength (%vec))
 	    // CHECK: ty:uint32 %temp.11 = uint32 4
 	    // CHECK: ty:uint32 %for_i_1.temp.12 = uint32 0
+	    // CHECK: ty:uint32 %licm.temp.15 = (builtin ArrayLength (%vec))
 	    // CHECK: branch block9
 
         // CHECK: block5: # cond
@@ -48,10 +50,10 @@ contract Testing {
 	    // CHECK: branch block2
 
         // CHECK: block9: # cond
-	    // CHECK: branchcond (unsigned less %for_i_1.temp.12 < (builtin ArrayLength (%vec))), block11, block12
+	    // CHECK: branchcond (unsigned less %for_i_1.temp.12 < %licm.temp.15), block11, block12
 
         // CHECK: block10: # next
-	    // CHECK: ty:uint32 %for_i_1.temp.12 = (%for_i_1.temp.12 + uint32 1)
+	    // CHECK: ty:uint32 %for_i_1.temp.12 = (overflowing %for_i_1.temp.12 + uint32 1)
 	    // CHECK: branch block9
 
         // CHECK: block11: # body
@@ -61,7 +63,7 @@ contract Testing {
 	    // CHECK: branch block13
 
         // CHECK: block12: # end_for
-	    // CHECK: ty:uint32 %temp.11 = (%temp.11 - uint32 0)
+	    // CHECK: ty:uint32 %temp.11 = (overflowing %temp.11 - uint32 0)
         // CHECK: ty:bytes %b = %abi_encoded.temp.10
 	    // CHECK: return %b
 
@@ -87,65 +89,65 @@ contract Testing {
         uint16[][] memory vec = abi.decode(bb, uint16[][]);
         return vec;
 
-	    // CHECK: ty:uint32 %temp.14 = (builtin ArrayLength ((arg #0)))
-	    // CHECK: ty:uint32 %temp.16 = uint32 0
-	    // CHECK: ty:uint32 %temp.17 = (builtin ReadFromBuffer ((arg #0), uint32 0))
-	    // CHECK: branchcond (unsigned uint32 4 <= %temp.14), block1, block2
+	    // CHECK: ty:uint32 %temp.16 = (builtin ArrayLength ((arg #0)))
+	    // CHECK: ty:uint32 %temp.18 = uint32 0
+	    // CHECK: ty:uint32 %temp.19 = (builtin ReadFromBuffer ((arg #0), uint32 0))
+	    // CHECK: branchcond (unsigned uint32 4 <= %temp.16), block1, block2
         
         // CHECK: block1: # inbounds
-	    // CHECK: ty:uint32 %temp.16 = uint32 4
-	    // CHECK: ty:uint16[][] %temp.18 = (alloc uint16[][] len %temp.17)
-	    // CHECK: ty:uint16[][] %temp.15 = %temp.18
-	    // CHECK: ty:uint32 %for_i_1.temp.19 = uint32 0
+	    // CHECK: ty:uint32 %temp.18 = uint32 4
+	    // CHECK: ty:uint16[][] %temp.20 = (alloc uint16[][] len %temp.19)
+	    // CHECK: ty:uint16[][] %temp.17 = %temp.20
+	    // CHECK: ty:uint32 %for_i_1.temp.21 = uint32 0
 	    // CHECK: branch block3
 
         // CHECK: block2: # out_of_bounds
 	    // CHECK: assert-failure
 
         // CHECK: block3: # cond
-	    // CHECK: branchcond (unsigned less %for_i_1.temp.19 < (builtin ArrayLength (%temp.15))), block5, block6
+	    // CHECK: branchcond (unsigned less %for_i_1.temp.21 < (builtin ArrayLength (%temp.17))), block5, block6
 
         // CHECK: block4: # next
-	    // CHECK: ty:uint32 %for_i_1.temp.19 = (%for_i_1.temp.19 + uint32 1)
+	    // CHECK: ty:uint32 %for_i_1.temp.21 = (%for_i_1.temp.21 + uint32 1)
         // CHECK: branch block3
 
         // CHECK: block5: # body
-	    // CHECK: ty:uint32 %temp.20 = (builtin ReadFromBuffer ((arg #0), %temp.16))
-	    // CHECK: ty:uint32 %1.cse_temp = (%temp.16 + uint32 4)
-	    // CHECK: branchcond (unsigned %1.cse_temp <= %temp.14), block7, block8
+	    // CHECK: ty:uint32 %temp.22 = (builtin ReadFromBuffer ((arg #0), %temp.18))
+	    // CHECK: ty:uint32 %1.cse_temp = (%temp.18 + uint32 4)
+	    // CHECK: branchcond (unsigned %1.cse_temp <= %temp.16), block7, block8
 
         // CHECK: block6: # end_for
-	    // CHECK: ty:uint32 %temp.16 = (%temp.16 - uint32 0)
-	    // CHECK: branchcond (unsigned less (uint32 0 + %temp.16) < %temp.14), block15, block16
+	    // CHECK: ty:uint32 %temp.18 = (overflowing %temp.18 - uint32 0)
+	    // CHECK: branchcond (unsigned less (uint32 0 + %temp.18) < %temp.16), block15, block16
 
         // CHECK: block7: # inbounds
-	    // CHECK: ty:uint32 %temp.16 = %1.cse_temp
-	    // CHECK: ty:uint16[] %temp.21 = (alloc uint16[] len %temp.20)
-	    // CHECK: store (subscript uint16[][] %temp.15[%for_i_1.temp.19]), %temp.21
-	    // CHECK: ty:uint32 %for_i_0.temp.22 = uint32 0
+	    // CHECK: ty:uint32 %temp.18 = %1.cse_temp
+	    // CHECK: ty:uint16[] %temp.23 = (alloc uint16[] len %temp.22)
+	    // CHECK: store (subscript uint16[][] %temp.17[%for_i_1.temp.21]), %temp.23
+	    // CHECK: ty:uint32 %for_i_0.temp.24 = uint32 0
 	    // CHECK: branch block9
 
         // CHECK: block8: # out_of_bounds
 	    // CHECK: assert-failure
 
         // CHECK: block9: # cond
-	    // CHECK: branchcond (unsigned less %for_i_0.temp.22 < (builtin ArrayLength ((load (subscript uint16[][] %temp.15[%for_i_1.temp.19]))))), block11, block12
+	    // CHECK: branchcond (unsigned less %for_i_0.temp.24 < (builtin ArrayLength ((load (subscript uint16[][] %temp.17[%for_i_1.temp.21]))))), block11, block12
 
         // CHECK: block10: # next
-	    // CHECK: ty:uint32 %for_i_0.temp.22 = (%for_i_0.temp.22 + uint32 1)
+	    // CHECK: ty:uint32 %for_i_0.temp.24 = (%for_i_0.temp.24 + uint32 1)
 	    // CHECK: branch block9
 
         // CHECK: block11: # body
-	    // CHECK: ty:uint32 %2.cse_temp = (%temp.16 + uint32 2)
-	    // CHECK: branchcond (unsigned %2.cse_temp <= %temp.14), block13, block14
+	    // CHECK: ty:uint32 %2.cse_temp = (%temp.18 + uint32 2)
+	    // CHECK: branchcond (unsigned %2.cse_temp <= %temp.16), block13, block14
 
         // CHECK: block12: # end_for
 	    // CHECK: branch block4
 
         // CHECK: block13: # inbounds
-	    // CHECK: ty:uint16 %temp.23 = (builtin ReadFromBuffer ((arg #0), %temp.16))
-	    // CHECK: store (subscript uint16[] (load (subscript uint16[][] %temp.15[%for_i_1.temp.19]))[%for_i_0.temp.22]), %temp.23
-	    // CHECK: ty:uint32 %temp.16 = %2.cse_temp
+	    // CHECK: ty:uint16 %temp.25 = (builtin ReadFromBuffer ((arg #0), %temp.18))
+	    // CHECK: store (subscript uint16[] (load (subscript uint16[][] %temp.17[%for_i_1.temp.21]))[%for_i_0.temp.24]), %temp.25
+	    // CHECK: ty:uint32 %temp.18 = %2.cse_temp
 	    // CHECK: branch block10
 
         // CHECK: block14: # out_of_bounds
@@ -155,7 +157,7 @@ contract Testing {
 	    // CHECK: assert-failure
 
         // CHECK: block16: # buffer_read
-	    // CHECK: ty:uint16[][] %vec = %temp.15
+	    // CHECK: ty:uint16[][] %vec = %temp.17
 	    // CHECK: return %vec
     }
 }
\ No newline at end of file
@@ -16,10 +16,14 @@ contract Foo {
 contract Cat is My.Dog {
     // BEGIN-CHECK: Cat::Cat::function::try_cat
     function try_cat() public pure {
-        // Internal calls
+        // Internal calls are inlined
         My.Dog.barks("woof");
-        // CHECK: Cat::Dog::function::barks__string (alloc string uint32 4 "woof")
+        // CHECK: ty:string %what.inline.13 = (alloc slice bytes1 uint32 4 "woof")
+        // CHECK: block1: # barks.entry
+        // CHECK: print %what.inline.13
         My.Dog.barks({what: "meow"});
-        // CHECK: Cat::Dog::function::barks__string (alloc string uint32 4 "meow")
+        // CHECK: ty:string %what.inline.15 = (alloc slice bytes1 uint32 4 "meow")
+        // CHECK: block3: # barks.entry
+        // CHECK: print %what.inline.15
     }
 }
\ No newline at end of file
@@ -0,0 +1,56 @@
+// RUN: --target polkadot --emit cfg
+
+contract Inline {
+    uint64 count;
+
+    function getCount() internal view returns (uint64) {
+        return count;
+    }
+
+    function pick(uint64 a, uint64 b) internal pure returns (uint64 r) {
+        if (a > b) {
+            r = a;
+        } else {
+            r = b;
+        }
+    }
+
+    function fib(uint64 n) internal pure returns (uint64) {
+        if (n < 2) {
+            return n;
+        }
+
+        return fib(n - 1) + fib(n - 2);
+    }
+
+    // BEGIN-CHECK: Inline::Inline::function::getter
+    function getter() public view returns (uint64) {
+        // CHECK: branch block1
+        // CHECK: block1: # getCount.entry
+        // CHECK: load storage slot(uint256 0) ty:uint64
+        // CHECK: branch block2
+        // CHECK: block2: # getCount.return
+        // CHECK: return
+        return getCount();
+    }
+
+    // BEGIN-CHECK: Inline::Inline::function::maximum__uint64_uint64
+    function maximum(uint64 x, uint64 y) public pure returns (uint64) {
+        // CHECK: ty:uint64 %a.inline
+        // CHECK: ty:uint64 %b.inline
+        // CHECK: block1: # pick.entry
+        // CHECK: block2: # pick.then
+        // CHECK: block3: # pick.else
+        // CHECK: block4: # pick.endif
+        // CHECK: block5: # pick.return
+        // CHECK: # phis:
+        return pick(x, y);
+    }
+
+    // recursive functions are never inlined
+    // BEGIN-CHECK: Inline::Inline::function::fibonacci__uint64
+    function fibonacci(uint64 n) public pure returns (uint64) {
+        // CHECK: call Inline::Inline::function::fib__uint64 (arg #0)
+        return fib(n);
+    }
+}
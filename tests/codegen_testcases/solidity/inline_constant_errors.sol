@@ -0,0 +1,25 @@
+// RUN: --target polkadot --emit cfg
+
+contract c {
+    // The calls are inlined with constant arguments. Division by zero and overflow only happen
+    // at runtime, so they must not be folded into compile errors.
+    // BEGIN-CHECK: c::c::function::f__bool
+    function f(bool cond) public pure returns (uint) {
+        // CHECK: block3: # div.entry
+        // CHECK: ty:uint256 %.temp.8 = (unsigned divide %a.inline.13 / %b.inline.14)
+        if (cond) return div(1, 0);
+        // CHECK: block5: # add.entry
+        // CHECK: ty:uint8 %.temp.9 = (%a.inline.18 + %b.inline.19)
+        return add(200, 100);
+        // CHECK-ABSENT: divide by zero
+        // CHECK-ABSENT: does not fit into type
+    }
+
+    function div(uint a, uint b) internal pure returns (uint) {
+        return a / b;
+    }
+
+    function add(uint8 a, uint8 b) internal pure returns (uint8) {
+        return a + b;
+    }
+}
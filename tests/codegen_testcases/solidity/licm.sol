@@ -0,0 +1,48 @@
+// RUN: --target polkadot --emit cfg
+
+contract licm {
+    uint256[] values;
+    mapping(address => uint256) weights;
+
+    // BEGIN-CHECK: licm::licm::function::sum_weights__address
+    function sum_weights(address user) public view returns (uint256 sum) {
+        // CHECK: block0: # entry
+        // CHECK: %temp.9 = load storage slot(uint256 0) ty:uint256
+        // CHECK: ty:mapping(address => uint256) storage %licm.temp.12 = (keccak256 uint256 1, (arg #0))
+        // CHECK: branch block2
+        // CHECK: block1: # body
+        // CHECK: %temp.10 = load storage slot(%licm.temp.12) ty:uint256
+        // CHECK: block2: # cond
+        // CHECK: branchcond (unsigned less %i < %temp.9), block1, block4
+        for (uint256 i = 0; i < values.length; i++) {
+            sum += weights[user] + i;
+        }
+    }
+
+    // BEGIN-CHECK: licm::licm::function::store_values__uint256
+    function store_values(uint256 x) public {
+        // the loop writes to storage, so the length is loaded on each iteration
+        // CHECK: block2: # cond
+        // CHECK: %temp.13 = load storage slot(uint256 0) ty:uint256
+        // CHECK: branchcond (unsigned less %i < %temp.13), block1, block4
+        for (uint256 i = 0; i < values.length; i++) {
+            values[i] = x;
+        }
+    }
+
+    // BEGIN-CHECK: licm::licm::function::checked__uint256_uint256
+    function checked(uint256 x, uint256 n) public pure returns (uint256 sum) {
+        // CHECK: ty:uint256 %licm.temp.19 = (overflowing (arg #0) * uint256 3)
+        // CHECK: branch block2
+        // CHECK: block1: # body
+        // CHECK: ty:uint256 %sum = (overflowing %sum + %licm.temp.19)
+        // checked arithmetic in the loop body might not be executed, so it stays in the loop
+        // CHECK: ty:uint256 %sum = (%sum + ((arg #0) + uint256 1))
+        for (uint256 i = 0; i < n; i++) {
+            unchecked {
+                sum += x * 3;
+            }
+            sum += x + 1;
+        }
+    }
+}
@@ -0,0 +1,17 @@
+// RUN: --target solana --emit cfg
+
+contract raw_memory {
+    // BEGIN-CHECK: raw_memory::raw_memory::function::raw_write__uint256:
+    function raw_write(uint256[] memory a) public pure returns (uint256 sum) {
+        // mstore can overwrite the length of the array, so it is not hoisted
+        // CHECK: block2: # cond
+        // CHECK: branchcond (unsigned less %i < (zext uint256 (builtin ArrayLength ((arg #0))))), block1, block4
+        // NOT-CHECK: licm.temp
+        for (uint256 i = 0; i < a.length; i++) {
+            assembly {
+                mstore(a, 0)
+            }
+            sum += a[i];
+        }
+    }
+}
@@ -1,4 +1,4 @@
-// RUN: --target polkadot --emit cfg
+// RUN: --target polkadot --emit cfg --no-inline
 contract Ownable {
     uint256 public _ext;
 
@@ -7,4 +7,8 @@ contract Ownable {
         // CHECK: return (builtin Calldata ())
         return msg.data;
     }
+
+    function msgData() public view returns (bytes memory) {
+        return _msgData();
+    }
 }
@@ -0,0 +1,65 @@
+// RUN: --target polkadot --emit cfg
+contract Packed {
+    bool a;
+    uint8 b;
+    int16 c;
+    uint256 big;
+    bool d;
+    uint64 e;
+    bytes31 f;
+    bytes2 g;
+    uint64 h;
+
+    // BEGIN-CHECK: Packed::Packed::function::set
+    function set() public {
+        // CHECK: store storage slot(uint256 0 offset 0) ty:bool = true
+        a = true;
+        // CHECK: store storage slot(uint256 0 offset 1) ty:uint8 = uint8 2
+        b = 2;
+        // CHECK: store storage slot(uint256 0 offset 2) ty:int16 = int16 -3
+        c = -3;
+        // CHECK: store storage slot(uint256 1) ty:uint256 = uint256 4
+        big = 4;
+        // CHECK: store storage slot(uint256 2 offset 0) ty:bool = true
+        d = true;
+        // CHECK: clear storage slot(uint256 2 offset 1) ty:uint64
+        delete e;
+        // bytes31 does not fit with anything else
+        // CHECK: store storage slot(uint256 3) ty:bytes31
+        f = "abc";
+        // CHECK: store storage slot(uint256 4 offset 0) ty:bytes2 = hex"6162"
+        g = "ab";
+        // CHECK: store storage slot(uint256 4 offset 2) ty:uint64 = uint64 7
+        h = 7;
+    }
+
+    // BEGIN-CHECK: Packed::Packed::function::get
+    function get() public view returns (bool, uint8, int16, bool, uint64, bytes31, bytes2, uint64, uint256) {
+        // CHECK: load storage slot(uint256 0 offset 0) ty:bool
+        // CHECK: load storage slot(uint256 0 offset 1) ty:uint8
+        // CHECK: load storage slot(uint256 0 offset 2) ty:int16
+        return (a, b, c, d, e, f, g, h, big);
+    }
+
+    // BEGIN-CHECK: Packed::Packed::function::offsets
+    function offsets() public pure returns (uint256 s, uint256 o) {
+        assembly {
+            s := e.slot
+            o := e.offset
+        }
+        // CHECK: return uint256 2, uint256 1
+    }
+}
+
+contract Alone {
+    uint8 a;
+    uint256 b;
+
+    // BEGIN-CHECK: Alone::Alone::function::set
+    function set() public {
+        // a small value with a slot to itself is not packed
+        // CHECK: store storage slot(uint256 0) ty:uint8 = uint8 1
+        a = 1;
+        b = 2;
+    }
+}
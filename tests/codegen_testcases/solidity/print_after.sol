@@ -0,0 +1,15 @@
+// RUN: --target polkadot --emit cfg --print-after constant-folding --print-after strength-reduce
+
+contract c {
+    function test(uint a) public pure returns (uint) {
+        uint b = 2 * 50;
+        return (a & 255) * b;
+    }
+}
+
+// CHECK: # *** CFG after constant-folding ***
+// CHECK: # function c::c::function::test__uint256
+// CHECK: return (((arg #0) & uint256 255) * uint256 100)
+// CHECK: # *** CFG after strength-reduce ***
+// CHECK: # function c::c::function::test__uint256
+// CHECK: return (zext uint256 (overflowing (trunc uint64 ((arg #0) & uint256 255)) * uint64 100))
@@ -0,0 +1,42 @@
+// RUN: --target solana --emit removed-checks
+
+contract c {
+    // CHECK: # function c::c::function::sum__uint64:
+    function sum(uint64[] memory a) public pure returns (uint64 total) {
+        for (uint i = 0; i < a.length; i++) {
+            // CHECK: removed_checks.sol:6:40-43: overflow check removed: implied by condition at
+            // CHECK: removed_checks.sol:9:22-26: bounds check removed: implied by condition at
+            total += a[i] & 0xffff;
+        }
+    }
+
+    // CHECK: # function c::c::function::countdown__uint256
+    function countdown(uint n) public pure returns (uint s) {
+        // CHECK: removed_checks.sol:16:33-36: overflow check removed: implied by condition at
+        for (uint i = n; i > 0; i--) {
+            s ^= i;
+        }
+    }
+
+    // CHECK: # function c::c::function::small__uint256
+    function small(uint a) public pure returns (uint) {
+        // CHECK: removed_checks.sol:25:16-35: overflow check removed: value range
+        // CHECK: removed_checks.sol:25:16-39: overflow check removed: value range
+        return (a & 0xffff) * 1000 + 5;
+    }
+
+    // mstore can overwrite the length of the array, so the bounds check is needed
+    // CHECK-ABSENT: removed_checks.sol:33:18-22: bounds check removed
+    function raw_write(uint[] memory a) public pure returns (uint s) {
+        for (uint i = 0; i < a.length; i++) {
+            assembly { mstore(a, 0) }
+            s += a[i];
+        }
+    }
+
+    // the overflow check is needed here
+    // CHECK-ABSENT: c::c::function::add__uint256_uint256
+    function add(uint a, uint b) public pure returns (uint) {
+        return a + b;
+    }
+}
@@ -0,0 +1,17 @@
+// RUN: --target polkadot --emit cfg
+
+contract Foo {
+    error Insufficient(uint256 available, uint256 required);
+
+    // BEGIN-CHECK: Foo::Foo::function::test__uint256
+    function test(uint256 c) public pure {
+        // CHECK: branchcond (unsigned more (arg #0) > uint256 10), block1, block2
+        // CHECK: block2: # doassert
+        // CHECK: print
+        // CHECK: writebuffer buffer:%abi_encoded.temp.1 offset:uint32 0 value:bytes4 3898738688
+        // CHECK: writebuffer buffer:%abi_encoded.temp.1 offset:uint32 4 value:(arg #0)
+        // CHECK: writebuffer buffer:%abi_encoded.temp.1 offset:uint32 36 value:uint256 10
+        // CHECK: assert-failure: buffer: %abi_encoded.temp.1
+        require(c > 10, Insufficient(c, 10));
+    }
+}
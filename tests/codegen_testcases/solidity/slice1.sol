@@ -27,8 +27,8 @@ contract c {
 		bytes x = "foo3";
 
 		foo(x);
-		// no slices for function arguments yet, so it must be a vector
-// CHECK: alloc bytes uint32 4 "foo3"
+		// foo is inlined, so x is no longer passed to a function and it can be a slice
+// CHECK: alloc slice bytes1 uint32 4 "foo3"
 	}
 
 
@@ -26,11 +26,13 @@ contract Other is Foo {
     }
     // BEGIN-CHECK: Other::Other::function::call_foo__address
     function call_foo(address id) external {
-        // internal calls
+        // internal calls are inlined
         Foo.get_b(id);
-        // CHECK: call Other::Foo::function::get_b__address (arg #0)
+        // CHECK: block1: # get_b.entry
+        // CHECK: %temp.30.inline.42 = load storage slot(uint32 16) ty:uint256
         Foo.get_b2({id: id});
-        // CHECK: call Other::Foo::function::get_b2__address (arg #0)
+        // CHECK: block3: # get_b2.entry
+        // CHECK: ty:uint256 %.temp.34 = %temp.31.inline.46
     }
     // BEGIN-CHECK: Other::Other::function::call_foo2__address_address
     function call_foo2(address id, address acc) external {
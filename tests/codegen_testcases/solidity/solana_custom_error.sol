@@ -0,0 +1,30 @@
+// RUN: --target solana --emit cfg
+
+contract vault {
+    error Unauthorized();
+    error InsufficientBalance(uint64 available, uint64 required);
+
+// BEGIN-CHECK: vault::vault::function::withdraw__uint64_uint64
+    function withdraw(uint64 amount, uint64 balance) public pure {
+        if (amount > balance) {
+            // CHECK: writebuffer buffer:%abi_encoded.temp.4 offset:uint32 0 value:bytes4 2486347758
+            // CHECK: writebuffer buffer:%abi_encoded.temp.4 offset:uint32 4 value:(arg #1)
+            // CHECK: writebuffer buffer:%abi_encoded.temp.4 offset:uint32 12 value:(arg #0)
+            // CHECK: assert-failure: buffer: %abi_encoded.temp.4 error code: 6001
+            revert InsufficientBalance(balance, amount);
+        }
+    }
+
+// BEGIN-CHECK: vault::vault::function::check__uint64_uint64
+    function check(uint64 amount, uint64 balance) public pure {
+        // CHECK: assert-failure: buffer: %abi_encoded.temp.5 error code: 6001
+        require(amount <= balance, InsufficientBalance({required: amount, available: balance}));
+    }
+
+// BEGIN-CHECK: vault::vault::function::deny
+    function deny() public pure {
+        // CHECK: writebuffer buffer:%abi_encoded.temp.6 offset:uint32 0 value:bytes4 2192845056
+        // CHECK: assert-failure: buffer: %abi_encoded.temp.6 error code: 6000
+        revert Unauthorized();
+    }
+}
@@ -0,0 +1,35 @@
+// RUN: --target solana --emit cfg
+
+@program_id("5kQ3iJ43gHNDjqmSAtE1vDu18CiSAfNbRe4v5uoobh3U")
+contract Callee {
+// BEGIN-CHECK: Callee::Callee::function::get__int64
+    function get(int64 a) public pure returns (int64) {
+        // CHECK: writebuffer buffer:%abi_encoded.temp.7 offset:uint32 0 value:bytes4 147028384
+        // CHECK: assert-failure: buffer: %abi_encoded.temp.7
+        require(a > 0, "negative");
+        return a * 2;
+    }
+}
+
+contract Caller {
+// BEGIN-CHECK: Caller::Caller::function::call__int64
+    function call(int64 a) external returns (int64 r) {
+        // CHECK: %success.temp.17 = external call::regular address:(load (struct function(int64) external pure returns (int64)(struct { hex"a1e0323d05d27ad8", address 0x468cc81387e597f267c81a7f02173eea8dd15966a1481f089b7e477d975ce91f }) field 1)) payload:%abi_encoded.temp.18 value:uint64 0 gas:uint64 0 accounts:[0] [  ] seeds: contract|function:(0, 2) flags:
+        // CHECK: branchcond %success.temp.17, block4, block5
+        // CHECK: block4: # call_success
+        // CHECK: block5: # call_failed
+        // CHECK: ty:bytes %error_ret_data.temp.19 = (external call return data)
+        // CHECK: ty:int64 %temp.21 = (builtin ReadFromBuffer ((external call return data), uint32 0))
+        // CHECK: block11: # switch_on_err_id
+        // CHECK: case bytes4 147028384: goto block #12
+        // CHECK: ty:uint32 %temp.24 = (builtin ReadFromBuffer (%error_ret_data.temp.19, uint32 4))
+        try Callee.get(a) returns (int64 v) {
+            r = v;
+        } catch Error(string reason) {
+            print(reason);
+            r = -1;
+        } catch (bytes raw) {
+            r = -2;
+        }
+    }
+}
@@ -0,0 +1,85 @@
+// RUN: --target polkadot --emit cfg
+
+contract storage_cache {
+    struct Point {
+        uint64 x;
+        uint64 y;
+    }
+
+    uint256 total;
+    Point point;
+    mapping(address => uint256) balances;
+
+    // BEGIN-CHECK: storage_cache::storage_cache::function::deposit__uint256
+    function deposit(uint256 amount) public {
+        // CHECK: %temp.7 = load storage slot(uint256 0) ty:uint256
+        // CHECK: ty:uint256 %storage.temp.14 = %temp.7
+        // CHECK: ty:uint256 %storage.temp.14 = %temp.8
+        // CHECK: block1: # then
+        // CHECK: ty:uint256 %temp.9 = %storage.temp.14
+        // CHECK: ty:uint256 %storage.temp.14 = %temp.10
+        // CHECK: block2: # endif
+        // CHECK: # phis: storage.temp.14
+        // CHECK: ty:uint64 %storage.temp.15 = uint64 1
+        // CHECK: ty:uint64 %temp.12 = %storage.temp.15
+        // CHECK: ty:uint64 %storage.temp.15 = %temp.13
+        // CHECK: store storage slot(uint256 0) ty:uint256 = %storage.temp.14
+        // CHECK: store storage slot(uint256 1) ty:uint64 = %storage.temp.15
+        // CHECK: return
+        balances[msg.sender] += amount;
+        total += amount;
+        if (amount > 100) {
+            total += 1;
+        }
+        point.x = 1;
+        point.x += 2;
+    }
+
+    // BEGIN-CHECK: storage_cache::storage_cache::function::accumulate__uint256
+    function accumulate(uint256 n) public {
+        // the value is loaded before the loop, and stored after it
+        // CHECK: block0: # entry
+        // CHECK: %storage.temp.19 = load storage slot(uint256 0) ty:uint256
+        // CHECK: block1: # body
+        // CHECK: ty:uint256 %temp.16 = %storage.temp.19
+        // CHECK: ty:uint256 %storage.temp.19 = %temp.17
+        // CHECK: block2: # cond
+        // CHECK: # phis: i,storage.temp.19
+        // CHECK: block4: # endfor
+        // CHECK: store storage slot(uint256 0) ty:uint256 = %storage.temp.19
+        for (uint256 i = 0; i < n; i++) {
+            total += i;
+        }
+    }
+
+    // BEGIN-CHECK: storage_cache::storage_cache::function::notify__storage_cache
+    function notify(storage_cache other) public {
+        // the called contract may read or modify total
+        // CHECK: ty:uint256 %storage.temp.30 = %temp.23
+        // CHECK: store storage slot(uint256 0) ty:uint256 = %storage.temp.30
+        // CHECK: external call::regular address:(arg #0)
+        // CHECK: block1: # ret_success
+        // CHECK: %temp.26 = load storage slot(uint256 0) ty:uint256
+        // CHECK: ty:uint256 %storage.temp.30 = %temp.26
+        // CHECK: ty:uint256 %temp.28 = %storage.temp.30
+        // CHECK: store storage slot(uint256 0) ty:uint256 = %storage.temp.30
+        // CHECK: return
+        total += 1;
+        total += 2;
+        other.accumulate(1);
+        total += 3;
+        total += 4;
+    }
+
+    // BEGIN-CHECK: storage_cache::storage_cache::function::check__uint256
+    function check(uint256 amount) public {
+        // CHECK: ty:uint256 %temp.33 = %storage.temp.36
+        // CHECK: block1: # noassert
+        // CHECK: store storage slot(uint256 0) ty:uint256 = %storage.temp.36
+        // CHECK: block2: # doassert
+        // NOT-CHECK: store storage slot(uint256 0)
+        total += amount;
+        require(total < 1000, "too much");
+        total += 1;
+    }
+}
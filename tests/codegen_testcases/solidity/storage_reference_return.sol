@@ -1,4 +1,4 @@
-// RUN: --target solana --emit cfg
+// RUN: --target solana --emit cfg --no-inline
 
 contract foo {
     struct S { int f1; }
@@ -22,6 +22,12 @@ contract foo {
         // NOT-CHECK: load storage
         return (arr[0], arr[1]);
     }
+
+    function callAll() public {
+        g();
+        h();
+        retTwo();
+    }
 }
 
 contract c {
@@ -48,4 +54,9 @@ contract c {
         // NOT-CHECK: load storage
         return (x, y);
     }
+
+    function callAll() public {
+        test();
+        test2(a, b);
+    }
 }
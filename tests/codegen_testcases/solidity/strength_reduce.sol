@@ -10,7 +10,7 @@ contract test {
             // this multiply can be done with a 64 bit instruction
             print("i:{}".format(i * 100));
         }
-// CHECK: zext uint256 ((trunc uint64 %i) * uint64 100)
+// CHECK: zext uint256 (overflowing (trunc uint64 %i) * uint64 100)
     }
 
 // BEGIN-CHECK: test::function::f2
@@ -37,7 +37,7 @@ contract test {
             if (x)
                 break;
         }
-// CHECK: (zext uint256 ((trunc uint64 (%i & uint256 255)) * uint64 100))
+// CHECK: (zext uint256 (overflowing (trunc uint64 (%i & uint256 255)) * uint64 100))
     }
 
 // BEGIN-CHECK: test::function::f4
@@ -50,10 +50,10 @@ contract test {
         }
 // CHECK: (%i << uint256 15)
         for (uint i = 0; i < 10; i++) {
-            // Do not disable overflow checks
+            // i is less than 10 so this cannot overflow; the overflow check is removed
             print("i:{}".format(i * 32768));
         }
-// NOT CHECK: (%i << uint256 15)
+// CHECK: (%i.6 << uint256 15)
     }
 
 // BEGIN-CHECK: test::function::f5
@@ -0,0 +1,41 @@
+// RUN: --target solana --emit cfg
+
+contract Base {
+    function baseHelper() internal pure returns (uint64) {
+        return 1;
+    }
+
+    function overridden() public virtual returns (uint64) {
+        return 2;
+    }
+}
+
+contract Unreachable is Base {
+    function used(uint64 a) internal pure returns (uint64) {
+        return a * 3;
+    }
+
+    // only called from an unreachable function
+    function deadCaller(uint64 a) private pure returns (uint64) {
+        return deadCallee(a) + 1;
+    }
+
+    function deadCallee(uint64 a) private pure returns (uint64) {
+        return a + 2;
+    }
+
+    function overridden() public override returns (uint64) {
+        return used(5);
+    }
+
+    // BEGIN-CHECK: # function Unreachable::Unreachable::function::overridden public:true
+    // CHECK-ABSENT: function::deadCaller
+    // CHECK-ABSENT: function::deadCallee
+    // CHECK-ABSENT: function::baseHelper
+    // CHECK-ABSENT: # function Unreachable::Base::function::overridden
+
+    // CHECK: contract 'Unreachable' has unreachable functions which are not included in the output
+    // CHECK: function 'baseHelper' is never called
+    // CHECK: function 'deadCaller' is never called
+    // CHECK: function 'deadCallee' is never called
+}
@@ -55,7 +55,9 @@ contract c {
         x = 102 + (t * y) / (t + 5 * y) + g + test3();
         return 2;
         // NOT-CHECK: ty:int32 %x = (int32 103 + %temp.6)
-        // CHECK: call c::c::function::test3
+        // test3 is inlined
+        // CHECK: block1: # test3.entry
+        // CHECK: ty:int32 %.temp.88 = int32 2
     }
 
     // BEGIN-CHECK: c::function::test5
@@ -74,9 +76,11 @@ contract c {
             vec.push(2) +
             ct.sum(1, 2);
         return 2;
+        // CHECK: block10: # buffer_read
+        // CHECK: return int32 2
+        // CHECK: block11: # test3.entry
         // CHECK: push array ty:int32[] value:int32 2
         // CHECK: external call::regular address:%ct
-        // CHECK: return int32 2
     }
 }
 
@@ -86,13 +90,13 @@ contract c3 {
         c2 ct = new c2();
 
         return 3;
-        // CHECK: constructor(no: ) salt: value: gas:uint64 0 address: seeds: c2 encoded buffer: %abi_encoded.temp.120 accounts:
+        // CHECK: constructor(no: ) salt: value: gas:uint64 0 address: seeds: c2 encoded buffer: %abi_encoded.temp.128 accounts:
     }
 
     // BEGIN-CHECK: c3::function::test7
     function test7() public returns (int32) {
         c2 ct = new c2();
-        // constructor salt: value: gas:uint64 0 address: seeds: c2 (encoded buffer: %abi_encoded.temp.123, buffer len: uint32 4)
+        // constructor salt: value: gas:uint64 0 address: seeds: c2 (encoded buffer: %abi_encoded.temp.131, buffer len: uint32 4)
         address ad = address(ct);
         (bool p, ) = ad.call(hex"ba");
         // CHECK: external call::regular address:%ad payload:(alloc bytes uint32 1 hex"ba") value:uint128 0 gas:uint64 0
@@ -143,7 +147,7 @@ contract c3 {
         int f = 4;
 
         int c = 32 + 4 * (f = it1 + it2);
-        // CHECK: ty:int256 %c = (int256 32 + (sext int256 (int64 4 * (trunc int64 (%temp.130 + %temp.131)))))
+        // CHECK: ty:int256 %c = (int256 32 + (sext int256 (int64 4 * (trunc int64 (%temp.138 + %temp.139)))))
         // NOT-CHECK: ty:int256 %f = (%temp.
         return c;
     }
@@ -183,7 +187,7 @@ contract c3 {
     function test14() public returns (int) {
         int[] storage ptrArr = testArr;
 
-        // CHECK: store storage slot(%temp.154) ty:int256 storage = int256 3
+        // CHECK: store storage slot(%temp.162) ty:int256 storage = int256 3
         ptrArr.push(3);
 
         return ptrArr[0];
@@ -129,7 +129,7 @@ contract testing {
             // CHECK: ty:uint256 %k = uint256 16
             let k := storage_vec.slot
 
-            // CHECK: ty:uint256 %l = uint32 20
+            // CHECK: ty:uint256 %l = uint256 20
             let l := l_storage_vec.slot
 
             // CHECK: ty:uint256 %m = uint256 0
@@ -169,17 +169,21 @@ contract testing {
             // CHECK: ty:int32 %k = (trunc int32 (overflowing (zext uint256 (arg #0)) + uint256 2))
             let k : s32 := add(a, 2)
 
-            // CHECK: %ret.temp.58 = call testing::yul_function_0::get (sext uint256 (trunc int32 (overflowing (zext uint256 (arg #0)) + uint256 2)))
+            // the yul functions are inlined
+            // CHECK: block1: # get.entry
+            // CHECK: ty:uint256 %ret.temp.58 = (overflowing (sext uint256 (trunc int32 (overflowing (zext uint256 (arg #0)) + uint256 2))) - uint256 2)
             let x := get(k)
-            // CHECK: ty:uint256 %x = %ret.temp.58
+            // CHECK: ty:uint256 %x = (overflowing (sext uint256 (trunc int32 (overflowing (zext uint256 (arg #0)) + uint256 2))) - uint256 2)
 
-            // CHECK: %ret1.temp.59, %ret2.temp.60 = call testing::yul_function_2::multipleReturns %x, (trunc int32 (overflowing (zext uint256 (arg #0)) + uint256 2))
+            // CHECK: block3: # multipleReturns.entry
+            // CHECK: ty:uint8 %ret1.temp.59 = %ret1.inline.66
+            // CHECK: ty:int64 %ret2.temp.60 = %ret2.inline.67
             let l, m := multipleReturns(x, k)
             // CHECK: ty:uint256 %l = (zext uint256 %ret1.temp.59)
             // CHECK: ty:uint256 %m = (sext uint256 %ret2.temp.60)
 
 
-            // CHECK: = call testing::yul_function_1::doSmth
+            // CHECK: block5: # doSmth.entry
             doSmth()
 
             function multipleReturns(a, v : s32) -> ret1 : u8, ret2 : s64 {
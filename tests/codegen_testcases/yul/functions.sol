@@ -1,4 +1,4 @@
-// RUN: --target solana --emit cfg -Onone --no-cse
+// RUN: --target solana --emit cfg --no-inline -Onone --no-cse
 
 contract testing {
     function yul_function() public pure returns (uint256) {
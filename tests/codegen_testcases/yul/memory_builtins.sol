@@ -0,0 +1,97 @@
+// RUN: --target solana --emit cfg -Onone --no-cse
+
+contract testing {
+// BEGIN-CHECK: testing::testing::function::load_store__uint256
+    function load_store(uint256 a) public pure returns (uint256 r) {
+        assembly {
+            // CHECK: ty:uint256 %temp.9 = (overflowing uint256 32 + (overflowing uint256((unsigned less uint256 32 < uint256 2176)) * uint256 12884932480))
+            // CHECK: branchcond (unsigned (overflowing %temp.9 - uint256 12884901888) <= uint256 32736), block1, block2
+            // CHECK: block1: # in_bounds
+            // CHECK: writebuffer buffer:buffer_pointer((trunc uint64 %temp.9)) offset:uint32 0 value:bytes32((arg #0))
+            mstore(0x20, a)
+
+            // CHECK: ty:uint256 %temp.10 = (overflowing uint256 256 + (overflowing uint256((unsigned less uint256 256 < uint256 2176)) * uint256 12884932480))
+            // CHECK: branchcond (unsigned (overflowing %temp.10 - uint256 12884901888) <= uint256 32767), block3, block4
+            // CHECK: block2: # out_of_bounds
+            // CHECK: assert-failure
+            // CHECK: writebuffer buffer:buffer_pointer((trunc uint64 %temp.10)) offset:uint32 0 value:(trunc uint8 (arg #0))
+            mstore8(0x100, a)
+
+            // CHECK: ty:uint256 %temp.12 = uint256((builtin ReadFromBuffer (buffer_pointer((trunc uint64 %temp.11)), uint32 0)))
+            // CHECK: ty:uint256 %r = %temp.12
+            r := mload(0x20)
+        }
+    }
+
+// BEGIN-CHECK: testing::testing::function::pointer__bytes
+    function pointer(bytes memory b) public pure returns (bytes32 r) {
+        assembly {
+            // CHECK: ty:uint256 %temp.13 = (overflowing (zext uint256 uint64((arg #0))) + (overflowing uint256((unsigned less (zext uint256 uint64((arg #0))) < uint256 2176)) * uint256 12884932480))
+            // CHECK: ty:uint256 %temp.14 = uint256((builtin ReadFromBuffer (buffer_pointer((trunc uint64 %temp.13)), uint32 0)))
+            r := mload(b)
+        }
+    }
+
+// BEGIN-CHECK: testing::testing::function::hash__uint256
+    function hash(uint256 a) public pure returns (bytes32 r) {
+        assembly {
+            // CHECK: ty:uint256 %temp.15 = (overflowing uint256 64 + (overflowing uint256((unsigned less uint256 64 < uint256 2176)) * uint256 12884932480))
+            // CHECK: ty:uint256 %temp.16 = uint256((builtin ReadFromBuffer (buffer_pointer((trunc uint64 %temp.15)), uint32 0)))
+            // CHECK: ty:uint256 %p = %temp.16
+            let p := msize()
+            mstore(p, a)
+
+            // CHECK: ty:uint256 %temp.18 = (overflowing %p + (overflowing uint256((unsigned less %p < uint256 2176)) * uint256 12884932480))
+            // CHECK: ty:bytes %temp.19 = (alloc bytes len uint32 32)
+            // CHECK: memcpy src: buffer_pointer((trunc uint64 %temp.18)), dest: %temp.19, bytes_len: uint32 32
+            // CHECK: ty:uint256 %temp.20 = uint256((builtin Keccak256 (%temp.19)))
+            r := keccak256(p, 32)
+        }
+    }
+
+// BEGIN-CHECK: testing::testing::function::call_data
+    function call_data() public pure returns (uint256 r) {
+        assembly {
+            // CHECK: ty:bytes %temp.22 = (builtin Calldata ())
+            // CHECK: branchcond (unsigned less (zext uint256 (builtin ArrayLength (%temp.21))) < (zext uint256 (builtin ArrayLength (%temp.22)))), block1, block2
+            // CHECK: block3: # done
+            // CHECK: branchcond (unsigned uint256 32 <= %temp.24), block4, block5
+            // CHECK: block4: # in_range
+            // CHECK: ty:uint256 %temp.23 = uint256((builtin ReadFromBuffer (%temp.22, (trunc uint32 (zext uint256 (builtin ArrayLength (%temp.21)))))))
+            // CHECK: block5: # out_of_range
+            // CHECK: ty:bytes %temp.25 = (alloc bytes len uint32 32)
+            // CHECK: memcpy src: (advance ptr: %temp.22, by: (trunc uint32 (zext uint256 (builtin ArrayLength (%temp.21))))), dest: %temp.25, bytes_len: (trunc uint32 %temp.24)
+            // CHECK: ty:uint256 %temp.23 = uint256((builtin ReadFromBuffer (%temp.25, uint32 0)))
+            // CHECK: block6: # done
+            // CHECK: # phis: temp.23
+            r := calldataload(calldatasize())
+
+            // CHECK: ty:uint256 %temp.26 = (overflowing uint256 0 + (overflowing uint256((unsigned less uint256 0 < uint256 2176)) * uint256 12884932480))
+            // CHECK: block12: # in_range
+            // CHECK: memcpy src: (advance ptr: %temp.27, by: uint32 4), dest: buffer_pointer((trunc uint64 %temp.26)), bytes_len: uint32 32
+            // CHECK: block13: # out_of_range
+            // CHECK: ty:bytes %temp.29 = (alloc bytes len uint32 32)
+            // CHECK: memcpy src: (advance ptr: %temp.27, by: uint32 4), dest: %temp.29, bytes_len: (trunc uint32 %temp.28)
+            // CHECK: memcpy src: %temp.29, dest: buffer_pointer((trunc uint64 %temp.26)), bytes_len: uint32 32
+            calldatacopy(0, 4, 32)
+        }
+    }
+
+// BEGIN-CHECK: testing::testing::function::return_data
+    function return_data() public pure returns (uint256 r) {
+        assembly {
+            // CHECK: ty:bytes %temp.30 = (external call return data)
+            // CHECK: ty:uint256 %r = (zext uint256 (builtin ArrayLength (%temp.30)))
+            r := returndatasize()
+
+            // the size is checked before the offset
+            // CHECK: branchcond (unsigned %r <= uint256 32768), block3, block2
+            // CHECK: block3: # size_in_bounds
+            // CHECK: branchcond (unsigned (overflowing %temp.31 - uint256 12884901888) <= (overflowing uint256 32768 - %r)), block1, block2
+            // CHECK: branchcond (unsigned %r <= %temp.33), block7, block8
+            // CHECK: block8: # out_of_range
+            // CHECK: assert-failure
+            returndatacopy(0, 0, r)
+        }
+    }
+}
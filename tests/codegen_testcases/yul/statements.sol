@@ -9,7 +9,8 @@ contract testing {
                 let y := mul(c, d)
             }
 
-            // CHECK:  = call testing::yul_function_0::test1 (zext uint128 (arg #0)), (zext uint256 (arg #1))
+            // CHECK: block1: # test1.entry
+            // CHECK: ty:uint256 %y.inline.47 = (overflowing (zext uint256 (zext uint128 (arg #0))) * (zext uint256 (arg #1)))
             test1(a, b)
 
             // CHECK: assert-failure
@@ -47,13 +48,16 @@ contract testing {
                 ret3 := 6
             }
 
-            // CHECK: %ret3.temp.40 = call testing::yul_function_2::unique
-            // CHECK: ty:uint256 %c = %ret3.temp.40
+            // CHECK: block1: # unique.entry
+            // CHECK: ty:uint256 %ret3.temp.40 = uint256 6
+            // CHECK: ty:uint256 %c = uint256 6
             let c := unique()
 
-            // CHECK: %ret1.temp.41, %ret2.temp.42 = call testing::yul_function_1::multiple
-            // CHECK: ty:uint256 %d = %ret1.temp.41
-            // CHECK: ty:int8 %e = (trunc int8 %ret2.temp.42)
+            // CHECK: block3: # multiple.entry
+            // CHECK: ty:uint256 %ret1.temp.41 = uint256 4
+            // CHECK: ty:int32 %ret2.temp.42 = int32 6
+            // CHECK: ty:uint256 %d = uint256 4
+            // CHECK: ty:int8 %e = int8 6
             let d, e : s8 := multiple()
 
             // CHECK: ty:uint256 %f = uint256(((arg #0) == bool 0))
@@ -330,6 +334,8 @@ contract testing {
                     // CHECK: block3: # body
                     let j := 2
                     // CHECK: ty:uint256 %j = uint256 2
+                    // i - 2 does not change in the inner loop
+                    // CHECK: ty:uint256 %licm.temp.43 = (overflowing %i - uint256 2)
                     // CHECK: branch block5
                 } lt(j, 10) {
                     // after outer for:
@@ -359,7 +365,7 @@ contract testing {
 
                     // After inner if:
                     // CHECK: block10: # endif
-                    // CHECK: ty:uint256 %j = (overflowing %i - uint256 2)
+                    // CHECK: ty:uint256 %j = %licm.temp.43
                     j := sub(i, 2)
                     // CHECK: branch block6
                 }
@@ -428,6 +434,7 @@ contract testing {
                     // CHECK: block3: # body
                     let j := 2
                     // CHECK: ty:uint256 %j = uint256 2
+                    // CHECK: ty:uint256 %licm.temp.44 = (overflowing %i - uint256 2)
                     // CHECK: branch block5
                 } lt(j, 10) {
                     // after outer for:
@@ -457,7 +464,7 @@ contract testing {
 
                     // After inner if:
                     // CHECK: block10: # endif
-                    // CHECK: ty:uint256 %j = (overflowing %i - uint256 2)
+                    // CHECK: ty:uint256 %j = %licm.temp.44
                     j := sub(i, 2)
                     // CHECK: branch block6
                 }
@@ -99,7 +99,7 @@ fn parse_file(path: PathBuf, target: Target) -> io::Result<()> {
             if contract.instantiable {
                 let code = match ns.target {
                     Target::Solana | Target::Polkadot { .. } => {
-                        contract.emit(&ns, &Default::default())
+                        contract.emit(&ns, &Default::default()).unwrap()
                     }
                     Target::EVM => b"beep".to_vec(),
                 };
@@ -678,6 +678,7 @@ function _approve(
     //
 }/**//**//**//**//**//**//**///
 // ---- Expect: diagnostics ----
+// warning: 11:17-24: solidity version '^0.6.12' is not satisfied by solidity language level 0.8.22
 // warning: 195:50-56: conversion truncates uint256 to uint128, as value is type uint128 on target EVM
 // warning: 268:17-25: function parameter 'weiValue' is unused
 // warning: 269:23-35: function parameter 'errorMessage' is unused
@@ -0,0 +1,25 @@
+pragma solidity ^0.8.0;
+pragma solidity >=0.7.0 <0.9.0;
+pragma solidity 0.8.22;
+pragma solidity >= 0.8.0;
+pragma solidity 0.8.0 - 0.8.30;
+pragma solidity ^0.4.24 || ^0.8.4;
+pragma solidity 0.8.x;
+pragma solidity ^0.7.6;
+pragma solidity <0.8.0 || >0.9.0;
+pragma solidity >=0.8.5 <0.8.5;
+pragma solidity ^0.8.1 <0.8.0 || 0.5 >0.6;
+pragma solidity >=0.8.foo;
+pragma solidity >= ;
+pragma solidity ^0.8.0 ||;
+
+contract c {}
+
+// ---- Expect: diagnostics ----
+// warning: 8:17-23: solidity version '^0.7.6' is not satisfied by solidity language level 0.8.22
+// warning: 9:17-33: solidity version '<0.8.0 || >0.9.0' is not satisfied by solidity language level 0.8.22
+// error: 10:17-31: solidity version '>=0.8.5 <0.8.5' cannot be satisfied by any version
+// error: 11:17-42: solidity version '^0.8.1 <0.8.0 || 0.5 >0.6' cannot be satisfied by any version
+// error: 12:17-26: invalid solidity version '>=0.8.foo': unexpected character 'f' while parsing patch version number
+// error: 13:17-19: invalid solidity version '>=': missing version after '>='
+// error: 14:17-26: invalid solidity version '^0.8.0 ||': missing version
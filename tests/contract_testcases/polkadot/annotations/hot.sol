@@ -0,0 +1,26 @@
+contract hot {
+	@hot(1)
+	constructor() {}
+	@hot(1)
+	modifier m() {_;}
+	@hot(1)
+	function i() internal {}
+	@hot(2)
+	@hot(3)
+	function dup() public {}
+	@hot(x)
+	function variable() public {}
+	@hot(0x100000000)
+	function big() public {}
+	@hot(100)
+	function ok() external {}
+}
+
+// ---- Expect: diagnostics ----
+// error: 2:2-9: @hot not permitted on constructor
+// error: 4:2-9: @hot not permitted on modifier
+// error: 6:2-9: @hot only permitted on 'public' or 'external' function, not 'internal'
+// error: 9:2-9: duplicate @hot annotation for function
+// 	note 8:2-9: previous @hot
+// error: 11:7-8: literal number expected
+// error: 13:7-18: value 4294967296 does not fit into type uint32.
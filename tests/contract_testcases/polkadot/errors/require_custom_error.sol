@@ -0,0 +1,20 @@
+contract A {
+    error Insufficient(uint256 available, uint256 required);
+
+    function a(uint256 x) public pure {
+        require(x > 10, Insufficient(x, 10));
+        require(x > 10, Insufficient({required: 10, available: x}));
+        require(x > 10, Insufficient(x));
+        require(x > 10, Insufficient({available: x, foo: 1}));
+        require(x, Insufficient(x, 10));
+    }
+}
+
+// ---- Expect: diagnostics ----
+// error: 7:25-37: error 'Insufficient' has 2 fields, 1 provided
+// 	note 2:11-23: definition of 'Insufficient'
+// error: 8:25-37: missing field 'required'
+// 	note 2:11-23: definition of 'Insufficient'
+// error: 8:53-56: error 'Insufficient' has no field called 'foo'
+// 	note 2:11-23: definition of 'Insufficient'
+// error: 9:17-18: conversion from uint256 to bool not possible
@@ -25,5 +25,6 @@ contract Contract {
 }
 
 // ---- Expect: diagnostics ----
+// warning: 2:17-23: solidity version '0.6.12' is not satisfied by solidity language level 0.8.22
 // warning: 20:26-34: local variable 'struct_1' has been assigned, but never read
 // warning: 20:51-55: conversion truncates uint256 to uint32, as memory size is type uint32 on target Solana
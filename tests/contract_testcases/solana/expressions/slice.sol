@@ -8,6 +8,10 @@ contract slice {
     }
 }
 // ---- Expect: diagnostics ----
+// warning: 3:5-35: function can be declared 'pure'
 // warning: 3:24-27: declaration of 'foo' shadows function
 // 	note 3:14-17: previous declaration of function
-// error: 4:20-27: slice not supported yet
+// warning: 4:15-17: local variable 'x1' is unused
+// warning: 5:15-17: local variable 'x2' is unused
+// warning: 6:15-17: local variable 'x3' is unused
+// warning: 7:15-17: local variable 'x4' is unused
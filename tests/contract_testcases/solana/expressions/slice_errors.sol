@@ -0,0 +1,45 @@
+contract slice {
+    bytes data;
+    uint64[] arr;
+
+    function storage_slice() public view returns (bytes memory) {
+        return data[1:];
+    }
+
+    function storage_array_slice() public view returns (uint64[] memory) {
+        return arr[:2];
+    }
+
+    function string_slice(string memory s) public pure returns (bytes memory) {
+        return s[1:2];
+    }
+
+    function fixed_slice(uint64[4] memory a) public pure returns (uint64[] memory) {
+        return a[1:2];
+    }
+
+    function bytes32_slice(bytes32 b) public pure returns (bytes memory) {
+        return b[1:2];
+    }
+
+    function reversed(bytes memory b) public pure returns (bytes memory) {
+        return b[3:1];
+    }
+
+    function signed(bytes memory b, int32 i) public pure returns (bytes memory) {
+        return b[i:];
+    }
+
+    function not_array(uint64 i) public pure returns (bytes memory) {
+        return i[1:];
+    }
+}
+// ---- Expect: diagnostics ----
+// error: 6:16-24: array slice is only supported on memory and calldata arrays
+// error: 10:16-23: array slice is only supported on memory and calldata arrays
+// error: 14:16-17: array slice is not permitted on string
+// error: 18:16-17: array slice is only supported on dynamic arrays, not 'uint64[4]'
+// error: 22:16-17: array slice is only supported on dynamic arrays, not 'bytes32'
+// error: 26:16-22: array slice start 3 is greater than end 1
+// error: 30:18-19: array slice bound must be an unsigned integer, not 'int32'
+// error: 34:16-17: expression is not an array
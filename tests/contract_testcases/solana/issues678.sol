@@ -56,4 +56,5 @@ contract Shares {
 }
 
 // ---- Expect: diagnostics ----
+// warning: 2:17-23: solidity version '^0.7.0' is not satisfied by solidity language level 0.8.22
 // error: 43:29-42: balance is not available on Solana. Use tx.accounts.account_name.lamports to fetch the balance.
@@ -8,4 +8,4 @@
             }
         }
 // ---- Expect: diagnostics ----
-// error: 5:44-55: builtin 'mload' is not available for target Solana. Please, open a GitHub issue at https://github.com/hyperledger/solang/issues if there is need to support this function
+// error: 6:21-59: builtin 'revert' is not available for target Solana. Please, open a GitHub issue at https://github.com/hyperledger/solang/issues if there is need to support this function
@@ -1,14 +1,1054 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use ethabi::Token;
+use primitive_types::{U256, U512};
 use rayon::prelude::*;
-use solang::{file_resolver::FileResolver, parse_and_resolve, sema::ast, Target};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use solang::{
+    codegen::{codegen, Options},
+    evm,
+    file_resolver::FileResolver,
+    parse_and_resolve,
+    sema::ast,
+    Target,
+};
 use std::{
+    collections::HashMap,
     ffi::OsStr,
     fs,
     path::{Path, PathBuf},
 };
+use tiny_keccak::{Hasher, Keccak};
 use walkdir::WalkDir;
 
+mod evm_tests;
+
+type Address = [u8; 20];
+
+/// Gas given to each top level call or deploy
+const TX_GAS: u64 = 1 << 40;
+
+/// Memory beyond this size is considered a bug in the generated code
+const MEMORY_LIMIT: usize = 1 << 24;
+
+/// An account in the mock EVM
+#[derive(Clone, Default)]
+pub struct Account {
+    pub code: Vec<u8>,
+    pub storage: HashMap<U256, U256>,
+    pub balance: U256,
+    nonce: u64,
+}
+
+/// An event emitted with one of the `LOG` instructions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    pub address: Address,
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+}
+
+/// A contract compiled from the test source, with the selectors of its functions
+pub struct Program {
+    pub name: String,
+    pub code: Vec<u8>,
+    pub messages: HashMap<String, Vec<u8>>,
+}
+
+/// How a call frame finished
+#[derive(Debug)]
+enum Halt {
+    Return(Vec<u8>),
+    Revert(Vec<u8>),
+    Invalid,
+}
+
+/// The execution environment of a call frame
+struct Frame {
+    address: Address,
+    caller: Address,
+    value: U256,
+    input: Vec<u8>,
+    code: Vec<u8>,
+    is_static: bool,
+    gas: u64,
+}
+
+/// A small EVM interpreter for testing the EVM backend. It implements the instructions and
+/// precompiles the backend uses. Anything which points at broken code generation, like an
+/// invalid jump or a stack underflow, panics rather than failing the call.
+///
+/// Gas is accounted for very roughly; every instruction costs one unit and memory costs one
+/// unit per word.
+pub struct MockEvm {
+    accounts: HashMap<Address, Account>,
+    programs: Vec<Program>,
+    account: usize,
+    caller: Address,
+    value: U256,
+    output: Vec<u8>,
+    events: Vec<Event>,
+}
+
+impl MockEvm {
+    fn new(programs: Vec<Program>) -> Self {
+        let caller = [0x1d; 20];
+        let mut accounts = HashMap::new();
+
+        accounts.insert(
+            caller,
+            Account {
+                balance: U256::from(10).pow(U256::from(24)),
+                ..Default::default()
+            },
+        );
+
+        MockEvm {
+            accounts,
+            programs,
+            account: 0,
+            caller,
+            value: U256::zero(),
+            output: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// The address of the contract account with the given index
+    pub fn address(&self, index: usize) -> Address {
+        let mut address = [0xc0; 20];
+        address[19] = index as u8;
+        address
+    }
+
+    /// Specify the contract account index for the next function or constructor call.
+    pub fn set_account(&mut self, index: usize) {
+        self.account = index;
+    }
+
+    /// Specify the value transferred with the next function or constructor call.
+    pub fn set_transferred_value(&mut self, amount: u128) {
+        self.value = U256::from(amount);
+    }
+
+    /// Get the balance of the given contract account.
+    pub fn balance(&self, index: usize) -> u128 {
+        self.accounts
+            .get(&self.address(index))
+            .map(|account| account.balance.as_u128())
+            .unwrap_or_default()
+    }
+
+    /// Get the address of the calling account.
+    pub fn caller(&self) -> Address {
+        self.caller
+    }
+
+    /// Get the output or revert data of the last function or constructor call.
+    pub fn output(&self) -> Vec<u8> {
+        self.output.clone()
+    }
+
+    /// Get the emitted events of the last function or constructor call.
+    pub fn events(&self) -> Vec<Event> {
+        self.events.clone()
+    }
+
+    /// Read the storage of the contract account that was (or is about to be) called.
+    pub fn storage(&self) -> HashMap<U256, U256> {
+        self.accounts
+            .get(&self.address(self.account))
+            .map(|account| account.storage.clone())
+            .unwrap_or_default()
+    }
+
+    /// Get the selector of the given `function_name` on the given `contract` index.
+    pub fn selector(&self, contract: usize, function_name: &str) -> &[u8] {
+        &self.programs[contract].messages[function_name]
+    }
+
+    /// Execute the constructor with the given ABI encoded `args`.
+    pub fn constructor(&mut self, args: Vec<u8>) {
+        self.raw_constructor(args);
+    }
+
+    /// Execute the constructor with the given ABI encoded `args`, and expect it to revert.
+    pub fn constructor_expect_failure(&mut self, args: Vec<u8>) {
+        assert!(!self.deploy(args), "constructor did not fail");
+    }
+
+    /// Deploy the contract with the given input, which are the ABI encoded constructor
+    /// arguments. Panics if the constructor reverts.
+    pub fn raw_constructor(&mut self, input: Vec<u8>) {
+        assert!(
+            self.deploy(input),
+            "constructor failed: {}",
+            hex::encode(&self.output)
+        );
+    }
+
+    /// Call the contract function `name` with the given input `args`.
+    /// Panics if the contract reverts.
+    pub fn function(&mut self, name: &str, args: Vec<u8>) {
+        let mut input = self.programs[self.account].messages[name].clone();
+        input.extend(args);
+        self.raw_function(input);
+    }
+
+    /// Expect the contract function `name` with the given input `args` to revert.
+    pub fn function_expect_failure(&mut self, name: &str, args: Vec<u8>) {
+        let mut input = self.programs[self.account].messages[name].clone();
+        input.extend(args);
+        self.raw_function_failure(input);
+    }
+
+    /// Call the contract with the given `input`, which should start with the function
+    /// selector. Panics if the contract reverts.
+    pub fn raw_function(&mut self, input: Vec<u8>) {
+        assert!(
+            self.invoke(input),
+            "call failed: {}",
+            hex::encode(&self.output)
+        );
+    }
+
+    /// Call the contract with the given `input` and expect it to revert.
+    pub fn raw_function_failure(&mut self, input: Vec<u8>) {
+        assert!(!self.invoke(input), "call did not fail");
+    }
+
+    fn deploy(&mut self, input: Vec<u8>) -> bool {
+        let address = self.address(self.account);
+        let mut code = self.programs[self.account].code.clone();
+        code.extend(input);
+
+        self.events.clear();
+        let value = std::mem::take(&mut self.value);
+        let (address, output, _) = self.create(self.caller, address, value, code, TX_GAS, 0);
+        self.output = output;
+
+        address.is_some()
+    }
+
+    fn invoke(&mut self, input: Vec<u8>) -> bool {
+        let address = self.address(self.account);
+
+        self.events.clear();
+        let value = std::mem::take(&mut self.value);
+        let (success, output, _) = self.call(
+            Frame {
+                address,
+                caller: self.caller,
+                value,
+                input,
+                code: Vec::new(),
+                is_static: false,
+                gas: TX_GAS,
+            },
+            address,
+            true,
+            0,
+        );
+        self.output = output;
+
+        success
+    }
+
+    fn account(&mut self, address: Address) -> &mut Account {
+        self.accounts.entry(address).or_default()
+    }
+
+    fn transfer(&mut self, from: Address, to: Address, value: U256) -> bool {
+        if self.account(from).balance < value {
+            return false;
+        }
+
+        self.account(from).balance -= value;
+        self.account(to).balance += value;
+
+        true
+    }
+
+    /// Execute a message call. The code is taken from `code_address`, which differs from the
+    /// address of the frame for delegate calls. Returns success, the output and the gas left.
+    fn call(
+        &mut self,
+        mut frame: Frame,
+        code_address: Address,
+        transfer: bool,
+        depth: usize,
+    ) -> (bool, Vec<u8>, u64) {
+        if depth > 1024 {
+            return (false, Vec::new(), frame.gas);
+        }
+
+        let snapshot = (self.accounts.clone(), self.events.len());
+
+        if transfer && !self.transfer(frame.caller, frame.address, frame.value) {
+            return (false, Vec::new(), frame.gas);
+        }
+
+        if let Some(output) = precompile(&code_address, &frame.input) {
+            return (true, output, frame.gas);
+        }
+
+        frame.code = self
+            .accounts
+            .get(&code_address)
+            .map(|account| account.code.clone())
+            .unwrap_or_default();
+
+        if frame.code.is_empty() {
+            return (true, Vec::new(), frame.gas);
+        }
+
+        match self.execute(&frame, depth) {
+            (Halt::Return(output), gas) => (true, output, gas),
+            (halt, gas) => {
+                (self.accounts, _) = snapshot.clone();
+                self.events.truncate(snapshot.1);
+
+                match halt {
+                    Halt::Revert(output) => (false, output, gas),
+                    _ => (false, Vec::new(), 0),
+                }
+            }
+        }
+    }
+
+    /// Create a contract at the given address. Returns the address if successful, the revert
+    /// data otherwise, and the gas left.
+    fn create(
+        &mut self,
+        creator: Address,
+        address: Address,
+        value: U256,
+        code: Vec<u8>,
+        gas: u64,
+        depth: usize,
+    ) -> (Option<Address>, Vec<u8>, u64) {
+        self.account(creator).nonce += 1;
+
+        if depth > 1024
+            || self
+                .accounts
+                .get(&address)
+                .is_some_and(|a| !a.code.is_empty())
+        {
+            return (None, Vec::new(), gas);
+        }
+
+        let snapshot = (self.accounts.clone(), self.events.len());
+
+        if !self.transfer(creator, address, value) {
+            return (None, Vec::new(), gas);
+        }
+
+        let frame = Frame {
+            address,
+            caller: creator,
+            value,
+            input: Vec::new(),
+            code,
+            is_static: false,
+            gas,
+        };
+
+        match self.execute(&frame, depth) {
+            (Halt::Return(code), gas) => {
+                self.account(address).code = code;
+                (Some(address), Vec::new(), gas)
+            }
+            (halt, gas) => {
+                (self.accounts, _) = snapshot.clone();
+                self.events.truncate(snapshot.1);
+
+                match halt {
+                    Halt::Revert(output) => (None, output, gas),
+                    _ => (None, Vec::new(), 0),
+                }
+            }
+        }
+    }
+
+    /// Run the code of a frame until it halts. Returns how it halted and the gas left.
+    fn execute(&mut self, frame: &Frame, depth: usize) -> (Halt, u64) {
+        let code = &frame.code;
+        let jumpdests = jumpdests(code);
+        let mut stack: Vec<U256> = Vec::new();
+        let mut memory: Vec<u8> = Vec::new();
+        let mut return_data: Vec<u8> = Vec::new();
+        let mut gas = frame.gas;
+        let mut pc = 0;
+
+        macro_rules! pop {
+            () => {
+                stack
+                    .pop()
+                    .unwrap_or_else(|| panic!("{pc:#x}: stack underflow"))
+            };
+        }
+
+        macro_rules! push {
+            ($value:expr) => {{
+                let value: U256 = $value;
+                assert!(stack.len() < 1024, "{pc:#x}: stack overflow");
+                stack.push(value);
+            }};
+        }
+
+        // Resize memory for an access, returning the offset
+        macro_rules! memory {
+            ($offset:expr, $len:expr) => {{
+                let (offset, len): (U256, U256) = ($offset, $len);
+                if len.is_zero() {
+                    0
+                } else {
+                    let end = offset.saturating_add(len);
+                    assert!(
+                        end <= U256::from(MEMORY_LIMIT),
+                        "{pc:#x}: memory access at {offset:#x} of {len:#x} bytes"
+                    );
+                    let end = (end.as_usize() + 31) & !31;
+                    if end > memory.len() {
+                        gas = gas
+                            .checked_sub((end - memory.len()) as u64 / 32)
+                            .expect("out of gas");
+                        memory.resize(end, 0);
+                    }
+                    offset.as_usize()
+                }
+            }};
+        }
+
+        loop {
+            let Some(op) = code.get(pc).copied() else {
+                return (Halt::Return(Vec::new()), gas);
+            };
+
+            gas = gas.checked_sub(1).expect("out of gas");
+
+            match op {
+                // STOP
+                0x00 => return (Halt::Return(Vec::new()), gas),
+                // ADD, MUL, SUB, DIV, SDIV, MOD, SMOD
+                0x01 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(a.overflowing_add(b).0);
+                }
+                0x02 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(a.overflowing_mul(b).0);
+                }
+                0x03 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(a.overflowing_sub(b).0);
+                }
+                0x04 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(if b.is_zero() { b } else { a / b });
+                }
+                0x05 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(sdiv(a, b));
+                }
+                0x06 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(if b.is_zero() { b } else { a % b });
+                }
+                0x07 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(smod(a, b));
+                }
+                // ADDMOD, MULMOD
+                0x08 | 0x09 => {
+                    let (a, b, n) = (pop!(), pop!(), pop!());
+                    push!(if n.is_zero() {
+                        n
+                    } else {
+                        let res = if op == 0x08 {
+                            (U512::from(a) + U512::from(b)) % U512::from(n)
+                        } else {
+                            a.full_mul(b) % U512::from(n)
+                        };
+                        U256::try_from(res).unwrap()
+                    });
+                }
+                // EXP
+                0x0a => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(a.overflowing_pow(b).0);
+                }
+                // SIGNEXTEND
+                0x0b => {
+                    let (b, x) = (pop!(), pop!());
+                    push!(if b < U256::from(31) {
+                        let bit = b.as_usize() * 8 + 7;
+                        let mask = (U256::one() << (bit + 1)) - 1;
+                        if x.bit(bit) {
+                            x | !mask
+                        } else {
+                            x & mask
+                        }
+                    } else {
+                        x
+                    });
+                }
+                // LT, GT, SLT, SGT, EQ
+                0x10 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(U256::from((a < b) as u8));
+                }
+                0x11 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(U256::from((a > b) as u8));
+                }
+                0x12 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(U256::from((flip(a) < flip(b)) as u8));
+                }
+                0x13 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(U256::from((flip(a) > flip(b)) as u8));
+                }
+                0x14 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(U256::from((a == b) as u8));
+                }
+                // ISZERO
+                0x15 => {
+                    let a = pop!();
+                    push!(U256::from(a.is_zero() as u8));
+                }
+                // AND, OR, XOR, NOT
+                0x16 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(a & b);
+                }
+                0x17 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(a | b);
+                }
+                0x18 => {
+                    let (a, b) = (pop!(), pop!());
+                    push!(a ^ b);
+                }
+                0x19 => {
+                    let a = pop!();
+                    push!(!a);
+                }
+                // BYTE
+                0x1a => {
+                    let (i, x) = (pop!(), pop!());
+                    push!(if i < U256::from(32) {
+                        (x >> (8 * (31 - i.as_usize()))) & U256::from(0xff)
+                    } else {
+                        U256::zero()
+                    });
+                }
+                // SHL, SHR, SAR
+                0x1b => {
+                    let (shift, x) = (pop!(), pop!());
+                    push!(if shift < U256::from(256) {
+                        x << shift.as_usize()
+                    } else {
+                        U256::zero()
+                    });
+                }
+                0x1c => {
+                    let (shift, x) = (pop!(), pop!());
+                    push!(if shift < U256::from(256) {
+                        x >> shift.as_usize()
+                    } else {
+                        U256::zero()
+                    });
+                }
+                0x1d => {
+                    let (shift, x) = (pop!(), pop!());
+                    let shift = if shift < U256::from(256) {
+                        shift.as_usize()
+                    } else {
+                        256
+                    };
+                    push!(match (x.bit(255), shift) {
+                        (false, 256) => U256::zero(),
+                        (true, 256) => U256::MAX,
+                        (false, _) => x >> shift,
+                        (true, _) => !(!x >> shift),
+                    });
+                }
+                // KECCAK256
+                0x20 => {
+                    let (offset, len) = (pop!(), pop!());
+                    let offset = memory!(offset, len);
+                    let hash = keccak256(&memory[offset..offset + len.as_usize()]);
+                    push!(U256::from_big_endian(&hash));
+                }
+                // ADDRESS, BALANCE, ORIGIN, CALLER, CALLVALUE
+                0x30 => push!(address_word(&frame.address)),
+                0x31 => {
+                    let address = word_address(pop!());
+                    push!(self.account(address).balance);
+                }
+                0x32 => push!(address_word(&self.caller)),
+                0x33 => push!(address_word(&frame.caller)),
+                0x34 => push!(frame.value),
+                // CALLDATALOAD, CALLDATASIZE, CALLDATACOPY
+                0x35 => {
+                    let offset = pop!();
+                    push!(U256::from_big_endian(&slice(&frame.input, offset, 32)));
+                }
+                0x36 => push!(U256::from(frame.input.len())),
+                0x37 => {
+                    let (dest, offset, len) = (pop!(), pop!(), pop!());
+                    let dest = memory!(dest, len);
+                    let data = slice(&frame.input, offset, len.as_usize());
+                    memory[dest..dest + data.len()].copy_from_slice(&data);
+                }
+                // CODESIZE, CODECOPY
+                0x38 => push!(U256::from(code.len())),
+                0x39 => {
+                    let (dest, offset, len) = (pop!(), pop!(), pop!());
+                    let dest = memory!(dest, len);
+                    let data = slice(code, offset, len.as_usize());
+                    memory[dest..dest + data.len()].copy_from_slice(&data);
+                }
+                // GASPRICE
+                0x3a => push!(U256::zero()),
+                // EXTCODESIZE, EXTCODECOPY
+                0x3b => {
+                    let address = word_address(pop!());
+                    push!(U256::from(self.account(address).code.len()));
+                }
+                0x3c => {
+                    let (address, dest, offset, len) = (pop!(), pop!(), pop!(), pop!());
+                    let dest = memory!(dest, len);
+                    let ext_code = self.account(word_address(address)).code.clone();
+                    let data = slice(&ext_code, offset, len.as_usize());
+                    memory[dest..dest + data.len()].copy_from_slice(&data);
+                }
+                // RETURNDATASIZE, RETURNDATACOPY
+                0x3d => push!(U256::from(return_data.len())),
+                0x3e => {
+                    let (dest, offset, len) = (pop!(), pop!(), pop!());
+                    assert!(
+                        offset.saturating_add(len) <= U256::from(return_data.len()),
+                        "{pc:#x}: return data out of bounds"
+                    );
+                    let dest = memory!(dest, len);
+                    let offset = offset.as_usize();
+                    let len = len.as_usize();
+                    memory[dest..dest + len].copy_from_slice(&return_data[offset..offset + len]);
+                }
+                // EXTCODEHASH
+                0x3f => {
+                    let address = word_address(pop!());
+                    let ext_code = &self.account(address).code;
+                    push!(U256::from_big_endian(&keccak256(ext_code)));
+                }
+                // BLOCKHASH, COINBASE
+                0x40 => {
+                    pop!();
+                    push!(U256::zero());
+                }
+                0x41 => push!(U256::zero()),
+                // TIMESTAMP, NUMBER, PREVRANDAO, GASLIMIT, CHAINID, SELFBALANCE, BASEFEE
+                0x42 => push!(U256::from(1_700_000_000)),
+                0x43 => push!(U256::from(18_000_000)),
+                0x44 => push!(U256::from(0x5eed)),
+                0x45 => push!(U256::from(30_000_000)),
+                0x46 => push!(U256::one()),
+                0x47 => push!(self.account(frame.address).balance),
+                0x48 => push!(U256::from(7)),
+                // POP
+                0x50 => {
+                    pop!();
+                }
+                // MLOAD, MSTORE, MSTORE8
+                0x51 => {
+                    let offset = memory!(pop!(), U256::from(32));
+                    push!(U256::from_big_endian(&memory[offset..offset + 32]));
+                }
+                0x52 => {
+                    let (offset, value) = (pop!(), pop!());
+                    let offset = memory!(offset, U256::from(32));
+                    value.to_big_endian(&mut memory[offset..offset + 32]);
+                }
+                0x53 => {
+                    let (offset, value) = (pop!(), pop!());
+                    let offset = memory!(offset, U256::one());
+                    memory[offset] = value.byte(0);
+                }
+                // SLOAD, SSTORE
+                0x54 => {
+                    let key = pop!();
+                    let value = self
+                        .account(frame.address)
+                        .storage
+                        .get(&key)
+                        .copied()
+                        .unwrap_or_default();
+                    push!(value);
+                }
+                0x55 => {
+                    let (key, value) = (pop!(), pop!());
+                    assert!(!frame.is_static, "{pc:#x}: SSTORE in static call");
+                    let storage = &mut self.account(frame.address).storage;
+                    if value.is_zero() {
+                        storage.remove(&key);
+                    } else {
+                        storage.insert(key, value);
+                    }
+                }
+                // JUMP, JUMPI
+                0x56 | 0x57 => {
+                    let dest = pop!();
+                    let cond = if op == 0x57 { !pop!().is_zero() } else { true };
+                    if cond {
+                        assert!(
+                            dest < U256::from(code.len()) && jumpdests[dest.as_usize()],
+                            "{pc:#x}: invalid jump destination {dest:#x}"
+                        );
+                        pc = dest.as_usize();
+                        continue;
+                    }
+                }
+                // PC, MSIZE, GAS
+                0x58 => push!(U256::from(pc)),
+                0x59 => push!(U256::from(memory.len())),
+                0x5a => push!(U256::from(gas)),
+                // JUMPDEST
+                0x5b => (),
+                // PUSH0 .. PUSH32
+                0x5f..=0x7f => {
+                    let len = (op - 0x5f) as usize;
+                    let bytes = slice(code, U256::from(pc + 1), len);
+                    push!(U256::from_big_endian(&bytes));
+                    pc += len;
+                }
+                // DUP1 .. DUP16
+                0x80..=0x8f => {
+                    let n = (op - 0x7f) as usize;
+                    assert!(stack.len() >= n, "{pc:#x}: stack underflow");
+                    push!(stack[stack.len() - n]);
+                }
+                // SWAP1 .. SWAP16
+                0x90..=0x9f => {
+                    let n = (op - 0x8f) as usize;
+                    let len = stack.len();
+                    assert!(len > n, "{pc:#x}: stack underflow");
+                    stack.swap(len - 1, len - 1 - n);
+                }
+                // LOG0 .. LOG4
+                0xa0..=0xa4 => {
+                    assert!(!frame.is_static, "{pc:#x}: LOG in static call");
+                    let (offset, len) = (pop!(), pop!());
+                    let topics = (0..op - 0xa0)
+                        .map(|_| {
+                            let mut topic = [0u8; 32];
+                            pop!().to_big_endian(&mut topic);
+                            topic
+                        })
+                        .collect();
+                    let offset = memory!(offset, len);
+                    self.events.push(Event {
+                        address: frame.address,
+                        topics,
+                        data: memory[offset..offset + len.as_usize()].to_vec(),
+                    });
+                }
+                // CREATE, CREATE2
+                0xf0 | 0xf5 => {
+                    assert!(!frame.is_static, "{pc:#x}: CREATE in static call");
+                    let (value, offset, len) = (pop!(), pop!(), pop!());
+                    let offset = memory!(offset, len);
+                    let init_code = memory[offset..offset + len.as_usize()].to_vec();
+                    let address = if op == 0xf5 {
+                        let mut preimage = vec![0xff];
+                        preimage.extend(frame.address);
+                        let mut salt = [0u8; 32];
+                        pop!().to_big_endian(&mut salt);
+                        preimage.extend(salt);
+                        preimage.extend(keccak256(&init_code));
+                        keccak256(&preimage)[12..].try_into().unwrap()
+                    } else {
+                        let mut preimage = frame.address.to_vec();
+                        preimage.extend(self.account(frame.address).nonce.to_be_bytes());
+                        keccak256(&preimage)[12..].try_into().unwrap()
+                    };
+                    let call_gas = gas - gas / 64;
+                    let (address, output, gas_left) = self.create(
+                        frame.address,
+                        address,
+                        value,
+                        init_code,
+                        call_gas,
+                        depth + 1,
+                    );
+                    gas -= call_gas - gas_left;
+                    return_data = output;
+                    push!(address.map_or(U256::zero(), |a| address_word(&a)));
+                }
+                // CALL, DELEGATECALL, STATICCALL
+                0xf1 | 0xf4 | 0xfa => {
+                    let call_gas = pop!();
+                    let address = word_address(pop!());
+                    let value = if op == 0xf1 { pop!() } else { U256::zero() };
+                    let (args_offset, args_len) = (pop!(), pop!());
+                    let (ret_offset, ret_len) = (pop!(), pop!());
+                    assert!(
+                        !frame.is_static || value.is_zero(),
+                        "{pc:#x}: value transfer in static call"
+                    );
+                    let args_offset = memory!(args_offset, args_len);
+                    let ret_offset = memory!(ret_offset, ret_len);
+                    let input = memory[args_offset..args_offset + args_len.as_usize()].to_vec();
+                    let call_gas = std::cmp::min(call_gas, U256::from(gas - gas / 64)).as_u64();
+                    let callee = match op {
+                        0xf4 => Frame {
+                            address: frame.address,
+                            caller: frame.caller,
+                            value: frame.value,
+                            input,
+                            code: Vec::new(),
+                            is_static: frame.is_static,
+                            gas: call_gas,
+                        },
+                        _ => Frame {
+                            address,
+                            caller: frame.address,
+                            value,
+                            input,
+                            code: Vec::new(),
+                            is_static: frame.is_static || op == 0xfa,
+                            gas: call_gas,
+                        },
+                    };
+                    let (success, output, gas_left) =
+                        self.call(callee, address, op == 0xf1, depth + 1);
+                    gas -= call_gas - gas_left;
+                    let len = std::cmp::min(ret_len.as_usize(), output.len());
+                    memory[ret_offset..ret_offset + len].copy_from_slice(&output[..len]);
+                    return_data = output;
+                    push!(U256::from(success as u8));
+                }
+                // RETURN, REVERT
+                0xf3 | 0xfd => {
+                    let (offset, len) = (pop!(), pop!());
+                    let offset = memory!(offset, len);
+                    let data = memory[offset..offset + len.as_usize()].to_vec();
+                    return if op == 0xf3 {
+                        (Halt::Return(data), gas)
+                    } else {
+                        (Halt::Revert(data), gas)
+                    };
+                }
+                // INVALID
+                0xfe => return (Halt::Invalid, 0),
+                // SELFDESTRUCT
+                0xff => {
+                    assert!(!frame.is_static, "{pc:#x}: SELFDESTRUCT in static call");
+                    let recipient = word_address(pop!());
+                    let balance = self.account(frame.address).balance;
+                    self.transfer(frame.address, recipient, balance);
+                    self.accounts.remove(&frame.address);
+                    return (Halt::Return(Vec::new()), gas);
+                }
+                _ => panic!("{pc:#x}: invalid opcode {op:#04x}"),
+            }
+
+            pc += 1;
+        }
+    }
+}
+
+/// Build all contracts found in `src` and set up a mock EVM.
+///
+/// Each contract has its own account, see `MockEvm::address()`. Contracts are not deployed
+/// until their constructor is called.
+pub fn build_solidity(src: &str) -> MockEvm {
+    let mut cache = FileResolver::default();
+
+    cache.set_file_contents("test.sol", src.to_string());
+
+    let mut ns = parse_and_resolve(OsStr::new("test.sol"), &mut cache, Target::EVM);
+
+    codegen(&mut ns, &Options::default());
+
+    ns.print_diagnostics_in_plain(&cache, false);
+
+    assert!(!ns.diagnostics.any_errors());
+
+    let programs = ns
+        .contracts
+        .iter()
+        .enumerate()
+        .filter(|(_, contract)| contract.instantiable)
+        .map(|(contract_no, contract)| {
+            let code = evm::compile(contract, &ns).unwrap_or_else(|diag| {
+                panic!("{}", diag.message);
+            });
+
+            let messages = contract
+                .all_functions
+                .keys()
+                .filter(|function_no| {
+                    ns.function_externally_callable(contract_no, Some(**function_no))
+                })
+                .map(|function_no| &ns.functions[*function_no])
+                .filter(|f| !f.is_constructor())
+                .map(|f| {
+                    let name = if f.mangled_name_contracts.contains(&contract_no) {
+                        &f.mangled_name
+                    } else {
+                        &f.id.name
+                    };
+                    (name.clone(), f.selector(&ns, &contract_no))
+                })
+                .collect();
+
+            Program {
+                name: contract.id.name.clone(),
+                code,
+                messages,
+            }
+        })
+        .collect();
+
+    MockEvm::new(programs)
+}
+
+/// The precompiles for sha256, ripemd160 and identity
+fn precompile(address: &Address, input: &[u8]) -> Option<Vec<u8>> {
+    if address[..19].iter().any(|b| *b != 0) {
+        return None;
+    }
+
+    match address[19] {
+        2 => Some(Sha256::digest(input).to_vec()),
+        3 => {
+            let mut output = vec![0; 12];
+            output.extend(Ripemd160::digest(input));
+            Some(output)
+        }
+        4 => Some(input.to_vec()),
+        _ => None,
+    }
+}
+
+/// Find the valid jump destinations, skipping over push data
+fn jumpdests(code: &[u8]) -> Vec<bool> {
+    let mut dests = vec![false; code.len()];
+    let mut pc = 0;
+
+    while pc < code.len() {
+        match code[pc] {
+            0x5b => dests[pc] = true,
+            op @ 0x60..=0x7f => pc += (op - 0x5f) as usize,
+            _ => (),
+        }
+        pc += 1;
+    }
+
+    dests
+}
+
+/// Read `len` bytes at `offset`, padded with zeros
+fn slice(data: &[u8], offset: U256, len: usize) -> Vec<u8> {
+    let mut res = vec![0; len];
+
+    if offset < U256::from(data.len()) {
+        let offset = offset.as_usize();
+        let available = std::cmp::min(len, data.len() - offset);
+        res[..available].copy_from_slice(&data[offset..offset + available]);
+    }
+
+    res
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    hasher.finalize(&mut hash);
+    hash
+}
+
+/// The ABI encoding token of an unsigned integer
+pub fn uint(value: u128) -> Token {
+    Token::Uint(value.into())
+}
+
+/// The ABI encoding token of a signed integer, which is sign extended to 256 bits
+pub fn int(value: i128) -> Token {
+    if value < 0 {
+        Token::Int(!U256::from((-(value + 1)) as u128))
+    } else {
+        Token::Int(U256::from(value as u128))
+    }
+}
+
+/// The ABI encoding token of an address
+pub fn address_token(address: Address) -> Token {
+    Token::Address(address.into())
+}
+
+fn address_word(address: &Address) -> U256 {
+    U256::from_big_endian(address)
+}
+
+fn word_address(word: U256) -> Address {
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    bytes[12..].try_into().unwrap()
+}
+
+/// Flip the sign bit, so that signed values can be compared as unsigned values
+fn flip(value: U256) -> U256 {
+    value ^ (U256::one() << 255)
+}
+
+fn negate(value: U256) -> U256 {
+    (!value).overflowing_add(U256::one()).0
+}
+
+fn abs(value: U256) -> U256 {
+    if value.bit(255) {
+        negate(value)
+    } else {
+        value
+    }
+}
+
+fn sdiv(a: U256, b: U256) -> U256 {
+    if b.is_zero() {
+        return b;
+    }
+
+    let res = abs(a) / abs(b);
+
+    if a.bit(255) != b.bit(255) {
+        negate(res)
+    } else {
+        res
+    }
+}
+
+fn smod(a: U256, b: U256) -> U256 {
+    if b.is_zero() {
+        return b;
+    }
+
+    let res = abs(a) % abs(b);
+
+    if a.bit(255) {
+        negate(res)
+    } else {
+        res
+    }
+}
+
 fn test_solidity(src: &str) -> ast::Namespace {
     let mut cache = FileResolver::default();
 
@@ -173,6 +1213,41 @@ contract testing  {
     assert!(!ns.diagnostics.any_errors());
 }
 
+#[test]
+fn unsupported() {
+    let mut cache = FileResolver::default();
+
+    cache.set_file_contents(
+        "test.sol",
+        r#"
+        contract c {
+            function f(int x) public pure returns (string) {
+                return "{}".format(x);
+            }
+        }"#
+        .to_string(),
+    );
+
+    let (contracts, ns) = solang::compile(
+        OsStr::new("test.sol"),
+        &mut cache,
+        Target::EVM,
+        &Options::default(),
+        vec!["unknown".to_string()],
+        "0.0.1",
+    );
+
+    assert!(contracts.is_empty());
+
+    let errors = ns.diagnostics.errors();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "string formatting is not supported on EVM"
+    );
+}
+
 #[test]
 fn ethereum_solidity_tests() {
     let error_matcher =
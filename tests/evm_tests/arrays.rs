@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{build_solidity, int, uint};
+use ethabi::{encode, Token};
+
+#[test]
+fn memory_arrays() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            function squares(uint32 n) public pure returns (uint64[] memory) {
+                uint64[] memory res = new uint64[](n);
+                for (uint32 i = 0; i < n; i++) {
+                    res[i] = uint64(i) * i;
+                }
+                return res;
+            }
+
+            function sum(int32[] memory a) public pure returns (int64 total) {
+                for (uint i = 0; i < a.length; i++) {
+                    total += a[i];
+                }
+            }
+
+            function push_pop() public pure returns (uint16[] memory, uint16) {
+                uint16[] memory a = new uint16[](0);
+                a.push(1);
+                a.push(2);
+                a.push(3);
+                uint16 last = a.pop();
+                return (a, last);
+            }
+
+            function fixed(uint8[4] memory a) public pure returns (uint8[4] memory) {
+                a[0] = a[3];
+                return a;
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.function("squares", encode(&[uint(5)]));
+    let squares = [0, 1, 4, 9, 16].into_iter().map(uint).collect();
+    assert_eq!(runtime.output(), encode(&[Token::Array(squares)]));
+
+    let values = [1, -20, 300, -4000].into_iter().map(int).collect();
+    runtime.function("sum", encode(&[Token::Array(values)]));
+    assert_eq!(runtime.output(), encode(&[int(-3719)]));
+
+    runtime.function("push_pop", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        encode(&[Token::Array(vec![uint(1), uint(2)]), uint(3)])
+    );
+
+    let values = [1, 2, 3, 4].into_iter().map(uint).collect();
+    runtime.function("fixed", encode(&[Token::FixedArray(values)]));
+    let values = [4, 2, 3, 4].into_iter().map(uint).collect();
+    assert_eq!(runtime.output(), encode(&[Token::FixedArray(values)]));
+}
+
+#[test]
+fn struct_arrays() {
+    let point = |x: i128, y: i128| Token::Tuple(vec![int(x), int(y)]);
+
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            struct Point { int32 x; int32 y; }
+            Point[] points;
+
+            function add(Point memory p) public {
+                points.push(p);
+            }
+
+            function mirror() public view returns (Point[] memory) {
+                Point[] memory res = points;
+                for (uint i = 0; i < res.length; i++) {
+                    int32 x = res[i].x;
+                    res[i].x = res[i].y;
+                    res[i].y = x;
+                }
+                return res;
+            }
+
+            function remove() public {
+                delete points;
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.function("add", encode(&[point(1, -1)]));
+    runtime.function("add", encode(&[point(2, 300)]));
+
+    runtime.function("mirror", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        encode(&[Token::Array(vec![point(-1, 1), point(300, 2)])])
+    );
+
+    runtime.function("remove", Vec::new());
+    runtime.function("mirror", Vec::new());
+    assert_eq!(runtime.output(), encode(&[Token::Array(vec![])]));
+    assert!(runtime.storage().is_empty());
+}
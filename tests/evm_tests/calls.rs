@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{build_solidity, int, uint};
+use ethabi::{encode, Token};
+
+#[test]
+fn create_and_call() {
+    let mut runtime = build_solidity(
+        r##"
+        contract creator {
+            child c;
+
+            function create(int32 x) public {
+                c = new child(x);
+            }
+
+            function call(int32 y) public view returns (int32) {
+                return c.add(y);
+            }
+
+            function call_failing() public view returns (bool) {
+                (bool success, ) = address(c).staticcall(abi.encodeWithSignature("fail()"));
+                return success;
+            }
+
+            function bubble() public view {
+                c.fail();
+            }
+        }
+
+        contract child {
+            int32 x;
+
+            constructor(int32 a) {
+                x = a;
+            }
+
+            function add(int32 y) public view returns (int32) {
+                return x + y;
+            }
+
+            function fail() public pure {
+                revert("failed");
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+    runtime.function("create", encode(&[int(100)]));
+
+    runtime.function("call", encode(&[int(23)]));
+    assert_eq!(runtime.output(), encode(&[int(123)]));
+
+    runtime.function_expect_failure("call", encode(&[int(i32::MAX.into())]));
+
+    runtime.function("call_failing", Vec::new());
+    assert_eq!(runtime.output(), encode(&[Token::Bool(false)]));
+
+    runtime.function_expect_failure("bubble", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        [
+            vec![0x08, 0xc3, 0x79, 0xa0],
+            encode(&[Token::String("failed".into())])
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn value_transfer() {
+    let mut runtime = build_solidity(
+        r##"
+        contract bank {
+            function deposit() public payable {}
+
+            function withdraw(uint128 amount) public {
+                payable(msg.sender).transfer(amount);
+            }
+
+            function balance() public view returns (uint128) {
+                return address(this).balance;
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.set_transferred_value(1000);
+    runtime.function("deposit", Vec::new());
+    assert_eq!(runtime.balance(0), 1000);
+
+    runtime.function("withdraw", encode(&[uint(400)]));
+    assert_eq!(runtime.balance(0), 600);
+
+    runtime.function("balance", Vec::new());
+    assert_eq!(runtime.output(), encode(&[uint(600)]));
+
+    runtime.function_expect_failure("withdraw", encode(&[uint(601)]));
+
+    // non-payable functions reject value
+    runtime.set_transferred_value(1);
+    runtime.function_expect_failure("balance", Vec::new());
+}
+
+#[test]
+fn external_function_types() {
+    let mut runtime = build_solidity(
+        r##"
+        contract caller {
+            function(int64) external returns (int64) stored;
+
+            function set(callee c) public {
+                stored = c.triple;
+            }
+
+            function call(int64 x) public returns (int64) {
+                return stored(x);
+            }
+
+            function create() public returns (callee) {
+                return new callee{salt: hex"01"}();
+            }
+        }
+
+        contract callee {
+            function triple(int64 x) public pure returns (int64) {
+                return x * 3;
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.function("create", Vec::new());
+    let callee = runtime.output();
+    assert_eq!(callee.len(), 32);
+
+    runtime.function("set", callee.clone());
+    runtime.function("call", encode(&[int(-7)]));
+    assert_eq!(runtime.output(), encode(&[int(-21)]));
+
+    // the same salt gives the same address, so creating it again fails
+    runtime.function_expect_failure("create", Vec::new());
+}
+
+#[test]
+fn try_catch() {
+    let mut runtime = build_solidity(
+        r##"
+        contract caller {
+            callee c = new callee();
+
+            function call(int32 x) public returns (int32 res, string memory reason, uint code) {
+                try c.check(x) returns (int32 y) {
+                    res = y;
+                } catch Error(string memory r) {
+                    reason = r;
+                } catch Panic(uint p) {
+                    code = p;
+                }
+            }
+
+            function create(int32 x) public returns (bool) {
+                try new callee2(x) {
+                    return true;
+                } catch (bytes memory) {
+                    return false;
+                }
+            }
+        }
+
+        contract callee {
+            function check(int32 x) public pure returns (int32) {
+                require(x >= 0, "negative");
+                assert(x != 1);
+                return x * 2;
+            }
+        }
+
+        contract callee2 {
+            constructor(int32 x) {
+                require(x > 0);
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    let result = |res: i128, reason: &str, code: u128| {
+        encode(&[int(res), Token::String(reason.into()), uint(code)])
+    };
+
+    runtime.function("call", encode(&[int(21)]));
+    assert_eq!(runtime.output(), result(42, "", 0));
+
+    runtime.function("call", encode(&[int(-1)]));
+    assert_eq!(runtime.output(), result(0, "negative", 0));
+
+    runtime.function("call", encode(&[int(1)]));
+    assert_eq!(runtime.output(), result(0, "", 1));
+
+    runtime.function("create", encode(&[int(1)]));
+    assert_eq!(runtime.output(), encode(&[Token::Bool(true)]));
+
+    runtime.function("create", encode(&[int(0)]));
+    assert_eq!(runtime.output(), encode(&[Token::Bool(false)]));
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{address_token, build_solidity, keccak256, uint};
+use ethabi::{encode, Token};
+
+#[test]
+fn emit() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            event Transfer(address indexed from, uint64 amount, string memo);
+            event Anonymous(bool b, string indexed s) anonymous;
+
+            function send(uint64 amount) public {
+                emit Transfer(msg.sender, amount, "memo");
+                emit Anonymous(true, "indexed");
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+    runtime.function("send", encode(&[uint(1000)]));
+
+    let events = runtime.events();
+    assert_eq!(events.len(), 2);
+
+    assert_eq!(events[0].address, runtime.address(0));
+    assert_eq!(
+        events[0].topics,
+        vec![
+            keccak256(b"Transfer(address,uint64,string)"),
+            encode(&[address_token(runtime.caller())])
+                .try_into()
+                .unwrap()
+        ]
+    );
+    assert_eq!(
+        events[0].data,
+        encode(&[uint(1000), Token::String("memo".into())])
+    );
+
+    assert_eq!(events[1].topics, vec![keccak256(b"indexed")]);
+    assert_eq!(events[1].data, encode(&[Token::Bool(true)]));
+}
@@ -0,0 +1,259 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{address_token, build_solidity, int, uint};
+use ethabi::{encode, Token};
+use primitive_types::U256;
+
+#[test]
+fn arithmetic() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            function add(int64 a, int64 b) public pure returns (int64) {
+                return a + b;
+            }
+
+            function sub(uint32 a, uint32 b) public pure returns (uint32) {
+                return a - b;
+            }
+
+            function mul(int128 a, int128 b) public pure returns (int128) {
+                return a * b;
+            }
+
+            function div(int32 a, int32 b) public pure returns (int32, int32) {
+                return (a / b, a % b);
+            }
+
+            function pow(uint64 a, uint64 b) public pure returns (uint64) {
+                return a ** b;
+            }
+
+            function unchecked_add(uint8 a, uint8 b) public pure returns (uint8) {
+                unchecked {
+                    return a + b;
+                }
+            }
+
+            function shifts(int16 a, uint8 b) public pure returns (int16, int16) {
+                return (a << b, a >> b);
+            }
+
+            function wide(uint256 a, uint256 b) public pure returns (uint256, uint256) {
+                return (a * b, addmod(a, b, 7));
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.function("add", encode(&[int(-5), int(3)]));
+    assert_eq!(runtime.output(), encode(&[int(-2)]));
+    runtime.function_expect_failure("add", encode(&[int(i64::MAX.into()), int(1)]));
+    runtime.function_expect_failure("add", encode(&[int(i64::MIN.into()), int(-1)]));
+
+    runtime.function("sub", encode(&[uint(5), uint(3)]));
+    assert_eq!(runtime.output(), encode(&[uint(2)]));
+    runtime.function_expect_failure("sub", encode(&[uint(3), uint(5)]));
+
+    runtime.function("mul", encode(&[int(-7), int(9)]));
+    assert_eq!(runtime.output(), encode(&[int(-63)]));
+    runtime.function_expect_failure("mul", encode(&[int(i128::MAX), int(2)]));
+
+    runtime.function("div", encode(&[int(-7), int(2)]));
+    assert_eq!(runtime.output(), encode(&[int(-3), int(-1)]));
+    runtime.function_expect_failure("div", encode(&[int(1), int(0)]));
+
+    runtime.function("pow", encode(&[uint(3), uint(40)]));
+    assert_eq!(runtime.output(), encode(&[uint(3u128.pow(40))]));
+    runtime.function_expect_failure("pow", encode(&[uint(3), uint(41)]));
+
+    runtime.function("unchecked_add", encode(&[uint(200), uint(100)]));
+    assert_eq!(runtime.output(), encode(&[uint(44)]));
+
+    runtime.function("shifts", encode(&[int(-100), uint(2)]));
+    assert_eq!(runtime.output(), encode(&[int(-400), int(-25)]));
+
+    // 2**128 * 5, and 2**128 + 5 mod 7
+    let a = U256::one() << 128;
+    runtime.function("wide", encode(&[Token::Uint(a), uint(5)]));
+    assert_eq!(runtime.output(), encode(&[Token::Uint(a * 5), uint(2)]));
+}
+
+#[test]
+fn panic_codes() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            function assert_false() public pure {
+                assert(false);
+            }
+
+            function overflow(uint8 a) public pure returns (uint8) {
+                return a + 255;
+            }
+
+            function index(uint32 i) public pure returns (uint8) {
+                uint8[] memory a = new uint8[](2);
+                return a[i];
+            }
+
+            function revert_reason() public pure {
+                revert("no");
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    let panic = |code: u128| [vec![0x4e, 0x48, 0x7b, 0x71], encode(&[uint(code)])].concat();
+
+    runtime.function_expect_failure("assert_false", Vec::new());
+    assert_eq!(runtime.output(), panic(0x01));
+
+    runtime.function_expect_failure("overflow", encode(&[uint(1)]));
+    assert_eq!(runtime.output(), panic(0x11));
+
+    runtime.function_expect_failure("index", encode(&[uint(2)]));
+    assert_eq!(runtime.output(), panic(0x32));
+
+    runtime.function_expect_failure("revert_reason", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        [
+            vec![0x08, 0xc3, 0x79, 0xa0],
+            encode(&[Token::String("no".into())])
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn builtins() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            function hashes(bytes memory b) public pure returns (bytes32, bytes32, bytes20) {
+                return (keccak256(b), sha256(b), ripemd160(b));
+            }
+
+            function sender() public view returns (address, address) {
+                return (msg.sender, address(this));
+            }
+
+            function block_values() public view returns (uint64, uint64) {
+                return (block.number, block.timestamp);
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    let data = b"Hello, World!".to_vec();
+    runtime.function("hashes", encode(&[Token::Bytes(data)]));
+    assert_eq!(
+        hex::encode(runtime.output()),
+        "acaf3289d7b601cbd114fb36c4d29c85bbfd5e133f14cb355c3fd8d99367964f\
+        dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f\
+        527a6a4b9a6da75607546842e0e00105350b1aaf000000000000000000000000"
+    );
+
+    runtime.function("sender", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        encode(&[
+            address_token(runtime.caller()),
+            address_token(runtime.address(0))
+        ])
+    );
+
+    runtime.function("block_values", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        encode(&[uint(18_000_000), uint(1_700_000_000)])
+    );
+}
+
+#[test]
+fn internal_calls() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            function fib(uint32 n) public pure returns (uint32) {
+                if (n < 2) {
+                    return n;
+                }
+                return fib(n - 1) + fib(n - 2);
+            }
+
+            function apply(uint32 n) public pure returns (uint32, uint32) {
+                function(uint32) internal pure returns (uint32) f = double;
+                (uint32 a, uint32 b) = pair(f(n));
+                return (a, b);
+            }
+
+            function double(uint32 n) internal pure returns (uint32) {
+                return n * 2;
+            }
+
+            function pair(uint32 n) internal pure returns (uint32, uint32) {
+                return (n, n + 1);
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.function("fib", encode(&[uint(20)]));
+    assert_eq!(runtime.output(), encode(&[uint(6765)]));
+
+    runtime.function("apply", encode(&[uint(21)]));
+    assert_eq!(runtime.output(), encode(&[uint(42), uint(43)]));
+}
+
+#[test]
+fn enums_and_user_types() {
+    let mut runtime = build_solidity(
+        r##"
+        type Price is uint64;
+
+        contract c {
+            enum State { Start, Running, Done }
+            State state;
+            Price price;
+
+            function next() public {
+                if (state != State.Done) {
+                    state = State(uint8(state) + 1);
+                }
+            }
+
+            function get() public view returns (State) {
+                return state;
+            }
+
+            function set_price(Price p) public {
+                price = Price.wrap(Price.unwrap(p) * 2);
+            }
+
+            function get_price() public view returns (Price) {
+                return price;
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.function("next", Vec::new());
+    runtime.function("get", Vec::new());
+    assert_eq!(runtime.output(), encode(&[uint(1)]));
+
+    runtime.function("next", Vec::new());
+    runtime.function("next", Vec::new());
+    runtime.function("get", Vec::new());
+    assert_eq!(runtime.output(), encode(&[uint(2)]));
+
+    runtime.function("set_price", encode(&[uint(21)]));
+    runtime.function("get_price", Vec::new());
+    assert_eq!(runtime.output(), encode(&[uint(42)]));
+}
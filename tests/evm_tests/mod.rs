@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: Apache-2.0
+
+mod arrays;
+mod calls;
+mod events;
+mod expressions;
+mod storage;
+mod strings;
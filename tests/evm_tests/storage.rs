@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{address_token, build_solidity, int, uint};
+use ethabi::{encode, Token};
+
+#[test]
+fn flipper() {
+    let mut runtime = build_solidity(
+        r##"
+        contract flipper {
+            bool private value;
+
+            constructor(bool initvalue) {
+                value = initvalue;
+            }
+
+            function flip() public {
+                value = !value;
+            }
+
+            function get() public view returns (bool) {
+                return value;
+            }
+        }"##,
+    );
+
+    runtime.constructor(encode(&[Token::Bool(true)]));
+
+    runtime.function("get", Vec::new());
+    assert_eq!(runtime.output(), encode(&[Token::Bool(true)]));
+
+    runtime.function("flip", Vec::new());
+    runtime.function("get", Vec::new());
+    assert_eq!(runtime.output(), encode(&[Token::Bool(false)]));
+}
+
+#[test]
+fn storage_slots() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            int64 a = -102;
+            uint128 b = 300;
+            address owner = msg.sender;
+            bytes4 d = hex"deadcafe";
+
+            function get() public view returns (int64, uint128, address, bytes4) {
+                return (a, b, owner, d);
+            }
+
+            function set(int64 x, uint128 y) public {
+                a = x;
+                b = y;
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.function("get", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        encode(&[
+            int(-102),
+            uint(300),
+            address_token(runtime.caller()),
+            Token::FixedBytes(vec![0xde, 0xad, 0xca, 0xfe]),
+        ])
+    );
+
+    runtime.function("set", encode(&[int(i64::MIN.into()), uint(u128::MAX)]));
+    runtime.function("get", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        encode(&[
+            int(i64::MIN.into()),
+            uint(u128::MAX),
+            address_token(runtime.caller()),
+            Token::FixedBytes(vec![0xde, 0xad, 0xca, 0xfe]),
+        ])
+    );
+
+    // slots are numbered sequentially, one per variable
+    let storage = runtime.storage();
+    assert_eq!(storage.len(), 4);
+    assert!((0..4).all(|slot| storage.contains_key(&slot.into())));
+}
+
+#[test]
+fn structs() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            struct S { uint32 f1; bool f2; bytes f3; }
+            S s;
+
+            function set(S memory x) public {
+                s = x;
+            }
+
+            function get() public view returns (S memory) {
+                return s;
+            }
+
+            function clear() public {
+                delete s;
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    let val = encode(&[Token::Tuple(vec![
+        uint(102),
+        Token::Bool(true),
+        Token::Bytes(b"a string which is longer than thirty two bytes".to_vec()),
+    ])]);
+
+    runtime.function("set", val.clone());
+    runtime.function("get", Vec::new());
+    assert_eq!(runtime.output(), val);
+
+    runtime.function("clear", Vec::new());
+    runtime.function("get", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        encode(&[Token::Tuple(vec![
+            uint(0),
+            Token::Bool(false),
+            Token::Bytes(vec![])
+        ])])
+    );
+    assert!(runtime.storage().is_empty());
+}
+
+#[test]
+fn mappings() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            mapping(uint64 => int32) values;
+            mapping(address => mapping(uint8 => bool)) nested;
+
+            function set(uint64 key, int32 value) public {
+                values[key] = value;
+            }
+
+            function get(uint64 key) public view returns (int32) {
+                return values[key];
+            }
+
+            function set_nested(uint8 key) public {
+                nested[msg.sender][key] = true;
+            }
+
+            function get_nested(address addr, uint8 key) public view returns (bool) {
+                return nested[addr][key];
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    for i in 0..10 {
+        runtime.function("set", encode(&[uint(i * 7), int(-(i as i128))]));
+    }
+
+    for i in 0..10 {
+        runtime.function("get", encode(&[uint(i * 7)]));
+        assert_eq!(runtime.output(), encode(&[int(-(i as i128))]));
+    }
+
+    runtime.function("get", encode(&[uint(1)]));
+    assert_eq!(runtime.output(), encode(&[int(0)]));
+
+    let caller = address_token(runtime.caller());
+    runtime.function("set_nested", encode(&[uint(5)]));
+    runtime.function("get_nested", encode(&[caller.clone(), uint(5)]));
+    assert_eq!(runtime.output(), encode(&[Token::Bool(true)]));
+    runtime.function("get_nested", encode(&[caller, uint(6)]));
+    assert_eq!(runtime.output(), encode(&[Token::Bool(false)]));
+}
+
+#[test]
+fn storage_arrays() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            int16[] arr;
+            uint32[3] fixed;
+            bytes bs;
+
+            function push(int16 x) public {
+                arr.push(x);
+            }
+
+            function pop() public returns (int16) {
+                return arr.pop();
+            }
+
+            function get() public view returns (int16[] memory) {
+                return arr;
+            }
+
+            function set_fixed(uint32[3] memory x) public {
+                fixed = x;
+            }
+
+            function get_fixed(uint32 i) public view returns (uint32) {
+                return fixed[i];
+            }
+
+            function bytes_ops() public returns (bytes memory) {
+                bs.push(0x41);
+                bs.push(0x42);
+                bs[0] = 0x43;
+                bs.push(bs[1]);
+                bs.pop();
+                bs.push(0x44);
+                return bs;
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    for i in 0..5 {
+        runtime.function("push", encode(&[int(i - 2)]));
+    }
+
+    runtime.function("get", Vec::new());
+    let values = (-2..=2).map(int).collect();
+    assert_eq!(runtime.output(), encode(&[Token::Array(values)]));
+
+    runtime.function("pop", Vec::new());
+    assert_eq!(runtime.output(), encode(&[int(2)]));
+
+    runtime.function("get", Vec::new());
+    let values = (-2..=1).map(int).collect();
+    assert_eq!(runtime.output(), encode(&[Token::Array(values)]));
+
+    let fixed = Token::FixedArray(vec![uint(7), uint(8), uint(9)]);
+    runtime.function("set_fixed", encode(&[fixed]));
+    runtime.function("get_fixed", encode(&[uint(2)]));
+    assert_eq!(runtime.output(), encode(&[uint(9)]));
+
+    runtime.function_expect_failure("get_fixed", encode(&[uint(3)]));
+
+    runtime.function("bytes_ops", Vec::new());
+    assert_eq!(runtime.output(), encode(&[Token::Bytes(b"CBD".to_vec())]));
+
+    for _ in 0..4 {
+        runtime.function("pop", Vec::new());
+    }
+    runtime.function_expect_failure("pop", Vec::new());
+}
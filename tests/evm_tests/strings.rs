@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{build_solidity, uint};
+use ethabi::{encode, Token};
+
+#[test]
+fn concat_and_compare() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            string s;
+
+            function set(string memory x) public {
+                s = x;
+            }
+
+            function greet(string memory name) public view returns (string memory) {
+                return string.concat(s, ", ", name, "!");
+            }
+
+            function equal(string memory a, string memory b) public pure returns (bool) {
+                return a == b;
+            }
+
+            function length(bytes memory b) public pure returns (uint32) {
+                return uint32(b.length);
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    let string = |s: &str| Token::String(s.into());
+
+    runtime.function("set", encode(&[string("Hello")]));
+    runtime.function("greet", encode(&[string("World")]));
+    assert_eq!(runtime.output(), encode(&[string("Hello, World!")]));
+
+    let long = "a much longer string which needs more than one storage slot";
+    runtime.function("set", encode(&[string(long)]));
+    runtime.function("greet", encode(&[string("x")]));
+    assert_eq!(runtime.output(), encode(&[string(&format!("{long}, x!"))]));
+
+    runtime.function("equal", encode(&[string("abc"), string("abc")]));
+    assert_eq!(runtime.output(), encode(&[Token::Bool(true)]));
+    runtime.function("equal", encode(&[string("abc"), string("abd")]));
+    assert_eq!(runtime.output(), encode(&[Token::Bool(false)]));
+
+    runtime.function("length", encode(&[Token::Bytes(vec![1; 100])]));
+    assert_eq!(runtime.output(), encode(&[uint(100)]));
+}
+
+#[test]
+fn bytes_values() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            function convert(bytes memory b) public pure returns (bytes4, bytes memory) {
+                bytes4 x = bytes4(b);
+                b[0] = 0xff;
+                return (x, b);
+            }
+
+            function index(bytes8 b, uint8 i) public pure returns (bytes1) {
+                return b[i];
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.function("convert", encode(&[Token::Bytes(vec![1, 2, 3, 4])]));
+    assert_eq!(
+        runtime.output(),
+        encode(&[
+            Token::FixedBytes(vec![1, 2, 3, 4]),
+            Token::Bytes(vec![0xff, 2, 3, 4])
+        ])
+    );
+
+    let b = Token::FixedBytes(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    runtime.function("index", encode(&[b, uint(2)]));
+    assert_eq!(runtime.output(), encode(&[Token::FixedBytes(vec![3])]));
+
+    let b = Token::FixedBytes(vec![0; 8]);
+    runtime.function_expect_failure("index", encode(&[b, uint(8)]));
+}
+
+#[test]
+fn storage_bytes() {
+    let mut runtime = build_solidity(
+        r##"
+        contract c {
+            bytes data;
+
+            function fill(uint8 n) public {
+                for (uint8 i = 0; i < n; i++) {
+                    data.push(bytes1(i));
+                }
+            }
+
+            function shrink(uint8 n) public {
+                for (uint8 i = 0; i < n; i++) {
+                    data.pop();
+                }
+            }
+
+            function get() public view returns (bytes memory) {
+                return data;
+            }
+
+            function at(uint32 i) public view returns (bytes1) {
+                return data[i];
+            }
+        }"##,
+    );
+
+    runtime.constructor(Vec::new());
+
+    runtime.function("fill", encode(&[uint(70)]));
+    runtime.function("get", Vec::new());
+    assert_eq!(runtime.output(), encode(&[Token::Bytes((0..70).collect())]));
+
+    runtime.function("at", encode(&[uint(65)]));
+    assert_eq!(runtime.output(), encode(&[Token::FixedBytes(vec![65])]));
+    runtime.function_expect_failure("at", encode(&[uint(70)]));
+
+    runtime.function("shrink", encode(&[uint(40)]));
+    runtime.function("get", Vec::new());
+    assert_eq!(runtime.output(), encode(&[Token::Bytes((0..30).collect())]));
+
+    // the length and one chunk remain
+    assert_eq!(runtime.storage().len(), 2);
+
+    runtime.function("shrink", encode(&[uint(30)]));
+    assert!(runtime.storage().is_empty());
+}
@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{build_wasm, load_abi};
-use ink_metadata::{InkProject, TypeSpec};
+use ink_metadata::{layout::Layout, InkProject, TypeSpec};
 use once_cell::sync::Lazy;
 use scale_info::{
     form::PortableForm, Path, TypeDef, TypeDefComposite, TypeDefPrimitive, TypeDefVariant,
@@ -284,3 +284,41 @@ fn custom_errors_in_metadata() {
         _ => panic!("expected uint256 type"),
     }
 }
+
+/// Small storage variables are packed into one storage slot, which has a single root
+/// in the storage layout.
+#[test]
+fn packed_storage_layout() {
+    let src = r#"
+    contract Packed {
+        bool a;
+        uint8 b;
+        uint256 c;
+        int64 d;
+    }"#;
+
+    let abi = load_abi(&build_wasm(src, false, false)[0].1);
+
+    let Layout::Struct(storage) = abi.layout() else {
+        panic!("expected struct layout");
+    };
+
+    let names: Vec<&str> = storage.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(names, ["packed_0", "c", "d"]);
+
+    let Layout::Root(root) = storage.fields()[0].layout() else {
+        panic!("expected root layout");
+    };
+    assert_eq!(*root.root_key().key(), 0);
+
+    let Layout::Struct(packed) = root.layout() else {
+        panic!("expected struct layout");
+    };
+    let names: Vec<&str> = packed.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(names, ["a", "b"]);
+
+    let Layout::Root(root) = storage.fields()[2].layout() else {
+        panic!("expected root layout");
+    };
+    assert_eq!(*root.root_key().key(), 2);
+}
@@ -1627,3 +1627,51 @@ fn abi_decode_dynamic_array3() {
 
     runtime.function("decode_empty", vec![]);
 }
+
+#[test]
+fn array_slices() {
+    let mut runtime = build_solidity(
+        r#"
+        contract Slices {
+            function selector(bytes calldata data) public pure returns (bytes4) {
+                return bytes4(data[:4]);
+            }
+
+            function decode(bytes calldata data) public pure returns (uint64, bool) {
+                return abi.decode(data[4:], (uint64, bool));
+            }
+
+            function middle(uint32[] memory arr, uint32 start, uint32 end) public pure returns (uint32[] memory) {
+                return arr[start:end];
+            }
+
+            function len(bytes memory data, uint32 start) public pure returns (uint32) {
+                return data[start:].length;
+            }
+        }
+        "#,
+    );
+
+    runtime.function("selector", vec![1u8, 2, 3, 4, 5].encode());
+    assert_eq!(runtime.output(), [1u8, 2, 3, 4].encode());
+
+    let mut data = vec![0xaau8, 0xbb, 0xcc, 0xdd];
+    data.extend((42u64, true).encode());
+    runtime.function("decode", data.encode());
+    assert_eq!(runtime.output(), (42u64, true).encode());
+
+    let arr = vec![1u32, 2, 3, 4, 5];
+    runtime.function("middle", (arr.clone(), 1u32, 3u32).encode());
+    assert_eq!(runtime.output(), vec![2u32, 3].encode());
+
+    runtime.function("middle", (arr.clone(), 5u32, 5u32).encode());
+    assert_eq!(runtime.output(), Vec::<u32>::new().encode());
+
+    runtime.function_expect_failure("middle", (arr.clone(), 3u32, 1u32).encode());
+    runtime.function_expect_failure("middle", (arr, 2u32, 6u32).encode());
+
+    runtime.function("len", (vec![1u8, 2, 3, 4, 5], 2u32).encode());
+    assert_eq!(runtime.output(), 3u32.encode());
+
+    runtime.function_expect_failure("len", (vec![1u8, 2, 3, 4, 5], 6u32).encode());
+}
@@ -120,6 +120,32 @@ fn require_without_message() {
     assert!(runtime.output().is_empty());
 }
 
+#[test]
+fn require_custom_error() {
+    let mut runtime = build_solidity(
+        r#"contract RuntimeErrors {
+        error Insufficient(uint256 available, uint256 required);
+
+        function require_test(uint256 num) public pure returns (int8) {
+            require(num > 10, Insufficient(num, 10));
+            return 0;
+        }
+    }"#,
+    );
+
+    runtime.function_expect_failure("require_test", U256::from(9).encode());
+    assert!(runtime
+        .debug_buffer()
+        .contains("runtime_error: Insufficient require condition failed in test.sol"));
+    assert_eq!(
+        runtime.output(),
+        ([0xe8u8, 0x62, 0x08, 0x00], U256::from(9), U256::from(10)).encode()
+    );
+
+    runtime.function("require_test", U256::from(11).encode());
+    assert_eq!(runtime.output(), 0i8.encode());
+}
+
 #[test]
 fn assert() {
     let mut runtime = build_solidity(
@@ -358,6 +384,46 @@ fn truncated_type_overflow() {
     assert_eq!(runtime.output(), PanicData::from(MathOverflow).encode());
 }
 
+#[test]
+fn enum_cast_oob() {
+    let mut runtime = build_solidity(
+        r#"contract RuntimeErrors {
+        enum Color { Red, Green, Blue }
+
+        function to_color(int64 input) public returns (Color) {
+            return Color(input);
+        }
+    }"#,
+    );
+
+    runtime.function("to_color", 2i64.encode());
+    assert_eq!(runtime.output(), 2u8.encode());
+
+    runtime.function_expect_failure("to_color", 3i64.encode());
+    assert!(runtime
+        .debug_buffer()
+        .contains("runtime_error: enum conversion out of range in test.sol"));
+    assert_eq!(runtime.output(), PanicData::from(EnumCastOob).encode());
+
+    runtime.function_expect_failure("to_color", (-1i64).encode());
+    assert_eq!(runtime.output(), PanicData::from(EnumCastOob).encode());
+}
+
+#[test]
+fn out_of_memory() {
+    let mut runtime = build_solidity(
+        r#"contract RuntimeErrors {
+        function alloc(uint32 input) public returns (uint256) {
+            uint256[] a = new uint256[](input);
+            return a.length;
+        }
+    }"#,
+    );
+
+    runtime.function_expect_failure("alloc", 1_000_000u32.encode());
+    assert_eq!(runtime.output(), PanicData::from(OutOfMemory).encode());
+}
+
 #[test]
 fn byte_cast_fail() {
     let mut runtime = build_solidity(
@@ -114,13 +114,13 @@ fn inherit_variables() {
 
     runtime.constructor(0, Vec::new());
 
-    let mut slot = [0u8; 32];
-
-    assert_eq!(runtime.contracts()[0].storage[&slot], vec!(102, 0));
+    // both variables are packed into the first slot
+    let slot = [0u8; 32];
 
-    slot[0] = 1;
+    let mut packed = vec![0u8; 32];
+    packed[..4].copy_from_slice(&[102, 0, 0xff, 0xff]);
 
-    assert_eq!(runtime.contracts()[0].storage[&slot], vec!(0xff, 0xff));
+    assert_eq!(runtime.contracts()[0].storage[&slot], packed);
 
     let mut runtime = build_solidity(
         r##"
@@ -141,13 +141,9 @@ fn inherit_variables() {
     runtime.constructor(0, Vec::new());
     runtime.function("test", Vec::new());
 
-    let mut slot = [0u8; 32];
-
-    assert_eq!(runtime.contracts()[0].storage[&slot], vec!(102, 0));
-
-    slot[0] = 1;
+    let slot = [0u8; 32];
 
-    assert_eq!(runtime.contracts()[0].storage[&slot], vec!(0xff, 0xff));
+    assert_eq!(runtime.contracts()[0].storage[&slot], packed);
 }
 
 #[test]
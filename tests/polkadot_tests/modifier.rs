@@ -184,14 +184,17 @@ fn inherit_modifier() {
 
     runtime.constructor(0, Vec::new());
 
-    let mut slot = [0u8; 32];
-    slot[0] = 1;
+    // s1 and s2 are packed into the first slot, s2 is at offset 1
+    let slot = [0u8; 32];
 
     assert_eq!(runtime.storage().get(&slot), None);
 
     runtime.function("test", Vec::new());
 
-    assert_eq!(runtime.storage().get(&slot).unwrap(), &vec!(7, 0, 0, 0));
+    let mut packed = vec![0u8; 32];
+    packed[1] = 7;
+
+    assert_eq!(runtime.storage().get(&slot).unwrap(), &packed);
 
     // now override it
     let mut runtime = build_solidity(
@@ -223,14 +226,17 @@ fn inherit_modifier() {
 
     runtime.constructor(0, Vec::new());
 
-    let mut slot = [0u8; 32];
-    slot[0] = 1;
+    // s1 and s2 are packed into the first slot, s2 is at offset 1
+    let slot = [0u8; 32];
 
     assert_eq!(runtime.storage().get(&slot), None);
 
     runtime.function("test", Vec::new());
 
-    assert_eq!(runtime.storage().get(&slot).unwrap(), &vec!(10, 0, 0, 0));
+    let mut packed = vec![0u8; 32];
+    packed[1] = 10;
+
+    assert_eq!(runtime.storage().get(&slot).unwrap(), &packed);
 }
 
 #[test]
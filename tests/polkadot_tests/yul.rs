@@ -315,3 +315,68 @@ fn storage_slot_on_return_data() {
     runtime.function("get", key.to_vec());
     assert_eq!(runtime.output(), runtime.caller())
 }
+
+#[test]
+fn memory_builtins() {
+    let mut runtime = build_solidity(
+        r#"
+contract testing {
+    function hash(bytes32 a, bytes32 b) public pure returns (bool) {
+        bytes32 h;
+        assembly {
+            mstore(0, a)
+            mstore(0x20, b)
+            h := keccak256(0, 0x40)
+        }
+
+        return h == keccak256(abi.encodePacked(a, b));
+    }
+
+    function store_byte() public pure returns (uint256 r) {
+        assembly {
+            mstore(0, 0)
+            mstore8(31, 0xab)
+            r := mload(0)
+        }
+    }
+
+    function allocate() public pure returns (uint256 p, uint256 v, uint256 s) {
+        assembly {
+            p := mload(0x40)
+            mstore(p, 0x1234)
+            mstore(0x40, add(p, 0x20))
+            v := mload(p)
+            s := msize()
+        }
+    }
+
+    function overflow() public pure {
+        assembly {
+            // runs past the end of the arena
+            mstore(0x861, 1)
+        }
+    }
+}"#,
+    );
+
+    runtime.constructor(0, Vec::new());
+
+    runtime.function("hash", ([1u8; 32], [2u8; 32]).encode());
+    assert_eq!(runtime.output(), true.encode());
+
+    runtime.function("store_byte", Vec::new());
+    assert_eq!(runtime.output(), Val256(U256::from(0xab)).encode());
+
+    runtime.function("allocate", Vec::new());
+    assert_eq!(
+        runtime.output(),
+        (
+            Val256(U256::from(0x80)),
+            Val256(U256::from(0x1234)),
+            Val256(U256::from(0xa0))
+        )
+            .encode()
+    );
+
+    runtime.function_expect_failure("overflow", Vec::new());
+}
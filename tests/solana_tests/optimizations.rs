@@ -29,6 +29,9 @@ static NO_OPTIMIZATIONS: Lazy<Options> = Lazy::new(|| Options {
     strength_reduce: false,
     vector_to_slice: false,
     common_subexpression_elimination: false,
+    inline: false,
+    licm: false,
+    storage_cache: false,
     ..Default::default()
 });
 
@@ -325,5 +325,78 @@ contract calle_contract {
         vm.logs,
         "runtime_error: I reverted! revert encountered in test.sol:100:9-30,\n"
     );
-    assert!(vm.return_data.is_none());
+
+    // The reason is set as return data, encoded as Error(string)
+    let mut reason = hex::decode("08c379a00b000000").unwrap();
+    reason.extend_from_slice(b"I reverted!");
+    assert_eq!(vm.return_data.as_ref().unwrap().1, reason);
+}
+
+#[test]
+fn custom_errors() {
+    let mut vm = build_solidity(
+        r#"
+error Unused();
+
+contract vault {
+    error Unauthorized();
+    error InsufficientBalance(uint64 available, uint64 required);
+
+    function withdraw(uint64 amount, uint64 balance) public pure {
+        require(amount <= balance, InsufficientBalance(balance, amount));
+    }
+
+    function deny() public pure {
+        revert Unauthorized();
+    }
+}"#,
+    );
+
+    let data_account = vm.initialize_data_account();
+    vm.function("new")
+        .accounts(vec![("dataAccount", data_account)])
+        .call();
+
+    // The custom errors the contract may revert with are numbered from 6000, in order of
+    // declaration
+    let res = vm.function("deny").must_fail();
+    assert_eq!(res.unwrap(), 6000);
+    assert_eq!(
+        vm.return_data.as_ref().unwrap().1,
+        hex::decode("82b42900").unwrap()
+    );
+
+    let res = vm
+        .function("withdraw")
+        .arguments(&[
+            BorshToken::Uint {
+                width: 64,
+                value: BigInt::from(10u8),
+            },
+            BorshToken::Uint {
+                width: 64,
+                value: BigInt::from(3u8),
+            },
+        ])
+        .must_fail();
+    assert_eq!(res.unwrap(), 6001);
+
+    // The selector of InsufficientBalance(uint64,uint64), followed by the Borsh encoded fields
+    let mut data = hex::decode("9432a7ee").unwrap();
+    data.extend_from_slice(&3u64.to_le_bytes());
+    data.extend_from_slice(&10u64.to_le_bytes());
+    assert_eq!(vm.return_data.as_ref().unwrap().1, data);
+
+    vm.function("withdraw")
+        .arguments(&[
+            BorshToken::Uint {
+                width: 64,
+                value: BigInt::from(3u8),
+            },
+            BorshToken::Uint {
+                width: 64,
+                value: BigInt::from(10u8),
+            },
+        ])
+        .call();
 }
@@ -746,3 +746,92 @@ contract Testing {
         },
     );
 }
+
+#[test]
+fn memory_builtins() {
+    let mut vm = build_solidity(
+        r#"
+contract testing {
+    function hash(bytes32 a, bytes32 b) public pure returns (bool) {
+        bytes32 h;
+        assembly {
+            mstore(0, a)
+            mstore(0x20, b)
+            h := keccak256(0, 0x40)
+        }
+
+        return h == keccak256(abi.encodePacked(a, b));
+    }
+
+    function store_byte() public pure returns (uint256 r) {
+        assembly {
+            mstore(0, 0)
+            mstore8(31, 0xab)
+            r := mload(0)
+        }
+    }
+
+    function allocate() public pure returns (uint256 p, uint256 v, uint256 s) {
+        assembly {
+            p := mload(0x40)
+            mstore(p, 0x1234)
+            mstore(0x40, add(p, 0x20))
+            v := mload(p)
+            s := msize()
+        }
+    }
+
+    function overflow() public pure {
+        assembly {
+            // runs past the end of the arena
+            mstore(0x861, 1)
+        }
+    }
+}"#,
+    );
+
+    let data_account = vm.initialize_data_account();
+    vm.function("new")
+        .accounts(vec![("dataAccount", data_account)])
+        .call();
+
+    let returns = vm
+        .function("hash")
+        .arguments(&[
+            BorshToken::FixedBytes(vec![1; 32]),
+            BorshToken::FixedBytes(vec![2; 32]),
+        ])
+        .call()
+        .unwrap();
+    assert_eq!(returns, BorshToken::Bool(true));
+
+    let returns = vm.function("store_byte").call().unwrap();
+    assert_eq!(
+        returns,
+        BorshToken::Uint {
+            width: 256,
+            value: BigInt::from(0xabu8)
+        }
+    );
+
+    let returns = vm.function("allocate").call().unwrap().unwrap_tuple();
+    assert_eq!(
+        returns,
+        vec![
+            BorshToken::Uint {
+                width: 256,
+                value: BigInt::from(0x80u8)
+            },
+            BorshToken::Uint {
+                width: 256,
+                value: BigInt::from(0x1234u16)
+            },
+            BorshToken::Uint {
+                width: 256,
+                value: BigInt::from(0xa0u8)
+            },
+        ]
+    );
+
+    vm.function("overflow").must_fail();
+}
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use solang::codegen::{codegen, OptimizationLevel, Options};
+use solang::codegen::{codegen, DispatchStrategy, OptimizationLevel, Options};
 use solang::file_resolver::FileResolver;
 use solang::sema::ast::Diagnostic;
 use solang::sema::ast::Namespace;
@@ -21,13 +21,20 @@ fn parse_and_codegen(src: &'static str) -> Namespace {
         strength_reduce: false,
         vector_to_slice: false,
         common_subexpression_elimination: false,
+        inline: false,
+        licm: false,
+        storage_cache: false,
         opt_level: OptimizationLevel::Default,
+        dispatch: DispatchStrategy::Linear,
         generate_debug_information: false,
         log_api_return_codes: false,
         log_runtime_errors: false,
         log_prints: true,
         #[cfg(feature = "wasm_opt")]
         wasm_opt: None,
+        print_after: Vec::new(),
+        time_passes: None,
+        verify_cfg: true,
     };
 
     codegen(&mut ns, &opt);